@@ -22,8 +22,12 @@ fn main() -> Result<(), Box<dyn Error>> {
         "check_context",
         "memory_syscalls",
         "page_fault",
+        "pipe",
         "sched_yield",
+        "sleep",
+        "syscall_stats",
         "trap_handler",
+        "wait",
     ];
 
     for bin in binaries {
@@ -227,9 +227,11 @@ fn make_chart(
 #[inline(never)]
 #[panic_handler]
 fn panic(panic_info: &PanicInfo) -> ! {
-    text::TEXT
-        .lock()
-        .set_attribute(Attribute::new(text::Color::WHITE, text::Color::RED));
+    text::TEXT.lock().set_attribute(Attribute::new(
+        text::Color::WHITE,
+        text::Color::RED,
+        text::BLINK_ENABLED,
+    ));
 
     println!("{panic_info}");
 
@@ -0,0 +1,123 @@
+#!/usr/bin/env python3
+
+# Links the kernel so that it symbolizes its own backtraces.
+#
+# The first pass is the ordinary link, it fixes the addresses of the functions.
+# Then their table sorted by address, one `address name` line per function,
+# is linked into the `.kernel_symbols` section, and the `.kernel_symbol_table`
+# memory is reserved for parsing it into `(usize, &str)` pairs,
+# see `kernel_symbols()` in `src/lib.rs`.
+# Both sections are placed after `.bss` by `kernel_symbols.ld`,
+# so the second pass does not move any function of the first one.
+#
+# Rustc runs the linker with the `rust-lld`, `llvm-nm` and `llvm-objcopy`
+# of the toolchain in `PATH`.
+
+import os
+import re
+import subprocess
+import sys
+import tempfile
+
+
+LINKER_SCRIPT = os.path.join(os.path.dirname(os.path.abspath(__file__)), 'kernel_symbols.ld')
+
+RUST_HASH = re.compile(r'::h[0-9a-f]{16}$')
+
+RUST_ESCAPE = re.compile(r'\$(SP|BP|RF|LT|GT|LP|RP|C|u[0-9a-f]+)\$')
+
+RUST_ESCAPES = {
+    'SP': '@', 'BP': '*', 'RF': '&', 'LT': '<', 'GT': '>', 'LP': '(', 'RP': ')', 'C': ',',
+}
+
+# A `(usize, &str)` pair takes three machine words.
+PAIR_SIZE = 3 * 8
+
+
+def link(arguments, table_object=None, table_size=0):
+    subprocess.run(
+        [
+            'rust-lld', *arguments,
+            f'--defsym=KERNEL_SYMBOL_TABLE_SIZE={table_size}',
+            '--script', LINKER_SCRIPT,
+            *([table_object] if table_object else []),
+        ],
+        check=True,
+    )
+
+
+# `llvm-nm --demangle` strips only the outer layer of the legacy Rust mangling
+# and keeps its escapes like `_$LT$core..fmt..Debug$GT$`.
+def demangle(name):
+    components = []
+
+    for component in RUST_HASH.sub('', name).split('::'):
+        if component.startswith('_$'):
+            component = component[1:]
+
+        component = RUST_ESCAPE.sub(
+            lambda escape: RUST_ESCAPES.get(escape[1]) or chr(int(escape[1][1:], 16)),
+            component.replace('..', '::'),
+        )
+        components.append(component)
+
+    return '::'.join(components)
+
+
+def functions(executable):
+    output = subprocess.run(
+        ['llvm-nm', '--defined-only', '--demangle', '--numeric-sort', executable],
+        check=True, capture_output=True, text=True,
+    ).stdout
+
+    table = []
+
+    for line in output.splitlines():
+        fields = line.split(' ', 2)
+        if len(fields) != 3 or fields[1] not in ('t', 'T'):
+            continue
+
+        address, _, name = fields
+        address = int(address, 16)
+        if table and table[-1][0] == address:
+            continue
+
+        table.append((address, demangle(name)))
+
+    return table
+
+
+def make_object(table, directory):
+    text = os.path.join(directory, 'kernel_symbols.txt')
+    with open(text, 'w', encoding='utf-8') as file:
+        file.writelines(f'{address:x} {name}\n' for address, name in table)
+
+    table_object = os.path.join(directory, 'kernel_symbols.o')
+    subprocess.run(
+        [
+            'llvm-objcopy',
+            '--input-target=binary', '--output-target=elf64-x86-64', '--strip-all',
+            '--rename-section=.data=.kernel_symbols,alloc,load,readonly,data,contents',
+            text, table_object,
+        ],
+        check=True,
+    )
+
+    return table_object
+
+
+def main(arguments):
+    executable = arguments[arguments.index('-o') + 1]
+
+    link(arguments)
+    table = functions(executable)
+
+    with tempfile.TemporaryDirectory() as directory:
+        link(arguments, make_object(table, directory), len(table) * PAIR_SIZE)
+
+    if functions(executable) != table:
+        sys.exit(f'functions of {executable} moved while linking its symbol table')
+
+
+if __name__ == '__main__':
+    main(sys.argv[1:])
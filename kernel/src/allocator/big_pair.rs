@@ -17,6 +17,7 @@ use ku::{
 };
 
 use crate::memory::{
+    self,
     AddressSpace,
     FrameGuard,
     Translate,
@@ -114,6 +115,13 @@ impl BigAllocatorPair for BigPair<'_> {
         matches!(self.address_spaces, AddressSpacePair::Same { .. })
     }
 
+    fn is_user_block(
+        &self,
+        block: Block<Page>,
+    ) -> bool {
+        memory::is_user_block(block)
+    }
+
     unsafe fn copy_mapping(
         &mut self,
         src_block: Block<Page>,
@@ -34,7 +34,10 @@ pub(crate) use memory_allocator::MemoryAllocator;
 // Used in docs.
 #[allow(unused)]
 use {
-    crate::memory::AddressSpace,
+    crate::memory::{
+        AddressSpace,
+        FRAME_ALLOCATOR,
+    },
     ku::allocator::BigAllocatorPair,
 };
 
@@ -55,6 +58,18 @@ pub(crate) fn pages_deallocation(pages: usize) {
     }
 }
 
+/// Возвращает в [`FRAME_ALLOCATOR`] физическую память,
+/// которую глобальный аллокатор ядра набрал под уже освобождённые блоки памяти.
+/// Стоит вызывать после временного всплеска выделений памяти
+/// или при нехватке физических фреймов.
+///
+/// Возвращает количество освобождённых физических фреймов.
+pub fn reclaim() -> usize {
+    let frames = GLOBAL_ALLOCATOR.reclaim();
+    debug!(frames, "reclaimed kernel heap");
+    frames
+}
+
 /// Распечатывает детальную статистику аллокатора.
 pub fn dump_info() {
     /// Память под детальную статистику аллокатора.
@@ -62,7 +77,7 @@ pub fn dump_info() {
 
     let mut allocator_info = DETAILED_INFO.lock();
     GLOBAL_ALLOCATOR.detailed_info(&mut allocator_info);
-    debug!(%allocator_info);
+    debug!(%allocator_info, histogram = %allocator_info.histogram());
 }
 
 /// Обработчик ошибок выделения памяти.
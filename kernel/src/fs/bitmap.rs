@@ -110,6 +110,15 @@ impl Bitmap {
         self.bitmap.allocate().ok_or(NoDisk)
     }
 
+    /// Выделяет элемент `number`, если он свободен и не зарезервирован.
+    /// Возвращает `true`, если элемент удалось выделить.
+    pub(super) fn try_allocate(
+        &mut self,
+        number: usize,
+    ) -> bool {
+        self.elements.contains(&number) && self.bitmap.try_allocate(number)
+    }
+
     /// Возвращает количество свободных элементов в файловой системе.
     pub(super) fn free_count(&self) -> usize {
         self.bitmap.free()
@@ -1,6 +1,10 @@
+use alloc::boxed::Box;
 use core::{
     alloc::Layout,
-    sync::atomic::Ordering,
+    sync::atomic::{
+        AtomicUsize,
+        Ordering,
+    },
 };
 
 use lazy_static::lazy_static;
@@ -34,28 +38,29 @@ use crate::memory::{
 
 use super::{
     BLOCK_SIZE,
-    disk::{
-        Disk,
-        SECTOR_SIZE,
-    },
+    block_device::BlockDevice,
+    disk::SECTOR_SIZE,
 };
 
+// Used in docs.
+#[allow(unused)]
+use ku::error::Error;
+
 // ANCHOR: block_cache
 /// [Блочный кэш](https://en.wikipedia.org/wiki/Page_cache)
 /// для ускорения работы с диском за счёт кэширования блоков файловой системы в памяти.
-#[derive(Clone, Debug)]
+#[derive(Debug)]
 pub struct BlockCache {
     /// Диапазон памяти для кэширования блоков.
     cache: Cache,
 
-    /// Диск, обращения к которому кэшируются.
-    disk: Disk,
+    /// Блочное устройство, обращения к которому кэшируются.
+    /// Хранится как `dyn BlockDevice`, так как блочный кэш один на всё ядро ---
+    /// [`struct@BLOCK_CACHE`], а статическая переменная не может быть обобщённой.
+    disk: Box<dyn BlockDevice>,
 
     /// Политика вытеснения блоков из кэша.
     eviction_policy: Lru<usize, ()>,
-
-    /// Статистика работы блочного кэша.
-    stats: Stats,
 }
 // ANCHOR_END: block_cache
 
@@ -70,7 +75,7 @@ impl BlockCache {
     /// Политика вытеснения блоков из кэша ограничивает
     /// количество одновременно отображённых в память блоков параметром `capacity`.
     pub(super) fn init(
-        disk: Disk,
+        disk: Box<dyn BlockDevice>,
         block_count: usize,
         capacity: usize,
     ) -> Result<()> {
@@ -104,6 +109,13 @@ impl BlockCache {
         }
     }
 
+    /// Записывает на диск все изменённые блоки кэша.
+    ///
+    /// Возвращает ошибку [`Error::NoDisk`], если блочный кэш не инициализирован.
+    pub fn flush_all() -> Result<()> {
+        Self::flush(Self::cache()?.block_count())
+    }
+
     /// Сбрасывает первые `count` блоков на диск.
     ///
     /// См. также [`BlockCache::flush_block_impl()`].
@@ -130,12 +142,18 @@ impl BlockCache {
     }
 
     /// Статистика работы блочного кэша.
-    pub fn stats() -> Stats {
-        if let Some(block_cache) = BLOCK_CACHE.lock().as_ref() {
-            block_cache.stats
-        } else {
-            Stats::default()
-        }
+    ///
+    /// Бит [`PageTableFlags::DIRTY`] процессор устанавливает без участия ядра,
+    /// поэтому количество изменённых блоков [`CacheStats::dirty()`]
+    /// пересчитывается по таблице страниц при каждом вызове.
+    /// Блокировка [`struct@BLOCK_CACHE`] нужна только для того,
+    /// чтобы узнать диапазон памяти кэша,
+    /// и освобождается до захвата [`BASE_ADDRESS_SPACE`].
+    pub fn stats() -> &'static CacheStats {
+        let dirty = Self::cache().map_or(0, |cache| cache.dirty_count());
+        CACHE_STATS.dirty.store(dirty, Ordering::Relaxed);
+
+        &CACHE_STATS
     }
 
     // ANCHOR: flush_block_impl
@@ -170,9 +188,7 @@ impl BlockCache {
 
 impl Drop for BlockCache {
     fn drop(&mut self) {
-        let block_count = self.cache.0.count() * Page::SIZE / BLOCK_SIZE;
-
-        for block_number in 0 .. block_count {
+        for block_number in 0 .. self.cache.block_count() {
             self.flush_block_impl(block_number).expect("failed to flush the block cache");
         }
     }
@@ -183,6 +199,26 @@ impl Drop for BlockCache {
 pub(super) struct Cache(Block<Page>);
 
 impl Cache {
+    /// Количество блоков диска, которые помещаются в кэш.
+    fn block_count(&self) -> usize {
+        self.0.count() * Page::SIZE / BLOCK_SIZE
+    }
+
+    /// Количество отображённых в память блоков, помеченных как [`PageTableFlags::DIRTY`].
+    /// То есть блоков, которые нужно записать на диск.
+    fn dirty_count(&self) -> usize {
+        let mut address_space = BASE_ADDRESS_SPACE.lock();
+
+        self.0
+            .into_iter()
+            .filter(|page| {
+                address_space
+                    .translate(page.address())
+                    .is_ok_and(|pte| pte.is_present() && pte.is_dirty())
+            })
+            .count()
+    }
+
     // ANCHOR: block
     /// Возвращает блок памяти блочного кэша,
     /// который отвечает блоку `block_number` диска.
@@ -197,19 +233,74 @@ impl Cache {
 }
 
 /// Статистика работы блочного кэша.
-#[derive(Clone, Copy, Default, Debug)]
-pub struct Stats {
+///
+/// Счётчики атомарны и хранятся вне [`struct@BLOCK_CACHE`],
+/// поэтому обновляются и читаются без его блокировки.
+///
+/// Обращения к уже отображённым в память блокам проходят мимо ядра,
+/// поэтому попадания в кэш не подсчитываются.
+/// Каждый промах читает блок с диска, так что промахам соответствует [`CacheStats::reads()`].
+#[derive(Debug)]
+pub struct CacheStats {
     /// Количество блоков, которые не пришлось записывать на диск в [`BlockCache::flush_block_impl()`].
-    discards: usize,
+    discards: AtomicUsize,
+
+    /// Количество изменённых, но ещё не записанных на диск блоков.
+    /// Вычисляется в момент вызова [`BlockCache::stats()`].
+    dirty: AtomicUsize,
 
     /// Количество блоков, которые были вытеснены из кэша в [`BlockCache::trap_handler()`].
-    evictions: usize,
+    evictions: AtomicUsize,
 
     /// Количество блоков, которые были прочитаны с диска в [`BlockCache::trap_handler()`].
-    reads: usize,
+    reads: AtomicUsize,
 
     /// Количество блоков, которые были записаны на диск в [`BlockCache::flush_block_impl()`].
-    writes: usize,
+    writes: AtomicUsize,
+}
+
+impl CacheStats {
+    /// Создаёт обнулённую статистику.
+    const fn new() -> Self {
+        Self {
+            discards: AtomicUsize::new(0),
+            dirty: AtomicUsize::new(0),
+            evictions: AtomicUsize::new(0),
+            reads: AtomicUsize::new(0),
+            writes: AtomicUsize::new(0),
+        }
+    }
+
+    /// Количество блоков, которые не пришлось записывать на диск,
+    /// так как они не изменялись.
+    pub fn discards(&self) -> usize {
+        self.discards.load(Ordering::Relaxed)
+    }
+
+    /// Количество изменённых, но ещё не записанных на диск блоков.
+    pub fn dirty(&self) -> usize {
+        self.dirty.load(Ordering::Relaxed)
+    }
+
+    /// Количество блоков, которые были вытеснены из кэша.
+    pub fn evictions(&self) -> usize {
+        self.evictions.load(Ordering::Relaxed)
+    }
+
+    /// Количество промахов кэша, то есть блоков, которые были прочитаны с диска.
+    pub fn misses(&self) -> usize {
+        self.reads()
+    }
+
+    /// Количество блоков, которые были прочитаны с диска.
+    pub fn reads(&self) -> usize {
+        self.reads.load(Ordering::Relaxed)
+    }
+
+    /// Количество блоков, которые были записаны на диск.
+    pub fn writes(&self) -> usize {
+        self.writes.load(Ordering::Relaxed)
+    }
 }
 
 lazy_static! {
@@ -218,11 +309,15 @@ lazy_static! {
     pub(super) static ref BLOCK_CACHE: FastSpinlock<Option<BlockCache>> = FastSpinlock::new(None);
 }
 
+/// Статистика работы блочного кэша.
+static CACHE_STATS: CacheStats = CacheStats::new();
+
 /// Количество секторов диска в одном блоке файловой системы.
 pub(super) const SECTORS_PER_BLOCK: usize = BLOCK_SIZE / SECTOR_SIZE;
 
 #[doc(hidden)]
 pub mod test_scaffolding {
+    use alloc::boxed::Box;
     use core::sync::atomic::{
         AtomicBool,
         Ordering,
@@ -240,9 +335,9 @@ pub mod test_scaffolding {
     };
 
     use super::{
+        super::disk::Disk,
         BLOCK_CACHE,
         BlockCache,
-        Disk,
     };
 
     pub fn block_cache_init(
@@ -250,7 +345,7 @@ pub mod test_scaffolding {
         block_count: usize,
         capacity: usize,
     ) -> Result<()> {
-        BlockCache::init(Disk::new(disk)?, block_count, capacity)
+        BlockCache::init(Box::new(Disk::new(disk)?), block_count, capacity)
     }
 
     pub fn cache() -> Result<Block<Page>> {
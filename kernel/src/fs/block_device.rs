@@ -0,0 +1,51 @@
+use core::fmt;
+
+use ku::error::Result;
+
+// Used in docs.
+#[allow(unused)]
+use {
+    super::{
+        BLOCK_SIZE,
+        block_cache::BlockCache,
+        disk::Disk,
+        ram_disk::RamDisk,
+    },
+    ku::error::Error,
+};
+
+/// [Блочное устройство](https://en.wikipedia.org/wiki/Device_file#Block_devices) ---
+/// носитель, который читается и записывается блоками файловой системы размера [`BLOCK_SIZE`].
+///
+/// Отделяет [`BlockCache`] и файловую систему от конкретного драйвера.
+/// Реализуется, например,
+/// [PATA](https://en.wikipedia.org/wiki/Parallel_ATA)--диском [`Disk`] и
+/// [RAM--диском](https://en.wikipedia.org/wiki/RAM_drive) [`RamDisk`].
+pub trait BlockDevice: fmt::Debug + Send {
+    /// Количество блоков файловой системы, которые вмещает устройство.
+    fn block_count(&self) -> Result<usize>;
+
+    /// Читает блок номер `block_number` в буфер `buffer` размера [`BLOCK_SIZE`].
+    ///
+    /// Возвращает ошибку [`Error::InvalidArgument`],
+    /// если блока с таким номером нет или размер `buffer` не равен [`BLOCK_SIZE`].
+    fn read_block(
+        &mut self,
+        block_number: usize,
+        buffer: &mut [u8],
+    ) -> Result<()>;
+
+    /// Записывает блок номер `block_number` из буфера `buffer` размера [`BLOCK_SIZE`].
+    ///
+    /// Возвращает ошибку [`Error::InvalidArgument`],
+    /// если блока с таким номером нет или размер `buffer` не равен [`BLOCK_SIZE`].
+    fn write_block(
+        &mut self,
+        block_number: usize,
+        buffer: &[u8],
+    ) -> Result<()>;
+
+    /// Записывает на физический носитель все данные,
+    /// которые устройство могло буферизовать.
+    fn flush(&mut self) -> Result<()>;
+}
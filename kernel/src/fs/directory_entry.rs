@@ -108,7 +108,7 @@ impl DirectoryEntry {
     /// Возвращает ошибку [`Error::InvalidArgument`],
     /// если `name` содержит не-[ASCII](https://en.wikipedia.org/wiki/ASCII) символы
     /// или разделитель пути `/`.
-    fn validate(name: &str) -> Result<()> {
+    pub(super) fn validate(name: &str) -> Result<()> {
         let bytes = name.as_bytes();
 
         if bytes.is_empty() ||
@@ -3,17 +3,25 @@ use core::{
     hint,
     mem,
     ops::Range,
+    sync::atomic::{
+        AtomicI64,
+        AtomicUsize,
+        Ordering,
+    },
 };
 
 use bitflags::bitflags;
 use chrono::Duration;
 use derive_more::Display;
+use scopeguard::defer;
 use static_assertions::const_assert_eq;
 use x86::io;
+use x86_64::instructions::interrupts;
 
 use ku::{
     error::{
         Error::{
+            InvalidArgument,
             Medium,
             NoDisk,
             Timeout,
@@ -23,49 +31,136 @@ use ku::{
     log::{
         error,
         trace,
+        warn,
+    },
+    memory::{
+        Block,
+        size,
     },
-    memory::size,
     time,
 };
 
-use super::block_cache::SECTORS_PER_BLOCK;
+use super::{
+    BLOCK_SIZE,
+    block_cache::SECTORS_PER_BLOCK,
+    block_device::BlockDevice,
+};
+
+use crate::trap::Trap;
+
+// Used in docs.
+#[allow(unused)]
+use ku::error::Error;
 
 /// [PATA](https://en.wikipedia.org/wiki/Parallel_ATA)--диск.
 #[derive(Clone, Copy, Debug, Display)]
-#[display("{{ id: {}, io_port: {:#04X}, io_disk: {} }}", id, io_port, io_disk)]
+#[display(
+    "{{ id: {}, io_port: {:#04X}, io_disk: {}, interrupt_driven: {}, sector_count: {} }}",
+    id,
+    io_port,
+    io_disk,
+    interrupt_driven,
+    sector_count
+)]
 pub(super) struct Disk {
     /// Идентификатор диска --- `0..4`.
     id: u8,
 
+    /// Ожидать ли готовности данных при чтении по прерыванию контроллера,
+    /// а не опросом его регистра статуса.
+    interrupt_driven: bool,
+
     /// Идентификатор диска,
     /// передаваемый в [порт ввода--вывода](https://wiki.osdev.org/Port_IO) при операциях с ним.
     io_disk: u8,
 
     /// Базовый [порт ввода--вывода](https://wiki.osdev.org/Port_IO) для операций с диском.
     io_port: u16,
+
+    /// Количество секторов диска, которое он сообщил в ответ на команду
+    /// [`Command::IDENTIFY`].
+    sector_count: usize,
 }
 
 impl Disk {
     /// Возвращает диск с заданным `id`.
+    ///
+    /// Опрашивает диск командой [`Command::IDENTIFY`], чтобы узнать количество его секторов.
+    /// Если диска нет или он не является PATA--диском, например, это привод компакт--дисков,
+    /// возвращает ошибку [`Error::NoDisk`].
     pub(super) fn new(id: usize) -> Result<Self> {
         let id = id.try_into().map_err(|_| NoDisk)?;
 
-        Ok(Self {
+        let mut disk = Self {
+            interrupt_driven: false,
             io_port: Self::io_port(id)?,
             io_disk: Self::io_disk(id),
             id,
-        })
+            sector_count: 0,
+        };
+
+        disk.sector_count = disk.identify()?;
+
+        Ok(disk)
     }
 
-    /// Записывает содержимое кэша диска на физический носитель.
-    pub(super) fn flush(&self) -> Result<()> {
-        let result = unsafe { self.send_command(Command::FLUSH_CACHE, 0) };
+    /// Включает или выключает чтение с ожиданием прерывания контроллера.
+    ///
+    /// В этом режиме поток, читающий с диска, не крутится в цикле опроса регистра статуса,
+    /// а останавливает процессор до прерывания, которое сообщит о готовности очередного сектора.
+    /// Если прерывание не пришло за [`INTERRUPT_TIMEOUT_IN_MILLISECONDS`],
+    /// чтение продолжается опросом.
+    pub(super) fn set_interrupt_driven(
+        &mut self,
+        interrupt_driven: bool,
+    ) {
+        self.interrupt_driven = interrupt_driven;
+    }
 
-        if result.is_err() {
-            error!(sector = self.read_sector_number(), "flush failed");
+    /// Выполняет команду [`Command::IDENTIFY`] и возвращает количество секторов диска,
+    /// адресуемых в режиме [`LBA`].
+    ///
+    /// Возвращает ошибку [`Error::NoDisk`], если диска нет или он отвергает команду.
+    fn identify(&self) -> Result<usize> {
+        unsafe {
+            io::outb(
+                self.io_port + 6,
+                RESERVED_SHOULD_BE_ONES | (self.io_disk << 4),
+            );
         }
 
-        result
+        let status = self.status();
+        if status.is_empty() || status.is_all() {
+            return Err(NoDisk);
+        }
+
+        unsafe {
+            io::outb(self.io_port + 2, 0);
+            self.write_sector_number(0);
+            io::outb(self.io_port + 7, Command::IDENTIFY.bits());
+        }
+
+        if self.status().is_empty() {
+            return Err(NoDisk);
+        }
+
+        self.wait(Status::DATA_REQUEST).map_err(|error| match error {
+            Medium => NoDisk,
+            error => error,
+        })?;
+
+        let mut identity = [0_u32; SECTOR_SIZE / mem::size_of::<u32>()];
+        unsafe {
+            ins32(self.io_port, &mut identity);
+        }
+
+        let sector_count = size::from(u32::from_le(identity[IDENTIFY_LBA_SECTOR_COUNT]));
+        if sector_count == 0 {
+            warn!(disk = %self, "the disk does not support LBA");
+            return Err(NoDisk);
+        }
+
+        Ok(sector_count)
     }
 
     /// Читает с диска диапазон секторов `sectors` размера [`SECTOR_SIZE`]
@@ -78,12 +173,18 @@ impl Disk {
     ) -> Result<()> {
         assert_eq!(mem::size_of_val(buffer), sectors.len() * SECTOR_SIZE);
 
+        let waiter = self.interrupt_driven.then(|| Waiter::new(self.completion()));
+
         unsafe {
             self.send_rw_command(Command::READ, sectors.start, sectors.len())?;
         }
 
-        for sector in buffer.chunks_mut(SECTOR_SIZE / mem::size_of::<u32>()) {
-            self.wait_ready()?;
+        for (index, sector) in buffer.chunks_mut(SECTOR_SIZE / mem::size_of::<u32>()).enumerate() {
+            if let Some(waiter) = &waiter {
+                waiter.wait(index + 1);
+            }
+
+            self.wait(Status::DATA_REQUEST)?;
 
             unsafe {
                 ins32(self.io_port, sector);
@@ -93,15 +194,6 @@ impl Disk {
         Ok(())
     }
 
-    /// Количество блоков файловой системы, которые вмещает диск.
-    pub(super) fn block_count(&self) -> Result<usize> {
-        unsafe { self.send_command(Command::READ_NATIVE_MAX_ADDRESS, LBA)? };
-
-        let max_sector_number = self.read_sector_number();
-
-        Ok((max_sector_number + 1) / SECTORS_PER_BLOCK)
-    }
-
     /// Записывает на диск диапазон секторов `sectors` размера [`SECTOR_SIZE`]
     /// из буфера `buffer` методом
     /// [программного ввода--вывода](https://en.wikipedia.org/wiki/Programmed_input%E2%80%93output).
@@ -117,7 +209,7 @@ impl Disk {
         }
 
         for sector in buffer.chunks(SECTOR_SIZE / mem::size_of::<u32>()) {
-            self.wait_ready()?;
+            self.wait(Status::DATA_REQUEST)?;
 
             unsafe {
                 outs32(self.io_port, sector);
@@ -135,7 +227,7 @@ impl Disk {
         command: Command,
         argument: u8,
     ) -> Result<()> {
-        self.wait_ready()?;
+        self.wait(Status::READY)?;
 
         unsafe {
             io::outb(
@@ -145,7 +237,7 @@ impl Disk {
             io::outb(self.io_port + 7, command.bits());
         }
 
-        self.wait_ready()
+        self.wait(Status::READY)
     }
 
     /// Посылает в диск команду `command` для
@@ -159,7 +251,7 @@ impl Disk {
     ) -> Result<()> {
         assert!(sector_count < (1 << 8));
 
-        self.wait_ready()?;
+        self.wait(Status::READY)?;
 
         unsafe {
             io::outb(self.io_port + 2, sector_count as u8);
@@ -196,58 +288,84 @@ impl Disk {
         }
     }
 
-    /// Ожидает готовности диска к приёму команды.
-    fn wait_ready(&self) -> Result<()> {
-        bitflags! {
-            /// Регистр статуса.
-            #[derive(Clone, Copy, Debug, Eq, PartialEq)]
-            struct Status: u8 {
-                const ERROR = 1 << 0;
-                const FAILURE = 1 << 5;
-                const READY = 1 << 6;
-                const BUSY = 1 << 7;
-            }
-        }
+    /// Читает регистр статуса диска.
+    fn status(&self) -> Status {
+        Status::from_bits_retain(unsafe { io::inb(self.io_port + 7) })
+    }
 
+    /// Ожидает, пока диск сбросит флаг [`Status::BUSY`] и выставит флаги `expected`.
+    /// Для приёма команды нужно ждать [`Status::READY`],
+    /// а для передачи очередного сектора данных --- [`Status::DATA_REQUEST`].
+    ///
+    /// Возвращает ошибку [`Error::Medium`], если диск сообщил об ошибке,
+    /// и [`Error::Timeout`], если он не ответил за [`TIMEOUT_IN_SECONDS`].
+    fn wait(
+        &self,
+        expected: Status,
+    ) -> Result<()> {
         let mut iterations = 1;
         let mut last_status = None;
         let start = time::timer();
         let timeout = Duration::seconds(TIMEOUT_IN_SECONDS);
 
+        let spin_start = time::tsc();
+        defer! {
+            SPIN_TSC.fetch_add(time::tsc() - spin_start, Ordering::Relaxed);
+        }
+
         while last_status.is_none() || !start.has_passed(timeout) {
-            let status = Status::from_bits_truncate(unsafe { io::inb(self.io_port + 7) });
+            let status = self.status();
             last_status = Some(status);
 
-            if status.contains(Status::READY) && !status.contains(Status::BUSY) {
-                trace!(elapsed = %start.elapsed(), iterations, ?status, "waited for IDE");
+            if !status.contains(Status::BUSY) {
+                if status.intersects(Status::ERROR | Status::FAILURE) {
+                    warn!(elapsed = %start.elapsed(), iterations, ?status, ?expected, "IDE error");
+                    return Err(Medium);
+                }
 
-                return if status.intersects(Status::ERROR | Status::FAILURE) {
-                    Err(Medium)
-                } else {
-                    Ok(())
-                };
+                if status.contains(expected) {
+                    trace!(elapsed = %start.elapsed(), iterations, ?status, "waited for IDE");
+                    return Ok(());
+                }
             }
 
             hint::spin_loop();
             iterations += 1;
         }
 
-        error!(elapsed = %start.elapsed(), iterations, ?last_status, "timeout waiting for IDE");
+        error!(
+            elapsed = %start.elapsed(),
+            iterations,
+            ?last_status,
+            ?expected,
+            "timeout waiting for IDE",
+        );
 
         Err(Timeout)
     }
 
+    /// Диапазон секторов, которые занимает блок файловой системы номер `block_number`.
+    ///
+    /// Возвращает ошибку [`Error::InvalidArgument`],
+    /// если блок не адресуется номерами секторов
+    /// или размер буфера `buffer_size` не равен [`BLOCK_SIZE`].
+    fn sectors(
+        block_number: usize,
+        buffer_size: usize,
+    ) -> Result<Range<usize>> {
+        let max_block_count = (SECTOR_NUMBER_MASK + 1) / SECTORS_PER_BLOCK;
+
+        if block_number < max_block_count && buffer_size == BLOCK_SIZE {
+            let start = block_number * SECTORS_PER_BLOCK;
+            Ok(start .. start + SECTORS_PER_BLOCK)
+        } else {
+            Err(InvalidArgument)
+        }
+    }
+
     /// Базовый [порт ввода--вывода](https://wiki.osdev.org/Port_IO)
     /// для операций с диском имеющим порядковый номер `id`.
     fn io_port(id: u8) -> Result<u16> {
-        /// Базовый порт первого и второго
-        /// [PATA](https://en.wikipedia.org/wiki/Parallel_ATA)--диска.
-        const ATA0_BASE_PORT: u16 = 0x01F0;
-
-        /// Базовый порт третьего и четвёртого
-        /// [PATA](https://en.wikipedia.org/wiki/Parallel_ATA)--диска.
-        const ATA1_BASE_PORT: u16 = 0x0170;
-
         match id / 2 {
             0 => Ok(ATA0_BASE_PORT),
             1 => Ok(ATA1_BASE_PORT),
@@ -255,6 +373,11 @@ impl Disk {
         }
     }
 
+    /// Состояние ожидания прерывания контроллера, к которому подключён диск.
+    fn completion(&self) -> &'static Completion {
+        Completion::find(self.io_port).expect("unknown PATA controller")
+    }
+
     /// Идентификатор диска,
     /// передаваемый в [порт ввода--вывода](https://wiki.osdev.org/Port_IO)
     /// для операций с диском имеющим порядковый номер `id`.
@@ -268,6 +391,180 @@ impl Disk {
     }
 }
 
+impl BlockDevice for Disk {
+    fn block_count(&self) -> Result<usize> {
+        Ok(self.sector_count / SECTORS_PER_BLOCK)
+    }
+
+    fn read_block(
+        &mut self,
+        block_number: usize,
+        buffer: &mut [u8],
+    ) -> Result<()> {
+        let sectors = Self::sectors(block_number, buffer.len())?;
+        let buffer = unsafe { Block::from_slice_mut(buffer).try_into_mut_slice::<u32>()? };
+
+        self.pio_read(sectors, buffer)
+    }
+
+    fn write_block(
+        &mut self,
+        block_number: usize,
+        buffer: &[u8],
+    ) -> Result<()> {
+        let sectors = Self::sectors(block_number, buffer.len())?;
+        let buffer = unsafe { Block::from_slice(buffer).try_into_slice::<u32>()? };
+
+        self.pio_write(sectors, buffer)
+    }
+
+    /// Записывает содержимое кэша диска на физический носитель.
+    fn flush(&mut self) -> Result<()> {
+        let result = unsafe { self.send_command(Command::FLUSH_CACHE, 0) };
+
+        if result.is_err() {
+            error!(sector = self.read_sector_number(), "flush failed");
+        }
+
+        result
+    }
+}
+
+/// Обрабатывает прерывание `trap` контроллера
+/// [PATA](https://en.wikipedia.org/wiki/Parallel_ATA).
+///
+/// Сопоставляет прерывание с запросом по базовому порту контроллера
+/// и засчитывает его зарегистрированным для него [`Waiter`].
+/// Остановленный в [`Waiter::wait()`] процессор продолжает работу
+/// уже благодаря самому прерыванию.
+/// Если такого нет, диск работает в режиме опроса и прерывание игнорируется.
+pub(crate) fn interrupt(trap: Trap) {
+    let io_port = match trap {
+        Trap::Ata0 => ATA0_BASE_PORT,
+        Trap::Ata1 => ATA1_BASE_PORT,
+        _ => return,
+    };
+
+    if let Some(completion) = Completion::find(io_port) &&
+        completion.waiters.load(Ordering::Acquire) > 0
+    {
+        completion.completions.fetch_add(1, Ordering::Release);
+    }
+}
+
+/// Состояние ожидания прерываний одного контроллера
+/// [PATA](https://en.wikipedia.org/wiki/Parallel_ATA).
+struct Completion {
+    /// Количество прерываний, пришедших при зарегистрированных [`Waiter`].
+    completions: AtomicUsize,
+
+    /// Базовый [порт ввода--вывода](https://wiki.osdev.org/Port_IO) контроллера,
+    /// по которому прерывание сопоставляется с запросом.
+    io_port: u16,
+
+    /// Количество зарегистрированных [`Waiter`].
+    waiters: AtomicUsize,
+}
+
+impl Completion {
+    /// Создаёт состояние ожидания для контроллера с базовым портом `io_port`.
+    const fn new(io_port: u16) -> Self {
+        Self {
+            completions: AtomicUsize::new(0),
+            io_port,
+            waiters: AtomicUsize::new(0),
+        }
+    }
+
+    /// Состояние ожидания для контроллера с базовым портом `io_port`.
+    fn find(io_port: u16) -> Option<&'static Completion> {
+        COMPLETIONS.iter().find(|completion| completion.io_port == io_port)
+    }
+}
+
+/// Ожидающий прерываний контроллера
+/// [PATA](https://en.wikipedia.org/wiki/Parallel_ATA).
+/// Зарегистрирован в [`Completion`] всё время своего существования.
+struct Waiter {
+    /// Состояние ожидания контроллера.
+    completion: &'static Completion,
+
+    /// Значение [`Completion::completions`] в момент регистрации.
+    start: usize,
+}
+
+impl Waiter {
+    /// Регистрирует ожидающего прерываний контроллера `completion`.
+    ///
+    /// Регистрироваться нужно до отправки команды,
+    /// иначе первое прерывание может прийти раньше и потеряться.
+    fn new(completion: &'static Completion) -> Self {
+        completion.waiters.fetch_add(1, Ordering::AcqRel);
+
+        Self {
+            completion,
+            start: completion.completions.load(Ordering::Acquire),
+        }
+    }
+
+    /// Количество прерываний, пришедших после регистрации.
+    fn completions(&self) -> usize {
+        self.completion.completions.load(Ordering::Acquire).wrapping_sub(self.start)
+    }
+
+    /// Ждёт, пока с момента регистрации придёт `count` прерываний.
+    ///
+    /// Останавливает процессор до следующего прерывания, если прерывания разрешены,
+    /// иначе ждёт в цикле.
+    /// Блокировать поток ядра до прерывания нельзя ---
+    /// в ядре нет потоков, которые мог бы переключить планировщик.
+    /// По истечении [`INTERRUPT_TIMEOUT_IN_MILLISECONDS`] сдаётся,
+    /// оставляя дальнейшее ожидание опросу регистра статуса.
+    fn wait(
+        &self,
+        count: usize,
+    ) {
+        let start = time::timer();
+        let timeout = Duration::milliseconds(INTERRUPT_TIMEOUT_IN_MILLISECONDS);
+
+        while self.completions() < count {
+            if start.has_passed(timeout) {
+                warn!(
+                    count,
+                    completions = self.completions(),
+                    "no PATA interrupt, falling back to polling",
+                );
+                return;
+            }
+
+            if interrupts::are_enabled() {
+                // The completion is checked again with interrupts disabled,
+                // so an interrupt arriving between the check and `hlt`
+                // does not leave the processor halted until the next timer tick.
+                interrupts::disable();
+
+                if self.completions() < count {
+                    interrupts::enable_and_hlt();
+                } else {
+                    interrupts::enable();
+                }
+            } else {
+                let spin_start = time::tsc();
+                hint::spin_loop();
+                SPIN_TSC.fetch_add(time::tsc() - spin_start, Ordering::Relaxed);
+            }
+        }
+
+        trace!(elapsed = %start.elapsed(), count, "waited for PATA interrupt");
+    }
+}
+
+impl Drop for Waiter {
+    fn drop(&mut self) {
+        self.completion.waiters.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
 /// Записывает в порт ввода--вывода номер `port` данные из буфера `buffer`.
 unsafe fn outs32(
     port: u16,
@@ -312,6 +609,29 @@ pub(super) const SECTOR_SIZE: usize = 1 << 9;
 /// Тайм-аут ожидания готовности диска к приёму команды в секундах.
 const TIMEOUT_IN_SECONDS: i64 = 10;
 
+/// Тайм-аут ожидания прерывания контроллера в миллисекундах,
+/// после которого [`Waiter`] переходит к опросу регистра статуса.
+const INTERRUPT_TIMEOUT_IN_MILLISECONDS: i64 = 100;
+
+/// Базовый порт первого и второго
+/// [PATA](https://en.wikipedia.org/wiki/Parallel_ATA)--диска.
+const ATA0_BASE_PORT: u16 = 0x01F0;
+
+/// Базовый порт третьего и четвёртого
+/// [PATA](https://en.wikipedia.org/wiki/Parallel_ATA)--диска.
+const ATA1_BASE_PORT: u16 = 0x0170;
+
+/// Количество тактов процессора, потраченных на активное ожидание диска в цикле.
+/// Позволяет оценить, сколько процессорного времени освобождает чтение по прерываниям.
+static SPIN_TSC: AtomicI64 = AtomicI64::new(0);
+
+/// Состояния ожидания прерываний для каждого из контроллеров
+/// [PATA](https://en.wikipedia.org/wiki/Parallel_ATA).
+static COMPLETIONS: [Completion; 2] = [
+    Completion::new(ATA0_BASE_PORT),
+    Completion::new(ATA1_BASE_PORT),
+];
+
 const_assert_eq!(SECTOR_SIZE % mem::size_of::<u32>(), 0);
 
 bitflags! {
@@ -320,17 +640,44 @@ bitflags! {
         /// Запись содержимого кэша диска на физический носитель.
         const FLUSH_CACHE = 0xE7;
 
+        /// Получение описания диска --- 256 16-битных слов,
+        /// которые передаются так же, как один сектор данных.
+        const IDENTIFY = 0xEC;
+
         /// Чтение диапазона секторов с диска.
         const READ = 0x20;
 
-        /// Получение максимального номера сектора диска.
-        const READ_NATIVE_MAX_ADDRESS = 0xF8;
-
         /// Запись диапазона секторов на диск.
         const WRITE = 0x30;
     }
 }
 
+bitflags! {
+    /// Регистр статуса [PATA](https://en.wikipedia.org/wiki/Parallel_ATA)--диска.
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    struct Status: u8 {
+        /// Последняя команда завершилась ошибкой.
+        const ERROR = 1 << 0;
+
+        /// Диск готов принять или отдать очередной сектор данных.
+        const DATA_REQUEST = 1 << 3;
+
+        /// Диск неисправен.
+        const FAILURE = 1 << 5;
+
+        /// Диск готов принять команду.
+        const READY = 1 << 6;
+
+        /// Диск занят, остальные флаги статуса недостоверны.
+        const BUSY = 1 << 7;
+    }
+}
+
+/// Индекс 32-битной величины в ответе на команду [`Command::IDENTIFY`],
+/// которая содержит количество секторов, адресуемых в режиме [`LBA`] ---
+/// 16-битные слова `60` и `61`.
+const IDENTIFY_LBA_SECTOR_COUNT: usize = 60 / 2;
+
 /// Выбирает режим логической адресации блоков диска
 /// ([Logical block addressing](https://en.wikipedia.org/wiki/Logical_block_addressing), LBA).
 const LBA: u8 = 1 << 6;
@@ -343,11 +690,40 @@ const SECTOR_NUMBER_MASK: usize = (1 << 28) - 1;
 
 #[doc(hidden)]
 pub mod test_scaffolding {
+    use core::sync::atomic::Ordering;
+
     use ku::error::Result;
 
-    use super::Disk;
+    use super::{
+        BlockDevice,
+        Disk,
+        SPIN_TSC,
+    };
 
     pub fn block_count(disk: usize) -> Result<usize> {
         Disk::new(disk)?.block_count()
     }
+
+    pub fn read_block(
+        disk: usize,
+        block_number: usize,
+        buffer: &mut [u8],
+        interrupt_driven: bool,
+    ) -> Result<()> {
+        let mut disk = Disk::new(disk)?;
+        disk.set_interrupt_driven(interrupt_driven);
+        disk.read_block(block_number, buffer)
+    }
+
+    pub fn sector_count(disk: usize) -> Result<usize> {
+        Ok(Disk::new(disk)?.sector_count)
+    }
+
+    pub fn interrupt_completions(disk: usize) -> Result<usize> {
+        Ok(Disk::new(disk)?.completion().completions.load(Ordering::Relaxed))
+    }
+
+    pub fn spin_tsc() -> i64 {
+        SPIN_TSC.load(Ordering::Relaxed)
+    }
 }
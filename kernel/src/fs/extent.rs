@@ -0,0 +1,179 @@
+use core::mem;
+
+use static_assertions::const_assert_eq;
+
+use ku::error::{
+    Error::{
+        InvalidArgument,
+        Overflow,
+    },
+    Result,
+};
+
+use super::{
+    BLOCK_SIZE,
+    bitmap::Bitmap,
+};
+
+// Used in docs.
+#[allow(unused)]
+use {
+    super::inode::Inode,
+    ku::error::Error,
+};
+
+// ANCHOR: extent
+/// [Экстент](https://en.wikipedia.org/wiki/Extent_(file_systems)) ---
+/// непрерывный диапазон блоков файловой системы,
+/// хранящий подряд идущие блоки данных [`Inode`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[repr(C)]
+pub(super) struct Extent {
+    /// Номер первого блока экстента.
+    start: usize,
+
+    /// Количество блоков в экстенте.
+    len: usize,
+}
+// ANCHOR_END: extent
+
+impl Extent {
+    /// Номер блока, следующего за последним блоком экстента.
+    fn end(&self) -> usize {
+        self.start + self.len
+    }
+}
+
+const_assert_eq!(BLOCK_SIZE % mem::size_of::<Extent>(), 0);
+
+/// Отображение блоков [`Inode`] в номера блоков файловой системы
+/// в виде списка [экстентов](https://en.wikipedia.org/wiki/Extent_(file_systems)).
+///
+/// Сам список хранится в отдельном блоке файловой системы,
+/// а количество занятых в нём записей --- в метаданных [`Inode`].
+/// Соседние блоки объединяются в один [`Extent`],
+/// поэтому для непрерывного файла список состоит из единственной записи
+/// независимо от размера файла.
+pub(super) struct Extents<'a> {
+    /// Количество занятых записей в [`Extents::table`].
+    count: &'a mut usize,
+
+    /// Таблица экстентов --- содержимое блока файловой системы в памяти блочного кэша.
+    table: &'a mut [Extent],
+}
+
+impl<'a> Extents<'a> {
+    /// Создаёт отображение поверх таблицы `table` из `count` занятых записей.
+    ///
+    /// Возвращает ошибку [`Error::InvalidArgument`],
+    /// если `count` превышает размер таблицы.
+    pub(super) fn new(
+        table: &'a mut [Extent],
+        count: &'a mut usize,
+    ) -> Result<Self> {
+        if *count <= table.len() {
+            Ok(Self { count, table })
+        } else {
+            Err(InvalidArgument)
+        }
+    }
+
+    /// Количество экстентов.
+    pub(super) fn len(&self) -> usize {
+        *self.count
+    }
+
+    /// Количество блоков данных, отображаемых экстентами.
+    pub(super) fn block_count(&self) -> usize {
+        self.extents().iter().map(|extent| extent.len).sum()
+    }
+
+    /// Возвращает номер блока файловой системы,
+    /// хранящего блок `inode_block_number` внутри данных [`Inode`].
+    ///
+    /// Возвращает ошибку [`Error::InvalidArgument`],
+    /// если `inode_block_number` выходит за пределы отображённых блоков.
+    pub(super) fn block(
+        &self,
+        inode_block_number: usize,
+    ) -> Result<usize> {
+        let mut skip = inode_block_number;
+
+        for extent in self.extents() {
+            if skip < extent.len {
+                return Ok(extent.start + skip);
+            }
+            skip -= extent.len;
+        }
+
+        Err(InvalidArgument)
+    }
+
+    /// Добавляет в конец отображения ещё один блок, выделяя его в `block_bitmap`.
+    /// Возвращает номер выделенного блока.
+    ///
+    /// В первую очередь пробует выделить блок, следующий за последним экстентом,
+    /// чтобы просто удлинить его.
+    /// Возвращает ошибку [`Error::Overflow`],
+    /// если новый блок не примыкает к последнему экстенту, а таблица экстентов заполнена.
+    pub(super) fn push(
+        &mut self,
+        block_bitmap: &mut Bitmap,
+    ) -> Result<usize> {
+        if let Some(last) = self.last_mut() &&
+            block_bitmap.try_allocate(last.end())
+        {
+            last.len += 1;
+            return Ok(last.end() - 1);
+        }
+
+        let block = block_bitmap.allocate()?;
+
+        if let Some(last) = self.last_mut() &&
+            last.end() == block
+        {
+            last.len += 1;
+        } else if *self.count < self.table.len() {
+            self.table[*self.count] = Extent {
+                start: block,
+                len: 1,
+            };
+            *self.count += 1;
+        } else {
+            block_bitmap.set_free(block);
+            return Err(Overflow);
+        }
+
+        Ok(block)
+    }
+
+    /// Удаляет из конца отображения последний блок, освобождая его в `block_bitmap`.
+    ///
+    /// # Panics
+    ///
+    /// Паникует, если отображение пусто.
+    pub(super) fn pop(
+        &mut self,
+        block_bitmap: &mut Bitmap,
+    ) {
+        let last = self.last_mut().expect("the extent list is empty");
+
+        last.len -= 1;
+        let block = last.end();
+        if last.len == 0 {
+            *self.count -= 1;
+        }
+
+        block_bitmap.set_free(block);
+    }
+
+    /// Занятые записи таблицы экстентов.
+    fn extents(&self) -> &[Extent] {
+        &self.table[.. *self.count]
+    }
+
+    /// Последний экстент, если он есть.
+    fn last_mut(&mut self) -> Option<&mut Extent> {
+        self.table[.. *self.count].last_mut()
+    }
+}
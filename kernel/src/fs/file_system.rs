@@ -1,4 +1,6 @@
 use alloc::{
+    boxed::Box,
+    format,
     string::String,
     vec::Vec,
 };
@@ -8,6 +10,7 @@ use chrono::{
     DateTime,
     Utc,
 };
+use heapless::String as NameString;
 
 use ku::{
     collections::Lru,
@@ -16,6 +19,7 @@ use ku::{
             FileNotFound,
             Medium,
             NotDirectory,
+            NotEmpty,
         },
         Result,
     },
@@ -31,13 +35,20 @@ use super::{
     BLOCK_SIZE,
     bitmap::Bitmap,
     block_cache::BlockCache,
-    directory_entry::DirectoryEntry,
+    block_device::BlockDevice,
+    directory_entry::{
+        DirectoryEntry,
+        MAX_NAME_LEN,
+    },
     disk::Disk,
     file::File,
     inode::{
         Inode,
         Kind,
+        List,
+        Mapping,
     },
+    split_path,
     superblock::Superblock,
 };
 
@@ -87,10 +98,25 @@ impl FileSystem {
         block_cache_capacity: usize,
         resolve_cache_capacity: usize,
     ) -> Result<FileSystem> {
-        let disk = Disk::new(disk)?;
-        let block_count = disk.block_count()?;
+        Self::mount_device(
+            Disk::new(disk)?,
+            block_cache_capacity,
+            resolve_cache_capacity,
+        )
+    }
 
-        BlockCache::init(disk, block_count, block_cache_capacity)?;
+    /// [Монтирует](https://en.wikipedia.org/wiki/Mount_(computing))
+    /// файловую систему с блочного устройства `device`.
+    /// Параметры `block_cache_capacity` и `resolve_cache_capacity`
+    /// аналогичны параметрам [`FileSystem::mount()`].
+    pub fn mount_device<D: BlockDevice + 'static>(
+        device: D,
+        block_cache_capacity: usize,
+        resolve_cache_capacity: usize,
+    ) -> Result<FileSystem> {
+        let block_count = device.block_count()?;
+
+        BlockCache::init(Box::new(device), block_count, block_cache_capacity)?;
 
         let superblock = Superblock::new()?;
 
@@ -118,14 +144,19 @@ impl FileSystem {
 
     /// Форматирует файловую систему на диске номер `disk`.
     pub fn format(disk: usize) -> Result<()> {
-        let disk = Disk::new(disk)?;
-        let block_count = disk.block_count()?;
+        Self::format_device(Disk::new(disk)?)
+    }
+
+    /// Форматирует файловую систему на блочном устройстве `device`.
+    pub fn format_device<D: BlockDevice + 'static>(device: D) -> Result<()> {
+        let block_count = device.block_count()?;
 
         let default_blocks_per_inode = 4;
         let block_cache_capacity = 1 << 10;
         let inode_count = block_count / default_blocks_per_inode;
 
-        BlockCache::init(disk, block_count, block_cache_capacity)?;
+        let disk = format!("{device:?}");
+        BlockCache::init(Box::new(device), block_count, block_cache_capacity)?;
 
         let superblock = Superblock::format(block_count, inode_count)?;
         Bitmap::format(superblock.block_bitmap().start, superblock.blocks())?;
@@ -258,6 +289,34 @@ impl FileSystem {
         Ok(list)
     }
 
+    /// Проходит от корня файловой системы по заданному полному пути `path` к директории
+    /// и возвращает итератор по её файлам и поддиректориям.
+    /// Итератор выдаёт имя и тип каждой занятой записи директории,
+    /// свободные записи пропускаются.
+    ///
+    /// Итератор держит [`FileSystem`] заимствованной,
+    /// поэтому пока он жив, директорию нельзя изменить.
+    ///
+    /// Возвращает ошибки:
+    ///   - [`Error::FileNotFound`] если пути `path` нет в файловой системе.
+    ///   - [`Error::NotDirectory`] если `path` указывает на файл.
+    pub fn read_dir(
+        &mut self,
+        path: &str,
+    ) -> Result<ReadDir<'_>> {
+        let directory = self.open(path)?.inode();
+
+        let (before, rest) = self.inodes.split_at_mut(directory);
+        let (inode, after) = rest.split_first_mut().ok_or(FileNotFound)?;
+
+        Ok(ReadDir {
+            after,
+            before,
+            directory,
+            list: inode.list()?,
+        })
+    }
+
     /// Вставляет в директорию запись с именем `name` и типом `kind`.
     /// Обновляет как время модификации выделенной записи, так и время модификации самой директории.
     ///
@@ -267,24 +326,84 @@ impl FileSystem {
         directory: &File,
         name: &str,
         kind: Kind,
+    ) -> Result<File> {
+        self.insert_with_mapping(directory, name, kind, Mapping::Forest)
+    }
+
+    /// Аналогичен [`FileSystem::insert()`],
+    /// но дополнительно задаёт способ `mapping` отображения блоков данных
+    /// новой записи в номера блоков файловой системы.
+    pub fn insert_with_mapping(
+        &mut self,
+        directory: &File,
+        name: &str,
+        kind: Kind,
+        mapping: Mapping,
     ) -> Result<File> {
         let entry = self.inodes[directory.inode()].insert(name, &mut self.block_bitmap)?;
         let inode = self.inode_bitmap.allocate()?;
         entry.set_inode(inode);
-        self.inodes[inode].init(kind);
+        self.inodes[inode].init_with_mapping(kind, mapping);
 
         Ok(File::new(inode, name, directory.inode()))
     }
 
+    /// Создаёт по заданному полному пути `path` файл или поддиректорию типа `kind`.
+    ///
+    /// Возвращает ошибки:
+    ///   - [`Error::FileExists`] если запись с таким именем уже есть.
+    ///   - [`Error::FileNotFound`] если нет родительской директории.
+    ///   - [`Error::InvalidArgument`] если последний элемент пути пуст или недопустим.
+    ///   - [`Error::NotDirectory`] если родительский элемент пути не является директорией.
+    pub fn create(
+        &mut self,
+        path: &str,
+        kind: Kind,
+    ) -> Result<File> {
+        let (directory, name) = split_path(path)?;
+        let directory = self.open(directory)?;
+        self.insert(&directory, name, kind)
+    }
+
+    /// Удаляет файл или пустую поддиректорию по заданному полному пути `path`.
+    /// Жёстких ссылок в файловой системе нет,
+    /// поэтому [inode](https://en.wikipedia.org/wiki/Inode) и его блоки освобождаются сразу.
+    ///
+    /// Возвращает ошибки:
+    ///   - [`Error::FileNotFound`] если какого-либо из элементов пути нет.
+    ///   - [`Error::InvalidArgument`] если последний элемент пути пуст, например для корня.
+    ///   - [`Error::NotEmpty`] если `path` указывает на непустую директорию.
+    pub fn unlink(
+        &mut self,
+        path: &str,
+    ) -> Result<()> {
+        split_path(path)?;
+        let file = self.open(path)?;
+
+        let inode = &mut self.inodes[file.inode()];
+        if inode.kind() == Kind::Directory && inode.list()?.next().is_some() {
+            return Err(NotEmpty);
+        }
+
+        self.remove(&file)
+    }
+
     /// Удаляет файл.
+    ///
+    /// Сначала освобождает запись в директории, затем блоки данных
+    /// и только потом сам [inode](https://en.wikipedia.org/wiki/Inode).
+    /// Поэтому при прерывании на любом шаге освобождённые ресурсы никем не используются,
+    /// а ещё не освобождённые --- в худшем случае теряются, но не выделяются повторно.
     pub fn remove(
         &mut self,
         file: &File,
     ) -> Result<()> {
         self.resolve_cache.remove(&(file.parent(), file.name().into()));
         self.inodes[file.parent()].find(file.name())?.set_free();
+        self.remove_inode(file.inode())?;
         self.inode_bitmap.set_free(file.inode());
-        self.remove_inode(file.inode())
+
+        Ok(())
     }
 
     /// Читает из файла по смещению `offset` в буфер `buffer` столько байт,
@@ -332,6 +451,22 @@ impl FileSystem {
         self.superblock.blocks().count() * BLOCK_SIZE - self.free_space()
     }
 
+    /// Возвращает сводку об использовании блоков и
+    /// [inode](https://en.wikipedia.org/wiki/Inode)
+    /// файловой системы, аналогичную выводу команды
+    /// [`df`](https://en.wikipedia.org/wiki/Df_(Unix)).
+    ///
+    /// Не сканирует битовые карты,
+    /// так как [`Bitmap`] поддерживает количество свободных элементов инкрементально.
+    pub fn usage(&self) -> FsUsage {
+        FsUsage {
+            block_count: self.superblock.blocks().count(),
+            free_block_count: self.block_bitmap.free_count(),
+            free_inode_count: self.inode_bitmap.free_count(),
+            inode_count: self.superblock.inodes().count(),
+        }
+    }
+
     /// Удаляет `inode`.
     pub fn remove_inode(
         &mut self,
@@ -355,6 +490,81 @@ impl Drop for FileSystem {
     }
 }
 
+/// Сводка об использовании блоков и
+/// [inode](https://en.wikipedia.org/wiki/Inode)
+/// файловой системы.
+///
+/// Учитывает только блоки и
+/// [inode](https://en.wikipedia.org/wiki/Inode),
+/// доступные для пользовательских файлов и директорий.
+/// Зарезервированные под метаданные самой файловой системы в неё не входят.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct FsUsage {
+    /// Полное количество блоков для пользовательских данных и директорий.
+    block_count: usize,
+
+    /// Количество свободных блоков.
+    free_block_count: usize,
+
+    /// Количество свободных [inode](https://en.wikipedia.org/wiki/Inode).
+    free_inode_count: usize,
+
+    /// Полное количество [inode](https://en.wikipedia.org/wiki/Inode)
+    /// для пользовательских файлов и директорий.
+    inode_count: usize,
+}
+
+impl FsUsage {
+    /// Полное количество блоков для пользовательских данных и директорий.
+    pub fn block_count(&self) -> usize {
+        self.block_count
+    }
+
+    /// Количество свободных блоков.
+    pub fn free_block_count(&self) -> usize {
+        self.free_block_count
+    }
+
+    /// Количество занятых блоков.
+    pub fn used_block_count(&self) -> usize {
+        self.block_count - self.free_block_count
+    }
+
+    /// Полное количество [inode](https://en.wikipedia.org/wiki/Inode)
+    /// для пользовательских файлов и директорий.
+    pub fn inode_count(&self) -> usize {
+        self.inode_count
+    }
+
+    /// Количество свободных [inode](https://en.wikipedia.org/wiki/Inode).
+    pub fn free_inode_count(&self) -> usize {
+        self.free_inode_count
+    }
+
+    /// Количество занятых [inode](https://en.wikipedia.org/wiki/Inode).
+    pub fn used_inode_count(&self) -> usize {
+        self.inode_count - self.free_inode_count
+    }
+}
+
+impl fmt::Display for FsUsage {
+    fn fmt(
+        &self,
+        formatter: &mut fmt::Formatter,
+    ) -> fmt::Result {
+        write!(
+            formatter,
+            "{{ blocks: {} / {} = {} / {}, inodes: {} / {} }}",
+            self.used_block_count(),
+            self.block_count(),
+            Size::bytes(self.used_block_count() * BLOCK_SIZE),
+            Size::bytes(self.block_count() * BLOCK_SIZE),
+            self.used_inode_count(),
+            self.inode_count(),
+        )
+    }
+}
+
 /// Элемент списка файлов и поддиректорий в директории.
 #[derive(Clone, Debug)]
 pub struct Entry {
@@ -375,6 +585,23 @@ pub struct Entry {
 }
 
 impl Entry {
+    /// Создаёт элемент списка файлов и поддиректорий.
+    pub(super) fn new(
+        inode: usize,
+        kind: Kind,
+        modify_time: DateTime<Utc>,
+        name: &str,
+        size: usize,
+    ) -> Self {
+        Self {
+            inode,
+            kind,
+            modify_time,
+            name: name.into(),
+            size,
+        }
+    }
+
     /// Тип --- файл или поддиректория.
     pub fn kind(&self) -> Kind {
         self.kind
@@ -414,6 +641,56 @@ impl fmt::Display for Entry {
     }
 }
 
+/// Итератор по файлам и поддиректориям в директории, см. [`FileSystem::read_dir()`].
+pub struct ReadDir<'a> {
+    /// [Inode](https://en.wikipedia.org/wiki/Inode) файловой системы
+    /// с номерами больше номера самой директории.
+    after: &'a [Inode],
+
+    /// [Inode](https://en.wikipedia.org/wiki/Inode) файловой системы
+    /// с номерами меньше номера самой директории.
+    before: &'a [Inode],
+
+    /// Номер [inode](https://en.wikipedia.org/wiki/Inode) самой директории.
+    directory: usize,
+
+    /// Итератор по занятым записям директории.
+    list: List<'a>,
+}
+
+impl ReadDir<'_> {
+    /// Возвращает имя и тип записи директории `directory_entry`.
+    ///
+    /// Возвращает ошибку [`Error::Medium`],
+    /// если запись на диске не корректна.
+    fn entry(
+        &self,
+        directory_entry: &DirectoryEntry,
+    ) -> Result<(NameString<MAX_NAME_LEN>, Kind)> {
+        let name = NameString::try_from(directory_entry.name()?).map_err(|_| Medium)?;
+
+        let inode = directory_entry.inode();
+        let kind = if inode < self.directory {
+            self.before[inode].kind()
+        } else if inode == self.directory {
+            Kind::Directory
+        } else {
+            self.after.get(inode - self.directory - 1).ok_or(Medium)?.kind()
+        };
+
+        Ok((name, kind))
+    }
+}
+
+impl Iterator for ReadDir<'_> {
+    type Item = Result<(NameString<MAX_NAME_LEN>, Kind)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let directory_entry = self.list.next()?;
+        Some(self.entry(directory_entry))
+    }
+}
+
 #[doc(hidden)]
 pub mod test_scaffolding {
     use ku::error::Result;
@@ -438,4 +715,11 @@ pub mod test_scaffolding {
     ) -> Result<()> {
         file_system.remove_inode(file.inode())
     }
+
+    pub fn extent_count(
+        file_system: &FileSystem,
+        file: &File,
+    ) -> Option<usize> {
+        file_system.inodes[file.inode()].extent_count()
+    }
 }
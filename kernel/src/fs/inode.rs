@@ -12,6 +12,7 @@ use core::{
     ops::Add,
 };
 
+use bitflags::bitflags;
 use chrono::{
     DateTime,
     Utc,
@@ -24,6 +25,7 @@ use ku::{
             FileExists,
             FileNotFound,
             InvalidArgument,
+            Medium,
             NoDisk,
             NotDirectory,
             NotFile,
@@ -46,6 +48,10 @@ use super::{
         Cache,
     },
     directory_entry::DirectoryEntry,
+    extent::{
+        Extent,
+        Extents,
+    },
 };
 
 // Used in docs.
@@ -66,6 +72,28 @@ pub enum Kind {
 }
 // ANCHOR_END: kind
 
+/// Способ отображения блоков [`Inode`] в номера блоков файловой системы.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Mapping {
+    /// Лес косвенных блоков, хранящих номера блоков данных по одному.
+    #[default]
+    Forest,
+
+    /// Список [экстентов](https://en.wikipedia.org/wiki/Extent_(file_systems)) ---
+    /// непрерывных диапазонов блоков данных.
+    /// Экономит метаданные для больших непрерывных файлов.
+    Extents,
+}
+
+bitflags! {
+    /// Флаги [`Inode`].
+    #[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+    struct Flags: u32 {
+        /// Блоки [`Inode`] отображаются списком экстентов, см. [`Mapping::Extents`].
+        const EXTENTS = 1 << 0;
+    }
+}
+
 // ANCHOR: inode
 /// Метаинформация об объекте с данными --- [inode](https://en.wikipedia.org/wiki/Inode).
 #[derive(Clone, Copy, Debug, Default)]
@@ -77,11 +105,19 @@ pub(super) struct Inode {
     /// Время последней модификации [`Inode`].
     modify_time: DateTime<Utc>,
 
+    /// Флаги [`Inode`].
+    /// Занимают место, которое иначе ушло бы на выравнивание следующего поля.
+    flags: Flags,
+
     /// Размер данных в байтах.
     size: usize,
 
     /// Лес, отвечающий за отображение блоков [`Inode`]
     /// в номера блоков файловой системы.
+    ///
+    /// Для [`Mapping::Extents`] вместо леса хранит
+    /// номер блока с таблицей экстентов в записи [`EXTENT_TABLE`] и
+    /// количество экстентов в записи [`EXTENT_COUNT`].
     root_blocks: Forest,
 }
 // ANCHOR_END: inode
@@ -91,9 +127,23 @@ impl Inode {
     pub(super) fn init(
         &mut self,
         kind: Kind,
+    ) {
+        self.init_with_mapping(kind, Mapping::Forest);
+    }
+
+    /// Инициализирует [`Inode`] заданным типом `kind`
+    /// и способом отображения блоков `mapping`.
+    pub(super) fn init_with_mapping(
+        &mut self,
+        kind: Kind,
+        mapping: Mapping,
     ) {
         self.kind = kind;
         self.modify_time = time::now_ms();
+        self.flags = match mapping {
+            Mapping::Forest => Flags::empty(),
+            Mapping::Extents => Flags::EXTENTS,
+        };
         self.size = 0;
         self.root_blocks.fill(NO_BLOCK);
     }
@@ -103,6 +153,20 @@ impl Inode {
         self.kind
     }
 
+    /// Способ отображения блоков [`Inode`] в номера блоков файловой системы.
+    pub(super) fn mapping(&self) -> Mapping {
+        if self.flags.contains(Flags::EXTENTS) {
+            Mapping::Extents
+        } else {
+            Mapping::Forest
+        }
+    }
+
+    /// Количество экстентов для [`Mapping::Extents`] или [`None`] для [`Mapping::Forest`].
+    pub(super) fn extent_count(&self) -> Option<usize> {
+        (self.mapping() == Mapping::Extents).then_some(self.root_blocks[EXTENT_COUNT])
+    }
+
     /// Удаляет [`Inode`].
     pub(super) fn remove(
         &mut self,
@@ -134,6 +198,10 @@ impl Inode {
         block_bitmap: &mut Bitmap,
     ) -> Result<()> {
         // ANCHOR_END: set_size
+        if self.mapping() == Mapping::Extents {
+            return self.set_extents_size(size, block_bitmap);
+        }
+
         // TODO: your code here.
         unimplemented!();
     }
@@ -296,8 +364,116 @@ impl Inode {
     ) -> Result<Block<Virt>> {
         // ANCHOR_END: block
         assert!(inode_block_number < self.size.div_ceil(BLOCK_SIZE));
+
+        if self.mapping() == Mapping::Extents {
+            let block = self.extents(cache)?.block(inode_block_number)?;
+            return Ok(cache.block(block));
+        }
+
         Ok(cache.block(*self.block_entry(inode_block_number, None, cache)?))
     }
+
+    /// Реализация [`Inode::set_size()`] для [`Mapping::Extents`].
+    ///
+    /// Таблицу экстентов выделяет при появлении первого блока данных и
+    /// освобождает при удалении последнего.
+    /// Если при расширении не удалось выделить все нужные блоки,
+    /// возвращает [`Inode`] в исходное состояние.
+    fn set_extents_size(
+        &mut self,
+        size: usize,
+        block_bitmap: &mut Bitmap,
+    ) -> Result<()> {
+        if size > Self::max_size() {
+            return Err(NoDisk);
+        }
+
+        let cache = BlockCache::cache()?;
+        let old_block_count = self.size.div_ceil(BLOCK_SIZE);
+        let block_count = size.div_ceil(BLOCK_SIZE);
+
+        if size < self.size && size % BLOCK_SIZE != 0 {
+            let tail = size % BLOCK_SIZE;
+            unsafe {
+                self.block(block_count - 1, cache)?
+                    .try_into_mut_slice::<u8>()
+                    .expect(Self::BAD_MEMORY_BLOCK)[tail ..]
+                    .fill(0);
+            }
+        }
+
+        if block_count > 0 && self.root_blocks[EXTENT_TABLE] == NO_BLOCK {
+            let table = block_bitmap.allocate()?;
+            unsafe {
+                cache
+                    .block(table)
+                    .try_into_mut_slice::<usize>()
+                    .expect(Self::BAD_MEMORY_BLOCK)
+                    .fill(0);
+            }
+            self.root_blocks[EXTENT_TABLE] = table;
+            self.root_blocks[EXTENT_COUNT] = 0;
+        }
+
+        if self.root_blocks[EXTENT_TABLE] != NO_BLOCK {
+            let mut extents = self.extents(cache)?;
+
+            while extents.block_count() < block_count {
+                match extents.push(block_bitmap) {
+                    Ok(block) => unsafe {
+                        cache
+                            .block(block)
+                            .try_into_mut_slice::<usize>()
+                            .expect(Self::BAD_MEMORY_BLOCK)
+                            .fill(0);
+                    },
+                    Err(error) => {
+                        while extents.block_count() > old_block_count {
+                            extents.pop(block_bitmap);
+                        }
+                        return Err(error);
+                    },
+                }
+            }
+
+            while extents.block_count() > block_count {
+                extents.pop(block_bitmap);
+            }
+        }
+
+        if block_count == 0 && self.root_blocks[EXTENT_TABLE] != NO_BLOCK {
+            block_bitmap.set_free(self.root_blocks[EXTENT_TABLE]);
+            self.root_blocks.fill(NO_BLOCK);
+        }
+
+        self.size = size;
+        self.modify_time = time::now_ms();
+
+        Ok(())
+    }
+
+    /// Возвращает список экстентов [`Inode`] для [`Mapping::Extents`].
+    ///
+    /// Возвращает ошибку [`Error::Medium`],
+    /// если таблица экстентов не выделена или её метаданные не корректны.
+    fn extents(
+        &mut self,
+        cache: Cache,
+    ) -> Result<Extents<'_>> {
+        let table = self.root_blocks[EXTENT_TABLE];
+        if table == NO_BLOCK {
+            return Err(Medium);
+        }
+
+        let table = unsafe {
+            cache.block(table).try_into_mut_slice::<Extent>().expect(Self::BAD_MEMORY_BLOCK)
+        };
+
+        Extents::new(table, &mut self.root_blocks[EXTENT_COUNT]).map_err(|_| Medium)
+    }
+
+    /// Блок виртуальных адресов не подходит для хранения метаданных [`Inode`].
+    const BAD_MEMORY_BLOCK: &'static str = "bad memory block for inode metadata";
 }
 
 impl fmt::Display for Inode {
@@ -488,6 +664,14 @@ const MAX_HEIGHT: usize = 4;
 /// Зарезервированный номер блока, означающий что блок не выделен.
 const NO_BLOCK: usize = 0;
 
+/// Запись [`Inode::root_blocks`], хранящая для [`Mapping::Extents`]
+/// номер блока с таблицей экстентов.
+const EXTENT_TABLE: usize = 0;
+
+/// Запись [`Inode::root_blocks`], хранящая для [`Mapping::Extents`]
+/// количество экстентов в таблице.
+const EXTENT_COUNT: usize = 1;
+
 #[doc(hidden)]
 pub mod test_scaffolding {
     use ku::error::Result;
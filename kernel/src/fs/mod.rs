@@ -8,6 +8,10 @@ mod bitmap;
 /// для ускорения работы с диском за счёт кэширования блоков диска в памяти.
 mod block_cache;
 
+/// Интерфейс [блочного устройства](https://en.wikipedia.org/wiki/Device_file#Block_devices),
+/// отделяющий файловую систему от конкретного драйвера диска.
+mod block_device;
+
 /// Запись [директории](https://en.wikipedia.org/wiki/Directory_(computing)) с [`Inode`],
 /// который содержится в этой директории, и его именем.
 mod directory_entry;
@@ -15,6 +19,10 @@ mod directory_entry;
 /// Интерфейс для работы с [PATA](https://en.wikipedia.org/wiki/Parallel_ATA)--дисками.
 mod disk;
 
+/// Отображение блоков [`Inode`] в номера блоков файловой системы
+/// списком [экстентов](https://en.wikipedia.org/wiki/Extent_(file_systems)).
+mod extent;
+
 /// Интерфейс к файлaм и директориям файловой системы.
 mod file;
 
@@ -24,30 +32,113 @@ mod file_system;
 /// Метаинформация об объекте с данными --- [inode](https://en.wikipedia.org/wiki/Inode).
 mod inode;
 
+/// [RAM--диск](https://en.wikipedia.org/wiki/RAM_drive) ---
+/// блочное устройство в памяти.
+mod ram_disk;
+
 /// Суперблок
 /// ([superblock](https://en.wikipedia.org/wiki/Unix_File_System#Design))
 /// файловой системы.
 mod superblock;
 
-use ku::memory::Page;
+/// Файловая система в памяти ---
+/// [tmpfs](https://en.wikipedia.org/wiki/Tmpfs).
+mod tmp_fs;
+
+use lazy_static::lazy_static;
+
+use ku::{
+    error::{
+        Error::InvalidArgument,
+        Result,
+    },
+    log::info,
+    memory::Page,
+    sync::spinlock::{
+        Spinlock,
+        SpinlockGuard,
+    },
+};
+
+pub(crate) use disk::interrupt as ata_interrupt;
 
-pub use block_cache::BlockCache;
+pub use block_cache::{
+    BlockCache,
+    CacheStats,
+};
+pub use block_device::BlockDevice;
 pub use directory_entry::MAX_NAME_LEN;
 pub use file::File;
-pub use file_system::FileSystem;
-pub use inode::Kind;
+pub use file_system::{
+    FileSystem,
+    FsUsage,
+    ReadDir,
+};
+pub use inode::{
+    Kind,
+    Mapping,
+};
+pub use ram_disk::RamDisk;
+pub use tmp_fs::TmpFs;
 
 // Used in docs.
 #[allow(unused)]
 use {
     bitmap::Bitmap,
     inode::Inode,
+    ku::error::Error,
     superblock::Superblock,
 };
 
+/// Инициализирует подсистему файловых систем ---
+/// монтирует пустую [`TmpFs`] в [`TMP_MOUNT_POINT`].
+/// Она не зависит от диска, поэтому доступна сразу после инициализации памяти.
+pub fn init() {
+    *TMP_FS.lock() = TmpFs::new();
+
+    info!(mount_point = TMP_MOUNT_POINT, "mounted tmpfs");
+}
+
+/// Возвращает [`TmpFs`], смонтированную в [`TMP_MOUNT_POINT`].
+/// Пути внутри неё получаются из полных путей функцией [`tmp_path()`].
+pub fn tmp_fs() -> SpinlockGuard<'static, TmpFs> {
+    TMP_FS.lock()
+}
+
+/// Если полный путь `path` лежит внутри [`TMP_MOUNT_POINT`],
+/// возвращает путь относительно корня [`tmp_fs()`].
+/// Иначе возвращает [`None`].
+pub fn tmp_path(path: &str) -> Option<&str> {
+    let path = path.strip_prefix(TMP_MOUNT_POINT)?;
+
+    (path.is_empty() || path.starts_with('/')).then_some(path)
+}
+
+/// Разделяет полный путь `path` на путь к родительской директории и имя последнего элемента.
+///
+/// Возвращает ошибку [`Error::InvalidArgument`],
+/// если у пути нет последнего элемента, например для корня или пути, заканчивающегося на `/`.
+fn split_path(path: &str) -> Result<(&str, &str)> {
+    let (directory, name) = path.rsplit_once('/').unwrap_or(("", path));
+
+    if name.is_empty() {
+        Err(InvalidArgument)
+    } else {
+        Ok((directory, name))
+    }
+}
+
 /// Размер блока данных файловой системы.
 const BLOCK_SIZE: usize = Page::SIZE;
 
+/// Точка монтирования [`tmp_fs()`].
+pub const TMP_MOUNT_POINT: &str = "/tmp";
+
+lazy_static! {
+    /// Файловая система в памяти, смонтированная в [`TMP_MOUNT_POINT`].
+    static ref TMP_FS: Spinlock<TmpFs> = Spinlock::new(TmpFs::new());
+}
+
 #[doc(hidden)]
 pub mod test_scaffolding {
     pub use super::{
@@ -60,4 +151,5 @@ pub mod test_scaffolding {
     };
 
     pub const BLOCK_SIZE: usize = super::BLOCK_SIZE;
+    pub const SECTOR_SIZE: usize = super::disk::SECTOR_SIZE;
 }
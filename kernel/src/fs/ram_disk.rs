@@ -0,0 +1,92 @@
+use alloc::{
+    sync::Arc,
+    vec,
+    vec::Vec,
+};
+use core::ops::Range;
+
+use ku::{
+    error::{
+        Error::InvalidArgument,
+        Result,
+    },
+    sync::FastSpinlock,
+};
+
+use super::{
+    BLOCK_SIZE,
+    block_device::BlockDevice,
+};
+
+// Used in docs.
+#[allow(unused)]
+use ku::error::Error;
+
+/// [RAM--диск](https://en.wikipedia.org/wiki/RAM_drive) ---
+/// [`BlockDevice`], хранящий все блоки в куче.
+///
+/// Копии [`RamDisk`] разделяют одно и то же содержимое.
+/// Это позволяет, например, отформатировать диск и затем смонтировать его же копию.
+#[derive(Clone, Debug)]
+pub struct RamDisk {
+    /// Содержимое диска.
+    data: Arc<FastSpinlock<Vec<u8>>>,
+}
+
+impl RamDisk {
+    /// Создаёт заполненный нулями RAM--диск из `block_count` блоков.
+    pub fn new(block_count: usize) -> Self {
+        Self {
+            data: Arc::new(FastSpinlock::new(vec![0; block_count * BLOCK_SIZE])),
+        }
+    }
+
+    /// Диапазон байт, которые занимает блок номер `block_number`.
+    ///
+    /// Возвращает ошибку [`Error::InvalidArgument`],
+    /// если блока с таким номером нет или размер буфера `buffer_size` не равен [`BLOCK_SIZE`].
+    fn block(
+        &self,
+        block_number: usize,
+        buffer_size: usize,
+    ) -> Result<Range<usize>> {
+        if block_number < self.block_count()? && buffer_size == BLOCK_SIZE {
+            let start = block_number * BLOCK_SIZE;
+            Ok(start .. start + BLOCK_SIZE)
+        } else {
+            Err(InvalidArgument)
+        }
+    }
+}
+
+impl BlockDevice for RamDisk {
+    fn block_count(&self) -> Result<usize> {
+        Ok(self.data.lock().len() / BLOCK_SIZE)
+    }
+
+    fn read_block(
+        &mut self,
+        block_number: usize,
+        buffer: &mut [u8],
+    ) -> Result<()> {
+        let block = self.block(block_number, buffer.len())?;
+        buffer.copy_from_slice(&self.data.lock()[block]);
+
+        Ok(())
+    }
+
+    fn write_block(
+        &mut self,
+        block_number: usize,
+        buffer: &[u8],
+    ) -> Result<()> {
+        let block = self.block(block_number, buffer.len())?;
+        self.data.lock()[block].copy_from_slice(buffer);
+
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
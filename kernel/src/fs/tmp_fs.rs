@@ -0,0 +1,442 @@
+use alloc::{
+    collections::BTreeMap,
+    string::String,
+    vec,
+    vec::Vec,
+};
+
+use chrono::{
+    DateTime,
+    Utc,
+};
+
+use ku::{
+    error::{
+        Error::{
+            FileExists,
+            FileNotFound,
+            InvalidArgument,
+            NotDirectory,
+            NotEmpty,
+            NotFile,
+        },
+        Result,
+    },
+    time,
+};
+
+use super::{
+    directory_entry::DirectoryEntry,
+    file::File,
+    file_system::Entry,
+    inode::Kind,
+    split_path,
+};
+
+// Used in docs.
+#[allow(unused)]
+use {
+    super::file_system::FileSystem,
+    ku::error::Error,
+};
+
+/// Файловая система, целиком расположенная в памяти ---
+/// [tmpfs](https://en.wikipedia.org/wiki/Tmpfs).
+///
+/// Не требует диска, поэтому доступна ещё до монтирования [`FileSystem`].
+/// Данные файлов хранятся в куче как растущие массивы байт,
+/// а директории --- как отображения из имён в номера узлов.
+/// Содержимое не сохраняется и теряется вместе с [`TmpFs`].
+///
+/// Предоставляет тот же интерфейс, что и [`FileSystem`].
+#[derive(Debug)]
+pub struct TmpFs {
+    /// Узлы файловой системы, индексированные номерами
+    /// [inode](https://en.wikipedia.org/wiki/Inode).
+    /// Освобождённые узлы содержат [`None`] и переиспользуются.
+    nodes: Vec<Option<Node>>,
+}
+
+impl TmpFs {
+    /// Создаёт пустую файловую систему, состоящую из одной корневой директории.
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![Some(Node::new(Kind::Directory))],
+        }
+    }
+
+    /// Проходит от корня файловой системы по заданному полному пути `path`.
+    /// Возвращает [`File`], соответствующий этому `path`.
+    ///
+    /// Возвращает ошибки:
+    ///   - [`Error::FileNotFound`] если какого-либо из элементов пути нет.
+    ///   - [`Error::NotDirectory`] если промежуточный элемент пути
+    ///     или путь, заканчивающийся на `/`, не является директорией.
+    pub fn open(
+        &mut self,
+        path: &str,
+    ) -> Result<File> {
+        let mut file = File::new(Self::ROOT, "", Self::ROOT);
+
+        for name in path.split('/').filter(|name| !name.is_empty()) {
+            file = self.find(&file, name)?;
+        }
+
+        if path.ends_with('/') && self.kind(&file) != Kind::Directory {
+            return Err(NotDirectory);
+        }
+
+        Ok(file)
+    }
+
+    /// Тип --- файл или директория.
+    ///
+    /// # Panics
+    ///
+    /// Паникует, если `file` уже удалён.
+    pub fn kind(
+        &self,
+        file: &File,
+    ) -> Kind {
+        self.live_node(file).kind
+    }
+
+    /// Время последней модификации файла или директории.
+    ///
+    /// # Panics
+    ///
+    /// Паникует, если `file` уже удалён.
+    pub fn modify_time(
+        &self,
+        file: &File,
+    ) -> DateTime<Utc> {
+        self.live_node(file).modify_time
+    }
+
+    /// Размер данных в байтах.
+    /// Для директорий всегда равен нулю.
+    ///
+    /// # Panics
+    ///
+    /// Паникует, если `file` уже удалён.
+    pub fn size(
+        &self,
+        file: &File,
+    ) -> usize {
+        self.live_node(file).data.len()
+    }
+
+    /// Устанавливает размер данных в байтах.
+    /// Если файл расширяется, то новые байты содержат нули.
+    /// Обновляет время последней модификации файла.
+    ///
+    /// Размер директории всегда равен нулю,
+    /// поэтому для неё допустим только нулевой `size`.
+    /// Иначе возвращается ошибка [`Error::NotFile`].
+    pub fn set_size(
+        &mut self,
+        file: &File,
+        size: usize,
+    ) -> Result<()> {
+        let node = self.node_mut(file.inode())?;
+
+        if node.kind == Kind::Directory && size != 0 {
+            return Err(NotFile);
+        }
+
+        node.data.resize(size, 0);
+        node.modify_time = time::now_ms();
+
+        Ok(())
+    }
+
+    /// Находит файл или поддиректорию с именем `name` в директории `directory`.
+    /// Возвращает ошибку [`Error::FileNotFound`], если такого файла нет.
+    pub fn find(
+        &mut self,
+        directory: &File,
+        name: &str,
+    ) -> Result<File> {
+        self.node(directory.inode())?
+            .entries()?
+            .get(name)
+            .map(|&inode| File::new(inode, name, directory.inode()))
+            .ok_or(FileNotFound)
+    }
+
+    /// Возвращает список файлов и поддиректорий в директории.
+    pub fn list(
+        &mut self,
+        directory: &File,
+    ) -> Result<Vec<Entry>> {
+        let mut list = Vec::new();
+
+        for (name, &inode) in self.node(directory.inode())?.entries()? {
+            let node = self.node(inode)?;
+            list.push(Entry::new(
+                inode,
+                node.kind,
+                node.modify_time,
+                name,
+                node.data.len(),
+            ));
+        }
+
+        Ok(list)
+    }
+
+    /// Вставляет в директорию запись с именем `name` и типом `kind`.
+    /// Обновляет как время модификации выделенной записи, так и время модификации самой директории.
+    ///
+    /// Возвращает ошибки:
+    ///   - [`Error::FileExists`] если запись с таким именем уже есть.
+    ///   - [`Error::InvalidArgument`] если имя `name` недопустимо.
+    pub fn insert(
+        &mut self,
+        directory: &File,
+        name: &str,
+        kind: Kind,
+    ) -> Result<File> {
+        DirectoryEntry::validate(name)?;
+
+        if self.node(directory.inode())?.entries()?.contains_key(name) {
+            return Err(FileExists);
+        }
+
+        let inode = self.allocate(kind);
+
+        let directory_node = self.node_mut(directory.inode())?;
+        directory_node.entries.insert(name.into(), inode);
+        directory_node.modify_time = time::now_ms();
+
+        Ok(File::new(inode, name, directory.inode()))
+    }
+
+    /// Создаёт по заданному полному пути `path` файл или поддиректорию типа `kind`.
+    ///
+    /// Возвращает ошибки:
+    ///   - [`Error::FileExists`] если запись с таким именем уже есть.
+    ///   - [`Error::FileNotFound`] если нет родительской директории.
+    ///   - [`Error::InvalidArgument`] если последний элемент пути пуст или недопустим.
+    ///   - [`Error::NotDirectory`] если родительский элемент пути не является директорией.
+    pub fn create(
+        &mut self,
+        path: &str,
+        kind: Kind,
+    ) -> Result<File> {
+        let (directory, name) = split_path(path)?;
+        let directory = self.open(directory)?;
+        self.insert(&directory, name, kind)
+    }
+
+    /// Удаляет файл или пустую поддиректорию по заданному полному пути `path`.
+    ///
+    /// Возвращает ошибки:
+    ///   - [`Error::FileNotFound`] если какого-либо из элементов пути нет.
+    ///   - [`Error::InvalidArgument`] если последний элемент пути пуст, например для корня.
+    ///   - [`Error::NotEmpty`] если `path` указывает на непустую директорию.
+    pub fn unlink(
+        &mut self,
+        path: &str,
+    ) -> Result<()> {
+        split_path(path)?;
+        let file = self.open(path)?;
+
+        let node = self.node(file.inode())?;
+        if node.kind == Kind::Directory && !node.entries.is_empty() {
+            return Err(NotEmpty);
+        }
+
+        self.remove(&file)
+    }
+
+    /// Удаляет файл.
+    /// Если `file` является директорией, рекурсивно удаляет и всё её содержимое.
+    pub fn remove(
+        &mut self,
+        file: &File,
+    ) -> Result<()> {
+        let parent = self.node_mut(file.parent())?;
+
+        if parent.entries.get(file.name()) != Some(&file.inode()) {
+            return Err(FileNotFound);
+        }
+
+        parent.entries.remove(file.name());
+        parent.modify_time = time::now_ms();
+
+        self.free(file.inode());
+
+        Ok(())
+    }
+
+    /// Читает из файла по смещению `offset` в буфер `buffer` столько байт,
+    /// сколько остаётся до конца файла или до конца буфера.
+    ///
+    /// Возвращает количество прочитанных байт.
+    /// Если `offset` равен размеру файла, возвращает `0` прочитанных байт.
+    ///
+    /// Возвращает ошибки:
+    ///   - [`Error::NotFile`] если `file` не является файлом.
+    ///   - [`Error::InvalidArgument`] если `offset` превышает размер файла.
+    pub fn read(
+        &mut self,
+        file: &File,
+        offset: usize,
+        buffer: &mut [u8],
+    ) -> Result<usize> {
+        let data = self.node(file.inode())?.data()?;
+        let tail = data.get(offset ..).ok_or(InvalidArgument)?;
+        let len = buffer.len().min(tail.len());
+
+        buffer[.. len].copy_from_slice(&tail[.. len]);
+
+        Ok(len)
+    }
+
+    /// Записывает в файл по смещению `offset` байты из буфера `buffer`.
+    /// При необходимости расширяет размер файла.
+    ///
+    /// Возвращает количество записанных байт.
+    /// Если `offset` превышает размер файла, расширяет файл нулями до заданного `offset`.
+    ///
+    /// Возвращает ошибку [`Error::NotFile`] если `file` не является файлом.
+    pub fn write(
+        &mut self,
+        file: &File,
+        offset: usize,
+        buffer: &[u8],
+    ) -> Result<usize> {
+        let node = self.node_mut(file.inode())?;
+        if node.kind != Kind::File {
+            return Err(NotFile);
+        }
+
+        let end = offset.checked_add(buffer.len()).ok_or(InvalidArgument)?;
+        if node.data.len() < end {
+            node.data.resize(end, 0);
+        }
+
+        node.data[offset .. end].copy_from_slice(buffer);
+        node.modify_time = time::now_ms();
+
+        Ok(buffer.len())
+    }
+
+    /// Возвращает суммарный размер данных всех файлов в байтах.
+    pub fn used_space(&self) -> usize {
+        self.nodes.iter().flatten().map(|node| node.data.len()).sum()
+    }
+
+    /// Выделяет новый узел типа `kind`, переиспользуя освобождённые.
+    /// Возвращает его номер.
+    fn allocate(
+        &mut self,
+        kind: Kind,
+    ) -> usize {
+        let node = Some(Node::new(kind));
+
+        if let Some(inode) = self.nodes.iter().position(Option::is_none) {
+            self.nodes[inode] = node;
+            inode
+        } else {
+            self.nodes.push(node);
+            self.nodes.len() - 1
+        }
+    }
+
+    /// Освобождает узел `inode` и, если это директория, все узлы в ней.
+    fn free(
+        &mut self,
+        inode: usize,
+    ) {
+        if let Some(node) = self.nodes[inode].take() {
+            for inode in node.entries.into_values() {
+                self.free(inode);
+            }
+        }
+    }
+
+    /// Возвращает узел `inode` или ошибку [`Error::FileNotFound`], если он удалён.
+    fn node(
+        &self,
+        inode: usize,
+    ) -> Result<&Node> {
+        self.nodes.get(inode).and_then(Option::as_ref).ok_or(FileNotFound)
+    }
+
+    /// Возвращает узел `inode` или ошибку [`Error::FileNotFound`], если он удалён.
+    fn node_mut(
+        &mut self,
+        inode: usize,
+    ) -> Result<&mut Node> {
+        self.nodes.get_mut(inode).and_then(Option::as_mut).ok_or(FileNotFound)
+    }
+
+    /// Возвращает узел, соответствующий `file`.
+    ///
+    /// # Panics
+    ///
+    /// Паникует, если `file` уже удалён.
+    fn live_node(
+        &self,
+        file: &File,
+    ) -> &Node {
+        self.node(file.inode()).expect("the file is removed")
+    }
+
+    /// Номер [inode](https://en.wikipedia.org/wiki/Inode) корневой директории.
+    const ROOT: usize = 0;
+}
+
+impl Default for TmpFs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Узел [`TmpFs`] --- файл или директория.
+#[derive(Debug)]
+struct Node {
+    /// Данные файла. Для директории всегда пусты.
+    data: Vec<u8>,
+
+    /// Записи директории --- имена и номера узлов. Для файла всегда пусты.
+    entries: BTreeMap<String, usize>,
+
+    /// Тип --- файл или директория.
+    kind: Kind,
+
+    /// Время последней модификации.
+    modify_time: DateTime<Utc>,
+}
+
+impl Node {
+    /// Создаёт пустой узел типа `kind`.
+    fn new(kind: Kind) -> Self {
+        Self {
+            data: Vec::new(),
+            entries: BTreeMap::new(),
+            kind,
+            modify_time: time::now_ms(),
+        }
+    }
+
+    /// Данные файла или ошибка [`Error::NotFile`] для директории.
+    fn data(&self) -> Result<&[u8]> {
+        if self.kind == Kind::File {
+            Ok(&self.data)
+        } else {
+            Err(NotFile)
+        }
+    }
+
+    /// Записи директории или ошибка [`Error::NotDirectory`] для файла.
+    fn entries(&self) -> Result<&BTreeMap<String, usize>> {
+        if self.kind == Kind::Directory {
+            Ok(&self.entries)
+        } else {
+            Err(NotDirectory)
+        }
+    }
+}
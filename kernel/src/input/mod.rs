@@ -0,0 +1,125 @@
+/// Драйвер контроллера клавиатуры
+/// [PS/2](https://en.wikipedia.org/wiki/PS/2_port).
+pub(crate) mod ps2;
+
+/// Очередь [`EventQueue`] событий клавиатуры,
+/// которую обработчик прерывания пополняет без блокировок.
+mod queue;
+
+use bitflags::bitflags;
+
+use queue::EventQueue;
+
+/// Событие клавиатуры.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum KeyEvent {
+    /// Нажатие клавиши --- make code.
+    Press {
+        /// Нажатая клавиша.
+        key: Key,
+
+        /// Модификаторы, действовавшие в момент нажатия,
+        /// с учётом самой клавиши [`Key`].
+        modifiers: Modifiers,
+    },
+
+    /// Отпускание клавиши --- break code.
+    Release {
+        /// Отпущенная клавиша.
+        key: Key,
+
+        /// Модификаторы, действовавшие в момент отпускания,
+        /// с учётом самой клавиши [`Key`].
+        modifiers: Modifiers,
+    },
+}
+
+impl KeyEvent {
+    /// Клавиша, к которой относится событие.
+    pub fn key(&self) -> Key {
+        match *self {
+            Self::Press { key, .. } | Self::Release { key, .. } => key,
+        }
+    }
+
+    /// Модификаторы, действовавшие в момент события.
+    pub fn modifiers(&self) -> Modifiers {
+        match *self {
+            Self::Press { modifiers, .. } | Self::Release { modifiers, .. } => modifiers,
+        }
+    }
+
+    /// Возвращает `true` для нажатия клавиши.
+    pub fn is_press(&self) -> bool {
+        matches!(self, Self::Press { .. })
+    }
+}
+
+/// Клавиша, заданная своим
+/// [скан-кодом из набора 1](https://wiki.osdev.org/PS/2_Keyboard#Scan_Code_Set_1).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Key {
+    /// Скан-код нажатия клавиши без префикса.
+    code: u8,
+
+    /// Скан-код клавиши начинается с префикса `0xE0`.
+    extended: bool,
+}
+
+impl Key {
+    /// Возвращает клавишу со скан-кодом нажатия `code`,
+    /// который предварялся префиксом `0xE0`, если `extended` равен `true`.
+    pub const fn new(
+        code: u8,
+        extended: bool,
+    ) -> Self {
+        Self { code, extended }
+    }
+
+    /// Скан-код нажатия клавиши без префикса.
+    pub fn code(&self) -> u8 {
+        self.code
+    }
+
+    /// Возвращает `true`, если скан-код клавиши начинается с префикса `0xE0`.
+    pub fn is_extended(&self) -> bool {
+        self.extended
+    }
+}
+
+bitflags! {
+    /// Клавиши--модификаторы.
+    #[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+    pub struct Modifiers: u8 {
+        /// Нажат левый или правый Shift.
+        const SHIFT = 1 << 0;
+
+        /// Нажат левый или правый Ctrl.
+        const CTRL = 1 << 1;
+
+        /// Нажат левый или правый Alt.
+        const ALT = 1 << 2;
+
+        /// Включён режим Caps Lock.
+        const CAPS_LOCK = 1 << 3;
+    }
+}
+
+/// Достаёт из очереди самое старое событие клавиатуры, если такое есть.
+/// Не ждёт поступления событий.
+pub fn poll_key() -> Option<KeyEvent> {
+    EVENTS.pop()
+}
+
+/// Количество событий клавиатуры, потерянных из-за переполнения очереди.
+pub fn lost_key_count() -> usize {
+    EVENTS.lost()
+}
+
+/// События клавиатуры, ещё не прочитанные ядром.
+static EVENTS: EventQueue = EventQueue::new();
+
+#[doc(hidden)]
+pub mod test_scaffolding {
+    pub use super::ps2::test_scaffolding::*;
+}
@@ -0,0 +1,178 @@
+use core::sync::atomic::{
+    AtomicBool,
+    AtomicU8,
+    Ordering,
+};
+
+use x86::io;
+
+use super::{
+    EVENTS,
+    Key,
+    KeyEvent,
+    Modifiers,
+};
+
+/// Обработчик прерывания клавиатуры.
+/// Читает очередной скан-код из контроллера и кладёт полученное событие в очередь
+/// для [`super::poll_key()`].
+/// Не блокируется --- при переполнении очереди событие теряется.
+pub(crate) fn interrupt() {
+    let scancode = unsafe { io::inb(DATA_PORT) };
+    process(scancode);
+}
+
+/// Декодирует скан-код `scancode` и кладёт полученное событие, если оно есть, в очередь.
+fn process(scancode: u8) {
+    if let Some(event) = DECODER.decode(scancode) {
+        EVENTS.push(event);
+    }
+}
+
+/// Декодер [скан-кодов набора 1](https://wiki.osdev.org/PS/2_Keyboard#Scan_Code_Set_1)
+/// в события [`KeyEvent`].
+///
+/// Хранит своё состояние в атомарных переменных,
+/// так как используется из обработчика прерывания только одного процессора.
+struct Decoder {
+    /// Предыдущий скан-код был префиксом [`EXTENDED`].
+    extended: AtomicBool,
+
+    /// Текущие модификаторы.
+    modifiers: AtomicU8,
+
+    /// Сколько ещё байт последовательности клавиши Pause нужно пропустить,
+    /// см. [`PAUSE`].
+    skip: AtomicU8,
+}
+
+impl Decoder {
+    /// Создаёт декодер без нажатых модификаторов.
+    const fn new() -> Self {
+        Self {
+            extended: AtomicBool::new(false),
+            modifiers: AtomicU8::new(0),
+            skip: AtomicU8::new(0),
+        }
+    }
+
+    /// Обрабатывает очередной скан-код `scancode`.
+    /// Возвращает событие, если `scancode` завершил его.
+    fn decode(
+        &self,
+        scancode: u8,
+    ) -> Option<KeyEvent> {
+        let skip = self.skip.load(Ordering::Relaxed);
+        if skip > 0 {
+            self.skip.store(skip - 1, Ordering::Relaxed);
+            return None;
+        }
+
+        match scancode {
+            EXTENDED => {
+                self.extended.store(true, Ordering::Relaxed);
+                return None;
+            },
+            PAUSE => {
+                self.skip.store(PAUSE_TAIL_LEN, Ordering::Relaxed);
+                return None;
+            },
+            ACKNOWLEDGE | ERROR | OVERRUN | RESEND => return None,
+            _ => {},
+        }
+
+        let extended = self.extended.swap(false, Ordering::Relaxed);
+        let is_press = scancode & BREAK == 0;
+        let key = Key::new(scancode & !BREAK, extended);
+
+        // Клавиша Print Screen окружена фиктивными нажатиями Shift с префиксом.
+        if extended && (key.code() == LEFT_SHIFT || key.code() == RIGHT_SHIFT) {
+            return None;
+        }
+
+        let modifiers = self.update_modifiers(key, is_press);
+
+        Some(
+            if is_press {
+                KeyEvent::Press { key, modifiers }
+            } else {
+                KeyEvent::Release { key, modifiers }
+            },
+        )
+    }
+
+    /// Учитывает нажатие или отпускание клавиши `key` в модификаторах
+    /// и возвращает их новое значение.
+    fn update_modifiers(
+        &self,
+        key: Key,
+        is_press: bool,
+    ) -> Modifiers {
+        let mut modifiers = Modifiers::from_bits_truncate(self.modifiers.load(Ordering::Relaxed));
+
+        match key.code() {
+            LEFT_SHIFT | RIGHT_SHIFT => modifiers.set(Modifiers::SHIFT, is_press),
+            CTRL => modifiers.set(Modifiers::CTRL, is_press),
+            ALT => modifiers.set(Modifiers::ALT, is_press),
+            CAPS_LOCK if is_press => modifiers.toggle(Modifiers::CAPS_LOCK),
+            _ => {},
+        }
+
+        self.modifiers.store(modifiers.bits(), Ordering::Relaxed);
+
+        modifiers
+    }
+}
+
+/// Состояние декодера скан-кодов клавиатуры.
+static DECODER: Decoder = Decoder::new();
+
+/// Ответ контроллера об успешном выполнении команды.
+const ACKNOWLEDGE: u8 = 0xFA;
+
+/// Скан-код нажатия левого и, с префиксом [`EXTENDED`], правого Alt.
+const ALT: u8 = 0x38;
+
+/// Бит, отличающий скан-код отпускания клавиши (break code) от скан-кода её нажатия (make code).
+const BREAK: u8 = 1 << 7;
+
+/// Скан-код нажатия Caps Lock.
+const CAPS_LOCK: u8 = 0x3A;
+
+/// Скан-код нажатия левого и, с префиксом [`EXTENDED`], правого Ctrl.
+const CTRL: u8 = 0x1D;
+
+/// Порт данных контроллера клавиатуры.
+const DATA_PORT: u16 = 0x60;
+
+/// Ответ контроллера об ошибке.
+const ERROR: u8 = 0xFF;
+
+/// Префикс скан-кодов дополнительных клавиш.
+const EXTENDED: u8 = 0xE0;
+
+/// Скан-код нажатия левого Shift.
+const LEFT_SHIFT: u8 = 0x2A;
+
+/// Ответ контроллера о переполнении его буфера.
+const OVERRUN: u8 = 0x00;
+
+/// Префикс последовательности клавиши Pause `E1 1D 45 E1 9D C5`.
+/// У Pause нет отдельного скан-кода отпускания, поэтому она не порождает событий.
+const PAUSE: u8 = 0xE1;
+
+/// Количество байт, следующих за каждым префиксом [`PAUSE`].
+const PAUSE_TAIL_LEN: u8 = 2;
+
+/// Запрос контроллера на повторную отправку команды.
+const RESEND: u8 = 0xFE;
+
+/// Скан-код нажатия правого Shift.
+const RIGHT_SHIFT: u8 = 0x36;
+
+#[doc(hidden)]
+pub mod test_scaffolding {
+    pub fn process(scancode: u8) {
+        super::process(scancode);
+    }
+}
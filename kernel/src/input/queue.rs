@@ -0,0 +1,135 @@
+use core::sync::atomic::{
+    AtomicU32,
+    AtomicUsize,
+    Ordering,
+};
+
+use super::{
+    Key,
+    KeyEvent,
+    Modifiers,
+};
+
+/// Кольцевой буфер событий клавиатуры [`KeyEvent`] без блокировок.
+///
+/// Пополняется единственным писателем --- обработчиком прерывания клавиатуры,
+/// поэтому [`EventQueue::push()`] никогда не ждёт.
+/// Читать можно с любого процессора,
+/// читатели согласуются между собой сравнением с обменом [`EventQueue::head`].
+///
+/// События хранятся упакованными в [`AtomicU32`], см. [`EventQueue::pack()`].
+pub(super) struct EventQueue {
+    /// Событие для [`EventQueue::pop()`] лежит в [`EventQueue::events`]
+    /// по индексу `head % CAPACITY`.
+    head: AtomicUsize,
+
+    /// Упакованные события.
+    events: [AtomicU32; EventQueue::CAPACITY],
+
+    /// Количество событий, не поместившихся в переполненную очередь.
+    lost: AtomicUsize,
+
+    /// Событие от [`EventQueue::push()`] записывается в [`EventQueue::events`]
+    /// по индексу `tail % CAPACITY`.
+    tail: AtomicUsize,
+}
+
+impl EventQueue {
+    /// Создаёт пустую очередь.
+    pub(super) const fn new() -> Self {
+        Self {
+            head: AtomicUsize::new(0),
+            events: [const { AtomicU32::new(0) }; Self::CAPACITY],
+            lost: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+        }
+    }
+
+    /// Добавляет событие `event` в конец очереди.
+    /// Если очередь переполнена, отбрасывает событие и учитывает его в [`EventQueue::lost()`].
+    ///
+    /// Может вызываться только единственным писателем.
+    pub(super) fn push(
+        &self,
+        event: KeyEvent,
+    ) {
+        let tail = self.tail.load(Ordering::Relaxed);
+
+        if tail.wrapping_sub(self.head.load(Ordering::Acquire)) >= Self::CAPACITY {
+            self.lost.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+
+        self.events[tail % Self::CAPACITY].store(Self::pack(event), Ordering::Relaxed);
+        self.tail.store(tail.wrapping_add(1), Ordering::Release);
+    }
+
+    /// Достаёт из начала очереди самое старое событие, если такое есть.
+    pub(super) fn pop(&self) -> Option<KeyEvent> {
+        loop {
+            let head = self.head.load(Ordering::Relaxed);
+            if head == self.tail.load(Ordering::Acquire) {
+                return None;
+            }
+
+            // Писатель не перезапишет это событие, пока `head` не сдвинется.
+            let event = self.events[head % Self::CAPACITY].load(Ordering::Relaxed);
+
+            if self
+                .head
+                .compare_exchange(
+                    head,
+                    head.wrapping_add(1),
+                    Ordering::AcqRel,
+                    Ordering::Relaxed,
+                )
+                .is_ok()
+            {
+                return Some(Self::unpack(event));
+            }
+        }
+    }
+
+    /// Количество событий, не поместившихся в переполненную очередь.
+    pub(super) fn lost(&self) -> usize {
+        self.lost.load(Ordering::Relaxed)
+    }
+
+    /// Упаковывает событие `event` в [`u32`]:
+    ///   - биты `0..=7` --- [`Key::code()`];
+    ///   - бит `8` --- [`Key::is_extended()`];
+    ///   - бит `9` --- [`KeyEvent::is_press()`];
+    ///   - биты `16..=23` --- [`KeyEvent::modifiers()`].
+    fn pack(event: KeyEvent) -> u32 {
+        let key = event.key();
+
+        u32::from(key.code()) |
+            u32::from(key.is_extended()) << EXTENDED_SHIFT |
+            u32::from(event.is_press()) << PRESS_SHIFT |
+            u32::from(event.modifiers().bits()) << MODIFIERS_SHIFT
+    }
+
+    /// Распаковывает событие, упакованное [`EventQueue::pack()`].
+    fn unpack(event: u32) -> KeyEvent {
+        let key = Key::new(event as u8, event & (1 << EXTENDED_SHIFT) != 0);
+        let modifiers = Modifiers::from_bits_truncate((event >> MODIFIERS_SHIFT) as u8);
+
+        if event & (1 << PRESS_SHIFT) != 0 {
+            KeyEvent::Press { key, modifiers }
+        } else {
+            KeyEvent::Release { key, modifiers }
+        }
+    }
+
+    /// Ёмкость очереди.
+    pub(super) const CAPACITY: usize = 64;
+}
+
+/// Номер бита признака [`Key::is_extended()`] в упакованном событии.
+const EXTENDED_SHIFT: u32 = 8;
+
+/// Номер младшего бита модификаторов [`KeyEvent::modifiers()`] в упакованном событии.
+const MODIFIERS_SHIFT: u32 = 16;
+
+/// Номер бита признака [`KeyEvent::is_press()`] в упакованном событии.
+const PRESS_SHIFT: u32 = 9;
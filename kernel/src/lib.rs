@@ -30,6 +30,9 @@ pub mod error;
 /// [Файловая система](https://en.wikipedia.org/wiki/File_system).
 pub mod fs;
 
+/// Ввод с клавиатуры.
+pub mod input;
+
 /// Поддержка журналирования макросами библиотеки [`tracing`].
 pub mod log;
 
@@ -39,6 +42,9 @@ pub mod memory;
 /// Здесь находится часть работы с процессами, которая происходит только в ядре.
 pub mod process;
 
+/// Быстрая проверка работоспособности основных подсистем ядра при загрузке.
+pub mod self_test;
+
 /// Поддержка симметричной многопроцессорности
 /// ([Symmetric multiprocessing](https://en.wikipedia.org/wiki/Symmetric_multiprocessing), SMP).
 pub mod smp;
@@ -46,6 +52,9 @@ pub mod smp;
 /// Здесь находится часть работы со временем, которая происходит только в ядре.
 pub mod time;
 
+/// Лёгкая трассировка событий ядра для профилирования.
+pub mod trace;
+
 /// Система обработки [прерываний](https://en.wikipedia.org/wiki/Interrupt).
 ///
 /// External Interrupts in the x86 system:
@@ -57,7 +66,10 @@ pub mod trap;
 use core::{
     any,
     fmt::Write,
+    mem::MaybeUninit,
     panic::PanicInfo,
+    slice,
+    str,
 };
 
 use bitflags::bitflags;
@@ -67,7 +79,10 @@ use x86::io;
 use ku::{
     self,
     SystemInfo,
-    backtrace::Backtrace,
+    backtrace::{
+        self,
+        Backtrace,
+    },
     error::Error::NoPage,
 };
 use text::println;
@@ -78,6 +93,8 @@ use log::{
 };
 use memory::gdt;
 
+pub use self_test::self_test;
+
 // Used in docs.
 #[allow(unused)]
 use error::Error;
@@ -140,8 +157,18 @@ bitflags! {
         /// Симметричной многопроцессорности: запуск Application Processors.
         const BOOT_APS = 1 << 9;
 
+        /// Симметричной многопроцессорности: контроллер прерываний IO APIC
+        /// вместо устаревшего PIC 8259.
+        const IO_APIC = 1 << 10;
+
         /// Все части подсистемы симметричной многопроцессорности.
-        const SMP = Self::LOCAL_APIC.bits() | Self::CPUS.bits() | Self::BOOT_APS.bits();
+        const SMP = Self::LOCAL_APIC.bits() |
+            Self::CPUS.bits() |
+            Self::BOOT_APS.bits() |
+            Self::IO_APIC.bits();
+
+        /// Файловые системы: [`fs::TmpFs`], смонтированная в [`fs::TMP_MOUNT_POINT`].
+        const FS = 1 << 11;
     }
 }
 
@@ -156,6 +183,10 @@ pub fn init_subsystems(
 ) {
     ku::set_system_info(&SYSTEM_INFO);
 
+    if let Some(symbols) = kernel_symbols() {
+        backtrace::set_symbols(symbols);
+    }
+
     smp::preinit();
 
     text::TEXT.lock().init();
@@ -164,6 +195,12 @@ pub fn init_subsystems(
 
     info!(now = %time::now(), tsc = ?time::timer(), "Nikka booted");
 
+    if text::TEXT.lock().serial_mut().self_test() {
+        info!("serial port self-test passed");
+    } else {
+        warn!("serial port self-test failed");
+    }
+
     gdt::init();
     trap::init();
 
@@ -182,6 +219,14 @@ pub fn init_subsystems(
     if subsystems.intersects(Subsystems::PROCESS) {
         process::init(subsystems);
     }
+
+    if subsystems.contains(Subsystems::FS) {
+        fs::init();
+    }
+
+    if cfg!(feature = "self-test") && !self_test(subsystems) {
+        warn!("kernel self-test failed");
+    }
 }
 
 /// Инициализация всех подсистем ядра.
@@ -274,5 +319,61 @@ fn panic(panic_info: &PanicInfo) -> ! {
     fail_test(panic_info)
 }
 
+/// Возвращает таблицу символов ядра для печати трассировок стека,
+/// см. [`backtrace::set_symbols()`].
+///
+/// Таблицу строит скрипт `scripts/link_kernel.py` при компоновке ядра,
+/// поэтому она соответствует именно тому ELF--файлу, который загружен ---
+/// ядру, интеграционному тесту или примеру.
+/// Он записывает в секцию `.kernel_symbols` по строке `адрес имя` на функцию
+/// в порядке возрастания адресов и резервирует ровно под эту таблицу
+/// память `.kernel_symbol_table`, в которой она здесь разбирается в пары из адреса и имени.
+/// Если таблица пуста или не разбирается, возвращает `None`
+/// и трассировки стека печатаются только адресами.
+///
+/// Вызывается один раз при инициализации ядра.
+fn kernel_symbols() -> Option<&'static [(usize, &'static str)]> {
+    let text = unsafe {
+        let start = &raw const kernel_symbols_start;
+        let end = &raw const kernel_symbols_end;
+        slice::from_raw_parts(start, end.offset_from_unsigned(start))
+    };
+    let text = str::from_utf8(text).ok()?;
+
+    let table = unsafe {
+        let start = (&raw mut kernel_symbol_table_start).cast::<MaybeUninit<(usize, &str)>>();
+        let end = (&raw mut kernel_symbol_table_end).cast::<MaybeUninit<(usize, &str)>>();
+        slice::from_raw_parts_mut(start, end.offset_from_unsigned(start))
+    };
+
+    if text.is_empty() || text.lines().count() > table.len() {
+        return None;
+    }
+
+    let mut len = 0;
+    for (pair, line) in table.iter_mut().zip(text.lines()) {
+        let (address, name) = line.split_once(' ')?;
+        pair.write((usize::from_str_radix(address, 16).ok()?, name));
+        len += 1;
+    }
+
+    Some(unsafe { table[.. len].assume_init_ref() })
+}
+
 /// Страница памяти с общей информацией о системе.
 static SYSTEM_INFO: SystemInfo = SystemInfo::new();
+
+#[allow(non_upper_case_globals)]
+unsafe extern "C" {
+    /// Начало секции `.kernel_symbols`, см. [`kernel_symbols()`].
+    static kernel_symbols_start: u8;
+
+    /// Конец секции `.kernel_symbols`.
+    static kernel_symbols_end: u8;
+
+    /// Начало памяти `.kernel_symbol_table` под пары из адреса и имени, см. [`kernel_symbols()`].
+    static mut kernel_symbol_table_start: u8;
+
+    /// Конец памяти `.kernel_symbol_table`.
+    static mut kernel_symbol_table_end: u8;
+}
@@ -1,8 +1,15 @@
-use core::fmt::{
-    Debug,
-    Write,
+use core::{
+    fmt::{
+        Debug,
+        Write,
+    },
+    sync::atomic::{
+        AtomicUsize,
+        Ordering,
+    },
 };
 
+use heapless::Vec;
 use serde::Deserialize;
 use tracing::{
     Collect,
@@ -20,6 +27,7 @@ use tracing::{
     },
 };
 use tracing_core::{
+    collect::Interest,
     dispatch,
     dispatch::Dispatch,
     span::Current,
@@ -28,16 +36,19 @@ use tracing_core::{
 use ku::{
     ReadBuffer,
     log::{
+        AtomicLevel,
         LogField,
         LogFieldValue,
         LogMetadata,
         level_into_symbol,
     },
     sync::{
+        IrqSpinlock,
         PanicStrategy,
         Spinlock,
     },
     time::{
+        self,
         Tsc,
         datetime_ms,
     },
@@ -50,7 +61,11 @@ use text::{
 
 use crate::{
     error::{
-        Error::Unimplemented,
+        Error::{
+            InvalidArgument,
+            Overflow,
+            Unimplemented,
+        },
         Result,
     },
     process::Pid,
@@ -65,6 +80,10 @@ pub use tracing::{
     warn,
 };
 
+// Used in docs.
+#[allow(unused)]
+use crate::error::Error;
+
 /// Инициализация журналирования.
 pub(super) fn init() {
     dispatch::set_global_default(Dispatch::from_static(&LOG_COLLECTOR)).unwrap();
@@ -79,6 +98,72 @@ pub(super) fn user_events(
     LOG_COLLECTOR.log.lock().user_events(pid, log);
 }
 
+/// Возвращает текущий уровень журналирования.
+pub fn level() -> Level {
+    LOG_COLLECTOR.level.load()
+}
+
+/// Устанавливает уровень журналирования `level`.
+/// Дальше печатаются только сообщения ядра и пользовательских процессов
+/// с уровнем `level` и выше.
+///
+/// Сообщение, уже прошедшее проверку уровня, печатается целиком,
+/// даже если уровень поменялся во время его печати.
+pub fn set_level(level: Level) {
+    LOG_COLLECTOR.level.store(level);
+}
+
+/// Устанавливает для сообщений, цель которых ([`Metadata::target()`]) равна `prefix`
+/// или вложена в неё, например `kernel::memory` и `kernel::memory::phys` для `kernel::memory`,
+/// уровень журналирования `level` вместо общего уровня [`set_level()`].
+/// Действует и на сообщения пользовательских процессов.
+///
+/// Если цели сообщения соответствуют несколько правил, применяется правило с самым длинным префиксом.
+/// Повторный вызов с тем же `prefix` заменяет уровень его правила.
+///
+/// Возвращает ошибки:
+///   - [`Error::InvalidArgument`], если `prefix` пуст --- для всех целей есть [`set_level()`].
+///   - [`Error::Overflow`], если уже зарегистрировано [`MAX_TARGET_FILTERS`] правил.
+pub fn set_target_filter(
+    prefix: &'static str,
+    level: Level,
+) -> Result<()> {
+    if prefix.is_empty() {
+        return Err(InvalidArgument);
+    }
+
+    let mut filters = LOG_COLLECTOR.target_filters.lock();
+
+    if let Some(filter) = filters.iter_mut().find(|filter| filter.prefix == prefix) {
+        filter.level = level;
+    } else {
+        let filter = TargetFilter { prefix, level };
+        let position = filters
+            .iter()
+            .position(|filter| filter.prefix.len() < prefix.len())
+            .unwrap_or(filters.len());
+        filters.insert(position, filter).map_err(|_| Overflow)?;
+    }
+
+    LOG_COLLECTOR.target_filter_count.store(filters.len(), Ordering::Relaxed);
+
+    Ok(())
+}
+
+/// Удаляет правило фильтрации сообщений для цели `prefix`, см. [`set_target_filter()`].
+/// Если такого правила нет, ничего не делает.
+pub fn remove_target_filter(prefix: &str) {
+    let mut filters = LOG_COLLECTOR.target_filters.lock();
+    filters.retain(|filter| filter.prefix != prefix);
+    LOG_COLLECTOR.target_filter_count.store(filters.len(), Ordering::Relaxed);
+}
+
+/// Возвращает количество сообщений ядра, записанных в журнал с момента загрузки.
+/// Позволяет убедиться, что сообщение действительно дошло до журнала.
+pub fn event_count() -> usize {
+    LOG_COLLECTOR.event_count.load(Ordering::Relaxed)
+}
+
 /// Вспомогательная структура для печати сообщения.
 struct LogEvent {
     /// Признак того, что нужно записать разделитель полей после ранее записанного поля.
@@ -307,6 +392,9 @@ impl Log {
     ) -> Result<()> {
         let metadata = LogMetadata::deserialize(&mut *deserializer)?;
         let level = metadata.level().map_err(|_| Unimplemented)?;
+        if !LOG_COLLECTOR.is_enabled(metadata.target(), &level) {
+            return Ok(());
+        }
         self.log_metadata(&level, metadata);
 
         let count = u8::deserialize(&mut *deserializer)?;
@@ -336,12 +424,25 @@ impl Log {
 
 /// Сборщик сообщений журнала, печатающий сообщения на экран и в COM--порт.
 struct LogCollector {
+    /// Количество записанных в журнал сообщений ядра.
+    event_count: AtomicUsize,
+
     /// Уровень журналирования.
     /// Печатаются только сообщения с уровнем журналирования, равным [`LogCollector::level`] и выше.
-    level: Level,
+    level: AtomicLevel,
 
     /// Сборщик записей журнала для печати сообщений в заданном формате.
     log: Spinlock<Log, { PanicStrategy::KnockDown }>,
+
+    /// Количество правил в [`LogCollector::target_filters`].
+    /// Пока правил нет, проверка уровня сообщения обходится без блокировки.
+    target_filter_count: AtomicUsize,
+
+    /// Правила фильтрации сообщений по их цели,
+    /// упорядоченные от самого длинного префикса к самому короткому.
+    /// Блокировка запрещает прерывания, так как сообщения пишутся и из их обработчиков.
+    target_filters:
+        IrqSpinlock<Vec<TargetFilter, MAX_TARGET_FILTERS>, { PanicStrategy::KnockDown }>,
 }
 
 impl LogCollector {
@@ -352,10 +453,33 @@ impl LogCollector {
         level: Level,
     ) -> Self {
         Self {
-            level,
+            event_count: AtomicUsize::new(0),
+            level: AtomicLevel::new(level),
             log: Spinlock::new(Log::new(format)),
+            target_filter_count: AtomicUsize::new(0),
+            target_filters: IrqSpinlock::new(Vec::new()),
         }
     }
+
+    /// Возвращает `true`, если сообщение с уровнем журналирования `level` и целью `target`
+    /// нужно печатать, см. [`set_level()`] и [`set_target_filter()`].
+    fn is_enabled(
+        &self,
+        target: &str,
+        level: &Level,
+    ) -> bool {
+        let max_level = if self.target_filter_count.load(Ordering::Relaxed) == 0 {
+            None
+        } else {
+            self.target_filters
+                .lock()
+                .iter()
+                .find(|filter| filter.matches(target))
+                .map(|filter| filter.level)
+        };
+
+        level <= &max_level.unwrap_or_else(|| self.level.load())
+    }
 }
 
 impl Collect for LogCollector {
@@ -370,8 +494,9 @@ impl Collect for LogCollector {
         &self,
         event: &Event<'_>,
     ) {
-        let now = Tsc::now();
+        let now = time::monotonic();
         self.log.lock().log_event(event, now);
+        self.event_count.fetch_add(1, Ordering::Relaxed);
     }
 
     fn record(
@@ -388,11 +513,20 @@ impl Collect for LogCollector {
     ) {
     }
 
+    /// Не даёт [`tracing`] запомнить результат [`Collect::enabled()`] для точки вызова,
+    /// так как уровень журналирования может поменяться, см. [`set_level()`].
+    fn register_callsite(
+        &self,
+        _metadata: &'static Metadata<'static>,
+    ) -> Interest {
+        Interest::sometimes()
+    }
+
     fn enabled(
         &self,
         metadata: &Metadata<'_>,
     ) -> bool {
-        metadata.level() <= &self.level
+        self.is_enabled(metadata.target(), metadata.level())
     }
 
     fn enter(
@@ -412,5 +546,32 @@ impl Collect for LogCollector {
     }
 }
 
+/// Правило фильтрации сообщений журнала по их цели, см. [`set_target_filter()`].
+#[derive(Clone, Copy, Debug)]
+struct TargetFilter {
+    /// Цель сообщений, к которым применяется правило, вместе с вложенными в неё целями.
+    prefix: &'static str,
+
+    /// Уровень журналирования для этих сообщений.
+    level: Level,
+}
+
+impl TargetFilter {
+    /// Возвращает `true`, если правило применяется к сообщениям с целью `target`.
+    /// Префикс должен заканчиваться на границе пути модуля,
+    /// так что правило для `kernel::mem` не действует на `kernel::memory`.
+    fn matches(
+        &self,
+        target: &str,
+    ) -> bool {
+        target
+            .strip_prefix(self.prefix)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+    }
+}
+
 /// Сборщик сообщений журнала, печатающий сообщения на экран и в COM--порт.
 static LOG_COLLECTOR: LogCollector = LogCollector::new(Format::Compact, Level::DEBUG);
+
+/// Максимальное количество правил фильтрации сообщений по их цели, см. [`set_target_filter()`].
+pub const MAX_TARGET_FILTERS: usize = 16;
@@ -81,6 +81,10 @@ fn kernel_main(boot_info: &'static BootInfo) -> ! {
             if count != 0 {
                 let mnemonic = stats.mnemonic();
                 debug!(number, %mnemonic, count, "trap stats");
+
+                for (cpu, count) in stats.per_cpu() {
+                    debug!(number, %mnemonic, cpu, count, "trap stats per CPU");
+                }
             }
         }
 
@@ -99,7 +103,11 @@ fn panic(panic_info: &PanicInfo) -> ! {
     if cfg!(test) {
         kernel::fail_test(panic_info)
     } else {
-        text::TEXT.lock().set_attribute(Attribute::new(Color::WHITE, Color::RED));
+        text::TEXT.lock().set_attribute(Attribute::new(
+            Color::WHITE,
+            Color::RED,
+            text::BLINK_ENABLED,
+        ));
 
         println!("{panic_info}");
 
@@ -38,6 +38,7 @@ use super::{
     Phys,
     Phys2Virt,
     Translate,
+    UserAccess,
     Virt,
     block::Block,
     frage::{
@@ -47,6 +48,8 @@ use super::{
     mapping::Mapping,
     mmu::{
         self,
+        FULL_ACCESS,
+        PAGE_TABLE_LEAF_LEVEL,
         PageTableFlags,
     },
     page_allocator::PageAllocator,
@@ -146,6 +149,46 @@ impl AddressSpace {
         Ok(address_space)
     }
 
+    /// Создаёт копию адресного пространства, которая разделяет с ним страницы пользователя
+    /// в режиме [копирования при записи](https://en.wikipedia.org/wiki/Copy-on-write).
+    ///
+    /// Доступные на запись страницы пользователя в обоих адресных пространствах
+    /// становятся доступными только на чтение и помечаются флагом
+    /// [`PageTableFlags::COPY_ON_WRITE`].
+    /// Остальные страницы пользователя отображаются в копию с теми же флагами.
+    /// Физические фреймы не копируются, увеличиваются только их счётчики ссылок.
+    /// Собственный фрейм страница получает при записи в неё,
+    /// см. [`AddressSpace::copy_on_write()`].
+    pub(crate) fn duplicate_cow(&mut self) -> Result<Self> {
+        let mut address_space = self.duplicate()?;
+
+        for mut path in self.mapping()?.iter_mut() {
+            let (level, pte) = path.deepest_pte();
+            if level != PAGE_TABLE_LEAF_LEVEL || !pte.is_present() || !pte.is_user() {
+                continue;
+            }
+
+            let page = path.pages(level).start_element();
+            let pte = path.get_mut()?;
+            let mut flags = pte.flags();
+            if flags.is_writable() {
+                flags = (flags - PageTableFlags::WRITABLE) | PageTableFlags::COPY_ON_WRITE;
+                pte.set_flags(flags);
+                unsafe {
+                    mmu::flush(page);
+                }
+            }
+
+            unsafe {
+                address_space.map_page_to_frame(page, pte.frame()?, flags)?;
+            }
+        }
+
+        info!(original = %self, %address_space, "duplicate copy-on-write");
+
+        Ok(address_space)
+    }
+
     /// Устанавливает [`AddressSpace::user_page_allocator`] в состояние эквивалентное `original`.
     /// Текущий [`AddressSpace`] должен был быть получен
     /// из `original` методом [`AddressSpace::duplicate()`].
@@ -347,6 +390,118 @@ impl AddressSpace {
         }
     }
 
+    /// Отображает страницу `page` на тот же физический фрейм,
+    /// на который она отображена в адресном пространстве `donor`,
+    /// если содержимое этого фрейма совпадает с содержимым собственного фрейма страницы.
+    /// Флаги доступа к странице не меняются,
+    /// а её собственный фрейм освобождается, если на него не осталось других ссылок.
+    ///
+    /// Страница становится общей, только если в обоих адресных пространствах
+    /// она отображена с одинаковыми флагами доступа, без [`PageTableFlags::WRITABLE`] и
+    /// без [`PageTableFlags::COPY_ON_WRITE`].
+    /// Иначе запись в неё в одном адресном пространстве стала бы видна в другом,
+    /// поэтому страница остаётся собственной копией `self`.
+    ///
+    /// Возвращает `true`, если страница стала общей.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::NoPage`] --- страница `page` не отображена в `self` или в `donor`.
+    ///
+    /// # Safety
+    ///
+    /// Вызывающий код должен гарантировать, что инварианты управления памятью в Rust'е
+    /// не будут нарушены.
+    /// В частности, не осталось ссылок, которые ведут в собственный фрейм страницы `page`.
+    pub unsafe fn share_page(
+        &mut self,
+        donor: &mut AddressSpace,
+        page: Page,
+    ) -> Result<bool> {
+        let pte = self.translate(page.address())?;
+        let (frame, flags) = (pte.frame()?, pte.flags());
+        let donor_pte = donor.translate(page.address())?;
+        let (donor_frame, donor_flags) = (donor_pte.frame()?, donor_pte.flags());
+
+        let is_writable = (flags | donor_flags)
+            .intersects(PageTableFlags::WRITABLE | PageTableFlags::COPY_ON_WRITE);
+        let same_access = flags & FULL_ACCESS == donor_flags & FULL_ACCESS;
+
+        if frame == donor_frame || is_writable || !same_access {
+            return Ok(false);
+        }
+
+        let phys2virt = self.mapping()?.phys2virt();
+        let content = |frame: Frame| -> Result<&'static [u8]> {
+            unsafe { phys2virt.frame_to_virt(frame)?.try_into_slice(Page::SIZE) }
+        };
+
+        if content(frame)? != content(donor_frame)? {
+            return Ok(false);
+        }
+
+        unsafe {
+            self.map_page_to_frame(page, donor_frame, flags)?;
+        }
+
+        Ok(true)
+    }
+
+    /// Обрабатывает запись в страницу `page`,
+    /// которая отображена с флагом [`PageTableFlags::COPY_ON_WRITE`].
+    /// Если на её физический фрейм есть другие ссылки, копирует его содержимое в новый фрейм.
+    /// После этого страница становится доступной на запись,
+    /// а флаг [`PageTableFlags::COPY_ON_WRITE`] с неё снимается.
+    ///
+    /// Возвращает `false`, если страница отображена без флага
+    /// [`PageTableFlags::COPY_ON_WRITE`].
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::NoPage`] --- страница `page` не отображена.
+    /// - [`Error::NoFrame`] --- не хватило физических фреймов для копии.
+    ///
+    /// # Safety
+    ///
+    /// Вызывающий код должен гарантировать, что инварианты управления памятью в Rust'е
+    /// не будут нарушены.
+    /// В частности, не осталось ссылок, которые ведут в страницу `page`.
+    pub unsafe fn copy_on_write(
+        &mut self,
+        page: Page,
+    ) -> Result<bool> {
+        let pte = self.translate(page.address())?;
+        let (frame, flags) = (pte.frame()?, pte.flags());
+
+        if !flags.contains(PageTableFlags::COPY_ON_WRITE) {
+            return Ok(false);
+        }
+
+        let flags = (flags - PageTableFlags::COPY_ON_WRITE) | PageTableFlags::WRITABLE;
+
+        if FRAME_ALLOCATOR.lock().reference_count(frame)? == 1 {
+            pte.set_flags(flags);
+            unsafe {
+                mmu::flush(page);
+            }
+            return Ok(true);
+        }
+
+        let copy = FrameGuard::allocate()?;
+        let phys2virt = self.mapping()?.phys2virt();
+        unsafe {
+            let content = phys2virt.frame_to_virt(frame)?.try_into_slice::<u8>(Page::SIZE)?;
+            phys2virt
+                .frame_to_virt(*copy)?
+                .try_into_mut_slice::<u8>(Page::SIZE)?
+                .copy_from_slice(content);
+
+            self.mapping()?.path(page.address()).map(copy, flags)?;
+        }
+
+        Ok(true)
+    }
+
     /// Выделяет нужное количество физических фреймов
     /// и отображает в них заданный блок виртуальных страниц `pages`
     /// с заданными флагами доступа `flags`.
@@ -465,6 +620,7 @@ impl AddressSpace {
     ) -> Result<&'static mut [T]> {
         assert!(flags.is_writable());
         let slice = self.map_slice_uninit(len, flags)?;
+        let _user_access = flags.is_user().then(UserAccess::new);
         Ok(slice.write_with(|_| default()))
     }
 
@@ -486,6 +642,7 @@ impl AddressSpace {
         assert!(flags.is_writable());
 
         let slice = self.map_slice_uninit(len, flags)?;
+        let _user_access = flags.is_user().then(UserAccess::new);
 
         for element in slice.iter_mut() {
             *element = MaybeUninit::zeroed();
@@ -828,6 +985,10 @@ pub(super) mod test_scaffolding {
         address_space.duplicate()
     }
 
+    pub fn duplicate_cow(address_space: &mut AddressSpace) -> Result<AddressSpace> {
+        address_space.duplicate_cow()
+    }
+
     pub fn iter_mut(address_space: &mut AddressSpace) -> MappingIterator<'_> {
         mapping_mut(address_space).iter_mut()
     }
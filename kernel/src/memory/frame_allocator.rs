@@ -25,6 +25,10 @@ use crate::{
         info,
         trace,
     },
+    smp::{
+        CpuId,
+        LocalApic,
+    },
     time,
 };
 
@@ -35,6 +39,10 @@ use super::{
     KERNEL_RW,
     Size,
     frage::Frame,
+    numa::{
+        MAX_NUMA_NODES,
+        NumaTopology,
+    },
     size,
 };
 
@@ -146,8 +154,14 @@ pub struct FrameAllocator {
     /// Количество свободных физических фреймов.
     free_count: usize,
 
-    /// Голова интрузивного списка номеров свободных физических фреймов.
-    free_frame: Option<usize>,
+    /// Головы интрузивных списков номеров свободных физических фреймов,
+    /// по одному списку на каждый узел NUMA.
+    /// В системе без NUMA используется только список узла `0`.
+    free_frames: [Option<usize>; MAX_NUMA_NODES],
+
+    /// Топология NUMA, по которой свободные фреймы распределяются по спискам
+    /// [`FrameAllocator::free_frames`].
+    numa: NumaTopology,
 }
 
 impl FrameAllocator {
@@ -161,7 +175,8 @@ impl FrameAllocator {
             access_beyond_frame_info: false,
             frame_info,
             free_count: 0,
-            free_frame: None,
+            free_frames: [None; MAX_NUMA_NODES],
+            numa: NumaTopology::default(),
         };
 
         let frame_count = frame_allocator.frame_info.len();
@@ -182,6 +197,11 @@ impl FrameAllocator {
         self.free_count
     }
 
+    /// Возвращает количество физических фреймов, информацию о которых хранит аллокатор.
+    pub(super) fn tracked_count(&self) -> usize {
+        self.frame_info.len()
+    }
+
     // ANCHOR: allocate
     /// Выделяет ровно один физический фрейм.
     /// Возвращает [`FrameGuard`], владеющий ссылкой на этот фрейм.
@@ -190,14 +210,30 @@ impl FrameAllocator {
     /// возвращает ошибку [`Error::NoFrame`].
     pub fn allocate(&mut self) -> Result<FrameGuard> {
         // ANCHOR_END: allocate
-        if self.free_count == 0 || self.free_frame.is_none() {
-            return Err(NoFrame);
-        }
-        let frame_index = self.free_frame.unwrap();
+        self.allocate_for(LocalApic::id())
+    }
+
+    /// Выделяет ровно один физический фрейм для процессора `cpu`.
+    /// Предпочитает фреймы узла NUMA этого процессора,
+    /// а когда они заканчиваются --- берёт фреймы других узлов.
+    /// Возвращает [`FrameGuard`], владеющий ссылкой на этот фрейм.
+    ///
+    /// Если свободных физических фреймов не осталось,
+    /// возвращает ошибку [`Error::NoFrame`].
+    pub fn allocate_for(
+        &mut self,
+        cpu: CpuId,
+    ) -> Result<FrameGuard> {
+        let local_node = self.numa.cpu_node(cpu);
+        let node = (local_node .. MAX_NUMA_NODES)
+            .chain(0 .. local_node)
+            .find(|&node| self.free_frames[node].is_some())
+            .ok_or(NoFrame)?;
+        let frame_index = self.free_frames[node].unwrap();
         let frame_info = self.frame_info[frame_index];
         match frame_info {
             FrameInfo::Free { next_free } => {
-                self.free_frame = next_free;
+                self.free_frames[node] = next_free;
                 self.free_count -= 1;
                 self.frame_info[frame_index] = FrameInfo::Used {
                     reference_count: 1,
@@ -239,10 +275,7 @@ impl FrameAllocator {
             FrameInfo::Used { reference_count } => {
                 *reference_count -= 1;
                 if *reference_count == 0 {
-                    *frame_info = FrameInfo::Free {
-                        next_free: self.free_frame,
-                    };
-                    self.free_frame = Some(frame_index);
+                    self.push_free(frame_index);
                     self.free_count += 1;
                 }
             }
@@ -296,6 +329,33 @@ impl FrameAllocator {
         }
     }
 
+    /// Топология NUMA, по которой аллокатор выбирает фреймы.
+    pub fn numa_topology(&self) -> &NumaTopology {
+        &self.numa
+    }
+
+    /// Устанавливает топологию NUMA `numa` и перераспределяет свободные фреймы
+    /// по спискам узлов в соответствии с ней.
+    pub fn set_numa_topology(
+        &mut self,
+        numa: NumaTopology,
+    ) {
+        self.numa = numa;
+        self.free_frames = [None; MAX_NUMA_NODES];
+
+        for frame_index in (0 .. self.frame_info.len()).rev() {
+            if let FrameInfo::Free { .. } = self.frame_info[frame_index] {
+                self.push_free(frame_index);
+            }
+        }
+
+        info!(
+            node_count = self.numa.node_count(),
+            free_frame_count = self.free_count,
+            "frame allocator NUMA topology",
+        );
+    }
+
     /// Проверяет, что заданный физический фрейм уже был выделен.
     pub fn is_used(
         &self,
@@ -330,10 +390,7 @@ impl FrameAllocator {
                     for frame in intersection {
                         let frame_index = frame.index();
                         if frame_index < self.frame_info.len() {
-                            self.frame_info[frame_index] = FrameInfo::Free {
-                                next_free: self.free_frame,
-                            };
-                            self.free_frame = Some(frame_index);
+                            self.push_free(frame_index);
                             self.free_count += 1;
                         }
                     }
@@ -390,6 +447,21 @@ impl FrameAllocator {
         old_frame_info
     }
 
+    /// Помечает фрейм с номером `frame_index` свободным и добавляет его
+    /// в голову списка свободных фреймов его узла NUMA.
+    /// Счётчик [`FrameAllocator::free_count`] не меняет.
+    fn push_free(
+        &mut self,
+        frame_index: usize,
+    ) {
+        let node = self.numa.frame_node(frame_index);
+
+        self.frame_info[frame_index] = FrameInfo::Free {
+            next_free: self.free_frames[node],
+        };
+        self.free_frames[node] = Some(frame_index);
+    }
+
     /// Возвращает ссылку на [`FrameInfo`], соответствующую физическому фрейму `frame`.
     ///
     /// Если номер фрейма `frame` попадает в диапазон [`FrameAllocator::frame_info`],
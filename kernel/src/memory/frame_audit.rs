@@ -0,0 +1,125 @@
+use core::iter;
+
+use alloc::vec;
+
+use crate::log::{
+    debug,
+    error,
+};
+
+use super::{
+    AddressSpace,
+    BASE_ADDRESS_SPACE,
+    FRAME_ALLOCATOR,
+    Frame,
+    Mapping,
+    mmu::{
+        PAGE_TABLE_LEAF_LEVEL,
+        PAGE_TABLE_ROOT_LEVEL,
+    },
+};
+
+// Used in docs.
+#[allow(unused)]
+use super::FrameGuard;
+
+/// Проверяет, что счётчики ссылок на физические фреймы во [`static@FRAME_ALLOCATOR`]
+/// согласованы с отображениями базового адресного пространства
+/// [`static@BASE_ADDRESS_SPACE`] и адресных пространств `address_spaces`.
+///
+/// Ожидаемое количество ссылок на фрейм равно количеству его отображений
+/// во всех этих адресных пространствах плюс количеству узлов их таблиц страниц,
+/// которые в нём хранятся.
+/// Поэтому `address_spaces` должны включать все живые адресные пространства, кроме базового.
+///
+/// Не проверяются:
+///   - Фреймы, на которые не ссылается ни одно отображение, ---
+///     ими может временно владеть [`FrameGuard`].
+///   - Фреймы больших страниц и зарезервированные фреймы ---
+///     у них нет счётчиков ссылок.
+///
+/// Записывает каждое расхождение в журнал и возвращает количество фреймов,
+/// счётчики ссылок которых не совпали с ожидаемыми.
+pub fn check_frame_references(address_spaces: &mut [&mut AddressSpace]) -> usize {
+    let frame_count = FRAME_ALLOCATOR.lock().tracked_count();
+    let mut references = vec![0; frame_count];
+
+    let mut base_address_space = BASE_ADDRESS_SPACE.lock();
+    let address_spaces = iter::once(&mut *base_address_space)
+        .chain(address_spaces.iter_mut().map(|address_space| &mut **address_space));
+    for address_space in address_spaces {
+        if let Ok(mapping) = address_space.mapping() {
+            count_reference(&mut references, mapping.page_table_root());
+            count_subtree_references(
+                mapping,
+                &mut references,
+                mapping.page_table_root(),
+                PAGE_TABLE_ROOT_LEVEL,
+            );
+        }
+    }
+
+    let frame_allocator = FRAME_ALLOCATOR.lock();
+    let mut checked = 0;
+    let mut mismatches = 0;
+
+    for (index, &expected) in references.iter().enumerate().filter(|(_, x)| **x != 0) {
+        let frame = Frame::from_index(index).expect("frame index out of the physical memory");
+        let Ok(recorded) = frame_allocator.reference_count(frame) else {
+            continue;
+        };
+
+        checked += 1;
+        if recorded != expected {
+            error!(%frame, expected, recorded, "frame reference count mismatch");
+            mismatches += 1;
+        }
+    }
+
+    debug!(checked, mismatches, "frame references");
+
+    mismatches
+}
+
+/// Учитывает в `references` ссылки поддерева отображения `mapping`
+/// с корнем в узле `node` уровня `level`.
+/// Рекурсивные записи таблицы страниц и большие страницы пропускаются.
+fn count_subtree_references(
+    mapping: &Mapping,
+    references: &mut [usize],
+    node: Frame,
+    level: u32,
+) {
+    let page_table = unsafe { mapping.page_table_ref(node) };
+
+    for pte in page_table.iter() {
+        if !pte.is_present() || pte.is_huge() {
+            continue;
+        }
+
+        let Ok(frame) = pte.frame() else {
+            continue;
+        };
+
+        if frame == mapping.page_table_root() {
+            continue;
+        }
+
+        count_reference(references, frame);
+
+        if level > PAGE_TABLE_LEAF_LEVEL {
+            count_subtree_references(mapping, references, frame, level - 1);
+        }
+    }
+}
+
+/// Учитывает в `references` одну ссылку на фрейм `frame`,
+/// если он попадает в `references`.
+fn count_reference(
+    references: &mut [usize],
+    frame: Frame,
+) {
+    if let Some(count) = references.get_mut(frame.index()) {
+        *count += 1;
+    }
+}
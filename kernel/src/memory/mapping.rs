@@ -324,7 +324,7 @@ impl Mapping {
         self: reference([Self]),
         frame: Frame,
     ) -> reference([return_type]) {
-        let page_table = self.phys2virt().map(frame.address()).expect("bad frame");
+        let page_table = self.phys2virt().frame_to_virt(frame).expect("bad frame");
         unsafe { page_table.try_into_mut().expect("bad phys2virt or frame") }
     }
 
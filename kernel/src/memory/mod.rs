@@ -13,6 +13,10 @@ mod address_space;
 /// Аллокатор физических фреймов [`FrameAllocator`].
 mod frame_allocator;
 
+/// Проверка согласованности счётчиков ссылок на физические фреймы
+/// с отображениями адресных пространств.
+mod frame_audit;
+
 /// RAII для операции выделения одного [`Frame`].
 mod frame_guard;
 
@@ -25,6 +29,9 @@ mod mapping;
 /// Аллокатор страниц виртуальной памяти [`PageAllocator`].
 mod page_allocator;
 
+/// Топология NUMA [`NumaTopology`] для аллокатора физических фреймов.
+mod numa;
+
 /// Путь в дереве отображения заданного виртуального адреса.
 mod path;
 
@@ -40,10 +47,19 @@ mod phys2virt;
 /// выделенные стеки для непредвиденных исключений [`ExceptionStacks`].
 mod stack;
 
+/// Сброс кэша страничного преобразования на всех процессорах системы ---
+/// [TLB shootdown](https://en.wikipedia.org/wiki/Translation_lookaside_buffer#TLB_shootdown).
+mod tlb;
+
 /// Сегмент состояния задачи
 /// ([Task State Segment](https://en.wikipedia.org/wiki/Task_state_segment), TSS).
 mod tss;
 
+/// Защита памяти пользователя от случайных обращений ядра ---
+/// [SMEP](https://en.wikipedia.org/wiki/Control_register#SMEP) и
+/// [SMAP](https://en.wikipedia.org/wiki/Supervisor_Mode_Access_Prevention).
+mod user_access;
+
 use bootloader::BootInfo;
 use lazy_static::lazy_static;
 use x86_64::registers::model_specific::{
@@ -103,6 +119,7 @@ pub use frage::{
     Page,
 };
 pub use frame_allocator::FRAME_ALLOCATOR;
+pub use frame_audit::check_frame_references;
 pub use frame_guard::FrameGuard;
 pub use mapping::Translate;
 pub use mmu::{
@@ -116,10 +133,24 @@ pub use mmu::{
     USER_RW,
     USER_RX,
 };
+pub use numa::{
+    MAX_NUMA_NODES,
+    NumaTopology,
+};
 pub use size::{
     Size,
     SizeOf,
 };
+pub use tlb::tlb_shootdown;
+pub use user_access::{
+    UserAccess,
+    copy_from_user,
+    copy_to_user,
+    is_smap_enabled,
+    is_smep_enabled,
+    read_user_str,
+    smap_violations,
+};
 
 pub(crate) use gdt::{
     GDT,
@@ -136,10 +167,12 @@ pub(crate) use stack::{
     EXCEPTION_STACKS,
     Stack,
 };
+pub(crate) use tlb::serve_tlb_shootdown;
 pub(crate) use tss::{
     DOUBLE_FAULT_IST_INDEX,
     PAGE_FAULT_IST_INDEX,
 };
+pub(crate) use user_access::copy_fault_handler;
 
 // Used in docs.
 #[allow(unused)]
@@ -165,6 +198,8 @@ pub(super) fn init(
         Efer::write(EferFlags::NO_EXECUTE_ENABLE | Efer::read());
     }
 
+    user_access::init();
+
     let physical_memory = range::physical(&boot_info.memory_map);
 
     if subsystems.contains(Subsystems::PHYS_MEMORY) {
@@ -215,5 +250,6 @@ pub mod test_scaffolding {
         path::test_scaffolding::*,
         phys2virt::test_scaffolding::*,
         range::test_scaffolding::*,
+        user_access::test_scaffolding::*,
     };
 }
@@ -0,0 +1,122 @@
+use alloc::vec::Vec;
+
+use crate::{
+    error::{
+        Error::Overflow,
+        Result,
+    },
+    smp::CpuId,
+};
+
+use super::{
+    block::Block,
+    frage::Frame,
+};
+
+// Used in docs.
+#[allow(unused)]
+use {
+    super::frame_allocator::FrameAllocator,
+    crate::error::Error,
+};
+
+/// Топология
+/// [Non-uniform memory access](https://en.wikipedia.org/wiki/Non-uniform_memory_access) (NUMA) ---
+/// к какому узлу относится каждый процессор и каждый диапазон физической памяти.
+///
+/// Узлы нумеруются подряд с нуля в порядке их появления,
+/// номер домена близости (proximity domain) из таблицы SRAT
+/// [ACPI](https://en.wikipedia.org/wiki/ACPI) хранится отдельно.
+/// Процессоры и фреймы, про которые топология ничего не знает, относятся к узлу `0`.
+/// Пустая топология, как и топология из одного узла, описывает систему без NUMA.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct NumaTopology {
+    /// Процессоры и номера их узлов.
+    cpus: Vec<(CpuId, usize)>,
+
+    /// Номера доменов близости узлов в порядке номеров узлов.
+    domains: Vec<u32>,
+
+    /// Диапазоны физических фреймов и номера их узлов.
+    memory: Vec<(Block<Frame>, usize)>,
+}
+
+impl NumaTopology {
+    /// Относит процессор `cpu` к узлу с доменом близости `domain`.
+    ///
+    /// Возвращает ошибку [`Error::Overflow`], если узлов становится больше [`MAX_NUMA_NODES`].
+    pub fn add_cpu(
+        &mut self,
+        cpu: CpuId,
+        domain: u32,
+    ) -> Result<()> {
+        let node = self.node(domain)?;
+        self.cpus.push((cpu, node));
+
+        Ok(())
+    }
+
+    /// Относит диапазон физических фреймов `frames` к узлу с доменом близости `domain`.
+    ///
+    /// Возвращает ошибку [`Error::Overflow`], если узлов становится больше [`MAX_NUMA_NODES`].
+    pub fn add_memory(
+        &mut self,
+        frames: Block<Frame>,
+        domain: u32,
+    ) -> Result<()> {
+        let node = self.node(domain)?;
+        self.memory.push((frames, node));
+
+        Ok(())
+    }
+
+    /// Возвращает `true`, если в системе больше одного узла NUMA.
+    pub fn is_numa(&self) -> bool {
+        self.domains.len() > 1
+    }
+
+    /// Количество узлов NUMA, не меньше одного.
+    pub fn node_count(&self) -> usize {
+        self.domains.len().max(1)
+    }
+
+    /// Номер узла, к которому относится процессор `cpu`.
+    pub fn cpu_node(
+        &self,
+        cpu: CpuId,
+    ) -> usize {
+        self.cpus.iter().find(|(id, _)| *id == cpu).map_or(0, |(_, node)| *node)
+    }
+
+    /// Номер узла, к которому относится физический фрейм с номером `frame_index`.
+    pub fn frame_node(
+        &self,
+        frame_index: usize,
+    ) -> usize {
+        self.memory
+            .iter()
+            .find(|(frames, _)| frames.contains_index(frame_index))
+            .map_or(0, |(_, node)| *node)
+    }
+
+    /// Возвращает номер узла с доменом близости `domain`, при необходимости заводя новый узел.
+    fn node(
+        &mut self,
+        domain: u32,
+    ) -> Result<usize> {
+        if let Some(node) = self.domains.iter().position(|&x| x == domain) {
+            return Ok(node);
+        }
+
+        if self.domains.len() >= MAX_NUMA_NODES {
+            return Err(Overflow);
+        }
+
+        self.domains.push(domain);
+
+        Ok(self.domains.len() - 1)
+    }
+}
+
+/// Максимальное количество узлов NUMA, которое поддерживает [`FrameAllocator`].
+pub const MAX_NUMA_NODES: usize = 8;
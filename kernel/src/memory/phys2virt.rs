@@ -96,6 +96,64 @@ impl Phys2Virt {
         }
     }
 
+    /// Возвращает виртуальный адрес начала физического фрейма `frame`
+    /// внутри области [`Phys2Virt`].
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::Overflow`] --- фрейм `frame` не попадает в физическую память,
+    ///   имеющуюся в системе.
+    pub fn frame_to_virt(
+        &self,
+        frame: Frame,
+    ) -> Result<Virt> {
+        self.map(frame.address())
+    }
+
+    /// Возвращает страницу области [`Phys2Virt`], в которую отображён физический фрейм `frame`.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::Overflow`] --- фрейм `frame` не попадает в физическую память,
+    ///   имеющуюся в системе.
+    pub fn page_of_frame(
+        &self,
+        frame: Frame,
+    ) -> Result<Page> {
+        Page::new(self.frame_to_virt(frame)?)
+    }
+
+    /// Для виртуального адреса `virt` внутри области [`Phys2Virt`] возвращает
+    /// физический адрес, который в него отображён.
+    /// Обратна к [`Phys2Virt::map()`].
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::Overflow`] --- адрес `virt` не попадает в область [`Phys2Virt`].
+    pub fn virt_to_phys(
+        &self,
+        virt: Virt,
+    ) -> Result<Phys> {
+        if self.mapping.contains_address(virt) {
+            Phys::new((virt - self.mapping.start_address())?)
+        } else {
+            Err(Overflow)
+        }
+    }
+
+    /// Возвращает физический фрейм, который отображён в страницу `page` области [`Phys2Virt`].
+    /// Обратна к [`Phys2Virt::page_of_frame()`].
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::Overflow`] --- страница `page` не попадает в область [`Phys2Virt`].
+    pub fn frame_of_page(
+        &self,
+        page: Page,
+    ) -> Result<Frame> {
+        Frame::new(self.virt_to_phys(page.address())?)
+    }
+
     // ANCHOR: make
     /// Создаёт отображение [`Phys2Virt`] всей физической памяти `physical_memory`
     /// (см. [`kernel::memory::range::physical()`]),
@@ -110,7 +110,7 @@ pub(crate) fn is_user_block(pages: Block<Page>) -> bool {
 }
 
 /// Возвращает `true`, если страница `page` зарезервирована для пространства пользователя.
-fn is_user_page(page: Page) -> bool {
+pub(super) fn is_user_page(page: Page) -> bool {
     user_pages().contains(page)
 }
 
@@ -71,6 +71,32 @@ impl Stack {
         (Block::from_slice(zones.0), Block::from_slice(zones.1))
     }
 
+    /// Заполняет блок для данных в стеке значением [`Stack::UNUSED`],
+    /// чтобы потом по нему определить максимальную глубину стека,
+    /// см. [`Stack::high_water_mark()`].
+    ///
+    /// Стек должен быть отображён в текущее адресное пространство.
+    pub(crate) fn fill_unused(&mut self) {
+        for word in self.0[Self::GUARD_ZONE_SIZE ..].chunks_exact_mut(mem::size_of::<u64>()) {
+            word.copy_from_slice(&Self::UNUSED.to_ne_bytes());
+        }
+    }
+
+    /// Возвращает максимальную глубину стека в байтах, которой он когда-либо достигал.
+    /// Для этого ищет от дна стека к его вершине первое слово,
+    /// значение которого отличается от записанного [`Stack::fill_unused()`].
+    ///
+    /// Стек должен быть отображён в текущее адресное пространство.
+    pub(crate) fn high_water_mark(&self) -> usize {
+        let data = &self.0[Self::GUARD_ZONE_SIZE ..];
+        let unused = data
+            .chunks_exact(mem::size_of::<u64>())
+            .take_while(|word| **word == Self::UNUSED.to_ne_bytes())
+            .count();
+
+        data.len() - unused * mem::size_of::<u64>()
+    }
+
     /// Создаёт в стеке не отображённый блок памяти,
     /// защищающий от неопределённого поведения при переполнении стека.
     unsafe fn make_guard_zone(
@@ -87,6 +113,9 @@ impl Stack {
 
     /// Размер стеков, включая не отображённую в память защитную область.
     const STACK_SIZE: usize = 32 * Page::SIZE;
+
+    /// Значение, которым [`Stack::fill_unused()`] заполняет ещё не использованный стек.
+    const UNUSED: u64 = 0xDEAD_BEEF_DEAD_BEEF;
 }
 
 /// Создаёт статически выделенный стек.
@@ -0,0 +1,186 @@
+use core::{
+    hint,
+    sync::atomic::{
+        AtomicBool,
+        AtomicU64,
+        AtomicUsize,
+        Ordering,
+    },
+};
+
+use x86::tlb;
+use x86_64::instructions::interrupts;
+
+use crate::{
+    smp::{
+        self,
+        CPU_ID_COUNT,
+        CpuId,
+        LocalApic,
+    },
+    trap::Trap,
+};
+
+use super::{
+    Block,
+    Page,
+    Virt,
+    mmu,
+};
+
+/// Сбрасывает кэш страничного преобразования
+/// ([Translation Lookaside Buffer, TLB](https://en.wikipedia.org/wiki/Translation_lookaside_buffer))
+/// для блока адресов `range` на всех процессорах системы
+/// ([TLB shootdown](https://en.wikipedia.org/wiki/Translation_lookaside_buffer#TLB_shootdown)).
+///
+/// Текущий процессор сбрасывает свой TLB сам,
+/// остальным работающим процессорам рассылается межпроцессорное прерывание
+/// [`Trap::TlbShootdown`], см. [`LocalApic::send_ipi()`].
+/// Функция возвращает управление только после того,
+/// как каждый из них подтвердит, что сбросил свой TLB.
+///
+/// Одновременно выполняется только один запрос, остальные инициаторы его дожидаются.
+/// Ожидая своей очереди, процессор обслуживает текущий запрос сам, не дожидаясь прерывания.
+/// Поэтому два процессора, одновременно сбрасывающих TLB друг другу, не блокируют друг друга.
+/// Процессор, который занят обработкой другого прерывания или запретил прерывания,
+/// получит [`Trap::TlbShootdown`] чуть позже --- его local APIC запомнит это прерывание.
+///
+/// Вызывающий код не должен удерживать блокировки,
+/// которые другой процессор может ждать с запрещёнными прерываниями.
+/// Иначе тот не сможет ни получить прерывание, ни подтвердить сброс TLB.
+pub fn tlb_shootdown(range: Block<Virt>) {
+    let pages = range.enclosing();
+
+    flush(pages);
+
+    let current_cpu = LocalApic::id();
+    let mut targets = [0; TARGET_WORDS];
+    let mut target_count = 0;
+    for cpu in smp::online_cpus().filter(|&cpu| cpu != current_cpu) {
+        let (word, bit) = target_bit(cpu);
+        targets[word] |= bit;
+        target_count += 1;
+    }
+
+    if target_count == 0 {
+        return;
+    }
+
+    // Пока процессор владеет SHOOTDOWN, обработчик прерывания на нём
+    // не должен начать ещё один сброс TLB --- тот ждал бы SHOOTDOWN вечно.
+    interrupts::without_interrupts(|| {
+        while SHOOTDOWN
+            .busy
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            serve_tlb_shootdown();
+            hint::spin_loop();
+        }
+
+        SHOOTDOWN.start.store(pages.start(), Ordering::Relaxed);
+        SHOOTDOWN.end.store(pages.end(), Ordering::Relaxed);
+        SHOOTDOWN.pending.store(target_count, Ordering::Relaxed);
+
+        for (word, &bits) in SHOOTDOWN.targets.iter().zip(targets.iter()) {
+            word.fetch_or(bits, Ordering::Release);
+        }
+
+        for cpu in 0 ..= CpuId::MAX {
+            let (word, bit) = target_bit(cpu);
+            if targets[word] & bit != 0 {
+                LocalApic::send_ipi(cpu, Trap::TlbShootdown);
+            }
+        }
+
+        while SHOOTDOWN.pending.load(Ordering::Acquire) != 0 {
+            hint::spin_loop();
+        }
+
+        SHOOTDOWN.busy.store(false, Ordering::Release);
+    });
+}
+
+/// Обслуживает текущий запрос на сброс TLB, если он адресован текущему процессору
+/// и ещё не был им обслужен.
+/// Вызывается обработчиком прерывания [`Trap::TlbShootdown`].
+///
+/// Процессор снимает свой бит в наборе адресатов атомарно.
+/// Поэтому каждый запрос подтверждается каждым процессором ровно один раз,
+/// даже если он обслужил запрос, ожидая своей очереди в [`tlb_shootdown()`],
+/// а прерывание от этого запроса пришло позже.
+pub(crate) fn serve_tlb_shootdown() {
+    let (word, bit) = target_bit(LocalApic::id());
+
+    if SHOOTDOWN.targets[word].fetch_and(!bit, Ordering::Acquire) & bit == 0 {
+        return;
+    }
+
+    let start = SHOOTDOWN.start.load(Ordering::Relaxed);
+    let end = SHOOTDOWN.end.load(Ordering::Relaxed);
+    flush(Block::from_index(start, end).unwrap());
+
+    SHOOTDOWN.pending.fetch_sub(1, Ordering::Release);
+}
+
+/// Сбрасывает TLB текущего процессора для блока страниц `pages`.
+///
+/// Если страниц больше [`MAX_FLUSHED_PAGES`], сбрасывает TLB целиком ---
+/// это дешевле, чем сбрасывать страницы по одной.
+/// Так не сбрасываются записи глобальных страниц, но ядро их не использует.
+fn flush(pages: Block<Page>) {
+    if pages.count() > MAX_FLUSHED_PAGES {
+        unsafe {
+            tlb::flush_all();
+        }
+    } else {
+        for page in pages {
+            unsafe {
+                mmu::flush(page);
+            }
+        }
+    }
+}
+
+/// Возвращает номер слова и бит процессора `cpu` в [`TlbShootdown::targets`].
+fn target_bit(cpu: CpuId) -> (usize, u64) {
+    let cpu = usize::from(cpu);
+
+    (cpu / TARGET_BITS, 1 << (cpu % TARGET_BITS))
+}
+
+/// Запрос на сброс TLB, общий для всех процессоров.
+struct TlbShootdown {
+    /// Занят ли [`SHOOTDOWN`] одним из инициаторов.
+    busy: AtomicBool,
+
+    /// Номер первой страницы блока, для которого нужно сбросить TLB.
+    start: AtomicUsize,
+
+    /// Номер страницы, следующей за последней страницей блока.
+    end: AtomicUsize,
+
+    /// Сколько адресатов ещё не подтвердили сброс TLB.
+    pending: AtomicUsize,
+
+    /// Набор адресатов, которые ещё не обслужили запрос, по биту на каждый [`CpuId`].
+    targets: [AtomicU64; TARGET_WORDS],
+}
+
+/// Текущий запрос на сброс TLB.
+static SHOOTDOWN: TlbShootdown = TlbShootdown {
+    busy: AtomicBool::new(false),
+    start: AtomicUsize::new(0),
+    end: AtomicUsize::new(0),
+    pending: AtomicUsize::new(0),
+    targets: [const { AtomicU64::new(0) }; TARGET_WORDS],
+};
+
+/// Максимальное количество страниц, TLB для которых сбрасывается постранично.
+const MAX_FLUSHED_PAGES: usize = 32;
+
+/// Количество бит в одном слове [`TlbShootdown::targets`].
+const TARGET_BITS: usize = u64::BITS as usize;
+
+/// Количество слов в [`TlbShootdown::targets`].
+const TARGET_WORDS: usize = CPU_ID_COUNT.div_ceil(TARGET_BITS);
@@ -0,0 +1,471 @@
+use core::{
+    arch::{
+        asm,
+        naked_asm,
+    },
+    str,
+    sync::atomic::{
+        AtomicBool,
+        AtomicUsize,
+        Ordering,
+    },
+};
+
+use x86::cpuid::CpuId;
+use x86_64::registers::control::{
+    Cr4,
+    Cr4Flags,
+};
+
+use ku::{
+    memory::PageFaultInfo,
+    process::{
+        Info,
+        MiniContext,
+        RFlags,
+    },
+};
+
+use crate::{
+    error::{
+        Error::{
+            InvalidArgument,
+            NoPage,
+            Overflow,
+            PermissionDenied,
+        },
+        Result,
+    },
+    log::{
+        error,
+        info,
+    },
+    trap::TrapContext,
+};
+
+use super::{
+    Block,
+    Page,
+    Virt,
+    range,
+};
+
+// Used in docs.
+#[allow(unused)]
+use crate::error::Error;
+
+/// Включает
+/// [Supervisor Mode Execution Prevention (SMEP)](https://en.wikipedia.org/wiki/Control_register#SMEP)
+/// и
+/// [Supervisor Mode Access Prevention (SMAP)](https://en.wikipedia.org/wiki/Supervisor_Mode_Access_Prevention),
+/// если процессор их поддерживает.
+///
+/// SMEP запрещает ядру исполнять код со страниц пользователя.
+/// SMAP запрещает ядру обращаться к страницам пользователя
+/// вне окон, открытых с помощью [`UserAccess`].
+///
+/// Application Processors копируют регистр `CR4` у Bootstrap Processor,
+/// поэтому вызывать [`init()`] нужно до их запуска.
+pub(super) fn init() {
+    let features = CpuId::new().get_extended_feature_info();
+    let smep = features.as_ref().is_some_and(|features| features.has_smep());
+    let smap = features.as_ref().is_some_and(|features| features.has_smap());
+
+    let mut cr4 = Cr4::read();
+    cr4.set(Cr4Flags::SUPERVISOR_MODE_EXECUTION_PROTECTION, smep);
+    cr4.set(Cr4Flags::SUPERVISOR_MODE_ACCESS_PREVENTION, smap);
+
+    unsafe {
+        Cr4::write(cr4);
+    }
+
+    SMEP.store(smep, Ordering::Relaxed);
+    SMAP.store(smap, Ordering::Relaxed);
+
+    info!(smep, smap, "supervisor mode protections");
+}
+
+/// Возвращает `true`, если включён
+/// [Supervisor Mode Access Prevention (SMAP)](https://en.wikipedia.org/wiki/Supervisor_Mode_Access_Prevention).
+pub fn is_smap_enabled() -> bool {
+    SMAP.load(Ordering::Relaxed)
+}
+
+/// Возвращает `true`, если включён
+/// [Supervisor Mode Execution Prevention (SMEP)](https://en.wikipedia.org/wiki/Control_register#SMEP).
+pub fn is_smep_enabled() -> bool {
+    SMEP.load(Ordering::Relaxed)
+}
+
+/// Возвращает количество обращений ядра к памяти пользователя вне окон [`UserAccess`],
+/// которые обнаружил
+/// [SMAP](https://en.wikipedia.org/wiki/Supervisor_Mode_Access_Prevention).
+pub fn smap_violations() -> usize {
+    SMAP_VIOLATIONS.load(Ordering::Relaxed)
+}
+
+/// Копирует в буфер ядра `dst` столько же байт из памяти пользователя,
+/// начиная с адреса `user_src`.
+///
+/// Копирование выполняется внутри окна [`UserAccess`].
+/// Если во время копирования произошёл page fault, например,
+/// потому что страница пользователя не отображена или удалена другим потоком,
+/// он не приводит к панике ядра --- копирование прерывается и
+/// возвращается ошибка [`Error::NoPage`].
+/// Часть `dst` при этом может оказаться уже скопированной.
+///
+/// Возвращает ошибку [`Error::PermissionDenied`],
+/// если исходный диапазон не лежит целиком в памяти пользователя.
+pub fn copy_from_user(
+    dst: &mut [u8],
+    user_src: Virt,
+) -> Result<()> {
+    let src = user_block(user_src, dst.len())?;
+
+    unsafe { copy(dst.as_mut_ptr(), src.into_ptr_u8(), dst.len()) }
+}
+
+/// Копирует буфер ядра `src` в память пользователя, начиная с адреса `user_dst`.
+///
+/// Как и [`copy_from_user()`], превращает page fault во время копирования
+/// в ошибку [`Error::NoPage`].
+/// В частности, ядро не обрабатывает копирование при записи ---
+/// запись в такую страницу тоже завершится ошибкой [`Error::NoPage`].
+///
+/// Возвращает ошибку [`Error::PermissionDenied`],
+/// если целевой диапазон не лежит целиком в памяти пользователя.
+pub fn copy_to_user(
+    user_dst: Virt,
+    src: &[u8],
+) -> Result<()> {
+    let dst = user_block(user_dst, src.len())?;
+
+    unsafe { copy(dst.into_mut_ptr_u8(), src.as_ptr(), src.len()) }
+}
+
+/// Читает из памяти пользователя, начиная с адреса `user_src`,
+/// строку, завершающуюся нулевым байтом.
+/// Использует `buffer` как буфер и возвращает прочитанную строку без нулевого байта.
+/// Нулевой байт тоже должен поместиться в `buffer`,
+/// так что длина строки не превосходит `buffer.len() - 1`.
+///
+/// Копирует память с помощью [`copy_from_user()`] кусками до конца очередной страницы.
+/// Поэтому байты после нулевого до конца его страницы тоже попадают в `buffer`,
+/// а следующие страницы не читаются.
+/// Так что строка может заканчиваться у самой границы отображённой памяти.
+///
+/// Возвращает ошибки:
+///   - [`Error::Overflow`], если среди первых `buffer.len()` байт нет нулевого,
+///     в том числе если `buffer` пуст.
+///   - [`Error::InvalidArgument`], если строка не является корректной
+///     [UTF-8](https://en.wikipedia.org/wiki/UTF-8).
+///   - [`Error::NoPage`], если не отображена одна из страниц,
+///     начиная с `user_src` и заканчивая страницей нулевого байта.
+///   - [`Error::PermissionDenied`], если одна из этих страниц лежит вне памяти пользователя
+///     или адрес очередного куска не помещается в адресное пространство.
+pub fn read_user_str(
+    user_src: Virt,
+    buffer: &mut [u8],
+) -> Result<&str> {
+    let mut len = 0;
+
+    while len < buffer.len() {
+        let address = (user_src + len).map_err(|_| PermissionDenied)?;
+        let page_tail = Page::SIZE - address.into_usize() % Page::SIZE;
+        let chunk = len .. len + page_tail.min(buffer.len() - len);
+
+        copy_from_user(&mut buffer[chunk.clone()], address)?;
+
+        if let Some(nul) = buffer[chunk.clone()].iter().position(|&x| x == 0) {
+            return str::from_utf8(&buffer[.. chunk.start + nul]).map_err(|_| InvalidArgument);
+        }
+
+        len = chunk.end;
+    }
+
+    Err(Overflow)
+}
+
+/// Обрабатывает исключение `info`, которое возникло в ядре в контексте `context`.
+/// Возвращает `true`, если это page fault на странице пользователя
+/// внутри [`copy_from_user()`] или [`copy_to_user()`].
+/// В этом случае продолжает исполнение с адреса [`copy_user_fixup`],
+/// так что копирование завершается ошибкой [`Error::NoPage`].
+pub(crate) fn copy_fault_handler(
+    info: &Info,
+    context: &mut TrapContext,
+) -> bool {
+    let Info::PageFault { address, code } = *info else {
+        return false;
+    };
+
+    let mut mode_context = context.get();
+    let mini_context = mode_context.mini_context();
+
+    if mini_context.rip() != Virt::from_ptr(&raw const copy_user_instruction) ||
+        !range::is_user_page(Page::containing(address))
+    {
+        return false;
+    }
+
+    info!(%address, %code, %context, "page fault while copying the user memory");
+
+    mode_context.set_mini_context(MiniContext::new(
+        Virt::from_ptr(&raw const copy_user_fixup),
+        mini_context.rsp(),
+    ));
+    context.set(mode_context);
+
+    true
+}
+
+/// Окно, в котором ядру разрешено обращаться к памяти пользователя.
+///
+/// Пока [`UserAccess`] существует, на текущем процессоре установлен флаг
+/// [`RFlags::ALIGNMENT_CHECK`] и
+/// [SMAP](https://en.wikipedia.org/wiki/Supervisor_Mode_Access_Prevention)
+/// не мешает обращениям к страницам пользователя.
+/// Окно должно охватывать только намеренные обращения к памяти пользователя,
+/// доступ к которой уже проверен, например,
+/// методом [`AddressSpace::check_permission()`][super::AddressSpace::check_permission].
+///
+/// Окна могут быть вложенными --- флаг сбрасывает только самое внешнее из них.
+/// Если SMAP выключен, [`UserAccess`] ничего не делает.
+#[must_use]
+pub struct UserAccess {
+    /// Это окно установило флаг [`RFlags::ALIGNMENT_CHECK`] и должно его сбросить.
+    is_outermost: bool,
+}
+
+impl UserAccess {
+    /// Открывает окно доступа ядра к памяти пользователя.
+    pub fn new() -> Self {
+        let is_outermost = is_smap_enabled() && !RFlags::read().contains(RFlags::ALIGNMENT_CHECK);
+
+        if is_outermost {
+            unsafe {
+                stac();
+            }
+        }
+
+        Self { is_outermost }
+    }
+
+    /// Обрабатывает исключение `info`, которое возникло в ядре в контексте `context`.
+    /// Возвращает `true`, если исключение вызвано обращением ядра к памяти пользователя
+    /// вне окна [`UserAccess`] и обработано.
+    ///
+    /// Такое обращение --- ошибка в ядре, поэтому оно всегда учитывается в
+    /// [`smap_violations()`] и записывается в журнал.
+    /// Продолжить исполнение можно только там, где такое обращение ожидается.
+    /// Тогда исключение обрабатывается повторным исполнением инструкции
+    /// с установленным флагом [`RFlags::ALIGNMENT_CHECK`].
+    /// Иначе возвращается `false` и исключение остаётся фатальным.
+    pub(crate) fn trap_handler(
+        info: &Info,
+        context: &mut TrapContext,
+    ) -> bool {
+        let mut mode_context = context.get();
+
+        let Info::PageFault { address, code } = *info else {
+            return false;
+        };
+
+        let is_violation = is_smap_enabled() &&
+            code.contains(PageFaultInfo::PRESENT) &&
+            !code.intersects(PageFaultInfo::USER | PageFaultInfo::EXECUTE) &&
+            !mode_context.rflags().contains(RFlags::ALIGNMENT_CHECK) &&
+            range::is_user_page(Page::containing(address));
+
+        if !is_violation {
+            return false;
+        }
+
+        SMAP_VIOLATIONS.fetch_add(1, Ordering::Relaxed);
+        error!(%address, %code, %context, "kernel access to the user memory outside of UserAccess");
+
+        if !EXPECT_VIOLATION.load(Ordering::Relaxed) {
+            return false;
+        }
+
+        mode_context.set_rflags(mode_context.rflags() | RFlags::ALIGNMENT_CHECK);
+        context.set(mode_context);
+
+        true
+    }
+}
+
+impl Default for UserAccess {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for UserAccess {
+    fn drop(&mut self) {
+        if self.is_outermost {
+            unsafe {
+                clac();
+            }
+        }
+    }
+}
+
+/// Возвращает блок из `len` байт памяти пользователя, начиная с адреса `address`.
+///
+/// Возвращает ошибку [`Error::PermissionDenied`],
+/// если блок не лежит целиком в памяти пользователя.
+fn user_block(
+    address: Virt,
+    len: usize,
+) -> Result<Block<Virt>> {
+    let start = address.into_usize();
+    let end = start.checked_add(len).ok_or(PermissionDenied)?;
+    let block = Block::<Virt>::from_index(start, end).map_err(|_| PermissionDenied)?;
+
+    if len == 0 || range::is_user_block(block.enclosing()) {
+        Ok(block)
+    } else {
+        Err(PermissionDenied)
+    }
+}
+
+/// Копирует `len` байт из `src` в `dst` внутри окна [`UserAccess`].
+///
+/// Возвращает ошибку [`Error::NoPage`], если копирование прервал page fault,
+/// см. [`copy_fault_handler()`].
+///
+/// # Safety
+///
+/// Одна из областей должна лежать в памяти ядра и быть доступна,
+/// а другая --- лежать в памяти пользователя.
+unsafe fn copy(
+    dst: *mut u8,
+    src: *const u8,
+    len: usize,
+) -> Result<()> {
+    let _user_access = UserAccess::new();
+
+    if unsafe { copy_user(dst, src, len) } == 0 {
+        Ok(())
+    } else {
+        Err(NoPage)
+    }
+}
+
+/// Копирует `len` байт из `src` в `dst` инструкцией
+/// [`rep movsb`](https://www.felixcloutier.com/x86/movs:movsb:movsw:movsd:movsq).
+///
+/// Возвращает `0`, если копирование завершено.
+/// Если инструкция по метке [`copy_user_instruction`] вызвала page fault,
+/// [`copy_fault_handler()`] переносит исполнение на метку [`copy_user_fixup`]
+/// и функция возвращает `1`.
+#[unsafe(naked)]
+unsafe extern "C" fn copy_user(
+    dst: *mut u8,   // rdi
+    src: *const u8, // rsi
+    len: usize,     // rdx
+) -> usize {
+    naked_asm!(
+        "
+        mov rcx, rdx
+
+        .global copy_user_instruction
+    copy_user_instruction:
+        rep movsb
+
+        xor eax, eax
+        ret
+
+        .global copy_user_fixup
+    copy_user_fixup:
+        mov eax, 1
+        ret
+        ",
+    );
+}
+
+#[allow(non_upper_case_globals)]
+unsafe extern "C" {
+    /// Метка инструкции внутри [`copy_user()`], которая обращается к памяти.
+    static copy_user_instruction: u8;
+
+    /// Метка внутри [`copy_user()`], с которой продолжается исполнение
+    /// после page fault на инструкции [`copy_user_instruction`].
+    static copy_user_fixup: u8;
+}
+
+/// Разрешает ядру обращаться к страницам пользователя, устанавливая флаг
+/// [`RFlags::ALIGNMENT_CHECK`] инструкцией [`stac`](https://www.felixcloutier.com/x86/stac).
+///
+/// # Safety
+///
+/// Процессор должен поддерживать SMAP, иначе инструкция вызовет исключение.
+unsafe fn stac() {
+    unsafe {
+        asm!("stac", options(nostack));
+    }
+}
+
+/// Запрещает ядру обращаться к страницам пользователя, сбрасывая флаг
+/// [`RFlags::ALIGNMENT_CHECK`] инструкцией [`clac`](https://www.felixcloutier.com/x86/clac).
+///
+/// # Safety
+///
+/// Процессор должен поддерживать SMAP, иначе инструкция вызовет исключение.
+unsafe fn clac() {
+    unsafe {
+        asm!("clac", options(nostack));
+    }
+}
+
+/// Ожидается ли обращение ядра к памяти пользователя вне окна [`UserAccess`].
+/// Используется только в тестах.
+static EXPECT_VIOLATION: AtomicBool = AtomicBool::new(false);
+
+/// Включён ли
+/// [Supervisor Mode Access Prevention (SMAP)](https://en.wikipedia.org/wiki/Supervisor_Mode_Access_Prevention).
+static SMAP: AtomicBool = AtomicBool::new(false);
+
+/// Количество обращений ядра к памяти пользователя вне окон [`UserAccess`].
+static SMAP_VIOLATIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// Включён ли
+/// [Supervisor Mode Execution Prevention (SMEP)](https://en.wikipedia.org/wiki/Control_register#SMEP).
+static SMEP: AtomicBool = AtomicBool::new(false);
+
+#[doc(hidden)]
+pub(super) mod test_scaffolding {
+    use core::sync::atomic::{
+        self,
+        Ordering,
+    };
+
+    use super::{
+        EXPECT_VIOLATION,
+        SMAP_VIOLATIONS,
+        clac,
+        is_smap_enabled,
+    };
+
+    pub fn unbracketed_read(address: *const u8) -> (u8, bool) {
+        let start_violations = SMAP_VIOLATIONS.load(Ordering::Relaxed);
+
+        EXPECT_VIOLATION.store(true, Ordering::Relaxed);
+        atomic::compiler_fence(Ordering::SeqCst);
+        let value = unsafe { address.read_volatile() };
+        atomic::compiler_fence(Ordering::SeqCst);
+        EXPECT_VIOLATION.store(false, Ordering::Relaxed);
+
+        if is_smap_enabled() {
+            unsafe {
+                clac();
+            }
+        }
+
+        (
+            value,
+            SMAP_VIOLATIONS.load(Ordering::Relaxed) != start_violations,
+        )
+    }
+}
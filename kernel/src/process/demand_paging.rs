@@ -0,0 +1,140 @@
+use core::cmp;
+
+use ku::process::elf;
+
+use crate::{
+    error::Result,
+    memory::{
+        self,
+        AddressSpace,
+        Block,
+        Page,
+        USER_R,
+        USER_RW,
+        UserAccess,
+        Virt,
+        mmu::{
+            self,
+            PageTableFlags,
+        },
+    },
+};
+
+/// Готовит адресное пространство `address_space` к загрузке страниц
+/// [ELF--файла](https://en.wikipedia.org/wiki/Executable_and_Linkable_Format) `elf_file`
+/// по требованию.
+///
+/// Резервирует страницы всех его загружаемых сегментов, но отображает только страницы
+/// сегментов, которые нужно загрузить сразу, --- см. [`is_eager()`].
+/// Остальные страницы загружает [`load_page()`] при первом обращении к ним.
+/// Возвращает количество загруженных сразу страниц.
+///
+/// На время работы переключается в `address_space`,
+/// а в конце переключается в `original_address_space`.
+pub(super) fn prepare(
+    address_space: &mut AddressSpace,
+    original_address_space: &AddressSpace,
+    elf_file: &[u8],
+) -> Result<usize> {
+    let segments = elf::loadable_segments(elf_file, memory::is_user_block)?;
+
+    let mut reserved_end = 0;
+    for (segment, _) in &segments {
+        let pages = segment.enclosing();
+        let start = cmp::max(pages.start(), reserved_end);
+        if start < pages.end() {
+            address_space.reserve(Block::from_index(start, pages.end())?, USER_R)?;
+            reserved_end = pages.end();
+        }
+    }
+
+    address_space.switch_to();
+    let eager_page_count = load_eager_pages(address_space, elf_file, &segments);
+    original_address_space.switch_to();
+
+    eager_page_count
+}
+
+/// Загружает страницу `page` из ELF--файла `elf_file` в текущее адресное пространство
+/// `address_space`.
+///
+/// Возвращает `false`, если страница не принадлежит ни одному загружаемому сегменту.
+pub(super) fn load_page(
+    address_space: &mut AddressSpace,
+    elf_file: &[u8],
+    page: Page,
+) -> Result<bool> {
+    let Some(flags) = elf::page_flags(elf_file, page)? else {
+        return Ok(false);
+    };
+
+    unsafe {
+        address_space.map_page(page, USER_RW)?;
+    }
+
+    let user_access = UserAccess::new();
+    let content = unsafe { Block::from_element(page)?.try_into_mut_slice::<u8>() };
+    let result = content.and_then(|content| elf::load_page(elf_file, page, content));
+    drop(user_access);
+
+    if let Err(error) = result {
+        unsafe {
+            address_space.unmap_page(page)?;
+        }
+        return Err(error);
+    }
+
+    unsafe {
+        address_space.remap_block(Block::from_element(page)?, flags | USER_R)?;
+        mmu::flush(page);
+    }
+
+    Ok(true)
+}
+
+/// Загружает в текущее адресное пространство `address_space` страницы тех сегментов `segments`
+/// ELF--файла `elf_file`, которые нужно загрузить сразу.
+/// Возвращает количество загруженных страниц.
+fn load_eager_pages(
+    address_space: &mut AddressSpace,
+    elf_file: &[u8],
+    segments: &[(Block<Virt>, PageTableFlags)],
+) -> Result<usize> {
+    let mut eager_page_count = 0;
+    let mut loaded_end = 0;
+
+    for &(segment, flags) in segments {
+        let pages = segment.enclosing();
+        if is_eager(pages, flags) {
+            for page in pages.into_iter().filter(|page| page.index() >= loaded_end) {
+                load_page(address_space, elf_file, page)?;
+                eager_page_count += 1;
+            }
+            loaded_end = cmp::max(loaded_end, pages.end());
+        }
+    }
+
+    Ok(eager_page_count)
+}
+
+/// Возвращает `true`, если сегмент из страниц `pages` с флагами `flags` нужно загрузить сразу.
+///
+/// Сразу загружаются:
+///   - Небольшие сегменты --- обработка Page Fault для них обойдётся дороже,
+///     чем копирование при создании процесса.
+///   - Сегменты данных, как доступные на запись, так и только на чтение.
+///     Процесс может передать ядру ссылку на свои статические переменные или константы
+///     в системном вызове, а ядро не загружает страницы по требованию
+///     при собственных обращениях к памяти пользователя.
+///
+/// По требованию загружаются только большие сегменты кода.
+fn is_eager(
+    pages: Block<Page>,
+    flags: PageTableFlags,
+) -> bool {
+    pages.count() < LAZY_SEGMENT_MIN_PAGE_COUNT || !flags.is_executable()
+}
+
+/// Минимальный размер сегмента в страницах, начиная с которого
+/// его страницы загружаются по требованию.
+const LAZY_SEGMENT_MIN_PAGE_COUNT: usize = 4;
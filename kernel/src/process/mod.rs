@@ -1,22 +1,45 @@
+/// Загрузка страниц сегментов ELF--файлов процессов по требованию.
+mod demand_paging;
+
 /// Содержит структуру пользовательского процесса [`Process`].
 #[allow(clippy::module_inception)]
 mod process;
 
+/// Каналы для передачи байт между процессами.
+mod pipe;
+
 /// Описывает состояние регистров процесса [`Registers`] и
 /// контекст исполнения содержащий уровень привилегий [`ModeContext`].
 mod registers;
 
-/// Планировщик процессов.
-/// Реализует простейшее
-/// [циклическое исполнение процессов](https://en.wikipedia.org/wiki/Round-robin_scheduling).
+/// Планировщик процессов с приоритетами.
 mod scheduler;
 
+/// Общие для процессов одного ELF--файла неизменяемые страницы.
+mod shared_pages;
+
+/// Разделяемая между процессами память, которую один процесс
+/// явно предоставляет другому по имени.
+mod shared_memory;
+
+/// Журнал переходов процесса между состояниями.
+mod state_audit;
+
 /// Реализует системные вызовы.
 pub(crate) mod syscall;
 
+/// Статистика системных вызовов.
+mod syscall_stats;
+
+/// Запись последовательности системных вызовов процесса.
+mod syscall_trace;
+
 /// Таблица процессов.
 mod table;
 
+/// Колесо таймеров для спящих процессов.
+mod timer_wheel;
+
 use ku::process::elf;
 
 use crate::{
@@ -39,6 +62,19 @@ pub use ku::process::Pid;
 
 pub use process::Process;
 pub use scheduler::Scheduler;
+pub use state_audit::{
+    StateAudit,
+    StateTransition,
+};
+pub use syscall_stats::{
+    SYSCALL_STATS,
+    SyscallStatistics,
+    SyscallStats,
+};
+pub use syscall_trace::{
+    SyscallRecord,
+    SyscallTrace,
+};
 pub use table::Table;
 
 pub(crate) use registers::{
@@ -67,13 +103,48 @@ pub fn init(subsystems: Subsystems) {
 /// вставляет его в таблицу процессов и
 /// возвращает его идентификатор.
 pub fn create(elf_file: &[u8]) -> Result<Pid> {
-    Table::allocate(create_process(elf_file)?)
+    create_with_log_frame_count(elf_file, Process::DEFAULT_LOG_FRAME_COUNT)
+}
+
+/// Создаёт процесс для заданного
+/// [ELF--файла](https://en.wikipedia.org/wiki/Executable_and_Linkable_Format)
+/// `elf_file` с буфером журналирования из `log_frame_count` фреймов памяти,
+/// вставляет его в таблицу процессов и
+/// возвращает его идентификатор.
+///
+/// Больший буфер позволяет процессу, который активно пишет в журнал,
+/// реже терять сообщения.
+/// Допустимые значения `log_frame_count` --- от `1` до [`Process::MAX_LOG_FRAME_COUNT`].
+pub fn create_with_log_frame_count(
+    elf_file: &[u8],
+    log_frame_count: usize,
+) -> Result<Pid> {
+    Table::allocate(create_process(elf_file, log_frame_count)?)
 }
 
 /// Создаёт процесс для заданного
 /// [ELF--файла](https://en.wikipedia.org/wiki/Executable_and_Linkable_Format)
-/// `elf_file` и возвращает его.
-fn create_process(elf_file: &[u8]) -> Result<Process> {
+/// `elf_file`, вставляет его в таблицу процессов и возвращает его идентификатор.
+///
+/// В отличие от [`create()`], не копирует сегменты ELF--файла целиком,
+/// а загружает их страницы по требованию --- при первом обращении процесса к ним.
+/// Небольшие и доступные на запись сегменты всё равно загружаются сразу.
+/// Поэтому ELF--файл должен оставаться доступным всё время жизни процесса.
+pub fn create_lazy(elf_file: &'static [u8]) -> Result<Pid> {
+    let process = create_lazy_process(elf_file, Process::DEFAULT_LOG_FRAME_COUNT)?;
+    Table::allocate(process)
+}
+
+/// Создаёт процесс для заданного
+/// [ELF--файла](https://en.wikipedia.org/wiki/Executable_and_Linkable_Format)
+/// `elf_file` с буфером журналирования из `log_frame_count` фреймов памяти и возвращает его.
+///
+/// Если в таблице процессов уже есть процесс, загруженный из того же ELF--файла,
+/// неизменяемые страницы нового процесса делит с ним, см. [`shared_pages::share()`].
+fn create_process(
+    elf_file: &[u8],
+    log_frame_count: usize,
+) -> Result<Process> {
     let mut base_address_space = BASE_ADDRESS_SPACE.lock();
     let mut process_address_space = base_address_space.duplicate()?;
     let mut src_dst = BigPair::new_pair(
@@ -84,12 +155,53 @@ fn create_process(elf_file: &[u8]) -> Result<Process> {
     );
 
     let entry = unsafe { elf::load(&mut src_dst, elf_file)? };
+    let build_info = elf::BuildInfo::new(elf_file).unwrap_or_default();
 
     drop(base_address_space);
 
-    let process = Process::new(process_address_space, entry)?;
+    let shared_page_count = shared_pages::share(&mut process_address_space, elf_file)?;
+
+    let mut process = Process::new(process_address_space, entry, log_frame_count)?;
+    process.set_elf_image(elf_file);
 
-    info!(%entry, file_size = %Size::from_slice(elf_file), %process, "loaded ELF file");
+    info!(
+        %entry,
+        %build_info,
+        shared_page_count,
+        file_size = %Size::from_slice(elf_file),
+        %process,
+        "loaded ELF file",
+    );
+
+    Ok(process)
+}
+
+/// Создаёт процесс для заданного
+/// [ELF--файла](https://en.wikipedia.org/wiki/Executable_and_Linkable_Format)
+/// `elf_file` с буфером журналирования из `log_frame_count` фреймов памяти и возвращает его.
+/// Страницы сегментов ELF--файла загружаются по требованию, см. [`create_lazy()`].
+fn create_lazy_process(
+    elf_file: &'static [u8],
+    log_frame_count: usize,
+) -> Result<Process> {
+    let entry = elf::entry_point(elf_file)?;
+
+    let base_address_space = BASE_ADDRESS_SPACE.lock();
+    let mut process_address_space = base_address_space.duplicate()?;
+    let eager_page_count =
+        demand_paging::prepare(&mut process_address_space, &base_address_space, elf_file)?;
+    drop(base_address_space);
+
+    let mut process = Process::new(process_address_space, entry, log_frame_count)?;
+    process.set_demand_paging(elf_file);
+
+    info!(
+        %entry,
+        eager_page_count,
+        file_size = %Size::from_slice(elf_file),
+        %process,
+        "loaded ELF file on demand",
+    );
 
     Ok(process)
 }
@@ -121,13 +233,28 @@ pub mod test_scaffolding {
     };
 
     pub fn create_process(elf_file: &[u8]) -> Result<Process> {
-        super::create_process(elf_file)
+        super::create_process(elf_file, Process::DEFAULT_LOG_FRAME_COUNT)
+    }
+
+    pub fn create_lazy_process(elf_file: &'static [u8]) -> Result<Process> {
+        super::create_lazy_process(elf_file, Process::DEFAULT_LOG_FRAME_COUNT)
+    }
+
+    pub fn create_process_with_log_frame_count(
+        elf_file: &[u8],
+        log_frame_count: usize,
+    ) -> Result<Process> {
+        super::create_process(elf_file, log_frame_count)
     }
 
     pub fn dummy_process() -> Result<Pid> {
         let address_space = BASE_ADDRESS_SPACE.lock().duplicate()?;
 
-        let process = Process::new(address_space, Virt::default())?;
+        let process = Process::new(
+            address_space,
+            Virt::default(),
+            Process::DEFAULT_LOG_FRAME_COUNT,
+        )?;
 
         Table::allocate(process)
     }
@@ -0,0 +1,224 @@
+use alloc::{
+    collections::VecDeque,
+    sync::Arc,
+    vec::Vec,
+};
+use core::cmp;
+
+use chrono::{
+    DateTime,
+    Utc,
+};
+
+use ku::{
+    ipc::pipe::Error::Closed,
+    sync::Spinlock,
+};
+
+use crate::error::{
+    Error::{
+        self,
+        NoData,
+        PermissionDenied,
+    },
+    Result,
+};
+
+use super::{
+    Pid,
+    Scheduler,
+};
+
+/// Конец канала для передачи байт между процессами ---
+/// для чтения [`PipeEnd::Reader`] или для записи [`PipeEnd::Writer`].
+///
+/// Копия конца канала, например при дублировании процесса, открывает его ещё раз.
+/// Канал считается закрытым с одной из сторон,
+/// когда удалены все копии соответствующего конца.
+#[derive(Debug)]
+pub(super) enum PipeEnd {
+    /// Конец канала для чтения.
+    Reader(Arc<Spinlock<Pipe>>),
+
+    /// Конец канала для записи.
+    Writer(Arc<Spinlock<Pipe>>),
+}
+
+impl PipeEnd {
+    /// Создаёт новый пустой канал и возвращает его концы для чтения и для записи.
+    pub(super) fn new() -> (Self, Self) {
+        let pipe = Arc::new(Spinlock::new(Pipe {
+            buffer: VecDeque::new(),
+            readers: 1,
+            waiters: Vec::new(),
+            writers: 1,
+        }));
+
+        (Self::Reader(pipe.clone()), Self::Writer(pipe))
+    }
+
+    /// Читает из канала в `buffer` столько байт, сколько в нём есть, но не больше размера `buffer`.
+    /// Возвращает количество прочитанных байт.
+    /// Ноль означает, что все концы канала для записи закрыты и данных больше не будет.
+    ///
+    /// Сам не блокируется.
+    /// Если канал пуст, но его конец для записи ещё открыт,
+    /// усыпляет процесс `pid` методом [`Scheduler::sleep()`] до следующего изменения канала
+    /// и возвращает ошибку [`Error::NoData`].
+    /// Тогда процесс должен уступить процессор и повторить чтение.
+    ///
+    /// Возвращает ошибку [`Error::PermissionDenied`], если это конец канала для записи.
+    pub(super) fn read(
+        &self,
+        pid: Pid,
+        buffer: &mut [u8],
+    ) -> Result<usize> {
+        let Self::Reader(pipe) = self else {
+            return Err(PermissionDenied);
+        };
+
+        if buffer.is_empty() {
+            return Ok(0);
+        }
+
+        let mut pipe = pipe.lock();
+
+        if pipe.buffer.is_empty() {
+            return if pipe.writers == 0 {
+                Ok(0)
+            } else {
+                Err(pipe.wait(pid))
+            };
+        }
+
+        let len = cmp::min(buffer.len(), pipe.buffer.len());
+        for (dst, src) in buffer.iter_mut().zip(pipe.buffer.drain(.. len)) {
+            *dst = src;
+        }
+
+        pipe.wake_up_waiters();
+
+        Ok(len)
+    }
+
+    /// Записывает в канал столько байт из `buffer`, сколько в нём есть места.
+    /// Возвращает количество записанных байт.
+    ///
+    /// Сам не блокируется.
+    /// Если канал заполнен, усыпляет процесс `pid` методом [`Scheduler::sleep()`]
+    /// до следующего изменения канала и возвращает ошибку [`Error::NoData`].
+    /// Тогда процесс должен уступить процессор и повторить запись.
+    ///
+    /// Возвращает ошибки:
+    ///   - [`Error::Pipe`] с [`Closed`], если все концы канала для чтения закрыты.
+    ///   - [`Error::PermissionDenied`], если это конец канала для чтения.
+    pub(super) fn write(
+        &self,
+        pid: Pid,
+        buffer: &[u8],
+    ) -> Result<usize> {
+        let Self::Writer(pipe) = self else {
+            return Err(PermissionDenied);
+        };
+
+        let mut pipe = pipe.lock();
+
+        if pipe.readers == 0 {
+            return Err(Closed.into());
+        }
+
+        if buffer.is_empty() {
+            return Ok(0);
+        }
+
+        let free_space = Self::CAPACITY - pipe.buffer.len();
+        if free_space == 0 {
+            return Err(pipe.wait(pid));
+        }
+
+        let len = cmp::min(buffer.len(), free_space);
+        pipe.buffer.extend(&buffer[.. len]);
+
+        pipe.wake_up_waiters();
+
+        Ok(len)
+    }
+
+    /// Ёмкость канала в байтах.
+    pub(super) const CAPACITY: usize = 4096;
+}
+
+impl Clone for PipeEnd {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Reader(pipe) => {
+                pipe.lock().readers += 1;
+                Self::Reader(pipe.clone())
+            },
+            Self::Writer(pipe) => {
+                pipe.lock().writers += 1;
+                Self::Writer(pipe.clone())
+            },
+        }
+    }
+}
+
+impl Drop for PipeEnd {
+    fn drop(&mut self) {
+        let mut pipe = match self {
+            Self::Reader(pipe) => {
+                let mut pipe = pipe.lock();
+                pipe.readers -= 1;
+                pipe
+            },
+            Self::Writer(pipe) => {
+                let mut pipe = pipe.lock();
+                pipe.writers -= 1;
+                pipe
+            },
+        };
+
+        // Ожидающие процессы должны увидеть конец данных или ошибку.
+        pipe.wake_up_waiters();
+    }
+}
+
+/// Канал для передачи байт между процессами.
+#[derive(Debug)]
+pub(super) struct Pipe {
+    /// Записанные в канал, но ещё не прочитанные из него байты.
+    buffer: VecDeque<u8>,
+
+    /// Количество открытых концов канала для чтения.
+    readers: usize,
+
+    /// Процессы, которые ждут изменения канала, чтобы повторить чтение или запись.
+    waiters: Vec<Pid>,
+
+    /// Количество открытых концов канала для записи.
+    writers: usize,
+}
+
+impl Pipe {
+    /// Усыпляет процесс `pid` до следующего изменения канала.
+    /// Возвращает ошибку [`Error::NoData`], которую нужно вернуть процессу.
+    fn wait(
+        &mut self,
+        pid: Pid,
+    ) -> Error {
+        if !self.waiters.contains(&pid) {
+            self.waiters.push(pid);
+        }
+
+        Scheduler::sleep(pid, DateTime::<Utc>::MAX_UTC);
+
+        NoData
+    }
+
+    /// Будит все процессы, которые ждут изменения канала.
+    fn wake_up_waiters(&mut self) {
+        for pid in self.waiters.drain(..) {
+            Scheduler::wake(pid);
+        }
+    }
+}
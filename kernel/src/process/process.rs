@@ -1,3 +1,4 @@
+use alloc::vec::Vec;
 use core::{
     alloc::Layout,
     fmt,
@@ -10,6 +11,7 @@ use ku::{
         self,
         ReadBuffer,
     },
+    memory::PageFaultInfo,
     process::{
         Info,
         MiniContext,
@@ -21,6 +23,7 @@ use ku::{
         Spinlock,
         SpinlockGuard,
     },
+    time::TscDuration,
 };
 
 use crate::{
@@ -30,6 +33,8 @@ use crate::{
         Error::{
             InvalidArgument,
             NoPage,
+            Overflow,
+            PermissionDenied,
         },
         Result,
     },
@@ -45,12 +50,17 @@ use crate::{
         BASE_ADDRESS_SPACE,
         Block,
         FrameGuard,
+        Page,
         Stack,
         Translate,
         USER_R,
         USER_RW,
+        UserAccess,
         Virt,
-        mmu::PageTableFlags,
+        mmu::{
+            self,
+            PageTableFlags,
+        },
     },
     smp::Cpu,
     trap::{
@@ -62,8 +72,24 @@ use crate::{
 
 use super::{
     Pid,
+    Scheduler,
     Table,
+    demand_paging,
+    pipe::PipeEnd,
     registers::Registers,
+    shared_memory::SharedRegion,
+    state_audit::{
+        StateAudit,
+        StateTransition,
+    },
+    syscall_trace::SyscallTrace,
+};
+
+// Used in docs.
+#[allow(unused)]
+use {
+    crate::error::Error,
+    ku::process::ExitCode,
 };
 
 /// Описывает пользовательский процесс.
@@ -72,25 +98,70 @@ pub struct Process {
     /// Виртуальное адресное пространство процесса.
     address_space: Spinlock<AddressSpace>,
 
+    /// Идентификатор группы процессов, в которую входит данный процесс.
+    /// Совпадает с идентификатором процесса, создавшего группу, --- её лидера.
+    group: Pid,
+
+    /// [ELF--файл](https://en.wikipedia.org/wiki/Executable_and_Linkable_Format) процесса,
+    /// если страницы его сегментов загружаются по требованию.
+    /// См. [`Process::demand_page()`].
+    elf_file: Option<&'static [u8]>,
+
+    /// Блок памяти с ELF--файлом, из которого загружен процесс.
+    /// Позволяет находить процессы, загруженные из того же файла,
+    /// чтобы делать общими их неизменяемые страницы.
+    elf_image: Option<Block<Virt>>,
+
     /// Блок памяти, через который ядро предоставляет процессу информацию о нём.
     /// В этом блоке находится структура типа [`ProcessInfo`].
     info: Block<Virt>,
 
+    /// Процесс нужно завершить при его следующем входе в ядро или перепланировании,
+    /// см. [`Table::kill_group()`].
+    killed: bool,
+
     /// Буфер, в который код пользователя записывает свои сообщения журнала.
     log: ReadBuffer,
 
+    /// Количество фреймов памяти, отведённых под буфер журналирования процесса.
+    log_frame_count: usize,
+
+    /// Наибольшее время, которое процесс провёл в очереди планировщика
+    /// между постановкой в неё и получением процессора.
+    max_scheduling_latency: Option<TscDuration>,
+
     /// Идентификатор процесса--родителя, который создал данный процесс.
     parent: Option<Pid>,
 
     /// Идентификатор процесса.
     pid: Pid,
 
+    /// Открытые процессом концы каналов, индексированные их дескрипторами.
+    pipe_ends: Vec<Option<PipeEnd>>,
+
+    /// Приоритет процесса в планировщике [`Scheduler`].
+    priority: u8,
+
     /// Состояние регистров процесса.
     registers: Registers,
 
+    /// Участки памяти, которые процесс разрешил присоединить другим процессам.
+    shared_regions: Vec<SharedRegion>,
+
     /// Состояние процесса.
     state: State,
 
+    /// Журнал последних переходов процесса между состояниями.
+    state_audit: StateAudit,
+
+    /// Процесс приостановлен и не получает процессорного времени,
+    /// см. [`Table::suspend_group()`].
+    suspended: bool,
+
+    /// Запись системных вызовов процесса, если она включена
+    /// методом [`Process::trace_syscalls()`].
+    syscall_trace: Option<SyscallTrace>,
+
     /// Контекст пользователя, в который передаются исключения и прерывания,
     /// относящиеся к данному процессу.
     /// Например, Page Fault при некорректном доступе к памяти в коде пользователя.
@@ -98,13 +169,25 @@ pub struct Process {
 }
 
 impl Process {
-    /// Создаёт новый процесс.
+    /// Создаёт новый процесс с буфером журналирования из `log_frame_count` фреймов памяти.
+    ///
+    /// Возвращает ошибку [`Error::InvalidArgument`], если `log_frame_count` равно нулю или
+    /// превышает [`Process::MAX_LOG_FRAME_COUNT`].
     pub(super) fn new(
         mut address_space: AddressSpace,
         entry: Virt,
+        log_frame_count: usize,
     ) -> Result<Self> {
-        let (info, log, rsp) =
-            Process::init_address_space(&mut address_space, &BASE_ADDRESS_SPACE, Block::default())?;
+        if !(1 ..= Self::MAX_LOG_FRAME_COUNT).contains(&log_frame_count) {
+            return Err(InvalidArgument);
+        }
+
+        let (info, log, rsp) = Process::init_address_space(
+            &mut address_space,
+            &BASE_ADDRESS_SPACE,
+            Block::default(),
+            log_frame_count,
+        )?;
         let pid = Pid::Current;
         let registers = Registers::new(MiniContext::new(entry, rsp), info.start_address());
 
@@ -112,44 +195,119 @@ impl Process {
 
         Ok(Self {
             address_space: Spinlock::new(address_space),
+            elf_file: None,
+            elf_image: None,
+            group: Pid::Current,
             info,
+            killed: false,
             log,
+            log_frame_count,
+            max_scheduling_latency: None,
             parent: None,
             pid,
+            pipe_ends: Vec::new(),
+            priority: Self::DEFAULT_PRIORITY,
             registers,
+            shared_regions: Vec::new(),
             state: State::Runnable,
+            state_audit: StateAudit::default(),
+            suspended: false,
+            syscall_trace: None,
             trap_context: TrapContext::default(),
         })
     }
 
     /// Дублирует существующий процесс.
+    /// Копия входит в ту же группу процессов и имеет тот же приоритет, что и исходный процесс.
+    /// Открытые концы каналов копия наследует под теми же дескрипторами.
+    /// Разрешения на присоединение разделяемой памяти не наследуются.
     pub(super) fn duplicate(
         &mut self,
         rax: usize,
         rdi: usize,
     ) -> Result<Self> {
+        let address_space = self.address_space.lock().duplicate()?;
+        self.duplicate_with(address_space, rax, rdi)
+    }
+
+    /// Дублирует существующий процесс вместе с его памятью, как `fork()` в Unix.
+    /// В отличие от [`Process::duplicate()`], копия получает все страницы пользователя
+    /// исходного процесса в режиме копирования при записи, см. [`AddressSpace::duplicate_cow()`].
+    /// Запись в такие страницы обрабатывает [`Process::copy_on_write()`].
+    ///
+    /// Свои [`ProcessInfo`] и буфер журнала у копии отдельные.
+    /// Поэтому эти страницы исходного процесса в копию не попадают,
+    /// а в самом исходном процессе снова становятся доступными на запись ---
+    /// ядро пишет в них и не обрабатывает копирование при записи.
+    pub(super) fn fork(
+        &mut self,
+        rax: usize,
+        rdi: usize,
+    ) -> Result<Self> {
+        let mut address_space = self.address_space.get_mut().duplicate_cow()?;
+
+        for block in [self.info.enclosing(), self.log.block()] {
+            unsafe {
+                address_space.unmap_block(block)?;
+                self.address_space.get_mut().remap_block(block, USER_RW)?;
+            }
+            for page in block {
+                unsafe {
+                    mmu::flush(page);
+                }
+            }
+        }
+
+        self.duplicate_with(address_space, rax, rdi)
+    }
+
+    /// Создаёт копию процесса с адресным пространством `address_space`,
+    /// полученным из адресного пространства исходного процесса.
+    /// См. [`Process::duplicate()`] и [`Process::fork()`].
+    fn duplicate_with(
+        &mut self,
+        mut address_space: AddressSpace,
+        rax: usize,
+        rdi: usize,
+    ) -> Result<Self> {
+        let user_access = UserAccess::new();
         let stack = if let Ok(info) = unsafe { self.info() } {
             info.stack()
         } else {
             Block::default()
         };
+        drop(user_access);
 
-        let mut address_space = self.address_space.lock().duplicate()?;
-
-        let (info, log, _) =
-            Self::init_address_space(&mut address_space, &self.address_space, stack)?;
+        let (info, log, _) = Self::init_address_space(
+            &mut address_space,
+            &self.address_space,
+            stack,
+            self.log_frame_count,
+        )?;
 
         address_space.duplicate_allocator_state(&self.address_space.lock())?;
         address_space.dump();
 
         Ok(Self {
             address_space: Spinlock::new(address_space),
+            elf_file: self.elf_file,
+            elf_image: self.elf_image,
+            group: self.group,
             info,
+            killed: false,
             log,
+            log_frame_count: self.log_frame_count,
+            max_scheduling_latency: None,
             parent: Some(self.pid),
             pid: Pid::Current,
+            pipe_ends: self.pipe_ends.clone(),
+            priority: self.priority,
             registers: self.registers.duplicate(rax, rdi, info.start_address().into_usize()),
+            shared_regions: Vec::new(),
             state: State::Exofork,
+            state_audit: StateAudit::default(),
+            suspended: false,
+            syscall_trace: None,
             trap_context: TrapContext::default(),
         })
     }
@@ -185,6 +343,7 @@ impl Process {
 
     /// Возвращает ссылку на структуру [`ProcessInfo`],
     /// через которую ядро предоставляет процессу информацию о нём.
+    /// Обращаться к ней можно только внутри окна [`UserAccess`].
     unsafe fn info(&mut self) -> Result<&mut ProcessInfo> {
         let flags = USER_RW;
         let info = self
@@ -200,6 +359,7 @@ impl Process {
     }
 
     /// Возвращает буфер, в который код пользователя записывает свои сообщения журнала.
+    /// Читать его можно только внутри окна [`UserAccess`].
     fn log(&mut self) -> Result<&mut ReadBuffer> {
         let flags = USER_RW;
         self.address_space
@@ -208,6 +368,95 @@ impl Process {
         Ok(&mut self.log)
     }
 
+    /// Количество фреймов памяти, отведённых под буфер журналирования процесса.
+    pub fn log_frame_count(&self) -> usize {
+        self.log_frame_count
+    }
+
+    /// Наибольшее время, которое процесс провёл в очереди планировщика
+    /// между постановкой в неё и получением процессора.
+    /// Равно [`None`], если планировщик ещё ни разу не запускал процесс.
+    pub fn max_scheduling_latency(&self) -> Option<TscDuration> {
+        self.max_scheduling_latency
+    }
+
+    /// Учитывает очередное время `latency`, которое процесс провёл в очереди планировщика.
+    pub(super) fn record_scheduling_latency(
+        &mut self,
+        latency: TscDuration,
+    ) {
+        self.max_scheduling_latency =
+            Some(self.max_scheduling_latency.map_or(latency, |max| max.max(latency)));
+    }
+
+    /// Возвращает идентификатор группы процессов, в которую входит данный процесс.
+    pub fn group(&self) -> Pid {
+        self.group
+    }
+
+    /// Переводит процесс в группу процессов `group`.
+    pub(super) fn set_group(
+        &mut self,
+        group: Pid,
+    ) {
+        self.group = group;
+    }
+
+    /// Возвращает `true`, если процесс приостановлен и не получает процессорного времени.
+    pub fn is_suspended(&self) -> bool {
+        self.suspended
+    }
+
+    /// Приостанавливает процесс или возобновляет его работу.
+    pub(super) fn set_suspended(
+        &mut self,
+        suspended: bool,
+    ) {
+        self.suspended = suspended;
+    }
+
+    /// Возвращает `true`, если процесс помечен для завершения методом [`Process::kill()`].
+    pub fn is_killed(&self) -> bool {
+        self.killed
+    }
+
+    /// Помечает процесс для завершения.
+    /// Используется для процессов, которые исполняются на других процессорах
+    /// и поэтому не могут быть удалены сразу.
+    /// Помеченный процесс завершается с кодом [`ExitCode::Killed`]
+    /// при следующем входе в ядро --- исключении, прерывании или системном вызове ---
+    /// либо когда планировщик выберет его для исполнения.
+    pub(super) fn kill(&mut self) {
+        self.killed = true;
+    }
+
+    /// Возвращает приоритет процесса в планировщике [`Scheduler`].
+    pub fn priority(&self) -> u8 {
+        self.priority
+    }
+
+    /// Устанавливает приоритет процесса в планировщике [`Scheduler`] равным `priority`.
+    /// Чем больше приоритет, тем раньше процесс исполняется.
+    ///
+    /// Возвращает ошибку [`Error::InvalidArgument`], если `priority` превышает
+    /// [`Process::MAX_PRIORITY`].
+    pub fn set_priority(
+        &mut self,
+        priority: u8,
+    ) -> Result<()> {
+        if priority > Self::MAX_PRIORITY {
+            return Err(InvalidArgument);
+        }
+
+        self.priority = priority;
+
+        if self.pid != Pid::Current {
+            Scheduler::set_priority(self.pid, priority);
+        }
+
+        Ok(())
+    }
+
     /// Возвращает идентификатор процесса--родителя, который создал данный процесс.
     pub fn parent(&self) -> Option<Pid> {
         self.parent
@@ -224,12 +473,17 @@ impl Process {
     }
 
     /// Устанавливает идентификатор процесса.
+    /// Если процесс ещё не входит ни в одну группу процессов,
+    /// создаёт для него новую группу, лидером которой он является.
     pub(super) fn set_pid(
         &mut self,
         pid: Pid,
     ) {
         if let Pid::Id { .. } = pid {
             self.pid = pid;
+            if self.group == Pid::Current {
+                self.group = pid;
+            }
             self.address_space.get_mut().set_pid(pid);
 
             test_scaffolding::pid_callback(self);
@@ -248,9 +502,80 @@ impl Process {
         &mut self,
         state: State,
     ) {
+        self.set_state_with_reason(state, "unspecified")
+    }
+
+    /// Устанавливает состояние процесса `state`.
+    /// Записывает переход в журнал [`Process::state_transitions()`] с причиной `reason`.
+    pub(super) fn set_state_with_reason(
+        &mut self,
+        state: State,
+        reason: &'static str,
+    ) {
+        self.state_audit.record(self.pid, self.state, state, reason);
         self.state = state
     }
 
+    /// Возвращает максимальную глубину пользовательского стека процесса в байтах,
+    /// которой он достигал с момента создания.
+    /// Позволяет понять, достаточен ли размер стека.
+    ///
+    /// Переключается в адресное пространство процесса.
+    ///
+    /// Возвращает ошибку, если процесс испортил информацию о своём стеке в [`ProcessInfo`].
+    pub fn stack_high_water_mark(&mut self) -> Result<usize> {
+        self.address_space.get_mut().switch_to();
+
+        let _user_access = UserAccess::new();
+        let stack = unsafe { self.info()? }.stack();
+        let stack = unsafe { stack.try_into_ref::<Stack>()? };
+        self.address_space.get_mut().check_permission::<u8>(stack.zones().1, USER_R)?;
+
+        Ok(stack.high_water_mark())
+    }
+
+    /// Возвращает последние [`StateAudit::CAPACITY`] переходов процесса между состояниями
+    /// в хронологическом порядке.
+    /// Переходы записываются только при включённой опции `state-audit`.
+    pub fn state_transitions(&self) -> impl Iterator<Item = &StateTransition> {
+        self.state_audit.iter()
+    }
+
+    /// Включает запись системных вызовов процесса и возвращает её.
+    /// Если запись уже включена, возвращает ту же самую запись.
+    /// Процессы, созданные этим процессом, не наследуют запись.
+    pub fn trace_syscalls(&mut self) -> SyscallTrace {
+        self.syscall_trace.get_or_insert_default().clone()
+    }
+
+    /// Возвращает блок памяти с ELF--файлом, из которого загружен процесс.
+    pub(super) fn elf_image(&self) -> Option<Block<Virt>> {
+        self.elf_image
+    }
+
+    /// Запоминает ELF--файл `elf_file`, из которого загружен процесс.
+    pub(super) fn set_elf_image(
+        &mut self,
+        elf_file: &[u8],
+    ) {
+        self.elf_image = Some(Block::from_slice(elf_file));
+    }
+
+    /// Включает загрузку страниц сегментов ELF--файла `elf_file` по требованию.
+    /// Адресное пространство процесса должно быть подготовлено к этому заранее
+    /// функцией `demand_paging::prepare()`.
+    pub(super) fn set_demand_paging(
+        &mut self,
+        elf_file: &'static [u8],
+    ) {
+        self.elf_file = Some(elf_file);
+    }
+
+    /// Запись системных вызовов процесса, если она включена.
+    pub(super) fn syscall_trace(&self) -> Option<&SyscallTrace> {
+        self.syscall_trace.as_ref()
+    }
+
     /// Сохраняет результат системного вызова `result` в регистры `rax` и `rdi`
     /// в соответствии с Nikka Syscall ABI.
     pub(super) fn set_syscall_result(
@@ -266,6 +591,86 @@ impl Process {
         self.registers.set_rdi(value);
     }
 
+    /// Открывает в процессе конец канала `pipe_end` и возвращает его дескриптор.
+    ///
+    /// Возвращает ошибку [`Error::Overflow`], если у процесса уже открыто
+    /// [`Process::MAX_PIPE_END_COUNT`] концов каналов.
+    pub(super) fn open_pipe_end(
+        &mut self,
+        pipe_end: PipeEnd,
+    ) -> Result<usize> {
+        if let Some(handle) = self.pipe_ends.iter().position(Option::is_none) {
+            self.pipe_ends[handle] = Some(pipe_end);
+            Ok(handle)
+        } else if self.pipe_ends.len() < Self::MAX_PIPE_END_COUNT {
+            self.pipe_ends.push(Some(pipe_end));
+            Ok(self.pipe_ends.len() - 1)
+        } else {
+            Err(Overflow)
+        }
+    }
+
+    /// Возвращает открытый процессом конец канала с дескриптором `handle`.
+    ///
+    /// Возвращает ошибку [`Error::InvalidArgument`], если такой конец канала не открыт.
+    pub(super) fn pipe_end(
+        &self,
+        handle: usize,
+    ) -> Result<&PipeEnd> {
+        self.pipe_ends.get(handle).and_then(Option::as_ref).ok_or(InvalidArgument)
+    }
+
+    /// Закрывает открытый процессом конец канала с дескриптором `handle`.
+    ///
+    /// Возвращает ошибку [`Error::InvalidArgument`], если такой конец канала не открыт.
+    pub(super) fn close_pipe_end(
+        &mut self,
+        handle: usize,
+    ) -> Result<()> {
+        let pipe_end = self.pipe_ends.get_mut(handle).and_then(Option::take);
+        pipe_end.map(drop).ok_or(InvalidArgument)
+    }
+
+    /// Разрешает присоединить участок памяти `region` процессу, которому он предназначен.
+    ///
+    /// Возвращает ошибки:
+    ///   - [`Error::InvalidArgument`], если этому процессу уже предоставлен участок с тем же именем.
+    ///   - [`Error::Overflow`], если процесс уже предоставил
+    ///     [`Process::MAX_SHARED_REGION_COUNT`] участков.
+    pub(super) fn share_region(
+        &mut self,
+        region: SharedRegion,
+    ) -> Result<()> {
+        let is_taken = |x: &SharedRegion| x.is_granted(region.name(), region.grantee());
+        if self.shared_regions.iter().any(is_taken) {
+            return Err(InvalidArgument);
+        }
+
+        if self.shared_regions.len() >= Self::MAX_SHARED_REGION_COUNT {
+            return Err(Overflow);
+        }
+
+        self.shared_regions.push(region);
+
+        Ok(())
+    }
+
+    /// Возвращает участок памяти, который процесс предоставил процессу `grantee`
+    /// под именем `name`.
+    ///
+    /// Возвращает ошибку [`Error::PermissionDenied`], если такого участка нет.
+    pub(super) fn shared_region(
+        &self,
+        name: usize,
+        grantee: Pid,
+    ) -> Result<SharedRegion> {
+        self.shared_regions
+            .iter()
+            .find(|region| region.is_granted(name, grantee))
+            .copied()
+            .ok_or(PermissionDenied)
+    }
+
     /// Возвращает контекст пользователя, в который передаются исключения и прерывания,
     /// относящиеся к данному процессу.
     /// Например, Page Fault при некорректном доступе к памяти в коде пользователя.
@@ -292,14 +697,16 @@ impl Process {
         process.address_space.get_mut().switch_to();
 
         if let Ok(info) = unsafe { process.info() } {
+            let _user_access = UserAccess::new();
             info.set_pid(pid);
         }
 
         Cpu::set_current_process(Some(pid));
+        Cpu::start_time_slice();
 
         let registers = &mut process.registers as *mut Registers;
 
-        process.state = State::Running;
+        process.set_state_with_reason(State::Running, "entering the user mode");
 
         debug!(%pid, registers = %process.registers, "entering the user mode");
 
@@ -316,9 +723,14 @@ impl Process {
         if let Some(user_context) = Cpu::take_user_context() {
             let mut process = Table::get(pid).expect("failed to find the current process in the process table");
             process.registers.set_mode_context(user_context);
-            process.state = State::Runnable;
+            process.set_state_with_reason(State::Runnable, "preempted");
             
-            info!(%pid, user_context = %user_context, "the process was preempted");
+            info!(
+                %pid,
+                user_context = %user_context,
+                ticks = Cpu::time_slice_ticks(),
+                "the process was preempted",
+            );
             
             true
         } else {
@@ -327,13 +739,15 @@ impl Process {
     }
 
     /// Выполняет переключение текущего контекста исполнения `context`
-    /// на контекст ядра в случае, если текущий контекст исполняется в режиме пользователя.
+    /// на контекст ядра в случае, если текущий контекст исполняется в режиме пользователя
+    /// и его квант времени [`Scheduler::time_slice()`] исчерпан.
     /// Текущий контекст пользователя сохраняет в структуре `Cpu` текущего процессора.
     ///
     /// Этот метод вызывается из функции обработки прерывания от таймера `trap::timer()`.
+    /// Поэтому квант времени фактически округляется вверх до целого числа тиков таймера.
     #[inline(always)]
     pub(crate) fn preempt(context: &mut trap::TrapContext) {
-        if context.is_user_mode() {
+        if context.is_user_mode() && Cpu::count_time_slice_tick(Scheduler::time_slice()) {
             Cpu::set_user_context(context.get());
             context.set(Cpu::kernel_context());
         }
@@ -353,6 +767,71 @@ impl Process {
         }
     }
 
+    /// Обрабатывает Page Fault `info` при обращении к ещё не загруженной странице
+    /// ELF--файла процесса, если его страницы загружаются по требованию.
+    ///
+    /// Возвращает `true`, если страница загружена и процесс может повторить обращение.
+    pub(crate) fn demand_page(
+        &mut self,
+        info: Info,
+    ) -> bool {
+        let (Some(elf_file), Info::PageFault { address, code }) = (self.elf_file, info) else {
+            return false;
+        };
+
+        if code.contains(PageFaultInfo::PRESENT) {
+            return false;
+        }
+
+        let page = Page::containing(address);
+
+        match demand_paging::load_page(self.address_space.get_mut(), elf_file, page) {
+            Ok(is_loaded) => {
+                if is_loaded {
+                    trace!(pid = %self.pid, %page, "loaded an ELF page on demand");
+                }
+                is_loaded
+            },
+            Err(error) => {
+                warn!(pid = %self.pid, %page, ?error, "failed to load an ELF page on demand");
+                false
+            },
+        }
+    }
+
+    /// Обрабатывает Page Fault `info` при записи в страницу, которая разделяется
+    /// с другим процессом в режиме копирования при записи, см. [`Process::fork()`].
+    /// Даёт процессу собственную копию страницы методом [`AddressSpace::copy_on_write()`].
+    ///
+    /// Возвращает `true`, если страница стала доступна на запись и процесс может повторить обращение.
+    pub(crate) fn copy_on_write(
+        &mut self,
+        info: Info,
+    ) -> bool {
+        let Info::PageFault { address, code } = info else {
+            return false;
+        };
+
+        if !code.contains(PageFaultInfo::PRESENT | PageFaultInfo::WRITE) {
+            return false;
+        }
+
+        let page = Page::containing(address);
+
+        match unsafe { self.address_space.get_mut().copy_on_write(page) } {
+            Ok(is_copied) => {
+                if is_copied {
+                    trace!(pid = %self.pid, %page, "resolved a copy-on-write page fault");
+                }
+                is_copied
+            },
+            Err(error) => {
+                warn!(pid = %self.pid, %page, ?error, "failed to copy a page on write");
+                false
+            },
+        }
+    }
+
     // ANCHOR: trap
     /// Подготавливает контекст `context` к вызову
     /// пользовательского обработчика исключения или прерывания номер `trap`, если он установлен.
@@ -371,6 +850,9 @@ impl Process {
 
         let number = usize::from(trap);
 
+        // The trap info and the user context are written onto the user trap stack.
+        let _user_access = UserAccess::new();
+
         // TODO: your code here.
         false // TODO: remove before flight.
     }
@@ -380,6 +862,7 @@ impl Process {
         let pid = self.pid;
 
         if let Ok(log) = self.log() {
+            let _user_access = UserAccess::new();
             log::user_events(pid, log);
             trace!(read_stats = ?*log.read_stats());
         } else {
@@ -389,7 +872,7 @@ impl Process {
 
     /// Инициализирует адресное пространство `address_space` для нового процесса.
     ///
-    /// Размещает в нём пользовательский стек, буфер для журналирования,
+    /// Размещает в нём пользовательский стек, буфер для журналирования из `log_frame_count` фреймов,
     /// страницы с общей информацией о системе и с информацией о самом процессе ---
     /// [`SystemInfo`] и [`ProcessInfo`].
     ///
@@ -404,13 +887,16 @@ impl Process {
         address_space: &mut AddressSpace,
         original_address_space: &Spinlock<AddressSpace>,
         mut stack: Block<Virt>,
+        log_frame_count: usize,
     ) -> Result<(Block<Virt>, ReadBuffer, Virt)> {
         address_space.switch_to();
 
+        let user_access = UserAccess::new();
+
         let flags = USER_RW;
 
         let (read_buffer, write_buffer) =
-            pipe::make(Self::LOG_FRAME_COUNT, &mut address_space.allocator(flags))?;
+            pipe::make(log_frame_count, &mut address_space.allocator(flags))?;
         let recursive_mapping = address_space.make_recursive_mapping()?;
         let system_info = Self::map_system_info(address_space)?;
 
@@ -419,10 +905,15 @@ impl Process {
         })?;
 
         if stack == Block::default() {
-            stack = Block::from_mut(Stack::new(address_space, flags)?);
+            let new_stack = Stack::new(address_space, flags)?;
+            new_stack.fill_unused();
+            stack = Block::from_mut(new_stack);
         }
+        Self::check_non_executable(address_space, stack)?;
         process_info.set_stack(stack);
 
+        drop(user_access);
+
         original_address_space.lock().switch_to();
 
         Ok((
@@ -432,6 +923,30 @@ impl Process {
         ))
     }
 
+    /// Проверяет, что ни одна отображённая страница стека `stack`
+    /// в адресном пространстве `address_space` не доступна на исполнение.
+    /// Иначе код, записанный в стек, можно было бы исполнить.
+    ///
+    /// Блок `stack` может прийти из доступной пользователю [`ProcessInfo`],
+    /// поэтому нарушение не считается ошибкой ядра и
+    /// приводит к [`Error::PermissionDenied`].
+    fn check_non_executable(
+        address_space: &mut AddressSpace,
+        stack: Block<Virt>,
+    ) -> Result<()> {
+        for page in stack.enclosing() {
+            if let Ok(pte) = address_space.translate(page.address()) &&
+                pte.is_present() &&
+                pte.flags().is_executable()
+            {
+                warn!(%page, "the user stack page is executable");
+                return Err(PermissionDenied);
+            }
+        }
+
+        Ok(())
+    }
+
     /// Создаёт для процесса отображение в его адресное пространство `address_space`
     /// страницы с общей информацией о системе.
     /// См. [`SystemInfo`].
@@ -455,8 +970,25 @@ impl Process {
         system_info_page.address().try_into_ptr()
     }
 
-    /// Количество фреймов памяти, которые отводятся под буфер журналирования процесса.
-    const LOG_FRAME_COUNT: usize = 4;
+    /// Количество фреймов памяти, которые по умолчанию отводятся под буфер журналирования процесса.
+    pub const DEFAULT_LOG_FRAME_COUNT: usize = 4;
+
+    /// Максимальное количество фреймов памяти, которые можно отвести
+    /// под буфер журналирования процесса.
+    pub const MAX_LOG_FRAME_COUNT: usize = 64;
+
+    /// Приоритет, который процесс получает при создании.
+    pub const DEFAULT_PRIORITY: u8 = 16;
+
+    /// Максимальное количество одновременно открытых процессом концов каналов.
+    pub const MAX_PIPE_END_COUNT: usize = 64;
+
+    /// Максимальный приоритет процесса.
+    pub const MAX_PRIORITY: u8 = 31;
+
+    /// Максимальное количество участков памяти,
+    /// которые процесс может одновременно предоставить другим процессам.
+    pub const MAX_SHARED_REGION_COUNT: usize = 64;
 }
 
 impl fmt::Display for Process {
@@ -547,9 +1079,21 @@ pub(super) mod test_scaffolding {
 
     use static_assertions::const_assert_eq;
 
+    use ku::ipc::pipe::WriteBuffer;
+
+    use crate::{
+        error::Result,
+        memory::{
+            Block,
+            UserAccess,
+            Virt,
+        },
+    };
+
     use super::{
         super::registers::test_scaffolding,
         Pid,
+        PipeEnd,
         Process,
         State,
     };
@@ -558,10 +1102,60 @@ pub(super) mod test_scaffolding {
         test_scaffolding::disable_interrupts(&mut process.registers);
     }
 
+    pub fn duplicate_process(process: &mut Process) -> Result<Process> {
+        process.duplicate(0, 0)
+    }
+
+    pub fn fork_process(process: &mut Process) -> Result<Process> {
+        process.fork(0, 0)
+    }
+
+    pub fn log_write_buffer(process: &mut Process) -> Result<&mut WriteBuffer> {
+        Ok(unsafe { process.info()? }.log())
+    }
+
+    pub fn pipe(process: &mut Process) -> Result<(usize, usize)> {
+        let (reader, writer) = PipeEnd::new();
+        let reader = process.open_pipe_end(reader)?;
+        let writer = process.open_pipe_end(writer)?;
+
+        Ok((reader, writer))
+    }
+
+    pub fn pipe_close(
+        process: &mut Process,
+        handle: usize,
+    ) -> Result<()> {
+        process.close_pipe_end(handle)
+    }
+
+    pub fn pipe_read(
+        process: &Process,
+        handle: usize,
+        buffer: &mut [u8],
+    ) -> Result<usize> {
+        process.pipe_end(handle)?.read(process.pid(), buffer)
+    }
+
+    pub fn pipe_write(
+        process: &Process,
+        handle: usize,
+        buffer: &[u8],
+    ) -> Result<usize> {
+        process.pipe_end(handle)?.write(process.pid(), buffer)
+    }
+
     pub fn registers(process: &Process) -> [usize; 15] {
         test_scaffolding::registers(&process.registers)
     }
 
+    pub fn set_parent(
+        process: &mut Process,
+        parent: Pid,
+    ) {
+        process.parent = Some(parent);
+    }
+
     pub fn set_pid(
         process: &mut Process,
         pid: Pid,
@@ -584,9 +1178,23 @@ pub(super) mod test_scaffolding {
         }
     }
 
+    pub fn stack(process: &mut Process) -> Result<Block<Virt>> {
+        let _user_access = UserAccess::new();
+        Ok(unsafe { process.info()? }.stack())
+    }
+
+    pub fn set_process_state(
+        process: &mut Process,
+        state: State,
+    ) {
+        process.set_state(state);
+    }
+
     pub fn state(process: &Process) -> State {
         process.state()
     }
 
+    pub const PIPE_CAPACITY: usize = PipeEnd::CAPACITY;
+
     static PID_CALLBACK: AtomicPtr<()> = AtomicPtr::new(ptr::null_mut());
 }
@@ -374,6 +374,19 @@ impl ModeContext {
         self.rsp = context.rsp();
     }
 
+    /// Возвращает регистр флагов.
+    pub fn rflags(&self) -> RFlags {
+        self.rflags
+    }
+
+    /// Устанавливает регистр флагов.
+    pub(crate) fn set_rflags(
+        &mut self,
+        rflags: RFlags,
+    ) {
+        self.rflags = rflags;
+    }
+
     /// Возвращает `true`, если контекст имеет привилегии пользователя.
     pub fn is_user_mode(&self) -> bool {
         assert_eq!(
@@ -1,38 +1,99 @@
-use alloc::collections::VecDeque;
+use alloc::{
+    collections::VecDeque,
+    vec,
+    vec::Vec,
+};
+use core::{
+    cmp,
+    sync::atomic::{
+        AtomicU64,
+        Ordering,
+    },
+};
 
+use chrono::{
+    DateTime,
+    Duration,
+    Utc,
+};
 use lazy_static::lazy_static;
-use x86_64::instructions;
+use x86_64::instructions::interrupts;
 
-use ku::sync::Spinlock;
+use ku::{
+    process::ExitCode,
+    sync::IrqSpinlock,
+    time::{
+        self,
+        Tsc,
+    },
+};
 
 use crate::{
-    log::info,
-    smp::LocalApic,
+    log::{
+        info,
+        warn,
+    },
+    smp::{
+        Cpu,
+        LocalApic,
+    },
 };
 
 use super::{
     Pid,
     process::Process,
     table::Table,
+    timer_wheel::TimerWheel,
 };
 
-/// Планировщик процессов.
-/// Реализует простейшее
-/// [циклическое исполнение процессов](https://en.wikipedia.org/wiki/Round-robin_scheduling).
+/// Планировщик процессов с
+/// [приоритетами](https://en.wikipedia.org/wiki/Scheduling_(computing)#Priority_scheduling).
+///
+/// Каждый процесс имеет приоритет от `0` до [`Process::MAX_PRIORITY`],
+/// см. [`Process::set_priority()`].
+/// Из очереди готовых процессов первым исполняется процесс с наибольшим приоритетом.
+/// Среди процессов с равным приоритетом реализуется
+/// [циклическое исполнение](https://en.wikipedia.org/wiki/Round-robin_scheduling) ---
+/// они обслуживаются в порядке
+/// [FIFO](https://en.wikipedia.org/wiki/FIFO_(computing_and_electronics)).
+/// Каждый процесс может стоять в очереди не более одного раза.
+/// Поэтому и вытесненный, и добровольно уступивший процессор, и разбуженный процесс
+/// попадает в конец очереди.
+///
+/// Чтобы низкоприоритетные процессы не
+/// [голодали](https://en.wikipedia.org/wiki/Starvation_(computer_science)),
+/// планировщик применяет [старение](https://en.wikipedia.org/wiki/Aging_(scheduling)):
+/// пока процесс стоит в очереди, его приоритет постепенно повышается
+/// с каждым разом, когда его обходят процессы с большим приоритетом.
+/// Наибольшую наблюдавшуюся задержку планирования каждого процесса
+/// можно узнать методом [`Process::max_scheduling_latency()`].
+///
+/// Заснувшие методом [`Scheduler::sleep()`] процессы хранятся не в очереди,
+/// а в колесе таймеров и попадают в очередь только с наступлением момента пробуждения,
+/// см. [`Scheduler::wake_up()`].
 pub struct Scheduler {
-    /// Очередь готовых к исполнению процессов.
-    queue: VecDeque<Pid>,
+    /// Приоритеты процессов, индексированные номером слота процесса в [`Table`].
+    priorities: Vec<u8>,
+
+    /// Очередь готовых к исполнению процессов с моментами их постановки в очередь и
+    /// количеством раз, когда их обошли процессы с большим приоритетом.
+    queue: VecDeque<(Pid, Tsc, usize)>,
+
+    /// Спящие процессы.
+    sleepers: TimerWheel,
 }
 
 impl Scheduler {
     /// Инициализирует глобальный планировщик процессов с очередью на `count` процессов.
     pub fn init(count: usize) {
         *SCHEDULER.lock() = Scheduler {
+            priorities: vec![Process::DEFAULT_PRIORITY; count],
             queue: VecDeque::with_capacity(count),
+            sleepers: TimerWheel::new(),
         }
     }
 
-    /// Выполняет один цикл работы --- берёт первый процесс из очереди и
+    /// Выполняет один цикл работы --- берёт из очереди процесс с наибольшим приоритетом и
     /// исполняет его пользовательский код.
     /// Если в процессе выполнения пользовательского кода
     /// процесс был снят с CPU принудительно,
@@ -41,14 +102,36 @@ impl Scheduler {
     ///
     /// Должен корректно обрабатывать ситуацию, когда `pid` есть в очереди планирования,
     /// но соответствующего процесса уже нет в [`Table`].
+    /// Приостановленный процесс убирает из очереди, не исполняя его,
+    /// см. [`Table::suspend_group()`].
+    ///
+    /// Предварительно будит процессы, время сна которых истекло, см. [`Scheduler::wake_up()`].
     pub fn run_one() -> bool {
-        let pid = match Self::dequeue() {
-            Some(pid) => pid,
+        Self::wake_up();
+
+        let (pid, enqueued) = match Self::dequeue() {
+            Some(entry) => entry,
             None => return false,
         };
 
-        if let Ok(process) = Table::get(pid) {
+        if let Ok(mut process) = Table::get(pid) {
+            if process.is_killed() {
+                drop(process);
+                if let Err(error) = Table::exit(pid, ExitCode::Killed.into()) {
+                    warn!(%pid, ?error, "failed to exit the killed process");
+                }
+                return true;
+            }
+
+            if process.is_suspended() {
+                return true;
+            }
+
+            process.record_scheduling_latency(enqueued.synchronized_elapsed());
+
+            let start = Tsc::now();
             let preempted = Process::enter_user_mode(process);
+            Cpu::record_busy(start);
 
             if preempted {
                 Self::enqueue(pid);
@@ -58,45 +141,250 @@ impl Scheduler {
         true
     }
 
-    /// В вечном цикле выполняет готовые процессы методом [`Scheduler::run_one()`].
-    /// Если в очереди на исполнение процессов не нашлось,
-    /// выключает процессор до прихода следующего прерывания,
-    /// самое долгое --- до следующего тика таймера.
+    /// В вечном цикле выполняет готовые процессы методом [`Scheduler::run_one_or_idle()`].
     pub(crate) fn run() -> ! {
         test_scaffolding::run_handler();
 
-        let cpu = LocalApic::id();
-
         loop {
-            if !Scheduler::run_one() {
-                info!(cpu, "nothing to do");
-                instructions::hlt();
-            }
+            Self::run_one_or_idle();
+        }
+    }
+
+    /// Выполняет один цикл работы методом [`Scheduler::run_one()`].
+    /// Если в очереди на исполнение процессов не нашлось,
+    /// переходит в цикл простоя [`Scheduler::idle()`].
+    /// Возвращает `true` если в очереди на исполнение нашёлся хотя бы один процесс.
+    fn run_one_or_idle() -> bool {
+        if Self::run_one() {
+            true
+        } else {
+            info!(cpu = LocalApic::id(), "nothing to do");
+            Self::idle();
+            false
         }
     }
 
-    /// Ставит процесс, заданный идентификатором `pid`, в очередь исполнения.
+    /// Цикл простоя --- планируется только когда очередь на исполнение пуста.
+    /// Включает прерывания и выключает процессор до прихода следующего прерывания,
+    /// самое долгое --- до следующего тика таймера.
+    /// Это экономит процессорное время хоста при запуске под QEMU
+    /// по сравнению с активным ожиданием.
+    ///
+    /// После пробуждения учитывает его в [`Cpu::idle_ticks()`],
+    /// а время простоя --- в загрузке процессора [`crate::smp::cpu_usage()`].
+    /// Сюда же стоит добавлять низкоприоритетную фоновую работу ядра.
+    fn idle() {
+        let start = Tsc::now();
+        interrupts::enable_and_hlt();
+        Cpu::record_idle(start);
+        Cpu::count_idle_tick();
+    }
+
+    /// Возвращает квант времени --- сколько процесс исполняется, прежде чем будет вытеснен
+    /// по прерыванию таймера.
+    pub fn time_slice() -> Duration {
+        let milliseconds = TIME_SLICE_MILLISECONDS.load(Ordering::Relaxed);
+        i64::try_from(milliseconds)
+            .ok()
+            .and_then(Duration::try_milliseconds)
+            .unwrap_or(Duration::MAX)
+    }
+
+    /// Устанавливает квант времени равным `milliseconds` миллисекундам.
+    ///
+    /// Процесс вытесняется на первом тике таймера после того, как исчерпал квант,
+    /// поэтому квант фактически округляется вверх до целого числа тиков.
+    /// Длинный квант уменьшает накладные расходы на переключение процессов,
+    /// а короткий --- улучшает отзывчивость.
+    /// При нулевом кванте процесс вытесняется на каждом тике таймера.
+    pub fn set_time_slice(milliseconds: u64) {
+        TIME_SLICE_MILLISECONDS.store(milliseconds, Ordering::Relaxed);
+        info!(milliseconds, "set the time slice");
+    }
+
+    /// Ставит процесс, заданный идентификатором `pid`, в конец очереди исполнения.
+    /// Если он уже стоит в очереди, ничего не делает,
+    /// чтобы процесс не получал больше процессорного времени, чем остальные.
+    /// Спящий процесс тоже не ставит в очередь раньше момента его пробуждения.
     pub fn enqueue(pid: Pid) {
-        SCHEDULER.lock().queue.push_back(pid);
+        let mut scheduler = SCHEDULER.lock();
+
+        if !scheduler.sleepers.contains(pid) {
+            scheduler.push_back(pid);
+        }
     }
 
-    /// Достаёт из очереди первый готовый к исполнению процесс.
-    fn dequeue() -> Option<Pid> {
-        let pid = SCHEDULER.lock().queue.pop_front();
-        info!("dequeue; pid = {pid:?}");
-        pid
+    /// Усыпляет процесс, заданный идентификатором `pid`, до момента `deadline`,
+    /// округлённого вверх до миллисекунд.
+    /// До этого момента процесс не исполняется,
+    /// даже если его попытаются поставить в очередь методом [`Scheduler::enqueue()`].
+    pub(super) fn sleep(
+        pid: Pid,
+        deadline: DateTime<Utc>,
+    ) {
+        let is_fractional = deadline.timestamp_subsec_nanos() % NANOSECONDS_PER_MILLISECOND != 0;
+        let deadline = deadline.timestamp_millis() + i64::from(is_fractional);
+
+        SCHEDULER.lock().sleepers.insert(pid, deadline);
+    }
+
+    /// Ставит в очередь исполнения спящие процессы, момент пробуждения которых уже наступил.
+    ///
+    /// Вызывается из обработчика прерывания
+    /// [часов реального времени](https://en.wikipedia.org/wiki/Real-time_clock),
+    /// а также перед каждым циклом работы планировщика в [`Scheduler::run_one()`].
+    /// Прерывания RTC приходят раз в секунду, а циклы работы планировщика, в том числе
+    /// выход из цикла простоя [`Scheduler::idle()`], происходят хотя бы раз за тик таймера.
+    /// Поэтому процессы просыпаются с точностью до тика таймера.
+    pub(crate) fn wake_up() {
+        let now = time::now().timestamp_millis();
+        let mut scheduler = SCHEDULER.lock();
+        let scheduler = &mut *scheduler;
+
+        scheduler.sleepers.advance(now, |pid| {
+            if !scheduler.queue.iter().any(|&(queued, ..)| queued == pid) {
+                scheduler.queue.push_back((pid, Tsc::synchronized(), 0));
+            }
+        });
+    }
+
+    /// Досрочно будит процесс, заданный идентификатором `pid`,
+    /// усыплённый методом [`Scheduler::sleep()`], и ставит его в конец очереди исполнения.
+    /// Если процесс не спит, ничего не делает.
+    pub(super) fn wake(pid: Pid) {
+        let mut scheduler = SCHEDULER.lock();
+
+        if scheduler.sleepers.remove(pid) {
+            scheduler.push_back(pid);
+        }
+    }
+
+    /// Убирает процесс, заданный идентификатором `pid`, из спящих.
+    /// Вызывается при удалении процесса из [`Table`],
+    /// чтобы колесо таймеров не хранило его до момента пробуждения.
+    pub(super) fn cancel_sleep(pid: Pid) {
+        SCHEDULER.lock().sleepers.remove(pid);
+    }
+
+    /// Переставляет процесс, заданный идентификатором `pid`, в начало очереди исполнения,
+    /// чтобы он был исполнен следующим.
+    /// Процессы с большим приоритетом по-прежнему исполняются раньше него,
+    /// а от старения стоявших в очереди процессов с тем же приоритетом
+    /// его защищает наибольший среди них счётчик ожидания.
+    /// Если процесса нет в очереди --- он не готов к исполнению или уже исполняется ---
+    /// ничего не делает и возвращает `false`.
+    ///
+    /// Очередь общая для всех процессоров.
+    /// Поэтому процесс обычно достаётся тому же процессору, который вызвал [`Scheduler::yield_to()`]
+    /// и сразу же возвращается в [`Scheduler::run_one()`],
+    /// но его может перехватить и другой процессор.
+    /// Момент постановки в очередь сохраняется,
+    /// так что задержка планирования процесса учитывается честно.
+    pub fn yield_to(pid: Pid) -> bool {
+        let mut scheduler = SCHEDULER.lock();
+
+        let Some(position) = scheduler.queue.iter().position(|&(queued, ..)| queued == pid) else {
+            return false;
+        };
+
+        let max_skipped = scheduler.queue.iter().map(|&(.., skipped)| skipped).max().unwrap_or(0);
+        if let Some((pid, enqueued, _)) = scheduler.queue.remove(position) {
+            scheduler.queue.push_front((pid, enqueued, max_skipped));
+        }
+
+        true
+    }
+
+    /// Запоминает приоритет `priority` процесса, заданного идентификатором `pid`.
+    /// Если процесс стоит в очереди, новый приоритет учитывается сразу.
+    pub(super) fn set_priority(
+        pid: Pid,
+        priority: u8,
+    ) {
+        if let Some(slot) = SCHEDULER.lock().priorities.get_mut(pid.slot()) {
+            *slot = priority;
+        }
+    }
+
+    /// Достаёт из очереди готовый к исполнению процесс с наибольшим с учётом старения
+    /// приоритетом вместе с моментом его постановки в очередь.
+    /// Из процессов с равным приоритетом выбирает стоящий ближе к началу очереди.
+    /// Всем обойдённым процессам увеличивает счётчик ожидания.
+    fn dequeue() -> Option<(Pid, Tsc)> {
+        let mut scheduler = SCHEDULER.lock();
+
+        let mut best = None;
+        for (position, &(pid, _, skipped)) in scheduler.queue.iter().enumerate() {
+            let priority = scheduler.effective_priority(pid, skipped);
+            if best.is_none_or(|(_, best_priority)| priority > best_priority) {
+                best = Some((position, priority));
+            }
+        }
+
+        let entry = best.and_then(|(position, _)| scheduler.queue.remove(position));
+        for (_, _, skipped) in scheduler.queue.iter_mut() {
+            *skipped += 1;
+        }
+        drop(scheduler);
+
+        info!("dequeue; pid = {:?}", entry.map(|(pid, ..)| pid));
+
+        entry.map(|(pid, enqueued, _)| (pid, enqueued))
+    }
+
+    /// Возвращает приоритет процесса `pid`, который обошли в очереди `skipped` раз,
+    /// повышенный за счёт старения.
+    fn effective_priority(
+        &self,
+        pid: Pid,
+        skipped: usize,
+    ) -> u8 {
+        let priority =
+            self.priorities.get(pid.slot()).copied().unwrap_or(Process::DEFAULT_PRIORITY);
+        let aging = u8::try_from(skipped / AGING_STEP).unwrap_or(u8::MAX);
+
+        cmp::min(priority.saturating_add(aging), Process::MAX_PRIORITY)
+    }
+
+    /// Ставит процесс `pid` в конец очереди исполнения, если его там ещё нет.
+    fn push_back(
+        &mut self,
+        pid: Pid,
+    ) {
+        if !self.contains(pid) {
+            self.queue.push_back((pid, Tsc::synchronized(), 0));
+        }
+    }
+
+    /// Возвращает `true`, если процесс `pid` стоит в очереди исполнения.
+    fn contains(
+        &self,
+        pid: Pid,
+    ) -> bool {
+        self.queue.iter().any(|&(queued, ..)| queued == pid)
     }
 }
 
 lazy_static! {
-    /// Планировщик процессов.
-    /// Реализует простейшее
-    /// [циклическое исполнение процессов](https://en.wikipedia.org/wiki/Round-robin_scheduling).
-    static ref SCHEDULER: Spinlock<Scheduler> = Spinlock::new(Scheduler {
+    /// Планировщик процессов с приоритетами.
+    /// Захватывается и в обработчике прерывания RTC, поэтому защищён [`IrqSpinlock`].
+    static ref SCHEDULER: IrqSpinlock<Scheduler> = IrqSpinlock::new(Scheduler {
+        priorities: Vec::new(),
         queue: VecDeque::new(),
+        sleepers: TimerWheel::new(),
     });
 }
 
+/// Квант времени в миллисекундах, см. [`Scheduler::time_slice()`].
+static TIME_SLICE_MILLISECONDS: AtomicU64 = AtomicU64::new(0);
+
+/// Сколько раз процесс должны обойти в очереди планировщика,
+/// чтобы его приоритет повысился на единицу.
+const AGING_STEP: usize = 4;
+
+/// Количество наносекунд в одной миллисекунде.
+const NANOSECONDS_PER_MILLISECOND: u32 = 1_000_000;
+
 #[doc(hidden)]
 pub mod test_scaffolding {
     use core::{
@@ -114,6 +402,7 @@ pub mod test_scaffolding {
     use super::{
         Pid,
         SCHEDULER,
+        Scheduler,
     };
 
     pub fn scheduler_enable() {
@@ -121,7 +410,23 @@ pub mod test_scaffolding {
     }
 
     pub fn scheduler_has_pid(pid: Pid) -> bool {
-        SCHEDULER.lock().queue.contains(&pid)
+        SCHEDULER.lock().contains(pid)
+    }
+
+    pub fn scheduler_is_sleeping(pid: Pid) -> bool {
+        SCHEDULER.lock().sleepers.contains(pid)
+    }
+
+    pub fn scheduler_front() -> Option<Pid> {
+        SCHEDULER.lock().queue.front().map(|&(pid, ..)| pid)
+    }
+
+    pub fn scheduler_dequeue() -> Option<Pid> {
+        Scheduler::dequeue().map(|(pid, _)| pid)
+    }
+
+    pub fn scheduler_run_one_or_idle() -> bool {
+        Scheduler::run_one_or_idle()
     }
 
     pub fn set_handler(handler: fn()) {
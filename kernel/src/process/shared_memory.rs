@@ -0,0 +1,183 @@
+use alloc::vec::Vec;
+use core::alloc::Layout;
+
+use crate::{
+    error::{
+        Error::{
+            InvalidArgument,
+            NoPage,
+            Overflow,
+            PermissionDenied,
+        },
+        Result,
+    },
+    memory::{
+        self,
+        AddressSpace,
+        Block,
+        FrameGuard,
+        Page,
+        Translate,
+        USER_RW,
+        Virt,
+        mmu::PageTableFlags,
+    },
+};
+
+use super::Pid;
+
+// Used in docs.
+#[allow(unused)]
+use crate::error::Error;
+
+/// Именованный участок
+/// [разделяемой памяти](https://en.wikipedia.org/wiki/Shared_memory),
+/// который процесс разрешил отобразить к себе другому процессу.
+///
+/// Модель безопасности:
+///   - Процесс может предоставить только свою собственную память,
+///     отображённую в пользовательскую часть его адресного пространства на запись.
+///   - Разрешение выдаётся одному конкретному процессу `grantee` под именем `name`.
+///     Имена у каждого процесса свои, угадать чужое имя недостаточно.
+///   - Присоединить участок может только `grantee` и только по имени,
+///     под которым его предоставил процесс--владелец.
+///     Во всех остальных случаях, в том числе когда такого имени нет вовсе,
+///     возвращается одна и та же ошибка [`Error::PermissionDenied`].
+///     Так по ней нельзя узнать, какие имена есть у других процессов.
+///   - Разрешения хранятся в процессе--владельце и пропадают вместе с ним.
+///     Уже присоединённые участки при этом остаются отображены.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) struct SharedRegion {
+    /// Блок страниц в адресном пространстве процесса--владельца.
+    block: Block<Page>,
+
+    /// Процесс, которому разрешено присоединить участок.
+    grantee: Pid,
+
+    /// Имя участка, выбранное процессом--владельцем.
+    name: usize,
+}
+
+impl SharedRegion {
+    /// Создаёт разрешение процессу `grantee` присоединить по имени `name`
+    /// блок страниц, который начинается с адреса `address` и имеет размер `size` байт.
+    ///
+    /// Возвращает ошибки:
+    ///   - [`Error::InvalidArgument`], если `grantee` --- это [`Pid::Current`] или блок пуст.
+    ///   - [`Error::InvalidAlignment`], если блок не выровнен на границу страниц.
+    ///   - [`Error::PermissionDenied`], если блок выходит за пределы
+    ///     пользовательской части адресного пространства.
+    pub(super) fn new(
+        name: usize,
+        grantee: Pid,
+        address: usize,
+        size: usize,
+    ) -> Result<Self> {
+        let end = address.checked_add(size).ok_or(Overflow)?;
+        let block = Block::new(Page::new(Virt::new(address)?)?, Page::new(Virt::new(end)?)?)?;
+
+        if grantee == Pid::Current || block.is_empty() {
+            return Err(InvalidArgument);
+        }
+
+        if !memory::is_user_block(block) {
+            return Err(PermissionDenied);
+        }
+
+        Ok(Self {
+            block,
+            grantee,
+            name,
+        })
+    }
+
+    /// Блок страниц в адресном пространстве процесса--владельца.
+    pub(super) fn block(&self) -> Block<Page> {
+        self.block
+    }
+
+    /// Процесс, которому разрешено присоединить участок.
+    pub(super) fn grantee(&self) -> Pid {
+        self.grantee
+    }
+
+    /// Имя участка, выбранное процессом--владельцем.
+    pub(super) fn name(&self) -> usize {
+        self.name
+    }
+
+    /// Возвращает `true`, если участок предоставлен процессу `grantee` под именем `name`.
+    pub(super) fn is_granted(
+        &self,
+        name: usize,
+        grantee: Pid,
+    ) -> bool {
+        self.name == name && self.grantee == grantee
+    }
+
+    /// Возвращает физические фреймы, в которые отображены страницы участка
+    /// в адресном пространстве процесса--владельца `address_space`.
+    ///
+    /// Счётчик ссылок каждого фрейма увеличивается, за это отвечает [`FrameGuard`].
+    /// Поэтому фреймы не освободятся, даже если владелец успеет удалить их отображение
+    /// до того, как они будут отображены в присоединяющий процесс.
+    ///
+    /// Возвращает ошибки:
+    ///   - [`Error::NoPage`], если какая-нибудь страница участка не отображена.
+    ///   - [`Error::PermissionDenied`], если какая-нибудь страница участка
+    ///     не доступна пользователю на запись.
+    ///     В том числе, если она копируется при записи ---
+    ///     первая же запись владельца разделила бы такую страницу.
+    pub(super) fn frames(
+        &self,
+        address_space: &mut AddressSpace,
+    ) -> Result<Vec<FrameGuard>> {
+        let mut frames = Vec::with_capacity(self.block.count());
+
+        for page in self.block {
+            let pte = address_space.translate(page.address())?;
+            if !pte.is_present() {
+                return Err(NoPage);
+            }
+
+            let flags = pte.flags();
+            if !flags.contains(USER_RW) || flags.contains(PageTableFlags::COPY_ON_WRITE) {
+                return Err(PermissionDenied);
+            }
+
+            frames.push(FrameGuard::reference(pte.frame()?));
+        }
+
+        Ok(frames)
+    }
+}
+
+/// Отображает физические фреймы `frames` в свободный участок
+/// адресного пространства `address_space` с флагами [`USER_RW`].
+/// Возвращает блок страниц этого участка.
+///
+/// Фреймы при этом становятся общими --- каждый из них освободится только тогда,
+/// когда будут удалены все его отображения во всех процессах.
+pub(super) fn attach(
+    address_space: &mut AddressSpace,
+    frames: Vec<FrameGuard>,
+) -> Result<Block<Page>> {
+    let layout = Layout::from_size_align(frames.len() * Page::SIZE, Page::SIZE)?;
+    let block = address_space.allocate(layout, USER_RW)?;
+
+    for (i, (page, frame)) in block.into_iter().zip(frames.iter()).enumerate() {
+        let result = unsafe { address_space.map_page_to_frame(page, **frame, USER_RW) };
+
+        if let Err(error) = result {
+            for page in block.into_iter().take(i) {
+                unsafe {
+                    address_space.unmap_page(page)?;
+                }
+            }
+            address_space.deallocate(block)?;
+            return Err(error);
+        }
+    }
+
+    Ok(block)
+}
@@ -0,0 +1,54 @@
+use ku::process::elf;
+
+use crate::{
+    error::{
+        Error::NoPage,
+        Result,
+    },
+    memory::{
+        self,
+        AddressSpace,
+        Block,
+    },
+};
+
+use super::table::Table;
+
+/// Делает общими с другим процессом, загруженным из того же
+/// [ELF--файла](https://en.wikipedia.org/wiki/Executable_and_Linkable_Format) `elf_file`,
+/// физические фреймы тех страниц адресного пространства `address_space`,
+/// которые принадлежат только недоступным на запись загружаемым сегментам.
+/// То есть, например, страницы кода и констант программы.
+/// Возвращает количество страниц, ставших общими.
+///
+/// Фреймы разделяются с помощью подсчёта ссылок на них,
+/// поэтому остаются занятыми, пока их использует хотя бы один процесс.
+/// Страница становится общей только если содержимое фреймов совпадает.
+/// Так что разделение безопасно, даже если по тому же адресу
+/// теперь находится уже другой ELF--файл.
+pub(super) fn share(
+    address_space: &mut AddressSpace,
+    elf_file: &[u8],
+) -> Result<usize> {
+    let image = Block::from_slice(elf_file);
+    let Some(mut donor) = Table::try_find(|process| process.elf_image() == Some(image)) else {
+        return Ok(0);
+    };
+    let donor_address_space = donor.address_space();
+
+    let mut shared_page_count = 0;
+
+    for (segment, _) in elf::loadable_segments(elf_file, memory::is_user_block)? {
+        for page in segment.enclosing() {
+            if elf::page_flags(elf_file, page)?.is_some_and(|flags| !flags.is_writable()) {
+                match unsafe { address_space.share_page(donor_address_space, page) } {
+                    Ok(true) => shared_page_count += 1,
+                    Ok(false) | Err(NoPage) => {},
+                    Err(error) => return Err(error),
+                }
+            }
+        }
+    }
+
+    Ok(shared_page_count)
+}
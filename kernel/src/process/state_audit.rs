@@ -0,0 +1,96 @@
+use ku::{
+    process::State,
+    time::Tsc,
+};
+
+use crate::log::debug;
+
+use super::Pid;
+
+/// Переход процесса из одного состояния [`State`] в другое.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct StateTransition {
+    /// Новое состояние процесса.
+    new_state: State,
+
+    /// Предыдущее состояние процесса.
+    old_state: State,
+
+    /// Причина перехода.
+    reason: &'static str,
+
+    /// Момент перехода.
+    timestamp: Tsc,
+}
+
+impl StateTransition {
+    /// Новое состояние процесса.
+    pub fn new_state(&self) -> State {
+        self.new_state
+    }
+
+    /// Предыдущее состояние процесса.
+    pub fn old_state(&self) -> State {
+        self.old_state
+    }
+
+    /// Причина перехода.
+    pub fn reason(&self) -> &'static str {
+        self.reason
+    }
+
+    /// Момент перехода.
+    pub fn timestamp(&self) -> Tsc {
+        self.timestamp
+    }
+}
+
+/// Журнал последних [`StateAudit::CAPACITY`] переходов процесса между состояниями.
+///
+/// Позволяет восстановить, почему процесс не исполняется.
+/// Переходы записываются только при включённой опции `state-audit`,
+/// иначе журнал всегда пуст.
+/// Вместе с записью в журнал процесса переход выводится в журнал ядра.
+#[derive(Debug, Default)]
+pub struct StateAudit {
+    /// Позиция в [`StateAudit::transitions`], куда будет записан следующий переход.
+    next: usize,
+
+    /// Циклический буфер последних переходов.
+    transitions: [Option<StateTransition>; StateAudit::CAPACITY],
+}
+
+impl StateAudit {
+    /// Записывает переход процесса `pid` из состояния `old_state` в состояние `new_state`
+    /// по причине `reason`, вытесняя самый старый из записанных переходов.
+    pub(super) fn record(
+        &mut self,
+        pid: Pid,
+        old_state: State,
+        new_state: State,
+        reason: &'static str,
+    ) {
+        if !cfg!(feature = "state-audit") {
+            return;
+        }
+
+        debug!(%pid, ?old_state, ?new_state, reason, "state transition");
+
+        self.transitions[self.next] = Some(StateTransition {
+            new_state,
+            old_state,
+            reason,
+            timestamp: Tsc::synchronized(),
+        });
+        self.next = (self.next + 1) % Self::CAPACITY;
+    }
+
+    /// Возвращает записанные переходы в хронологическом порядке.
+    pub(super) fn iter(&self) -> impl Iterator<Item = &StateTransition> {
+        let (newer, older) = self.transitions.split_at(self.next);
+        older.iter().chain(newer).flatten()
+    }
+
+    /// Максимальное количество хранимых переходов.
+    pub const CAPACITY: usize = 16;
+}
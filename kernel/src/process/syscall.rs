@@ -1,14 +1,23 @@
-use alloc::vec::Vec;
+use alloc::{
+    vec,
+    vec::Vec,
+};
 use core::{
     arch::{
         asm,
         naked_asm,
     },
+    mem,
     str,
 };
 
 use super::registers::Registers;
 
+use chrono::{
+    DateTime,
+    Duration,
+    Utc,
+};
 use x86_64::registers::model_specific::{
     Efer,
     EferFlags,
@@ -20,6 +29,7 @@ use x86_64::registers::model_specific::{
 use ku::{
     log::{
         self,
+        HexBytes,
         Level,
         event,
     },
@@ -32,6 +42,7 @@ use ku::{
         Syscall,
     },
     sync::spinlock::SpinlockGuard,
+    time,
 };
 
 use crate::{
@@ -40,6 +51,7 @@ use crate::{
         Error::{
             InvalidAlignment,
             InvalidArgument,
+            NoData,
             NoPage,
             Overflow,
             PermissionDenied,
@@ -60,8 +72,11 @@ use crate::{
         FrameGuard,
         KERNEL_RW,
         Page,
+        SYSCALL_ALLOWED_FLAGS,
         Translate,
         USER_R,
+        USER_RW,
+        UserAccess,
         Virt,
         mmu::{
             PageTableEntry,
@@ -77,9 +92,15 @@ use crate::{
 use super::{
     Pid,
     Process,
+    SYSCALL_STATS,
     Scheduler,
     Table,
     TrapContext,
+    pipe::PipeEnd,
+    shared_memory::{
+        self,
+        SharedRegion,
+    },
 };
 
 use lock_set::{
@@ -189,26 +210,88 @@ extern "C" fn syscall(
         }
     };
 
-    match syscall_result {
+    if let Ok(syscall) = syscall_result {
+        SYSCALL_STATS[syscall].inc();
+    }
+
+    // A process killed with its group while running finishes on its next syscall.
+    if process.as_ref().is_ok_and(|process| process.is_killed()) {
+        exit(process.unwrap(), ExitCode::Killed.into());
+    }
+
+    let trace = if let Ok(syscall) = syscall_result &&
+        let Ok(process) = &process &&
+        let Some(trace) = process.syscall_trace() &&
+        trace.begin(syscall, [arg0, arg1, arg2, arg3, arg4])
+    {
+        Some(trace.clone())
+    } else {
+        None
+    };
+
+    // The diverging and blocking syscalls may not return here,
+    // so they drop the trace beforehand.
+    let result = match syscall_result {
         Ok(Syscall::Exit) => {
-            exit(process.unwrap(), arg0);
-        }
-        Ok(Syscall::LogValue) => {
-            let result = log_value(process.unwrap(), arg0, arg1, arg2, arg3);
-            sysret(context, result);
-        }
+            drop(trace);
+            exit(process.unwrap(), arg0)
+        },
+        Ok(Syscall::LogValue) => log_value(process.unwrap(), arg0, arg1, arg2, arg3),
         Ok(Syscall::SchedYield) => {
-            sched_yield(process.unwrap(), context);
-        }
+            drop(trace);
+            sched_yield(process.unwrap(), context)
+        },
+        Ok(Syscall::LogBytes) => log_bytes(process.unwrap(), arg0, arg1, arg2, arg3, arg4),
+        Ok(Syscall::Map) => check_map_arguments(arg2, arg3)
+            .and_then(|()| map(process.unwrap(), arg0, arg1, arg2, arg3)),
+        Ok(Syscall::SetGroup) => set_group(process.unwrap(), arg0, arg1),
+        Ok(Syscall::Suspend) => suspend(process.unwrap(), arg0),
+        Ok(Syscall::Resume) => resume(process.unwrap(), arg0),
+        Ok(Syscall::YieldTo) => {
+            drop(trace);
+            yield_to(process.unwrap(), context, arg0)
+        },
+        Ok(Syscall::Wait) => {
+            drop(trace);
+            wait(process.unwrap(), context, arg0)
+        },
+        Ok(Syscall::SetPriority) => set_priority(process.unwrap(), arg0, arg1),
+        Ok(Syscall::Sleep) => {
+            drop(trace);
+            sleep(process.unwrap(), context, arg0)
+        },
+        Ok(Syscall::Pipe) => pipe(process.unwrap(), arg0),
+        Ok(Syscall::PipeRead) => {
+            drop(trace);
+            pipe_read(process.unwrap(), context, arg0, arg1, arg2)
+        },
+        Ok(Syscall::PipeWrite) => {
+            drop(trace);
+            pipe_write(process.unwrap(), context, arg0, arg1, arg2)
+        },
+        Ok(Syscall::PipeClose) => pipe_close(process.unwrap(), arg0),
+        Ok(Syscall::Share) => share(process.unwrap(), arg0, arg1, arg2, arg3),
+        Ok(Syscall::Attach) => attach(process.unwrap(), arg0, arg1, arg2),
+        Ok(Syscall::SetLogLevel) => set_log_level(process.unwrap(), arg0),
         Err(_) => {
             warn!(?syscall_result, %number, %arg0, %arg1, %arg2, %arg3, %arg4, "unknown syscall");
-            sysret(context, Err(InvalidArgument));
-        }
+            Err(InvalidArgument)
+        },
         _ => {
             warn!(?syscall_result, "unimplemented syscall");
-            sysret(context, Err(crate::error::Error::Unimplemented));
-        }
+            Err(crate::error::Error::Unimplemented)
+        },
     };
+
+    if let Ok(syscall) = syscall_result {
+        SYSCALL_STATS[syscall].record(&result);
+    }
+
+    if let Some(trace) = trace {
+        trace.end(&result);
+    }
+
+    sysret(context, result);
 }
 
 // ANCHOR: sysret
@@ -277,22 +360,24 @@ fn sysret(
 /// Выполняет системный вызов
 /// [`lib::syscall::exit(code)`](https://sergey-v-galtsev.gitlab.io/labs-description/doc/lib/syscall/fn.exit.html).
 ///
-/// Освобождает слот таблицы процессов и возвращается в контекст ядра,
+/// Записывает в журнал максимальную глубину стека процесса,
+/// освобождает слот таблицы процессов и возвращается в контекст ядра,
 /// из которого пользовательский процесс был запущен.
 fn exit(
-    process: SpinlockGuard<Process>,
+    mut process: SpinlockGuard<Process>,
     code: usize,
 ) -> ! {
     // ANCHOR_END: exit
     let pid = process.pid();
     let exit_code = ExitCode::try_from(code);
+    let stack_high_water_mark = process.stack_high_water_mark();
     
-    info!(?pid, ?code, ?exit_code, "syscall = \"exit\"");
+    info!(?pid, ?code, ?exit_code, ?stack_high_water_mark, "syscall = \"exit\"");
     
     memory::BASE_ADDRESS_SPACE.lock().switch_to();
     
     drop(process);
-    Table::free(pid).expect("failed to free process in exit syscall");
+    Table::exit(pid, code).expect("failed to free process in exit syscall");
     
     Cpu::set_current_process(None);
 
@@ -324,15 +409,9 @@ fn log_value(
     let pid = process.pid();
     let level_char = level as u8 as char;
     let log_level = log::level_try_from_symbol(level_char).map_err(|_| InvalidArgument)?;
-    let end = start.checked_add(len).ok_or(Overflow)?;
-
-    let block = Block::<Virt>::from_index(start, end)?;
-
-    let _checked_slice = process.lock_address_space().check_permission::<u8>(block, USER_R)?;
-    let bytes = unsafe {
-        core::slice::from_raw_parts(start as *const u8, len)
-    };
-    let message = str::from_utf8(bytes).map_err(|_| InvalidArgument)?;
+    let _user_access = UserAccess::new();
+    let message =
+        str::from_utf8(check_user_bytes(&process, start, len)?).map_err(|_| InvalidArgument)?;
     match log_level {
         Level::TRACE => trace!(%pid, %message, %value, hex_value = format_args!("{:#X}", value)),
         Level::DEBUG => debug!(%pid, %message, %value, hex_value = format_args!("{:#X}", value)),
@@ -344,6 +423,83 @@ fn log_value(
     Ok(0)
 }
 
+/// Выполняет системный вызов
+/// [`lib::syscall::log_bytes(level, label, bytes)`](https://sergey-v-galtsev.gitlab.io/labs-description/doc/lib/syscall/fn.log_bytes.html).
+///
+/// Записывает в журнал строку `label` типа `&str`, заданную началом `label_start` и
+/// длиной `label_len`, а также шестнадцатеричное представление байт,
+/// заданных началом `bytes_start` и длиной `bytes_len`.
+/// Записывает не более [`LOG_BYTES_MAX_LEN`] первых байт,
+/// а их полное количество --- в отдельное поле.
+fn log_bytes(
+    process: SpinlockGuard<Process>,
+    level: usize,
+    label_start: usize,
+    label_len: usize,
+    bytes_start: usize,
+    bytes_len: usize,
+) -> Result<usize> {
+    let pid = process.pid();
+    let level_char = level as u8 as char;
+    let log_level = log::level_try_from_symbol(level_char).map_err(|_| InvalidArgument)?;
+
+    let _user_access = UserAccess::new();
+    let label = str::from_utf8(check_user_bytes(&process, label_start, label_len)?)
+        .map_err(|_| InvalidArgument)?;
+    let bytes = HexBytes::new(check_user_bytes(
+        &process,
+        bytes_start,
+        bytes_len.min(LOG_BYTES_MAX_LEN),
+    )?);
+
+    match log_level {
+        Level::TRACE => trace!(%pid, %label, len = bytes_len, %bytes),
+        Level::DEBUG => debug!(%pid, %label, len = bytes_len, %bytes),
+        Level::INFO => info!(%pid, %label, len = bytes_len, %bytes),
+        Level::WARN => warn!(%pid, %label, len = bytes_len, %bytes),
+        Level::ERROR => error!(%pid, %label, len = bytes_len, %bytes),
+    }
+
+    Ok(0)
+}
+
+/// Проверяет, что блок памяти, заданный началом `start` и длиной `len`,
+/// доступен процессу `process` на чтение.
+/// Возвращает срез байт, расположенный в этом блоке.
+///
+/// Читать срез можно только внутри окна [`UserAccess`].
+fn check_user_bytes(
+    process: &SpinlockGuard<Process>,
+    start: usize,
+    len: usize,
+) -> Result<&'static [u8]> {
+    let end = start.checked_add(len).ok_or(Overflow)?;
+    let block = Block::<Virt>::from_index(start, end)?;
+
+    process.lock_address_space().check_permission(block, USER_R)
+}
+
+/// Выполняет системный вызов
+/// [`lib::syscall::set_log_level(level)`](https://sergey-v-galtsev.gitlab.io/labs-description/doc/lib/syscall/fn.set_log_level.html).
+///
+/// Устанавливает уровень журналирования ядра, заданный символом `level`
+/// в кодировке [`log::level_into_symbol()`], см. [`crate::log::set_level()`].
+/// Если `level` не задаёт уровень журналирования,
+/// возвращает ошибку [`Error::InvalidArgument`].
+fn set_log_level(
+    process: SpinlockGuard<Process>,
+    level: usize,
+) -> Result<usize> {
+    let level_char = char::from(u8::try_from(level).map_err(|_| InvalidArgument)?);
+    let log_level = log::level_try_from_symbol(level_char).map_err(|_| InvalidArgument)?;
+
+    info!(pid = %process.pid(), level = %log_level, "syscall = \"set_log_level\"");
+
+    crate::log::set_level(log_level);
+
+    Ok(0)
+}
+
 /// Выполняет системный вызов
 /// [`lib::syscall::sched_yield()`](https://sergey-v-galtsev.gitlab.io/labs-description/doc/lib/syscall/fn.sched_yield.html).
 ///
@@ -383,6 +539,311 @@ fn sched_yield(
     }
 }
 
+/// Выполняет системный вызов
+/// [`lib::syscall::yield_to(target)`](https://sergey-v-galtsev.gitlab.io/labs-description/doc/lib/syscall/fn.yield_to.html).
+///
+/// Отдаёт остаток кванта времени вызывающего процесса `process` процессу,
+/// заданному идентификатором `target`, ставя его в начало очереди планировщика
+/// методом [`Scheduler::yield_to()`].
+/// Это уменьшает задержку синхронной передачи управления, например,
+/// от клиента к серверу, которому клиент только что отправил запрос.
+///
+/// Если целевой процесс не готов к исполнению, то есть не стоит в очереди планировщика,
+/// или совпадает с вызывающим, работает так же, как [`sched_yield()`].
+/// В остальном тоже делает то же самое, что и [`sched_yield()`].
+fn yield_to(
+    process: SpinlockGuard<Process>,
+    context: MiniContext,
+    target: usize,
+) -> ! {
+    let pid = process.pid();
+    let target = Pid::from_usize(target);
+
+    info!(?pid, ?target, "syscall = \"yield_to\"");
+
+    let donated = target.is_ok_and(|target| target != pid && Scheduler::yield_to(target));
+
+    if !donated {
+        debug!(?pid, ?target, "the target is not runnable");
+    }
+
+    sched_yield(process, context);
+}
+
+/// Выполняет системный вызов
+/// [`lib::syscall::sleep(duration)`](https://sergey-v-galtsev.gitlab.io/labs-description/doc/lib/syscall/fn.sleep.html).
+///
+/// Усыпляет вызывающий процесс `process` на `milliseconds` миллисекунд
+/// методом [`Scheduler::sleep()`] и забирает у него CPU так же, как [`sched_yield()`].
+/// Спящий процесс [`Scheduler::enqueue()`] в очередь не ставит,
+/// поэтому процесс вернётся из системного вызова не раньше момента пробуждения.
+/// При нулевой длительности работает так же, как [`sched_yield()`].
+fn sleep(
+    process: SpinlockGuard<Process>,
+    context: MiniContext,
+    milliseconds: usize,
+) -> ! {
+    let pid = process.pid();
+
+    info!(?pid, milliseconds, "syscall = \"sleep\"");
+
+    if milliseconds != 0 {
+        let deadline = i64::try_from(milliseconds)
+            .ok()
+            .and_then(Duration::try_milliseconds)
+            .and_then(|duration| time::now().checked_add_signed(duration))
+            .unwrap_or(DateTime::<Utc>::MAX_UTC);
+
+        Scheduler::sleep(pid, deadline);
+    }
+
+    sched_yield(process, context);
+}
+
+/// Выполняет системный вызов
+/// [`lib::syscall::pipe()`](https://sergey-v-galtsev.gitlab.io/labs-description/doc/lib/syscall/fn.pipe.html).
+///
+/// Создаёт канал [`PipeEnd::new()`] и открывает в вызывающем процессе `process`
+/// оба его конца.
+/// Записывает в память пользователя по адресу `handles` пару дескрипторов ---
+/// конца канала для чтения и конца канала для записи.
+///
+/// Возвращает ошибки:
+///   - [`Error::Overflow`], если у процесса слишком много открытых концов каналов.
+///   - [`Error::NoPage`] или [`Error::PermissionDenied`],
+///     если по адресу `handles` нельзя записать дескрипторы.
+fn pipe(
+    mut process: SpinlockGuard<Process>,
+    handles: usize,
+) -> Result<usize> {
+    let pid = process.pid();
+    let handles_address = Virt::new(handles)?;
+    let mut handle_bytes = [0; 2 * mem::size_of::<usize>()];
+    memory::copy_to_user(handles_address, &handle_bytes)?;
+
+    let (reader, writer) = PipeEnd::new();
+    let reader = process.open_pipe_end(reader)?;
+    let writer = match process.open_pipe_end(writer) {
+        Ok(writer) => writer,
+        Err(error) => {
+            process.close_pipe_end(reader)?;
+            return Err(error);
+        },
+    };
+
+    info!(%pid, reader, writer, "syscall = \"pipe\"");
+
+    let (reader_bytes, writer_bytes) = handle_bytes.split_at_mut(mem::size_of::<usize>());
+    reader_bytes.copy_from_slice(&reader.to_ne_bytes());
+    writer_bytes.copy_from_slice(&writer.to_ne_bytes());
+
+    memory::copy_to_user(handles_address, &handle_bytes).inspect_err(|_| {
+        for handle in [reader, writer] {
+            process.close_pipe_end(handle).expect("the pipe end has just been opened");
+        }
+    })?;
+
+    Ok(0)
+}
+
+/// Выполняет системный вызов
+/// [`lib::syscall::PipeReader::read(buffer)`](https://sergey-v-galtsev.gitlab.io/labs-description/doc/lib/syscall/struct.PipeReader.html#method.read).
+///
+/// Читает методом [`PipeEnd::read()`] из конца канала с дескриптором `handle`
+/// в память пользователя, заданную началом `start` и длиной `len`.
+/// Возвращает количество прочитанных байт, ноль означает конец данных.
+///
+/// Если канал пуст, блокирует вызывающий процесс `process` функцией [`block()`].
+fn pipe_read(
+    process: SpinlockGuard<Process>,
+    context: MiniContext,
+    handle: usize,
+    start: usize,
+    len: usize,
+) -> Result<usize> {
+    let pid = process.pid();
+    let address = Virt::new(start)?;
+    let mut buffer = vec![0; len.min(PipeEnd::CAPACITY)];
+
+    // Check the user buffer before taking the data out of the pipe,
+    // but do not overwrite the bytes that will not be read.
+    let end = start.checked_add(buffer.len()).ok_or(Overflow)?;
+    let block = Block::<Virt>::from_index(start, end)?;
+    process.lock_address_space().check_permission_mut::<u8>(block, USER_RW)?;
+
+    let result = process.pipe_end(handle)?.read(pid, &mut buffer);
+
+    info!(%pid, handle, len, ?result, "syscall = \"pipe_read\"");
+
+    match result {
+        Ok(count) => {
+            memory::copy_to_user(address, &buffer[.. count])?;
+            Ok(count)
+        },
+        Err(NoData) => block(process, context),
+        Err(error) => Err(error),
+    }
+}
+
+/// Выполняет системный вызов
+/// [`lib::syscall::PipeWriter::write(buffer)`](https://sergey-v-galtsev.gitlab.io/labs-description/doc/lib/syscall/struct.PipeWriter.html#method.write).
+///
+/// Записывает методом [`PipeEnd::write()`] в конец канала с дескриптором `handle`
+/// байты из памяти пользователя, заданной началом `start` и длиной `len`.
+/// Возвращает количество записанных байт.
+///
+/// Если канал заполнен, блокирует вызывающий процесс `process` функцией [`block()`].
+fn pipe_write(
+    process: SpinlockGuard<Process>,
+    context: MiniContext,
+    handle: usize,
+    start: usize,
+    len: usize,
+) -> Result<usize> {
+    let pid = process.pid();
+    let mut buffer = vec![0; len.min(PipeEnd::CAPACITY)];
+    memory::copy_from_user(&mut buffer, Virt::new(start)?)?;
+
+    let result = process.pipe_end(handle)?.write(pid, &buffer);
+
+    info!(%pid, handle, len, ?result, "syscall = \"pipe_write\"");
+
+    match result {
+        Err(NoData) => block(process, context),
+        result => result,
+    }
+}
+
+/// Выполняет системный вызов, которым удаляемые
+/// [`lib::syscall::PipeReader`](https://sergey-v-galtsev.gitlab.io/labs-description/doc/lib/syscall/struct.PipeReader.html) и
+/// [`lib::syscall::PipeWriter`](https://sergey-v-galtsev.gitlab.io/labs-description/doc/lib/syscall/struct.PipeWriter.html)
+/// закрывают свои концы канала.
+///
+/// Закрывает в вызывающем процессе `process` конец канала с дескриптором `handle`.
+/// Когда закрыты все копии конца канала, другой конец видит конец данных или
+/// ошибку [`Error::Pipe`].
+fn pipe_close(
+    mut process: SpinlockGuard<Process>,
+    handle: usize,
+) -> Result<usize> {
+    info!(pid = %process.pid(), handle, "syscall = \"pipe_close\"");
+
+    process.close_pipe_end(handle)?;
+
+    Ok(0)
+}
+
+/// Блокирует вызывающий процесс `process`, который уже усыплён методом [`Scheduler::sleep()`]
+/// до изменения канала, см. [`PipeEnd::read()`] и [`PipeEnd::write()`].
+/// Записывает в него результат системного вызова [`Error::NoData`] и
+/// забирает у него CPU так же, как [`sched_yield()`].
+/// Пока процесс спит, [`Scheduler::enqueue()`] его в очередь не ставит.
+///
+/// Блокировка на `process` удерживается до сохранения его контекста.
+/// Поэтому, даже если канал изменится раньше, разбуженный процесс не будет запущен
+/// с устаревшим контекстом.
+/// Проснувшись, процесс получает [`Error::NoData`] и повторяет системный вызов.
+fn block(
+    mut process: SpinlockGuard<Process>,
+    context: MiniContext,
+) -> ! {
+    process.set_syscall_result(Err(NoData));
+    sched_yield(process, context);
+}
+
+/// Выполняет системный вызов
+/// [`lib::syscall::share(grantee, name, block)`](https://sergey-v-galtsev.gitlab.io/labs-description/doc/lib/syscall/fn.share.html).
+///
+/// Разрешает процессу `grantee` присоединить к себе системным вызовом [`attach()`]
+/// под именем `name` блок страниц вызывающего процесса `process`,
+/// который начинается с адреса `address` и имеет размер `size` байт.
+/// Модель безопасности описана в [`SharedRegion`].
+///
+/// Страницы блока должны быть отображены с флагами [`USER_RW`] и
+/// не должны копироваться при записи.
+/// Это проверяется сразу и ещё раз при присоединении,
+/// так как процесс может изменить своё отображение в промежутке между ними.
+///
+/// Возвращает ошибки:
+///   - [`Error::InvalidArgument`], если `grantee` не задаёт процесс
+///     или ему уже предоставлен участок с тем же именем.
+///   - Ошибки [`SharedRegion::new()`], [`SharedRegion::frames()`] и
+///     [`Process::share_region()`].
+fn share(
+    mut process: SpinlockGuard<Process>,
+    grantee: usize,
+    name: usize,
+    address: usize,
+    size: usize,
+) -> Result<usize> {
+    let grantee = Pid::from_usize(grantee)?;
+    let region = SharedRegion::new(name, grantee, address, size)?;
+    drop(region.frames(process.address_space())?);
+
+    info!(
+        pid = %process.pid(),
+        %grantee,
+        name,
+        block = %region.block(),
+        "syscall = \"share\"",
+    );
+
+    process.share_region(region)?;
+
+    Ok(0)
+}
+
+/// Выполняет системный вызов
+/// [`lib::syscall::attach(granter, name, size)`](https://sergey-v-galtsev.gitlab.io/labs-description/doc/lib/syscall/fn.attach.html).
+///
+/// Отображает в свободный участок адресного пространства вызывающего процесса `process`
+/// с флагами [`USER_RW`] физические фреймы блока страниц,
+/// который процесс `granter` предоставил ему системным вызовом [`share()`] под именем `name`.
+/// Размер блока должен быть равен `size` байт.
+/// Возвращает начальный адрес выбранного участка.
+///
+/// Блокировки процессов захватываются по очереди, а не одновременно.
+/// Поэтому встречные присоединения двух процессов друг к другу не приводят к
+/// [взаимоблокировке](https://en.wikipedia.org/wiki/Deadlock).
+/// Пока не держится ни одна из них, фреймы удерживает их счётчик ссылок.
+///
+/// Возвращает ошибки:
+///   - [`Error::InvalidArgument`], если `granter` --- это [`Pid::Current`]
+///     или `size` не совпадает с размером блока.
+///   - [`Error::NoProcess`], если процесса `granter` не существует.
+///   - [`Error::PermissionDenied`], если `granter` не предоставлял
+///     вызывающему процессу участок с именем `name`.
+///   - Ошибки [`SharedRegion::frames()`] и [`shared_memory::attach()`].
+fn attach(
+    process: SpinlockGuard<Process>,
+    granter: usize,
+    name: usize,
+    size: usize,
+) -> Result<usize> {
+    let pid = process.pid();
+    let granter = Pid::from_usize(granter)?;
+    drop(process);
+
+    if granter == Pid::Current {
+        return Err(InvalidArgument);
+    }
+
+    let mut granter_process = Table::get(granter)?;
+    let region = granter_process.shared_region(name, pid)?;
+    if region.block().size() != size {
+        return Err(InvalidArgument);
+    }
+    let frames = region.frames(granter_process.address_space())?;
+    drop(granter_process);
+
+    let mut process = Table::get(pid)?;
+    let block = shared_memory::attach(process.address_space(), frames)?;
+
+    info!(%pid, %granter, name, %block, "syscall = \"attach\"");
+
+    Ok(block.start_address().into_usize())
+}
+
 // ANCHOR: exofork
 /// Выполняет системный вызов
 /// [`lib::syscall::exofork()`](https://sergey-v-galtsev.gitlab.io/labs-description/doc/lib/syscall/fn.exofork.html).
@@ -539,6 +1000,208 @@ fn set_trap_handler(
     unimplemented!();
 }
 
+/// Выполняет системный вызов
+/// [`lib::syscall::set_group(dst_pid, group)`](https://sergey-v-galtsev.gitlab.io/labs-description/doc/lib/syscall/fn.set_group.html).
+///
+/// Переводит целевой процесс, заданный идентификатором `dst_pid`,
+/// в группу процессов `group`.
+/// Допустимые группы:
+///   - [`Pid::Current`] или идентификатор самого целевого процесса ---
+///     новая группа, лидером которой становится целевой процесс.
+///   - Группа вызывающего процесса `process`.
+///
+/// Для остальных групп возвращает ошибку [`Error::PermissionDenied`].
+fn set_group(
+    process: SpinlockGuard<Process>,
+    dst_pid: usize,
+    group: usize,
+) -> Result<usize> {
+    let group = Pid::from_usize(group)?;
+    let src_group = process.group();
+
+    let mut lock_set = lock_dst(process, dst_pid)?;
+    let dst = lock_set.dst_mut();
+
+    let group = if group == Pid::Current {
+        dst.pid()
+    } else {
+        group
+    };
+
+    if group != dst.pid() && group != src_group {
+        return Err(PermissionDenied);
+    }
+
+    info!(dst = %dst.pid(), %group, "syscall = \"set_group\"");
+
+    dst.set_group(group);
+
+    Ok(0)
+}
+
+/// Выполняет системный вызов
+/// [`lib::syscall::set_priority(dst_pid, priority)`](https://sergey-v-galtsev.gitlab.io/labs-description/doc/lib/syscall/fn.set_priority.html).
+///
+/// Устанавливает целевому процессу, заданному идентификатором `dst_pid`,
+/// приоритет `priority` в планировщике, см. [`Process::set_priority()`].
+/// Если `priority` превышает [`Process::MAX_PRIORITY`],
+/// возвращает ошибку [`Error::InvalidArgument`].
+fn set_priority(
+    process: SpinlockGuard<Process>,
+    dst_pid: usize,
+    priority: usize,
+) -> Result<usize> {
+    let priority = u8::try_from(priority).map_err(|_| InvalidArgument)?;
+
+    let mut lock_set = lock_dst(process, dst_pid)?;
+    let dst = lock_set.dst_mut();
+
+    info!(dst = %dst.pid(), priority, "syscall = \"set_priority\"");
+
+    dst.set_priority(priority)?;
+
+    Ok(0)
+}
+
+/// Выполняет системный вызов
+/// [`lib::syscall::suspend(dst_pid)`](https://sergey-v-galtsev.gitlab.io/labs-description/doc/lib/syscall/fn.suspend.html).
+///
+/// Приостанавливает целевой процесс, заданный идентификатором `dst_pid`,
+/// который должен быть потомком вызывающего процесса `process`, см. [`lock_descendant()`].
+/// Приостановленный процесс не планируется на исполнение,
+/// пока не будет возобновлён системным вызовом [`resume()`].
+///
+/// Если целевой процесс прямо сейчас исполняется на другом процессоре,
+/// он останавливается при следующем возврате в планировщик ---
+/// по [`sched_yield()`] или по прерыванию таймера.
+/// Пока процесс находится в режиме пользователя, он не держит блокировок ядра.
+/// Поэтому остановить его в этот момент безопасно.
+fn suspend(
+    process: SpinlockGuard<Process>,
+    dst_pid: usize,
+) -> Result<usize> {
+    let mut dst = lock_descendant(process, dst_pid)?;
+
+    info!(dst = %dst.pid(), "syscall = \"suspend\"");
+
+    dst.set_suspended(true);
+
+    Ok(0)
+}
+
+/// Выполняет системный вызов
+/// [`lib::syscall::resume(dst_pid)`](https://sergey-v-galtsev.gitlab.io/labs-description/doc/lib/syscall/fn.resume.html).
+///
+/// Возобновляет работу целевого процесса, заданного идентификатором `dst_pid`,
+/// приостановленного системным вызовом [`suspend()`].
+/// Целевой процесс должен быть потомком вызывающего процесса `process`,
+/// см. [`lock_descendant()`].
+/// Если он готов к исполнению, ставит его в очередь планировщика.
+fn resume(
+    process: SpinlockGuard<Process>,
+    dst_pid: usize,
+) -> Result<usize> {
+    let mut dst = lock_descendant(process, dst_pid)?;
+    let pid = dst.pid();
+
+    info!(dst = %pid, "syscall = \"resume\"");
+
+    dst.set_suspended(false);
+    let is_runnable = dst.state() == State::Runnable;
+    drop(dst);
+
+    if is_runnable {
+        Scheduler::enqueue(pid);
+    }
+
+    Ok(0)
+}
+
+/// Выполняет системный вызов
+/// [`lib::syscall::wait()`](https://sergey-v-galtsev.gitlab.io/labs-description/doc/lib/syscall/fn.wait.html).
+///
+/// Забирает код завершения одного из завершившихся непосредственных потомков
+/// вызывающего процесса `process` методом [`Table::reap()`].
+/// Записывает код завершения в память пользователя по адресу `exit_code`
+/// и возвращает [`Pid`] потомка.
+///
+/// Если потомки есть, но ни один из них ещё не завершился,
+/// блокирует вызывающий процесс функцией [`block()`] до завершения какого-нибудь из них.
+/// После пробуждения `lib::syscall::wait()` повторяет вызов.
+///
+/// Возвращает ошибки:
+///   - [`Error::NoProcess`], если у вызывающего процесса нет потомков.
+///   - [`Error::NoPage`] или [`Error::PermissionDenied`],
+///     если по адресу `exit_code` нельзя записать код завершения.
+fn wait(
+    process: SpinlockGuard<Process>,
+    context: MiniContext,
+    exit_code: usize,
+) -> Result<usize> {
+    let pid = process.pid();
+
+    // Table::reap() locks the processes in the table,
+    // so the lock on the caller should not be held meanwhile.
+    drop(process);
+
+    let exit_code_address = Virt::new(exit_code)?;
+    memory::copy_to_user(exit_code_address, &0_usize.to_ne_bytes())?;
+
+    loop {
+        let exit_count = Table::exit_count();
+
+        if let Some((child, code)) = Table::reap(pid)? {
+            info!(%pid, %child, code, "syscall = \"wait\"");
+
+            memory::copy_to_user(exit_code_address, &code.to_ne_bytes())?;
+
+            return Ok(child.into_usize());
+        }
+
+        let process = Table::get(pid)?;
+        if Table::sleep_until_exit(pid, exit_count) {
+            block(process, context);
+        }
+    }
+}
+
+/// Проверяет, что целевой процесс, заданный идентификатором `dst_pid`,
+/// является потомком процесса `src` --- не обязательно непосредственным.
+/// Сам процесс `src` своим потомком не считается.
+///
+/// Возвращает блокировку на целевой процесс.
+/// Блокировку на `src` при этом освобождает,
+/// так как при обходе предков целевого процесса может понадобиться и она.
+///
+/// Возвращает ошибки:
+///   - [`Error::NoProcess`], если целевого процесса не существует.
+///   - [`Error::PermissionDenied`], если он не является потомком `src`.
+fn lock_descendant(
+    src: SpinlockGuard<Process>,
+    dst_pid: usize,
+) -> Result<SpinlockGuard<'static, Process>> {
+    let src_pid = src.pid();
+    let dst_pid = Pid::from_usize(dst_pid)?;
+    drop(src);
+
+    if dst_pid == Pid::Current || dst_pid == src_pid {
+        return Err(PermissionDenied);
+    }
+
+    let dst = Table::get(dst_pid)?;
+    let mut ancestor = dst.parent();
+    drop(dst);
+
+    while let Some(pid) = ancestor {
+        if pid == src_pid {
+            return Table::get(dst_pid);
+        }
+        ancestor = Table::get(pid).ok().and_then(|process| process.parent());
+    }
+
+    Err(PermissionDenied)
+}
+
 /// Проверяет, что `address` и `size` задают корректно выровненный диапазон страниц,
 /// целиком лежащий внутри одной из
 /// [двух непрерывных половин](https://en.wikipedia.org/wiki/X86-64#Virtual_address_space_details)
@@ -589,6 +1252,33 @@ fn check_page_flags(flags: usize) -> Result<Option<PageTableFlags>> {
     unimplemented!();
 }
 
+/// Проверяет аргументы системного вызова [`map()`] --- размер `size` и флаги `flags`
+/// отображаемого блока --- так же, как это делает
+/// [`lib::syscall::mmap()`](https://sergey-v-galtsev.gitlab.io/labs-description/doc/lib/syscall/fn.mmap.html).
+/// Пользовательская библиотека может быть обойдена, поэтому ядро не полагается на её проверки.
+///
+/// Возвращает ошибки:
+///   - [`Error::InvalidArgument`], если `size` равен нулю или во `flags` установлен бит,
+///     не соответствующий никакому флагу [`PageTableFlags`].
+///   - [`Error::PermissionDenied`], если во `flags` нет флага [`PageTableFlags::USER`]
+///     или есть флаги, которые не входят в [`SYSCALL_ALLOWED_FLAGS`].
+fn check_map_arguments(
+    size: usize,
+    flags: usize,
+) -> Result<()> {
+    if size == 0 {
+        return Err(InvalidArgument);
+    }
+
+    let flags = PageTableFlags::from_bits(flags).ok_or(InvalidArgument)?;
+
+    if !flags.is_user() || !SYSCALL_ALLOWED_FLAGS.contains(flags) {
+        return Err(PermissionDenied);
+    }
+
+    Ok(())
+}
+
 /// Работа с блокировкой одного процесса или парой блокировок двух разных процессов.
 mod lock_set {
     use duplicate::duplicate_item;
@@ -768,6 +1458,9 @@ mod lock_set {
     }
 }
 
+/// Максимальное количество байт, которые записывает в журнал системный вызов [`log_bytes()`].
+const LOG_BYTES_MAX_LEN: usize = 256;
+
 #[doc(hidden)]
 pub mod test_scaffolding {
     use ku::{
@@ -789,6 +1482,31 @@ pub mod test_scaffolding {
         super::log_value(process, level, start, len, value)
     }
 
+    pub fn log_bytes(
+        process: SpinlockGuard<Process>,
+        level: usize,
+        label_start: usize,
+        label_len: usize,
+        bytes_start: usize,
+        bytes_len: usize,
+    ) -> Result<usize> {
+        super::log_bytes(
+            process,
+            level,
+            label_start,
+            label_len,
+            bytes_start,
+            bytes_len,
+        )
+    }
+
+    pub fn set_log_level(
+        process: SpinlockGuard<Process>,
+        level: usize,
+    ) -> Result<usize> {
+        super::set_log_level(process, level)
+    }
+
     pub fn exofork(process: SpinlockGuard<Process>) -> Result<usize> {
         super::exofork(process, MiniContext::default())
     }
@@ -830,4 +1548,53 @@ pub mod test_scaffolding {
     ) -> Result<usize> {
         super::set_state(process, dst_pid, state)
     }
+
+    pub fn set_group(
+        process: SpinlockGuard<Process>,
+        dst_pid: usize,
+        group: usize,
+    ) -> Result<usize> {
+        super::set_group(process, dst_pid, group)
+    }
+
+    pub fn set_priority(
+        process: SpinlockGuard<Process>,
+        dst_pid: usize,
+        priority: usize,
+    ) -> Result<usize> {
+        super::set_priority(process, dst_pid, priority)
+    }
+
+    pub fn suspend(
+        process: SpinlockGuard<Process>,
+        dst_pid: usize,
+    ) -> Result<usize> {
+        super::suspend(process, dst_pid)
+    }
+
+    pub fn share(
+        process: SpinlockGuard<Process>,
+        grantee: usize,
+        name: usize,
+        address: usize,
+        size: usize,
+    ) -> Result<usize> {
+        super::share(process, grantee, name, address, size)
+    }
+
+    pub fn attach(
+        process: SpinlockGuard<Process>,
+        granter: usize,
+        name: usize,
+        size: usize,
+    ) -> Result<usize> {
+        super::attach(process, granter, name, size)
+    }
+
+    pub fn resume(
+        process: SpinlockGuard<Process>,
+        dst_pid: usize,
+    ) -> Result<usize> {
+        super::resume(process, dst_pid)
+    }
 }
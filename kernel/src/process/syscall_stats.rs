@@ -0,0 +1,85 @@
+use core::{
+    ops::Index,
+    sync::atomic::{
+        AtomicUsize,
+        Ordering,
+    },
+};
+
+use ku::process::Syscall;
+
+use crate::error::Result;
+
+/// Информация о системном вызове.
+pub struct SyscallStatistics {
+    /// Сколько раз был выполнен этот системный вызов.
+    count: AtomicUsize,
+
+    /// Сколько раз этот системный вызов вернул ошибку.
+    errors: AtomicUsize,
+}
+
+impl SyscallStatistics {
+    /// Создаёт обнулённую информацию о системном вызове.
+    const fn new() -> Self {
+        Self {
+            count: AtomicUsize::new(0),
+            errors: AtomicUsize::new(0),
+        }
+    }
+
+    /// Сколько раз был выполнен этот системный вызов.
+    pub fn count(&self) -> usize {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// Сколько раз этот системный вызов вернул ошибку.
+    pub fn errors(&self) -> usize {
+        self.errors.load(Ordering::Relaxed)
+    }
+
+    /// Инкрементирует счётчик выполнения системного вызова.
+    pub(super) fn inc(&self) {
+        self.count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Инкрементирует счётчик ошибок, если системный вызов вернул ошибку в `result`.
+    pub(super) fn record<T>(
+        &self,
+        result: &Result<T>,
+    ) {
+        if result.is_err() {
+            self.errors.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Информация обо всех системных вызовах.
+pub struct SyscallStats([SyscallStatistics; COUNT]);
+
+impl SyscallStats {
+    /// Возвращает итератор по системным вызовам и их статистикам.
+    pub fn iter(&self) -> impl Iterator<Item = (Syscall, &SyscallStatistics)> {
+        self.0.iter().enumerate().map(|(number, statistics)| {
+            let syscall = Syscall::try_from(number).expect("a syscall number is out of range");
+            (syscall, statistics)
+        })
+    }
+}
+
+impl Index<Syscall> for SyscallStats {
+    type Output = SyscallStatistics;
+
+    fn index(
+        &self,
+        index: Syscall,
+    ) -> &Self::Output {
+        &self.0[usize::from(index)]
+    }
+}
+
+/// Информация обо всех системных вызовах.
+pub static SYSCALL_STATS: SyscallStats = SyscallStats([const { SyscallStatistics::new() }; COUNT]);
+
+/// Количество системных вызовов.
+const COUNT: usize = Syscall::SetLogLevel as usize + 1;
@@ -0,0 +1,108 @@
+use alloc::{
+    sync::Arc,
+    vec::Vec,
+};
+
+use ku::{
+    process::Syscall,
+    sync::FastSpinlock,
+};
+
+use crate::error::Result;
+
+/// Запись о выполненном процессом системном вызове.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SyscallRecord {
+    /// Аргументы системного вызова.
+    args: [usize; ARG_COUNT],
+
+    /// Результат системного вызова или [`None`],
+    /// если системный вызов не возвращает управление процессу с результатом,
+    /// как, например, [`Syscall::Exit`] и [`Syscall::SchedYield`].
+    result: Option<Result<usize>>,
+
+    /// Номер системного вызова.
+    syscall: Syscall,
+}
+
+impl SyscallRecord {
+    /// Аргументы системного вызова.
+    pub fn args(&self) -> [usize; ARG_COUNT] {
+        self.args
+    }
+
+    /// Результат системного вызова или [`None`],
+    /// если системный вызов не возвращает управление процессу с результатом.
+    pub fn result(&self) -> Option<&Result<usize>> {
+        self.result.as_ref()
+    }
+
+    /// Номер системного вызова.
+    pub fn syscall(&self) -> Syscall {
+        self.syscall
+    }
+}
+
+/// Запись последовательности системных вызовов процесса.
+///
+/// Включается для отдельного процесса методом [`Process::trace_syscalls()`].
+/// Копии [`SyscallTrace`] разделяют одни и те же записи,
+/// поэтому их можно прочитать и после завершения процесса.
+/// Записывает не более [`SyscallTrace::CAPACITY`] первых системных вызовов.
+///
+/// [`Process::trace_syscalls()`]: super::Process::trace_syscalls
+#[derive(Clone, Debug, Default)]
+pub struct SyscallTrace {
+    /// Записанные системные вызовы.
+    records: Arc<FastSpinlock<Vec<SyscallRecord>>>,
+}
+
+impl SyscallTrace {
+    /// Возвращает копию записанных системных вызовов в порядке их выполнения.
+    pub fn records(&self) -> Vec<SyscallRecord> {
+        self.records.lock().clone()
+    }
+
+    /// Возвращает номера записанных системных вызовов в порядке их выполнения.
+    pub fn syscalls(&self) -> Vec<Syscall> {
+        self.records.lock().iter().map(SyscallRecord::syscall).collect()
+    }
+
+    /// Записывает начало системного вызова `syscall` с аргументами `args`.
+    /// Возвращает `false`, если запись уже заполнена и системный вызов не записан.
+    pub(super) fn begin(
+        &self,
+        syscall: Syscall,
+        args: [usize; ARG_COUNT],
+    ) -> bool {
+        let mut records = self.records.lock();
+
+        if records.len() < Self::CAPACITY {
+            records.push(SyscallRecord {
+                args,
+                result: None,
+                syscall,
+            });
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Записывает результат `result` системного вызова,
+    /// начало которого было записано последним.
+    pub(super) fn end(
+        &self,
+        result: &Result<usize>,
+    ) {
+        if let Some(record) = self.records.lock().last_mut() {
+            record.result = Some(result.clone());
+        }
+    }
+
+    /// Максимальное количество записываемых системных вызовов.
+    pub const CAPACITY: usize = 1 << 10;
+}
+
+/// Количество аргументов системного вызова.
+const ARG_COUNT: usize = 5;
@@ -1,11 +1,24 @@
 use alloc::vec::Vec;
-use core::fmt;
+use core::{
+    fmt,
+    sync::atomic::{
+        AtomicUsize,
+        Ordering,
+    },
+};
 
+use chrono::{
+    DateTime,
+    Utc,
+};
 use lazy_static::lazy_static;
 
-use ku::sync::spinlock::{
-    Spinlock,
-    SpinlockGuard,
+use ku::{
+    process::State,
+    sync::spinlock::{
+        Spinlock,
+        SpinlockGuard,
+    },
 };
 
 use crate::{
@@ -24,6 +37,7 @@ use crate::{
 use super::{
     Pid,
     Process,
+    Scheduler,
 };
 
 // Used in docs.
@@ -47,6 +61,20 @@ enum Slot {
         /// Процесс, находящийся в этом слоте таблицы процессов.
         process: Spinlock<Process>,
     },
+
+    /// Процесс завершился, но его родитель ещё не забрал код завершения
+    /// методом [`Table::reap()`].
+    /// Память процесса уже освобождена, слот хранит только код завершения.
+    Zombie {
+        /// Код, с которым завершился процесс.
+        exit_code: usize,
+
+        /// Родитель завершившегося процесса.
+        parent: Pid,
+
+        /// Идентификатор завершившегося процесса.
+        pid: Pid,
+    },
 }
 
 impl fmt::Display for Slot {
@@ -57,6 +85,14 @@ impl fmt::Display for Slot {
         match self {
             Slot::Free { pid, next } => write!(formatter, "Free {{ pid: {pid}, next: {next:?} }}"),
             Slot::Used { process } => write!(formatter, "Process {}", *process.lock()),
+            Slot::Zombie {
+                exit_code,
+                parent,
+                pid,
+            } => write!(
+                formatter,
+                "Zombie {{ pid: {pid}, parent: {parent}, exit_code: {exit_code} }}",
+            ),
         }
     }
 }
@@ -102,6 +138,7 @@ impl Table {
     }
 
     /// Выделяет процессу `process` свободный слот таблицы и возвращает соответствующий [`Pid`].
+    /// Сообщает планировщику [`Scheduler`] приоритет процесса.
     /// Если свободного слота нет, возвращает ошибку [`Error::NoProcessSlot`].
     pub(super) fn allocate(mut process: Process) -> Result<Pid> {
         let mut table = TABLE.lock();
@@ -116,6 +153,7 @@ impl Table {
         };
 
         process.set_pid(pid);
+        Scheduler::set_priority(pid, process.priority());
 
         table.table[slot] = Slot::Used {
             process: Spinlock::new(process),
@@ -134,7 +172,118 @@ impl Table {
     /// При этом:
     ///   - Инкрементирует эпоху в освободившемся слоте.
     ///   - Вставляет слот в голову списка свободных слотов [`Table::free`].
-    pub fn free(mut pid: Pid) -> Result<()> {
+    ///
+    /// Удаляет и процесс, который уже завершился и ожидает [`Table::reap()`].
+    pub fn free(pid: Pid) -> Result<()> {
+        Self::remove(pid, None)
+    }
+
+    /// Удаляет завершившийся с кодом `exit_code` процесс `pid`.
+    ///
+    /// Если родитель процесса ещё существует,
+    /// оставляет в слоте код завершения [`Slot::Zombie`],
+    /// пока родитель не заберёт его методом [`Table::reap()`].
+    /// Иначе освобождает слот так же, как [`Table::free()`].
+    /// Завершившиеся потомки самого процесса `pid` больше никому не нужны,
+    /// поэтому их слоты тоже освобождаются.
+    pub fn exit(
+        pid: Pid,
+        exit_code: usize,
+    ) -> Result<()> {
+        // The lock on the process should be released before looking up its parent.
+        // Otherwise it would be held while locking TABLE, in the order opposite to Table::get().
+        let parent = Self::get(pid)?.parent();
+        let parent = parent.filter(|&parent| Self::get(parent).is_ok());
+
+        Self::remove(pid, parent.map(|parent| (parent, exit_code)))?;
+
+        while let Ok(Some(_)) = Self::reap(pid) {}
+
+        Ok(())
+    }
+
+    /// Забирает код завершения одного из завершившихся потомков процесса `parent`
+    /// и освобождает его слот.
+    /// Возвращает [`Pid`] потомка и его код завершения,
+    /// или [`None`], если потомки есть, но ни один из них ещё не завершился.
+    ///
+    /// Если у процесса `parent` нет потомков, возвращает ошибку [`Error::NoProcess`].
+    pub fn reap(parent: Pid) -> Result<Option<(Pid, usize)>> {
+        let mut has_children = false;
+        let mut zombie = None;
+
+        for slot in TABLE.lock().table.iter() {
+            match slot {
+                Slot::Used { process } => {
+                    has_children |= process.lock().parent() == Some(parent);
+                },
+                Slot::Zombie {
+                    exit_code,
+                    parent: zombie_parent,
+                    pid,
+                } if *zombie_parent == parent => {
+                    zombie = Some((*pid, *exit_code));
+                    break;
+                },
+                _ => {},
+            }
+        }
+
+        if let Some((pid, _)) = zombie {
+            Self::free(pid)?;
+            Ok(zombie)
+        } else if has_children {
+            Ok(None)
+        } else {
+            Err(NoProcess)
+        }
+    }
+
+    /// Возвращает количество завершений процессов, коды которых ждут [`Table::reap()`].
+    /// Позволяет [`Table::sleep_until_exit()`] узнать,
+    /// не завершился ли какой-нибудь процесс после проверки [`Table::reap()`].
+    pub(super) fn exit_count() -> usize {
+        EXIT_COUNT.load(Ordering::Acquire)
+    }
+
+    /// Усыпляет процесс `parent` методом [`Scheduler::sleep()`]
+    /// до завершения какого-нибудь из его потомков и возвращает `true`.
+    ///
+    /// Если после получения `exit_count` методом [`Table::exit_count()`]
+    /// какой-нибудь процесс уже завершился, не усыпляет `parent` и возвращает `false` ---
+    /// нужно снова проверить [`Table::reap()`].
+    ///
+    /// Вызывающий должен удерживать блокировку процесса `parent`
+    /// до сохранения его контекста, так же как при ожидании канала.
+    /// Иначе разбуженный процесс может быть запущен с устаревшим контекстом.
+    pub(super) fn sleep_until_exit(
+        parent: Pid,
+        exit_count: usize,
+    ) -> bool {
+        let mut waiters = WAITERS.lock();
+
+        if Self::exit_count() != exit_count {
+            return false;
+        }
+
+        if !waiters.contains(&parent) {
+            waiters.push(parent);
+        }
+        Scheduler::sleep(parent, DateTime::<Utc>::MAX_UTC);
+
+        true
+    }
+
+    /// Удаляет процесс с заданным `pid`.
+    /// Если задан `exit_status` --- родитель процесса и код его завершения,
+    /// оставляет их в слоте [`Slot::Zombie`] и будит родителя,
+    /// если он ждёт этого в [`Table::sleep_until_exit()`].
+    /// Иначе освобождает слот.
+    /// Если процесс спал, убирает его из планировщика.
+    fn remove(
+        mut pid: Pid,
+        exit_status: Option<(Pid, usize)>,
+    ) -> Result<()> {
         let mut table = TABLE.lock();
         let slot = pid.slot();
 
@@ -142,19 +291,47 @@ impl Table {
             return Err(NoProcess);
         }
 
-        let process = match &table.table[slot] {
+        let is_process = match &table.table[slot] {
             Slot::Used { process } => {
                 let locked_process = process.lock();
                 if locked_process.pid() != pid {
                     return Err(NoProcess);
                 }
-                drop(locked_process);
-                process
-            }
-            Slot::Free { .. } => return Err(NoProcess),
+                let process = &*locked_process;
+                let process_count = table.process_count - 1;
+                info!("free; slot = {process}; process_count = {process_count}");
+                true
+            },
+            Slot::Zombie { pid: zombie, .. } if *zombie == pid && exit_status.is_none() => {
+                info!("free; slot = {}", table.table[slot]);
+                false
+            },
+            _ => return Err(NoProcess),
         };
 
-        info!("free; slot = {}; process_count = {}", process.lock(), table.process_count - 1);
+        if is_process {
+            table.process_count -= 1;
+            WAITERS.lock().retain(|&waiter| waiter != pid);
+            Scheduler::cancel_sleep(pid);
+        }
+
+        if let Some((parent, exit_code)) = exit_status {
+            table.table[slot] = Slot::Zombie {
+                exit_code,
+                parent,
+                pid,
+            };
+
+            EXIT_COUNT.fetch_add(1, Ordering::Release);
+
+            let mut waiters = WAITERS.lock();
+            if let Some(position) = waiters.iter().position(|&waiter| waiter == parent) {
+                waiters.swap_remove(position);
+                Scheduler::wake(parent);
+            }
+
+            return Ok(());
+        }
 
         pid.next_epoch();
 
@@ -165,8 +342,6 @@ impl Table {
 
         table.free = Some(pid);
 
-        table.process_count -= 1;
-
         Ok(())
     }
 
@@ -190,8 +365,116 @@ impl Table {
                 }
                 Ok(process_guard)
             }
-            Slot::Free { .. } => Err(NoProcess),
+            Slot::Free { .. } | Slot::Zombie { .. } => Err(NoProcess),
+        }
+    }
+
+    /// Возвращает захваченную спин-блокировку [`SpinlockGuard`] с каким-нибудь процессом,
+    /// для которого `predicate` возвращает `true`.
+    /// Процессы, блокировки которых уже захвачены, пропускает, а не ждёт.
+    /// Поэтому может не найти подходящий процесс, даже если он есть.
+    pub(super) fn try_find(
+        predicate: impl Fn(&Process) -> bool
+    ) -> Option<SpinlockGuard<'static, Process>> {
+        TABLE.lock().table.iter().find_map(|slot| match slot {
+            Slot::Used { process } => unsafe { forge_static_lifetime(process) }
+                .try_lock()
+                .filter(|process| predicate(process)),
+            Slot::Free { .. } | Slot::Zombie { .. } => None,
+        })
+    }
+
+    /// Возвращает идентификаторы всех процессов, входящих в группу процессов `group`.
+    pub fn group(group: Pid) -> Vec<Pid> {
+        TABLE
+            .lock()
+            .table
+            .iter()
+            .filter_map(|slot| match slot {
+                Slot::Used { process } => {
+                    let process = process.lock();
+                    (process.group() == group).then(|| process.pid())
+                },
+                Slot::Free { .. } | Slot::Zombie { .. } => None,
+            })
+            .collect()
+    }
+
+    /// Удаляет все процессы группы `group`, например, всё дерево процессов,
+    /// порождённое одним процессом.
+    /// Процессы, которые в этот момент исполняются на других процессорах,
+    /// удалить сразу нельзя --- они помечаются методом [`Process::kill()`]
+    /// и завершаются при следующем входе в ядро или перепланировании.
+    /// Возвращает количество удалённых и помеченных для завершения процессов.
+    ///
+    /// Если в группе нет ни одного процесса, возвращает ошибку [`Error::NoProcess`].
+    pub fn kill_group(group: Pid) -> Result<usize> {
+        Self::for_each_in_group(group, |pid, mut process| {
+            if process.state() == State::Running {
+                process.kill();
+                return true;
+            }
+
+            drop(process);
+            Self::free(pid).is_ok()
+        })
+    }
+
+    /// Приостанавливает все процессы группы `group`.
+    /// Планировщик не выделяет им процессорного времени,
+    /// пока их работа не будет возобновлена методом [`Table::resume_group()`].
+    /// Возвращает количество процессов в группе.
+    ///
+    /// Если в группе нет ни одного процесса, возвращает ошибку [`Error::NoProcess`].
+    pub fn suspend_group(group: Pid) -> Result<usize> {
+        Self::for_each_in_group(group, |_, mut process| {
+            process.set_suspended(true);
+            true
+        })
+    }
+
+    /// Возобновляет работу всех процессов группы `group`,
+    /// приостановленных методом [`Table::suspend_group()`].
+    /// Готовые к исполнению процессы ставит в очередь планировщика.
+    /// Возвращает количество процессов в группе.
+    ///
+    /// Если в группе нет ни одного процесса, возвращает ошибку [`Error::NoProcess`].
+    pub fn resume_group(group: Pid) -> Result<usize> {
+        Self::for_each_in_group(group, |pid, mut process| {
+            process.set_suspended(false);
+            if process.state() == State::Runnable {
+                drop(process);
+                Scheduler::enqueue(pid);
+            }
+            true
+        })
+    }
+
+    /// Выполняет `action` для каждого процесса группы `group`.
+    /// Возвращает количество процессов, для которых `action` вернула `true`.
+    ///
+    /// Если в группе нет ни одного процесса, возвращает ошибку [`Error::NoProcess`].
+    fn for_each_in_group(
+        group: Pid,
+        mut action: impl FnMut(Pid, SpinlockGuard<'static, Process>) -> bool,
+    ) -> Result<usize> {
+        let members = Self::group(group);
+        if members.is_empty() {
+            return Err(NoProcess);
+        }
+
+        let mut count = 0;
+        for pid in members {
+            if let Ok(process) = Self::get(pid) &&
+                action(pid, process)
+            {
+                count += 1;
+            }
         }
+
+        info!(%group, count, "group operation");
+
+        Ok(count)
     }
 }
 
@@ -218,6 +501,14 @@ lazy_static! {
     pub(super) static ref TABLE: Spinlock<Table> = Spinlock::new(Table::default());
 }
 
+/// Количество завершений процессов, см. [`Table::exit_count()`].
+static EXIT_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Процессы, которые ждут завершения своих потомков, см. [`Table::sleep_until_exit()`].
+/// Защищены отдельной от [`TABLE`] блокировкой, так как ожидающий процесс
+/// удерживает свою блокировку, а её нельзя захватывать раньше блокировки [`TABLE`].
+static WAITERS: Spinlock<Vec<Pid>> = Spinlock::new(Vec::new());
+
 #[doc(hidden)]
 pub mod test_scaffolding {
     use crate::error::Result;
@@ -0,0 +1,101 @@
+use alloc::{
+    vec,
+    vec::Vec,
+};
+
+use super::Pid;
+
+/// [Колесо таймеров](https://en.wikipedia.org/wiki/Timing_wheel)
+/// для спящих процессов.
+///
+/// Моменты пробуждения измеряются в миллисекундах.
+/// Процесс с моментом пробуждения `deadline` лежит в корзине номер `deadline % SLOT_COUNT`.
+/// Поэтому при продвижении колеса на несколько миллисекунд
+/// достаточно просмотреть только соответствующие им корзины,
+/// а не всех спящих процессов.
+pub(super) struct TimerWheel {
+    /// Момент в миллисекундах, до которого включительно колесо уже продвинуто.
+    now: i64,
+
+    /// Корзины колеса --- спящие процессы вместе с моментами их пробуждения.
+    slots: Vec<Vec<(Pid, i64)>>,
+}
+
+impl TimerWheel {
+    /// Создаёт пустое колесо таймеров.
+    pub(super) fn new() -> Self {
+        Self {
+            now: 0,
+            slots: vec![Vec::new(); SLOT_COUNT],
+        }
+    }
+
+    /// Добавляет процесс `pid`, который нужно разбудить в момент `deadline`.
+    /// Если этот момент уже наступил, процесс будет разбужен
+    /// при следующем продвижении колеса.
+    pub(super) fn insert(
+        &mut self,
+        pid: Pid,
+        deadline: i64,
+    ) {
+        let deadline = deadline.max(self.now + 1);
+        self.slots[Self::slot(deadline)].push((pid, deadline));
+    }
+
+    /// Удаляет процесс `pid` из колеса.
+    /// Возвращает `true`, если процесс в нём был.
+    pub(super) fn remove(
+        &mut self,
+        pid: Pid,
+    ) -> bool {
+        for slot in &mut self.slots {
+            if let Some(position) = slot.iter().position(|&(sleeping, _)| sleeping == pid) {
+                slot.swap_remove(position);
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Возвращает `true`, если процесс `pid` спит.
+    pub(super) fn contains(
+        &self,
+        pid: Pid,
+    ) -> bool {
+        self.slots.iter().flatten().any(|&(sleeping, _)| sleeping == pid)
+    }
+
+    /// Продвигает колесо до момента `now` и вызывает `wake_up` для каждого процесса,
+    /// момент пробуждения которого уже наступил.
+    pub(super) fn advance(
+        &mut self,
+        now: i64,
+        mut wake_up: impl FnMut(Pid),
+    ) {
+        if now <= self.now {
+            return;
+        }
+
+        let slot_count = (now - self.now).min(SLOT_COUNT as i64);
+        for tick in self.now + 1 ..= self.now + slot_count {
+            self.slots[Self::slot(tick)].retain(|&(pid, deadline)| {
+                let expired = deadline <= now;
+                if expired {
+                    wake_up(pid);
+                }
+                !expired
+            });
+        }
+
+        self.now = now;
+    }
+
+    /// Возвращает номер корзины для момента `deadline`.
+    fn slot(deadline: i64) -> usize {
+        deadline.rem_euclid(SLOT_COUNT as i64) as usize
+    }
+}
+
+/// Количество корзин в колесе таймеров.
+const SLOT_COUNT: usize = 256;
@@ -0,0 +1,222 @@
+use core::{
+    fmt,
+    mem,
+};
+
+use chrono::Datelike;
+
+use ku::backtrace::Backtrace;
+
+use crate::{
+    Subsystems,
+    log::{
+        self,
+        error,
+        info,
+    },
+    memory::{
+        BASE_ADDRESS_SPACE,
+        FRAME_ALLOCATOR,
+        FrameGuard,
+        KERNEL_RW,
+        Page,
+    },
+    time::rtc,
+};
+
+/// Быстрая проверка работоспособности ядра сразу после загрузки.
+///
+/// Последовательно выполняет все проверки [`Check::ALL`],
+/// для которых инициализированы нужные им подсистемы `subsystems`,
+/// и пишет в журнал, а значит и в COM--порт, результат каждой из них.
+/// Остальные проверки пропускает.
+///
+/// Возвращает `true`, если все выполненные проверки прошли успешно.
+pub fn self_test(subsystems: Subsystems) -> bool {
+    let mut passed = true;
+
+    for check in Check::ALL {
+        if !subsystems.contains(check.subsystems()) {
+            info!(%check, "self-test skipped");
+            continue;
+        }
+
+        match check.run() {
+            Ok(()) => info!(%check, "self-test passed"),
+            Err(reason) => {
+                error!(%check, reason, "self-test failed");
+                passed = false;
+            },
+        }
+    }
+
+    passed
+}
+
+/// Отдельная проверка работоспособности подсистемы ядра.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Check {
+    /// Выделение и освобождение физического фрейма.
+    Frame,
+
+    /// Отображение виртуальной страницы в память,
+    /// запись в неё и чтение обратно, удаление отображения.
+    Page,
+
+    /// Получение трассировки стека.
+    Backtrace,
+
+    /// Чтение даты из
+    /// [часов реального времени (Real-time clock, RTC)](https://en.wikipedia.org/wiki/Real-time_clock).
+    Rtc,
+
+    /// Запись сообщения в журнал.
+    Log,
+}
+
+impl Check {
+    /// Все проверки в порядке их выполнения.
+    pub const ALL: [Self; 5] = [
+        Self::Frame,
+        Self::Page,
+        Self::Backtrace,
+        Self::Rtc,
+        Self::Log,
+    ];
+
+    /// Подсистемы, которые должны быть инициализированы для выполнения проверки.
+    pub fn subsystems(self) -> Subsystems {
+        match self {
+            Self::Frame | Self::Page => Subsystems::MEMORY,
+            Self::Backtrace | Self::Rtc | Self::Log => Subsystems::empty(),
+        }
+    }
+
+    /// Выполняет проверку.
+    /// В случае неудачи возвращает её причину.
+    pub fn run(self) -> Result<(), &'static str> {
+        match self {
+            Self::Frame => check_frame(),
+            Self::Page => check_page(),
+            Self::Backtrace => check_backtrace(),
+            Self::Rtc => check_rtc(),
+            Self::Log => check_log(),
+        }
+    }
+}
+
+impl fmt::Display for Check {
+    fn fmt(
+        &self,
+        formatter: &mut fmt::Formatter,
+    ) -> fmt::Result {
+        let name = match self {
+            Self::Frame => "frame",
+            Self::Page => "page",
+            Self::Backtrace => "backtrace",
+            Self::Rtc => "rtc",
+            Self::Log => "log",
+        };
+
+        write!(formatter, "{name}")
+    }
+}
+
+/// Выделяет физический фрейм и освобождает его,
+/// проверяя что количество свободных фреймов сначала уменьшилось, а потом восстановилось.
+fn check_frame() -> Result<(), &'static str> {
+    let free_frames = FRAME_ALLOCATOR.lock().count();
+
+    let frame = FrameGuard::allocate().map_err(|_| "failed to allocate a frame")?;
+    if FRAME_ALLOCATOR.lock().count() + 1 != free_frames {
+        return Err("the free frame count did not decrease");
+    }
+
+    drop(frame);
+    if FRAME_ALLOCATOR.lock().count() != free_frames {
+        return Err("the frame was not freed");
+    }
+
+    Ok(())
+}
+
+/// Отображает страницу в память, заполняет её и проверяет её содержимое.
+/// Затем удаляет отображение, проверяя что фрейм страницы освободился.
+///
+/// Промежуточные таблицы страниц, выделенные при отображении, не освобождаются.
+/// Поэтому количество свободных фреймов сравнивается с тем, что было сразу после отображения.
+fn check_page() -> Result<(), &'static str> {
+    let mut address_space = BASE_ADDRESS_SPACE.lock();
+
+    let page = address_space
+        .map_one(KERNEL_RW, || [0; WORDS_PER_PAGE])
+        .map_err(|_| "failed to map a page")?;
+    let free_frames = FRAME_ALLOCATOR.lock().count();
+
+    for (i, word) in page.iter_mut().enumerate() {
+        *word = PATTERN ^ i;
+    }
+    let is_intact = page.iter().enumerate().all(|(i, &word)| word == PATTERN ^ i);
+
+    unsafe {
+        address_space.unmap_one(page).map_err(|_| "failed to unmap the page")?;
+    }
+    drop(address_space);
+
+    if !is_intact {
+        return Err("the page content was corrupted");
+    }
+
+    if FRAME_ALLOCATOR.lock().count() != free_frames + 1 {
+        return Err("the page frame was not freed");
+    }
+
+    Ok(())
+}
+
+/// Проверяет, что трассировка текущего стека получается и не пуста.
+fn check_backtrace() -> Result<(), &'static str> {
+    let backtrace = Backtrace::current().map_err(|_| "failed to take a backtrace")?;
+
+    if backtrace.count() == 0 {
+        return Err("the backtrace has no frames");
+    }
+
+    Ok(())
+}
+
+/// Читает дату из RTC и проверяет, что год правдоподобен.
+fn check_rtc() -> Result<(), &'static str> {
+    let now = rtc::read().ok_or("failed to read the RTC")?;
+
+    if !(MIN_YEAR ..= MAX_YEAR).contains(&now.year()) {
+        return Err("the RTC year is implausible");
+    }
+
+    Ok(())
+}
+
+/// Пишет сообщение в журнал и проверяет, что оно было в нём учтено.
+fn check_log() -> Result<(), &'static str> {
+    let event_count = log::event_count();
+
+    info!("self-test log record");
+
+    if log::event_count() <= event_count {
+        return Err("the log record was not recorded");
+    }
+
+    Ok(())
+}
+
+/// Максимальный правдоподобный год по часам RTC.
+const MAX_YEAR: i32 = 2100;
+
+/// Минимальный правдоподобный год по часам RTC.
+const MIN_YEAR: i32 = 2020;
+
+/// Шаблон, которым заполняется проверяемая страница.
+const PATTERN: usize = 0x0123_4567_89AB_CDEF;
+
+/// Количество машинных слов в странице.
+const WORDS_PER_PAGE: usize = Page::SIZE / mem::size_of::<usize>();
@@ -2,25 +2,41 @@ use alloc::{
     alloc::Global,
     vec::Vec,
 };
-use core::ptr::NonNull;
+use core::{
+    ptr::NonNull,
+    slice,
+};
 
 use acpi::{
     AcpiHandler,
+    AcpiTable,
     AcpiTables,
     PhysicalMapping,
+    mcfg::Mcfg,
     platform::{
         ProcessorInfo,
         ProcessorState,
         interrupt::{
+            self,
             Apic,
             InterruptModel,
         },
     },
+    sdt::{
+        SdtHeader,
+        Signature,
+    },
 };
 
+use pci::MmioConfigSpace;
+
 use crate::{
     error::{
-        Error::Unimplemented,
+        Error::{
+            InvalidArgument,
+            Overflow,
+            Unimplemented,
+        },
         Result,
     },
     log::{
@@ -29,12 +45,24 @@ use crate::{
         warn,
     },
     memory::{
+        Block,
+        Frame,
+        NumaTopology,
         Phys,
         Phys2Virt,
+        size,
     },
 };
 
-use super::CpuId;
+use super::{
+    CpuId,
+    io_apic::{
+        ISA_IRQ_COUNT,
+        IsaIrq,
+        Polarity,
+        TriggerMode,
+    },
+};
 
 // Used in docs.
 #[allow(unused)]
@@ -58,6 +86,17 @@ pub(super) struct AcpiInfo {
 
     /// Идентификаторы доступных Application Processor.
     ap_ids: Vec<CpuId>,
+
+    /// IO APIC, который обслуживает линии шины ISA,
+    /// или [`None`], если в таблице MADT нет ни одного IO APIC.
+    io_apic: Option<IoApicInfo>,
+
+    /// Топология NUMA из таблицы SRAT.
+    /// Если таблицы SRAT нет, топология пуста.
+    numa: NumaTopology,
+
+    /// Область ECAM PCI Express из таблицы MCFG или [`None`], если таблицы MCFG нет.
+    pci_ecam: Option<PciEcam>,
 }
 
 impl AcpiInfo {
@@ -83,6 +122,9 @@ impl AcpiInfo {
             },
         };
 
+        let numa = numa_topology(&acpi_tables);
+        let pci_ecam = pci_ecam(&acpi_tables);
+
         let apic = apic(platform_info.interrupt_model)?;
         let cpus = platform_info.processor_info.ok_or(Unimplemented)?;
 
@@ -90,6 +132,9 @@ impl AcpiInfo {
             local_apic_address: Phys::new_u64(apic.local_apic_address)?,
             bsp_id: cpus.boot_processor.local_apic_id.try_into()?,
             ap_ids: usable_aps(&cpus),
+            io_apic: io_apic(&apic),
+            numa,
+            pci_ecam,
         };
 
         trace!(
@@ -121,6 +166,87 @@ impl AcpiInfo {
     pub(super) fn ap_ids(&self) -> &[CpuId] {
         &self.ap_ids
     }
+
+    /// IO APIC, который обслуживает линии шины ISA,
+    /// или [`None`], если в таблице MADT нет ни одного IO APIC.
+    pub(super) fn io_apic(&self) -> Option<IoApicInfo> {
+        self.io_apic
+    }
+
+    /// Топология NUMA из таблицы SRAT.
+    /// Если таблицы SRAT нет, топология пуста.
+    pub(super) fn numa(&self) -> &NumaTopology {
+        &self.numa
+    }
+
+    /// Область ECAM PCI Express из таблицы MCFG или [`None`], если таблицы MCFG нет.
+    pub(super) fn pci_ecam(&self) -> Option<PciEcam> {
+        self.pci_ecam
+    }
+}
+
+/// Описание [IO APIC](https://wiki.osdev.org/IOAPIC) из таблицы MADT.
+#[derive(Clone, Copy, Debug)]
+pub(super) struct IoApicInfo {
+    /// Физический адрес регистров IO APIC.
+    address: Phys,
+
+    /// Номер первого входа IO APIC в общей нумерации Global System Interrupts.
+    gsi_base: u32,
+
+    /// Подключение линий шины ISA ко входам IO APIC
+    /// с учётом записей Interrupt Source Override таблицы MADT.
+    isa_irqs: [IsaIrq; ISA_IRQ_COUNT],
+}
+
+impl IoApicInfo {
+    /// Физический адрес регистров IO APIC.
+    pub(super) fn address(&self) -> Phys {
+        self.address
+    }
+
+    /// Номер первого входа IO APIC в общей нумерации Global System Interrupts.
+    pub(super) fn gsi_base(&self) -> u32 {
+        self.gsi_base
+    }
+
+    /// Подключение линий шины ISA ко входам IO APIC
+    /// с учётом записей Interrupt Source Override таблицы MADT.
+    pub(super) fn isa_irqs(&self) -> [IsaIrq; ISA_IRQ_COUNT] {
+        self.isa_irqs
+    }
+}
+
+/// Область Enhanced Configuration Access Mechanism (ECAM) PCI Express ---
+/// отображённое в физическую память
+/// [пространство конфигурации PCI](https://en.wikipedia.org/wiki/PCI_configuration_space).
+#[derive(Clone, Copy, Debug)]
+pub(super) struct PciEcam {
+    /// Физический адрес пространства конфигурации шины [`PciEcam::start_bus`].
+    address: Phys,
+
+    /// Номер последней шины, которую обслуживает область, включительно.
+    end_bus: u8,
+
+    /// Номер первой шины, которую обслуживает область.
+    start_bus: u8,
+}
+
+impl PciEcam {
+    /// Физический адрес пространства конфигурации шины [`PciEcam::start_bus()`].
+    pub(super) fn address(&self) -> Phys {
+        self.address
+    }
+
+    /// Номер последней шины, которую обслуживает область, включительно.
+    pub(super) fn end_bus(&self) -> u8 {
+        self.end_bus
+    }
+
+    /// Номер первой шины, которую обслуживает область.
+    pub(super) fn start_bus(&self) -> u8 {
+        self.start_bus
+    }
 }
 
 /// Возвращает [`Apic`] если в `interrupt_model` из таблиц ACPI указан соответствующий
@@ -134,6 +260,180 @@ fn apic(interrupt_model: InterruptModel<'_, Global>) -> Result<Apic<'_, Global>>
     }
 }
 
+/// Возвращает описание IO APIC с наименьшим номером первого входа ---
+/// именно к нему подключены линии шины ISA.
+/// Если IO APIC в таблице MADT нет, возвращает [`None`].
+///
+/// Записи Interrupt Source Override могут подключить линию ISA к другому входу
+/// и задать для неё полярность и режим срабатывания.
+/// Значения "как у шины" соответствуют стандартным для ISA
+/// высокому активному уровню и срабатыванию по фронту.
+fn io_apic(apic: &Apic<'_, Global>) -> Option<IoApicInfo> {
+    let io_apics = &apic.io_apics;
+    let io_apic = io_apics.iter().min_by_key(|io_apic| io_apic.global_system_interrupt_base)?;
+    let mut isa_irqs = IsaIrq::defaults();
+
+    for source_override in apic.interrupt_source_overrides.iter() {
+        let Some(isa_irq) = isa_irqs.get_mut(usize::from(source_override.isa_source)) else {
+            warn!(?source_override, "invalid ACPI interrupt source override");
+            continue;
+        };
+
+        let polarity = match source_override.polarity {
+            interrupt::Polarity::ActiveLow => Polarity::ActiveLow,
+            interrupt::Polarity::ActiveHigh | interrupt::Polarity::SameAsBus =>
+                Polarity::ActiveHigh,
+        };
+        let trigger_mode = match source_override.trigger_mode {
+            interrupt::TriggerMode::Level => TriggerMode::Level,
+            interrupt::TriggerMode::Edge | interrupt::TriggerMode::SameAsBus => TriggerMode::Edge,
+        };
+
+        *isa_irq = IsaIrq::with_override(
+            source_override.global_system_interrupt,
+            polarity,
+            trigger_mode,
+        );
+    }
+
+    Some(IoApicInfo {
+        address: Phys::new_u64(io_apic.address.into()).ok()?,
+        gsi_base: io_apic.global_system_interrupt_base,
+        isa_irqs,
+    })
+}
+
+/// Возвращает область ECAM PCI Express для сегмента `0` из таблицы MCFG в `acpi_tables`.
+/// Если таблицы MCFG нет или она не описывает сегмент `0`, возвращает [`None`].
+///
+/// Адрес в таблице MCFG соответствует шине `0`,
+/// даже если область обслуживает шины начиная с ненулевой.
+fn pci_ecam(acpi_tables: &AcpiTables<AcpiMapper>) -> Option<PciEcam> {
+    let mcfg = match acpi_tables.find_table::<Mcfg>() {
+        Ok(mcfg) => mcfg,
+        Err(acpi_error) => {
+            info!(?acpi_error, "no ACPI MCFG table");
+            return None;
+        },
+    };
+
+    let entry = mcfg.entries().iter().find(|entry| entry.pci_segment_group == 0)?;
+    let (start_bus, end_bus) = (entry.bus_number_start, entry.bus_number_end);
+    let bus_offset = usize::from(start_bus) * MmioConfigSpace::BUS_SIZE;
+
+    let address = Phys::new_u64(entry.base_address).and_then(|address| address + bus_offset);
+    match address {
+        Ok(address) if start_bus <= end_bus => Some(PciEcam {
+            address,
+            end_bus,
+            start_bus,
+        }),
+        _ => {
+            warn!(?entry, "invalid ACPI MCFG entry");
+            None
+        },
+    }
+}
+
+/// Возвращает топологию NUMA по таблице SRAT из `acpi_tables`.
+/// Если таблицы SRAT нет или её не удалось разобрать, возвращает пустую топологию ---
+/// систему без NUMA.
+fn numa_topology(acpi_tables: &AcpiTables<AcpiMapper>) -> NumaTopology {
+    let srat = match acpi_tables.find_table::<Srat>() {
+        Ok(srat) => srat,
+        Err(acpi_error) => {
+            info!(?acpi_error, "no ACPI SRAT table");
+            return NumaTopology::default();
+        },
+    };
+
+    let length = srat.header().length;
+    let srat = unsafe {
+        slice::from_raw_parts(
+            srat.virtual_start().as_ptr().cast::<u8>(),
+            size::from(length),
+        )
+    };
+
+    parse_srat(srat).unwrap_or_else(|error| {
+        warn!(?error, "invalid ACPI SRAT table");
+        NumaTopology::default()
+    })
+}
+
+/// Разбирает таблицу System Resource Affinity Table (SRAT), которая вместе с заголовком
+/// записана в `srat`, и возвращает описанную в ней топологию NUMA.
+/// Выключенные записи пропускает.
+///
+/// Возвращает ошибку [`Error::InvalidArgument`], если таблица обрезана
+/// или содержит запись некорректной длины.
+///
+/// [Спецификация ACPI](https://uefi.org/sites/default/files/resources/ACPI_Spec_6_5_Aug29.pdf),
+/// 5.2.16 "System Resource Affinity Table (SRAT)".
+pub(super) fn parse_srat(srat: &[u8]) -> Result<NumaTopology> {
+    let mut numa = NumaTopology::default();
+    let mut entries = srat.get(SRAT_ENTRIES_OFFSET ..).ok_or(InvalidArgument)?;
+
+    while let [entry_type, length, ..] = *entries {
+        let length = usize::from(length);
+        if length < SRAT_ENTRY_HEADER_SIZE {
+            return Err(InvalidArgument);
+        }
+        let entry = entries.get(.. length).ok_or(InvalidArgument)?;
+
+        match entry_type {
+            SRAT_PROCESSOR_AFFINITY => {
+                let flags = u32::from_le_bytes(field(entry, 4)?);
+                if flags & SRAT_ENABLED != 0 {
+                    let [domain_lo, cpu] = field(entry, 2)?;
+                    let [domain_1, domain_2, domain_3] = field(entry, 9)?;
+                    let domain = u32::from_le_bytes([domain_lo, domain_1, domain_2, domain_3]);
+                    numa.add_cpu(cpu, domain)?;
+                }
+            },
+            SRAT_MEMORY_AFFINITY => {
+                let flags = u32::from_le_bytes(field(entry, 28)?);
+                if flags & SRAT_ENABLED != 0 {
+                    let domain = u32::from_le_bytes(field(entry, 2)?);
+                    let start = u64::from_le_bytes(field(entry, 8)?);
+                    let size = u64::from_le_bytes(field(entry, 16)?);
+                    let end = start.checked_add(size).ok_or(Overflow)?;
+                    let frame_size = size::into_u64(Frame::SIZE);
+                    let frames =
+                        Block::<Frame>::from_index_u64(start / frame_size, end / frame_size)?;
+                    numa.add_memory(frames, domain)?;
+                }
+            },
+            SRAT_X2APIC_AFFINITY => {
+                let flags = u32::from_le_bytes(field(entry, 12)?);
+                let x2apic_id = u32::from_le_bytes(field(entry, 8)?);
+                if flags & SRAT_ENABLED != 0 &&
+                    let Ok(cpu) = CpuId::try_from(x2apic_id)
+                {
+                    let domain = u32::from_le_bytes(field(entry, 4)?);
+                    numa.add_cpu(cpu, domain)?;
+                }
+            },
+            _ => {},
+        }
+
+        entries = &entries[length ..];
+    }
+
+    Ok(numa)
+}
+
+/// Возвращает `N` байт записи `entry` таблицы SRAT, начиная со смещения `offset`.
+fn field<const N: usize>(
+    entry: &[u8],
+    offset: usize,
+) -> Result<[u8; N]> {
+    entry
+        .get(offset .. offset + N)
+        .and_then(|field| field.try_into().ok())
+        .ok_or(InvalidArgument)
+}
+
 /// Возвращает идентификаторы доступных Application Processor по входной структуре `cpus`.
 fn usable_aps(cpus: &ProcessorInfo<'_, Global>) -> Vec<CpuId> {
     cpus.application_processors
@@ -144,6 +444,23 @@ fn usable_aps(cpus: &ProcessorInfo<'_, Global>) -> Vec<CpuId> {
         .collect()
 }
 
+/// Заголовок таблицы System Resource Affinity Table (SRAT),
+/// которая описывает топологию NUMA.
+/// Библиотека [`acpi`] её не разбирает, поэтому она разбирается в [`parse_srat()`].
+#[repr(C)]
+struct Srat {
+    /// Стандартный заголовок таблицы ACPI.
+    header: SdtHeader,
+}
+
+unsafe impl AcpiTable for Srat {
+    const SIGNATURE: Signature = Signature::SRAT;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
 /// Структура для отображения физической памяти в виртуальную, которая нужна библиотеке [`acpi`].
 #[derive(Clone, Copy)]
 struct AcpiMapper {
@@ -183,3 +500,22 @@ impl AcpiHandler for AcpiMapper {
     fn unmap_physical_region<T>(_: &PhysicalMapping<Self, T>) {
     }
 }
+
+/// Признак включённой записи таблицы SRAT.
+const SRAT_ENABLED: u32 = 1 << 0;
+
+/// Размер заголовка записи таблицы SRAT --- её типа и длины.
+const SRAT_ENTRY_HEADER_SIZE: usize = 2;
+
+/// Смещение первой записи таблицы SRAT от начала таблицы ---
+/// после стандартного заголовка и зарезервированных полей.
+const SRAT_ENTRIES_OFFSET: usize = 48;
+
+/// Тип записи таблицы SRAT о диапазоне физической памяти.
+const SRAT_MEMORY_AFFINITY: u8 = 1;
+
+/// Тип записи таблицы SRAT о процессоре, заданном идентификатором local APIC.
+const SRAT_PROCESSOR_AFFINITY: u8 = 0;
+
+/// Тип записи таблицы SRAT о процессоре, заданном идентификатором local x2APIC.
+const SRAT_X2APIC_AFFINITY: u8 = 2;
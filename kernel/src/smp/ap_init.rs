@@ -58,6 +58,7 @@ use crate::{
 use super::{
     LocalApic,
     cpu::Cpu,
+    set_online,
 };
 
 // Used in docs.
@@ -383,12 +384,15 @@ extern "C" fn ap_kernel_main(cpu: &mut Cpu, // rdi
 
     cpu.set_gs();
     cpu.set_tss();
+    cpu.set_tsc_aux();
 
     IDT.load();
     interrupts::enable();
 
     syscall::init();
 
+    cpu.synchronize_tsc();
+    set_online(cpu.id());
     cpu.signal_initialized();
 
     info!(cpu = cpu.id(), "report for duty");
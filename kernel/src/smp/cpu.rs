@@ -5,12 +5,25 @@ use core::{
     mem,
     sync::atomic::{
         AtomicBool,
+        AtomicUsize,
         Ordering,
     },
 };
 
 use chrono::Duration;
 use memoffset::offset_of;
+
+use ku::{
+    sync::Spinlock,
+    time::{
+        CpuUsage,
+        Tsc,
+    },
+};
+use x86::msr::{
+    self,
+    IA32_TSC_AUX,
+};
 use x86_64::{
     PrivilegeLevel,
     VirtAddr,
@@ -20,6 +33,7 @@ use x86_64::{
 };
 
 use crate::{
+    SYSTEM_INFO,
     error::{
         Error::{
             NoProcess,
@@ -30,6 +44,7 @@ use crate::{
     log::{
         debug,
         error,
+        info,
     },
     memory::{
         BASE_ADDRESS_SPACE,
@@ -53,6 +68,7 @@ use super::{
     CpuId,
     LocalApic,
     SavedMemory,
+    tsc_sync::TscSync,
 };
 
 // Used in docs.
@@ -75,6 +91,7 @@ pub(super) fn init(
     let cpu = &mut cpus[usize::from(current_cpu)];
     cpu.set_gs();
     cpu.set_tss();
+    cpu.set_tsc_aux();
     cpu.signal_initialized();
 
     Ok(cpus)
@@ -122,6 +139,11 @@ pub(crate) struct Cpu {
     /// Идентификатор данного CPU, копия идентификатора его Local APIC --- [`LocalApic::id()`].
     id: CpuId,
 
+    /// Количество пробуждений данного CPU из цикла простоя планировщика.
+    ///
+    /// Каждое пробуждение вызвано прерыванием, как правило --- очередным тиком таймера.
+    idle_ticks: AtomicUsize,
+
     /// Признак инициализированности данного CPU.
     initialized: AtomicBool,
 
@@ -141,12 +163,26 @@ pub(crate) struct Cpu {
     /// Адрес структуры [`Cpu`] для данного CPU.
     this: Virt,
 
+    /// Момент, когда текущий процесс получил этот CPU.
+    time_slice_start: Tsc,
+
+    /// Количество тиков таймера с момента [`Cpu::time_slice_start`].
+    time_slice_ticks: usize,
+
+    /// Измерение сдвига счётчика тактов данного CPU
+    /// относительно счётчика тактов Bootstrap Processor.
+    tsc_sync: TscSync,
+
     /// [Task State Segment](https://en.wikipedia.org/wiki/Task_state_segment) (TSS) данного CPU.
     ///
     /// В TSS описано, где находится стек ядра для текущего процессора.
     /// Стеки у разных CPU разные, поэтому и TSS тоже должны быть разные.
     tss: TaskStateSegment,
 
+    /// Загрузка данного CPU в скользящем окне из последних интервалов
+    /// исполнения процессов и простоя.
+    usage: Spinlock<CpuUsage>,
+
     /// Временное хранилище для контекста принудительно вытесненного с этого CPU процесса.
     user_context: Option<ModeContext>,
 }
@@ -163,12 +199,17 @@ impl Cpu {
 
         let mut result = Self {
             id,
+            idle_ticks: AtomicUsize::new(0),
             initialized: AtomicBool::new(false),
             current_process: None,
             kernel_stack: &stacks[0],
             page_fault_stack: &stacks[1],
             this: Virt::default(),
+            time_slice_start: Tsc::new(0),
+            time_slice_ticks: 0,
+            tsc_sync: TscSync::default(),
             tss: TaskStateSegment::new(),
+            usage: Spinlock::new(CpuUsage::new()),
             user_context: None,
         };
 
@@ -212,6 +253,43 @@ impl Cpu {
         cpu.current_process = process;
     }
 
+    /// Начинает отсчёт кванта времени процесса, получающего этот CPU.
+    ///
+    /// # Panics
+    ///
+    /// Паникует, если обнаруживает, что регистр `GS` этого CPU ещё не был инициализирован
+    /// методом [`Cpu::set_gs()`].
+    pub(crate) fn start_time_slice() {
+        let cpu = unsafe { Self::get() };
+        cpu.time_slice_start = Tsc::now();
+        cpu.time_slice_ticks = 0;
+    }
+
+    /// Учитывает очередной тик таймера в кванте времени текущего процесса.
+    /// Возвращает `true`, если с начала кванта прошло не меньше `time_slice`
+    /// и процесс пора вытеснять.
+    ///
+    /// # Panics
+    ///
+    /// Паникует, если обнаруживает, что регистр `GS` этого CPU ещё не был инициализирован
+    /// методом [`Cpu::set_gs()`].
+    pub(crate) fn count_time_slice_tick(time_slice: Duration) -> bool {
+        let cpu = unsafe { Self::get() };
+        cpu.time_slice_ticks += 1;
+        cpu.time_slice_start.has_passed(time_slice)
+    }
+
+    /// Количество тиков таймера в кванте времени текущего процесса.
+    ///
+    /// # Panics
+    ///
+    /// Паникует, если обнаруживает, что регистр `GS` этого CPU ещё не был инициализирован
+    /// методом [`Cpu::set_gs()`].
+    pub(crate) fn time_slice_ticks() -> usize {
+        let cpu = unsafe { Self::get() };
+        cpu.time_slice_ticks
+    }
+
     /// Возвращает контекст ядра для данного [`Cpu`], то есть с его стеком.
     /// В качестве `rip` использует функцию [`Registers::switch_from()`],
     /// то есть при переходе в этот контекст будет запущена именно она.
@@ -250,6 +328,82 @@ impl Cpu {
         cpu.user_context.take()
     }
 
+    /// Учитывает очередное пробуждение данного CPU из цикла простоя планировщика.
+    ///
+    /// # Panics
+    ///
+    /// Паникует, если обнаруживает, что регистр `GS` этого CPU ещё не был инициализирован
+    /// методом [`Cpu::set_gs()`].
+    pub(crate) fn count_idle_tick() {
+        let cpu = unsafe { Self::get() };
+        cpu.idle_ticks.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Количество пробуждений данного CPU из цикла простоя планировщика.
+    ///
+    /// # Panics
+    ///
+    /// Паникует, если обнаруживает, что регистр `GS` этого CPU ещё не был инициализирован
+    /// методом [`Cpu::set_gs()`].
+    pub(crate) fn idle_ticks() -> usize {
+        let cpu = unsafe { Self::get() };
+        cpu.idle_ticks.load(Ordering::Relaxed)
+    }
+
+    /// Учитывает в загрузке данного CPU интервал от `start` до текущего момента,
+    /// в течение которого CPU исполнял процесс.
+    ///
+    /// # Panics
+    ///
+    /// Паникует, если обнаруживает, что регистр `GS` этого CPU ещё не был инициализирован
+    /// методом [`Cpu::set_gs()`].
+    pub(crate) fn record_busy(start: Tsc) {
+        let cpu = unsafe { Self::get() };
+        cpu.usage.lock().record_busy(start, Tsc::now());
+    }
+
+    /// Учитывает в загрузке данного CPU интервал от `start` до текущего момента,
+    /// в течение которого CPU простаивал.
+    ///
+    /// # Panics
+    ///
+    /// Паникует, если обнаруживает, что регистр `GS` этого CPU ещё не был инициализирован
+    /// методом [`Cpu::set_gs()`].
+    pub(crate) fn record_idle(start: Tsc) {
+        let cpu = unsafe { Self::get() };
+        cpu.usage.lock().record_idle(start, Tsc::now());
+    }
+
+    /// Загрузка данного CPU в скользящем окне из последних интервалов
+    /// исполнения процессов и простоя.
+    pub(super) fn usage(&self) -> CpuUsage {
+        *self.usage.lock()
+    }
+
+    /// Идентификатор текущего CPU, прочитанный из его структуры [`Cpu`],
+    /// на которую указывает регистр
+    /// [`GS`](https://wiki.osdev.org/CPU_Registers_x86-64#FS.base.2C_GS.base).
+    ///
+    /// # Panics
+    ///
+    /// Паникует, если обнаруживает, что регистр `GS` этого CPU ещё не был инициализирован
+    /// методом [`Cpu::set_gs()`].
+    pub(super) fn current_id() -> CpuId {
+        let cpu = unsafe { Self::get() };
+        cpu.id
+    }
+
+    /// Идентификатор текущего CPU, как и [`Cpu::current_id()`].
+    /// Если регистр `GS` этого CPU ещё не был инициализирован
+    /// методом [`Cpu::set_gs()`], возвращает [`None`].
+    pub(crate) fn try_current_id() -> Option<CpuId> {
+        if Virt::from(GsBase::read()) == Virt::default() {
+            None
+        } else {
+            Some(Self::current_id())
+        }
+    }
+
     /// Идентификатор данного CPU, копия идентификатора его Local APIC --- [`LocalApic::id()`].
     pub(super) fn id(&self) -> CpuId {
         self.id
@@ -296,6 +450,37 @@ impl Cpu {
         }
     }
 
+    /// Записывает идентификатор текущего CPU в регистр
+    /// [`IA32_TSC_AUX`](https://wiki.osdev.org/Model_Specific_Registers).
+    ///
+    /// Инструкция [`rdtscp`](https://www.felixcloutier.com/x86/rdtscp)
+    /// возвращает его вместе со счётчиком тактов процессора.
+    /// Так пространство пользователя атомарно узнаёт, на каком CPU был прочитан счётчик,
+    /// см. [`ku::time::tscp()`].
+    ///
+    /// Если процессор не поддерживает `rdtscp`, регистра `IA32_TSC_AUX` у него нет,
+    /// и запись в него привела бы к General Protection Fault.
+    pub(super) fn set_tsc_aux(&self) {
+        if !time::has_rdtscp() {
+            return;
+        }
+
+        unsafe {
+            msr::wrmsr(IA32_TSC_AUX, self.id.into());
+        }
+    }
+
+    /// Измеряет сдвиг счётчика тактов текущего Application Processor
+    /// относительно счётчика тактов Bootstrap Processor
+    /// и записывает поправку к нему в [`ku::SystemInfo::tsc_offsets()`].
+    /// Bootstrap Processor в это время должен ждать инициализации данного CPU
+    /// в [`Cpu::wait_initialized()`].
+    pub(super) fn synchronize_tsc(&self) {
+        let (tsc_offset, round_trip) = self.tsc_sync.measure();
+        SYSTEM_INFO.tsc_offsets().set(self.id.into(), tsc_offset);
+        info!(cpu = self.id, tsc_offset, round_trip, "TSC synchronized");
+    }
+
     /// Сигнализирует запускающему процессору Bootstrap Processor,
     /// что Application Processor закончил свою инициализацию.
     pub(super) fn signal_initialized(&self) {
@@ -306,6 +491,8 @@ impl Cpu {
 
     /// Ждёт пока запускаемый Application Processor не завершит свою инициализацию.
     /// Если этого не произойдёт за отведённый `timeout`, возвращает ошибку [`Error::Timeout`].
+    /// Пока ждёт, отвечает на запросы Application Processor
+    /// при измерении сдвига его счётчика тактов в [`Cpu::synchronize_tsc()`].
     ///
     /// Аргумент `_saved_memory` хранит исходное состояние памяти,
     /// которой Application Processor может пользоваться по своему усмотрению во время загрузки.
@@ -329,6 +516,7 @@ impl Cpu {
             if self.initialized.load(Ordering::Acquire) {
                 return Ok(());
             }
+            self.tsc_sync.serve();
             hint::spin_loop();
         }
 
@@ -378,4 +566,8 @@ pub mod test_scaffolding {
         let cpu = unsafe { Cpu::get() };
         cpu.id()
     }
+
+    pub fn idle_ticks() -> usize {
+        Cpu::idle_ticks()
+    }
 }
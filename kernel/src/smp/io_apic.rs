@@ -0,0 +1,437 @@
+use core::{
+    array,
+    sync::atomic::{
+        AtomicU16,
+        Ordering,
+    },
+};
+
+use bitflags::bitflags;
+use x86_64::instructions::interrupts;
+
+use ku::sync::spinlock::Spinlock;
+
+use crate::{
+    error::{
+        Error::{
+            self,
+            InvalidArgument,
+        },
+        Result,
+    },
+    log::{
+        info,
+        warn,
+    },
+    memory::{
+        BASE_ADDRESS_SPACE,
+        Frame,
+        KERNEL_MMIO,
+        Page,
+        Phys,
+        Virt,
+    },
+    trap::Trap,
+};
+
+use super::{
+    CpuId,
+    acpi_info::IoApicInfo,
+};
+
+/// Полярность сигнала на линии прерывания.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) enum Polarity {
+    /// Активный уровень сигнала --- высокий, стандартный вариант для шины ISA.
+    ActiveHigh,
+
+    /// Активный уровень сигнала --- низкий.
+    ActiveLow,
+}
+
+/// Режим срабатывания прерывания.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) enum TriggerMode {
+    /// Прерывание срабатывает по фронту сигнала, стандартный вариант для шины ISA.
+    Edge,
+
+    /// Прерывание срабатывает по уровню сигнала.
+    Level,
+}
+
+/// Подключение линии прерывания шины ISA ко входу IO APIC.
+///
+/// По умолчанию линия `irq` подключена ко входу с тем же номером
+/// в общей нумерации Global System Interrupts (GSI),
+/// срабатывает по фронту и имеет высокий активный уровень.
+/// Таблица MADT [ACPI](https://en.wikipedia.org/wiki/ACPI) может переопределить это
+/// записями Interrupt Source Override.
+/// Например, в QEMU таймер PIT подключён ко входу `2`, а не `0`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) struct IsaIrq {
+    /// Номер входа в общей нумерации Global System Interrupts.
+    gsi: u32,
+
+    /// Полярность сигнала.
+    polarity: Polarity,
+
+    /// Режим срабатывания.
+    trigger_mode: TriggerMode,
+}
+
+impl IsaIrq {
+    /// Подключение линии с номером `irq` шины ISA по умолчанию.
+    pub(super) fn new(irq: u8) -> Self {
+        Self {
+            gsi: irq.into(),
+            polarity: Polarity::ActiveHigh,
+            trigger_mode: TriggerMode::Edge,
+        }
+    }
+
+    /// Подключение линии ко входу `gsi` с полярностью `polarity` и режимом срабатывания
+    /// `trigger_mode`.
+    pub(super) fn with_override(
+        gsi: u32,
+        polarity: Polarity,
+        trigger_mode: TriggerMode,
+    ) -> Self {
+        Self {
+            gsi,
+            polarity,
+            trigger_mode,
+        }
+    }
+
+    /// Подключения всех линий шины ISA по умолчанию.
+    pub(super) fn defaults() -> [Self; ISA_IRQ_COUNT] {
+        array::from_fn(|irq| Self::new(irq.try_into().unwrap()))
+    }
+}
+
+/// [Memory--mapped I/O (MMIO)](https://en.wikipedia.org/wiki/Memory-mapped_I/O)
+/// для работы с [IO APIC](https://wiki.osdev.org/IOAPIC),
+/// который заменяет устаревшую каскадную пару
+/// [PIC 8259](https://en.wikipedia.org/wiki/Intel_8259).
+///
+/// Прерывания шины ISA направляются на те же номера [`Trap`],
+/// которые использовались для PIC 8259, так что их обработчики не меняются.
+/// Меняется только сигнал о завершении обработки прерывания ---
+/// он посылается в local APIC, см. [`IoApic::is_routed()`].
+///
+/// <https://pdos.csail.mit.edu/6.828/2018/readings/ia32/ioapic.pdf>
+#[derive(Debug)]
+pub(crate) struct IoApic {
+    /// Виртуальный адрес регистров IO APIC.
+    registers: Virt,
+
+    /// Номер первого входа IO APIC в общей нумерации Global System Interrupts.
+    gsi_base: u32,
+
+    /// Количество входов IO APIC, то есть записей в его таблице перенаправления.
+    entry_count: u32,
+
+    /// Подключение линий шины ISA ко входам IO APIC.
+    isa_irqs: [IsaIrq; ISA_IRQ_COUNT],
+}
+
+impl IoApic {
+    /// Инициализирует IO APIC по его описанию `io_apic_info` из таблицы MADT.
+    ///
+    /// Все входы IO APIC сначала маскируются.
+    /// Затем все линии шины ISA, кроме каскадной,
+    /// направляются на процессор `destination`.
+    /// После этого обе микросхемы PIC 8259 маскируются целиком.
+    ///
+    /// Возвращает ошибку [`Error::InvalidArgument`],
+    /// если какая-нибудь из линий ISA подключена ко входу, которого у этого IO APIC нет.
+    pub(super) fn init(
+        io_apic_info: IoApicInfo,
+        destination: CpuId,
+    ) -> Result<()> {
+        let address = io_apic_info.address();
+        let mut io_apic = Self {
+            registers: Self::map(address)?,
+            gsi_base: io_apic_info.gsi_base(),
+            entry_count: 0,
+            isa_irqs: io_apic_info.isa_irqs(),
+        };
+
+        io_apic.entry_count = ((io_apic.read(VERSION) >> MAX_ENTRY_SHIFT) & MAX_ENTRY_MASK) + 1;
+
+        for entry in 0 .. io_apic.entry_count {
+            io_apic.write_entry(entry, RedirectionEntry::MASKED.bits());
+        }
+
+        for irq in Self::isa_irqs() {
+            io_apic.entry(irq)?;
+        }
+
+        interrupts::without_interrupts(|| {
+            let mut routed = 0;
+
+            for irq in Self::isa_irqs() {
+                io_apic.route(irq, destination)?;
+                routed |= 1 << irq;
+            }
+
+            for irq in 0 .. pic8259::PIC_INTERRUPT_COUNT {
+                unsafe {
+                    pic8259::mask(irq);
+                }
+            }
+
+            ROUTED_IRQS.store(routed, Ordering::Relaxed);
+
+            Ok::<_, Error>(())
+        })?;
+
+        info!(
+            %address,
+            gsi_base = io_apic.gsi_base,
+            entry_count = io_apic.entry_count,
+            cpu = destination,
+            "IO APIC init",
+        );
+
+        *IO_APIC.lock() = Some(io_apic);
+
+        Ok(())
+    }
+
+    /// Возвращает `true`, если линия `irq` шины ISA обслуживается IO APIC,
+    /// а не PIC 8259.
+    /// Для таких линий сигнал о завершении обработки прерывания
+    /// нужно посылать в local APIC.
+    pub(crate) fn is_routed(irq: u8) -> bool {
+        usize::from(irq) < ISA_IRQ_COUNT && ROUTED_IRQS.load(Ordering::Relaxed) & (1 << irq) != 0
+    }
+
+    /// Направляет прерывания линии `irq` шины ISA на процессор `cpu`.
+    ///
+    /// Возвращает ошибку [`Error::InvalidArgument`], если линия `irq` не обслуживается IO APIC,
+    /// в том числе если IO APIC не инициализирован.
+    pub(crate) fn set_destination(
+        irq: u8,
+        cpu: CpuId,
+    ) -> Result<()> {
+        if !Self::is_routed(irq) {
+            return Err(InvalidArgument);
+        }
+
+        IO_APIC.lock().as_mut().ok_or(InvalidArgument)?.route(irq, cpu)
+    }
+
+    /// Возвращает процессор, на который направлены прерывания линии `irq` шины ISA.
+    ///
+    /// Возвращает ту же ошибку, что и [`IoApic::set_destination()`].
+    pub(crate) fn destination(irq: u8) -> Result<CpuId> {
+        if !Self::is_routed(irq) {
+            return Err(InvalidArgument);
+        }
+
+        let io_apic = IO_APIC.lock();
+        let io_apic = io_apic.as_ref().ok_or(InvalidArgument)?;
+        let entry = io_apic.read_entry(io_apic.entry(irq)?);
+
+        Ok((entry >> DESTINATION_SHIFT).try_into()?)
+    }
+
+    /// Возвращает номера линий шины ISA, которые обслуживает IO APIC, ---
+    /// все, кроме каскадной линии PIC 8259.
+    fn isa_irqs() -> impl Iterator<Item = u8> {
+        (0 .. ISA_IRQ_COUNT as u8).filter(|&irq| irq != CASCADE_IRQ)
+    }
+
+    /// Отображает регистры IO APIC, которые находятся по физическому адресу `address`,
+    /// в виртуальную память с флагами [`KERNEL_MMIO`] и возвращает их виртуальный адрес.
+    fn map(address: Phys) -> Result<Virt> {
+        let frame = Frame::containing(address);
+        let offset = address.into_usize() % Frame::SIZE;
+
+        let mut address_space = BASE_ADDRESS_SPACE.lock();
+        let page = address_space.allocate(Page::layout_array(1), KERNEL_MMIO)?.start_element();
+
+        unsafe {
+            address_space.map_page_to_frame(page, frame, KERNEL_MMIO)?;
+        }
+
+        page.address() + offset
+    }
+
+    /// Записывает в таблицу перенаправления запись для линии `irq` шины ISA,
+    /// которая направляет её прерывания на процессор `cpu`.
+    fn route(
+        &mut self,
+        irq: u8,
+        cpu: CpuId,
+    ) -> Result<()> {
+        let isa_irq = self.isa_irqs[usize::from(irq)];
+        let vector = u64::try_from(usize::from(Trap::Pit) + usize::from(irq))?;
+
+        let mut flags = RedirectionEntry::empty();
+        if isa_irq.polarity == Polarity::ActiveLow {
+            flags |= RedirectionEntry::ACTIVE_LOW;
+        }
+        if isa_irq.trigger_mode == TriggerMode::Level {
+            flags |= RedirectionEntry::LEVEL_TRIGGERED;
+        }
+
+        let entry = (u64::from(cpu) << DESTINATION_SHIFT) | flags.bits() | vector;
+
+        let index = self.entry(irq)?;
+        self.write_entry(index, entry);
+
+        Ok(())
+    }
+
+    /// Возвращает номер записи в таблице перенаправления для линии `irq` шины ISA.
+    fn entry(
+        &self,
+        irq: u8,
+    ) -> Result<u32> {
+        let gsi = self.isa_irqs[usize::from(irq)].gsi;
+
+        match gsi.checked_sub(self.gsi_base) {
+            Some(entry) if entry < self.entry_count => Ok(entry),
+            _ => {
+                warn!(irq, gsi, "ISA IRQ is not connected to IO APIC");
+                Err(InvalidArgument)
+            },
+        }
+    }
+
+    /// Читает запись номер `index` таблицы перенаправления.
+    fn read_entry(
+        &self,
+        index: u32,
+    ) -> u64 {
+        let register = REDIRECTION_TABLE + 2 * index;
+        let lo = u64::from(self.read(register));
+        let hi = u64::from(self.read(register + 1));
+
+        (hi << u32::BITS) | lo
+    }
+
+    /// Записывает `entry` в запись номер `index` таблицы перенаправления.
+    /// Старшая половина, задающая процессор, записывается первой,
+    /// чтобы запись не начала действовать с неверным процессором.
+    fn write_entry(
+        &mut self,
+        index: u32,
+        entry: u64,
+    ) {
+        let register = REDIRECTION_TABLE + 2 * index;
+        self.write(register + 1, (entry >> u32::BITS) as u32);
+        self.write(register, entry as u32);
+    }
+
+    /// Читает регистр IO APIC номер `register`.
+    fn read(
+        &self,
+        register: u32,
+    ) -> u32 {
+        unsafe {
+            self.register(SELECT_OFFSET).write_volatile(register);
+            self.register(WINDOW_OFFSET).read_volatile()
+        }
+    }
+
+    /// Записывает `value` в регистр IO APIC номер `register`.
+    fn write(
+        &mut self,
+        register: u32,
+        value: u32,
+    ) {
+        unsafe {
+            self.register(SELECT_OFFSET).write_volatile(register);
+            self.register(WINDOW_OFFSET).write_volatile(value);
+        }
+    }
+
+    /// Возвращает указатель на регистр IOREGSEL или IOWIN,
+    /// который находится по смещению `offset` от начала регистров IO APIC.
+    fn register(
+        &self,
+        offset: usize,
+    ) -> *mut u32 {
+        (self.registers + offset)
+            .and_then(Virt::try_into_mut_ptr)
+            .expect("the IO APIC registers are mapped and aligned")
+    }
+}
+
+bitflags! {
+    /// Флаги записи таблицы перенаправления IO APIC.
+    /// Номер прерывания занимает младший байт записи,
+    /// номер процессора --- старший.
+    /// Остальные поля --- фиксированный режим доставки
+    /// и физическая адресация процессора --- нулевые.
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    struct RedirectionEntry: u64 {
+        /// Активный уровень сигнала --- низкий.
+        const ACTIVE_LOW = 1 << 13;
+
+        /// Прерывание срабатывает по уровню сигнала, а не по фронту.
+        const LEVEL_TRIGGERED = 1 << 15;
+
+        /// Прерывание замаскировано.
+        const MASKED = 1 << 16;
+    }
+}
+
+/// Линии шины ISA, которые обслуживает IO APIC, см. [`IoApic::is_routed()`].
+static ROUTED_IRQS: AtomicU16 = AtomicU16::new(0);
+
+/// Единственный поддерживаемый IO APIC.
+static IO_APIC: Spinlock<Option<IoApic>> = Spinlock::new(None);
+
+/// Каскадная линия PIC 8259, которая не используется с IO APIC.
+const CASCADE_IRQ: u8 = 2;
+
+/// Сдвиг номера процессора в записи таблицы перенаправления.
+const DESTINATION_SHIFT: u32 = 56;
+
+/// Количество линий прерываний шины ISA.
+pub(super) const ISA_IRQ_COUNT: usize = 16;
+
+/// Маска номера последней записи таблицы перенаправления в регистре [`VERSION`].
+const MAX_ENTRY_MASK: u32 = 0xFF;
+
+/// Сдвиг номера последней записи таблицы перенаправления в регистре [`VERSION`].
+const MAX_ENTRY_SHIFT: u32 = 16;
+
+/// Номер первого регистра таблицы перенаправления.
+/// Каждая запись занимает два регистра.
+const REDIRECTION_TABLE: u32 = 0x10;
+
+/// Смещение регистра IOREGSEL от начала регистров IO APIC.
+const SELECT_OFFSET: usize = 0x00;
+
+/// Номер регистра версии IO APIC.
+const VERSION: u32 = 0x01;
+
+/// Смещение регистра IOWIN от начала регистров IO APIC.
+const WINDOW_OFFSET: usize = 0x10;
+
+#[doc(hidden)]
+pub mod test_scaffolding {
+    use crate::error::Result;
+
+    use super::IoApic;
+
+    pub fn io_apic_destination(irq: u8) -> Result<u8> {
+        IoApic::destination(irq)
+    }
+
+    pub fn is_routed(irq: u8) -> bool {
+        IoApic::is_routed(irq)
+    }
+
+    pub fn set_io_apic_destination(
+        irq: u8,
+        cpu: u8,
+    ) -> Result<()> {
+        IoApic::set_destination(irq, cpu)
+    }
+}
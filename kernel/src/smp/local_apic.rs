@@ -7,6 +7,7 @@ use core::{
 use bitflags::bitflags;
 use chrono::Duration;
 use static_assertions::const_assert_eq;
+use x86_64::instructions::interrupts;
 
 use ku::time;
 
@@ -224,27 +225,48 @@ impl LocalApic {
 
         let init_data = InterruptCommand::INIT | InterruptCommand::TRIGGER_MODE_LEVEL;
 
-        local_apic.send_ipi(id, (init_data | InterruptCommand::LEVEL_ASSERT).bits());
+        local_apic.send_icr(id, (init_data | InterruptCommand::LEVEL_ASSERT).bits());
         time::delay(Duration::microseconds(200));
 
-        local_apic.send_ipi(id, (init_data | InterruptCommand::LEVEL_DEASSERT).bits());
+        local_apic.send_icr(id, (init_data | InterruptCommand::LEVEL_DEASSERT).bits());
         time::delay(Duration::microseconds(200));
 
         for _ in 0 .. 2 {
-            local_apic.send_ipi(id, InterruptCommand::START_UP.bits() | boot_page);
+            local_apic.send_icr(id, InterruptCommand::START_UP.bits() | boot_page);
             time::delay(Duration::microseconds(200));
         }
 
         Ok(())
     }
 
+    /// Посылает процессору `target` межпроцессорное прерывание
+    /// ([inter-processor interrupt](https://en.wikipedia.org/wiki/Inter-processor_interrupt), IPI)
+    /// с номером `vector`.
+    ///
+    /// Если у процессора `target` прерывания сейчас запрещены или он занят обработкой
+    /// другого прерывания, его local APIC запомнит прерывание `vector`
+    /// и доставит его, как только это станет возможно.
+    pub(crate) fn send_ipi(
+        target: CpuId,
+        vector: Trap,
+    ) {
+        let vector = size::try_into::<u32>(vector.into()).unwrap();
+
+        // Регистры команды общие для всего local APIC,
+        // обработчик прерывания не должен вклиниться между записями в них.
+        interrupts::without_interrupts(|| {
+            Self::get().send_icr(target, vector);
+        });
+    }
+
     /// Посылает процессору `id` прерывание
     /// ([inter-processor interrupt](https://en.wikipedia.org/wiki/Inter-processor_interrupt), IPI)
-    /// с дополнительными данными `data`.
+    /// с дополнительными данными `data`,
+    /// записывая их в регистр команд прерываний (Interrupt Command Register, ICR).
     ///
     /// <https://www.intel.com/content/dam/www/public/us/en/documents/manuals/64-ia-32-architectures-software-developer-vol-3a-part-1-manual.pdf>,
     /// 10.6 "Issuing Interprocessor Interrupts"
-    fn send_ipi(
+    fn send_icr(
         &mut self,
         id: CpuId,
         data: u32,
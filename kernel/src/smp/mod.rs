@@ -9,16 +9,38 @@ mod ap_init;
 /// каждая из которых принадлежит своему процессору системы.
 mod cpu;
 
+/// Код работы с [IO APIC](https://wiki.osdev.org/IOAPIC),
+/// который заменяет устаревший контроллер прерываний
+/// [PIC 8259](https://en.wikipedia.org/wiki/Intel_8259).
+mod io_apic;
+
 /// Код работы с
 /// local [APIC](https://en.wikipedia.org/wiki/Advanced_Programmable_Interrupt_Controller).
 mod local_apic;
 
+/// Данные [`PerCpu`], у каждого процессора системы свои.
+mod per_cpu;
+
+/// Измерение сдвига счётчиков тактов Application Processors
+/// относительно счётчика тактов Bootstrap Processor.
+mod tsc_sync;
+
 use alloc::vec::Vec;
-use core::cmp;
+use core::{
+    cmp,
+    sync::atomic::{
+        AtomicBool,
+        Ordering,
+    },
+};
 
 use lazy_static::lazy_static;
 
-use ku::sync::spinlock::Spinlock;
+use ku::{
+    sync::spinlock::Spinlock,
+    time::CpuUsage,
+};
+use pci::MmioConfigSpace;
 
 use crate::{
     Subsystems,
@@ -30,17 +52,32 @@ use crate::{
         info,
         warn,
     },
-    memory::Phys2Virt,
+    memory::{
+        BASE_ADDRESS_SPACE,
+        Block,
+        FRAME_ALLOCATOR,
+        Frame,
+        KERNEL_MMIO,
+        Page,
+        Phys2Virt,
+    },
     time,
+    trap,
 };
 
-use acpi_info::AcpiInfo;
+use acpi_info::{
+    AcpiInfo,
+    PciEcam,
+};
 use ap_init::SavedMemory;
 
+pub use per_cpu::PerCpu;
+
 pub(crate) use cpu::{
     Cpu,
     KERNEL_RSP_OFFSET_IN_CPU,
 };
+pub(crate) use io_apic::IoApic;
 pub(crate) use local_apic::{
     CpuId,
     LocalApic,
@@ -69,6 +106,46 @@ pub(super) fn init(
     }
 }
 
+/// Суммарная загрузка всех процессоров системы в процентах
+/// в скользящем окне из последних интервалов исполнения процессов и простоя,
+/// см. [`CpuUsage`].
+/// Равна [`None`], если ни один процессор ещё не исполнял процессы и не простаивал.
+pub fn cpu_usage() -> Option<u8> {
+    let usages: Vec<_> = CPUS.lock().iter().map(Cpu::usage).collect();
+    CpuUsage::total_percent(&usages)
+}
+
+/// Загрузка каждого из процессоров системы в процентах,
+/// в порядке их идентификаторов, см. [`cpu_usage()`].
+pub fn cpu_usage_per_cpu() -> Vec<Option<u8>> {
+    CPUS.lock().iter().map(|cpu| cpu.usage().percent()).collect()
+}
+
+/// Возвращает [`MmioConfigSpace`] для работы с расширенным
+/// [пространством конфигурации PCI Express](https://en.wikipedia.org/wiki/PCI_configuration_space)
+/// через область ECAM, описанную в таблице MCFG
+/// [ACPI](https://en.wikipedia.org/wiki/ACPI).
+/// Если такой таблицы нет, например, на машине с чипсетом без PCI Express,
+/// возвращает [`None`] и остаётся только [`pci::PortConfigSpace`].
+pub fn pci_config_space() -> Option<MmioConfigSpace> {
+    *PCI_CONFIG_SPACE.lock()
+}
+
+/// Возвращает `true`, если ядро работает в деградированном режиме без таблиц
+/// [ACPI](https://en.wikipedia.org/wiki/ACPI), см. [`init_legacy()`].
+/// В этом режиме процессор единственный, а вытеснение процессов выполняется по прерываниям
+/// [Programmable Interval Timer](https://en.wikipedia.org/wiki/Programmable_interval_timer)
+/// вместо таймера local APIC.
+pub fn is_legacy_mode() -> bool {
+    LEGACY_MODE.load(Ordering::Relaxed)
+}
+
+/// Возвращает идентификаторы процессоров, которые закончили инициализацию
+/// и принимают межпроцессорные прерывания, см. [`LocalApic::send_ipi()`].
+pub(crate) fn online_cpus() -> impl Iterator<Item = CpuId> {
+    (0 ..= CpuId::MAX).filter(|&cpu| ONLINE_CPUS[usize::from(cpu)].load(Ordering::Acquire))
+}
+
 /// Инициализация симметричной многопроцессорности
 /// ([Symmetric multiprocessing](https://en.wikipedia.org/wiki/Symmetric_multiprocessing), SMP).
 /// Внутренняя функция, которая выполняет всю работу.
@@ -80,7 +157,26 @@ fn init_smp(
         return Err(Unimplemented);
     }
 
-    let acpi_info = AcpiInfo::new(phys2virt)?;
+    let acpi_info = match AcpiInfo::new(phys2virt) {
+        Ok(acpi_info) => acpi_info,
+        Err(error) => {
+            warn!(?error, "failed to read ACPI tables");
+            return init_legacy(subsystems);
+        },
+    };
+
+    if let Some(pci_ecam) = acpi_info.pci_ecam() {
+        match map_pci_config_space(pci_ecam) {
+            Ok(config_space) => *PCI_CONFIG_SPACE.lock() = Some(config_space),
+            Err(error) => warn!(?error, ?pci_ecam, "failed to map PCI configuration space"),
+        }
+    }
+
+    let numa = acpi_info.numa();
+    if numa.is_numa() {
+        FRAME_ALLOCATOR.lock().set_numa_topology(numa.clone());
+    }
+
     let local_apic_address = acpi_info.local_apic_address();
 
     LocalApic::map(local_apic_address)?;
@@ -96,6 +192,12 @@ fn init_smp(
         return Err(Unimplemented);
     }
 
+    set_online(current_cpu);
+
+    if subsystems.contains(Subsystems::IO_APIC) {
+        init_io_apic(&acpi_info, current_cpu);
+    }
+
     let max_cpu_id = cmp::max(
         bootstrap_processor,
         *acpi_info.ap_ids().iter().max().unwrap_or(&bootstrap_processor),
@@ -120,15 +222,110 @@ fn init_smp(
     }
 
     *CPUS.lock() = cpus;
+    trap::init_statistics();
 
     Ok(())
 }
 
+/// Передаёт прерывания шины ISA от
+/// [PIC 8259](https://en.wikipedia.org/wiki/Intel_8259) к IO APIC,
+/// который направляет их на процессор `destination`.
+/// Если IO APIC нет или его не удалось инициализировать,
+/// прерывания остаются на PIC 8259.
+fn init_io_apic(
+    acpi_info: &AcpiInfo,
+    destination: CpuId,
+) {
+    let Some(io_apic_info) = acpi_info.io_apic() else {
+        warn!("no IO APIC, keeping the legacy PIC");
+        return;
+    };
+
+    if let Err(error) = IoApic::init(io_apic_info, destination) {
+        warn!(?error, "failed to init IO APIC, keeping the legacy PIC");
+    }
+}
+
+/// Инициализация в деградированном режиме, когда таблиц ACPI нет или их не удалось прочитать.
+///
+/// Без ACPI неизвестны ни адрес local APIC, ни идентификаторы Application Processors.
+/// Поэтому ядро работает только на Bootstrap Processor, local APIC не отображается в память,
+/// а для вытеснения процессов используется таймер PIT,
+/// подключённый к [PIC 8259](https://en.wikipedia.org/wiki/Intel_8259).
+/// Пока local APIC не отображён, [`LocalApic::id()`] возвращает `0`,
+/// так что единственный процессор получает идентификатор `0`.
+fn init_legacy(subsystems: Subsystems) -> Result<()> {
+    if !subsystems.contains(Subsystems::CPUS) {
+        return Err(Unimplemented);
+    }
+
+    let current_cpu = LocalApic::id();
+    let cpus = cpu::init(1, current_cpu)?;
+
+    *CPUS.lock() = cpus;
+    trap::init_statistics();
+    LEGACY_MODE.store(true, Ordering::Relaxed);
+
+    warn!(
+        cpu = current_cpu,
+        "degraded mode: no ACPI, running on a single CPU with the legacy PIC and PIT timer",
+    );
+
+    Ok(())
+}
+
+/// Отмечает, что процессор `cpu` закончил инициализацию
+/// и принимает межпроцессорные прерывания, см. [`online_cpus()`].
+fn set_online(cpu: CpuId) {
+    ONLINE_CPUS[usize::from(cpu)].store(true, Ordering::Release);
+}
+
+/// Отображает в виртуальную память область ECAM `pci_ecam` с флагами [`KERNEL_MMIO`]
+/// и возвращает [`MmioConfigSpace`] для работы с ней.
+fn map_pci_config_space(pci_ecam: PciEcam) -> Result<MmioConfigSpace> {
+    let size = MmioConfigSpace::size(pci_ecam.start_bus(), pci_ecam.end_bus());
+    let frames = Block::new(
+        Frame::new(pci_ecam.address())?,
+        Frame::new((pci_ecam.address() + size)?)?,
+    )?;
+
+    let mut address_space = BASE_ADDRESS_SPACE.lock();
+    let pages = address_space.allocate(Page::layout_array(frames.count()), KERNEL_MMIO)?;
+
+    for (page, frame) in pages.into_iter().zip(frames) {
+        unsafe {
+            address_space.map_page_to_frame(page, frame, KERNEL_MMIO)?;
+        }
+    }
+
+    info!(?pci_ecam, %frames, %pages, "mapped PCI configuration space");
+
+    Ok(unsafe {
+        MmioConfigSpace::new(
+            pages.start_address(),
+            pci_ecam.start_bus(),
+            pci_ecam.end_bus(),
+        )
+    })
+}
+
 lazy_static! {
     /// Структуры [`Cpu`] для всех процессоров в системе.
     static ref CPUS: Spinlock<Vec<Cpu>> = Spinlock::new(Vec::<Cpu>::default());
 }
 
+/// Признак деградированного режима без ACPI, см. [`is_legacy_mode()`].
+static LEGACY_MODE: AtomicBool = AtomicBool::new(false);
+
+/// Процессоры, которые принимают межпроцессорные прерывания, см. [`online_cpus()`].
+static ONLINE_CPUS: [AtomicBool; CPU_ID_COUNT] = [const { AtomicBool::new(false) }; CPU_ID_COUNT];
+
+/// Пространство конфигурации PCI Express, см. [`pci_config_space()`].
+static PCI_CONFIG_SPACE: Spinlock<Option<MmioConfigSpace>> = Spinlock::new(None);
+
+/// Количество различных идентификаторов [`CpuId`].
+pub(crate) const CPU_ID_COUNT: usize = CpuId::MAX as usize + 1;
+
 #[doc(hidden)]
 pub mod test_scaffolding {
     use crate::{
@@ -136,6 +333,7 @@ pub mod test_scaffolding {
         error::Result,
         memory::{
             Block,
+            NumaTopology,
             Phys2Virt,
             Virt,
         },
@@ -145,6 +343,7 @@ pub mod test_scaffolding {
 
     pub use super::{
         cpu::test_scaffolding::*,
+        io_apic::test_scaffolding::*,
         local_apic::test_scaffolding::*,
     };
 
@@ -152,6 +351,10 @@ pub mod test_scaffolding {
         CPUS.lock().len()
     }
 
+    pub fn init_legacy(subsystems: Subsystems) -> Result<()> {
+        super::init_legacy(subsystems)
+    }
+
     pub fn init_smp(
         phys2virt: Phys2Virt,
         subsystems: Subsystems,
@@ -162,4 +365,8 @@ pub mod test_scaffolding {
     pub fn kernel_stack_zones(cpu: usize) -> (Block<Virt>, Block<Virt>) {
         CPUS.lock()[cpu].kernel_stack().zones()
     }
+
+    pub fn parse_srat(srat: &[u8]) -> Result<NumaTopology> {
+        super::acpi_info::parse_srat(srat)
+    }
 }
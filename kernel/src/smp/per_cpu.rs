@@ -0,0 +1,96 @@
+use alloc::vec::Vec;
+
+use x86_64::instructions::interrupts;
+
+use crate::error::{
+    Error::Unimplemented,
+    Result,
+};
+
+use super::{
+    CPUS,
+    cpu::Cpu,
+};
+
+// Used in docs.
+#[allow(unused)]
+use crate::error::Error;
+
+/// Данные, у каждого процессора системы свои.
+///
+/// Хранит по одному экземпляру `T` на каждый процессор.
+/// Экземпляр текущего процессора находится через его структуру [`Cpu`],
+/// на которую указывает регистр
+/// [`GS`](https://wiki.osdev.org/CPU_Registers_x86-64#FS.base.2C_GS.base).
+/// Поэтому, например, счётчики событий процессора можно обновлять
+/// без глобальной блокировки и без борьбы процессоров за одну и ту же кэш--линию.
+///
+/// Экземпляры разных процессоров выровнены на размер кэш--линии,
+/// так же как и сами структуры [`Cpu`].
+/// Общее состояние, которое разделяют процессоры, `T` должен хранить в атомарных
+/// переменных или под блокировками --- [`PerCpu::iter()`] позволяет любому процессору
+/// прочитать экземпляры всех остальных, например, чтобы просуммировать счётчики.
+#[derive(Debug)]
+pub struct PerCpu<T> {
+    /// Экземпляры `T`, индексированные идентификаторами процессоров.
+    values: Vec<Aligned<T>>,
+}
+
+impl<T> PerCpu<T> {
+    /// Создаёт по одному экземпляру `T` для каждого процессора системы,
+    /// вызывая для каждого из них `init`.
+    ///
+    /// Возвращает ошибку [`Error::Unimplemented`], если `smp::init()`
+    /// ещё не выполнена и количество процессоров неизвестно.
+    pub fn new<F: FnMut() -> T>(mut init: F) -> Result<Self> {
+        let cpu_count = CPUS.lock().len();
+        if cpu_count == 0 {
+            return Err(Unimplemented);
+        }
+
+        let values = (0 .. cpu_count).map(|_| Aligned(init())).collect();
+
+        Ok(Self { values })
+    }
+
+    /// Возвращает экземпляр `T` текущего процессора.
+    ///
+    /// # Panics
+    ///
+    /// Паникует, если регистр `GS` текущего процессора ещё не инициализирован,
+    /// то есть при вызове до `smp::init()`
+    /// или на Application Processor до завершения его инициализации.
+    pub fn get(&self) -> &T {
+        &self.values[usize::from(Cpu::current_id())].0
+    }
+
+    /// Вызывает `f` для экземпляра `T` текущего процессора и возвращает её результат.
+    ///
+    /// На время вызова `f` прерывания запрещены.
+    /// Поэтому обработчики прерываний текущего процессора
+    /// не вклиниваются в последовательность операций `f` над его экземпляром.
+    ///
+    /// # Panics
+    ///
+    /// Паникует в тех же случаях, что и [`PerCpu::get()`].
+    pub fn with<F: FnOnce(&T) -> R, R>(
+        &self,
+        f: F,
+    ) -> R {
+        interrupts::without_interrupts(|| f(self.get()))
+    }
+
+    /// Возвращает итератор по экземплярам `T` всех процессоров
+    /// в порядке их идентификаторов.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.values.iter().map(|value| &value.0)
+    }
+}
+
+/// Экземпляр `T`, выровненный на размер кэш--линии,
+/// чтобы экземпляры разных процессоров не делили одну кэш--линию.
+///
+/// [Why align on 128 bytes instead of 64?](https://docs.rs/crossbeam/latest/crossbeam/utils/struct.CachePadded.html#size-and-alignment)
+#[derive(Debug)]
+#[repr(align(128))]
+struct Aligned<T>(T);
@@ -0,0 +1,73 @@
+use core::{
+    hint,
+    sync::atomic::{
+        AtomicI64,
+        AtomicUsize,
+        Ordering,
+    },
+};
+
+use ku::time;
+
+/// Измерение сдвига счётчика тактов Application Processor
+/// относительно счётчика тактов Bootstrap Processor.
+///
+/// Application Processor несколько раз запрашивает показание счётчика Bootstrap Processor,
+/// засекая по своему счётчику моменты отправки запроса и получения ответа.
+/// Bootstrap Processor снимает показание где-то между ними,
+/// поэтому сдвиг оценивается относительно середины этого интервала.
+/// Из всех раундов выбирается самый короткий --- погрешность его оценки наименьшая.
+#[derive(Debug, Default)]
+pub(super) struct TscSync {
+    /// Показание счётчика тактов Bootstrap Processor в последнем отвеченном раунде.
+    bsp_tsc: AtomicI64,
+
+    /// Номер последнего раунда, запрошенного Application Processor.
+    request: AtomicUsize,
+
+    /// Номер последнего раунда, на который ответил Bootstrap Processor.
+    response: AtomicUsize,
+}
+
+impl TscSync {
+    /// Отвечает на очередной запрос Application Processor, если он есть.
+    /// Выполняется на Bootstrap Processor.
+    pub(super) fn serve(&self) {
+        let request = self.request.load(Ordering::Acquire);
+        if request != self.response.load(Ordering::Relaxed) {
+            self.bsp_tsc.store(time::tsc(), Ordering::Relaxed);
+            self.response.store(request, Ordering::Release);
+        }
+    }
+
+    /// Измеряет сдвиг счётчика тактов текущего Application Processor
+    /// относительно счётчика Bootstrap Processor, который в это время
+    /// должен вызывать [`TscSync::serve()`].
+    ///
+    /// Возвращает поправку, которую нужно прибавить к счётчику Application Processor,
+    /// и длительность самого короткого раунда в тактах --- погрешность поправки.
+    pub(super) fn measure(&self) -> (i64, i64) {
+        let mut offset = 0;
+        let mut min_round_trip = i64::MAX;
+
+        for round in 1 ..= ROUND_COUNT {
+            let start = time::tsc();
+            self.request.store(round, Ordering::Release);
+            while self.response.load(Ordering::Acquire) != round {
+                hint::spin_loop();
+            }
+            let end = time::tsc();
+
+            let round_trip = end - start;
+            if round_trip < min_round_trip {
+                min_round_trip = round_trip;
+                offset = self.bsp_tsc.load(Ordering::Relaxed) - (start + round_trip / 2);
+            }
+        }
+
+        (offset, min_round_trip)
+    }
+}
+
+/// Количество раундов измерения сдвига счётчика тактов.
+const ROUND_COUNT: usize = 64;
@@ -22,6 +22,8 @@ pub use ku::{
     Tsc,
     TscDuration,
     delay,
+    has_rdtscp,
+    monotonic,
     now,
     now_ms,
     timer,
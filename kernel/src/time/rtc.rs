@@ -55,6 +55,7 @@ pub(crate) fn interrupt() {
         let before_correction = time::datetime(Tsc::new(now.tsc()));
         rtc.store_prev(now);
         let after_correction = time::datetime(Tsc::new(now.tsc()));
+        SYSTEM_INFO.clock().update(rtc);
 
         if let Some(error) = (before_correction - after_correction).num_nanoseconds() {
             ERROR.store(error, Ordering::Relaxed);
@@ -143,6 +144,14 @@ pub fn error() -> Duration {
     Duration::nanoseconds(ERROR.load(Ordering::Relaxed))
 }
 
+/// Читает текущие дату и время непосредственно из микросхемы RTC,
+/// в отличие от [`time::now()`], который их предсказывает по счётчику тактов процессора.
+///
+/// Возвращает `None`, если согласованно прочитать их не удалось.
+pub fn read() -> Option<DateTime<Utc>> {
+    timestamp().and_then(|timestamp| DateTime::from_timestamp(timestamp, 0))
+}
+
 // ANCHOR: enable_next_interrupt
 /// Говорит микросхеме RTC, что процессор обработал
 /// [прерывание](https://en.wikipedia.org/wiki/Interrupt)
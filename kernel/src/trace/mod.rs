@@ -0,0 +1,288 @@
+use alloc::vec::Vec;
+use core::{
+    fmt::{
+        self,
+        Write,
+    },
+    ptr,
+    sync::atomic::{
+        AtomicI64,
+        AtomicPtr,
+        AtomicUsize,
+        Ordering,
+    },
+};
+
+use ku::time::{
+    self,
+    Tsc,
+};
+
+use crate::{
+    SYSTEM_INFO,
+    smp::LocalApic,
+};
+
+/// Записывает в трассу событие `name` категории `category`,
+/// например `trace_event!("scheduler", "dequeue")`.
+///
+/// Оба аргумента должны быть константными строками.
+/// Они сохраняются в статической [`TracePoint`],
+/// так что в момент события в буфер записываются только указатель на неё и номер такта.
+/// Никакого форматирования на горячем пути нет.
+#[macro_export]
+macro_rules! trace_event {
+    ($category:expr, $name:expr $(,)?) => {{
+        static TRACE_POINT: $crate::trace::TracePoint =
+            $crate::trace::TracePoint::new($category, $name);
+        $crate::trace::record(&TRACE_POINT);
+    }};
+}
+
+/// Место в коде ядра, события которого записываются в трассу макросом [`trace_event!`].
+#[derive(Debug)]
+pub struct TracePoint {
+    /// Категория события, например, подсистема ядра.
+    category: &'static str,
+
+    /// Имя события.
+    name: &'static str,
+}
+
+impl TracePoint {
+    /// Создаёт место трассировки для событий `name` категории `category`.
+    pub const fn new(
+        category: &'static str,
+        name: &'static str,
+    ) -> Self {
+        Self { category, name }
+    }
+
+    /// Категория события, например, подсистема ядра.
+    pub fn category(&self) -> &'static str {
+        self.category
+    }
+
+    /// Имя события.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+/// Событие, прочитанное из трассы функцией [`events()`].
+#[derive(Clone, Copy, Debug)]
+pub struct TraceEvent {
+    /// Номер процессора, на котором произошло событие.
+    cpu: u32,
+
+    /// Место трассировки, в котором произошло событие.
+    point: &'static TracePoint,
+
+    /// Момент события, приведённый к счётчику Bootstrap Processor.
+    tsc: Tsc,
+}
+
+impl TraceEvent {
+    /// Категория события.
+    pub fn category(&self) -> &'static str {
+        self.point.category
+    }
+
+    /// Номер процессора, на котором произошло событие.
+    pub fn cpu(&self) -> u32 {
+        self.cpu
+    }
+
+    /// Имя события.
+    pub fn name(&self) -> &'static str {
+        self.point.name
+    }
+
+    /// Момент события, приведённый к счётчику Bootstrap Processor,
+    /// как у [`Tsc::synchronized()`].
+    pub fn tsc(&self) -> Tsc {
+        self.tsc
+    }
+}
+
+/// Записывает событие места трассировки `point` в кольцевой буфер текущего процессора.
+/// Используется макросом [`trace_event!`].
+///
+/// Номер процессора и номер такта читаются одной инструкцией
+/// [`rdtscp`](https://www.felixcloutier.com/x86/rdtscp), см. [`time::tscp()`].
+/// Если процессор её не поддерживает, см. [`time::has_rdtscp()`],
+/// номер процессора берётся из [`LocalApic::id()`].
+/// События процессоров с номерами от [`CPU_COUNT`] и выше отбрасываются.
+#[inline(always)]
+pub fn record(point: &'static TracePoint) {
+    let (tsc, cpu) = if time::has_rdtscp() {
+        time::tscp()
+    } else {
+        (time::tsc(), LocalApic::id().into())
+    };
+
+    if let Some(buffer) = usize::try_from(cpu).ok().and_then(|cpu| BUFFERS.get(cpu)) {
+        buffer.push(point, tsc);
+    }
+}
+
+/// Возвращает события, сохранившиеся в буферах всех процессоров,
+/// упорядоченные по времени.
+///
+/// # Note
+///
+/// События, которые записываются одновременно с вызовом [`events()`],
+/// могут быть пропущены.
+pub fn events() -> Vec<TraceEvent> {
+    let mut events = Vec::new();
+
+    for (cpu, buffer) in BUFFERS.iter().enumerate() {
+        let cpu = cpu.try_into().expect("CPU_COUNT should fit into u32");
+        let offset = SYSTEM_INFO.tsc_offsets().get(cpu);
+        buffer.collect(cpu, offset, &mut events);
+    }
+
+    // Сортировка устойчива, так что события одного процессора
+    // с совпадающими номерами тактов сохраняют свой порядок.
+    events.sort_by_key(TraceEvent::tsc);
+
+    events
+}
+
+/// Записывает в `writer` события трассы в формате
+/// [Trace Event Format](https://docs.google.com/document/d/1CvAClvFfyA5R-PhYUmn5OOQtYMH4h6I0nSsKchNAySU),
+/// который понимают `chrome://tracing` и [Perfetto](https://ui.perfetto.dev/).
+///
+/// Время событий отсчитывается в микросекундах от самого раннего из них.
+pub fn dump_trace<W: Write>(writer: &mut W) -> fmt::Result {
+    let events = events();
+    let start = events.first().map(|event| time::datetime(event.tsc));
+
+    write!(writer, "{{\"traceEvents\":[")?;
+
+    for (i, event) in events.iter().enumerate() {
+        let nanoseconds = start
+            .and_then(|start| (time::datetime(event.tsc) - start).num_nanoseconds())
+            .unwrap_or(0);
+
+        if i > 0 {
+            write!(writer, ",")?;
+        }
+
+        write!(
+            writer,
+            "{{\"cat\":{:?},\"name\":{:?},\"ph\":\"i\",\"s\":\"t\",",
+            event.category(),
+            event.name(),
+        )?;
+        write!(
+            writer,
+            "\"pid\":0,\"tid\":{},\"ts\":{}.{:03}}}",
+            event.cpu,
+            nanoseconds / NSECS_PER_USEC,
+            nanoseconds % NSECS_PER_USEC,
+        )?;
+    }
+
+    write!(writer, "],\"displayTimeUnit\":\"ns\"}}")
+}
+
+/// Кольцевой буфер событий трассы одного процессора.
+///
+/// Пишет в него только свой процессор, поэтому запись не ждёт.
+/// Вложенные прерывания, которые тоже записывают события,
+/// получают различные ячейки благодаря [`TraceBuffer::next`].
+struct TraceBuffer {
+    /// Ячейки для событий.
+    /// Событие номер `i` хранится по индексу `i % CAPACITY`.
+    events: [TraceSlot; TraceBuffer::CAPACITY],
+
+    /// Количество событий, которые когда-либо были записаны в буфер.
+    next: AtomicUsize,
+}
+
+impl TraceBuffer {
+    /// Создаёт пустой буфер.
+    const fn new() -> Self {
+        Self {
+            events: [const { TraceSlot::new() }; Self::CAPACITY],
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    /// Записывает в буфер событие места трассировки `point`,
+    /// которое произошло в такт `tsc`.
+    /// Если буфер заполнен, перезаписывает самое старое событие.
+    #[inline(always)]
+    fn push(
+        &self,
+        point: &'static TracePoint,
+        tsc: i64,
+    ) {
+        let slot = &self.events[self.next.fetch_add(1, Ordering::Relaxed) % Self::CAPACITY];
+
+        slot.point.store(ptr::null_mut(), Ordering::Relaxed);
+        slot.tsc.store(tsc, Ordering::Relaxed);
+        slot.point.store(ptr::from_ref(point).cast_mut(), Ordering::Release);
+    }
+
+    /// Дописывает в `events` события буфера процессора `cpu` в порядке их записи,
+    /// прибавляя к их номерам тактов поправку `offset`.
+    fn collect(
+        &self,
+        cpu: u32,
+        offset: i64,
+        events: &mut Vec<TraceEvent>,
+    ) {
+        let next = self.next.load(Ordering::Acquire);
+
+        for i in next.saturating_sub(Self::CAPACITY) .. next {
+            let slot = &self.events[i % Self::CAPACITY];
+            let point = slot.point.load(Ordering::Acquire);
+            let tsc = slot.tsc.load(Ordering::Relaxed);
+
+            // Ячейка ещё не записана до конца или перезаписывается прямо сейчас.
+            if point.is_null() || slot.point.load(Ordering::Relaxed) != point {
+                continue;
+            }
+
+            events.push(TraceEvent {
+                cpu,
+                point: unsafe { &*point },
+                tsc: Tsc::new(tsc + offset),
+            });
+        }
+    }
+
+    /// Ёмкость буфера.
+    const CAPACITY: usize = 512;
+}
+
+/// Ячейка кольцевого буфера [`TraceBuffer`].
+struct TraceSlot {
+    /// Место трассировки, в котором произошло событие.
+    /// Равно нулю, пока ячейка не записана.
+    point: AtomicPtr<TracePoint>,
+
+    /// Номер такта, в который произошло событие, без поправки [`ku::time::TscOffsets`].
+    tsc: AtomicI64,
+}
+
+impl TraceSlot {
+    /// Создаёт пустую ячейку.
+    const fn new() -> Self {
+        Self {
+            point: AtomicPtr::new(ptr::null_mut()),
+            tsc: AtomicI64::new(0),
+        }
+    }
+}
+
+/// Кольцевые буферы событий трассы, по одному на процессор.
+static BUFFERS: [TraceBuffer; CPU_COUNT] = [const { TraceBuffer::new() }; CPU_COUNT];
+
+/// Количество процессоров, события которых записываются в трассу.
+pub const CPU_COUNT: usize = 16;
+
+/// Количество наносекунд в микросекунде.
+const NSECS_PER_USEC: i64 = 1_000;
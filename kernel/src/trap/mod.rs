@@ -1,9 +1,12 @@
+use alloc::boxed::Box;
 use core::{
     arch::naked_asm,
     fmt,
     mem,
     ops::Index,
+    ptr,
     sync::atomic::{
+        AtomicPtr,
         AtomicUsize,
         Ordering,
     },
@@ -27,9 +30,15 @@ use x86_64::{
 
 use ku::{
     backtrace::Backtrace,
-    process::Info,
+    process::{
+        ExitCode,
+        Info,
+        TrapInfo,
+    },
     sync::{
         self,
+        IrqSpinlock,
+        PanicStrategy,
         spinlock::Spinlock,
     },
 };
@@ -38,26 +47,44 @@ use ku::{
 use sentinel_frame::with_sentinel_frame;
 
 use crate::{
-    fs::BlockCache,
+    error::{
+        Error::{
+            InvalidArgument,
+            PermissionDenied,
+        },
+        Result,
+    },
+    fs::{
+        self,
+        BlockCache,
+    },
+    input::ps2,
     log::{
         error,
         info,
         warn,
     },
     memory::{
+        self,
         DOUBLE_FAULT_IST_INDEX,
         PAGE_FAULT_IST_INDEX,
+        UserAccess,
         Virt,
     },
     process::{
         ModeContext,
         Pid,
         Process,
+        Scheduler,
         Table,
     },
     smp::{
+        self,
         Cpu,
+        CpuId,
+        IoApic,
         LocalApic,
+        PerCpu,
     },
     time::{
         pit8254,
@@ -80,22 +107,33 @@ const PIC_BASE: usize = Trap::Pit as usize;
 /// Количество исключений и прерываний.
 const COUNT: usize = Trap::Spurious as usize + 1;
 
+/// Количество свободных входов
+/// [PIC 8259](https://en.wikipedia.org/wiki/Intel_8259) ---
+/// от [`Trap::Free29`] до [`Trap::Free2B`].
+const FREE_LINE_COUNT: usize = Trap::Free2B as usize - Trap::Free29 as usize + 1;
+
+/// Количество прерываний шины
+/// [ISA](https://en.wikipedia.org/wiki/Industry_Standard_Architecture) ---
+/// от [`Trap::Pit`] до [`Trap::Ata1`].
+const ISA_IRQ_COUNT: usize = Trap::Ata1 as usize - PIC_BASE + 1;
+
 // ANCHOR: statistics
 /// Информация о прерывании.
 pub struct Statistics {
-    /// Сколько раз сработало это прерывание.
-    count: AtomicUsize,
-
     /// Короткая мнемоника прерывания.
     mnemonic: &'static str,
 
     /// Имя прерывания.
     name: &'static str,
+
+    /// Номер прерывания --- индекс его счётчиков в [`CpuTrapCounts`].
+    number: usize,
 }
 // ANCHOR_END: statistics
 
 impl Statistics {
     /// Создаёт информацию о прерывании с именем `name` и короткой мнемоникой `mnemonic`.
+    /// Номер прерывания проставляет [`TrapStats::new()`].
     const fn new(
         name: &'static str,
         mnemonic: &'static str,
@@ -103,13 +141,53 @@ impl Statistics {
         Statistics {
             name,
             mnemonic,
-            count: AtomicUsize::new(0),
+            number: 0,
         }
     }
 
-    /// Сколько раз сработало это прерывание.
+    /// Сколько раз сработало это прерывание на всех процессорах вместе.
+    ///
+    /// Каждый из слагаемых счётчиков только растёт и читается один раз,
+    /// поэтому последовательные вызовы возвращают неубывающие значения.
     pub fn count(&self) -> usize {
-        self.count.load(Ordering::Relaxed)
+        let early = EARLY_TRAP_COUNTS.get(self.number);
+
+        trap_counts()
+            .into_iter()
+            .flat_map(PerCpu::iter)
+            .fold(early, |count, counts| count + counts.get(self.number))
+    }
+
+    /// Сколько раз это прерывание сработало на процессоре `cpu`.
+    ///
+    /// Срабатывания до того, как [`init_statistics()`] завела счётчики процессоров,
+    /// и до инициализации структуры [`Cpu`] текущего процессора
+    /// не относятся ни к одному процессору и учитываются только в [`Statistics::count()`].
+    /// Поэтому процессор, запущенный посреди работы системы,
+    /// начинает с нуля.
+    pub fn count_on(
+        &self,
+        cpu: CpuId,
+    ) -> usize {
+        trap_counts()
+            .and_then(|trap_counts| trap_counts.iter().nth(usize::from(cpu)))
+            .map_or(0, |counts| counts.get(self.number))
+    }
+
+    /// Возвращает итератор по процессорам, на которых это прерывание срабатывало,
+    /// и количествам его срабатываний на каждом из них, в порядке идентификаторов процессоров.
+    ///
+    /// Сумма по процессорам не превышает [`Statistics::count()`],
+    /// см. [`Statistics::count_on()`].
+    pub fn per_cpu(&self) -> impl Iterator<Item = (CpuId, usize)> + '_ {
+        trap_counts()
+            .into_iter()
+            .flat_map(|trap_counts| trap_counts.iter().enumerate())
+            .map(|(cpu, counts)| {
+                let cpu = CpuId::try_from(cpu).expect("too many CPUs");
+                (cpu, counts.get(self.number))
+            })
+            .filter(|&(_, count)| count != 0)
     }
 
     /// Короткая мнемоника прерывания.
@@ -122,9 +200,38 @@ impl Statistics {
         self.name
     }
 
-    /// Инкрементирует счётчик срабатывания прерывания.
+    /// Инкрементирует счётчик срабатывания прерывания на текущем процессоре.
+    ///
+    /// Счётчики текущего процессора находит [`PerCpu::get()`].
+    /// Они лежат в своей кэш--линии,
+    /// так что процессоры не борются за неё при каждом прерывании.
+    /// Пока счётчики процессоров не заведены или у текущего процессора
+    /// ещё нет структуры [`Cpu`], срабатывание учитывается в [`EARLY_TRAP_COUNTS`].
     fn inc(&self) {
-        self.count.fetch_add(1, Ordering::Relaxed);
+        let counts = match (trap_counts(), Cpu::try_current_id()) {
+            (Some(trap_counts), Some(_)) => trap_counts.get(),
+            _ => &EARLY_TRAP_COUNTS,
+        };
+
+        counts.0[self.number].fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Счётчики срабатываний всех прерываний на одном процессоре.
+struct CpuTrapCounts([AtomicUsize; COUNT]);
+
+impl CpuTrapCounts {
+    /// Создаёт обнулённые счётчики.
+    const fn new() -> Self {
+        Self([const { AtomicUsize::new(0) }; COUNT])
+    }
+
+    /// Сколько раз сработало прерывание номер `number`.
+    fn get(
+        &self,
+        number: usize,
+    ) -> usize {
+        self.0[number].load(Ordering::Relaxed)
     }
 }
 
@@ -132,6 +239,17 @@ impl Statistics {
 pub struct TrapStats([Statistics; COUNT]);
 
 impl TrapStats {
+    /// Создаёт информацию обо всех прерываниях, проставляя их номера.
+    const fn new(mut stats: [Statistics; COUNT]) -> Self {
+        let mut number = 0;
+        while number < COUNT {
+            stats[number].number = number;
+            number += 1;
+        }
+
+        Self(stats)
+    }
+
     /// Возвращает итератор по статистикам прерываний.
     pub fn iter(&self) -> core::slice::Iter<'_, Statistics> {
         self.0.iter()
@@ -150,7 +268,7 @@ impl Index<Trap> for TrapStats {
 }
 
 /// Информация обо всех прерываниях.
-pub static TRAP_STATS: TrapStats = TrapStats([
+pub static TRAP_STATS: TrapStats = TrapStats::new([
     Statistics::new("Divide Error", "#DE"),
     Statistics::new("Debug", "#DB"),
     Statistics::new("Non-maskable Interrupt", "#NM"),
@@ -200,6 +318,7 @@ pub static TRAP_STATS: TrapStats = TrapStats([
     Statistics::new("Primary ATA Hard Disk", "#PD"),
     Statistics::new("Secondary ATA Hard Disk", "#SD"),
     Statistics::new("Timer", "#TI"),
+    Statistics::new("TLB Shootdown", "#TL"),
     Statistics::new("Spurious", "#SP"),
 ]);
 
@@ -220,6 +339,29 @@ pub(super) fn init() {
 }
 // ANCHOR_END: init
 
+/// Заводит счётчики срабатываний прерываний для каждого процессора системы,
+/// см. [`Statistics::count_on()`].
+/// Вызывается на Bootstrap Processor, когда уже известны структуры [`Cpu`] всех процессоров.
+/// До этого все срабатывания учитываются в [`EARLY_TRAP_COUNTS`].
+/// Повторные вызовы ничего не меняют.
+pub(crate) fn init_statistics() {
+    if trap_counts().is_some() {
+        return;
+    }
+
+    match PerCpu::new(CpuTrapCounts::new) {
+        Ok(trap_counts) =>
+            TRAP_COUNTS.store(Box::into_raw(Box::new(trap_counts)), Ordering::Release),
+        Err(error) => warn!(?error, "failed to make per-CPU trap counters"),
+    }
+}
+
+/// Счётчики срабатываний прерываний на каждом из процессоров,
+/// если [`init_statistics()`] их уже завела.
+fn trap_counts() -> Option<&'static PerCpu<CpuTrapCounts>> {
+    unsafe { TRAP_COUNTS.load(Ordering::Acquire).as_ref() }
+}
+
 /// A helper to generate the code for exception handlers.
 macro_rules! exception_with_error_code {
     ($name:ident, $trap:expr) => {
@@ -335,6 +477,7 @@ impl Idt {
         idt.get_mut(Trap::Ata0).set_handler(ata0);
         idt.get_mut(Trap::Ata1).set_handler(ata1);
         idt.get_mut(Trap::Timer).set_handler(timer);
+        idt.get_mut(Trap::TlbShootdown).set_handler(tlb_shootdown);
         idt.get_mut(Trap::Spurious).set_handler(spurious);
 
         idt
@@ -486,11 +629,25 @@ extern "C" fn generic_trap(
         }
         let mut process =
             Table::get(pid).expect("failed to find the current process in the process table");
+        let killed = process.is_killed();
 
-        if process.trap(context, trap, info) {
+        if !killed &&
+            (process.demand_page(info) ||
+                process.copy_on_write(info) ||
+                process.trap(context, trap, info))
+        {
             return;
         }
 
+        if info.is_instruction_fetch() {
+            warn!(
+                %info,
+                %context,
+                %pid,
+                "user mode instruction fetch fault, maybe an attempt to execute data",
+            );
+        }
+
         info!(
             trap = TRAP_STATS[trap].name,
             number,
@@ -500,18 +657,24 @@ extern "C" fn generic_trap(
             "user mode trap",
         );
 
-        if fatal {
+        if fatal || killed {
             drop(process);
-            if let Err(error) = Table::free(pid) {
+            if let Err(error) = Table::exit(pid, ExitCode::Killed.into()) {
                 warn!(
                     %pid,
                     ?error,
-                    "failed to free the process, maybe it was destroyed concurrently",
+                    "failed to exit the process, maybe it was destroyed concurrently",
                 );
             }
             Process::sched_yield();
         }
     } else {
+        if UserAccess::trap_handler(&info, context) ||
+            memory::copy_fault_handler(&info, context)
+        {
+            return;
+        }
+
         match BlockCache::trap_handler(&info) {
             Ok(true) => return,
             Ok(false) => {},
@@ -595,43 +758,90 @@ extern "C" fn trap_trampoline() {
 /// [PIC 8259](https://en.wikipedia.org/wiki/Intel_8259).
 /// Аргумент `number` задаёт номер прерывания в общей нумерации таблицы обработчиков прерываний
 /// ([Interrupt descriptor table](https://en.wikipedia.org/wiki/Interrupt_descriptor_table), IDT).
-fn generic_pic_interrupt(number: Trap) {
+/// Вызывает зарегистрированный для прерывания `number` обработчик, если он есть,
+/// передавая ему контекст `context`, в котором возникло прерывание.
+///
+/// Ложные прерывания, см. [`pic8259::is_spurious()`], только учитывает в
+/// [`spurious_pic_interrupts()`] и не посылает на них лишний сигнал о завершении обработки.
+///
+/// Если линию прерывания обслуживает IO APIC, см. [`IoApic::is_routed()`],
+/// сигнал о завершении обработки посылается в local APIC, а не в PIC 8259.
+fn generic_pic_interrupt(
+    number: Trap,
+    context: &TrapContext,
+) {
+    let irq = usize::from(number) - PIC_BASE;
+    let irq = irq.try_into().expect("too many interrupt numbers");
+
+    if IoApic::is_routed(irq) {
+        generic_apic_interrupt(number, context);
+        return;
+    }
+
+    if pic8259::is_spurious(irq) {
+        SPURIOUS_PIC_INTERRUPTS.fetch_add(1, Ordering::Relaxed);
+        unsafe {
+            pic8259::end_of_spurious_interrupt(irq);
+        }
+        return;
+    }
+
+    registered_interrupt(number, context);
     TRAP_STATS[number].inc();
     unsafe {
-        pic8259::end_of_interrupt(usize::from(number) - PIC_BASE);
+        pic8259::end_of_interrupt(irq.into());
     }
 }
+
+/// Количество ложных прерываний
+/// [PIC 8259](https://en.wikipedia.org/wiki/Intel_8259),
+/// которые возникают, когда устройство снимает запрос прерывания
+/// до того, как процессор его подтвердил.
+pub fn spurious_pic_interrupts() -> usize {
+    SPURIOUS_PIC_INTERRUPTS.load(Ordering::Relaxed)
+}
 // ANCHOR_END: generic_pic_interrupt
 
 /// Обработчик прерывания таймера [Intel 8253/8254](https://en.wikipedia.org/wiki/Intel_8253)
 /// ([programmable interval timer, PIT](https://en.wikipedia.org/wiki/Programmable_interval_timer)).
-extern "x86-interrupt" fn pit(_context: TrapContext) {
+/// В деградированном режиме без ACPI вытесняет процессы вместо [`timer()`],
+/// см. [`smp::is_legacy_mode()`].
+extern "x86-interrupt" fn pit(mut context: TrapContext) {
     pit8254::interrupt();
-    generic_pic_interrupt(Trap::Pit);
+
+    if smp::is_legacy_mode() {
+        Process::preempt(&mut context);
+    }
+
+    generic_pic_interrupt(Trap::Pit, &context);
 }
 
 /// Обработчик прерывания клавиатуры.
-extern "x86-interrupt" fn keyboard(_context: TrapContext) {
-    generic_pic_interrupt(Trap::Keyboard);
+/// Переносит очередной скан-код клавиатуры в очередь событий [`crate::input::poll_key()`].
+extern "x86-interrupt" fn keyboard(context: TrapContext) {
+    ps2::interrupt();
+    generic_pic_interrupt(Trap::Keyboard, &context);
 }
 
 /// Обработчик каскадного прерывания первого контроллера
 /// [PIC 8259](https://en.wikipedia.org/wiki/Intel_8259),
 /// к которому подключён второй такой же.
-extern "x86-interrupt" fn cascade(_context: TrapContext) {
-    generic_pic_interrupt(Trap::Cascade);
+extern "x86-interrupt" fn cascade(context: TrapContext) {
+    generic_pic_interrupt(Trap::Cascade, &context);
 }
 
 /// Обработчик прерывания
 /// [последовательных портов](https://en.wikipedia.org/wiki/Serial_port) номер 2 и 4.
-extern "x86-interrupt" fn com2(_context: TrapContext) {
-    generic_pic_interrupt(Trap::Com2);
+extern "x86-interrupt" fn com2(context: TrapContext) {
+    generic_pic_interrupt(Trap::Com2, &context);
 }
 
 /// Обработчик прерывания
 /// [последовательных портов](https://en.wikipedia.org/wiki/Serial_port) номер 1 и 3.
-extern "x86-interrupt" fn com1(_context: TrapContext) {
-    generic_pic_interrupt(Trap::Com1);
+/// Переносит принятые первым последовательным портом байты в его буфер.
+extern "x86-interrupt" fn com1(context: TrapContext) {
+    text::TEXT.lock().serial_mut().poll();
+    generic_pic_interrupt(Trap::Com1, &context);
 }
 
 /// Обработчик прерывания второго параллельного порта
@@ -639,13 +849,13 @@ extern "x86-interrupt" fn com1(_context: TrapContext) {
 /// Так как через параллельные порты чаще всего подключались принтеры
 /// ([Line printer](https://en.wikipedia.org/wiki/Line_printer)),
 /// сохранилось их сокращение LPT.
-extern "x86-interrupt" fn lpt2(_context: TrapContext) {
-    generic_pic_interrupt(Trap::Lpt2);
+extern "x86-interrupt" fn lpt2(context: TrapContext) {
+    generic_pic_interrupt(Trap::Lpt2, &context);
 }
 
 /// Обработчик прерывания контроллера [дискет](https://en.wikipedia.org/wiki/Floppy_disk).
-extern "x86-interrupt" fn floppy_disk(_context: TrapContext) {
-    generic_pic_interrupt(Trap::FloppyDisk);
+extern "x86-interrupt" fn floppy_disk(context: TrapContext) {
+    generic_pic_interrupt(Trap::FloppyDisk, &context);
 }
 
 /// Обработчик прерывания первого и третьего параллельного порта
@@ -653,64 +863,199 @@ extern "x86-interrupt" fn floppy_disk(_context: TrapContext) {
 /// Так как через параллельные порты чаще всего подключались принтеры
 /// ([Line printer](https://en.wikipedia.org/wiki/Line_printer)),
 /// сохранилось их сокращение LPT.
-extern "x86-interrupt" fn lpt1(_context: TrapContext) {
-    generic_pic_interrupt(Trap::Lpt1);
+extern "x86-interrupt" fn lpt1(context: TrapContext) {
+    generic_pic_interrupt(Trap::Lpt1, &context);
 }
 
 // ANCHOR: rtc
 /// Обработчик прерываний
 /// [часов реального времени (Real-time clock, RTC)](https://en.wikipedia.org/wiki/Real-time_clock).
-extern "x86-interrupt" fn rtc(_context: TrapContext) {
+/// Будит процессы, время сна которых истекло.
+extern "x86-interrupt" fn rtc(context: TrapContext) {
     rtc::interrupt();
-    generic_pic_interrupt(Trap::Rtc);
+    Scheduler::wake_up();
+    generic_pic_interrupt(Trap::Rtc, &context);
 }
 // ANCHOR_END: rtc
 
+/// Обработчик прерывания свободного входа
+/// [PIC 8259](https://en.wikipedia.org/wiki/Intel_8259),
+/// который регистрирует драйвер подключённого к этому входу устройства
+/// с помощью [`register_irq_handler()`].
+/// Получает номер сработавшего прерывания и контекст, в котором оно возникло,
+/// так что один обработчик может обслуживать несколько входов.
+pub type IrqHandler = fn(&TrapInfo);
+
+/// Регистрирует `handler` как обработчик прерывания `trap` свободного входа
+/// [PIC 8259](https://en.wikipedia.org/wiki/Intel_8259).
+/// Так драйвер устройства, например, звуковой карты или второго сетевого адаптера,
+/// занимает свободный вход и начинает получать его прерывания,
+/// не меняя модуль [`crate::trap`].
+/// Регистрировать обработчики можно в любой момент после [`init()`],
+/// в том числе при включённых прерываниях.
+///
+/// Обработчик вызывается в контексте прерывания до отправки контроллеру прерываний
+/// сигнала о завершении обработки
+/// ([end of interrupt, EOI](https://wiki.osdev.org/8259_PIC#End_of_Interrupt)),
+/// поэтому не должен блокироваться.
+///
+/// Возвращает ошибки:
+///   - [`InvalidArgument`] если `trap` не является одним из прерываний
+///     [`Trap::Free29`], [`Trap::Free2A`] и [`Trap::Free2B`].
+///   - [`PermissionDenied`] если вход уже занят другим обработчиком.
+pub fn register_irq_handler(
+    trap: Trap,
+    handler: IrqHandler,
+) -> Result<()> {
+    let line = free_line(trap)?;
+    let mut handlers = IRQ_HANDLERS.lock();
+
+    if handlers[line].is_some() {
+        return Err(PermissionDenied);
+    }
+
+    handlers[line] = Some(handler);
+
+    Ok(())
+}
+
+/// Освобождает свободный вход
+/// [PIC 8259](https://en.wikipedia.org/wiki/Intel_8259),
+/// на который приходит прерывание `trap`.
+/// После этого его прерывания снова только подтверждаются.
+///
+/// Возвращает ошибку [`InvalidArgument`] если `trap` не является одним из прерываний
+/// [`Trap::Free29`], [`Trap::Free2A`] и [`Trap::Free2B`].
+pub fn unregister_irq_handler(trap: Trap) -> Result<()> {
+    IRQ_HANDLERS.lock()[free_line(trap)?] = None;
+
+    Ok(())
+}
+
+/// Направляет прерывания `trap` устройства шины
+/// [ISA](https://en.wikipedia.org/wiki/Industry_Standard_Architecture)
+/// на процессор `cpu`, перепрограммируя соответствующий вход IO APIC.
+/// Так можно распределить обработку прерываний разных устройств между процессорами.
+/// Изначально все прерывания направлены на Bootstrap Processor.
+///
+/// Возвращает ошибку [`InvalidArgument`] если:
+///   - `trap` не является прерыванием шины ISA, которое обслуживает IO APIC,
+///     в том числе если IO APIC не инициализирован.
+///   - Процессора `cpu` нет в системе или он ещё не закончил инициализацию.
+pub fn set_irq_affinity(
+    trap: Trap,
+    cpu: CpuId,
+) -> Result<()> {
+    let irq = isa_irq(trap)?;
+
+    if !smp::online_cpus().any(|online_cpu| online_cpu == cpu) {
+        return Err(InvalidArgument);
+    }
+
+    IoApic::set_destination(irq, cpu)
+}
+
+/// Возвращает номер входа шины ISA для прерывания `trap`.
+///
+/// Возвращает ошибку [`InvalidArgument`] если `trap` не является прерыванием шины ISA.
+fn isa_irq(trap: Trap) -> Result<u8> {
+    let irq = usize::from(trap).wrapping_sub(PIC_BASE);
+
+    if irq < ISA_IRQ_COUNT {
+        Ok(irq.try_into()?)
+    } else {
+        Err(InvalidArgument)
+    }
+}
+
+/// Возвращает индекс в [`IRQ_HANDLERS`] для прерывания `trap` свободного входа
+/// [PIC 8259](https://en.wikipedia.org/wiki/Intel_8259).
+///
+/// Возвращает ошибку [`InvalidArgument`] если `trap` не является одним из прерываний
+/// [`Trap::Free29`], [`Trap::Free2A`] и [`Trap::Free2B`].
+fn free_line(trap: Trap) -> Result<usize> {
+    usize::from(trap)
+        .checked_sub(usize::from(Trap::Free29))
+        .filter(|&line| line < FREE_LINE_COUNT)
+        .ok_or(InvalidArgument)
+}
+
+/// Вызывает зарегистрированный для прерывания `trap` обработчик, если он есть.
+/// Аргумент `context` задаёт контекст, в котором возникло прерывание.
+fn registered_interrupt(
+    trap: Trap,
+    context: &TrapContext,
+) {
+    let Ok(line) = free_line(trap) else {
+        return;
+    };
+
+    // Обработчик вызывается после освобождения блокировки,
+    // чтобы он мог, например, удалить сам себя.
+    let handler = IRQ_HANDLERS.lock()[line];
+
+    if let Some(handler) = handler {
+        handler(&TrapInfo::new(
+            trap.into(),
+            Info::None,
+            context.get().mini_context(),
+        ));
+    }
+}
+
 /// Обработчик прерывания входа `0x9` каскадной пары
 /// [PIC 8259](https://en.wikipedia.org/wiki/Intel_8259).
-extern "x86-interrupt" fn free_29(_context: TrapContext) {
-    generic_pic_interrupt(Trap::Free29);
+extern "x86-interrupt" fn free_29(context: TrapContext) {
+    generic_pic_interrupt(Trap::Free29, &context);
 }
 
 /// Обработчик прерывания входа `0xA` каскадной пары
 /// [PIC 8259](https://en.wikipedia.org/wiki/Intel_8259).
-extern "x86-interrupt" fn free_2a(_context: TrapContext) {
-    generic_pic_interrupt(Trap::Free2A);
+extern "x86-interrupt" fn free_2a(context: TrapContext) {
+    generic_pic_interrupt(Trap::Free2A, &context);
 }
 
 /// Обработчик прерывания входа `0xB` каскадной пары
 /// [PIC 8259](https://en.wikipedia.org/wiki/Intel_8259).
-extern "x86-interrupt" fn free_2b(_context: TrapContext) {
-    generic_pic_interrupt(Trap::Free2B);
+extern "x86-interrupt" fn free_2b(context: TrapContext) {
+    generic_pic_interrupt(Trap::Free2B, &context);
 }
 
 /// Обработчик прерывания мыши.
-extern "x86-interrupt" fn ps2_mouse(_context: TrapContext) {
-    generic_pic_interrupt(Trap::Ps2Mouse);
+extern "x86-interrupt" fn ps2_mouse(context: TrapContext) {
+    generic_pic_interrupt(Trap::Ps2Mouse, &context);
 }
 
 /// Обработчик прерывания сопроцессора.
-extern "x86-interrupt" fn coprocessor(_context: TrapContext) {
-    generic_pic_interrupt(Trap::Coprocessor);
+extern "x86-interrupt" fn coprocessor(context: TrapContext) {
+    generic_pic_interrupt(Trap::Coprocessor, &context);
 }
 
 /// Обработчик прерывания первого контроллера
 /// [PATA](https://en.wikipedia.org/wiki/Parallel_ATA).
-extern "x86-interrupt" fn ata0(_context: TrapContext) {
-    generic_pic_interrupt(Trap::Ata0);
+extern "x86-interrupt" fn ata0(context: TrapContext) {
+    fs::ata_interrupt(Trap::Ata0);
+    generic_pic_interrupt(Trap::Ata0, &context);
 }
 
 /// Обработчик прерывания второго контроллера
 /// [PATA](https://en.wikipedia.org/wiki/Parallel_ATA).
-extern "x86-interrupt" fn ata1(_context: TrapContext) {
-    generic_pic_interrupt(Trap::Ata1);
+extern "x86-interrupt" fn ata1(context: TrapContext) {
+    fs::ata_interrupt(Trap::Ata1);
+    generic_pic_interrupt(Trap::Ata1, &context);
 }
 
 /// Выполняет общую часть обработки для всех прерываний
 /// [APIC](https://en.wikipedia.org/wiki/Advanced_Programmable_Interrupt_Controller#APIC_timer).
 /// Аргумент `number` задаёт номер прерывания в общей нумерации таблицы обработчиков прерываний
 /// ([Interrupt descriptor table](https://en.wikipedia.org/wiki/Interrupt_descriptor_table), IDT).
-fn generic_apic_interrupt(number: Trap) {
+/// Вызывает зарегистрированный для прерывания `number` обработчик, если он есть,
+/// передавая ему контекст `context`, в котором возникло прерывание.
+fn generic_apic_interrupt(
+    number: Trap,
+    context: &TrapContext,
+) {
+    registered_interrupt(number, context);
     TRAP_STATS[number].inc();
     LocalApic::end_of_interrupt();
 }
@@ -720,20 +1065,51 @@ fn generic_apic_interrupt(number: Trap) {
 extern "x86-interrupt" fn timer(mut context: TrapContext) {
     Process::preempt(&mut context);
 
-    generic_apic_interrupt(Trap::Timer);
+    generic_apic_interrupt(Trap::Timer, &context);
+}
+
+/// Обработчик межпроцессорного прерывания
+/// ([inter-processor interrupt](https://en.wikipedia.org/wiki/Inter-processor_interrupt), IPI)
+/// [`Trap::TlbShootdown`], см. [`memory::tlb_shootdown()`].
+extern "x86-interrupt" fn tlb_shootdown(context: TrapContext) {
+    memory::serve_tlb_shootdown();
+
+    generic_apic_interrupt(Trap::TlbShootdown, &context);
 }
 
 /// Обработчик ложных прерываний
 /// ([spurious interrupt](https://en.wikipedia.org/wiki/Interrupt#Spurious_interrupts))
 /// [APIC](https://en.wikipedia.org/wiki/Advanced_Programmable_Interrupt_Controller).
-extern "x86-interrupt" fn spurious(_context: TrapContext) {
-    generic_apic_interrupt(Trap::Spurious);
+extern "x86-interrupt" fn spurious(context: TrapContext) {
+    generic_apic_interrupt(Trap::Spurious, &context);
 }
 
+/// Счётчики срабатываний прерываний, которые не относятся ни к одному процессору,
+/// см. [`Statistics::count_on()`].
+static EARLY_TRAP_COUNTS: CpuTrapCounts = CpuTrapCounts::new();
+
+/// Обработчики прерываний свободных входов
+/// [PIC 8259](https://en.wikipedia.org/wiki/Intel_8259),
+/// зарегистрированные драйверами устройств.
+/// Прерывание `trap` соответствует индексу `trap - Trap::Free29`.
+/// Захватывается и в обработчиках прерываний, поэтому защищена [`IrqSpinlock`].
+static IRQ_HANDLERS: IrqSpinlock<
+    [Option<IrqHandler>; FREE_LINE_COUNT],
+    { PanicStrategy::KnockDown },
+> = IrqSpinlock::new([None; FREE_LINE_COUNT]);
+
+/// Количество ложных прерываний
+/// [PIC 8259](https://en.wikipedia.org/wiki/Intel_8259), см. [`spurious_pic_interrupts()`].
+static SPURIOUS_PIC_INTERRUPTS: AtomicUsize = AtomicUsize::new(0);
+
 /// Блокировка, предназначенная для останова всех процессоров кроме одного,
 /// в случае возникновения исключения `Trap::DoubleFault`.
 static STOP_ALL_CPUS: Spinlock<()> = Spinlock::new(());
 
+/// Счётчики срабатываний прерываний на каждом из процессоров, см. [`init_statistics()`].
+/// Пока они не заведены, указатель нулевой.
+static TRAP_COUNTS: AtomicPtr<PerCpu<CpuTrapCounts>> = AtomicPtr::new(ptr::null_mut());
+
 #[doc(hidden)]
 pub mod test_scaffolding {
     use ku::sync::Spinlock;
@@ -20,6 +20,7 @@ use ku::{
     time::{
         self,
         Hz,
+        Tsc,
         pit8254::TICKS_PER_SECOND,
         test_scaffolding::{
             NSECS_PER_SEC,
@@ -238,6 +239,45 @@ fn high_resolution_date() {
     );
 }
 
+#[test_case]
+fn fast_clock() {
+    wait_for_two_correlation_points();
+
+    let clock = ku::system_info().clock();
+    let max_difference = Duration::microseconds(10);
+
+    for _ in 0 .. FAST_CLOCK_SAMPLES {
+        let tsc = Tsc::now();
+        let fast = clock.datetime(tsc).expect("the fast clock is not calibrated");
+        let reference = time::datetime(tsc);
+        let difference = (fast - reference).abs();
+
+        if difference > max_difference {
+            debug!(%fast, %reference, %difference);
+        }
+        assert!(
+            difference <= max_difference,
+            "the fast clock disagrees with the RTC correlation interval",
+        );
+
+        // The user space reads the time with the fast clock without a syscall,
+        // while a syscall would report the kernel time computed from the RTC correlation.
+        let syscall_before = time::datetime(Tsc::synchronized());
+        let user = time::now();
+        let syscall_after = time::datetime(Tsc::synchronized());
+
+        if user < syscall_before - max_difference || syscall_after + max_difference < user {
+            debug!(%syscall_before, %user, %syscall_after);
+        }
+        assert!(
+            syscall_before - max_difference <= user && user <= syscall_after + max_difference,
+            "the user space time disagrees with the kernel time",
+        );
+
+        instructions::hlt();
+    }
+}
+
 fn wait_for_two_correlation_points() {
     debug!("waiting for the RTC to tick twice");
 
@@ -246,3 +286,6 @@ fn wait_for_two_correlation_points() {
         instructions::hlt();
     }
 }
+
+/// Количество сравнений быстрых часов с вычислением времени по показаниям RTC.
+const FAST_CLOCK_SAMPLES: usize = 100;
@@ -0,0 +1,73 @@
+#![deny(warnings)]
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(kernel::test_runner)]
+
+use core::hint;
+
+use serial::Serial;
+use text::TEXT;
+
+use kernel::{
+    Subsystems,
+    log::debug,
+    trap::{
+        TRAP_STATS,
+        Trap,
+    },
+};
+
+mod init;
+
+init!(Subsystems::empty());
+
+#[test_case]
+fn receive_interrupt() {
+    let start_traps = TRAP_STATS[Trap::Com1].count();
+
+    {
+        let mut text = TEXT.lock();
+        let serial = text.serial_mut();
+
+        while serial.read_octet().is_some() {}
+
+        // Пока включён режим петли, журнал не должен ничего выводить.
+        // Иначе выведенное попадёт в приёмник.
+        // Поэтому прерывание приёмопередатчика обрабатывается уже после снятия блокировки.
+        serial.set_loopback(true);
+        for &octet in MESSAGE {
+            serial.print_octet(octet);
+        }
+    }
+
+    let mut received = 0;
+    for _ in 0 .. MAX_ITERATIONS {
+        received = TEXT.lock().serial_mut().input_len();
+        if received >= MESSAGE.len() {
+            break;
+        }
+        hint::spin_loop();
+    }
+
+    let mut message = [0; MESSAGE.len()];
+    {
+        let mut text = TEXT.lock();
+        let serial = text.serial_mut();
+        serial.set_loopback(false);
+        for octet in message.iter_mut() {
+            *octet = serial.read_octet().unwrap_or_default();
+        }
+    }
+
+    let traps = TRAP_STATS[Trap::Com1].count() - start_traps;
+    debug!(received, traps, message = ?core::str::from_utf8(&message));
+
+    assert_eq!(received, MESSAGE.len());
+    assert!(traps > 0);
+    assert_eq!(&message, MESSAGE);
+}
+
+const MAX_ITERATIONS: usize = 100_000_000;
+const MESSAGE: &[u8] = b"nikka";
@@ -0,0 +1,81 @@
+#![deny(warnings)]
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(kernel::test_runner)]
+
+use core::sync::atomic::{
+    AtomicUsize,
+    Ordering,
+};
+
+use x86_64::instructions::interrupts;
+
+use ku::process::TrapInfo;
+
+use kernel::{
+    Subsystems,
+    error::Error::{
+        InvalidArgument,
+        PermissionDenied,
+    },
+    log::debug,
+    trap::{
+        self,
+        TRAP_STATS,
+        Trap,
+    },
+};
+
+mod init;
+
+init!(Subsystems::empty());
+
+#[test_case]
+fn free_line_handler() {
+    let start_traps = TRAP_STATS[Trap::Free2A].count();
+
+    trap::register_irq_handler(Trap::Free2A, handler).unwrap();
+    assert_eq!(
+        trap::register_irq_handler(Trap::Free2A, handler),
+        Err(PermissionDenied),
+    );
+
+    fire_free_2a();
+    fire_free_2a();
+    assert_eq!(CALLS.load(Ordering::Relaxed), 2);
+
+    trap::unregister_irq_handler(Trap::Free2A).unwrap();
+    fire_free_2a();
+    assert_eq!(CALLS.load(Ordering::Relaxed), 2);
+
+    let traps = TRAP_STATS[Trap::Free2A].count() - start_traps;
+    debug!(traps, calls = CALLS.load(Ordering::Relaxed));
+    assert_eq!(traps, 3);
+}
+
+#[test_case]
+fn not_a_free_line() {
+    for trap in [Trap::Com1, Trap::Rtc, Trap::Ps2Mouse, Trap::Breakpoint] {
+        assert_eq!(
+            trap::register_irq_handler(trap, handler),
+            Err(InvalidArgument),
+        );
+        assert_eq!(trap::unregister_irq_handler(trap), Err(InvalidArgument));
+    }
+}
+
+/// Имитирует срабатывание прерывания свободного входа `0xA` каскадной пары PIC.
+fn fire_free_2a() {
+    unsafe {
+        interrupts::software_interrupt::<{ Trap::Free2A as usize }>();
+    }
+}
+
+fn handler(info: &TrapInfo) {
+    assert_eq!(info.number(), usize::from(Trap::Free2A));
+    CALLS.fetch_add(1, Ordering::Relaxed);
+}
+
+static CALLS: AtomicUsize = AtomicUsize::new(0);
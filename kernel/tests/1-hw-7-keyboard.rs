@@ -0,0 +1,154 @@
+#![deny(warnings)]
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(kernel::test_runner)]
+
+use kernel::{
+    Subsystems,
+    input::{
+        self,
+        Key,
+        KeyEvent,
+        Modifiers,
+        test_scaffolding::process,
+    },
+    log::debug,
+};
+
+mod init;
+
+init!(Subsystems::empty());
+
+#[test_case]
+fn make_and_break() {
+    drain();
+
+    let a = Key::new(0x1E, false);
+    assert_eq!(
+        feed(&[0x1E, 0x9E]),
+        [
+            Some(press(a, Modifiers::empty())),
+            Some(release(a, Modifiers::empty())),
+        ],
+    );
+    assert_eq!(input::poll_key(), None);
+}
+
+#[test_case]
+fn modifiers() {
+    drain();
+
+    let a = Key::new(0x1E, false);
+    let caps_lock = Key::new(0x3A, false);
+    let left_shift = Key::new(0x2A, false);
+
+    assert_eq!(
+        feed(&[0x2A, 0x1E, 0x9E, 0xAA]),
+        [
+            Some(press(left_shift, Modifiers::SHIFT)),
+            Some(press(a, Modifiers::SHIFT)),
+            Some(release(a, Modifiers::SHIFT)),
+            Some(release(left_shift, Modifiers::empty())),
+        ],
+    );
+
+    assert_eq!(
+        feed(&[0x3A, 0xBA, 0x1E, 0x3A, 0xBA]),
+        [
+            Some(press(caps_lock, Modifiers::CAPS_LOCK)),
+            Some(release(caps_lock, Modifiers::CAPS_LOCK)),
+            Some(press(a, Modifiers::CAPS_LOCK)),
+            Some(press(caps_lock, Modifiers::empty())),
+            Some(release(caps_lock, Modifiers::empty())),
+        ],
+    );
+}
+
+#[test_case]
+fn extended() {
+    drain();
+
+    let right_ctrl = Key::new(0x1D, true);
+    let print_screen = Key::new(0x37, true);
+
+    assert_eq!(
+        feed(&[0xE0, 0x1D, 0xE0, 0x9D]),
+        [
+            Some(press(right_ctrl, Modifiers::CTRL)),
+            Some(release(right_ctrl, Modifiers::empty())),
+            None,
+            None,
+        ],
+    );
+
+    // Print Screen окружена фиктивными нажатиями Shift, а Pause не порождает событий.
+    process_all(&[0xE0, 0x2A, 0xE0, 0x37, 0xE0, 0xB7, 0xE0, 0xAA]);
+    process_all(&[0xE1, 0x1D, 0x45, 0xE1, 0x9D, 0xC5]);
+    assert_eq!(
+        [input::poll_key(), input::poll_key(), input::poll_key()],
+        [
+            Some(press(print_screen, Modifiers::empty())),
+            Some(release(print_screen, Modifiers::empty())),
+            None,
+        ],
+    );
+}
+
+#[test_case]
+fn overflow() {
+    drain();
+
+    let start_lost = input::lost_key_count();
+    for _ in 0 .. EVENT_COUNT / 2 {
+        process_all(&[0x1E, 0x9E]);
+    }
+
+    let received = drain();
+    let lost = input::lost_key_count() - start_lost;
+    debug!(received, lost);
+
+    assert!(lost > 0, "the event queue should overflow");
+    assert_eq!(received + lost, EVENT_COUNT);
+}
+
+/// Вычитывает все события из очереди и возвращает их количество.
+fn drain() -> usize {
+    let mut count = 0;
+    while input::poll_key().is_some() {
+        count += 1;
+    }
+    count
+}
+
+/// Обрабатывает скан-коды `scancodes` и вычитывает из очереди
+/// столько событий, сколько было скан-кодов.
+fn feed<const N: usize>(scancodes: &[u8; N]) -> [Option<KeyEvent>; N] {
+    process_all(scancodes);
+    core::array::from_fn(|_| input::poll_key())
+}
+
+/// Обрабатывает скан-коды `scancodes` так же, как обработчик прерывания клавиатуры.
+fn process_all(scancodes: &[u8]) {
+    for &scancode in scancodes {
+        process(scancode);
+    }
+}
+
+fn press(
+    key: Key,
+    modifiers: Modifiers,
+) -> KeyEvent {
+    KeyEvent::Press { key, modifiers }
+}
+
+fn release(
+    key: Key,
+    modifiers: Modifiers,
+) -> KeyEvent {
+    KeyEvent::Release { key, modifiers }
+}
+
+/// Количество событий, которое заведомо не помещается в очередь.
+const EVENT_COUNT: usize = 1_000;
@@ -0,0 +1,102 @@
+#![deny(warnings)]
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(kernel::test_runner)]
+
+use core::sync::atomic::{
+    AtomicUsize,
+    Ordering,
+};
+
+use x86_64::instructions::interrupts;
+
+use ku::process::{
+    Info,
+    TrapInfo,
+};
+
+use kernel::{
+    Subsystems,
+    error::Error::{
+        InvalidArgument,
+        PermissionDenied,
+    },
+    log::debug,
+    trap::{
+        self,
+        TRAP_STATS,
+        Trap,
+    },
+};
+
+mod init;
+
+init!(Subsystems::empty());
+
+#[test_case]
+fn irq_handler() {
+    let start_traps = TRAP_STATS[Trap::Free2B].count();
+
+    trap::register_irq_handler(Trap::Free2B, handler).unwrap();
+    assert_eq!(
+        trap::register_irq_handler(Trap::Free2B, handler),
+        Err(PermissionDenied),
+    );
+
+    fire_free_2b();
+    fire_free_2b();
+    assert_eq!(CALLS.load(Ordering::Relaxed), 2);
+    assert_ne!(RIP.load(Ordering::Relaxed), 0);
+
+    trap::unregister_irq_handler(Trap::Free2B).unwrap();
+    fire_free_2b();
+    assert_eq!(CALLS.load(Ordering::Relaxed), 2);
+
+    let traps = TRAP_STATS[Trap::Free2B].count() - start_traps;
+    debug!(traps, calls = CALLS.load(Ordering::Relaxed));
+    assert_eq!(traps, 3);
+}
+
+#[test_case]
+fn fixed_handler() {
+    for trap in [
+        Trap::Breakpoint,
+        Trap::PageFault,
+        Trap::Pit,
+        Trap::Keyboard,
+        Trap::Com1,
+        Trap::Rtc,
+        Trap::Ata0,
+        Trap::Ps2Mouse,
+        Trap::Coprocessor,
+        Trap::Timer,
+        Trap::Spurious,
+    ] {
+        assert_eq!(
+            trap::register_irq_handler(trap, handler),
+            Err(InvalidArgument),
+        );
+        assert_eq!(trap::unregister_irq_handler(trap), Err(InvalidArgument));
+    }
+}
+
+/// Имитирует срабатывание прерывания свободного входа `0xB` каскадной пары PIC.
+fn fire_free_2b() {
+    unsafe {
+        interrupts::software_interrupt::<{ Trap::Free2B as usize }>();
+    }
+}
+
+fn handler(info: &TrapInfo) {
+    assert_eq!(info.number(), usize::from(Trap::Free2B));
+    assert!(matches!(info.info(), Info::None));
+
+    RIP.store(info.context().rip().into_usize(), Ordering::Relaxed);
+    CALLS.fetch_add(1, Ordering::Relaxed);
+}
+
+static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+static RIP: AtomicUsize = AtomicUsize::new(0);
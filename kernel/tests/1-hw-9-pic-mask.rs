@@ -0,0 +1,70 @@
+#![deny(warnings)]
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(kernel::test_runner)]
+
+use x86_64::instructions::interrupts;
+
+use kernel::{
+    Subsystems,
+    log::debug,
+};
+
+mod init;
+
+init!(Subsystems::empty());
+
+#[test_case]
+fn mask_and_unmask() {
+    interrupts::without_interrupts(|| {
+        let start = pic8259::masked();
+        debug!(start);
+
+        for irq in [FLOPPY_DISK, LPT2, FREE_29] {
+            unsafe {
+                pic8259::mask(irq);
+            }
+            assert_eq!(pic8259::masked(), start | (1 << irq));
+
+            unsafe {
+                pic8259::unmask(irq);
+            }
+            assert_eq!(pic8259::masked(), start & !(1 << irq));
+        }
+    });
+}
+
+#[test_case]
+fn cascade() {
+    interrupts::without_interrupts(|| {
+        let start = pic8259::masked();
+
+        unsafe {
+            pic8259::mask(CASCADE);
+        }
+        assert_ne!(pic8259::masked() & (1 << CASCADE), 0);
+
+        unsafe {
+            pic8259::unmask(FREE_29);
+        }
+        let masked = pic8259::masked();
+        assert_eq!(masked & (1 << CASCADE), 0);
+        assert_eq!(masked & (1 << FREE_29), 0);
+
+        assert_eq!(masked, start & !(1 << CASCADE) & !(1 << FREE_29));
+    });
+}
+
+/// Вход каскадного прерывания первого контроллера PIC.
+const CASCADE: u8 = 2;
+
+/// Вход контроллера дискет.
+const FLOPPY_DISK: u8 = 6;
+
+/// Вход `0x9` каскадной пары PIC.
+const FREE_29: u8 = 9;
+
+/// Вход второго параллельного порта.
+const LPT2: u8 = 5;
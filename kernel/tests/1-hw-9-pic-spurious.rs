@@ -0,0 +1,54 @@
+#![deny(warnings)]
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(kernel::test_runner)]
+
+use x86_64::instructions::interrupts;
+
+use kernel::{
+    Subsystems,
+    log::debug,
+    trap::{
+        self,
+        TRAP_STATS,
+        Trap,
+    },
+};
+
+mod init;
+
+init!(Subsystems::empty());
+
+#[test_case]
+fn genuine_interrupts() {
+    for irq in 0 .. pic8259::PIC_INTERRUPT_COUNT {
+        assert!(
+            !pic8259::is_spurious(irq),
+            "in the automatic End Of Interrupt mode IRQ{irq} should never be reported as spurious",
+        );
+    }
+}
+
+#[test_case]
+fn lpt1() {
+    let start_traps = TRAP_STATS[Trap::Lpt1].count();
+    let start_spurious = trap::spurious_pic_interrupts();
+
+    for _ in 0 .. FIRE_COUNT {
+        unsafe {
+            interrupts::software_interrupt::<{ Trap::Lpt1 as usize }>();
+        }
+    }
+
+    let traps = TRAP_STATS[Trap::Lpt1].count() - start_traps;
+    let spurious = trap::spurious_pic_interrupts() - start_spurious;
+    debug!(traps, spurious);
+
+    assert_eq!(traps, FIRE_COUNT);
+    assert_eq!(spurious, 0);
+}
+
+/// Сколько раз имитируется прерывание [`Trap::Lpt1`].
+const FIRE_COUNT: usize = 3;
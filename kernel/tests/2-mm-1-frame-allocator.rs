@@ -136,6 +136,7 @@ fn t3_allocated_frames_are_not_used() {
         frames[frames_poisoned] = Some(frame_guard);
 
         let page = Page::containing(phys2virt.map(frame.address()).unwrap());
+        assert_eq!(phys2virt.page_of_frame(frame), Ok(page));
         let page_block = Block::new(page, (page + 1).unwrap()).unwrap();
 
         let slice = unsafe { page_block.try_into_mut_slice().unwrap() };
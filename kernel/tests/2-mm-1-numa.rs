@@ -0,0 +1,200 @@
+#![deny(warnings)]
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(kernel::test_runner)]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use core::cmp;
+
+use ku::error::Error::InvalidArgument;
+
+use kernel::{
+    Subsystems,
+    log::debug,
+    memory::{
+        FRAME_ALLOCATOR,
+        Frame,
+        NumaTopology,
+        TiB,
+    },
+    smp::test_scaffolding::parse_srat,
+};
+
+mod init;
+mod mm_helpers;
+
+init!(Subsystems::MEMORY);
+
+#[test_case]
+fn prefer_local_node() {
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let split = {
+        let a = FRAME_ALLOCATOR.lock().allocate().unwrap();
+        let b = FRAME_ALLOCATOR.lock().allocate().unwrap();
+        cmp::min(*a, *b)
+    };
+
+    let numa = parse_srat(&two_node_srat(split.index(), TiB / Frame::SIZE)).unwrap();
+    debug!(?numa, %split);
+    assert!(numa.is_numa());
+    assert_eq!(numa.node_count(), 2);
+
+    FRAME_ALLOCATOR.lock().set_numa_topology(numa);
+
+    let mut frames = Vec::new();
+
+    for _ in 0 .. ALLOCATIONS {
+        let frame = FRAME_ALLOCATOR.lock().allocate_for(0).unwrap();
+        assert!(*frame < split, "CPU 0 got a frame of the remote node");
+        frames.push(frame);
+    }
+
+    for _ in 0 .. REMOTE_ALLOCATIONS {
+        let frame = FRAME_ALLOCATOR.lock().allocate_for(1).unwrap();
+        assert!(*frame >= split, "CPU 1 got a frame of the remote node");
+        frames.push(frame);
+    }
+
+    drop(frames);
+
+    FRAME_ALLOCATOR.lock().set_numa_topology(NumaTopology::default());
+}
+
+#[test_case]
+fn fall_back_to_remote_node() {
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let end = TiB / Frame::SIZE;
+    let numa = parse_srat(&two_node_srat(end, 2 * end)).unwrap();
+    assert!(numa.is_numa());
+
+    FRAME_ALLOCATOR.lock().set_numa_topology(numa);
+
+    let frame = FRAME_ALLOCATOR.lock().allocate_for(1);
+    debug!(?frame, "node 1 has no memory");
+    assert!(frame.is_ok());
+    drop(frame);
+
+    FRAME_ALLOCATOR.lock().set_numa_topology(NumaTopology::default());
+}
+
+#[test_case]
+fn single_node() {
+    let mut srat = srat_header();
+    processor_affinity(&mut srat, 0, 0);
+    memory_affinity(&mut srat, 0, 0, TiB / Frame::SIZE);
+    set_length(&mut srat);
+
+    let numa = parse_srat(&srat).unwrap();
+    assert!(!numa.is_numa());
+    assert_eq!(numa.node_count(), 1);
+
+    assert!(!FRAME_ALLOCATOR.lock().numa_topology().is_numa());
+}
+
+#[test_case]
+fn invalid_srat() {
+    assert_eq!(parse_srat(&[0; 10]), Err(InvalidArgument));
+
+    let mut srat = srat_header();
+    processor_affinity(&mut srat, 0, 0);
+    srat.truncate(srat.len() - 1);
+    assert_eq!(parse_srat(&srat), Err(InvalidArgument));
+
+    let mut srat = srat_header();
+    srat.extend_from_slice(&[SRAT_PROCESSOR_AFFINITY, 0]);
+    assert_eq!(parse_srat(&srat), Err(InvalidArgument));
+}
+
+/// Синтетическая таблица SRAT из двух узлов.
+/// Узлу `0` принадлежат процессор `0` и фреймы с номерами `0 .. split`,
+/// узлу `1` --- процессор `1` и фреймы с номерами `split .. end`.
+fn two_node_srat(
+    split: usize,
+    end: usize,
+) -> Vec<u8> {
+    let mut srat = srat_header();
+
+    processor_affinity(&mut srat, 0, 0);
+    processor_affinity(&mut srat, 1, 1);
+    memory_affinity(&mut srat, 0, 0, split);
+    memory_affinity(&mut srat, 1, split, end);
+    set_length(&mut srat);
+
+    srat
+}
+
+/// Заголовок таблицы SRAT без записей.
+fn srat_header() -> Vec<u8> {
+    let mut srat = Vec::new();
+    srat.extend_from_slice(b"SRAT");
+    srat.resize(SRAT_ENTRIES_OFFSET, 0);
+    srat
+}
+
+/// Добавляет в таблицу `srat` запись о процессоре `cpu` из домена `domain`.
+fn processor_affinity(
+    srat: &mut Vec<u8>,
+    cpu: u8,
+    domain: u8,
+) {
+    let mut entry = [0; 16];
+    entry[0] = SRAT_PROCESSOR_AFFINITY;
+    entry[1] = 16;
+    entry[2] = domain;
+    entry[3] = cpu;
+    entry[4 .. 8].copy_from_slice(&SRAT_ENABLED.to_le_bytes());
+
+    srat.extend_from_slice(&entry);
+}
+
+/// Добавляет в таблицу `srat` запись о фреймах с номерами `start .. end` из домена `domain`.
+fn memory_affinity(
+    srat: &mut Vec<u8>,
+    domain: u32,
+    start: usize,
+    end: usize,
+) {
+    let address = u64::try_from(start * Frame::SIZE).unwrap();
+    let size = u64::try_from((end - start) * Frame::SIZE).unwrap();
+
+    let mut entry = [0; 40];
+    entry[0] = SRAT_MEMORY_AFFINITY;
+    entry[1] = 40;
+    entry[2 .. 6].copy_from_slice(&domain.to_le_bytes());
+    entry[8 .. 16].copy_from_slice(&address.to_le_bytes());
+    entry[16 .. 24].copy_from_slice(&size.to_le_bytes());
+    entry[28 .. 32].copy_from_slice(&SRAT_ENABLED.to_le_bytes());
+
+    srat.extend_from_slice(&entry);
+}
+
+/// Записывает длину таблицы `srat` в её заголовок.
+fn set_length(srat: &mut [u8]) {
+    let length = u32::try_from(srat.len()).unwrap();
+    srat[4 .. 8].copy_from_slice(&length.to_le_bytes());
+}
+
+/// Сколько фреймов выделяется для процессора `0`.
+const ALLOCATIONS: usize = 100;
+
+/// Сколько фреймов выделяется для процессора `1`.
+/// В его узле заведомо свободны только два фрейма.
+const REMOTE_ALLOCATIONS: usize = 2;
+
+/// Признак включённой записи таблицы SRAT.
+const SRAT_ENABLED: u32 = 1 << 0;
+
+/// Смещение первой записи таблицы SRAT от начала таблицы.
+const SRAT_ENTRIES_OFFSET: usize = 48;
+
+/// Тип записи таблицы SRAT о диапазоне физической памяти.
+const SRAT_MEMORY_AFFINITY: u8 = 1;
+
+/// Тип записи таблицы SRAT о процессоре.
+const SRAT_PROCESSOR_AFFINITY: u8 = 0;
@@ -7,6 +7,7 @@
 #![test_runner(kernel::test_runner)]
 
 use ku::{
+    error::Error::Overflow,
     log::debug,
     memory::{
         Block,
@@ -32,9 +33,13 @@ use ku::{
 
 use kernel::{
     Subsystems,
-    memory::test_scaffolding::{
-        PAGES_PER_ROOT_LEVEL_ENTRY,
-        make_phys2virt,
+    memory::{
+        BASE_ADDRESS_SPACE,
+        test_scaffolding::{
+            PAGES_PER_ROOT_LEVEL_ENTRY,
+            make_phys2virt,
+            phys2virt,
+        },
     },
 };
 
@@ -126,3 +131,44 @@ fn basic() {
         }
     }
 }
+
+#[test_case]
+fn frame_conversions() {
+    let phys2virt = phys2virt(&BASE_ADDRESS_SPACE.lock());
+    let frame_count = init::frame_count();
+    let window_start = phys2virt.page_of_frame(Frame::default()).unwrap();
+    debug!(%phys2virt, %window_start, frame_count);
+
+    for index in [0, 1, frame_count / 2, frame_count - 1] {
+        let frame = Frame::from_index(index).unwrap();
+        let page = phys2virt.page_of_frame(frame).unwrap();
+        let virt = phys2virt.frame_to_virt(frame).unwrap();
+        debug!(%frame, %page, %virt);
+
+        assert_eq!(virt, page.address());
+        assert_eq!(
+            (page - window_start).unwrap(),
+            index,
+            "frames should be mapped linearly into the phys2virt window",
+        );
+        assert_eq!(phys2virt.frame_of_page(page), Ok(frame));
+        assert_eq!(phys2virt.virt_to_phys(virt), Ok(frame.address()));
+
+        let offset = Frame::SIZE - 1;
+        let last_byte = (virt + offset).unwrap();
+        let last_byte_phys = (frame.address() + offset).unwrap();
+        assert_eq!(phys2virt.virt_to_phys(last_byte), Ok(last_byte_phys));
+        assert_eq!(phys2virt.map(last_byte_phys), Ok(last_byte));
+    }
+
+    let outside_frame = Frame::from_index(2 * frame_count).unwrap();
+    assert_eq!(phys2virt.frame_to_virt(outside_frame), Err(Overflow));
+    assert_eq!(phys2virt.page_of_frame(outside_frame), Err(Overflow));
+
+    let before_window = (window_start - 1).unwrap();
+    assert_eq!(phys2virt.frame_of_page(before_window), Err(Overflow));
+    assert_eq!(
+        phys2virt.virt_to_phys((window_start.address() - 1).unwrap()),
+        Err(Overflow),
+    );
+}
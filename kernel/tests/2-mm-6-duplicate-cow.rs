@@ -0,0 +1,191 @@
+#![deny(warnings)]
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(kernel::test_runner)]
+
+use core::mem;
+
+use ku::memory::mmu::PageTableFlags;
+
+use kernel::{
+    Subsystems,
+    log::debug,
+    memory::{
+        AddressSpace,
+        BASE_ADDRESS_SPACE,
+        FRAME_ALLOCATOR,
+        Frame,
+        Page,
+        USER_R,
+        USER_RW,
+        check_frame_references,
+        test_scaffolding::{
+            duplicate,
+            duplicate_cow,
+            map_page,
+            phys2virt,
+            translate,
+            user_pages,
+        },
+    },
+};
+
+mod init;
+mod mm_helpers;
+
+init!(Subsystems::PHYS_MEMORY | Subsystems::VIRT_MEMORY);
+
+#[test_case]
+fn shared_frames() {
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let [writable, read_only] = pages();
+    let mut address_space = prepare();
+    let mut copy = duplicate_cow(&mut address_space).unwrap();
+
+    for page in [writable, read_only] {
+        let shared_frame = frame(&mut address_space, page);
+        assert_eq!(frame(&mut copy, page), shared_frame);
+
+        let reference_count = FRAME_ALLOCATOR.lock().reference_count(shared_frame).unwrap();
+        debug!(%page, frame = %shared_frame, reference_count);
+        assert_eq!(reference_count, 2);
+    }
+
+    for address_space in [&mut address_space, &mut copy] {
+        let writable_flags = flags(address_space, writable);
+        debug!(page = %writable, flags = ?writable_flags);
+        assert!(!writable_flags.is_writable());
+        assert!(writable_flags.contains(PageTableFlags::COPY_ON_WRITE));
+
+        let read_only_flags = flags(address_space, read_only);
+        debug!(page = %read_only, flags = ?read_only_flags);
+        assert!(!read_only_flags.is_writable());
+        assert!(!read_only_flags.contains(PageTableFlags::COPY_ON_WRITE));
+    }
+}
+
+#[test_case]
+fn write_copies_frame() {
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let [writable, read_only] = pages();
+    let mut address_space = prepare();
+    let mut copy = duplicate_cow(&mut address_space).unwrap();
+
+    let start_free_frames = FRAME_ALLOCATOR.lock().count();
+    assert_eq!(unsafe { copy.copy_on_write(read_only) }, Ok(false));
+    assert_eq!(FRAME_ALLOCATOR.lock().count(), start_free_frames);
+
+    assert_eq!(unsafe { copy.copy_on_write(writable) }, Ok(true));
+    assert_eq!(
+        FRAME_ALLOCATOR.lock().count(),
+        start_free_frames - 1,
+        "the write should copy exactly one frame",
+    );
+    assert_ne!(
+        frame(&mut address_space, writable),
+        frame(&mut copy, writable),
+    );
+
+    let copy_flags = flags(&mut copy, writable);
+    assert!(copy_flags.is_writable());
+    assert!(!copy_flags.contains(PageTableFlags::COPY_ON_WRITE));
+
+    assert!(content(&mut copy, writable).iter().all(|&x| x == ORIGINAL));
+    content(&mut copy, writable).fill(CHANGED);
+    assert!(
+        content(&mut address_space, writable).iter().all(|&x| x == ORIGINAL),
+        "the write to the copy is visible in the original address space",
+    );
+
+    // Теперь у фрейма исходного адресного пространства нет других владельцев.
+    let start_free_frames = FRAME_ALLOCATOR.lock().count();
+    let original_frame = frame(&mut address_space, writable);
+    assert_eq!(unsafe { address_space.copy_on_write(writable) }, Ok(true));
+    assert_eq!(FRAME_ALLOCATOR.lock().count(), start_free_frames);
+    assert_eq!(frame(&mut address_space, writable), original_frame);
+    assert!(flags(&mut address_space, writable).is_writable());
+}
+
+#[test_case]
+fn frame_references() {
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let [writable, _] = pages();
+    let mut address_space = prepare();
+    assert_eq!(check_frame_references(&mut [&mut address_space]), 0);
+
+    let mut copy = duplicate_cow(&mut address_space).unwrap();
+    let mismatches = check_frame_references(&mut [&mut address_space, &mut copy]);
+    assert_eq!(mismatches, 0);
+
+    assert_eq!(unsafe { copy.copy_on_write(writable) }, Ok(true));
+    let mismatches = check_frame_references(&mut [&mut address_space, &mut copy]);
+    assert_eq!(mismatches, 0);
+
+    // Лишняя ссылка на фрейм должна быть обнаружена.
+    let copy_frame = frame(&mut copy, writable);
+    let extra_reference = FRAME_ALLOCATOR.lock().reference(copy_frame);
+    let mismatches = check_frame_references(&mut [&mut address_space, &mut copy]);
+    assert_eq!(mismatches, 1);
+    drop(extra_reference);
+
+    drop(copy);
+    assert_eq!(check_frame_references(&mut [&mut address_space]), 0);
+}
+
+/// Возвращает доступную на запись и доступную только на чтение страницы для тестов.
+fn pages() -> [Page; 2] {
+    let mut pages = user_pages().into_iter();
+    [pages.next().unwrap(), pages.next().unwrap()]
+}
+
+/// Создаёт адресное пространство с отображёнными страницами [`pages()`],
+/// заполненными значением [`ORIGINAL`].
+fn prepare() -> AddressSpace {
+    let mut address_space = duplicate(&BASE_ADDRESS_SPACE.lock()).unwrap();
+
+    for (page, flags) in pages().into_iter().zip([USER_RW, USER_R]) {
+        unsafe {
+            map_page(&mut address_space, page, flags).unwrap();
+        }
+        content(&mut address_space, page).fill(ORIGINAL);
+    }
+
+    address_space
+}
+
+/// Возвращает физический фрейм, в который отображена страница `page`.
+fn frame(
+    address_space: &mut AddressSpace,
+    page: Page,
+) -> Frame {
+    translate(address_space, page.address()).unwrap().frame().unwrap()
+}
+
+/// Возвращает флаги, с которыми отображена страница `page`.
+fn flags(
+    address_space: &mut AddressSpace,
+    page: Page,
+) -> PageTableFlags {
+    translate(address_space, page.address()).unwrap().flags()
+}
+
+/// Возвращает содержимое страницы `page`, обращаясь к её физическому фрейму напрямую.
+fn content(
+    address_space: &mut AddressSpace,
+    page: Page,
+) -> &'static mut [u64] {
+    let frame = frame(address_space, page);
+    let virt = phys2virt(address_space).frame_to_virt(frame).unwrap();
+    unsafe { virt.try_into_mut_slice(Page::SIZE / mem::size_of::<u64>()).unwrap() }
+}
+
+/// Значение, которым заполнены страницы при создании адресного пространства.
+const ORIGINAL: u64 = 0x0123_4567_89AB_CDEF;
+
+/// Значение, которое записывается в копию страницы.
+const CHANGED: u64 = 0xFEDC_BA98_7654_3210;
@@ -0,0 +1,170 @@
+#![deny(warnings)]
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(kernel::test_runner)]
+
+use ku::error::Error::{
+    InvalidArgument,
+    NoPage,
+    Overflow,
+    PermissionDenied,
+};
+
+use kernel::{
+    Subsystems,
+    log::debug,
+    memory::{
+        BASE_ADDRESS_SPACE,
+        Block,
+        Page,
+        USER_RW,
+        Virt,
+        copy_from_user,
+        copy_to_user,
+        read_user_str,
+    },
+    trap::{
+        TRAP_STATS,
+        Trap,
+    },
+};
+
+mod init;
+mod mm_helpers;
+
+init!(Subsystems::MEMORY);
+
+#[test_case]
+fn round_trip() {
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let mut address_space = BASE_ADDRESS_SPACE.lock();
+    let pages = address_space.allocate(Page::layout_array(1), USER_RW).unwrap();
+    debug!(%pages);
+
+    unsafe {
+        address_space.map_block(pages, USER_RW).unwrap();
+    }
+
+    let start_page_faults = TRAP_STATS[Trap::PageFault].count();
+
+    let address = (pages.start_address().address() + OFFSET).unwrap();
+    let src = [TEST_VALUE; LEN];
+    copy_to_user(address, &src).unwrap();
+
+    let mut dst = [0; LEN];
+    copy_from_user(&mut dst, address).unwrap();
+
+    assert_eq!(dst, src);
+    assert_eq!(TRAP_STATS[Trap::PageFault].count(), start_page_faults);
+
+    unsafe {
+        address_space.unmap_block(pages).unwrap();
+    }
+}
+
+#[test_case]
+fn unmapped() {
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let mut address_space = BASE_ADDRESS_SPACE.lock();
+    let pages = address_space.allocate(Page::layout_array(2), USER_RW).unwrap();
+    let mapped = Block::from_element(pages.start_element()).unwrap();
+    debug!(%pages, %mapped);
+
+    unsafe {
+        address_space.map_block(mapped, USER_RW).unwrap();
+    }
+
+    let unmapped = (pages.start_address().address() + Page::SIZE).unwrap();
+    let crossing = (unmapped - LEN / 2).unwrap();
+
+    let start_page_faults = TRAP_STATS[Trap::PageFault].count();
+
+    let mut buffer = [TEST_VALUE; LEN];
+    assert_eq!(copy_from_user(&mut buffer, unmapped), Err(NoPage));
+    assert_eq!(copy_to_user(unmapped, &buffer), Err(NoPage));
+
+    assert_eq!(copy_to_user(crossing, &buffer), Err(NoPage));
+    assert_eq!(copy_from_user(&mut buffer, crossing), Err(NoPage));
+
+    let page_faults = TRAP_STATS[Trap::PageFault].count() - start_page_faults;
+    debug!(page_faults);
+    assert_eq!(page_faults, 4);
+
+    let mut copy = [0; LEN];
+    copy_from_user(&mut copy[.. LEN / 2], crossing).unwrap();
+    assert!(
+        copy[.. LEN / 2].iter().all(|&x| x == TEST_VALUE),
+        "the bytes before the fault should be copied",
+    );
+
+    unsafe {
+        address_space.unmap_block(mapped).unwrap();
+    }
+}
+
+#[test_case]
+fn kernel_memory() {
+    let kernel_buffer = [TEST_VALUE; LEN];
+    let kernel_address = Virt::from_ref(&kernel_buffer);
+
+    let mut buffer = [0; LEN];
+    assert_eq!(
+        copy_from_user(&mut buffer, kernel_address),
+        Err(PermissionDenied),
+    );
+    assert_eq!(copy_to_user(kernel_address, &buffer), Err(PermissionDenied));
+
+    assert!(buffer.iter().all(|&x| x == 0));
+    assert!(kernel_buffer.iter().all(|&x| x == TEST_VALUE));
+}
+
+#[test_case]
+fn read_str() {
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let mut address_space = BASE_ADDRESS_SPACE.lock();
+    let pages = address_space.allocate(Page::layout_array(2), USER_RW).unwrap();
+    let mapped = Block::from_element(pages.start_element()).unwrap();
+    debug!(%pages, %mapped);
+
+    unsafe {
+        address_space.map_block(mapped, USER_RW).unwrap();
+    }
+
+    let address = (pages.start_address().address() + OFFSET).unwrap();
+    let unmapped = (pages.start_address().address() + Page::SIZE).unwrap();
+    let mut buffer = [0; LEN];
+
+    copy_to_user(address, b"hello\0").unwrap();
+    assert_eq!(read_user_str(address, &mut buffer), Ok("hello"));
+    assert_eq!(read_user_str(address, &mut buffer[.. 6]), Ok("hello"));
+    assert_eq!(read_user_str(address, &mut buffer[.. 5]), Err(Overflow));
+
+    copy_to_user(address, &[0xFF, 0xFE, 0]).unwrap();
+    assert_eq!(read_user_str(address, &mut buffer), Err(InvalidArgument));
+
+    let at_boundary = (unmapped - 3).unwrap();
+    copy_to_user(at_boundary, b"ok\0").unwrap();
+    assert_eq!(read_user_str(at_boundary, &mut buffer), Ok("ok"));
+
+    let crossing = (unmapped - 2).unwrap();
+    assert_eq!(read_user_str(crossing, &mut buffer), Err(NoPage));
+    assert_eq!(read_user_str(crossing, &mut buffer[.. 2]), Err(Overflow));
+
+    unsafe {
+        address_space.unmap_block(mapped).unwrap();
+    }
+}
+
+/// Количество копируемых байт.
+const LEN: usize = 64;
+
+/// Смещение копируемых данных внутри страницы.
+const OFFSET: usize = 123;
+
+/// Значение, которым заполняются копируемые данные.
+const TEST_VALUE: u8 = 0x77;
@@ -0,0 +1,130 @@
+#![deny(warnings)]
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(kernel::test_runner)]
+
+use x86_64::registers::control::{
+    Cr4,
+    Cr4Flags,
+};
+
+use kernel::{
+    Subsystems,
+    log::debug,
+    memory::{
+        self,
+        BASE_ADDRESS_SPACE,
+        Page,
+        USER_R,
+        USER_RW,
+        UserAccess,
+        test_scaffolding::{
+            self,
+            check_permission,
+            check_permission_mut,
+        },
+    },
+};
+
+mod init;
+mod mm_helpers;
+
+init!(Subsystems::MEMORY);
+
+#[test_case]
+fn protections() {
+    let cr4 = Cr4::read();
+    let smep = memory::is_smep_enabled();
+    let smap = memory::is_smap_enabled();
+    debug!(smep, smap, ?cr4);
+
+    assert_eq!(
+        cr4.contains(Cr4Flags::SUPERVISOR_MODE_EXECUTION_PROTECTION),
+        smep,
+    );
+    assert_eq!(
+        cr4.contains(Cr4Flags::SUPERVISOR_MODE_ACCESS_PREVENTION),
+        smap,
+    );
+}
+
+#[test_case]
+fn bracketed_read() {
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let mut address_space = BASE_ADDRESS_SPACE.lock();
+    let pages = address_space.allocate(Page::layout_array(1), USER_RW).unwrap();
+    debug!(%pages);
+
+    unsafe {
+        address_space.map_block(pages, USER_RW).unwrap();
+    }
+
+    let start_violations = memory::smap_violations();
+
+    {
+        let _user_access = UserAccess::new();
+
+        check_permission_mut::<u8>(&mut address_space, pages.into(), USER_RW)
+            .expect("test block should be writable for the user space")
+            .fill(TEST_VALUE);
+
+        {
+            let _nested_user_access = UserAccess::new();
+        }
+
+        let slice = check_permission::<u8>(&mut address_space, pages.into(), USER_R)
+            .expect("test block should be readable for the user space");
+
+        assert!(slice.iter().all(|x| *x == TEST_VALUE));
+    }
+
+    assert_eq!(memory::smap_violations(), start_violations);
+
+    unsafe {
+        address_space.unmap_block(pages).unwrap();
+    }
+}
+
+#[test_case]
+fn unbracketed_read() {
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let mut address_space = BASE_ADDRESS_SPACE.lock();
+    let pages = address_space.allocate(Page::layout_array(1), USER_RW).unwrap();
+    debug!(%pages);
+
+    unsafe {
+        address_space.map_block(pages, USER_RW).unwrap();
+    }
+
+    {
+        let _user_access = UserAccess::new();
+        check_permission_mut::<u8>(&mut address_space, pages.into(), USER_RW)
+            .unwrap()
+            .fill(TEST_VALUE);
+    }
+
+    let start_violations = memory::smap_violations();
+    let (value, faulted) = test_scaffolding::unbracketed_read(pages.try_into_ptr().unwrap());
+    let violations = memory::smap_violations() - start_violations;
+    debug!(value, faulted, violations, smap = memory::is_smap_enabled());
+
+    assert_eq!(value, TEST_VALUE);
+    assert_eq!(
+        faulted,
+        memory::is_smap_enabled(),
+        "a kernel read of the user memory outside of UserAccess should fault if and only if SMAP \
+         is enabled",
+    );
+    assert_eq!(violations, usize::from(faulted));
+
+    unsafe {
+        address_space.unmap_block(pages).unwrap();
+    }
+}
+
+/// Значение, которым заполняется тестовая страница пользователя.
+const TEST_VALUE: u8 = 0x77;
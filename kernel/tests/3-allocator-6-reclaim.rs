@@ -0,0 +1,65 @@
+#![deny(warnings)]
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(kernel::test_runner)]
+
+extern crate alloc;
+
+use alloc::{
+    boxed::Box,
+    vec::Vec,
+};
+
+use ku::memory::Page;
+
+use kernel::{
+    Subsystems,
+    allocator,
+    log::debug,
+    memory::FRAME_ALLOCATOR,
+};
+
+mod init;
+
+init!(Subsystems::MEMORY);
+
+#[test_case]
+fn reclaim() {
+    allocator::reclaim();
+    let start_free_frames = FRAME_ALLOCATOR.lock().count();
+
+    let mut values = Vec::with_capacity(VALUE_COUNT);
+    for i in 0 .. VALUE_COUNT {
+        values.push(Box::new([i; VALUE_SIZE]));
+    }
+    let peak_free_frames = FRAME_ALLOCATOR.lock().count();
+
+    for (i, value) in values.iter().enumerate() {
+        assert!(value.iter().all(|&x| x == i));
+    }
+    drop(values);
+    let freed_free_frames = FRAME_ALLOCATOR.lock().count();
+
+    let reclaimed = allocator::reclaim();
+    let end_free_frames = FRAME_ALLOCATOR.lock().count();
+
+    let used = start_free_frames - peak_free_frames;
+    let retained = start_free_frames.saturating_sub(end_free_frames);
+    debug!(used, reclaimed, retained);
+
+    assert!(used >= VALUE_COUNT * VALUE_SIZE * size_of::<usize>() / Page::SIZE);
+    assert!(freed_free_frames < start_free_frames);
+    assert!(reclaimed > 0);
+    assert!(end_free_frames > freed_free_frames);
+    assert!(retained <= used / 10);
+}
+
+/// Количество одновременно живых значений во время всплеска выделений.
+const VALUE_COUNT: usize = 4 * 1024;
+
+/// Количество слов в каждом значении.
+/// Размер выбран так, чтобы остальной код ядра почти не пользовался
+/// соответствующим ему аллокатором блоков фиксированного размера.
+const VALUE_SIZE: usize = 125;
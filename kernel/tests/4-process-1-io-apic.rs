@@ -0,0 +1,94 @@
+#![deny(warnings)]
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(kernel::test_runner)]
+
+use x86_64::instructions;
+
+use ku::error::Error::InvalidArgument;
+
+use kernel::{
+    Subsystems,
+    log::debug,
+    smp::test_scaffolding::{
+        id,
+        io_apic_destination,
+        is_routed,
+        set_io_apic_destination,
+    },
+    trap::{
+        TRAP_STATS,
+        Trap,
+    },
+};
+
+mod init;
+
+init!(Subsystems::MEMORY | Subsystems::LOCAL_APIC | Subsystems::IO_APIC);
+
+#[test_case]
+fn handoff() {
+    let masked = pic8259::masked();
+    debug!(masked);
+    assert_eq!(masked, u16::MAX, "the legacy PIC should be fully masked");
+
+    for irq in 0 .. ISA_IRQ_COUNT {
+        let expected = irq != CASCADE;
+        assert_eq!(is_routed(irq), expected, "unexpected routing of IRQ {irq}");
+
+        if expected {
+            assert_eq!(io_apic_destination(irq), Ok(id()));
+        }
+    }
+
+    assert_eq!(io_apic_destination(CASCADE), Err(InvalidArgument));
+    assert!(!is_routed(ISA_IRQ_COUNT));
+}
+
+#[test_case]
+fn destination() {
+    for irq in [KEYBOARD, RTC, ATA0, ATA1] {
+        assert_eq!(set_io_apic_destination(irq, id()), Ok(()));
+        assert_eq!(io_apic_destination(irq), Ok(id()));
+    }
+
+    for irq in [CASCADE, ISA_IRQ_COUNT] {
+        assert_eq!(set_io_apic_destination(irq, id()), Err(InvalidArgument));
+    }
+}
+
+#[test_case]
+fn interrupts_delivered() {
+    for trap in [Trap::Pit, Trap::Rtc] {
+        let start = TRAP_STATS[trap].count();
+
+        while TRAP_STATS[trap].count() < start + TICKS {
+            instructions::hlt();
+        }
+
+        debug!(?trap, count = TRAP_STATS[trap].count());
+    }
+}
+
+/// Вход первого контроллера PATA.
+const ATA0: u8 = 14;
+
+/// Вход второго контроллера PATA.
+const ATA1: u8 = 15;
+
+/// Каскадный вход первого контроллера PIC, который IO APIC не обслуживает.
+const CASCADE: u8 = 2;
+
+/// Количество линий прерываний шины ISA.
+const ISA_IRQ_COUNT: u8 = 16;
+
+/// Вход клавиатуры.
+const KEYBOARD: u8 = 1;
+
+/// Вход часов реального времени.
+const RTC: u8 = 8;
+
+/// Сколько прерываний от каждого устройства нужно дождаться через IO APIC.
+const TICKS: usize = 3;
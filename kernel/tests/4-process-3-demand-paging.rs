@@ -0,0 +1,91 @@
+#![deny(warnings)]
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(kernel::test_runner)]
+
+use ku::process::{
+    ExitCode,
+    Syscall,
+};
+
+use kernel::{
+    Subsystems,
+    error::Result,
+    log::debug,
+    memory::FRAME_ALLOCATOR,
+    process::{
+        self,
+        Process,
+        Scheduler,
+        Table,
+        test_scaffolding::{
+            create_lazy_process,
+            create_process,
+        },
+    },
+    trap::{
+        TRAP_STATS,
+        Trap,
+    },
+};
+
+mod init;
+mod mm_helpers;
+mod process_helpers;
+
+init!(Subsystems::MEMORY | Subsystems::SYSCALL | Subsystems::SMP | Subsystems::PROCESS);
+
+const SYSCALL_STATS_ELF: &[u8] = page_aligned!("../../target/kernel/user/syscall_stats");
+
+#[test_case]
+fn initial_frames() {
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let eager = used_frames(|| create_process(SYSCALL_STATS_ELF));
+    let lazy = used_frames(|| create_lazy_process(SYSCALL_STATS_ELF));
+    debug!(eager, lazy);
+
+    assert!(
+        lazy < eager,
+        "demand paging should map fewer frames at the process creation",
+    );
+}
+
+#[test_case]
+fn lazy_process_runs() {
+    let _trap_guard = process_helpers::forbid_traps_except(&[Trap::PageFault]);
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let start_page_faults = TRAP_STATS[Trap::PageFault].count();
+
+    let pid = process::create_lazy(SYSCALL_STATS_ELF).unwrap();
+    let trace = Table::get(pid).unwrap().trace_syscalls();
+
+    Scheduler::enqueue(pid);
+    while Scheduler::run_one() {}
+
+    Table::get(pid).expect_err("the lazily loaded process was not run up to its completion");
+
+    let page_faults = TRAP_STATS[Trap::PageFault].count() - start_page_faults;
+    debug!(page_faults);
+    assert!(page_faults > 0, "no pages were loaded on demand");
+
+    let records = trace.records();
+    let exit = records.last().expect("the process made no syscalls");
+    debug!(?exit);
+    assert_eq!(exit.syscall(), Syscall::Exit);
+    assert_eq!(exit.args()[0], usize::from(ExitCode::Ok));
+}
+
+/// Возвращает количество физических фреймов,
+/// которые занимает созданный функцией `create` процесс.
+fn used_frames(create: impl FnOnce() -> Result<Process>) -> usize {
+    let start_free_frames = FRAME_ALLOCATOR.lock().count();
+    let process = create().expect("failed to create the test process");
+    let used_frames = start_free_frames - FRAME_ALLOCATOR.lock().count();
+    drop(process);
+
+    used_frames
+}
@@ -6,6 +6,8 @@
 #![reexport_test_harness_main = "test_main"]
 #![test_runner(kernel::test_runner)]
 
+use ku::process::elf::BuildInfo;
+
 use kernel::{
     Subsystems,
     log::info,
@@ -36,3 +38,12 @@ fn create_process_failure() {
 
     info!(?error, "expected a process creation failure");
 }
+
+#[test_case]
+fn build_info() {
+    let build_info = BuildInfo::new(LOOP_ELF).unwrap();
+    info!(%build_info);
+
+    assert!(build_info.build_id().is_some_and(|build_id| !build_id.is_empty()));
+    assert!(build_info.version().is_some_and(|version| !version.is_empty()));
+}
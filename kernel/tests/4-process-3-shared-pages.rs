@@ -0,0 +1,100 @@
+#![deny(warnings)]
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(kernel::test_runner)]
+
+use ku::process::{
+    Syscall,
+    elf,
+};
+
+use kernel::{
+    Subsystems,
+    log::debug,
+    memory::{
+        FRAME_ALLOCATOR,
+        Frame,
+        test_scaffolding::translate,
+    },
+    process::{
+        self,
+        Pid,
+        Scheduler,
+        Table,
+    },
+};
+
+mod init;
+mod mm_helpers;
+mod process_helpers;
+
+init!(Subsystems::MEMORY | Subsystems::SYSCALL | Subsystems::SMP | Subsystems::PROCESS);
+
+const SYSCALL_STATS_ELF: &[u8] = page_aligned!("../../target/kernel/user/syscall_stats");
+
+#[test_case]
+fn shared_frames() {
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let start_free_frames = FRAME_ALLOCATOR.lock().count();
+    let single = create();
+    let single_frames = start_free_frames - FRAME_ALLOCATOR.lock().count();
+    process_helpers::free(single);
+
+    let first = create();
+    let second = create();
+    let combined_frames = start_free_frames - FRAME_ALLOCATOR.lock().count();
+
+    debug!(single_frames, combined_frames);
+    assert!(
+        combined_frames < 2 * single_frames,
+        "processes of the same ELF file should share their read-only frames",
+    );
+
+    assert_eq!(
+        entry_point_frame(first),
+        entry_point_frame(second),
+        "the code page should be shared",
+    );
+
+    process_helpers::free(first);
+    process_helpers::free(second);
+}
+
+#[test_case]
+fn shared_frames_run() {
+    let _trap_guard = process_helpers::forbid_traps();
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let first = create();
+    let second = create();
+    assert_eq!(entry_point_frame(first), entry_point_frame(second));
+
+    let traces = [first, second].map(|pid| Table::get(pid).unwrap().trace_syscalls());
+
+    Scheduler::enqueue(first);
+    Scheduler::enqueue(second);
+    while Scheduler::run_one() {}
+
+    for pid in [first, second] {
+        Table::get(pid).expect_err("the process was not run up to its completion");
+    }
+
+    let [first_trace, second_trace] = traces;
+    assert_eq!(*first_trace.syscalls().last().unwrap(), Syscall::Exit);
+    assert_eq!(first_trace.syscalls(), second_trace.syscalls());
+}
+
+/// Создаёт процесс `syscall_stats` и возвращает его [`Pid`].
+fn create() -> Pid {
+    process::create(SYSCALL_STATS_ELF).expect("failed to create the test process")
+}
+
+/// Возвращает физический фрейм, в котором находится точка входа процесса `pid`.
+fn entry_point_frame(pid: Pid) -> Frame {
+    let entry_point = elf::entry_point(SYSCALL_STATS_ELF).unwrap();
+    let mut process = Table::get(pid).unwrap();
+    translate(process.address_space(), entry_point).unwrap().frame().unwrap()
+}
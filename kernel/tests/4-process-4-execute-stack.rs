@@ -0,0 +1,78 @@
+#![deny(warnings)]
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(kernel::test_runner)]
+
+use ku::{
+    memory::PageFaultInfo,
+    process::Info,
+};
+
+use kernel::{
+    Subsystems,
+    log::debug,
+    memory::Virt,
+    process::{
+        Process,
+        Table,
+    },
+    trap::{
+        TRAP_STATS,
+        Trap,
+    },
+};
+
+mod init;
+mod mm_helpers;
+mod process_helpers;
+
+init!(Subsystems::MEMORY | Subsystems::SMP | Subsystems::PROCESS);
+
+const EXECUTE_STACK_ELF: &[u8] = page_aligned!("../../target/kernel/user/execute_stack");
+
+#[test_case]
+fn instruction_fetch() {
+    let address = Virt::new(0x1000).unwrap();
+
+    let execute = Info::PageFault {
+        address,
+        code: PageFaultInfo::PRESENT | PageFaultInfo::USER | PageFaultInfo::EXECUTE,
+    };
+    let write = Info::PageFault {
+        address,
+        code: PageFaultInfo::PRESENT | PageFaultInfo::USER | PageFaultInfo::WRITE,
+    };
+
+    assert!(execute.is_instruction_fetch());
+    assert!(!write.is_instruction_fetch());
+    assert!(!Info::Code(PageFaultInfo::EXECUTE.bits()).is_instruction_fetch());
+    assert!(!Info::None.is_instruction_fetch());
+}
+
+#[test_case]
+fn execute_stack() {
+    let _trap_guard = process_helpers::forbid_traps_except(&[Trap::PageFault]);
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let start_page_faults = TRAP_STATS[Trap::PageFault].count();
+
+    let pid = process_helpers::allocate(EXECUTE_STACK_ELF).pid();
+
+    let process = Table::get(pid).expect("failed to find the new process in the process table");
+    let preempted = Process::enter_user_mode(process);
+
+    let page_faults = TRAP_STATS[Trap::PageFault].count() - start_page_faults;
+    debug!(preempted, page_faults);
+
+    assert!(!preempted);
+    assert_eq!(
+        page_faults, 1,
+        "the code on the stack should not be executed",
+    );
+    assert!(
+        Table::get(pid).is_err(),
+        "the process should be killed on the instruction fetch fault",
+    );
+}
@@ -17,6 +17,7 @@ use kernel::{
         Page,
         USER_R,
         USER_RW,
+        UserAccess,
         Virt,
         mmu::PageTableFlags,
         test_scaffolding::{
@@ -34,6 +35,7 @@ init!(Subsystems::MEMORY);
 #[test_case]
 fn user_rw() {
     let _guard = mm_helpers::forbid_frame_leaks();
+    let _user_access = UserAccess::new();
 
     let mut address_space = BASE_ADDRESS_SPACE.lock();
     let page_count = 4;
@@ -102,6 +104,7 @@ fn non_present() {
 #[test_case]
 fn stress() {
     let _guard = mm_helpers::forbid_frame_leaks();
+    let _user_access = UserAccess::new();
 
     let mut address_space = BASE_ADDRESS_SPACE.lock();
     let address_filter = |x: &Virt| (x.into_usize() + 2) % (Page::SIZE / 4) <= 4;
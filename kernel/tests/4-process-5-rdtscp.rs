@@ -0,0 +1,73 @@
+#![deny(warnings)]
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(kernel::test_runner)]
+
+use ku::{
+    sync::spinlock::Spinlock,
+    time,
+};
+
+use kernel::{
+    Subsystems,
+    log::debug,
+    process::{
+        Process,
+        test_scaffolding,
+    },
+    smp::test_scaffolding::cpu_id,
+    trap::Trap,
+};
+
+mod init;
+mod mm_helpers;
+mod process_helpers;
+
+init!(Subsystems::MEMORY | Subsystems::SMP);
+
+const RDTSCP_ELF: &[u8] = page_aligned!("../../target/kernel/user/rdtscp");
+
+#[test_case]
+fn kernel_mode() {
+    let (tsc, cpu) = time::tscp();
+    debug!(tsc, cpu);
+
+    assert_eq!(cpu, u32::from(cpu_id()));
+}
+
+#[test_case]
+fn user_mode() {
+    let _trap_guard = process_helpers::forbid_traps_except(&[Trap::PageFault]);
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let process = Spinlock::new(process_helpers::make(RDTSCP_ELF));
+
+    test_scaffolding::disable_interrupts(&mut process.lock());
+
+    let start = time::tsc();
+    Process::enter_user_mode(process.lock());
+    let end = time::tsc();
+
+    let user_registers = test_scaffolding::registers(&process.lock());
+    let user_cpu = user_registers[RDI];
+    let user_tsc = i64::try_from(user_registers[RSI]).unwrap();
+    debug!(user_cpu, user_tsc, start, end);
+
+    assert_eq!(
+        user_cpu,
+        usize::from(cpu_id()),
+        "rdtscp in the user space should return the id of the CPU running the process",
+    );
+    assert!(
+        start <= user_tsc && user_tsc <= end,
+        "rdtscp in the user space should return the current TSC",
+    );
+}
+
+/// Индекс регистра `rdi` в результате [`test_scaffolding::registers()`].
+const RDI: usize = 4;
+
+/// Индекс регистра `rsi` в результате [`test_scaffolding::registers()`].
+const RSI: usize = 5;
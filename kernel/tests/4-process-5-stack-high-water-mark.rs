@@ -0,0 +1,72 @@
+#![deny(warnings)]
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(kernel::test_runner)]
+
+use ku::sync::spinlock::Spinlock;
+
+use kernel::{
+    Subsystems,
+    log::debug,
+    process::{
+        Process,
+        test_scaffolding,
+    },
+    trap::Trap,
+};
+
+mod init;
+mod mm_helpers;
+mod process_helpers;
+
+init!(Subsystems::MEMORY | Subsystems::SMP);
+
+const RECURSION_ELF: &[u8] = page_aligned!("../../target/kernel/user/recursion");
+
+#[test_case]
+fn fresh_process() {
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let mut process = process_helpers::make(RECURSION_ELF);
+    let high_water_mark = process.stack_high_water_mark().unwrap();
+    debug!(high_water_mark);
+
+    assert_eq!(high_water_mark, 0, "the process has not run yet");
+}
+
+#[test_case]
+fn recursion() {
+    let _trap_guard = process_helpers::forbid_traps_except(&[Trap::PageFault]);
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let process = Spinlock::new(process_helpers::make(RECURSION_ELF));
+
+    test_scaffolding::disable_interrupts(&mut process.lock());
+
+    Process::enter_user_mode(process.lock());
+
+    let mut process = process.lock();
+    assert_eq!(test_scaffolding::registers(&process)[RDI], DEPTH + 1);
+
+    let stack_size = test_scaffolding::stack(&mut process).unwrap().size();
+    let high_water_mark = process.stack_high_water_mark().unwrap();
+    debug!(high_water_mark, stack_size);
+
+    assert!(
+        high_water_mark >= DEPTH * FRAME_SIZE,
+        "the recursion should have used at least {} bytes of the stack",
+        DEPTH * FRAME_SIZE,
+    );
+    assert!(high_water_mark < stack_size);
+}
+
+/// Глубина рекурсии в программе `recursion`.
+const DEPTH: usize = 64;
+
+/// Размер буфера, который программа `recursion` держит в каждом кадре стека.
+const FRAME_SIZE: usize = 256;
+
+/// Индекс регистра `rdi` в результате [`test_scaffolding::registers()`].
+const RDI: usize = 4;
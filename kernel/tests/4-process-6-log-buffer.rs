@@ -0,0 +1,88 @@
+#![deny(warnings)]
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(kernel::test_runner)]
+
+use kernel::{
+    Subsystems,
+    error::Error::InvalidArgument,
+    log::debug,
+    memory::{
+        BASE_ADDRESS_SPACE,
+        test_scaffolding::switch_to,
+    },
+    process::{
+        Process,
+        test_scaffolding::{
+            create_process_with_log_frame_count,
+            log_write_buffer,
+        },
+    },
+};
+
+mod init;
+mod process_helpers;
+
+init!(Subsystems::MEMORY);
+
+const LOOP_ELF: &[u8] = page_aligned!("../../target/kernel/user/loop");
+
+#[test_case]
+fn larger_log_buffer() {
+    let mut default_process = process_helpers::make(LOOP_ELF);
+    let mut large_process =
+        create_process_with_log_frame_count(LOOP_ELF, LARGE_LOG_FRAME_COUNT).unwrap();
+
+    assert_eq!(
+        default_process.log_frame_count(),
+        Process::DEFAULT_LOG_FRAME_COUNT,
+    );
+    assert_eq!(large_process.log_frame_count(), LARGE_LOG_FRAME_COUNT);
+
+    let default_messages = messages_before_loss(&mut default_process);
+    let large_messages = messages_before_loss(&mut large_process);
+    debug!(default_messages, large_messages);
+
+    // Вчетверо больший буфер с запасом вмещает хотя бы вдвое больше сообщений.
+    assert!(default_messages > 0);
+    assert!(large_messages >= 2 * default_messages);
+}
+
+#[test_case]
+fn invalid_log_buffer_size() {
+    for log_frame_count in [0, Process::MAX_LOG_FRAME_COUNT + 1] {
+        assert_eq!(
+            create_process_with_log_frame_count(LOOP_ELF, log_frame_count).err(),
+            Some(InvalidArgument),
+        );
+    }
+}
+
+/// Заполняет буфер журналирования процесса `process` одинаковыми сообщениями,
+/// пока они помещаются, и возвращает их количество.
+fn messages_before_loss(process: &mut Process) -> usize {
+    switch_to(process.address_space());
+
+    let log = log_write_buffer(process).unwrap();
+    let mut count = 0;
+
+    while let Some(mut tx) = log.write_tx() {
+        if tx.write(MESSAGE).is_err() {
+            break;
+        }
+        tx.commit();
+        count += 1;
+    }
+
+    switch_to(&BASE_ADDRESS_SPACE.lock());
+
+    count
+}
+
+/// Количество фреймов в увеличенном буфере журналирования.
+const LARGE_LOG_FRAME_COUNT: usize = 4 * Process::DEFAULT_LOG_FRAME_COUNT;
+
+/// Сообщение, которым заполняется буфер журналирования.
+const MESSAGE: &[u8] = b"a chatty process writes another log message";
@@ -29,12 +29,23 @@ use ku::{
 
 use kernel::{
     Subsystems,
-    memory::test_scaffolding::{
-        switch_to,
-        user_pages,
+    log::{
+        self as kernel_log,
+        debug,
+        event_count,
+        info,
+    },
+    memory::{
+        UserAccess,
+        test_scaffolding::{
+            switch_to,
+            user_pages,
+        },
     },
     process::test_scaffolding::{
+        log_bytes,
         log_value,
+        set_log_level,
         set_pid,
     },
 };
@@ -74,7 +85,10 @@ fn log_value_implementation() {
             .map_slice_zeroed::<u8>(pi.len(), USER_RW)
             .unwrap()
     };
-    user_memory[.. pi.len()].copy_from_slice(pi.as_bytes());
+    {
+        let _user_access = UserAccess::new();
+        user_memory[.. pi.len()].copy_from_slice(pi.as_bytes());
+    }
     let block = Block::from_slice(user_memory);
     let result = log_value(
         process.lock(),
@@ -109,7 +123,10 @@ fn log_value_implementation() {
     );
 
     let invalid_utf8 = b"\xFF";
-    user_memory[.. invalid_utf8.len()].copy_from_slice(invalid_utf8);
+    {
+        let _user_access = UserAccess::new();
+        user_memory[.. invalid_utf8.len()].copy_from_slice(invalid_utf8);
+    }
     let block = Block::from_slice(user_memory);
     assert_eq!(
         log_value(
@@ -133,3 +150,160 @@ fn log_value_implementation() {
         );
     }
 }
+
+#[test_case]
+fn log_bytes_implementation() {
+    let process = Spinlock::new(process_helpers::make(LOOP_ELF));
+    set_pid(&mut process.lock(), Pid::new(0));
+    switch_to(process.lock().address_space());
+
+    let info = size::from(u32::from(log::level_into_symbol(&Level::INFO)));
+
+    let label = "header";
+    let header = [0xDE, 0xAD, 0xBE, 0xEF];
+    let user_memory = unsafe {
+        process
+            .lock()
+            .address_space()
+            .map_slice_zeroed::<u8>(label.len() + header.len(), USER_RW)
+            .unwrap()
+    };
+    {
+        let _user_access = UserAccess::new();
+        user_memory[.. label.len()].copy_from_slice(label.as_bytes());
+        user_memory[label.len() .. label.len() + header.len()].copy_from_slice(&header);
+    }
+    let label_address = Block::from_slice(user_memory).start_address().into_usize();
+    let header_address = label_address + label.len();
+
+    let result = log_bytes(
+        process.lock(),
+        info,
+        label_address,
+        label.len(),
+        header_address,
+        header.len(),
+    );
+    assert!(result.is_ok(), "expected Ok(_), got {result:?}");
+
+    let kernel_bytes = Block::from_slice("some kernel memory".as_bytes());
+    assert_eq!(
+        log_bytes(
+            process.lock(),
+            info,
+            label_address,
+            label.len(),
+            kernel_bytes.start_address().into_usize(),
+            kernel_bytes.size(),
+        ),
+        Err(PermissionDenied),
+    );
+    assert_eq!(
+        log_bytes(
+            process.lock(),
+            info,
+            kernel_bytes.start_address().into_usize(),
+            label.len(),
+            header_address,
+            header.len(),
+        ),
+        Err(PermissionDenied),
+    );
+
+    // Ядро читает только первые байты длинного буфера,
+    // поэтому отсутствие отображения за его пределами не является ошибкой.
+    let result = log_bytes(
+        process.lock(),
+        info,
+        label_address,
+        label.len(),
+        header_address,
+        usize::MAX - header_address,
+    );
+    assert!(result.is_ok(), "expected Ok(_), got {result:?}");
+}
+
+#[test_case]
+fn set_log_level_implementation() {
+    let process = Spinlock::new(process_helpers::make(LOOP_ELF));
+    set_pid(&mut process.lock(), Pid::new(0));
+
+    let symbol = |level| size::from(u32::from(log::level_into_symbol(&level)));
+
+    assert_eq!(set_log_level(process.lock(), symbol(Level::INFO)), Ok(0));
+    assert_eq!(kernel_log::level(), Level::INFO);
+
+    let events = event_count();
+    debug!("this message should be filtered out");
+    assert_eq!(event_count(), events);
+    info!("this message should be logged");
+    assert_eq!(event_count(), events + 1);
+
+    for level in [0, symbol(Level::INFO) + 0x100, usize::from(b'X')] {
+        assert_eq!(set_log_level(process.lock(), level), Err(InvalidArgument));
+    }
+    assert_eq!(kernel_log::level(), Level::INFO);
+
+    assert_eq!(set_log_level(process.lock(), symbol(Level::DEBUG)), Ok(0));
+    assert_eq!(kernel_log::level(), Level::DEBUG);
+
+    let events = event_count();
+    debug!("this message should be logged again");
+    assert_eq!(event_count(), events + 1);
+}
+
+#[test_case]
+fn target_filters() {
+    assert_eq!(
+        kernel_log::set_target_filter("", Level::INFO),
+        Err(InvalidArgument),
+    );
+
+    assert_eq!(kernel_log::set_target_filter("muted", Level::INFO), Ok(()));
+    assert_eq!(
+        kernel_log::set_target_filter("muted::verbose", Level::DEBUG),
+        Ok(()),
+    );
+
+    let events = event_count();
+    debug!(target: "muted", "this message should be filtered out");
+    debug!(target: "muted::inner", "this message should be filtered out");
+    assert_eq!(event_count(), events);
+
+    info!(target: "muted", "this message should be logged");
+    debug!(target: "muted::verbose", "the more specific rule should win");
+    debug!(target: "muted::verbose::inner", "the more specific rule should win");
+    debug!(target: "muted_not", "the prefix should end at a module boundary");
+    debug!("the global level should apply to other targets");
+    assert_eq!(event_count(), events + 5);
+
+    assert_eq!(kernel_log::set_target_filter("muted", Level::DEBUG), Ok(()));
+    let events = event_count();
+    debug!(target: "muted", "the rule should be replaced");
+    assert_eq!(event_count(), events + 1);
+
+    assert_eq!(kernel_log::set_target_filter("muted", Level::ERROR), Ok(()));
+    kernel_log::remove_target_filter("muted");
+    kernel_log::remove_target_filter("muted::verbose");
+    let events = event_count();
+    debug!(target: "muted", "the rule should be removed");
+    assert_eq!(event_count(), events + 1);
+
+    let events = event_count();
+    let prefixes = (0 .. kernel_log::MAX_TARGET_FILTERS).map(|i| &PREFIXES[i .. i + 1]);
+    for prefix in prefixes.clone() {
+        assert_eq!(kernel_log::set_target_filter(prefix, Level::ERROR), Ok(()));
+    }
+    assert_eq!(
+        kernel_log::set_target_filter("one_too_many", Level::ERROR),
+        Err(Overflow),
+    );
+    for prefix in prefixes {
+        kernel_log::remove_target_filter(prefix);
+    }
+    debug!(target: "muted", "all rules should be removed");
+    assert_eq!(event_count(), events + 1);
+}
+
+/// Однобуквенные цели сообщений, их не меньше [`kernel_log::MAX_TARGET_FILTERS`].
+const PREFIXES: &str = "abcdefghijklmnopqrstuvwxyz";
@@ -0,0 +1,67 @@
+#![deny(warnings)]
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(kernel::test_runner)]
+
+use ku::error::Error::Unimplemented;
+
+use kernel::{
+    Subsystems,
+    log::debug,
+    process::{
+        Process,
+        Table,
+    },
+    smp::{
+        self,
+        test_scaffolding::{
+            cpu_count,
+            init_legacy,
+        },
+    },
+};
+
+mod init;
+mod mm_helpers;
+mod process_helpers;
+
+init!(Subsystems::MEMORY | Subsystems::PROCESS);
+
+const LOOP_ELF: &[u8] = page_aligned!("../../target/kernel/user/loop");
+
+#[test_case]
+fn single_cpu_without_acpi() {
+    assert!(!smp::is_legacy_mode());
+    assert_eq!(cpu_count(), 0);
+
+    assert_eq!(init_legacy(Subsystems::empty()), Err(Unimplemented));
+    assert!(!smp::is_legacy_mode());
+
+    init_legacy(Subsystems::CPUS).unwrap();
+
+    assert!(smp::is_legacy_mode());
+    assert_eq!(cpu_count(), 1);
+}
+
+#[test_case]
+fn preemption_by_pit() {
+    let _trap_guard = process_helpers::forbid_traps();
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    assert!(
+        smp::is_legacy_mode(),
+        "the test relies on the degraded mode set up by the previous one",
+    );
+
+    let pid = process_helpers::allocate(LOOP_ELF).pid();
+
+    let process = Table::get(pid).expect("failed to find the new process in the process table");
+    Process::enter_user_mode(process);
+
+    // If this does not happen and the test times out, the PIT does not preempt the process.
+    debug!("returned from the user space");
+
+    process_helpers::free(pid);
+}
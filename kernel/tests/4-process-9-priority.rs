@@ -0,0 +1,188 @@
+#![deny(warnings)]
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(kernel::test_runner)]
+
+use ku::{
+    error::Error::InvalidArgument,
+    process::Pid,
+};
+
+use kernel::{
+    Subsystems,
+    log::debug,
+    process::{
+        Process,
+        Scheduler,
+        Table,
+        test_scaffolding::{
+            self,
+            duplicate_process,
+            scheduler_dequeue,
+        },
+    },
+};
+
+mod init;
+mod mm_helpers;
+mod process_helpers;
+
+init!(Subsystems::MEMORY | Subsystems::SYSCALL | Subsystems::SMP | Subsystems::PROCESS);
+
+const LOOP_ELF: &[u8] = page_aligned!("../../target/kernel/user/loop");
+
+#[test_case]
+fn priority_order() {
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let low = allocate(LOW_PRIORITY);
+    let first_high = allocate(HIGH_PRIORITY);
+    let default = allocate(Process::DEFAULT_PRIORITY);
+    let second_high = allocate(HIGH_PRIORITY);
+
+    for pid in [low, first_high, default, second_high] {
+        Scheduler::enqueue(pid);
+    }
+
+    for pid in [first_high, second_high, default, low] {
+        assert_eq!(scheduler_dequeue(), Some(pid));
+    }
+    assert_eq!(scheduler_dequeue(), None);
+
+    for pid in [low, first_high, default, second_high] {
+        process_helpers::free(pid);
+    }
+}
+
+#[test_case]
+fn aging() {
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let low = allocate(0);
+    let high = [allocate(HIGH_PRIORITY), allocate(HIGH_PRIORITY)];
+
+    Scheduler::enqueue(low);
+    for pid in high {
+        Scheduler::enqueue(pid);
+    }
+
+    // Высокоприоритетные процессы постоянно готовы к исполнению,
+    // как если бы их вытесняло прерывание таймера.
+    let mut dequeues = 0;
+    loop {
+        let pid = scheduler_dequeue().expect("the run queue should not be empty");
+        dequeues += 1;
+
+        if pid == low {
+            break;
+        }
+
+        assert!(high.contains(&pid));
+        assert!(
+            dequeues < STARVATION_LIMIT,
+            "the low priority process starves",
+        );
+
+        Scheduler::enqueue(pid);
+    }
+
+    debug!(dequeues);
+    assert!(
+        dequeues > 1,
+        "the low priority process should not be run first",
+    );
+
+    while scheduler_dequeue().is_some() {}
+
+    for pid in high.into_iter().chain([low]) {
+        process_helpers::free(pid);
+    }
+}
+
+#[test_case]
+fn set_priority() {
+    let mut process = process_helpers::allocate(LOOP_ELF);
+    assert_eq!(process.priority(), Process::DEFAULT_PRIORITY);
+
+    process.set_priority(Process::MAX_PRIORITY).unwrap();
+    assert_eq!(process.priority(), Process::MAX_PRIORITY);
+
+    let result = process.set_priority(Process::MAX_PRIORITY + 1);
+    assert_eq!(result, Err(InvalidArgument));
+    assert_eq!(process.priority(), Process::MAX_PRIORITY);
+
+    let pid = process.pid();
+    drop(process);
+    process_helpers::free(pid);
+}
+
+#[test_case]
+fn syscall_set_priority() {
+    let process = process_helpers::allocate(LOOP_ELF);
+    let pid = process.pid();
+
+    let result =
+        test_scaffolding::set_priority(process, Pid::Current.into_usize(), LOW_PRIORITY.into());
+    assert_eq!(result, Ok(0));
+    assert_eq!(Table::get(pid).unwrap().priority(), LOW_PRIORITY);
+
+    let process = Table::get(pid).unwrap();
+    let result = test_scaffolding::set_priority(process, Pid::Current.into_usize(), usize::MAX);
+    assert_eq!(result, Err(InvalidArgument));
+    assert_eq!(Table::get(pid).unwrap().priority(), LOW_PRIORITY);
+
+    process_helpers::free(pid);
+}
+
+#[test_case]
+fn fork_preserves_priority() {
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let pid = allocate(HIGH_PRIORITY);
+
+    let mut process = Table::get(pid).unwrap();
+    let child = duplicate_process(&mut process).unwrap();
+    drop(process);
+
+    assert_eq!(child.priority(), HIGH_PRIORITY);
+    drop(child);
+
+    process_helpers::free(pid);
+}
+
+#[test_case]
+fn idle() {
+    while Scheduler::run_one() {}
+
+    let pid = allocate(0);
+    Scheduler::enqueue(pid);
+    process_helpers::free(pid);
+
+    assert!(
+        test_scaffolding::scheduler_run_one_or_idle(),
+        "a freed process still takes its turn in the run queue",
+    );
+    assert!(
+        !test_scaffolding::scheduler_run_one_or_idle(),
+        "there should be no runnable processes",
+    );
+}
+
+/// Создаёт процесс с приоритетом `priority` и возвращает его [`Pid`].
+fn allocate(priority: u8) -> Pid {
+    let mut process = process_helpers::allocate(LOOP_ELF);
+    process.set_priority(priority).unwrap();
+    process.pid()
+}
+
+/// Приоритет высокоприоритетных процессов в тестах.
+const HIGH_PRIORITY: u8 = 24;
+
+/// Приоритет низкоприоритетных процессов в тестах.
+const LOW_PRIORITY: u8 = 8;
+
+/// Сколько раз планировщик может обойти низкоприоритетный процесс,
+/// прежде чем тест [`aging()`] сочтёт, что процесс голодает.
+const STARVATION_LIMIT: usize = 1_000;
@@ -6,6 +6,11 @@
 #![reexport_test_harness_main = "test_main"]
 #![test_runner(kernel::test_runner)]
 
+use ku::time::{
+    CpuUsage,
+    Tsc,
+};
+
 use kernel::{
     Subsystems,
     log::debug,
@@ -15,6 +20,10 @@ use kernel::{
         Table,
         test_scaffolding,
     },
+    smp::{
+        self,
+        test_scaffolding::idle_ticks,
+    },
     trap::{
         TRAP_STATS,
         Trap,
@@ -28,6 +37,7 @@ mod process_helpers;
 init!(Subsystems::MEMORY | Subsystems::SMP | Subsystems::PROCESS);
 
 const EXIT_ELF: &[u8] = page_aligned!("../../target/kernel/user/exit");
+const LOOP_ELF: &[u8] = page_aligned!("../../target/kernel/user/loop");
 const PAGE_FAULT_ELF: &[u8] = page_aligned!("../../target/kernel/user/page_fault");
 const SCHED_YIELD_ELF: &[u8] = page_aligned!("../../target/kernel/user/sched_yield");
 
@@ -104,3 +114,176 @@ fn sched_yield_reschedules() {
     process_helpers::free(pid);
     while Scheduler::run_one() {}
 }
+
+#[test_case]
+fn fairness() {
+    let mut yield_process = process_helpers::allocate(SCHED_YIELD_ELF);
+    let yield_pid = yield_process.pid();
+    test_scaffolding::disable_interrupts(&mut yield_process);
+    drop(yield_process);
+
+    let loop_pid = process_helpers::allocate(LOOP_ELF).pid();
+
+    Scheduler::enqueue(yield_pid);
+    Scheduler::enqueue(loop_pid);
+
+    let start = Tsc::now();
+    let mut yield_runs = 0;
+    let mut loop_runs = 0;
+
+    for _ in 0 .. FAIRNESS_RUNS {
+        let pid = test_scaffolding::scheduler_front();
+
+        assert!(Scheduler::run_one(), "both processes should stay runnable");
+
+        if pid == Some(yield_pid) {
+            yield_runs += 1;
+        } else if pid == Some(loop_pid) {
+            loop_runs += 1;
+        }
+    }
+
+    let elapsed = start.elapsed();
+    let yield_latency = Table::get(yield_pid).unwrap().max_scheduling_latency().unwrap();
+    let loop_latency = Table::get(loop_pid).unwrap().max_scheduling_latency().unwrap();
+
+    debug!(
+        yield_runs,
+        loop_runs,
+        %yield_latency,
+        %loop_latency,
+        %elapsed,
+    );
+
+    // Процессы чередуются, каким бы частым ни был вызов `sched_yield()`.
+    assert!(yield_runs.abs_diff(loop_runs) <= 1);
+    assert_eq!(yield_runs + loop_runs, FAIRNESS_RUNS);
+
+    // Каждый процесс ждёт не дольше одного кванта другого процесса,
+    // а не бо́льшую часть всего времени работы теста.
+    for latency in [yield_latency, loop_latency] {
+        assert!(4 * latency.into_f64() < elapsed.into_f64());
+    }
+
+    process_helpers::free(yield_pid);
+    process_helpers::free(loop_pid);
+    while Scheduler::run_one() {}
+}
+
+#[test_case]
+fn yield_to() {
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let yielding_pid = process_helpers::allocate(LOOP_ELF).pid();
+    let round_robin_pid = process_helpers::allocate(LOOP_ELF).pid();
+    let target_pid = process_helpers::allocate(LOOP_ELF).pid();
+    let not_runnable_pid = process_helpers::allocate(LOOP_ELF).pid();
+
+    Scheduler::enqueue(round_robin_pid);
+    Scheduler::enqueue(target_pid);
+
+    assert!(
+        !Scheduler::yield_to(not_runnable_pid),
+        "a process that is not in the run queue can not be scheduled next",
+    );
+    assert_eq!(test_scaffolding::scheduler_front(), Some(round_robin_pid));
+
+    // Так системный вызов `yield_to()` обрабатывает процесс `yielding_pid`.
+    assert!(Scheduler::yield_to(target_pid));
+    Scheduler::enqueue(yielding_pid);
+
+    assert_eq!(
+        test_scaffolding::scheduler_front(),
+        Some(target_pid),
+        "the target process should be run next instead of the round-robin pick",
+    );
+
+    assert!(Scheduler::run_one());
+
+    let ran = |pid| Table::get(pid).unwrap().max_scheduling_latency().is_some();
+    debug!(
+        target = ran(target_pid),
+        round_robin = ran(round_robin_pid),
+        yielding = ran(yielding_pid),
+    );
+    assert!(ran(target_pid));
+    assert!(!ran(round_robin_pid));
+    assert!(!ran(yielding_pid));
+
+    for pid in [round_robin_pid, yielding_pid, target_pid] {
+        assert_eq!(test_scaffolding::scheduler_front(), Some(pid));
+        assert!(Scheduler::run_one());
+    }
+
+    for pid in [yielding_pid, round_robin_pid, target_pid, not_runnable_pid] {
+        process_helpers::free(pid);
+    }
+    while Scheduler::run_one() {}
+}
+
+#[test_case]
+fn idle() {
+    while Scheduler::run_one() {}
+
+    for _ in 0 .. 3 {
+        let start_idle_ticks = idle_ticks();
+
+        // If the test hangs here, the CPU is not woken up by the next interrupt.
+        assert!(
+            !test_scaffolding::scheduler_run_one_or_idle(),
+            "there should be no runnable processes",
+        );
+
+        let end_idle_ticks = idle_ticks();
+        debug!(start_idle_ticks, end_idle_ticks);
+        assert_eq!(end_idle_ticks, start_idle_ticks + 1);
+    }
+
+    let pid = process_helpers::allocate(EXIT_ELF).pid();
+    Scheduler::enqueue(pid);
+
+    let start_idle_ticks = idle_ticks();
+    assert!(test_scaffolding::scheduler_run_one_or_idle());
+    assert_eq!(
+        idle_ticks(),
+        start_idle_ticks,
+        "the idle loop should run only when there are no runnable processes",
+    );
+    Table::get(pid).expect_err("the 'exit' process was not run up to its completion");
+}
+
+#[test_case]
+fn cpu_usage() {
+    let pid = process_helpers::allocate(LOOP_ELF).pid();
+    Scheduler::enqueue(pid);
+
+    let busy_start = Tsc::now();
+    for _ in 0 .. CpuUsage::WINDOW / 2 {
+        assert!(Scheduler::run_one());
+    }
+    let busy = busy_start.elapsed().into_f64();
+
+    process_helpers::free(pid);
+    while Scheduler::run_one() {}
+
+    let idle_start = Tsc::now();
+    for _ in 0 .. CpuUsage::WINDOW / 2 {
+        assert!(!test_scaffolding::scheduler_run_one_or_idle());
+    }
+    let idle = idle_start.elapsed().into_f64();
+
+    let expected = 100.0 * busy / (busy + idle);
+    let usage = f64::from(smp::cpu_usage().expect("the CPU usage should be already known"));
+    debug!(usage, expected, per_cpu = ?smp::cpu_usage_per_cpu());
+
+    assert!(
+        expected - CPU_USAGE_TOLERANCE <= usage && usage <= expected + CPU_USAGE_TOLERANCE,
+        "the CPU usage should reflect the busy fraction of the time",
+    );
+}
+
+/// Допустимое отклонение загрузки процессора в процентах от ожидаемой в тесте [`cpu_usage()`].
+const CPU_USAGE_TOLERANCE: f64 = 10.0;
+
+/// Количество циклов работы планировщика в тесте [`fairness()`].
+const FAIRNESS_RUNS: usize = 20;
@@ -0,0 +1,139 @@
+#![deny(warnings)]
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(kernel::test_runner)]
+
+use chrono::Duration;
+
+use ku::{
+    process::{
+        Pid,
+        Syscall,
+    },
+    time,
+};
+
+use kernel::{
+    Subsystems,
+    log::debug,
+    process::{
+        Scheduler,
+        Table,
+        test_scaffolding::{
+            disable_interrupts,
+            scheduler_has_pid,
+            scheduler_is_sleeping,
+            scheduler_run_one_or_idle,
+        },
+    },
+};
+
+mod init;
+mod mm_helpers;
+mod process_helpers;
+
+init!(Subsystems::MEMORY | Subsystems::SYSCALL | Subsystems::SMP | Subsystems::PROCESS);
+
+const SLEEP_ELF: &[u8] = page_aligned!("../../target/kernel/user/sleep");
+
+#[test_case]
+fn sleep() {
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let mut process = process_helpers::allocate(SLEEP_ELF);
+    let trace = process.trace_syscalls();
+    let pid = process.pid();
+    disable_interrupts(&mut process);
+    drop(process);
+
+    Scheduler::enqueue(pid);
+    fall_asleep(pid);
+
+    let start = time::timer();
+    while Table::get(pid).is_ok() {
+        scheduler_run_one_or_idle();
+    }
+    let elapsed = Duration::try_from(start.elapsed()).unwrap();
+
+    // Первый сон начался ещё до замера времени.
+    debug!(%elapsed);
+    assert!(
+        elapsed >= Duration::milliseconds(SLEEP_MILLISECONDS * (SLEEP_COUNT - 1)),
+        "the process woke up before its deadline",
+    );
+
+    let sleeps = trace.syscalls().iter().filter(|&&syscall| syscall == Syscall::Sleep).count();
+    assert_eq!(sleeps, 1 + SLEEP_COUNT as usize);
+    assert_eq!(*trace.syscalls().last().unwrap(), Syscall::Exit);
+}
+
+#[test_case]
+fn enqueue_while_sleeping() {
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let pid = allocate();
+
+    Scheduler::enqueue(pid);
+    fall_asleep(pid);
+
+    Scheduler::enqueue(pid);
+    assert!(
+        !scheduler_has_pid(pid),
+        "a sleeping process should not be scheduled before its deadline",
+    );
+
+    process_helpers::free(pid);
+}
+
+#[test_case]
+fn free_while_sleeping() {
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let pid = allocate();
+
+    Scheduler::enqueue(pid);
+    fall_asleep(pid);
+
+    process_helpers::free(pid);
+    assert!(!scheduler_is_sleeping(pid));
+
+    let start = time::timer();
+    while !start.has_passed(Duration::milliseconds(2 * SLEEP_MILLISECONDS)) {
+        assert!(
+            !scheduler_run_one_or_idle(),
+            "a freed process should not be woken up",
+        );
+    }
+}
+
+/// Создаёт процесс `sleep`, который не вытесняется по прерываниям таймера,
+/// и возвращает его [`Pid`].
+fn allocate() -> Pid {
+    let mut process = process_helpers::allocate(SLEEP_ELF);
+    disable_interrupts(&mut process);
+    process.pid()
+}
+
+/// Исполняет стоящий в очереди процесс `sleep`, заданный идентификатором `pid`,
+/// до его первого засыпания.
+/// Проверяет, что перед этим сон нулевой длительности работает так же, как `sched_yield()`.
+fn fall_asleep(pid: Pid) {
+    assert!(Scheduler::run_one());
+    assert!(
+        scheduler_has_pid(pid),
+        "a zero duration sleep should reschedule the process",
+    );
+    assert!(!scheduler_is_sleeping(pid));
+
+    assert!(Scheduler::run_one());
+    assert!(!scheduler_has_pid(pid));
+    assert!(scheduler_is_sleeping(pid));
+}
+
+/// Сколько раз засыпает процесс `sleep`.
+const SLEEP_COUNT: i64 = 3;
+
+/// Длительность каждого сна процесса `sleep` в миллисекундах.
+const SLEEP_MILLISECONDS: i64 = 100;
@@ -0,0 +1,79 @@
+#![deny(warnings)]
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(kernel::test_runner)]
+
+use ku::process::State;
+
+use kernel::{
+    Subsystems,
+    log::debug,
+    process::{
+        Scheduler,
+        StateAudit,
+        Table,
+    },
+};
+
+mod init;
+mod process_helpers;
+
+init!(Subsystems::MEMORY | Subsystems::SMP | Subsystems::PROCESS);
+
+const LOOP_ELF: &[u8] = page_aligned!("../../target/kernel/user/loop");
+
+#[test_case]
+fn state_transitions() {
+    let pid = process_helpers::allocate(LOOP_ELF).pid();
+
+    for _ in 0 .. RUNS {
+        Scheduler::enqueue(pid);
+        assert!(Scheduler::run_one());
+    }
+
+    let process = Table::get(pid).unwrap();
+    let transitions = process.state_transitions();
+
+    if !cfg!(feature = "state-audit") {
+        assert_eq!(transitions.count(), 0);
+        drop(process);
+        process_helpers::free(pid);
+        return;
+    }
+
+    let mut count = 0;
+    let mut previous = None;
+
+    for transition in transitions {
+        debug!(?transition);
+
+        let expected = if count % 2 == 0 {
+            (State::Runnable, State::Running)
+        } else {
+            (State::Running, State::Runnable)
+        };
+        assert_eq!((transition.old_state(), transition.new_state()), expected);
+
+        if let Some(previous) = previous {
+            assert!(previous <= transition.timestamp());
+        }
+        previous = Some(transition.timestamp());
+
+        count += 1;
+    }
+
+    assert_eq!(
+        count,
+        StateAudit::CAPACITY,
+        "the audit trail should keep only the latest transitions",
+    );
+
+    drop(process);
+    process_helpers::free(pid);
+}
+
+/// Количество запусков процесса, каждый из которых даёт два перехода между состояниями.
+/// Их больше, чем помещается в журнал [`StateAudit`].
+const RUNS: usize = StateAudit::CAPACITY;
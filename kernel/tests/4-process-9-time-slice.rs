@@ -0,0 +1,102 @@
+#![deny(warnings)]
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(kernel::test_runner)]
+
+use core::hint;
+
+use chrono::Duration;
+
+use ku::time::{
+    Tsc,
+    TscDuration,
+};
+
+use kernel::{
+    Subsystems,
+    log::debug,
+    process::{
+        Pid,
+        Process,
+        Scheduler,
+        Table,
+    },
+};
+
+mod init;
+mod mm_helpers;
+mod process_helpers;
+
+init!(Subsystems::MEMORY | Subsystems::SMP | Subsystems::PROCESS);
+
+const LOOP_ELF: &[u8] = page_aligned!("../../target/kernel/user/loop");
+
+#[test_case]
+fn time_slice() {
+    let _trap_guard = process_helpers::forbid_traps();
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let time_slice = Duration::milliseconds(TIME_SLICE_MILLISECONDS.try_into().unwrap());
+    let time_slice = loop {
+        if let Ok(time_slice) = TscDuration::try_from(time_slice) {
+            break time_slice;
+        }
+        hint::spin_loop();
+    };
+
+    let pid = process_helpers::allocate(LOOP_ELF).pid();
+
+    Scheduler::set_time_slice(0);
+    assert_eq!(Scheduler::time_slice(), Duration::zero());
+    let tick = (0 .. TICK_SAMPLES).map(|_| run(pid)).max().unwrap();
+
+    Scheduler::set_time_slice(TIME_SLICE_MILLISECONDS);
+    assert_eq!(
+        Scheduler::time_slice(),
+        Duration::milliseconds(TIME_SLICE_MILLISECONDS.try_into().unwrap()),
+    );
+    let slices = [run(pid), run(pid)];
+
+    Scheduler::set_time_slice(0);
+    process_helpers::free(pid);
+
+    debug!(%time_slice, %tick, ?slices);
+
+    for slice in slices {
+        assert!(
+            slice >= time_slice,
+            "the process should not be preempted before its time slice {time_slice} is over, but \
+             it was preempted after {slice}",
+        );
+        assert!(
+            slice.into_f64() < time_slice.into_f64() + MAX_EXTRA_TICKS * tick.into_f64(),
+            "the process should be preempted on one of the first timer ticks after its time slice \
+             {time_slice} is over, but it was preempted after {slice} with a tick {tick}",
+        );
+    }
+}
+
+/// Исполняет процесс `pid` до его вытеснения и возвращает время, которое он исполнялся.
+fn run(pid: Pid) -> TscDuration {
+    let process = Table::get(pid).expect("failed to find the process in the process table");
+
+    let start = Tsc::now();
+    let preempted = Process::enter_user_mode(process);
+    let elapsed = start.elapsed();
+
+    assert!(preempted, "the loop process can only be preempted");
+
+    elapsed
+}
+
+/// С запасом на неточность измерений,
+/// сколько тиков таймера может пройти после окончания кванта до вытеснения процесса.
+const MAX_EXTRA_TICKS: f64 = 3.0;
+
+/// Сколько раз процесс исполняется с нулевым квантом для оценки периода таймера.
+const TICK_SAMPLES: usize = 3;
+
+/// Проверяемый квант времени.
+const TIME_SLICE_MILLISECONDS: u64 = 500;
@@ -0,0 +1,127 @@
+#![deny(warnings)]
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(kernel::test_runner)]
+
+use core::{
+    hint,
+    sync::atomic::{
+        AtomicUsize,
+        Ordering,
+    },
+};
+
+use x86_64::instructions::{
+    self,
+    interrupts,
+};
+
+use ku::{
+    sync::spinlock::Spinlock,
+    time::{
+        self,
+        Tsc,
+    },
+};
+
+use kernel::{
+    Subsystems,
+    log::info,
+    memory::{
+        BASE_ADDRESS_SPACE,
+        test_scaffolding::phys2virt,
+    },
+    process::test_scaffolding::set_handler,
+    smp::test_scaffolding::{
+        cpu_count,
+        cpu_id,
+        init_smp,
+    },
+};
+
+mod init;
+
+init!(Subsystems::MEMORY);
+
+#[test_case]
+fn monotonic_across_cpus() {
+    set_handler(ap_loop);
+
+    let phys2virt = phys2virt(&BASE_ADDRESS_SPACE.lock());
+    init_smp(phys2virt, Subsystems::SMP).unwrap();
+
+    assert!(cpu_count() > 1, "the test requires at least two CPUs");
+
+    ping_pong(BSP_TURN);
+
+    info!(
+        tsc_offsets = ?ku::system_info().tsc_offsets(),
+        backward_steps = BACKWARD_STEPS.load(Ordering::Relaxed),
+    );
+    assert_eq!(
+        BACKWARD_STEPS.load(Ordering::Relaxed),
+        0,
+        "the synchronized TSC went backwards when switching between CPUs",
+    );
+}
+
+fn ap_loop() {
+    if usize::from(cpu_id()) == PARTNER_CPU {
+        ping_pong(PARTNER_TURN);
+    }
+
+    loop {
+        interrupts::without_interrupts(instructions::hlt)
+    }
+}
+
+/// Поочерёдно с другим процессором читает синхронизированный счётчик тактов
+/// и сравнивает его с предыдущим показанием, снятым другим процессором.
+/// Аргумент `turn` задаёт очерёдность хода текущего процессора.
+fn ping_pong(turn: usize) {
+    let cpu = time::tscp().1;
+
+    for round in 0 .. ROUND_COUNT {
+        let my_turn = 2 * round + turn;
+        while TURN.load(Ordering::Acquire) != my_turn {
+            hint::spin_loop();
+        }
+
+        let now = Tsc::synchronized();
+        let mut last = LAST.lock();
+        if let Some((last_cpu, last_tsc)) = *last &&
+            last_cpu != cpu &&
+            now < last_tsc
+        {
+            BACKWARD_STEPS.fetch_add(1, Ordering::Relaxed);
+        }
+        *last = Some((cpu, now));
+        drop(last);
+
+        TURN.store(my_turn + 1, Ordering::Release);
+    }
+}
+
+/// Количество обнаруженных шагов синхронизированного счётчика тактов назад.
+static BACKWARD_STEPS: AtomicUsize = AtomicUsize::new(0);
+
+/// Последнее показание синхронизированного счётчика тактов
+/// вместе с номером процессора, на котором оно снято.
+static LAST: Spinlock<Option<(u32, Tsc)>> = Spinlock::new(None);
+
+/// Номер текущего хода в [`ping_pong()`].
+static TURN: AtomicUsize = AtomicUsize::new(0);
+
+/// Очерёдность хода Bootstrap Processor в [`ping_pong()`].
+const BSP_TURN: usize = 0;
+
+/// Очерёдность хода Application Processor в [`ping_pong()`].
+const PARTNER_TURN: usize = 1;
+
+/// Application Processor, который участвует в [`ping_pong()`].
+const PARTNER_CPU: usize = 1;
+
+/// Количество ходов каждого процессора в [`ping_pong()`].
+const ROUND_COUNT: usize = 10_000;
@@ -0,0 +1,91 @@
+#![deny(warnings)]
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(kernel::test_runner)]
+
+extern crate alloc;
+
+use alloc::{
+    format,
+    string::String,
+    vec::Vec,
+};
+
+use kernel::{
+    Subsystems,
+    log::debug,
+    trace::{
+        self,
+        TraceEvent,
+    },
+    trace_event,
+};
+
+mod init;
+
+init!(Subsystems::MEMORY);
+
+#[test_case]
+fn order_and_timestamps() {
+    emit("order");
+
+    let events = events("order");
+    debug!(?events);
+
+    assert_eq!(
+        events.iter().map(TraceEvent::name).collect::<Vec<_>>(),
+        NAMES,
+    );
+    assert!(events.is_sorted_by_key(TraceEvent::tsc));
+    assert!(events.windows(2).all(|pair| pair[0].tsc() < pair[1].tsc()));
+    assert!(events.iter().all(|event| event.cpu() == events[0].cpu()));
+}
+
+#[test_case]
+fn dump() {
+    emit("dump");
+
+    let mut trace = String::new();
+    trace::dump_trace(&mut trace).unwrap();
+    debug!(%trace);
+
+    assert!(trace.starts_with("{\"traceEvents\":["));
+    assert!(trace.ends_with("],\"displayTimeUnit\":\"ns\"}"));
+
+    let mut rest = trace.as_str();
+    for name in NAMES {
+        let event = format!("\"cat\":\"dump\",\"name\":\"{name}\"");
+        let position = rest.find(&event).expect("the event is missing in the dumped trace");
+        rest = &rest[position + event.len() ..];
+    }
+}
+
+/// Записывает в трассу события [`NAMES`] категории `category`.
+fn emit(category: &'static str) {
+    match category {
+        "order" => {
+            trace_event!("order", "first");
+            trace_event!("order", "second");
+            trace_event!("order", "third");
+        },
+        "dump" => {
+            trace_event!("dump", "first");
+            trace_event!("dump", "second");
+            trace_event!("dump", "third");
+        },
+        _ => unreachable!(),
+    }
+}
+
+/// Возвращает события трассы категории `category`.
+fn events(category: &str) -> Vec<TraceEvent> {
+    trace::events()
+        .into_iter()
+        .filter(|event| event.category() == category)
+        .collect()
+}
+
+/// Имена событий, которые записывает [`emit()`], в порядке их записи.
+const NAMES: [&str; 3] = ["first", "second", "third"];
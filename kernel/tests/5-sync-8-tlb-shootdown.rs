@@ -0,0 +1,262 @@
+#![deny(warnings)]
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(kernel::test_runner)]
+
+use core::{
+    hint,
+    sync::atomic::{
+        AtomicBool,
+        AtomicUsize,
+        Ordering,
+    },
+};
+
+use kernel::{
+    Subsystems,
+    log::debug,
+    memory::{
+        BASE_ADDRESS_SPACE,
+        Block,
+        FrameGuard,
+        KERNEL_RW,
+        Page,
+        Virt,
+        test_scaffolding::{
+            map_page_to_frame,
+            phys2virt,
+            unmap_page,
+        },
+        tlb_shootdown,
+    },
+    process::test_scaffolding::set_handler,
+    smp::test_scaffolding::{
+        cpu_count,
+        cpu_id,
+        init_smp,
+    },
+    trap::{
+        TRAP_STATS,
+        Trap,
+    },
+};
+
+mod init;
+
+init!(Subsystems::MEMORY);
+
+#[test_case]
+fn concurrent_shootdowns() {
+    start_aps();
+
+    let start_ipis = TRAP_STATS[Trap::TlbShootdown].count();
+
+    shoot();
+    while SHOOTERS_DONE.load(Ordering::Acquire) < ap_count() {
+        hint::spin_loop();
+    }
+
+    let ipis = TRAP_STATS[Trap::TlbShootdown].count() - start_ipis;
+    debug!(ipis, ap_count = ap_count());
+    assert!(ipis > 0);
+}
+
+#[test_case]
+fn no_stale_translations() {
+    start_aps();
+
+    let old_frame = FrameGuard::allocate().unwrap();
+    let new_frame = FrameGuard::allocate().unwrap();
+
+    let block = BASE_ADDRESS_SPACE.lock().allocate(Page::layout_array(1), KERNEL_RW).unwrap();
+    let page = block.into_iter().next().unwrap();
+    let probe = page.address().try_into_mut_ptr::<usize>().unwrap();
+
+    unsafe {
+        map_page(page, &old_frame);
+        probe.write_volatile(OLD_VALUE);
+    }
+
+    PROBE.store(probe as usize, Ordering::Release);
+    ROUND.store(WARM_UP, Ordering::Release);
+    wait_for_values(WARM_UP, OLD_VALUE);
+
+    ROUND.store(PAUSE, Ordering::Release);
+    wait_for_acks(ap_count());
+
+    unsafe {
+        unmap_page(&mut BASE_ADDRESS_SPACE.lock(), page).unwrap();
+        map_page(page, &new_frame);
+    }
+    tlb_shootdown(Block::<Virt>::from_index(probe as usize, probe as usize + 1).unwrap());
+
+    unsafe {
+        probe.write_volatile(NEW_VALUE);
+    }
+
+    ROUND.store(CHECK, Ordering::Release);
+    wait_for_values(CHECK, NEW_VALUE);
+
+    ROUND.store(PARK, Ordering::Release);
+    wait_for_acks(2 * ap_count());
+
+    unsafe {
+        unmap_page(&mut BASE_ADDRESS_SPACE.lock(), page).unwrap();
+    }
+    BASE_ADDRESS_SPACE.lock().deallocate(block).unwrap();
+}
+
+/// Запускает Application Processors, если они ещё не запущены.
+fn start_aps() {
+    if STARTED.swap(true, Ordering::Relaxed) {
+        return;
+    }
+
+    set_handler(ap_loop);
+
+    let phys2virt = phys2virt(&BASE_ADDRESS_SPACE.lock());
+    init_smp(phys2virt, Subsystems::SMP).unwrap();
+
+    assert!(cpu_count() <= MAX_CPUS);
+}
+
+/// Количество Application Processors.
+fn ap_count() -> usize {
+    cpu_count() - 1
+}
+
+/// Код Application Processors.
+/// Сначала сбрасывает TLB конкурентно с остальными процессорами.
+/// Затем в раундах [`WARM_UP`] и [`CHECK`] читает проверяемое слово и публикует прочитанное,
+/// а переход в раунды [`PAUSE`] и [`PARK`] подтверждает через [`ACKS`].
+fn ap_loop() {
+    shoot();
+    SHOOTERS_DONE.fetch_add(1, Ordering::Release);
+
+    let cpu = usize::from(cpu_id());
+    let mut acked = IDLE;
+
+    loop {
+        let round = ROUND.load(Ordering::Acquire);
+
+        match round {
+            WARM_UP | CHECK => {
+                let probe = PROBE.load(Ordering::Acquire) as *const usize;
+                let value = unsafe { probe.read_volatile() };
+                OBSERVED[round - 1][cpu].store(value, Ordering::Release);
+            },
+            PAUSE | PARK if acked != round => {
+                acked = round;
+                ACKS.fetch_add(1, Ordering::Release);
+            },
+            _ => {},
+        }
+
+        hint::spin_loop();
+    }
+}
+
+/// Отображает страницу `page` в фрейм `frame` с флагами [`KERNEL_RW`].
+///
+/// # Safety
+///
+/// Страница `page` не должна быть отображена.
+unsafe fn map_page(
+    page: Page,
+    frame: &FrameGuard,
+) {
+    let frame = FrameGuard::reference(**frame);
+    unsafe {
+        map_page_to_frame(&mut BASE_ADDRESS_SPACE.lock(), page, frame, KERNEL_RW).unwrap();
+    }
+}
+
+/// Многократно сбрасывает TLB для небольшого блока адресов.
+fn shoot() {
+    let range = Block::<Virt>::from_index(SHOT_ADDRESS, SHOT_ADDRESS + 3 * Page::SIZE).unwrap();
+
+    for _ in 0 .. SHOTS_PER_CPU {
+        tlb_shootdown(range);
+    }
+}
+
+/// Дожидается, пока Application Processors подтвердят переходы в раунды
+/// [`PAUSE`] и [`PARK`] `acks` раз в сумме.
+fn wait_for_acks(acks: usize) {
+    while ACKS.load(Ordering::Acquire) < acks {
+        hint::spin_loop();
+    }
+}
+
+/// Дожидается, пока все Application Processors прочитают в раунде `round` значение `expected`.
+/// Другое ненулевое значение означает, что процессор прочитал устаревшую трансляцию.
+fn wait_for_values(
+    round: usize,
+    expected: usize,
+) {
+    let current = usize::from(cpu_id());
+
+    for cpu in (0 .. cpu_count()).filter(|&cpu| cpu != current) {
+        let value = loop {
+            let value = OBSERVED[round - 1][cpu].load(Ordering::Acquire);
+            if value != 0 {
+                break value;
+            }
+            hint::spin_loop();
+        };
+
+        debug!(cpu, round, value, expected);
+        assert_eq!(value, expected);
+    }
+}
+
+/// Сколько раз Application Processors подтвердили переход в раунды [`PAUSE`] и [`PARK`].
+static ACKS: AtomicUsize = AtomicUsize::new(0);
+
+/// Слова, прочитанные каждым процессором в раундах [`WARM_UP`] и [`CHECK`].
+static OBSERVED: [[AtomicUsize; MAX_CPUS]; CHECK] =
+    [const { [const { AtomicUsize::new(0) }; MAX_CPUS] }; CHECK];
+
+/// Адрес проверяемого слова.
+static PROBE: AtomicUsize = AtomicUsize::new(0);
+
+/// Текущий раунд проверки.
+static ROUND: AtomicUsize = AtomicUsize::new(IDLE);
+
+/// Количество Application Processors, которые закончили конкурентные сбросы TLB.
+static SHOOTERS_DONE: AtomicUsize = AtomicUsize::new(0);
+
+/// Запущены ли Application Processors.
+static STARTED: AtomicBool = AtomicBool::new(false);
+
+/// Раунд, в котором процессоры читают проверяемое слово после сброса TLB.
+const CHECK: usize = 2;
+
+/// Раунд, в котором процессоры ещё не читают проверяемое слово.
+const IDLE: usize = 0;
+
+/// Максимальное количество процессоров в тесте.
+const MAX_CPUS: usize = 16;
+
+/// Значение проверяемого слова после смены отображения.
+const NEW_VALUE: usize = 0x2222_2222;
+
+/// Значение проверяемого слова до смены отображения.
+const OLD_VALUE: usize = 0x1111_1111;
+
+/// Раунд, в котором процессоры перестают читать проверяемое слово до конца теста.
+const PARK: usize = 4;
+
+/// Раунд, в котором процессоры не читают проверяемое слово, пока меняется его отображение.
+const PAUSE: usize = 3;
+
+/// Адрес блока, для которого процессоры конкурентно сбрасывают TLB.
+const SHOT_ADDRESS: usize = 0x7000_0000_0000;
+
+/// Количество сбросов TLB, которые выполняет каждый процессор.
+const SHOTS_PER_CPU: usize = 1_000;
+
+/// Раунд, в котором процессоры прогревают TLB, читая проверяемое слово.
+const WARM_UP: usize = 1;
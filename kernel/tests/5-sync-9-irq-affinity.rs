@@ -0,0 +1,120 @@
+#![deny(warnings)]
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(kernel::test_runner)]
+
+use core::hint;
+
+use x86_64::instructions;
+
+use ku::error::Error::InvalidArgument;
+
+use kernel::{
+    Subsystems,
+    log::debug,
+    memory::{
+        BASE_ADDRESS_SPACE,
+        test_scaffolding::phys2virt,
+    },
+    process::test_scaffolding::set_handler,
+    smp::test_scaffolding::{
+        cpu_count,
+        cpu_id,
+        init_smp,
+        io_apic_destination,
+    },
+    trap::{
+        self,
+        TRAP_STATS,
+        Trap,
+    },
+};
+
+mod init;
+
+init!(Subsystems::MEMORY);
+
+#[test_case]
+fn rtc_on_ap() {
+    set_handler(ap_loop);
+
+    let phys2virt = phys2virt(&BASE_ADDRESS_SPACE.lock());
+    init_smp(phys2virt, Subsystems::SMP).unwrap();
+    assert!(cpu_count() > 1);
+
+    let bsp = cpu_id();
+    let ap = u8::from(bsp == 0);
+
+    assert_eq!(io_apic_destination(RTC), Ok(bsp));
+    assert_eq!(trap::set_irq_affinity(Trap::Rtc, ap), Ok(()));
+    assert_eq!(io_apic_destination(RTC), Ok(ap));
+
+    // Прерывание, которое уже было направлено на BSP до смены маршрута,
+    // не должно повлиять на проверку.
+    wait_for_ticks(ap, 1);
+
+    let bsp_start = TRAP_STATS[Trap::Rtc].count_on(bsp);
+    wait_for_ticks(ap, TICKS);
+    let bsp_ticks = TRAP_STATS[Trap::Rtc].count_on(bsp) - bsp_start;
+
+    debug!(
+        bsp,
+        ap,
+        bsp_ticks,
+        ap_ticks = TRAP_STATS[Trap::Rtc].count_on(ap),
+    );
+    assert_eq!(bsp_ticks, 0, "the BSP should not service the RTC interrupt");
+
+    assert_eq!(trap::set_irq_affinity(Trap::Rtc, bsp), Ok(()));
+    assert_eq!(io_apic_destination(RTC), Ok(bsp));
+}
+
+#[test_case]
+fn invalid_affinity() {
+    let bsp = cpu_id();
+
+    for trap in [
+        Trap::PageFault,
+        Trap::Cascade,
+        Trap::Timer,
+        Trap::TlbShootdown,
+    ] {
+        assert_eq!(trap::set_irq_affinity(trap, bsp), Err(InvalidArgument));
+    }
+
+    assert_eq!(
+        trap::set_irq_affinity(Trap::Rtc, MISSING_CPU),
+        Err(InvalidArgument),
+    );
+    assert_eq!(io_apic_destination(RTC), Ok(bsp));
+}
+
+/// Код Application Processors, которые только обрабатывают прерывания.
+fn ap_loop() {
+    loop {
+        instructions::hlt();
+    }
+}
+
+/// Дожидается, пока процессор `cpu` обработает ещё `ticks` прерываний RTC.
+fn wait_for_ticks(
+    cpu: u8,
+    ticks: usize,
+) {
+    let start = TRAP_STATS[Trap::Rtc].count_on(cpu);
+
+    while TRAP_STATS[Trap::Rtc].count_on(cpu) < start + ticks {
+        hint::spin_loop();
+    }
+}
+
+/// Идентификатор процессора, которого нет в системе.
+const MISSING_CPU: u8 = u8::MAX;
+
+/// Вход часов реального времени.
+const RTC: u8 = 8;
+
+/// Сколько прерываний RTC нужно дождаться на Application Processor.
+const TICKS: usize = 3;
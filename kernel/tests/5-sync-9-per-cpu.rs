@@ -0,0 +1,120 @@
+#![deny(warnings)]
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(kernel::test_runner)]
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+use core::{
+    hint,
+    ptr,
+    sync::atomic::{
+        AtomicPtr,
+        AtomicUsize,
+        Ordering,
+    },
+};
+
+use x86_64::instructions;
+
+use ku::error::Error::Unimplemented;
+
+use kernel::{
+    Subsystems,
+    log::debug,
+    memory::{
+        BASE_ADDRESS_SPACE,
+        test_scaffolding::phys2virt,
+    },
+    process::test_scaffolding::set_handler,
+    smp::{
+        PerCpu,
+        test_scaffolding::{
+            cpu_count,
+            cpu_id,
+            init_smp,
+        },
+    },
+};
+
+mod init;
+
+init!(Subsystems::MEMORY);
+
+#[test_case]
+fn before_init() {
+    assert_eq!(
+        PerCpu::new(|| AtomicUsize::new(0)).err(),
+        Some(Unimplemented),
+    );
+}
+
+#[test_case]
+fn per_cpu_counters() {
+    set_handler(ap_loop);
+
+    let phys2virt = phys2virt(&BASE_ADDRESS_SPACE.lock());
+    init_smp(phys2virt, Subsystems::SMP).unwrap();
+
+    let counters = Box::leak(Box::new(PerCpu::new(|| AtomicUsize::new(0)).unwrap()));
+    COUNTERS.store(counters, Ordering::Release);
+
+    count(counters);
+
+    while DONE.load(Ordering::Acquire) < cpu_count() - 1 {
+        hint::spin_loop();
+    }
+
+    let bsp = usize::from(cpu_id());
+    assert!(ptr::eq(counters.get(), counters.iter().nth(bsp).unwrap()));
+
+    assert_eq!(counters.iter().count(), cpu_count());
+    for (cpu, counter) in counters.iter().enumerate() {
+        let counter = counter.load(Ordering::Relaxed);
+        debug!(cpu, counter);
+        assert_eq!(counter, INCREMENTS);
+    }
+}
+
+/// Код Application Processors.
+/// Дожидается создания счётчиков и увеличивает свой [`INCREMENTS`] раз.
+fn ap_loop() {
+    let counters = loop {
+        let counters = COUNTERS.load(Ordering::Acquire);
+        if !counters.is_null() {
+            break unsafe { &*counters };
+        }
+        hint::spin_loop();
+    };
+
+    count(counters);
+    DONE.fetch_add(1, Ordering::Release);
+
+    loop {
+        instructions::hlt();
+    }
+}
+
+/// Увеличивает [`INCREMENTS`] раз счётчик текущего процессора,
+/// через [`PerCpu::get()`] и [`PerCpu::with()`] поровну.
+fn count(counters: &PerCpu<AtomicUsize>) {
+    for i in 0 .. INCREMENTS {
+        if i % 2 == 0 {
+            counters.get().fetch_add(1, Ordering::Relaxed);
+        } else {
+            counters.with(|counter| counter.fetch_add(1, Ordering::Relaxed));
+        }
+    }
+}
+
+/// Счётчики процессоров.
+static COUNTERS: AtomicPtr<PerCpu<AtomicUsize>> = AtomicPtr::new(ptr::null_mut());
+
+/// Количество Application Processors, которые закончили увеличивать свои счётчики.
+static DONE: AtomicUsize = AtomicUsize::new(0);
+
+/// Сколько раз каждый процессор увеличивает свой счётчик.
+const INCREMENTS: usize = 100_000;
@@ -0,0 +1,193 @@
+#![deny(warnings)]
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(kernel::test_runner)]
+
+use core::{
+    hint,
+    sync::atomic::{
+        AtomicBool,
+        AtomicUsize,
+        Ordering,
+    },
+};
+
+use x86_64::instructions::{
+    self,
+    interrupts,
+};
+
+use kernel::{
+    Subsystems,
+    log::debug,
+    memory::{
+        BASE_ADDRESS_SPACE,
+        test_scaffolding::phys2virt,
+    },
+    process::test_scaffolding::set_handler,
+    smp::test_scaffolding::{
+        cpu_count,
+        cpu_id,
+        init_smp,
+    },
+    trap::{
+        self,
+        TRAP_STATS,
+        Trap,
+    },
+};
+
+mod init;
+
+init!(Subsystems::MEMORY);
+
+#[test_case]
+fn early_traps_are_not_attributed() {
+    let stats = &TRAP_STATS[Trap::Rtc];
+    let start = stats.count();
+    let mut count = start;
+
+    while count < start + TICKS {
+        instructions::hlt();
+
+        let next = stats.count();
+        assert!(next >= count, "the total count should be monotonic");
+        count = next;
+    }
+
+    for stats in TRAP_STATS.iter() {
+        assert_eq!(
+            stats.per_cpu().next(),
+            None,
+            "{} before SMP init",
+            stats.name(),
+        );
+    }
+}
+
+#[test_case]
+fn breakdown_sums_to_total() {
+    start_aps();
+
+    let stats = &TRAP_STATS[Trap::Breakpoint];
+    let start_count = stats.count();
+    let mut start_per_cpu = [0; MAX_CPUS];
+    for (cpu, start) in start_per_cpu.iter_mut().enumerate().take(cpu_count()) {
+        *start = stats.count_on(cpu.try_into().unwrap());
+    }
+
+    BREAK.store(true, Ordering::Release);
+    breakpoints();
+
+    while DONE.load(Ordering::Acquire) < cpu_count() - 1 {
+        hint::spin_loop();
+    }
+
+    for (cpu, start) in start_per_cpu.iter().enumerate().take(cpu_count()) {
+        let count = stats.count_on(cpu.try_into().unwrap()) - start;
+        debug!(cpu, count);
+        assert_eq!(count, BREAKPOINTS);
+    }
+
+    assert_eq!(stats.count() - start_count, cpu_count() * BREAKPOINTS);
+    assert!(stats.per_cpu().map(|(_, count)| count).sum::<usize>() <= stats.count());
+}
+
+#[test_case]
+fn breakdown_reflects_affinity() {
+    start_aps();
+
+    let stats = &TRAP_STATS[Trap::Rtc];
+    let bsp = cpu_id();
+    let ap = u8::from(bsp == 0);
+
+    trap::set_irq_affinity(Trap::Rtc, ap).unwrap();
+
+    // Прерывание, которое уже было направлено на BSP до смены маршрута,
+    // не должно повлиять на проверку.
+    wait_for_ticks(ap, 1);
+
+    let bsp_start = stats.count_on(bsp);
+    let ap_start = stats.count_on(ap);
+    wait_for_ticks(ap, TICKS);
+
+    for (cpu, count) in stats.per_cpu() {
+        debug!(cpu, count);
+    }
+
+    assert_eq!(stats.count_on(bsp), bsp_start);
+    assert!(stats.count_on(ap) >= ap_start + TICKS);
+    assert!(stats.per_cpu().map(|(_, count)| count).sum::<usize>() <= stats.count());
+
+    trap::set_irq_affinity(Trap::Rtc, bsp).unwrap();
+}
+
+/// Запускает Application Processors, если они ещё не запущены.
+fn start_aps() {
+    if STARTED.swap(true, Ordering::Relaxed) {
+        return;
+    }
+
+    set_handler(ap_loop);
+
+    let phys2virt = phys2virt(&BASE_ADDRESS_SPACE.lock());
+    init_smp(phys2virt, Subsystems::SMP).unwrap();
+
+    assert!(cpu_count() > 1);
+    assert!(cpu_count() <= MAX_CPUS);
+}
+
+/// Код Application Processors.
+/// Дожидается [`BREAK`], выполняет свою порцию точек останова
+/// и дальше только обрабатывает прерывания.
+fn ap_loop() {
+    while !BREAK.load(Ordering::Acquire) {
+        hint::spin_loop();
+    }
+
+    breakpoints();
+    DONE.fetch_add(1, Ordering::Release);
+
+    loop {
+        instructions::hlt();
+    }
+}
+
+/// Выполняет [`BREAKPOINTS`] исключений точки останова на текущем процессоре.
+fn breakpoints() {
+    for _ in 0 .. BREAKPOINTS {
+        interrupts::int3();
+    }
+}
+
+/// Дожидается, пока процессор `cpu` обработает ещё `ticks` прерываний RTC.
+fn wait_for_ticks(
+    cpu: u8,
+    ticks: usize,
+) {
+    let start = TRAP_STATS[Trap::Rtc].count_on(cpu);
+
+    while TRAP_STATS[Trap::Rtc].count_on(cpu) < start + ticks {
+        hint::spin_loop();
+    }
+}
+
+/// Разрешение Application Processors выполнить свои точки останова.
+static BREAK: AtomicBool = AtomicBool::new(false);
+
+/// Количество Application Processors, которые выполнили свои точки останова.
+static DONE: AtomicUsize = AtomicUsize::new(0);
+
+/// Запущены ли Application Processors.
+static STARTED: AtomicBool = AtomicBool::new(false);
+
+/// Сколько исключений точки останова выполняет каждый процессор.
+const BREAKPOINTS: usize = 10;
+
+/// Максимальное количество процессоров в тесте.
+const MAX_CPUS: usize = 16;
+
+/// Сколько прерываний RTC нужно дождаться на Application Processor.
+const TICKS: usize = 3;
@@ -0,0 +1,66 @@
+#![deny(warnings)]
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(kernel::test_runner)]
+
+use ku::process::Syscall;
+
+use kernel::{
+    Subsystems,
+    log::debug,
+    process::{
+        SYSCALL_STATS,
+        Scheduler,
+    },
+};
+
+mod init;
+mod mm_helpers;
+mod process_helpers;
+
+init!(Subsystems::MEMORY | Subsystems::SYSCALL | Subsystems::SMP | Subsystems::PROCESS);
+
+const SYSCALL_STATS_ELF: &[u8] = page_aligned!("../../target/kernel/user/syscall_stats");
+
+#[test_case]
+fn syscall_stats() {
+    let _trap_guard = process_helpers::forbid_traps();
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let syscalls = [Syscall::Exit, Syscall::Map, Syscall::SchedYield];
+    let counts = || syscalls.map(|syscall| SYSCALL_STATS[syscall].count());
+    let errors = || syscalls.map(|syscall| SYSCALL_STATS[syscall].errors());
+
+    let start_counts = counts();
+    let start_errors = errors();
+
+    Scheduler::enqueue(process_helpers::allocate(SYSCALL_STATS_ELF).pid());
+
+    while Scheduler::run_one() {}
+
+    let counts = counts().zip(start_counts).map(|(end, start)| end - start);
+    let errors = errors().zip(start_errors).map(|(end, start)| end - start);
+    debug!(?syscalls, ?counts, ?errors);
+
+    assert_eq!(counts, [1, MAP_COUNT, SCHED_YIELD_COUNT]);
+    assert_eq!(
+        errors,
+        [0, MAP_COUNT, 0],
+        "every map() of an empty block should fail and sched_yield() should not fail",
+    );
+
+    for (syscall, statistics) in SYSCALL_STATS.iter() {
+        let count = statistics.count();
+        let error_count = statistics.errors();
+        debug!(?syscall, count, error_count);
+        assert!(error_count <= count);
+    }
+}
+
+/// Количество системных вызовов `map()`, которые делает процесс `syscall_stats`.
+const MAP_COUNT: usize = 5;
+
+/// Количество системных вызовов `sched_yield()`, которые делает процесс `syscall_stats`.
+const SCHED_YIELD_COUNT: usize = 7;
@@ -0,0 +1,88 @@
+#![deny(warnings)]
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(kernel::test_runner)]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use core::iter;
+
+use ku::{
+    memory::mmu::USER_RW,
+    process::{
+        Pid,
+        Syscall,
+    },
+};
+
+use kernel::{
+    Subsystems,
+    log::debug,
+    process::Scheduler,
+};
+
+mod init;
+mod mm_helpers;
+mod process_helpers;
+
+init!(Subsystems::MEMORY | Subsystems::SYSCALL | Subsystems::SMP | Subsystems::PROCESS);
+
+const SYSCALL_STATS_ELF: &[u8] = page_aligned!("../../target/kernel/user/syscall_stats");
+
+#[test_case]
+fn syscall_trace() {
+    let _trap_guard = process_helpers::forbid_traps();
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let mut process = process_helpers::allocate(SYSCALL_STATS_ELF);
+    let trace = process.trace_syscalls();
+    let pid = process.pid();
+    drop(process);
+
+    Scheduler::enqueue(pid);
+
+    while Scheduler::run_one() {}
+
+    let records = trace.records();
+    for record in &records {
+        debug!(?record);
+    }
+
+    let expected = iter::repeat_n([Syscall::Map, Syscall::Unmap], MAP_COUNT)
+        .flatten()
+        .chain(iter::repeat_n(Syscall::SchedYield, SCHED_YIELD_COUNT))
+        .chain(iter::once(Syscall::Exit))
+        .collect::<Vec<_>>();
+    assert_eq!(trace.syscalls(), expected);
+
+    let empty_block = [Pid::Current.into_usize(), 0, 0];
+    for record in &records {
+        match record.syscall() {
+            Syscall::Map => {
+                assert_eq!(record.args()[.. 3], empty_block);
+                assert_eq!(record.args()[3], USER_RW.bits());
+                assert!(
+                    matches!(record.result(), Some(Err(_))),
+                    "map() of an empty block should fail",
+                );
+            },
+            Syscall::Unmap => {
+                assert_eq!(record.args()[.. 3], empty_block);
+                assert!(record.result().is_some());
+            },
+            Syscall::SchedYield | Syscall::Exit => {
+                assert_eq!(record.result(), None);
+            },
+            syscall => panic!("unexpected syscall {syscall:?}"),
+        }
+    }
+}
+
+/// Количество пар системных вызовов `map()` и `unmap()`, которые делает процесс `syscall_stats`.
+const MAP_COUNT: usize = 5;
+
+/// Количество системных вызовов `sched_yield()`, которые делает процесс `syscall_stats`.
+const SCHED_YIELD_COUNT: usize = 7;
@@ -0,0 +1,189 @@
+#![deny(warnings)]
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(kernel::test_runner)]
+
+use ku::{
+    error::Error::{
+        self,
+        InvalidArgument,
+        NoData,
+        PermissionDenied,
+    },
+    ipc::pipe,
+    process::{
+        ExitCode,
+        Syscall,
+    },
+};
+
+use kernel::{
+    Subsystems,
+    log::debug,
+    process::{
+        Scheduler,
+        Table,
+        test_scaffolding::{
+            PIPE_CAPACITY,
+            duplicate_process,
+            pipe,
+            pipe_close,
+            pipe_read,
+            pipe_write,
+            scheduler_has_pid,
+            scheduler_is_sleeping,
+        },
+    },
+};
+
+mod init;
+mod mm_helpers;
+mod process_helpers;
+
+init!(Subsystems::MEMORY | Subsystems::SYSCALL | Subsystems::SMP | Subsystems::PROCESS);
+
+const PIPE_ELF: &[u8] = page_aligned!("../../target/kernel/user/pipe");
+
+#[test_case]
+fn transfer() {
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let mut process = process_helpers::allocate(PIPE_ELF);
+    let pid = process.pid();
+    let (reader, writer) = pipe(&mut process).unwrap();
+
+    let mut buffer = [0; 2 * MESSAGE.len()];
+    let result = pipe_read(&process, writer, &mut buffer);
+    assert_eq!(result, Err(PermissionDenied));
+    assert_eq!(pipe_write(&process, reader, MESSAGE), Err(PermissionDenied));
+
+    assert_eq!(pipe_write(&process, writer, MESSAGE), Ok(MESSAGE.len()));
+    assert_eq!(pipe_read(&process, reader, &mut buffer), Ok(MESSAGE.len()));
+    assert_eq!(&buffer[.. MESSAGE.len()], MESSAGE);
+
+    assert_eq!(pipe_write(&process, writer, MESSAGE), Ok(MESSAGE.len()));
+    pipe_close(&mut process, writer).unwrap();
+    assert_eq!(pipe_write(&process, writer, MESSAGE), Err(InvalidArgument));
+
+    assert_eq!(
+        pipe_read(&process, reader, &mut buffer),
+        Ok(MESSAGE.len()),
+        "the data written before closing should stay readable",
+    );
+    assert_eq!(pipe_read(&process, reader, &mut buffer), Ok(0));
+
+    let (reader, writer) = pipe(&mut process).unwrap();
+    pipe_close(&mut process, reader).unwrap();
+    assert_eq!(pipe_write(&process, writer, MESSAGE), Err(CLOSED));
+
+    drop(process);
+    process_helpers::free(pid);
+}
+
+#[test_case]
+fn blocking() {
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let mut process = process_helpers::allocate(PIPE_ELF);
+    let pid = process.pid();
+    let (reader, writer) = pipe(&mut process).unwrap();
+
+    let mut buffer = [0; PIPE_CAPACITY];
+    assert_eq!(pipe_read(&process, reader, &mut buffer), Err(NoData));
+    assert!(scheduler_is_sleeping(pid), "the reader should block");
+
+    assert_eq!(pipe_write(&process, writer, MESSAGE), Ok(MESSAGE.len()));
+    assert!(!scheduler_is_sleeping(pid), "the write should wake up");
+    assert!(scheduler_has_pid(pid));
+
+    let written = pipe_write(&process, writer, &buffer).unwrap();
+    debug!(written);
+    assert_eq!(written, PIPE_CAPACITY - MESSAGE.len());
+    assert_eq!(pipe_write(&process, writer, MESSAGE), Err(NoData));
+    assert!(scheduler_is_sleeping(pid), "the writer should block");
+
+    pipe_close(&mut process, reader).unwrap();
+    assert!(!scheduler_is_sleeping(pid), "the close should wake up");
+    assert_eq!(pipe_write(&process, writer, MESSAGE), Err(CLOSED));
+
+    drop(process);
+    process_helpers::free(pid);
+}
+
+#[test_case]
+fn duplicated_ends() {
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let mut process = process_helpers::allocate(PIPE_ELF);
+    let pid = process.pid();
+    let (reader, writer) = pipe(&mut process).unwrap();
+    let child = duplicate_process(&mut process).unwrap();
+
+    let mut buffer = [0; MESSAGE.len()];
+    assert_eq!(pipe_write(&child, writer, MESSAGE), Ok(MESSAGE.len()));
+    assert_eq!(pipe_read(&process, reader, &mut buffer), Ok(MESSAGE.len()));
+    assert_eq!(&buffer, MESSAGE);
+
+    pipe_close(&mut process, writer).unwrap();
+    assert_eq!(
+        pipe_write(&child, writer, MESSAGE),
+        Ok(MESSAGE.len()),
+        "the copy of the writing end should stay open",
+    );
+    assert_eq!(pipe_read(&process, reader, &mut buffer), Ok(MESSAGE.len()));
+
+    drop(child);
+    assert_eq!(
+        pipe_read(&process, reader, &mut buffer),
+        Ok(0),
+        "all writing ends are closed",
+    );
+
+    drop(process);
+    process_helpers::free(pid);
+}
+
+#[test_case]
+fn pipe_syscalls() {
+    let _trap_guard = process_helpers::forbid_traps();
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let mut process = process_helpers::allocate(PIPE_ELF);
+    let trace = process.trace_syscalls();
+    let pid = process.pid();
+    drop(process);
+
+    Scheduler::enqueue(pid);
+    while Scheduler::run_one() {}
+
+    Table::get(pid).expect_err("the 'pipe' process was not run up to its completion");
+
+    let records = trace.records();
+    for record in records.iter() {
+        debug!(?record);
+    }
+
+    for syscall in [
+        Syscall::Pipe,
+        Syscall::PipeWrite,
+        Syscall::PipeRead,
+        Syscall::PipeClose,
+    ] {
+        assert!(
+            records.iter().any(|record| record.syscall() == syscall),
+            "{syscall:?} was not called",
+        );
+    }
+
+    let exit = records.last().unwrap();
+    assert_eq!(exit.syscall(), Syscall::Exit);
+    assert_eq!(exit.args()[0], usize::from(ExitCode::Ok));
+}
+
+/// Ошибка записи в канал, все концы которого для чтения закрыты.
+const CLOSED: Error = Error::Pipe(pipe::Error::Closed);
+
+/// Сообщение, которое передаётся через канал.
+const MESSAGE: &[u8] = b"Hello, pipe!";
@@ -0,0 +1,188 @@
+#![deny(warnings)]
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(kernel::test_runner)]
+
+use ku::{
+    error::Error::{
+        NoProcess,
+        PermissionDenied,
+    },
+    process::{
+        Pid,
+        State,
+    },
+};
+
+use kernel::{
+    Subsystems,
+    log::debug,
+    memory::{
+        BASE_ADDRESS_SPACE,
+        test_scaffolding::switch_to,
+    },
+    process::{
+        Scheduler,
+        Table,
+        test_scaffolding::{
+            exofork,
+            scheduler_has_pid,
+            set_group,
+            set_process_state,
+        },
+    },
+};
+
+mod init;
+mod mm_helpers;
+mod process_helpers;
+
+init!(Subsystems::MEMORY | Subsystems::SMP | Subsystems::PROCESS);
+
+const LOOP_ELF: &[u8] = page_aligned!("../../target/kernel/user/loop");
+
+#[test_case]
+fn kill_group() {
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let root = process_helpers::allocate(LOOP_ELF).pid();
+    let outsider = process_helpers::allocate(LOOP_ELF).pid();
+
+    let child = fork(root);
+    let sibling = fork(root);
+    let grandchild = fork(child);
+    let tree = [root, child, sibling, grandchild];
+
+    debug!(?tree, %outsider);
+
+    for pid in tree {
+        assert_eq!(Table::get(pid).unwrap().group(), root);
+    }
+    assert_eq!(Table::get(outsider).unwrap().group(), outsider);
+
+    let mut group = Table::group(root);
+    group.sort();
+    let mut expected = tree;
+    expected.sort();
+    assert_eq!(group, expected);
+
+    assert_eq!(Table::kill_group(root), Ok(tree.len()));
+
+    for pid in tree {
+        Table::get(pid).expect_err("a process of the killed group is still alive");
+    }
+    assert_eq!(Table::kill_group(root), Err(NoProcess));
+
+    Scheduler::enqueue(outsider);
+    assert!(Scheduler::run_one());
+    assert!(
+        scheduler_has_pid(outsider),
+        "the process outside of the killed group should keep running",
+    );
+
+    process_helpers::free(outsider);
+    while Scheduler::run_one() {}
+}
+
+#[test_case]
+fn kill_running_member() {
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let pid = process_helpers::allocate(LOOP_ELF).pid();
+    set_process_state(&mut Table::get(pid).unwrap(), State::Running);
+
+    assert_eq!(Table::kill_group(pid), Ok(1));
+    assert!(
+        Table::get(pid).unwrap().is_killed(),
+        "a running process of the killed group should be marked for termination",
+    );
+
+    set_process_state(&mut Table::get(pid).unwrap(), State::Runnable);
+    Scheduler::enqueue(pid);
+    assert!(Scheduler::run_one());
+
+    Table::get(pid).expect_err("a killed process should exit when it is scheduled");
+    assert!(!scheduler_has_pid(pid));
+}
+
+#[test_case]
+fn set_group_syscall() {
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let root = process_helpers::allocate(LOOP_ELF).pid();
+    let outsider = process_helpers::allocate(LOOP_ELF).pid();
+    let child = fork(root);
+
+    assert_eq!(
+        set_group(
+            Table::get(root).unwrap(),
+            outsider.into_usize(),
+            root.into_usize(),
+        ),
+        Err(PermissionDenied),
+        "only the process itself or its direct children can change the group",
+    );
+    assert_eq!(
+        set_group(
+            Table::get(root).unwrap(),
+            child.into_usize(),
+            outsider.into_usize(),
+        ),
+        Err(PermissionDenied),
+        "a process can not join an unrelated group",
+    );
+
+    set_group(
+        Table::get(root).unwrap(),
+        child.into_usize(),
+        Pid::Current.into_usize(),
+    )
+    .unwrap();
+    assert_eq!(Table::get(child).unwrap().group(), child);
+
+    assert_eq!(Table::kill_group(root), Ok(1));
+    assert!(Table::get(child).is_ok());
+
+    assert_eq!(Table::kill_group(child), Ok(1));
+    process_helpers::free(outsider);
+}
+
+#[test_case]
+fn suspend_group() {
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let pid = process_helpers::allocate(LOOP_ELF).pid();
+    Scheduler::enqueue(pid);
+
+    assert_eq!(Table::suspend_group(pid), Ok(1));
+    assert!(Table::get(pid).unwrap().is_suspended());
+
+    assert!(Scheduler::run_one());
+    assert!(
+        !scheduler_has_pid(pid),
+        "a suspended process should not be scheduled",
+    );
+
+    assert_eq!(Table::resume_group(pid), Ok(1));
+    assert!(!Table::get(pid).unwrap().is_suspended());
+    assert!(scheduler_has_pid(pid));
+
+    assert!(Scheduler::run_one());
+    assert!(scheduler_has_pid(pid));
+
+    process_helpers::free(pid);
+    while Scheduler::run_one() {}
+}
+
+/// Создаёт копию процесса `parent` системным вызовом `exofork()` и возвращает её [`Pid`].
+fn fork(parent: Pid) -> Pid {
+    let mut process = Table::get(parent).unwrap();
+    switch_to(process.address_space());
+
+    let child = exofork(process).expect("exofork() failed");
+    switch_to(&BASE_ADDRESS_SPACE.lock());
+
+    Pid::from_usize(child).expect("wrong child pid from exofork()")
+}
@@ -0,0 +1,215 @@
+#![deny(warnings)]
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(kernel::test_runner)]
+
+use core::alloc::Layout;
+
+use ku::{
+    error::Error::{
+        InvalidAlignment,
+        InvalidArgument,
+        NoPage,
+        PermissionDenied,
+    },
+    process::Pid,
+};
+
+use kernel::{
+    Subsystems,
+    error::Result,
+    log::debug,
+    memory::{
+        Block,
+        Frame,
+        Page,
+        USER_RW,
+        Virt,
+        test_scaffolding::{
+            map_page,
+            translate,
+            unmap_page,
+        },
+    },
+    process::{
+        Table,
+        test_scaffolding::{
+            attach,
+            share,
+        },
+    },
+};
+
+mod init;
+mod mm_helpers;
+mod process_helpers;
+
+init!(Subsystems::MEMORY | Subsystems::SYSCALL | Subsystems::SMP | Subsystems::PROCESS);
+
+const LOOP_ELF: &[u8] = page_aligned!("../../target/kernel/user/loop");
+
+#[test_case]
+fn share_and_attach() {
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let granter = process_helpers::allocate(LOOP_ELF).pid();
+    let grantee = process_helpers::allocate(LOOP_ELF).pid();
+    let outsider = process_helpers::allocate(LOOP_ELF).pid();
+
+    let block = map_block(granter, REGION_PAGES);
+    let address = block.start_address().into_usize();
+    let size = block.size();
+
+    let result = share_region(granter, grantee.into_usize(), address, size);
+    assert_eq!(result, Ok(0));
+
+    for (pid, name, size, error) in [
+        (outsider, NAME, size, PermissionDenied),
+        (grantee, NAME + 1, size, PermissionDenied),
+        (grantee, NAME, size + Page::SIZE, InvalidArgument),
+    ] {
+        let result = attach_region(pid, granter, name, size);
+        assert_eq!(result, Err(error));
+    }
+
+    let attached = attach_region(grantee, granter, NAME, size).unwrap();
+    let attached = page_block(attached, size);
+    debug!(%block, %attached);
+
+    let shared_frames = frames(granter, block);
+    assert_eq!(frames(grantee, attached), shared_frames);
+
+    for page in block {
+        let mut process = Table::get(granter).unwrap();
+        unsafe {
+            unmap_page(process.address_space(), page).unwrap();
+        }
+    }
+
+    let result = attach_region(grantee, granter, NAME, size);
+    assert_eq!(result, Err(NoPage), "the granter has unmapped the region");
+
+    assert_eq!(
+        frames(grantee, attached),
+        shared_frames,
+        "the attached pages should outlive the granter mapping",
+    );
+
+    for pid in [granter, grantee, outsider] {
+        process_helpers::free(pid);
+    }
+}
+
+#[test_case]
+fn share_errors() {
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let granter = process_helpers::allocate(LOOP_ELF).pid();
+    let grantee = process_helpers::allocate(LOOP_ELF).pid();
+
+    let block = map_block(granter, REGION_PAGES);
+    let address = block.start_address().into_usize();
+    let size = block.size();
+    let grantee_pid = grantee.into_usize();
+    let current = Pid::Current.into_usize();
+    let kernel_address = Page::containing(Virt::from_ref(&NAME)).address().into_usize();
+
+    for (pid, address, size, error) in [
+        (current, address, size, InvalidArgument),
+        (grantee_pid, address, 0, InvalidArgument),
+        (grantee_pid, address + 1, size, InvalidAlignment),
+        (grantee_pid, address, size + Page::SIZE, NoPage),
+        (grantee_pid, kernel_address, size, PermissionDenied),
+    ] {
+        let result = share_region(granter, pid, address, size);
+        debug!(pid, address, size, ?result);
+        assert_eq!(result, Err(error));
+    }
+
+    let result = share_region(granter, grantee_pid, address, size);
+    assert_eq!(result, Ok(0));
+
+    let result = share_region(granter, grantee_pid, address, size);
+    assert_eq!(result, Err(InvalidArgument), "the name is already taken");
+
+    for pid in [granter, grantee] {
+        process_helpers::free(pid);
+    }
+}
+
+/// Выполняет системный вызов `share()` от имени процесса `granter`
+/// для участка памяти с именем [`NAME`].
+fn share_region(
+    granter: Pid,
+    grantee: usize,
+    address: usize,
+    size: usize,
+) -> Result<usize> {
+    share(Table::get(granter).unwrap(), grantee, NAME, address, size)
+}
+
+/// Выполняет системный вызов `attach()` от имени процесса `grantee`.
+fn attach_region(
+    grantee: Pid,
+    granter: Pid,
+    name: usize,
+    size: usize,
+) -> Result<usize> {
+    let grantee = Table::get(grantee).unwrap();
+    attach(grantee, granter.into_usize(), name, size)
+}
+
+/// Отображает в адресное пространство процесса `pid` блок из `count` страниц
+/// с флагами [`USER_RW`].
+fn map_block(
+    pid: Pid,
+    count: usize,
+) -> Block<Page> {
+    let mut process = Table::get(pid).unwrap();
+    let address_space = process.address_space();
+
+    let layout = Layout::from_size_align(count * Page::SIZE, Page::SIZE).unwrap();
+    let block = address_space.allocate(layout, USER_RW).unwrap();
+
+    for page in block {
+        unsafe {
+            map_page(address_space, page, USER_RW).unwrap();
+        }
+    }
+
+    block
+}
+
+/// Возвращает блок страниц размера `size` байт, который начинается с адреса `address`.
+fn page_block(
+    address: usize,
+    size: usize,
+) -> Block<Page> {
+    let start = Virt::new(address).unwrap();
+    let end = (start + size).unwrap();
+
+    Block::new(Page::new(start).unwrap(), Page::new(end).unwrap()).unwrap()
+}
+
+/// Возвращает фреймы, в которые отображены страницы блока `block` процесса `pid`.
+fn frames(
+    pid: Pid,
+    block: Block<Page>,
+) -> [Frame; REGION_PAGES] {
+    let mut process = Table::get(pid).unwrap();
+    let mut frames = [Frame::default(); REGION_PAGES];
+
+    for (page, frame) in block.into_iter().zip(frames.iter_mut()) {
+        *frame = translate(process.address_space(), page.address()).unwrap().frame().unwrap();
+    }
+
+    frames
+}
+
+/// Имя разделяемого участка памяти в тестах.
+const NAME: usize = 42;
+
+/// Количество страниц в разделяемом участке памяти.
+const REGION_PAGES: usize = 3;
@@ -0,0 +1,137 @@
+#![deny(warnings)]
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(kernel::test_runner)]
+
+use ku::{
+    error::Error::PermissionDenied,
+    process::Pid,
+};
+
+use kernel::{
+    Subsystems,
+    log::debug,
+    process::{
+        Scheduler,
+        Table,
+        test_scaffolding::{
+            resume,
+            scheduler_front,
+            scheduler_has_pid,
+            set_parent,
+            suspend,
+        },
+    },
+};
+
+mod init;
+mod mm_helpers;
+mod process_helpers;
+
+init!(Subsystems::MEMORY | Subsystems::SMP | Subsystems::PROCESS);
+
+const LOOP_ELF: &[u8] = page_aligned!("../../target/kernel/user/loop");
+
+#[test_case]
+fn permissions() {
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let parent = process_helpers::allocate(LOOP_ELF).pid();
+    let child = allocate_child(parent);
+    let grandchild = allocate_child(child);
+    let outsider = process_helpers::allocate(LOOP_ELF).pid();
+
+    for (src, dst) in [
+        (parent, Pid::Current),
+        (parent, parent),
+        (parent, outsider),
+        (child, parent),
+        (grandchild, child),
+    ] {
+        assert_eq!(
+            suspend(Table::get(src).unwrap(), dst.into_usize()),
+            Err(PermissionDenied),
+            "only descendants can be suspended, src = {src}, dst = {dst}",
+        );
+        assert_eq!(
+            resume(Table::get(src).unwrap(), dst.into_usize()),
+            Err(PermissionDenied),
+            "only descendants can be resumed, src = {src}, dst = {dst}",
+        );
+    }
+
+    suspend(Table::get(parent).unwrap(), grandchild.into_usize()).unwrap();
+    assert!(Table::get(grandchild).unwrap().is_suspended());
+
+    resume(Table::get(parent).unwrap(), grandchild.into_usize()).unwrap();
+    assert!(!Table::get(grandchild).unwrap().is_suspended());
+
+    for pid in [parent, child, grandchild, outsider] {
+        process_helpers::free(pid);
+    }
+    while Scheduler::run_one() {}
+}
+
+#[test_case]
+fn suspend_and_resume() {
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let parent = process_helpers::allocate(LOOP_ELF).pid();
+    let child = allocate_child(parent);
+
+    Scheduler::enqueue(parent);
+    Scheduler::enqueue(child);
+
+    let running_runs = child_runs(child);
+    debug!(running_runs);
+    assert!(running_runs > 0);
+
+    suspend(Table::get(parent).unwrap(), child.into_usize()).unwrap();
+
+    let suspended_runs = child_runs(child);
+    debug!(suspended_runs);
+    assert_eq!(
+        suspended_runs, 0,
+        "a suspended process should not make progress",
+    );
+    assert!(!scheduler_has_pid(child));
+
+    resume(Table::get(parent).unwrap(), child.into_usize()).unwrap();
+    assert!(scheduler_has_pid(child));
+
+    let resumed_runs = child_runs(child);
+    debug!(resumed_runs);
+    assert!(resumed_runs > 0, "a resumed process should continue");
+
+    process_helpers::free(parent);
+    process_helpers::free(child);
+    while Scheduler::run_one() {}
+}
+
+/// Создаёт процесс, исполняющий бесконечный цикл,
+/// с процессом--родителем `parent` и возвращает его [`Pid`].
+fn allocate_child(parent: Pid) -> Pid {
+    let mut child = process_helpers::allocate(LOOP_ELF);
+    set_parent(&mut child, parent);
+    child.pid()
+}
+
+/// Выполняет [`RUNS`] циклов планировщика
+/// и возвращает, сколько из них досталось процессу `child`.
+fn child_runs(child: Pid) -> usize {
+    let mut runs = 0;
+
+    for _ in 0 .. RUNS {
+        if scheduler_front() == Some(child) {
+            runs += 1;
+        }
+        assert!(Scheduler::run_one());
+    }
+
+    runs
+}
+
+/// Количество циклов планировщика, за которые проверяется прогресс процесса.
+const RUNS: usize = 10;
@@ -0,0 +1,163 @@
+#![deny(warnings)]
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(kernel::test_runner)]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use ku::{
+    error::Error::{
+        NoData,
+        NoProcess,
+    },
+    process::{
+        Pid,
+        Syscall,
+    },
+};
+
+use kernel::{
+    Subsystems,
+    log::debug,
+    process::{
+        Scheduler,
+        Table,
+        test_scaffolding::set_parent,
+    },
+};
+
+mod init;
+mod mm_helpers;
+mod process_helpers;
+
+init!(Subsystems::MEMORY | Subsystems::SYSCALL | Subsystems::SMP | Subsystems::PROCESS);
+
+const EXIT_ELF: &[u8] = page_aligned!("../../target/kernel/user/exit");
+const LOOP_ELF: &[u8] = page_aligned!("../../target/kernel/user/loop");
+const SYSCALL_STATS_ELF: &[u8] = page_aligned!("../../target/kernel/user/syscall_stats");
+const WAIT_ELF: &[u8] = page_aligned!("../../target/kernel/user/wait");
+
+#[test_case]
+fn reap() {
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let parent = process_helpers::allocate(LOOP_ELF).pid();
+    assert_eq!(Table::reap(parent), Err(NoProcess));
+
+    let children = spawn(parent, EXIT_ELF, CHILD_COUNT);
+    assert_eq!(Table::reap(parent), Ok(None), "no child has exited yet");
+
+    for &child in &children {
+        Scheduler::enqueue(child);
+    }
+    while Scheduler::run_one() {}
+
+    let mut reaped = Vec::new();
+    while let Some((child, exit_code)) = Table::reap(parent).unwrap() {
+        debug!(%parent, %child, exit_code);
+        assert_eq!(exit_code, EXIT_CODE);
+        Table::get(child).expect_err("an exited child should not be alive");
+        reaped.push(child);
+    }
+    reaped.sort();
+    assert_eq!(reaped, children);
+
+    assert_eq!(Table::reap(parent), Err(NoProcess));
+
+    process_helpers::free(parent);
+}
+
+#[test_case]
+fn orphan() {
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let parent = process_helpers::allocate(LOOP_ELF).pid();
+    let child = spawn(parent, EXIT_ELF, 1)[0];
+
+    process_helpers::free(parent);
+
+    Scheduler::enqueue(child);
+    while Scheduler::run_one() {}
+
+    Table::get(child).expect_err("the 'exit' process was not run up to its completion");
+    assert_eq!(
+        Table::reap(parent),
+        Err(NoProcess),
+        "a child of a dead parent should not become a zombie",
+    );
+}
+
+#[test_case]
+fn wait_syscall() {
+    let _trap_guard = process_helpers::forbid_traps();
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let mut process = process_helpers::allocate(WAIT_ELF);
+    let trace = process.trace_syscalls();
+    let pid = process.pid();
+    drop(process);
+
+    let children = spawn(pid, SYSCALL_STATS_ELF, CHILD_COUNT);
+
+    Scheduler::enqueue(pid);
+    for &child in &children {
+        Scheduler::enqueue(child);
+    }
+    while Scheduler::run_one() {}
+
+    Table::get(pid).expect_err("the 'wait' process was not run up to its completion");
+
+    let records = trace.records();
+    let mut reaped = Vec::new();
+    let mut last_result = None;
+    for record in records.iter().filter(|record| record.syscall() == Syscall::Wait) {
+        debug!(?record);
+        match record.result() {
+            Some(Ok(child)) => reaped.push(Pid::from_usize(*child).unwrap()),
+            Some(Err(NoData)) => {},
+            result => last_result = result.cloned(),
+        }
+    }
+
+    assert_eq!(
+        last_result,
+        Some(Err(NoProcess)),
+        "wait() should fail when all children are reaped",
+    );
+
+    reaped.sort();
+    assert_eq!(reaped, children);
+
+    assert_eq!(*trace.syscalls().last().unwrap(), Syscall::Exit);
+}
+
+/// Создаёт `count` процессов из ELF--файла `elf_file`,
+/// делает их непосредственными потомками процесса `parent`
+/// и возвращает их [`Pid`] в порядке возрастания.
+fn spawn(
+    parent: Pid,
+    elf_file: &[u8],
+    count: usize,
+) -> Vec<Pid> {
+    let mut children = (0 .. count)
+        .map(|_| {
+            let mut child = process_helpers::allocate(elf_file);
+            set_parent(&mut child, parent);
+            child.pid()
+        })
+        .collect::<Vec<_>>();
+
+    children.sort();
+
+    children
+}
+
+/// Количество потомков, завершения которых ожидает родитель.
+const CHILD_COUNT: usize = 3;
+
+/// Код завершения процесса `exit`.
+const EXIT_CODE: usize = 7;
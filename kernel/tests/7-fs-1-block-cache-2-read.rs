@@ -5,7 +5,10 @@
 #![reexport_test_harness_main = "test_main"]
 #![test_runner(kernel::test_runner)]
 
-use core::mem;
+use core::{
+    mem,
+    ptr,
+};
 
 use ku::memory::size::MiB;
 
@@ -54,6 +57,45 @@ fn read_what_was_written() {
     debug!(block_cache_stats = ?BlockCache::stats());
 }
 
+#[test_case]
+fn lru_scan() {
+    let block_count = FS_SIZE / BLOCK_SIZE;
+
+    for (scan_blocks, expected_reads, expected_evictions) in [
+        (LRU_CAPACITY, LRU_CAPACITY, 0),
+        (
+            LRU_CAPACITY + 1,
+            LRU_SCAN_COUNT * (LRU_CAPACITY + 1),
+            LRU_SCAN_COUNT * (LRU_CAPACITY + 1) - LRU_CAPACITY,
+        ),
+    ] {
+        block_cache_init(FS_DISK, block_count, LRU_CAPACITY).unwrap();
+
+        let cache = cache().unwrap();
+
+        for _ in 0 .. LRU_SCAN_COUNT {
+            for block in 0 .. scan_blocks {
+                let address = (cache.start_address() + block * BLOCK_SIZE).unwrap();
+                let data = unsafe { ptr::read_volatile(address.try_into_ptr::<usize>().unwrap()) };
+                assert!(block.is_multiple_of(2) || data == INVALID_DISK_PATTERN);
+            }
+        }
+
+        let stats = BlockCache::stats();
+        debug!(scan_blocks, capacity = LRU_CAPACITY, ?stats);
+
+        // Если повторно просматриваемые блоки помещаются в кэш,
+        // промахи случаются только при первом просмотре.
+        // Иначе LRU вытесняет как раз тот блок, который понадобится раньше всех,
+        // и промахом оказывается каждое обращение.
+        assert_eq!(stats.reads(), expected_reads);
+        assert_eq!(stats.evictions(), expected_evictions);
+        assert_eq!(stats.dirty(), 0);
+    }
+}
+
 const FS_DISK: usize = 1;
 const FS_SIZE: usize = 32 * MiB;
 const INVALID_DISK_PATTERN: usize = 0xAAAA_AAAA_AAAA_AAAA;
+const LRU_CAPACITY: usize = 4;
+const LRU_SCAN_COUNT: usize = 3;
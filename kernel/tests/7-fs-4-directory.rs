@@ -28,8 +28,10 @@ use kernel::{
         FileSystem,
         Kind,
         MAX_NAME_LEN,
+        Mapping,
         test_scaffolding::{
             BLOCK_SIZE,
+            extent_count,
             make_file,
             remove_file,
         },
@@ -139,6 +141,105 @@ fn big_directory() {
     debug!(block_cache_stats = ?BlockCache::stats());
 }
 
+#[test_case]
+fn usage() {
+    FileSystem::format(FS_DISK).unwrap();
+    let mut fs = FileSystem::mount(FS_DISK, CACHE_BLOCK_COUNT, RESOLVE_CACHE_SIZE).unwrap();
+    let directory = make_file(&mut fs, Kind::Directory);
+
+    let start_usage = fs.usage();
+    debug!(%start_usage);
+
+    assert_eq!(start_usage.free_block_count() * BLOCK_SIZE, fs.free_space());
+    assert_eq!(start_usage.used_block_count() * BLOCK_SIZE, fs.used_space());
+    assert!(start_usage.free_inode_count() <= start_usage.inode_count());
+
+    let file = fs.insert(&directory, "file", Kind::File).unwrap();
+
+    let usage = fs.usage();
+    debug!(%usage);
+    assert_eq!(usage.free_inode_count() + 1, start_usage.free_inode_count());
+
+    let buffer = [b'*'; BLOCK_SIZE];
+    assert_eq!(fs.write(&file, 0, &buffer), Ok(buffer.len()));
+
+    let written_usage = fs.usage();
+    debug!(%written_usage);
+    assert_eq!(
+        written_usage.free_block_count() + 1,
+        usage.free_block_count(),
+    );
+    assert_eq!(written_usage.block_count(), start_usage.block_count());
+    assert_eq!(written_usage.inode_count(), start_usage.inode_count());
+
+    fs.remove(&file).unwrap();
+
+    let end_usage = fs.usage();
+    debug!(%end_usage);
+    assert_eq!(end_usage.free_block_count(), usage.free_block_count());
+    assert_eq!(end_usage.free_inode_count(), start_usage.free_inode_count());
+
+    debug!(block_cache_stats = ?BlockCache::stats());
+}
+
+#[test_case]
+fn extents() {
+    FileSystem::format(FS_DISK).unwrap();
+    let mut fs = FileSystem::mount(FS_DISK, CACHE_BLOCK_COUNT, RESOLVE_CACHE_SIZE).unwrap();
+    let directory = make_file(&mut fs, Kind::Directory);
+
+    let forest = fs.insert(&directory, "forest", Kind::File).unwrap();
+    let extents = fs
+        .insert_with_mapping(&directory, "extents", Kind::File, Mapping::Extents)
+        .unwrap();
+    assert_eq!(extent_count(&fs, &forest), None);
+    assert_eq!(extent_count(&fs, &extents), Some(0));
+
+    let start_free_block_count = fs.usage().free_block_count();
+    let mut metadata_block_counts = [0; 2];
+    let mut buffer = [0; BLOCK_SIZE];
+
+    let files = [&forest, &extents];
+    for (file, metadata_block_count) in files.into_iter().zip(&mut metadata_block_counts) {
+        let free_block_count = fs.usage().free_block_count();
+
+        for block in 0 .. FILE_BLOCK_COUNT {
+            buffer.fill(block as u8);
+            assert_eq!(
+                fs.write(file, block * BLOCK_SIZE, &buffer),
+                Ok(buffer.len()),
+            );
+        }
+
+        *metadata_block_count = free_block_count - fs.usage().free_block_count() - FILE_BLOCK_COUNT;
+    }
+
+    debug!(?metadata_block_counts, extent_count = ?extent_count(&fs, &extents));
+
+    assert_eq!(extent_count(&fs, &extents), Some(1));
+    assert_eq!(metadata_block_counts[1], 1);
+    assert!(metadata_block_counts[1] < metadata_block_counts[0]);
+
+    for block in 0 .. FILE_BLOCK_COUNT {
+        assert_eq!(
+            fs.read(&extents, block * BLOCK_SIZE, &mut buffer),
+            Ok(buffer.len()),
+        );
+        assert!(buffer.iter().all(|&x| x == block as u8));
+    }
+
+    let size = FILE_BLOCK_COUNT * BLOCK_SIZE / 2 + 1;
+    fs.set_size(&extents, size).unwrap();
+    assert_eq!(fs.size(&extents), size);
+    assert_eq!(extent_count(&fs, &extents), Some(1));
+
+    fs.remove(&extents).unwrap();
+    fs.remove(&forest).unwrap();
+    assert_eq!(fs.usage().free_block_count(), start_free_block_count);
+
+    debug!(block_cache_stats = ?BlockCache::stats());
+}
+
 #[test_case]
 fn max_name_len() {
     FileSystem::format(FS_DISK).unwrap();
@@ -168,5 +269,6 @@ fn max_name_len() {
 }
 
 const CACHE_BLOCK_COUNT: usize = 1 << 10;
+const FILE_BLOCK_COUNT: usize = 600;
 const FS_DISK: usize = 1;
 const RESOLVE_CACHE_SIZE: usize = 5;
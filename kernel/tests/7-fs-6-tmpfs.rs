@@ -0,0 +1,162 @@
+#![deny(warnings)]
+#![feature(custom_test_frameworks)]
+#![feature(int_roundings)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(kernel::test_runner)]
+
+extern crate alloc;
+
+use alloc::{
+    format,
+    string::String,
+    vec,
+    vec::Vec,
+};
+use core::str;
+
+use ku::error::Error::{
+    FileExists,
+    FileNotFound,
+    InvalidArgument,
+    NotDirectory,
+    NotEmpty,
+    NotFile,
+};
+
+use kernel::{
+    Subsystems,
+    fs::{
+        self,
+        File,
+        Kind,
+        TmpFs,
+        test_scaffolding::BLOCK_SIZE,
+    },
+    log::{
+        debug,
+        info,
+    },
+};
+
+mod init;
+
+init!(Subsystems::MEMORY | Subsystems::FS);
+
+#[test_case]
+fn fs() {
+    let mut fs = TmpFs::new();
+    test_fs(&mut fs);
+}
+
+#[test_case]
+fn nested() {
+    let mut fs = TmpFs::new();
+    let root = fs.open("/").unwrap();
+
+    let mut directory = fs.open("").unwrap();
+    for depth in 0 .. DEPTH {
+        directory = fs.insert(&directory, &format!("dir-{depth}"), Kind::Directory).unwrap();
+        let file = fs.insert(&directory, "file", Kind::File).unwrap();
+        let data = format!("data at depth {depth}");
+        assert_eq!(fs.write(&file, 0, data.as_bytes()), Ok(data.len()));
+    }
+
+    let mut path = String::new();
+    for depth in 0 .. DEPTH {
+        path = format!("{path}/dir-{depth}");
+        let directory = fs.open(&path).unwrap();
+        assert_eq!(fs.kind(&directory), Kind::Directory);
+
+        let file = fs.open(&format!("{path}/file")).unwrap();
+        let expected = format!("data at depth {depth}");
+        let mut buffer = [0; 64];
+        let len = fs.read(&file, 0, &mut buffer).unwrap();
+        debug!(%path, data = str::from_utf8(&buffer[.. len]).unwrap());
+        assert_eq!(&buffer[.. len], expected.as_bytes());
+        assert_eq!(fs.size(&file), expected.len());
+
+        let list = fs.list(&directory).unwrap();
+        let subdirectory_count = usize::from(depth + 1 < DEPTH);
+        assert_eq!(list.len(), 1 + subdirectory_count);
+    }
+
+    let used_space = fs.used_space();
+    debug!(used_space);
+    assert!(used_space > 0);
+
+    let top = fs.open("/dir-0").unwrap();
+    fs.remove(&top).unwrap();
+
+    assert_eq!(fs.open("/dir-0").unwrap_err(), FileNotFound);
+    assert_eq!(fs.open(&path).unwrap_err(), FileNotFound);
+    assert_eq!(fs.remove(&top).unwrap_err(), FileNotFound);
+    assert!(fs.list(&root).unwrap().is_empty());
+    assert_eq!(fs.used_space(), 0);
+}
+
+#[test_case]
+fn errors() {
+    let mut fs = TmpFs::new();
+    let root = fs.open("").unwrap();
+
+    let file = fs.insert(&root, "file", Kind::File).unwrap();
+    let directory = fs.insert(&root, "directory", Kind::Directory).unwrap();
+
+    let mut buffer = [0; 1];
+    assert_eq!(fs.read(&directory, 0, &mut buffer).unwrap_err(), NotFile);
+    assert_eq!(fs.write(&directory, 0, &buffer).unwrap_err(), NotFile);
+    assert_eq!(fs.set_size(&directory, 1).unwrap_err(), NotFile);
+    assert_eq!(fs.read(&file, 1, &mut buffer).unwrap_err(), InvalidArgument);
+
+    assert_eq!(fs.list(&file).unwrap_err(), NotDirectory);
+    assert_eq!(fs.find(&file, "file").unwrap_err(), NotDirectory);
+    assert_eq!(
+        fs.insert(&file, "file", Kind::File).unwrap_err(),
+        NotDirectory,
+    );
+    assert_eq!(fs.open("/file/").unwrap_err(), NotDirectory);
+
+    for kind in [Kind::Directory, Kind::File] {
+        assert_eq!(fs.insert(&root, "file", kind).unwrap_err(), FileExists);
+    }
+
+    for name in ["", "a/b", "not-ascii-\u{444}"] {
+        assert_eq!(
+            fs.insert(&root, name, Kind::File).unwrap_err(),
+            InvalidArgument,
+        );
+    }
+}
+
+#[test_case]
+fn mounted_at_tmp() {
+    assert_eq!(fs::tmp_path("/tmp"), Some(""));
+    assert_eq!(fs::tmp_path("/tmp/"), Some("/"));
+    assert_eq!(fs::tmp_path("/tmp/dir/file"), Some("/dir/file"));
+    assert_eq!(fs::tmp_path("/tmpfile"), None);
+    assert_eq!(fs::tmp_path("/dir/tmp"), None);
+
+    let mut tmp_fs = fs::tmp_fs();
+    let root = tmp_fs.open(fs::tmp_path("/tmp").unwrap()).unwrap();
+    assert_eq!(tmp_fs.kind(&root), Kind::Directory);
+
+    let path = fs::tmp_path("/tmp/file").unwrap();
+    let file = tmp_fs.create(path, Kind::File).unwrap();
+    assert_eq!(tmp_fs.write(&file, 0, b"data"), Ok(4));
+
+    let file = tmp_fs.open(path).unwrap();
+    let mut buffer = [0; 4];
+    assert_eq!(tmp_fs.read(&file, 0, &mut buffer), Ok(4));
+    assert_eq!(&buffer, b"data");
+
+    tmp_fs.unlink(path).unwrap();
+    assert!(tmp_fs.list(&root).unwrap().is_empty());
+}
+
+type Fs = TmpFs;
+
+include!("include/fs_open.rs");
+
+const DEPTH: usize = 10;
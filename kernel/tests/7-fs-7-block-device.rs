@@ -0,0 +1,205 @@
+#![deny(warnings)]
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(kernel::test_runner)]
+
+extern crate alloc;
+
+use alloc::{
+    format,
+    string::String,
+    vec,
+    vec::Vec,
+};
+
+use ku::{
+    error::{
+        Error::NoDisk,
+        Result,
+    },
+    memory::size::MiB,
+};
+
+use kernel::{
+    Subsystems,
+    fs::{
+        BlockCache,
+        BlockDevice,
+        FileSystem,
+        Kind,
+        RamDisk,
+        test_scaffolding::{
+            BLOCK_SIZE,
+            SECTOR_SIZE,
+            block_count,
+            sector_count,
+        },
+    },
+    log::debug,
+};
+
+mod init;
+
+init!(Subsystems::MEMORY);
+
+#[test_case]
+fn ram_disk() {
+    let mut disk = RamDisk::new(RAM_DISK_BLOCK_COUNT);
+    assert_eq!(disk.block_count(), Ok(RAM_DISK_BLOCK_COUNT));
+
+    let mut block = vec![0; BLOCK_SIZE];
+    assert!(disk.read_block(RAM_DISK_BLOCK_COUNT, &mut block).is_err());
+    assert!(disk.read_block(0, &mut block[1 ..]).is_err());
+
+    block.fill(b'*');
+    disk.clone().write_block(1, &block).unwrap();
+
+    let mut copy = vec![0; BLOCK_SIZE];
+    disk.read_block(1, &mut copy).unwrap();
+    assert_eq!(copy, block);
+    disk.read_block(0, &mut copy).unwrap();
+    assert!(copy.iter().all(|&x| x == 0));
+}
+
+#[test_case]
+fn pata_identify() {
+    let sector_count = sector_count(FS_DISK).unwrap();
+    let block_count = block_count(FS_DISK).unwrap();
+    debug!(sector_count, block_count);
+
+    assert!(block_count > 0);
+    assert_eq!(block_count, sector_count * SECTOR_SIZE / BLOCK_SIZE);
+
+    for disk in [NO_DISK, MAX_DISK_COUNT] {
+        assert_eq!(
+            block_count(disk),
+            Err(NoDisk),
+            "there should be no PATA disk {disk}",
+        );
+    }
+}
+
+#[test_case]
+fn same_results() {
+    FileSystem::format(FS_DISK).unwrap();
+    let pata = operations(|| FileSystem::mount(FS_DISK, CACHE_BLOCK_COUNT, RESOLVE_CACHE_SIZE));
+
+    let disk = RamDisk::new(RAM_DISK_BLOCK_COUNT);
+    FileSystem::format_device(disk.clone()).unwrap();
+    let ram = operations(|| {
+        FileSystem::mount_device(disk.clone(), CACHE_BLOCK_COUNT, RESOLVE_CACHE_SIZE)
+    });
+
+    debug!(?pata);
+    debug!(?ram);
+
+    assert_eq!(pata, ram);
+}
+
+#[test_case]
+fn flush_all() {
+    let disk = RamDisk::new(RAM_DISK_BLOCK_COUNT);
+    FileSystem::format_device(disk.clone()).unwrap();
+    let mut fs =
+        FileSystem::mount_device(disk.clone(), CACHE_BLOCK_COUNT, RESOLVE_CACHE_SIZE).unwrap();
+
+    let root = fs.open("").unwrap();
+    let file = fs.insert(&root, "file", Kind::File).unwrap();
+    fs.write(&file, 0, &vec![b'*'; 2 * BLOCK_SIZE]).unwrap();
+
+    let stats = BlockCache::stats();
+    debug!(?stats, "before flush_all()");
+
+    let dirty = stats.dirty();
+    let reads = stats.reads();
+    let writes = stats.writes();
+    assert!(dirty > 0);
+
+    BlockCache::flush_all().unwrap();
+
+    let stats = BlockCache::stats();
+    debug!(?stats, "after flush_all()");
+    assert_eq!(stats.dirty(), 0);
+    assert!(stats.writes() >= writes + dirty);
+    assert_eq!(stats.reads(), reads);
+}
+
+/// Выполняет одну и ту же последовательность операций над файловой системой,
+/// которую монтирует `mount`.
+/// Между операциями файловая система перемонтируется,
+/// чтобы данные прошли через блочное устройство.
+/// Возвращает протокол результатов операций.
+fn operations(mount: impl Fn() -> Result<FileSystem>) -> Vec<String> {
+    let mut results = Vec::new();
+
+    {
+        let mut fs = mount().unwrap();
+        let root = fs.open("").unwrap();
+
+        let directory = fs.insert(&root, "directory", Kind::Directory).unwrap();
+        let file = fs.insert(&directory, "file", Kind::File).unwrap();
+        let data: Vec<_> = (0 .. 3 * BLOCK_SIZE).map(|x| (x % 251) as u8).collect();
+        results.push(format!("{:?}", fs.write(&file, BLOCK_SIZE / 2, &data)));
+
+        let empty = fs.insert(&root, "empty", Kind::File).unwrap();
+        fs.set_size(&empty, 1234).unwrap();
+
+        results.push(format!(
+            "{:?}",
+            fs.insert(&root, "empty", Kind::File).map(|_| ())
+        ));
+    }
+
+    {
+        let mut fs = mount().unwrap();
+
+        for path in ["", "/directory"] {
+            let directory = fs.open(path).unwrap();
+            let mut list: Vec<_> = fs
+                .list(&directory)
+                .unwrap()
+                .iter()
+                .map(|entry| format!("{} {:?} {}", entry.name(), entry.kind(), entry.size()))
+                .collect();
+            introsort::sort(&mut list);
+            results.extend(list);
+        }
+
+        let file = fs.open("/directory/file").unwrap();
+        let mut buffer = vec![0; 4 * BLOCK_SIZE];
+        let len = fs.read(&file, 0, &mut buffer).unwrap();
+        results.push(format!("{len} {:?}", checksum(&buffer[.. len])));
+
+        fs.set_size(&file, BLOCK_SIZE + 1).unwrap();
+        let empty = fs.open("/empty").unwrap();
+        fs.remove(&empty).unwrap();
+    }
+
+    {
+        let mut fs = mount().unwrap();
+
+        let file = fs.open("/directory/file").unwrap();
+        let mut buffer = vec![0; 4 * BLOCK_SIZE];
+        let len = fs.read(&file, 0, &mut buffer).unwrap();
+        results.push(format!("{len} {:?}", checksum(&buffer[.. len])));
+        results.push(format!("{:?}", fs.open("/empty").map(|_| ())));
+    }
+
+    results
+}
+
+/// Простая контрольная сумма данных.
+fn checksum(data: &[u8]) -> u64 {
+    data.iter().fold(0, |checksum, &x| {
+        checksum.wrapping_mul(31).wrapping_add(x.into())
+    })
+}
+
+const CACHE_BLOCK_COUNT: usize = 1 << 10;
+const FS_DISK: usize = 1;
+const MAX_DISK_COUNT: usize = 4;
+const NO_DISK: usize = 3;
+const RAM_DISK_BLOCK_COUNT: usize = 4 * MiB / BLOCK_SIZE;
+const RESOLVE_CACHE_SIZE: usize = 5;
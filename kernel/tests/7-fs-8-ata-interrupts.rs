@@ -0,0 +1,65 @@
+#![deny(warnings)]
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(kernel::test_runner)]
+
+extern crate alloc;
+
+use alloc::vec;
+
+use kernel::{
+    Subsystems,
+    fs::test_scaffolding::{
+        BLOCK_SIZE,
+        interrupt_completions,
+        read_block,
+    },
+    log::debug,
+    trap::{
+        TRAP_STATS,
+        Trap,
+    },
+};
+
+mod init;
+
+init!(Subsystems::MEMORY);
+
+#[test_case]
+fn interrupt_driven_read() {
+    let mut polled = vec![0_u8; BLOCK_SIZE];
+    let mut interrupt_driven = vec![0_u8; BLOCK_SIZE];
+
+    for block_number in 0 .. BLOCK_COUNT {
+        read_block(FS_DISK, block_number, &mut polled, false).unwrap();
+
+        let start_completions = interrupt_completions(FS_DISK).unwrap();
+        let start_traps = TRAP_STATS[Trap::Ata0].count();
+
+        read_block(FS_DISK, block_number, &mut interrupt_driven, true).unwrap();
+
+        let completions = interrupt_completions(FS_DISK).unwrap() - start_completions;
+        let traps = TRAP_STATS[Trap::Ata0].count() - start_traps;
+        debug!(block_number, completions, traps);
+
+        assert_eq!(interrupt_driven, polled);
+        assert!(completions >= SECTORS_PER_BLOCK);
+        assert!(traps >= completions);
+    }
+}
+
+#[test_case]
+fn polled_read_ignores_interrupts() {
+    let mut buffer = vec![0_u8; BLOCK_SIZE];
+
+    let start_completions = interrupt_completions(FS_DISK).unwrap();
+    read_block(FS_DISK, 0, &mut buffer, false).unwrap();
+
+    assert_eq!(interrupt_completions(FS_DISK).unwrap(), start_completions);
+}
+
+const BLOCK_COUNT: usize = 4;
+const FS_DISK: usize = 1;
+const SECTORS_PER_BLOCK: usize = BLOCK_SIZE / 512;
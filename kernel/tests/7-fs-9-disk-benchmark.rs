@@ -0,0 +1,131 @@
+#![deny(warnings)]
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(kernel::test_runner)]
+
+extern crate alloc;
+
+use alloc::{
+    vec,
+    vec::Vec,
+};
+
+use chrono::Duration;
+
+use ku::{
+    error::Result,
+    memory::size::MiB,
+    time::{
+        self,
+        Tsc,
+    },
+};
+
+use kernel::{
+    Subsystems,
+    fs::{
+        BlockDevice,
+        RamDisk,
+        test_scaffolding::{
+            BLOCK_SIZE,
+            read_block,
+            spin_tsc,
+        },
+    },
+    log::info,
+};
+
+mod init;
+
+init!(Subsystems::MEMORY);
+
+#[test_case]
+fn polled_vs_interrupt_driven() {
+    let mut polled = vec![vec![0; BLOCK_SIZE]; BLOCK_COUNT];
+    let mut interrupt_driven = vec![vec![0; BLOCK_SIZE]; BLOCK_COUNT];
+    let mut ram = vec![vec![0; BLOCK_SIZE]; BLOCK_COUNT];
+
+    let polled_spin_tsc = measure("pata-polled", &mut polled, |block_number, buffer| {
+        read_block(FS_DISK, block_number, buffer, false)
+    });
+
+    let interrupt_driven_spin_tsc = measure(
+        "pata-interrupt",
+        &mut interrupt_driven,
+        |block_number, buffer| read_block(FS_DISK, block_number, buffer, true),
+    );
+
+    assert_eq!(polled, interrupt_driven);
+
+    let mut ram_disk = RamDisk::new(BLOCK_COUNT);
+    for (block_number, block) in polled.iter().enumerate() {
+        ram_disk.write_block(block_number, block).unwrap();
+    }
+
+    let ram_spin_tsc = measure("ram", &mut ram, |block_number, buffer| {
+        ram_disk.read_block(block_number, buffer)
+    });
+
+    assert_eq!(ram, polled);
+    assert_eq!(ram_spin_tsc, 0);
+
+    // The emulated disk may complete a request almost immediately,
+    // so only the order of the spin times is stable, not the gap between them.
+    assert!(
+        interrupt_driven_spin_tsc <= polled_spin_tsc,
+        "interrupt-driven reads should not spin longer than polled ones",
+    );
+}
+
+/// Читает `buffers.len()` блоков функцией `read` и выводит в лог
+/// пропускную способность и долю времени, потраченного на активное ожидание.
+/// Возвращает количество тактов процессора, потраченных на активное ожидание диска.
+fn measure(
+    mode: &str,
+    buffers: &mut [Vec<u8>],
+    mut read: impl FnMut(usize, &mut [u8]) -> Result<()>,
+) -> i64 {
+    let start_spin_tsc = spin_tsc();
+    let start_tsc = time::tsc();
+    let start = Tsc::now();
+
+    for (block_number, buffer) in buffers.iter_mut().enumerate() {
+        read(block_number, buffer).unwrap();
+    }
+
+    let elapsed = start.elapsed();
+    let elapsed_tsc = time::tsc() - start_tsc;
+    let spin_tsc = spin_tsc() - start_spin_tsc;
+
+    let bytes = buffers.len() * BLOCK_SIZE;
+    let microseconds = Duration::try_from(elapsed)
+        .ok()
+        .and_then(|elapsed| elapsed.num_microseconds())
+        .unwrap_or(0);
+    let mib_per_second = if microseconds > 0 {
+        (bytes as f64) * 1_000_000.0 / (MiB as f64) / (microseconds as f64)
+    } else {
+        0.0
+    };
+    let spin_percent = 100.0 * (spin_tsc as f64) / (elapsed_tsc.max(1) as f64);
+
+    info!(
+        benchmark = "disk_read",
+        mode,
+        blocks = buffers.len(),
+        bytes,
+        %elapsed,
+        elapsed_tsc,
+        spin_tsc,
+        mib_per_second,
+        spin_percent,
+        "benchmark result",
+    );
+
+    spin_tsc
+}
+
+const BLOCK_COUNT: usize = 256;
+const FS_DISK: usize = 1;
@@ -0,0 +1,313 @@
+#![deny(warnings)]
+#![feature(allocator_api)]
+#![feature(custom_test_frameworks)]
+#![feature(slice_ptr_get)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(kernel::test_runner)]
+
+extern crate alloc;
+
+use alloc::{
+    alloc::{
+        Allocator,
+        Global,
+        Layout,
+    },
+    boxed::Box,
+    collections::BTreeMap,
+    vec,
+    vec::Vec,
+};
+use core::{
+    cmp,
+    hint,
+    mem,
+};
+
+use chrono::Duration;
+
+use ku::{
+    allocator::Info,
+    memory::{
+        Page,
+        Virt,
+        size::{
+            MiB,
+            Size,
+        },
+    },
+    time::{
+        self,
+        TscDuration,
+    },
+};
+
+use kernel::{
+    Subsystems,
+    allocator,
+    log::{
+        debug,
+        info,
+    },
+    memory::{
+        BASE_ADDRESS_SPACE,
+        Block,
+        FRAME_ALLOCATOR,
+        KERNEL_RW,
+        test_scaffolding::{
+            map_page,
+            unmap_page,
+        },
+    },
+    process::{
+        Scheduler,
+        test_scaffolding::disable_interrupts,
+    },
+};
+
+mod init;
+mod mm_helpers;
+mod process_helpers;
+
+init!(Subsystems::MEMORY | Subsystems::SMP | Subsystems::PROCESS);
+
+const SCHED_YIELD_ELF: &[u8] = page_aligned!("../../target/kernel/user/sched_yield");
+
+#[test_case]
+fn frame_allocator() {
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    let mut frame_allocator = FRAME_ALLOCATOR.lock();
+    let mut frames = Vec::with_capacity(FRAME_COUNT);
+
+    let start = time::timer();
+    for _ in 0 .. FRAME_COUNT {
+        let frame = frame_allocator.allocate().unwrap();
+        frames.push(*frame);
+        mem::forget(frame);
+    }
+    let allocate = start.elapsed();
+
+    let start = time::timer();
+    for &frame in &frames {
+        frame_allocator.deallocate(frame);
+    }
+    let deallocate = start.elapsed();
+
+    report("frame_allocator", "allocate", FRAME_COUNT, 0, allocate);
+    report("frame_allocator", "deallocate", FRAME_COUNT, 0, deallocate);
+}
+
+#[test_case]
+fn map_unmap() {
+    let start_page = Page::containing(mm_helpers::unique_kernel_virt()).index();
+    let pages = Block::<Page>::from_index(start_page, start_page + PAGE_COUNT).unwrap();
+
+    let mut address_space = BASE_ADDRESS_SPACE.lock();
+
+    let start = time::timer();
+    for page in pages {
+        unsafe {
+            map_page(&mut address_space, page, KERNEL_RW).unwrap();
+        }
+    }
+    let map = start.elapsed();
+
+    let start = time::timer();
+    for page in pages {
+        unsafe {
+            unmap_page(&mut address_space, page).unwrap();
+        }
+    }
+    let unmap = start.elapsed();
+
+    report("map_unmap", "map", PAGE_COUNT, 0, map);
+    report("map_unmap", "unmap", PAGE_COUNT, 0, unmap);
+}
+
+#[test_case]
+fn heap() {
+    let mut allocations = Vec::with_capacity(HEAP_ALLOCATION_COUNT);
+
+    for size in HEAP_ALLOCATION_SIZES {
+        let layout = Layout::from_size_align(size, mem::align_of::<usize>()).unwrap();
+
+        let start = time::timer();
+        for _ in 0 .. HEAP_ALLOCATION_COUNT {
+            allocations.push(Global.allocate(layout).unwrap());
+        }
+        let allocate = start.elapsed();
+
+        let start = time::timer();
+        for allocation in allocations.drain(..) {
+            unsafe {
+                Global.deallocate(allocation.as_non_null_ptr(), layout);
+            }
+        }
+        let deallocate = start.elapsed();
+
+        let bytes = HEAP_ALLOCATION_COUNT * size;
+        report("heap", "allocate", HEAP_ALLOCATION_COUNT, bytes, allocate);
+        report(
+            "heap",
+            "deallocate",
+            HEAP_ALLOCATION_COUNT,
+            bytes,
+            deallocate,
+        );
+    }
+}
+
+#[test_case]
+fn heap_workloads() {
+    let start = time::timer();
+    memory_allocator_basic();
+    report("heap_workload", "basic", 1, 0, start.elapsed());
+
+    let start = time::timer();
+    memory_allocator_alignment();
+    report("heap_workload", "alignment", 1, 0, start.elapsed());
+
+    let start = time::timer();
+    memory_allocator_grow_and_shrink();
+    report("heap_workload", "grow_and_shrink", 1, 0, start.elapsed());
+
+    let start = time::timer();
+    let max_fragmentation_loss = |values| cmp::max(6 * values, 2 * MiB);
+    memory_allocator_stress(HEAP_STRESS_VALUES, max_fragmentation_loss);
+    report(
+        "heap_workload",
+        "stress",
+        HEAP_STRESS_VALUES,
+        0,
+        start.elapsed(),
+    );
+}
+
+#[test_case]
+fn memory_bandwidth() {
+    let source = vec![0xA5_u8; BANDWIDTH_BUFFER_SIZE];
+    let mut destination = vec![0_u8; BANDWIDTH_BUFFER_SIZE];
+    let bytes = BANDWIDTH_ITERATIONS * BANDWIDTH_BUFFER_SIZE;
+
+    let start = time::timer();
+    for _ in 0 .. BANDWIDTH_ITERATIONS {
+        destination.copy_from_slice(hint::black_box(&source));
+        hint::black_box(&mut destination);
+    }
+    let memcpy = start.elapsed();
+    assert_eq!(destination, source);
+
+    let start = time::timer();
+    for _ in 0 .. BANDWIDTH_ITERATIONS {
+        hint::black_box(&mut destination).fill(0);
+    }
+    let memzero = start.elapsed();
+    assert!(destination.iter().all(|&x| x == 0));
+
+    report(
+        "memory_bandwidth",
+        "memcpy",
+        BANDWIDTH_ITERATIONS,
+        bytes,
+        memcpy,
+    );
+    report(
+        "memory_bandwidth",
+        "memzero",
+        BANDWIDTH_ITERATIONS,
+        bytes,
+        memzero,
+    );
+}
+
+#[test_case]
+fn context_switch() {
+    let mut process = process_helpers::allocate(SCHED_YIELD_ELF);
+    let pid = process.pid();
+    disable_interrupts(&mut process);
+    drop(process);
+
+    Scheduler::enqueue(pid);
+
+    let start = time::timer();
+    for _ in 0 .. CONTEXT_SWITCH_ROUND_TRIPS {
+        assert!(Scheduler::run_one());
+    }
+    let round_trips = start.elapsed();
+
+    process_helpers::free(pid);
+    while Scheduler::run_one() {}
+
+    // Каждый проход туда и обратно --- это два переключения контекста:
+    // из ядра в процесс и из процесса, вызвавшего `sched_yield()`, обратно в ядро.
+    report(
+        "context_switch",
+        "round_trip",
+        CONTEXT_SWITCH_ROUND_TRIPS,
+        0,
+        round_trips,
+    );
+}
+
+/// Выводит в лог результат бенчмарка `benchmark` в машинно--читаемом формате:
+/// `count` операций `operation` заняли время `elapsed` и обработали `bytes` байт.
+///
+/// # Panics
+///
+/// Паникует, если результат неправдоподобен --- операции не заняли времени.
+fn report(
+    benchmark: &str,
+    operation: &str,
+    count: usize,
+    bytes: usize,
+    elapsed: TscDuration,
+) {
+    let tsc_per_operation = elapsed.into_f64() / (count as f64);
+    let nanoseconds = Duration::try_from(elapsed)
+        .ok()
+        .and_then(|elapsed| elapsed.num_nanoseconds())
+        .unwrap_or(0);
+    let nanoseconds_per_operation = (nanoseconds as f64) / (count as f64);
+    let mib_per_second = if nanoseconds > 0 {
+        (bytes as f64) * 1_000_000_000.0 / (MiB as f64) / (nanoseconds as f64)
+    } else {
+        0.0
+    };
+
+    info!(
+        benchmark,
+        operation,
+        count,
+        bytes,
+        %elapsed,
+        tsc_per_operation,
+        nanoseconds_per_operation,
+        mib_per_second,
+        "benchmark result",
+    );
+
+    assert!(
+        tsc_per_operation > 0.0,
+        "benchmark {benchmark} reported no time for {count} {operation} operations",
+    );
+}
+
+macro_rules! my_assert {
+    ($cond:expr $(,)?) => {{
+        assert!($cond);
+    }};
+}
+
+include!("include/memory_allocator.rs");
+
+const BANDWIDTH_BUFFER_SIZE: usize = MiB;
+const BANDWIDTH_ITERATIONS: usize = 64;
+const CONTEXT_SWITCH_ROUND_TRIPS: usize = 1_000;
+const FRAME_COUNT: usize = 1 << 10;
+const HEAP_ALLOCATION_COUNT: usize = 1 << 10;
+const HEAP_ALLOCATION_SIZES: [usize; 4] = [8, 64, 512, 4096];
+const HEAP_STRESS_VALUES: usize = 100_000;
+const PAGE_COUNT: usize = 1 << 10;
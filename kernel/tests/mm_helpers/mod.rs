@@ -139,11 +139,9 @@ pub(super) fn check_path(
     for (&child, &parent) in nodes(&path).iter().zip(nodes(&path)[1 ..].iter()) {
         if let Some(child) = child {
             let child_page = Page::containing(Virt::from_ptr(child.as_ptr()));
-            let phys2virt_start_page =
-                Page::containing(phys2virt(address_space).map(Phys::default()).unwrap());
-            let child_frame_index = (child_page - phys2virt_start_page).unwrap();
+            let child_frame = phys2virt(address_space).frame_of_page(child_page).unwrap();
             let parent_points_to = unsafe { parent.unwrap().as_ref().frame().unwrap() };
-            assert_eq!(child_frame_index, parent_points_to.index());
+            assert_eq!(child_frame, parent_points_to);
         }
     }
 }
@@ -173,6 +171,11 @@ pub fn forbid_frame_leaks() -> impl Drop {
             ku::log::error!(start_free_frames, end_free_frames, affected_frames, message);
             panic!("{}", message);
         }
+
+        if cfg!(feature = "frame-audit") {
+            let mismatches = kernel::memory::check_frame_references(&mut []);
+            assert_eq!(mismatches, 0, "frame reference counts are inconsistent");
+        }
     })
 }
 
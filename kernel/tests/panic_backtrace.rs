@@ -0,0 +1,73 @@
+#![deny(warnings)]
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(kernel::test_runner)]
+
+extern crate alloc;
+
+use alloc::format;
+use core::{
+    hint,
+    panic::PanicInfo,
+};
+
+use bootloader::{
+    BootInfo,
+    entry_point,
+};
+
+use ku::{
+    backtrace::Backtrace,
+    sync,
+};
+
+use kernel::log::info;
+
+entry_point!(test_entry);
+
+fn test_entry(boot_info: &'static BootInfo) -> ! {
+    kernel::init(boot_info);
+    test_main();
+    panic!("should not return to test_entry()")
+}
+
+#[panic_handler]
+fn panic(panic_info: &PanicInfo) -> ! {
+    sync::start_panicking();
+
+    let backtrace = Backtrace::current().map(|backtrace| format!("{backtrace:?}"));
+    info!(?backtrace);
+
+    let is_symbolized = backtrace
+        .is_ok_and(|backtrace| backtrace.contains("panic_backtrace::panicking_function+0x"));
+
+    if is_symbolized {
+        kernel::pass_test()
+    } else {
+        kernel::fail_test(panic_info)
+    }
+}
+
+#[test_case]
+fn panic_backtrace() {
+    panicking_function();
+}
+
+#[inline(never)]
+fn panicking_function() {
+    maybe_panic();
+
+    // Keeps the call above out of the tail position,
+    // so `panicking_function()` has its own frame in the backtrace.
+    hint::black_box(());
+}
+
+#[inline(never)]
+fn maybe_panic() {
+    // Hides from the compiler that this function never returns.
+    if hint::black_box(true) {
+        panic!("expected panic");
+    }
+}
@@ -0,0 +1,43 @@
+#![deny(warnings)]
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(kernel::test_runner)]
+
+use kernel::{
+    Subsystems,
+    log::debug,
+    self_test::Check,
+};
+
+mod init;
+mod mm_helpers;
+
+init!(Subsystems::MEMORY);
+
+#[test_case]
+fn each_check() {
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    for check in Check::ALL {
+        let result = check.run();
+        debug!(%check, ?result);
+        assert_eq!(result, Ok(()), "self-test check '{check}' failed");
+    }
+}
+
+#[test_case]
+fn all_checks() {
+    let _guard = mm_helpers::forbid_frame_leaks();
+
+    assert!(kernel::self_test(Subsystems::MEMORY));
+}
+
+#[test_case]
+fn skip_uninitialized() {
+    assert!(Check::Frame.subsystems().contains(Subsystems::MEMORY));
+    assert_eq!(Check::Log.subsystems(), Subsystems::empty());
+
+    assert!(kernel::self_test(Subsystems::empty()));
+}
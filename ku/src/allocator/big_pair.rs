@@ -35,6 +35,13 @@ pub trait BigAllocatorPair {
     /// Возвращает `true`, если `src` и `dst` --- это один и тот же [`BigAllocator`].
     fn is_same(&self) -> bool;
 
+    /// Возвращает `true`, если весь блок `block` в адресном пространстве `dst()`
+    /// зарезервирован для пространства пользователя.
+    fn is_user_block(
+        &self,
+        block: Block<Page>,
+    ) -> bool;
+
     /// Копирует отображение физических фреймов из `src()`/`src_block` в `dst()`/`dst_block`.
     /// Если изначально `dst_block` содержал отображённые страницы,
     /// их отображение удаляется.
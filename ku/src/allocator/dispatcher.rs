@@ -30,7 +30,9 @@ use super::{
         MaxPrimitiveType,
     },
     info::{
+        AtomicHistogram,
         AtomicInfo,
+        Histogram,
         Info,
     },
 };
@@ -52,6 +54,9 @@ pub struct Dispatcher<Q: Cache, T: Allocator + BigAllocatorGuard> {
     /// Каждый из них выделяет память блоками фиксированного размера.
     fixed_size: [Spinlock<FixedSizeAllocator>; FIXED_SIZE_COUNT],
 
+    /// Гистограмма размеров запросов на выделение памяти.
+    histogram: AtomicHistogram,
+
     /// Общая статистика аллокатора.
     info: AtomicInfo,
 }
@@ -67,6 +72,7 @@ impl<Q: Cache, T: Allocator + BigAllocatorGuard> Dispatcher<Q, T> {
             fallback,
             fallback_info: AtomicInfo::new(),
             fixed_size: [FIXED_SIZE_DUMMY; FIXED_SIZE_COUNT],
+            histogram: AtomicHistogram::new(),
             info: AtomicInfo::new(),
         }
     }
@@ -102,6 +108,7 @@ impl<Q: Cache, T: Allocator + BigAllocatorGuard> Dispatcher<Q, T> {
     ) {
         detailed_info.total = self.info();
         detailed_info.fallback = self.fallback_info.load();
+        detailed_info.histogram = self.histogram.load();
 
         for (info, allocator) in detailed_info.fixed_size.iter_mut().zip(&self.fixed_size) {
             *info = *allocator.lock().info();
@@ -149,6 +156,37 @@ impl<Q: Cache, T: Allocator + BigAllocatorGuard> Dispatcher<Q, T> {
         assert_eq!(self.info().pages().balance(), 0, "Not all pages are freed");
     }
 
+    /// Возвращает в [`Dispatcher::fallback`] память тех [`FixedSizeAllocator`],
+    /// все блоки которых свободны.
+    /// Так память, набранная во время временного всплеска выделений, не удерживается навсегда.
+    ///
+    /// Перед этим освобождает кэш блоков [`Dispatcher::cache`].
+    /// Если у каждого потока свой кэш, освобождается только кэш текущего потока.
+    /// Блоки из кэшей других потоков считаются занятыми и
+    /// не дают вернуть память соответствующих им [`FixedSizeAllocator`].
+    ///
+    /// Возвращает количество освобождённых физических фреймов.
+    pub fn reclaim(&self) -> usize {
+        let mut total_frames = 0;
+
+        for (index, allocator) in self.fixed_size.iter().enumerate() {
+            let mut allocator = allocator.lock();
+
+            if Q::CACHE_AVAILABLE {
+                self.cache.with_borrow_mut(index, |clip| allocator.unfill_clip(clip, 0));
+            }
+
+            if let Ok(frames) = allocator.reclaim(&self.fallback) &&
+                frames > 0
+            {
+                self.info.pages_deallocation(frames);
+                total_frames += frames;
+            }
+        }
+
+        total_frames
+    }
+
     /// Находит индекс [`FixedSizeAllocator`], который отвечает за заданный `layout`.
     ///
     /// Возвращает [`None`], если такого нет.
@@ -190,6 +228,7 @@ impl<Q: Cache, T: Allocator + BigAllocatorGuard> Dispatcher<Q, T> {
         };
 
         if let Ok(ptr) = ptr {
+            self.histogram.allocation(layout.size(), false);
             self.update_fallback_info(Operation::Allocation, layout);
 
             ptr.as_non_null_ptr().as_ptr()
@@ -214,6 +253,8 @@ impl<Q: Cache, T: Allocator + BigAllocatorGuard> Dispatcher<Q, T> {
             });
             
             if let Some(ptr) = ptr {
+                self.histogram.allocation(layout.size(), true);
+
                 // Fast path: allocated from cache, no lock needed for statistics
                 #[cfg(feature = "allocator-statistics")]
                 {
@@ -248,6 +289,7 @@ impl<Q: Cache, T: Allocator + BigAllocatorGuard> Dispatcher<Q, T> {
                 
                 // Try to allocate again
                 if let Some(ptr) = clip.pop() {
+                    self.histogram.allocation(layout.size(), false);
                     #[cfg(feature = "allocator-statistics")]
                     {
                         let size = Self::get_size(index);
@@ -276,6 +318,10 @@ impl<Q: Cache, T: Allocator + BigAllocatorGuard> Dispatcher<Q, T> {
             }
             
             let ptr = allocator.allocate(layout);
+
+            if !ptr.is_null() {
+                self.histogram.allocation(layout.size(), false);
+            }
             
             #[cfg(feature = "allocator-statistics")]
             if !ptr.is_null() {
@@ -560,6 +606,7 @@ unsafe impl<Q: Cache, T: Allocator + BigAllocatorGuard> GlobalAlloc for Dispatch
                 };
 
                 if let Ok(new_ptr) = new_ptr {
+                    self.histogram.allocation(new_layout.size(), false);
                     self.update_fallback_info(Operation::Allocation, new_layout);
                     self.update_fallback_info(Operation::Deallocation, old_layout);
 
@@ -589,6 +636,9 @@ pub struct DetailedInfo {
 
     /// Статистика аллокаторов [`FixedSizeAllocator`] для разных размеров блоков.
     fixed_size: [Info; FIXED_SIZE_COUNT],
+
+    /// Гистограмма размеров запросов на выделение памяти.
+    histogram: Histogram,
 }
 
 #[allow(rustdoc::private_intra_doc_links)]
@@ -599,6 +649,7 @@ impl DetailedInfo {
             total: Info::new(),
             fallback: Info::new(),
             fixed_size: [Info::new(); FIXED_SIZE_COUNT],
+            histogram: Histogram::new(),
         }
     }
 
@@ -617,6 +668,13 @@ impl DetailedInfo {
         &self.fixed_size
     }
 
+    /// Гистограмма размеров запросов на выделение памяти.
+    /// Позволяет оценить, подходят ли размеры блоков [`FixedSizeAllocator`]
+    /// под реальную нагрузку.
+    pub fn histogram(&self) -> &Histogram {
+        &self.histogram
+    }
+
     /// Проверяет инварианты статистики аллокатора.
     ///
     /// Требует эксклюзивного доступа к аллокатору в момент снятия детальной статистики.
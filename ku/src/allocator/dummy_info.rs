@@ -228,3 +228,85 @@ impl Counter {
         0
     }
 }
+
+/// Заглушка на случай выключенной опции `allocator-statistics`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Histogram;
+
+impl Histogram {
+    /// Заглушка на случай выключенной опции `allocator-statistics`.
+    #[inline(always)]
+    pub(super) const fn new() -> Self {
+        Self
+    }
+
+    /// Заглушка на случай выключенной опции `allocator-statistics`.
+    #[inline(always)]
+    pub const fn buckets(&self) -> &[usize] {
+        &[]
+    }
+
+    /// Заглушка на случай выключенной опции `allocator-statistics`.
+    #[inline(always)]
+    pub const fn cached(&self) -> usize {
+        0
+    }
+
+    /// Заглушка на случай выключенной опции `allocator-statistics`.
+    #[inline(always)]
+    pub const fn uncached(&self) -> usize {
+        0
+    }
+
+    /// Заглушка на случай выключенной опции `allocator-statistics`.
+    #[inline(always)]
+    pub const fn bucket_index(_size: usize) -> usize {
+        0
+    }
+
+    /// Поддерживается ли гистограмма размеров запросов.
+    /// Равно `true`, если включена опция `allocator-statistics`.
+    pub const IS_SUPPORTED: bool = false;
+}
+
+impl fmt::Display for Histogram {
+    fn fmt(
+        &self,
+        formatter: &mut fmt::Formatter,
+    ) -> fmt::Result {
+        write!(
+            formatter,
+            "{{ \"allocator-statistics\" feature is disabled }}",
+        )
+    }
+}
+
+/// Заглушка на случай выключенной опции `allocator-statistics`.
+#[derive(Debug, Default)]
+pub struct AtomicHistogram;
+
+impl AtomicHistogram {
+    /// Заглушка на случай выключенной опции `allocator-statistics`.
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self
+    }
+
+    /// Заглушка на случай выключенной опции `allocator-statistics`.
+    #[inline(always)]
+    pub const fn allocation(
+        &self,
+        _size: usize,
+        _cached: bool,
+    ) {
+    }
+
+    /// Заглушка на случай выключенной опции `allocator-statistics`.
+    #[inline(always)]
+    pub const fn load(&self) -> Histogram {
+        Histogram
+    }
+}
+
+/// Заглушка на случай выключенной опции `allocator-statistics`.
+pub const HISTOGRAM_BUCKET_COUNT: usize = 0;
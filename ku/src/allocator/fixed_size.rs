@@ -301,6 +301,26 @@ impl FixedSizeAllocator {
         Ok(total_frames)
     }
 
+    /// Если все блоки [`FixedSizeAllocator`] свободны,
+    /// возвращает всю использованную им память в страничный аллокатор `fallback`
+    /// так же, как [`FixedSizeAllocator::unmap()`].
+    /// Следующее выделение памяти снова наберёт её из `fallback`.
+    ///
+    /// Блоки, лежащие в [`Clip`], с точки зрения [`FixedSizeAllocator`] заняты.
+    /// Поэтому перед вызовом этого метода имеет смысл освободить их.
+    ///
+    /// Возвращает количество освобождённых физических фреймов.
+    pub(super) fn reclaim(
+        &mut self,
+        fallback: &impl BigAllocatorGuard,
+    ) -> Result<usize> {
+        if self.quarry.is_none() || self.bitmap.free() != self.bitmap.len() {
+            return Ok(0);
+        }
+
+        self.unmap(fallback)
+    }
+
     /// Освобождает выделенные ранее методом [`FixedSizeAllocator::allocate()`]
     /// блок памяти, начинающийся по указателю `ptr`.
     ///
@@ -544,3 +544,152 @@ impl fmt::Display for SizeCounter {
         }
     }
 }
+
+/// Гистограмма размеров запросов на выделение памяти.
+///
+/// Запрос размера `size` попадает в корзину номер [`Histogram::bucket_index()`],
+/// то есть в корзину `i` попадают размеры от `2^(i - 1) + 1` до `2^i` байт включительно.
+/// Помогает подобрать набор размеров блоков, которые кэширует [`super::Dispatcher`],
+/// под реальную нагрузку.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Histogram {
+    /// Количество запросов в каждой из корзин.
+    buckets: [usize; HISTOGRAM_BUCKET_COUNT],
+
+    /// Количество запросов, обслуженных из кэша выделяемых блоков.
+    cached: usize,
+
+    /// Количество запросов, обслуженных нижележащими аллокаторами.
+    uncached: usize,
+}
+
+impl Histogram {
+    /// Инициализирует гистограмму нулями.
+    pub(super) const fn new() -> Self {
+        Self {
+            buckets: [0; HISTOGRAM_BUCKET_COUNT],
+            cached: 0,
+            uncached: 0,
+        }
+    }
+
+    /// Количество запросов в каждой из корзин.
+    pub fn buckets(&self) -> &[usize] {
+        &self.buckets
+    }
+
+    /// Количество запросов, обслуженных из кэша выделяемых блоков.
+    pub fn cached(&self) -> usize {
+        self.cached
+    }
+
+    /// Количество запросов, обслуженных нижележащими аллокаторами.
+    pub fn uncached(&self) -> usize {
+        self.uncached
+    }
+
+    /// Номер корзины для запроса размера `size` ---
+    /// логарифм по основанию `2` от `size`, округлённый вверх.
+    pub const fn bucket_index(size: usize) -> usize {
+        if size <= 1 {
+            0
+        } else {
+            (usize::BITS - (size - 1).leading_zeros()) as usize
+        }
+    }
+
+    /// Поддерживается ли гистограмма размеров запросов.
+    /// Равно `true`, если включена опция `allocator-statistics`.
+    pub const IS_SUPPORTED: bool = true;
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for Histogram {
+    fn fmt(
+        &self,
+        formatter: &mut fmt::Formatter,
+    ) -> fmt::Result {
+        write!(formatter, "{{")?;
+
+        for (index, &count) in self.buckets.iter().enumerate() {
+            if count > 0 {
+                let size = Size::new::<Virt>(1 << index);
+                write!(formatter, " <={size}: {count},")?;
+            }
+        }
+
+        write!(
+            formatter,
+            " cached: {}, uncached: {} }}",
+            self.cached, self.uncached,
+        )
+    }
+}
+
+/// Предназначена для конкурентного обновления гистограммы размеров запросов [`Histogram`].
+///
+/// В отличие от [`AtomicInfo`] не гарантирует согласованности полей между собой,
+/// так как каждое из них само по себе является независимым счётчиком.
+#[derive(Debug)]
+pub struct AtomicHistogram {
+    /// Количество запросов в каждой из корзин.
+    buckets: [AtomicUsize; HISTOGRAM_BUCKET_COUNT],
+
+    /// Количество запросов, обслуженных из кэша выделяемых блоков.
+    cached: AtomicUsize,
+
+    /// Количество запросов, обслуженных нижележащими аллокаторами.
+    uncached: AtomicUsize,
+}
+
+impl AtomicHistogram {
+    /// Возвращает [`AtomicHistogram`], заполненную нулями.
+    pub const fn new() -> Self {
+        Self {
+            buckets: [const { AtomicUsize::new(0) }; HISTOGRAM_BUCKET_COUNT],
+            cached: AtomicUsize::new(0),
+            uncached: AtomicUsize::new(0),
+        }
+    }
+
+    /// Учитывает запрос на выделение `size` байт.
+    /// Если `cached` равен `true`, он был обслужен из кэша выделяемых блоков.
+    pub fn allocation(
+        &self,
+        size: usize,
+        cached: bool,
+    ) {
+        self.buckets[Histogram::bucket_index(size)].fetch_add(1, Ordering::Relaxed);
+
+        let counter = if cached {
+            &self.cached
+        } else {
+            &self.uncached
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Загрузить гистограмму [`Histogram`] из атомарного хранилища [`AtomicHistogram`].
+    pub fn load(&self) -> Histogram {
+        Histogram {
+            buckets: self.buckets.each_ref().map(|bucket| bucket.load(Ordering::Relaxed)),
+            cached: self.cached.load(Ordering::Relaxed),
+            uncached: self.uncached.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl Default for AtomicHistogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Количество корзин в гистограмме размеров запросов [`Histogram`].
+/// Достаточно для любого размера, который может быть задан в [`core::alloc::Layout`].
+pub const HISTOGRAM_BUCKET_COUNT: usize = usize::BITS as usize;
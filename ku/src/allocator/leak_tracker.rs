@@ -0,0 +1,252 @@
+use core::{
+    alloc::{
+        GlobalAlloc,
+        Layout,
+    },
+    fmt,
+};
+
+use crate::{
+    backtrace::Backtrace,
+    memory::{
+        Size,
+        Virt,
+    },
+    sync::Spinlock,
+};
+
+/// Обёртка над аллокатором `T`, которая запоминает точки выделения ещё не освобождённых блоков.
+/// Предназначена для отладки утечек памяти.
+///
+/// Хранит не более `N` живых выделений в таблице фиксированного размера,
+/// чтобы самой не обращаться к аллокатору.
+/// Живые выделения, не поместившиеся в таблицу, только подсчитываются, см. [`LeakTracker::lost()`].
+/// Поиск в таблице линейный, поэтому обёртка заметно замедляет выделение и освобождение памяти.
+#[derive(Debug)]
+pub struct LeakTracker<T, const N: usize> {
+    /// Таблица живых выделений.
+    allocations: Spinlock<Allocations<N>>,
+
+    /// Обёрнутый аллокатор.
+    allocator: T,
+}
+
+impl<T, const N: usize> LeakTracker<T, N> {
+    /// Оборачивает аллокатор `allocator`.
+    pub const fn new(allocator: T) -> Self {
+        Self {
+            allocations: Spinlock::new(Allocations::new()),
+            allocator,
+        }
+    }
+
+    /// Обёрнутый аллокатор.
+    pub fn allocator(&self) -> &T {
+        &self.allocator
+    }
+
+    /// Возвращает итератор по ещё не освобождённым выделениям.
+    ///
+    /// Блокировка таблицы не удерживается между итерациями,
+    /// так что итератор можно использовать для журналирования,
+    /// даже если оно само выделяет память.
+    pub fn leaks(&self) -> impl Iterator<Item = Leak> + '_ {
+        (0 .. N).filter_map(|index| self.allocations.lock().slots[index])
+    }
+
+    /// Количество живых выделений, которые не поместились в таблицу и поэтому не отслеживаются.
+    pub fn lost(&self) -> usize {
+        self.allocations.lock().lost
+    }
+
+    /// Запоминает выделение блока `ptr` размера `size` с трассировкой стека `backtrace`.
+    fn track(
+        &self,
+        ptr: *mut u8,
+        size: usize,
+        backtrace: Option<Backtrace>,
+    ) {
+        if ptr.is_null() {
+            return;
+        }
+
+        let mut leak = Leak {
+            address: Virt::from_ptr(ptr),
+            backtrace: [Virt::default(); LEAK_BACKTRACE_DEPTH],
+            depth: 0,
+            size,
+        };
+
+        for (return_address, stack_frame) in
+            leak.backtrace.iter_mut().zip(backtrace.into_iter().flatten())
+        {
+            *return_address = stack_frame.return_address();
+            leak.depth += 1;
+        }
+
+        self.allocations.lock().insert(leak);
+    }
+
+    /// Забывает выделение блока `ptr`.
+    fn untrack(
+        &self,
+        ptr: *mut u8,
+    ) {
+        self.allocations.lock().remove(Virt::from_ptr(ptr));
+    }
+}
+
+unsafe impl<T: GlobalAlloc, const N: usize> GlobalAlloc for LeakTracker<T, N> {
+    unsafe fn alloc(
+        &self,
+        layout: Layout,
+    ) -> *mut u8 {
+        let backtrace = Backtrace::current().ok();
+        let ptr = unsafe { self.allocator.alloc(layout) };
+        self.track(ptr, layout.size(), backtrace);
+        ptr
+    }
+
+    unsafe fn dealloc(
+        &self,
+        ptr: *mut u8,
+        layout: Layout,
+    ) {
+        self.untrack(ptr);
+        unsafe {
+            self.allocator.dealloc(ptr, layout);
+        }
+    }
+
+    unsafe fn alloc_zeroed(
+        &self,
+        layout: Layout,
+    ) -> *mut u8 {
+        let backtrace = Backtrace::current().ok();
+        let ptr = unsafe { self.allocator.alloc_zeroed(layout) };
+        self.track(ptr, layout.size(), backtrace);
+        ptr
+    }
+
+    unsafe fn realloc(
+        &self,
+        ptr: *mut u8,
+        layout: Layout,
+        new_size: usize,
+    ) -> *mut u8 {
+        let backtrace = Backtrace::current().ok();
+        let new_ptr = unsafe { self.allocator.realloc(ptr, layout, new_size) };
+        if !new_ptr.is_null() {
+            self.untrack(ptr);
+            self.track(new_ptr, new_size, backtrace);
+        }
+        new_ptr
+    }
+}
+
+/// Ещё не освобождённое выделение памяти.
+#[derive(Clone, Copy, Debug)]
+pub struct Leak {
+    /// Адрес выделенного блока.
+    address: Virt,
+
+    /// Адреса возврата самых вложенных стековых фреймов в точке выделения.
+    backtrace: [Virt; LEAK_BACKTRACE_DEPTH],
+
+    /// Количество заполненных элементов в [`Leak::backtrace`].
+    depth: usize,
+
+    /// Размер выделенного блока.
+    size: usize,
+}
+
+impl Leak {
+    /// Адрес выделенного блока.
+    pub fn address(&self) -> Virt {
+        self.address
+    }
+
+    /// Адреса возврата самых вложенных стековых фреймов в точке выделения.
+    pub fn backtrace(&self) -> &[Virt] {
+        &self.backtrace[.. self.depth]
+    }
+
+    /// Размер выделенного блока.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+}
+
+impl fmt::Display for Leak {
+    fn fmt(
+        &self,
+        formatter: &mut fmt::Formatter,
+    ) -> fmt::Result {
+        write!(
+            formatter,
+            "{{ address: {}, size: {}, backtrace: [",
+            self.address,
+            Size::new::<Virt>(self.size),
+        )?;
+
+        let mut separator = "";
+        for return_address in self.backtrace() {
+            write!(formatter, "{separator}{:#X}", return_address.into_usize())?;
+            separator = " ";
+        }
+
+        write!(formatter, "] }}")
+    }
+}
+
+/// Таблица живых выделений [`LeakTracker`].
+#[derive(Debug)]
+struct Allocations<const N: usize> {
+    /// Количество живых выделений, которые не поместились в таблицу.
+    lost: usize,
+
+    /// Ячейки таблицы.
+    slots: [Option<Leak>; N],
+}
+
+impl<const N: usize> Allocations<N> {
+    /// Создаёт пустую таблицу.
+    const fn new() -> Self {
+        Self {
+            lost: 0,
+            slots: [None; N],
+        }
+    }
+
+    /// Запоминает выделение `leak`.
+    fn insert(
+        &mut self,
+        leak: Leak,
+    ) {
+        if let Some(slot) = self.slots.iter_mut().find(|slot| slot.is_none()) {
+            *slot = Some(leak);
+        } else {
+            self.lost += 1;
+        }
+    }
+
+    /// Забывает выделение блока по адресу `address`.
+    /// Если оно не отслеживалось, значит оно не поместилось в таблицу.
+    fn remove(
+        &mut self,
+        address: Virt,
+    ) {
+        if let Some(slot) = self
+            .slots
+            .iter_mut()
+            .find(|slot| slot.is_some_and(|leak| leak.address == address))
+        {
+            *slot = None;
+        } else {
+            self.lost = self.lost.saturating_sub(1);
+        }
+    }
+}
+
+/// Количество запоминаемых [`LeakTracker`] стековых фреймов для каждого выделения.
+pub const LEAK_BACKTRACE_DEPTH: usize = 8;
@@ -24,9 +24,15 @@ mod dispatcher;
 /// Аллокатор, выделяющий память блоками одинакового размера.
 mod fixed_size;
 
+/// Обёртка над аллокатором [`LeakTracker`], отслеживающая утечки памяти.
+mod leak_tracker;
+
 /// Вспомогательная структура [`Quarry`] для [`FixedSizeAllocator`].
 mod quarry;
 
+/// Обёртка над аллокатором [`RetryAllocator`], повторяющая запросы при нехватке памяти.
+mod retry;
+
 pub use big::{
     BigAllocator,
     BigAllocatorGuard,
@@ -47,9 +53,18 @@ pub use dry::{
     Initialize,
 };
 pub use info::{
+    AtomicHistogram,
     AtomicInfo,
+    HISTOGRAM_BUCKET_COUNT,
+    Histogram,
     Info,
 };
+pub use leak_tracker::{
+    LEAK_BACKTRACE_DEPTH,
+    Leak,
+    LeakTracker,
+};
+pub use retry::RetryAllocator;
 
 use cache::CLIP_SIZE;
 use fixed_size::FixedSizeAllocator;
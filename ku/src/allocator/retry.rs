@@ -0,0 +1,119 @@
+use core::{
+    alloc::{
+        GlobalAlloc,
+        Layout,
+    },
+    sync::atomic::{
+        AtomicBool,
+        Ordering,
+    },
+};
+
+use crate::sync::Spinlock;
+
+/// Обёртка над аллокатором `T`, которая при нехватке памяти
+/// вызывает заданную пользователем функцию и повторяет запрос один раз.
+///
+/// Функция может, например, освободить кэши или уступить процессор,
+/// чтобы другие процессы успели освободить память.
+/// Если функция не задана, неудачный запрос сразу возвращает нулевой указатель,
+/// как и у обёрнутого аллокатора.
+///
+/// Функция может сама выделять память, но при повторной нехватке памяти внутри неё
+/// рекурсивно уже не вызывается.
+#[derive(Debug)]
+pub struct RetryAllocator<T> {
+    /// Обёрнутый аллокатор.
+    allocator: T,
+
+    /// Функция, которая вызывается перед повтором неудавшегося запроса.
+    hook: Spinlock<Option<fn()>>,
+
+    /// Функция [`RetryAllocator::hook`] выполняется в данный момент.
+    retrying: AtomicBool,
+}
+
+impl<T> RetryAllocator<T> {
+    /// Оборачивает аллокатор `allocator`.
+    pub const fn new(allocator: T) -> Self {
+        Self {
+            allocator,
+            hook: Spinlock::new(None),
+            retrying: AtomicBool::new(false),
+        }
+    }
+
+    /// Обёрнутый аллокатор.
+    pub fn allocator(&self) -> &T {
+        &self.allocator
+    }
+
+    /// Задаёт функцию `hook`, которая вызывается перед повтором запроса,
+    /// не удовлетворённого из-за нехватки памяти.
+    /// Если `hook` равен `None`, запросы не повторяются.
+    pub fn set_retry_hook(
+        &self,
+        hook: Option<fn()>,
+    ) {
+        *self.hook.lock() = hook;
+    }
+
+    /// Выполняет запрос `request`, а если он вернул нулевой указатель,
+    /// вызывает [`RetryAllocator::hook`] и повторяет запрос.
+    fn with_retry(
+        &self,
+        request: impl Fn() -> *mut u8,
+    ) -> *mut u8 {
+        let ptr = request();
+        if !ptr.is_null() {
+            return ptr;
+        }
+
+        let Some(hook) = *self.hook.lock() else {
+            return ptr;
+        };
+
+        if self.retrying.swap(true, Ordering::Acquire) {
+            return ptr;
+        }
+        hook();
+        self.retrying.store(false, Ordering::Release);
+
+        request()
+    }
+}
+
+unsafe impl<T: GlobalAlloc> GlobalAlloc for RetryAllocator<T> {
+    unsafe fn alloc(
+        &self,
+        layout: Layout,
+    ) -> *mut u8 {
+        self.with_retry(|| unsafe { self.allocator.alloc(layout) })
+    }
+
+    unsafe fn dealloc(
+        &self,
+        ptr: *mut u8,
+        layout: Layout,
+    ) {
+        unsafe {
+            self.allocator.dealloc(ptr, layout);
+        }
+    }
+
+    unsafe fn alloc_zeroed(
+        &self,
+        layout: Layout,
+    ) -> *mut u8 {
+        self.with_retry(|| unsafe { self.allocator.alloc_zeroed(layout) })
+    }
+
+    unsafe fn realloc(
+        &self,
+        ptr: *mut u8,
+        layout: Layout,
+        new_size: usize,
+    ) -> *mut u8 {
+        self.with_retry(|| unsafe { self.allocator.realloc(ptr, layout, new_size) })
+    }
+}
@@ -9,11 +9,15 @@ use core::{
 };
 
 use derive_more::Display;
+use spin::Once;
 
 #[cfg(miri)]
 use crate::error::Error::Unimplemented;
 use crate::{
-    error::Result,
+    error::{
+        Error::Overflow,
+        Result,
+    },
     memory::{
         Block,
         Page,
@@ -62,6 +66,10 @@ pub use callsite::Callsite;
 ///         "--codegen", "relocation-model=dynamic-no-pic",
 ///     ]
 /// ```
+///
+/// Если таблица символов зарегистрирована функцией [`set_symbols()`],
+/// то [`fmt::Debug`] печатает рядом с каждым адресом возврата `имя+смещение`
+/// и `llvm-symbolizer` для беглого взгляда не нужен.
 #[derive(Clone, Copy, Default)]
 pub struct Backtrace {
     /// Адрес, ниже которого не может быть расположен внешний фрейм, --- стек растёт вниз.
@@ -69,11 +77,16 @@ pub struct Backtrace {
     /// при поиске конца списка стековых фреймов.
     lower_limit: Virt,
 
+    /// Сколько ещё стековых фреймов может выдать итератор.
+    /// Ограничивает длину печатаемой трассировки, например, при глубокой рекурсии.
+    max_frames: usize,
+
     /// Стек, знание которого позволяет найти конец списка стековых фреймов.
     /// Радикально снижает вероятность некорректного обращения к памяти
     /// при поиске конца списка стековых фреймов.
-    /// Если не известен, устанавливается равным одной странице памяти,
-    /// в которую указывает регистр `RBP`.
+    /// Если не известен, устанавливается равным окну из
+    /// [`Backtrace::DEFAULT_STACK_WINDOW`] байт над регистром `RBP`,
+    /// округлённому до границ страниц.
     stack: Block<Virt>,
 
     /// Текущий стековый фрейм.
@@ -81,6 +94,17 @@ pub struct Backtrace {
 }
 
 impl Backtrace {
+    /// Размер окна стека над регистром `RBP` в байтах, которое используется,
+    /// если стек не известен.
+    ///
+    /// С опцией `conservative-backtraces` окно занимает только страницу,
+    /// в которую указывает регистр `RBP`.
+    pub const DEFAULT_STACK_WINDOW: usize = if cfg!(feature = "conservative-backtraces") {
+        1
+    } else {
+        32 * Page::SIZE
+    };
+
     /// Возвращает трассировку стека по значениям регистров `rbp` и `rsp`.
     ///
     /// Мы указываем компилятору выполнить встраивание этой функции,
@@ -91,7 +115,22 @@ impl Backtrace {
         rbp: usize,
         rsp: usize,
     ) -> Result<Self> {
-        Self::new_impl(rbp, rsp, StackFrame::new(rbp)?)
+        Self::with_window(rbp, rsp, Self::DEFAULT_STACK_WINDOW)
+    }
+
+    /// Возвращает трассировку стека по значениям регистров `rbp` и `rsp`.
+    /// Стек считается занимающим `window` байт над `rbp`, округлённых до границ страниц.
+    ///
+    /// Мы указываем компилятору выполнить встраивание этой функции,
+    /// чтобы не порождать дополнительный стековый фрейм под её вызов и не захламлять трассировку стека.
+    /// В результате, функция вызвавшая [`Backtrace::with_window()`], в него не попадёт.
+    #[inline(always)]
+    pub fn with_window(
+        rbp: usize,
+        rsp: usize,
+        window: usize,
+    ) -> Result<Self> {
+        Self::new_impl(rbp, rsp, window, StackFrame::new(rbp)?)
     }
 
     /// Возвращает ошибку, так как для получения трассировки стека нужен ассемблер.
@@ -137,7 +176,12 @@ impl Backtrace {
             return_address: context.rip().into_usize(),
         };
 
-        Self::new_impl(rbp, context.rsp().into_usize(), stack_frame)
+        Self::new_impl(
+            rbp,
+            context.rsp().into_usize(),
+            Self::DEFAULT_STACK_WINDOW,
+            stack_frame,
+        )
     }
 
     /// Возвращает трассировку текущего стека.
@@ -154,8 +198,18 @@ impl Backtrace {
         Ok(backtrace)
     }
 
+    /// Ограничивает трассировку стека `max_frames` самыми вложенными фреймами.
+    /// Например, чтобы паника при глубокой рекурсии не печатала сотни фреймов.
+    pub fn with_limit(
+        mut self,
+        max_frames: usize,
+    ) -> Self {
+        self.max_frames = self.max_frames.min(max_frames);
+        self
+    }
+
     /// Возвращает трассировку стека по значениям регистров `rbp` и `rsp`,
-    /// и с самым вложенным фреймом `stack_frame`.
+    /// с окном стека в `window` байт и с самым вложенным фреймом `stack_frame`.
     ///
     /// Мы указываем компилятору выполнить встраивание этой функции,
     /// чтобы не порождать дополнительный стековый фрейм под её вызов и не захламлять трассировку стека.
@@ -164,18 +218,16 @@ impl Backtrace {
     fn new_impl(
         rbp: usize,
         rsp: usize,
+        window: usize,
         stack_frame: StackFrame,
     ) -> Result<Self> {
         let lower_limit = Virt::new(rsp)?;
-        let stack_size = if cfg!(feature = "conservative-backtraces") {
-            1
-        } else {
-            32 * Page::SIZE
-        };
-        let stack = Block::from_index(rbp, rbp + stack_size)?.enclosing().into();
+        let stack_end = rbp.checked_add(window).ok_or(Overflow)?;
+        let stack = Block::from_index(rbp, stack_end)?.enclosing().into();
 
         Ok(Self {
             lower_limit,
+            max_frames: usize::MAX,
             stack,
             stack_frame,
         })
@@ -186,9 +238,11 @@ impl Iterator for Backtrace {
     type Item = StackFrame;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.stack_frame.outer == 0 {
+        if self.stack_frame.outer == 0 || self.max_frames == 0 {
             None
         } else {
+            self.max_frames -= 1;
+
             let next =
                 self.stack_frame.outer(&mut self.lower_limit, self.stack).unwrap_or_default();
 
@@ -206,6 +260,10 @@ impl fmt::Debug for Backtrace {
 
         for stack_frame in *self {
             write!(formatter, "\n  {stack_frame}")?;
+
+            if let Some((name, offset)) = symbolize(stack_frame.return_address) {
+                write!(formatter, " {name}+{offset:#X}")?;
+            }
         }
 
         Ok(())
@@ -324,6 +382,62 @@ impl StackFrame {
     }
 }
 
+/// Таблица символов для печати имён функций в трассировках стека.
+enum Symbols {
+    /// Пары из адреса начала функции и её имени, отсортированные по адресу.
+    Pairs(&'static [(usize, &'static str)]),
+}
+
+impl Symbols {
+    /// Возвращает адрес начала и имя последнего символа, который начинается не позже `address`.
+    fn find(
+        &self,
+        address: usize,
+    ) -> Option<(usize, &'static str)> {
+        match *self {
+            Symbols::Pairs(symbols) => {
+                let index = symbols.partition_point(|&(start, _)| start <= address);
+                symbols.get(index.checked_sub(1)?).copied()
+            },
+        }
+    }
+
+    /// Возвращает имя функции, которой принадлежит адрес возврата `return_address`,
+    /// и смещение адреса возврата от её начала.
+    /// Если адрес лежит до первого символа, возвращает `None`.
+    ///
+    /// Адрес возврата указывает на инструкцию после вызова.
+    /// Если вызов был последней инструкцией функции, то это уже следующая функция.
+    /// Поэтому ищется функция, содержащая предыдущий байт.
+    fn symbolize(
+        &self,
+        return_address: usize,
+    ) -> Option<(&'static str, usize)> {
+        let call_address = return_address.checked_sub(1)?;
+        let (address, name) = self.find(call_address)?;
+
+        Some((name, return_address - address))
+    }
+}
+
+/// Регистрирует таблицу символов `symbols` --- пары из адреса начала функции и её имени,
+/// отсортированные по адресу.
+/// После этого [`Backtrace`] печатает в [`fmt::Debug`] имена функций рядом с адресами возврата.
+///
+/// Таблица регистрируется один раз, повторные вызовы ничего не меняют.
+pub fn set_symbols(symbols: &'static [(usize, &'static str)]) {
+    debug_assert!(symbols.is_sorted_by_key(|&(address, _)| address));
+
+    SYMBOLS.call_once(|| Symbols::Pairs(symbols));
+}
+
+/// Возвращает имя функции, которой принадлежит адрес возврата `return_address`,
+/// и смещение адреса возврата от её начала, см. [`Symbols::symbolize()`].
+/// Если таблица символов не зарегистрирована, возвращает `None`.
+fn symbolize(return_address: usize) -> Option<(&'static str, usize)> {
+    SYMBOLS.get()?.symbolize(return_address)
+}
+
 /// Возвращает `true`, так как под
 /// [Miri](https://github.com/rust-lang/miri)
 /// не поддерживается получение значения регистра `RSP`.
@@ -379,6 +493,9 @@ fn rbp() -> usize {
     rbp
 }
 
+/// Таблица символов, зарегистрированная функцией [`set_symbols()`].
+static SYMBOLS: Once<Symbols> = Once::new();
+
 #[cfg(all(test, not(feature = "conservative-backtraces")))]
 mod test {
     use core::hint;
@@ -387,7 +504,10 @@ mod test {
 
     use crate::memory::Virt;
 
-    use super::Backtrace;
+    use super::{
+        Backtrace,
+        Symbols,
+    };
 
     #[derive(PartialEq, Eq, Debug, Default)]
     struct BacktraceStats {
@@ -436,6 +556,21 @@ mod test {
         run_at_depth(depth, capture_backtrace_fingerprint, backtrace_stats)
     }
 
+    #[allow(clippy::too_many_arguments)]
+    #[with_sentinel_frame]
+    fn write_backtrace_stats_with_stack_args_to(
+        depth: usize,
+        _a2: usize,
+        _a3: usize,
+        _a4: usize,
+        _a5: usize,
+        _a6: usize,
+        _a7: usize,
+        backtrace_stats: &mut BacktraceStats,
+    ) {
+        run_at_depth(depth, capture_backtrace_fingerprint, backtrace_stats)
+    }
+
     fn get_backtrace_stats(depth: usize) -> BacktraceStats {
         let mut stats = BacktraceStats::default();
         write_backtrace_stats_to(depth, &mut stats);
@@ -460,4 +595,63 @@ mod test {
             }
         }
     }
+
+    #[test]
+    fn sentinel_frame_with_stack_arguments() {
+        for backtrace_depth in 0 .. 10 {
+            let mut stats = BacktraceStats::default();
+            write_backtrace_stats_with_stack_args_to(backtrace_depth, 2, 3, 4, 5, 6, 7, &mut stats);
+
+            assert!(stats.found_sentinel);
+            assert!(stats.stopped_by_sentinel);
+            assert!(stats.backtrace_depth >= backtrace_depth);
+            assert!(stats.backtrace_depth < backtrace_depth + 7);
+        }
+    }
+
+    #[test]
+    fn limit() {
+        let (full, limited) = run_at_depth(
+            2 * LIMIT,
+            |_| {
+                let backtrace = Backtrace::current().unwrap();
+                (backtrace.count(), backtrace.with_limit(LIMIT).count())
+            },
+            (),
+        );
+
+        assert!(full > LIMIT);
+        assert_eq!(limited, LIMIT);
+
+        const LIMIT: usize = 5;
+    }
+
+    #[test]
+    fn symbols() {
+        static SYMBOLS: [(usize, &str); 3] = [
+            (0x1000, "first"),
+            (0x1010, "<second as core::fmt::Debug>::fmt"),
+            (0x1100, "third"),
+        ];
+
+        let symbols = Symbols::Pairs(&SYMBOLS);
+
+        assert_eq!(symbols.find(0xFFF), None);
+        assert_eq!(symbols.find(0x1000), Some((0x1000, "first")));
+        assert_eq!(
+            symbols.find(0x10FF),
+            Some((0x1010, "<second as core::fmt::Debug>::fmt")),
+        );
+        assert_eq!(symbols.find(0x1234), Some((0x1100, "third")));
+
+        assert_eq!(symbols.symbolize(0x0), None);
+        assert_eq!(symbols.symbolize(0x1000), None);
+        assert_eq!(symbols.symbolize(0x1001), Some(("first", 0x1)));
+        assert_eq!(symbols.symbolize(0x1010), Some(("first", 0x10)));
+        assert_eq!(
+            symbols.symbolize(0x1011),
+            Some(("<second as core::fmt::Debug>::fmt", 0x1)),
+        );
+        assert_eq!(symbols.symbolize(0x1234), Some(("third", 0x134)));
+    }
 }
@@ -136,6 +136,32 @@ impl<'a> Bitmap<'a> {
         self.free += 1;
     }
 
+    /// Помечает занятым элемент `number`, если он свободен.
+    /// Возвращает `true`, если элемент был свободен и теперь выделен,
+    /// и `false`, если он уже был занят.
+    ///
+    /// Позволяет выделять элементы подряд,
+    /// например для размещения данных в непрерывном диапазоне блоков.
+    ///
+    /// # Panics
+    ///
+    /// Паникует, если:
+    ///   - Значение `number` больше или равно размеру --- [`Bitmap::len()`].
+    pub fn try_allocate(
+        &mut self,
+        number: usize,
+    ) -> bool {
+        assert!(number < self.len());
+
+        if self.is_free(number) {
+            self.bitmap[number / Self::BITS_PER_ENTRY] |= 1 << (number % Self::BITS_PER_ENTRY);
+            self.free -= 1;
+            true
+        } else {
+            false
+        }
+    }
+
     // ANCHOR: allocate
     /// Находит в битовой карте свободный элемент и помечает его занятым.
     /// Возвращает номер выделенного элемента или [`None`], если свободных элементов не осталось.
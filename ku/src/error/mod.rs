@@ -55,6 +55,9 @@ pub enum Error {
     /// Заданный путь содержит объект, не являющийся директорией.
     NotDirectory,
 
+    /// Удаляемая директория не пуста.
+    NotEmpty,
+
     /// Заданный путь указывает на объект, не являющийся файлом.
     NotFile,
 
@@ -19,6 +19,8 @@ use super::{
     process::Pid,
     time::{
         AtomicCorrelationInterval,
+        FastClock,
+        TscOffsets,
         pit8254,
         rtc,
     },
@@ -129,6 +131,10 @@ impl ProcessInfo {
 #[derive(Debug, Default)]
 #[repr(C, align(4096))]
 pub struct SystemInfo {
+    /// Быстрые часы реального времени,
+    /// которые ядро калибрует по [`SystemInfo::rtc`].
+    clock: FastClock,
+
     /// Счётчик тиков PIT.
     pit: AtomicCorrelationInterval<{ pit8254::TICKS_PER_SECOND as i64 }>,
 
@@ -137,20 +143,35 @@ pub struct SystemInfo {
     /// Позволяют в пространстве пользователя узнать текущее время
     /// с помощью функций модуля [`ku::time`].
     rtc: AtomicCorrelationInterval<{ rtc::TICKS_PER_SECOND }>,
+
+    /// Поправки к счётчикам тактов процессоров,
+    /// которые приводят их к счётчику Bootstrap Processor.
+    tsc_offsets: TscOffsets,
 }
 
 const_assert_eq!(mem::align_of::<SystemInfo>(), Page::SIZE);
 const_assert_eq!(mem::size_of::<SystemInfo>() % Page::SIZE, 0);
+// Ядро отображает `SystemInfo` в пространство пользователя ровно одной страницей.
+const_assert_eq!(mem::size_of::<SystemInfo>(), Page::SIZE);
 
 impl SystemInfo {
     /// Инициализирует [`SystemInfo`].
     pub const fn new() -> Self {
         Self {
+            clock: FastClock::new(),
             pit: AtomicCorrelationInterval::new(),
             rtc: AtomicCorrelationInterval::new(),
+            tsc_offsets: TscOffsets::new(),
         }
     }
 
+    /// Быстрые часы реального времени.
+    /// Позволяют в пространстве пользователя узнать текущее время без системных вызовов,
+    /// см. [`ku::time::now()`].
+    pub fn clock(&self) -> &FastClock {
+        &self.clock
+    }
+
     /// Счётчик тиков PIT.
     pub fn pit(&self) -> &AtomicCorrelationInterval<{ pit8254::TICKS_PER_SECOND as i64 }> {
         &self.pit
@@ -163,6 +184,14 @@ impl SystemInfo {
     pub fn rtc(&self) -> &AtomicCorrelationInterval<{ rtc::TICKS_PER_SECOND }> {
         &self.rtc
     }
+
+    /// Поправки к счётчикам тактов процессоров,
+    /// которые приводят их к счётчику Bootstrap Processor.
+    /// Позволяют сравнивать показания счётчиков, снятые на разных процессорах,
+    /// см. [`ku::time::Tsc::synchronized()`].
+    pub fn tsc_offsets(&self) -> &TscOffsets {
+        &self.tsc_offsets
+    }
 }
 
 /// Информация о текущем процессе.
@@ -173,8 +202,13 @@ pub fn process_info() -> &'static mut ProcessInfo {
 
 /// Общая информации о системе.
 pub fn system_info() -> &'static SystemInfo {
+    try_system_info().expect("the system info is not initialized properly")
+}
+
+/// Общая информации о системе, если указатель на неё уже установлен.
+pub(crate) fn try_system_info() -> Option<&'static SystemInfo> {
     let system_info = SYSTEM_INFO.load(Ordering::Relaxed);
-    unsafe { system_info.as_ref().expect("the system info is not initialized properly") }
+    unsafe { system_info.as_ref() }
 }
 
 /// Устанавливает указатель на информации о текущем процессе.
@@ -573,6 +573,9 @@ pub struct RingBufferStats {
 /// Ошибки, которые могут возникать при работе с [`RingBuffer`].
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum Error {
+    /// Другой конец канала закрыт --- из него больше никто не прочитает записанные данные.
+    Closed,
+
     /// Буфер транзакции переполнен.
     Overflow {
         /// Место, остававшееся в буфере на момент старта транзакции.
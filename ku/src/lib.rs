@@ -104,10 +104,13 @@ pub use time::{
     Tsc,
     TscDuration,
     delay,
+    has_rdtscp,
+    monotonic,
     now,
     now_ms,
     timer,
     tsc,
+    tscp,
 };
 
 /// Останавливает процессор инструкцией
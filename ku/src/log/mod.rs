@@ -3,6 +3,10 @@ use core::{
     fmt,
     fmt::Write,
     result,
+    sync::atomic::{
+        AtomicU8,
+        Ordering,
+    },
 };
 
 use heapless::String;
@@ -31,7 +35,10 @@ use tracing::{
         Record,
     },
 };
-use tracing_core::span::Current;
+use tracing_core::{
+    collect::Interest,
+    span::Current,
+};
 
 use super::{
     RingBufferWriteTx,
@@ -42,6 +49,7 @@ use super::{
     },
     pipe,
     time::{
+        self,
         Tsc,
         datetime,
     },
@@ -420,6 +428,62 @@ impl<const N: usize> Write for PlanB<N> {
     }
 }
 
+/// Вспомогательная обёртка для печати среза байт в шестнадцатеричном виде,
+/// например `DE AD BE EF`.
+pub struct HexBytes<'a>(&'a [u8]);
+
+impl<'a> HexBytes<'a> {
+    /// Возвращает обёртку для печати среза байт `bytes` в шестнадцатеричном виде.
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl fmt::Display for HexBytes<'_> {
+    fn fmt(
+        &self,
+        formatter: &mut fmt::Formatter,
+    ) -> fmt::Result {
+        let mut separator = "";
+
+        for byte in self.0 {
+            write!(formatter, "{separator}{byte:02X}")?;
+            separator = " ";
+        }
+
+        Ok(())
+    }
+}
+
+/// Уровень журналирования, который можно атомарно менять во время работы.
+///
+/// Каждое сообщение сверяется с уровнем ровно один раз --- в [`Collect::enabled()`].
+/// Поэтому смена уровня не разрывает уже прошедшие проверку сообщения:
+/// они записываются целиком, а следующие сообщения проверяются уже по новому уровню.
+#[derive(Debug)]
+pub struct AtomicLevel(AtomicU8);
+
+impl AtomicLevel {
+    /// Создаёт атомарный уровень журналирования, равный `level`.
+    pub const fn new(level: Level) -> Self {
+        Self(AtomicU8::new(level_into_symbol(&level) as u8))
+    }
+
+    /// Возвращает текущий уровень журналирования.
+    pub fn load(&self) -> Level {
+        let symbol = char::from(self.0.load(Ordering::Relaxed));
+        level_try_from_symbol(symbol).expect("only valid levels are stored")
+    }
+
+    /// Устанавливает уровень журналирования `level`.
+    pub fn store(
+        &self,
+        level: Level,
+    ) {
+        self.0.store(level_into_symbol(&level) as u8, Ordering::Relaxed);
+    }
+}
+
 /// Сборщик сообщений журнала.
 pub struct LogCollector {
     /// Функция сброса буфера накопленных сообщений.
@@ -427,7 +491,9 @@ pub struct LogCollector {
     flush: Cell<Option<fn()>>,
 
     /// Текущий уровень журналирования.
-    level: Level,
+    /// Сообщения более подробных уровней отбрасываются до сериализации,
+    /// не занимая место в [`ku::info::ProcessInfo::log()`].
+    level: AtomicLevel,
 
     /// Количество потерянных сообщений с момента предыдущего служебного сообщения о таких потерях.
     lost_recently: Cell<usize>,
@@ -459,7 +525,7 @@ impl LogCollector {
     const fn new(level: Level) -> Self {
         LogCollector {
             flush: Cell::new(None),
-            level,
+            level: AtomicLevel::new(level),
             lost_recently: Cell::new(0),
             lost_totally: Cell::new(0),
             plan_b_failures: Cell::new(0),
@@ -477,6 +543,20 @@ impl LogCollector {
         self.flush.set(Some(flush));
     }
 
+    /// Возвращает текущий уровень журналирования.
+    pub fn level(&self) -> Level {
+        self.level.load()
+    }
+
+    /// Устанавливает уровень журналирования `level`.
+    /// Записываются только сообщения с уровнем `level` и выше.
+    pub fn set_level(
+        &self,
+        level: Level,
+    ) {
+        self.level.store(level);
+    }
+
     /// Возвращает `true` пока выполняется операция журналирования.
     ///
     /// Используется при обработки паник,
@@ -583,7 +663,7 @@ impl Collect for LogCollector {
         &self,
         event: &Event<'_>,
     ) {
-        let timestamp = Tsc::now();
+        let timestamp = time::monotonic();
 
         self.recursion.update(|x| x + 1);
         let recursion = self.recursion.get();
@@ -636,11 +716,20 @@ impl Collect for LogCollector {
     ) {
     }
 
+    /// Не даёт [`tracing`] запомнить результат [`Collect::enabled()`] для точки вызова,
+    /// так как уровень журналирования может поменяться.
+    fn register_callsite(
+        &self,
+        _metadata: &'static Metadata<'static>,
+    ) -> Interest {
+        Interest::sometimes()
+    }
+
     fn enabled(
         &self,
         metadata: &Metadata<'_>,
     ) -> bool {
-        metadata.level() <= &self.level
+        metadata.level() <= &self.level.load()
     }
 
     fn enter(
@@ -1,10 +1,16 @@
+use alloc::vec::Vec;
 use core::{
     cmp::{
         self,
         Ordering,
     },
-    mem::MaybeUninit,
+    fmt,
+    mem::{
+        self,
+        MaybeUninit,
+    },
     ops::Range,
+    str,
 };
 
 use derive_more::Display;
@@ -65,6 +71,12 @@ use crate::error::Error;
 /// См.
 /// [System V Application Binary Interface](https://refspecs.linuxbase.org/elf/gabi4+/ch5.pheader.html).
 ///
+/// Прежде чем что-либо отображать, проверяет что все загружаемые сегменты
+/// целиком лежат в области адресного пространства `allocator.dst()`,
+/// зарезервированной для пользователя.
+/// Иначе возвращает ошибку [`Error::Elf`] --- специально сформированный ELF--файл
+/// не должен иметь возможности испортить отображения ядра.
+///
 /// # Safety
 ///
 /// Вызывающая функция должна гарантировать,
@@ -76,6 +88,15 @@ pub unsafe fn load<T: BigAllocatorPair>(
     // ANCHOR_END: load
     let elf_file = ElfFile::new(file).map_err(|e| Elf(e))?;
     
+    for program_header in elf_file.program_iter() {
+        if program_header.get_type().map_err(|e| Elf(e))? == Type::Load {
+            validate_user_range(
+                |block| allocator.is_user_block(block),
+                &FileRange::try_from(program_header)?,
+            )?;
+        }
+    }
+    
     let mut loader = Loader::new(allocator, file);
     
     for program_header in elf_file.program_iter() {
@@ -98,6 +119,118 @@ pub unsafe fn load<T: BigAllocatorPair>(
     Ok(entry_point)
 }
 
+/// Возвращает точку входа
+/// [ELF--файла](https://en.wikipedia.org/wiki/Executable_and_Linkable_Format) `file`.
+pub fn entry_point(file: &[u8]) -> Result<Virt> {
+    let elf_file = ElfFile::new(file).map_err(Elf)?;
+    Virt::new_u64(elf_file.header.pt2.entry_point())
+}
+
+/// Возвращает блоки памяти, которые занимают загружаемые сегменты ELF--файла `file`,
+/// и флаги их отображения в порядке возрастания адресов.
+///
+/// Проверяет сегменты так же, как [`load()`], но ничего не отображает.
+/// В частности, требует чтобы каждый сегмент целиком лежал в области,
+/// для которой `is_user_block` возвращает `true` ---
+/// в зарезервированной для пользователя области целевого адресного пространства.
+/// Позволяет загружать страницы сегментов по требованию методом [`load_page()`].
+pub fn loadable_segments(
+    file: &[u8],
+    is_user_block: impl Fn(Block<Page>) -> bool,
+) -> Result<Vec<(Block<Virt>, PageTableFlags)>> {
+    let mut segments = Vec::new();
+    let mut prev: Option<VirtRange> = None;
+
+    for_each_loadable_segment(file, |file_range| {
+        validate_user_range(&is_user_block, &file_range)?;
+        let next = file_range.virt_range;
+        if let Some(prev) = &prev {
+            validate_order(prev, &next)?;
+        }
+        segments.push((next.memory, next.flags));
+        prev = Some(next);
+        Ok(())
+    })?;
+
+    Ok(segments)
+}
+
+/// Возвращает флаги, с которыми должна быть отображена страница `page`
+/// загружаемых сегментов ELF--файла `file`.
+/// Если страница принадлежит нескольким сегментам, их флаги объединяются,
+/// так же как это делает [`load()`].
+///
+/// Возвращает [`None`], если страница не принадлежит ни одному загружаемому сегменту.
+pub fn page_flags(
+    file: &[u8],
+    page: Page,
+) -> Result<Option<PageTableFlags>> {
+    let mut flags = None;
+
+    for_each_loadable_segment(file, |file_range| {
+        let virt_range = file_range.virt_range;
+        if virt_range.memory.enclosing().contains(page) {
+            flags = Some(flags.unwrap_or_default() | virt_range.flags);
+        }
+        Ok(())
+    })?;
+
+    Ok(flags)
+}
+
+/// Записывает в `content` содержимое страницы `page` образа процесса,
+/// загружаемого из ELF--файла `file`.
+/// Байты страницы, которые не покрыты данными сегментов из файла, заполняются нулями.
+///
+/// Возвращает ошибку [`Error::InvalidArgument`], если размер `content` не равен [`Page::SIZE`].
+pub fn load_page(
+    file: &[u8],
+    page: Page,
+    content: &mut [u8],
+) -> Result<()> {
+    if content.len() != Page::SIZE {
+        return Err(InvalidArgument);
+    }
+
+    content.fill(0);
+
+    let page_start = page.address().into_usize();
+    let page_block = Block::<Virt>::from_index(page_start, page_start + Page::SIZE)?;
+
+    for_each_loadable_segment(file, |file_range| {
+        let memory = file_range.virt_range.memory;
+        let file_size = file_range.file_range.len();
+        let file_memory = Block::<Virt>::from_index(memory.start(), memory.start() + file_size)?;
+        let intersection = file_memory.intersection(page_block);
+
+        if !intersection.is_empty() {
+            let file_start = file_range.file_range.start + (intersection.start() - memory.start());
+            let source =
+                file.get(file_start .. file_start + intersection.size()).ok_or(Overflow)?;
+            let offset = intersection.start() - page_start;
+            content[offset .. offset + source.len()].copy_from_slice(source);
+        }
+
+        Ok(())
+    })
+}
+
+/// Вызывает `f` для каждого загружаемого сегмента ELF--файла `file`.
+fn for_each_loadable_segment(
+    file: &[u8],
+    mut f: impl FnMut(FileRange) -> Result<()>,
+) -> Result<()> {
+    let elf_file = ElfFile::new(file).map_err(Elf)?;
+
+    for program_header in elf_file.program_iter() {
+        if program_header.get_type().map_err(Elf)? == Type::Load {
+            f(FileRange::try_from(program_header)?)?;
+        }
+    }
+
+    Ok(())
+}
+
 // ANCHOR: loader
 /// Состояние загрузчика ELF--файлов.
 struct Loader<'a, T: BigAllocatorPair> {
@@ -645,6 +778,160 @@ fn validate_order(
     }
 }
 
+/// Требует чтобы память сегмента `file_range` целиком лежала в области
+/// целевого адресного пространства, зарезервированной для пользователя, ---
+/// для неё `is_user_block` возвращает `true`.
+/// В противном случае, возвращает ошибку [`Error::Elf`].
+fn validate_user_range(
+    is_user_block: impl Fn(Block<Page>) -> bool,
+    file_range: &FileRange,
+) -> Result<()> {
+    let memory = file_range.virt_range.memory;
+
+    if is_user_block(memory.enclosing()) {
+        Ok(())
+    } else {
+        warn!(
+            program_header = %memory,
+            "ELF loadable program header is outside of the user address space",
+        );
+        Err(Elf("loadable program header is outside of the user address space"))
+    }
+}
+
+/// Метаданные сборки, записанные в
+/// [note--сегменты](https://refspecs.linuxbase.org/elf/gabi4+/ch5.pheader.html#note_section)
+/// [ELF--файла](https://en.wikipedia.org/wiki/Executable_and_Linkable_Format).
+///
+/// Позволяют по журналу процесса однозначно определить, какая сборка его породила.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct BuildInfo<'a> {
+    /// Идентификатор сборки из note `NT_GNU_BUILD_ID`,
+    /// который записывает линкер с опцией `--build-id`.
+    build_id: Option<&'a [u8]>,
+
+    /// Версия сборки из note `NT_VERSION` с именем [`BuildInfo::VERSION_NOTE_NAME`].
+    version: Option<&'a str>,
+}
+
+impl<'a> BuildInfo<'a> {
+    /// Извлекает метаданные сборки из note--сегментов ELF--файла `file`.
+    /// Если каких-то note в файле нет, соответствующие метаданные равны [`None`].
+    ///
+    /// Возвращает ошибки:
+    ///   - [`Error::Elf`], если `file` не является корректным ELF--файлом.
+    ///   - [`Error::InvalidArgument`], если note--сегменты выходят за пределы файла,
+    ///     повреждены или версия сборки не является корректной UTF-8 строкой.
+    pub fn new(file: &'a [u8]) -> Result<Self> {
+        let elf_file = ElfFile::new(file).map_err(Elf)?;
+        let mut build_info = Self::default();
+
+        for program_header in elf_file.program_iter() {
+            if program_header.get_type().map_err(Elf)? == Type::Note {
+                let start = size::from(program_header.offset());
+                let file_size = size::from(program_header.file_size());
+                let end = start.checked_add(file_size).ok_or(Overflow)?;
+                let notes = file.get(start .. end).ok_or(InvalidArgument)?;
+                let align = if program_header.align() == 8 {
+                    8
+                } else {
+                    4
+                };
+
+                build_info.parse_notes(notes, align)?;
+            }
+        }
+
+        Ok(build_info)
+    }
+
+    /// Идентификатор сборки, если он записан в ELF--файл.
+    pub fn build_id(&self) -> Option<&'a [u8]> {
+        self.build_id
+    }
+
+    /// Версия сборки, если она записана в ELF--файл.
+    pub fn version(&self) -> Option<&'a str> {
+        self.version
+    }
+
+    /// Разбирает последовательность note из одного note--сегмента `notes`,
+    /// поля которых выровнены на `align` байт.
+    fn parse_notes(
+        &mut self,
+        mut notes: &'a [u8],
+        align: usize,
+    ) -> Result<()> {
+        while !notes.is_empty() {
+            let header = notes.get(.. NOTE_HEADER_SIZE).ok_or(InvalidArgument)?;
+            let field = |index: usize| {
+                let bytes = header[4 * index .. 4 * (index + 1)].try_into().unwrap();
+                size::from(u32::from_le_bytes(bytes))
+            };
+            let name_size = field(0);
+            let description_size = field(1);
+            let note_type = field(2);
+
+            let name_start = NOTE_HEADER_SIZE;
+            let description_start = name_start + name_size.next_multiple_of(align);
+            let next_start = description_start + description_size.next_multiple_of(align);
+            let name = notes.get(name_start .. name_start + name_size).ok_or(InvalidArgument)?;
+            let description = notes
+                .get(description_start .. description_start + description_size)
+                .ok_or(InvalidArgument)?;
+
+            match (name, note_type) {
+                (GNU_NOTE_NAME, NT_GNU_BUILD_ID) => self.build_id = Some(description),
+                (Self::VERSION_NOTE_NAME, NT_VERSION) => {
+                    let version = str::from_utf8(description).map_err(|_| InvalidArgument)?;
+                    self.version = Some(version.trim_end_matches('\0'));
+                },
+                _ => {},
+            }
+
+            notes = notes.get(next_start ..).unwrap_or_default();
+        }
+
+        Ok(())
+    }
+
+    /// Имя note с версией сборки, включая завершающий нулевой байт.
+    pub const VERSION_NOTE_NAME: &'static [u8] = b"nikka\0";
+}
+
+impl fmt::Display for BuildInfo<'_> {
+    fn fmt(
+        &self,
+        formatter: &mut fmt::Formatter,
+    ) -> fmt::Result {
+        write!(formatter, "{{ build_id: ")?;
+
+        if let Some(build_id) = self.build_id {
+            for byte in build_id {
+                write!(formatter, "{byte:02x}")?;
+            }
+        } else {
+            write!(formatter, "none")?;
+        }
+
+        let version = self.version.unwrap_or("none");
+        write!(formatter, ", version: {version} }}")
+    }
+}
+
+/// Имя note, которые записывает GNU toolchain, включая завершающий нулевой байт.
+const GNU_NOTE_NAME: &[u8] = b"GNU\0";
+
+/// Размер заголовка note --- трёх 32-битных полей:
+/// размера имени, размера описания и типа note.
+const NOTE_HEADER_SIZE: usize = 3 * mem::size_of::<u32>();
+
+/// Тип note с идентификатором сборки, см. [`BuildInfo::build_id()`].
+const NT_GNU_BUILD_ID: usize = 3;
+
+/// Тип note с версией сборки, см. [`BuildInfo::version()`].
+const NT_VERSION: usize = 1;
+
 #[doc(hidden)]
 pub(super) mod test_scaffolding {
     use core::ops::Range;
@@ -46,6 +46,12 @@ impl RFlags {
     /// Все допустимые флаги.
     pub const ALL: RFlags = RFlags(rflags::RFlags::all().bits() as usize);
 
+    /// В режиме ядра при включённом
+    /// [SMAP](https://en.wikipedia.org/wiki/Supervisor_Mode_Access_Prevention)
+    /// разрешает доступ к страницам пользователя.
+    /// В режиме пользователя включает проверку выравнивания.
+    pub const ALIGNMENT_CHECK: RFlags = RFlags(rflags::RFlags::ALIGNMENT_CHECK.bits() as usize);
+
     /// [Разрешает внешние прерывания](https://en.wikipedia.org/wiki/Interrupt_flag).
     pub const INTERRUPT_FLAG: RFlags = RFlags(rflags::RFlags::INTERRUPT_FLAG.bits() as usize);
 
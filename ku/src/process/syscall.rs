@@ -3,9 +3,12 @@ use num_enum::{
     TryFromPrimitive,
 };
 
-use crate::error::{
-    Error,
-    Result,
+use crate::{
+    error::{
+        Error,
+        Result,
+    },
+    ipc::pipe,
 };
 
 /// Код выхода пользовательской программы, передаваемый в `syscall::exit()`.
@@ -20,6 +23,9 @@ pub enum ExitCode {
 
     /// Пользовательская программа выполнила несуществующий системный вызов.
     UnimplementedSyscall = 2,
+
+    /// Пользовательская программа завершена ядром из-за фатального исключения.
+    Killed = 3,
 }
 
 /// Номера системных вызовов.
@@ -52,6 +58,51 @@ pub enum Syscall {
 
     /// Номер системного вызова `set_trap_handler()`.
     SetTrapHandler = 8,
+
+    /// Номер системного вызова `log_bytes()`.
+    LogBytes = 9,
+
+    /// Номер системного вызова `set_group()`.
+    SetGroup = 10,
+
+    /// Номер системного вызова `suspend()`.
+    Suspend = 11,
+
+    /// Номер системного вызова `resume()`.
+    Resume = 12,
+
+    /// Номер системного вызова `yield_to()`.
+    YieldTo = 13,
+
+    /// Номер системного вызова `wait()`.
+    Wait = 14,
+
+    /// Номер системного вызова `set_priority()`.
+    SetPriority = 15,
+
+    /// Номер системного вызова `sleep()`.
+    Sleep = 16,
+
+    /// Номер системного вызова `pipe()`.
+    Pipe = 17,
+
+    /// Номер системного вызова `pipe_read()`.
+    PipeRead = 18,
+
+    /// Номер системного вызова `pipe_write()`.
+    PipeWrite = 19,
+
+    /// Номер системного вызова `pipe_close()`.
+    PipeClose = 20,
+
+    /// Номер системного вызова `share()`.
+    Share = 21,
+
+    /// Номер системного вызова `attach()`.
+    Attach = 22,
+
+    /// Номер системного вызова `set_log_level()`.
+    SetLogLevel = 23,
 }
 
 /// Код ошибки, возвращаемый из системных вызовов.
@@ -93,6 +144,12 @@ pub enum ResultCode {
 
     /// Код для [`Error::InvalidAlignment`].
     InvalidAlignment = 11,
+
+    /// Код для [`Error::NoData`].
+    NoData = 12,
+
+    /// Код для [`Error::Pipe`] с ошибкой [`pipe::Error::Closed`].
+    PipeClosed = 13,
 }
 
 impl From<ResultCode> for Result<()> {
@@ -110,6 +167,8 @@ impl From<ResultCode> for Result<()> {
             ResultCode::PermissionDenied => Err(Error::PermissionDenied),
             ResultCode::Unimplemented => Err(Error::Unimplemented),
             ResultCode::InvalidAlignment => Err(Error::InvalidAlignment),
+            ResultCode::NoData => Err(Error::NoData),
+            ResultCode::PipeClosed => Err(Error::Pipe(pipe::Error::Closed)),
 
             _ => panic!("unexpected error {:?}", result),
         }
@@ -133,10 +192,12 @@ impl<T> From<Result<T>> for ResultCode {
                 Error::Null => ResultCode::Null,
                 Error::Overflow => ResultCode::Overflow,
                 Error::PermissionDenied => ResultCode::PermissionDenied,
+                Error::Pipe(pipe::Error::Closed) => ResultCode::PipeClosed,
                 Error::Pipe(_) => ResultCode::Unexpected,
                 Error::Postcard(_) => ResultCode::Unexpected,
                 Error::Unimplemented => ResultCode::Unimplemented,
                 Error::InvalidAlignment => ResultCode::InvalidAlignment,
+                Error::NoData => ResultCode::NoData,
 
                 _ => ResultCode::Unexpected,
             },
@@ -168,6 +168,13 @@ pub enum Trap {
     /// [таймера APIC](https://en.wikipedia.org/wiki/Advanced_Programmable_Interrupt_Controller#APIC_timer).
     Timer,
 
+    /// Номер межпроцессорного прерывания
+    /// ([inter-processor interrupt](https://en.wikipedia.org/wiki/Inter-processor_interrupt), IPI),
+    /// которым процессоры просят друг друга сбросить
+    /// [TLB](https://en.wikipedia.org/wiki/Translation_lookaside_buffer)
+    /// ([TLB shootdown](https://en.wikipedia.org/wiki/Translation_lookaside_buffer#TLB_shootdown)).
+    TlbShootdown,
+
     /// Номер ложных прерываний
     /// ([spurious interrupt](https://en.wikipedia.org/wiki/Interrupt#Spurious_interrupts))
     /// [APIC](https://en.wikipedia.org/wiki/Advanced_Programmable_Interrupt_Controller).
@@ -312,6 +319,13 @@ impl Info {
             _ => Info::None,
         }
     }
+
+    /// Возвращает `true`, если это [`Info::PageFault`] при выборке инструкции,
+    /// например, при попытке исполнить код из стека или другой страницы без
+    /// [`crate::memory::mmu::PageTableFlags::EXECUTABLE`].
+    pub fn is_instruction_fetch(&self) -> bool {
+        matches!(self, Info::PageFault { code, .. } if code.contains(PageFaultInfo::EXECUTE))
+    }
 }
 
 impl fmt::Display for Info {
@@ -111,9 +111,14 @@ impl<const TICKS_PER_SECOND: i64> CorrelationInterval<TICKS_PER_SECOND> {
             .expect(UNEXPECTED_TIMESTAMP)
     }
 
+    /// Возвращает значение [`CorrelationPoint`] в базовый момент времени.
+    pub(super) fn base(&self) -> CorrelationPoint {
+        self.base
+    }
+
     /// Возвращает частоту процессора с точки зрения часов,
     /// которые отслеживает этот [`CorrelationInterval`].
-    fn tsc_per_second(&self) -> i64 {
+    pub(super) fn tsc_per_second(&self) -> i64 {
         let elapsed_count = self.elapsed_count();
         if elapsed_count > 0 {
             TICKS_PER_SECOND * self.elapsed_tsc() / elapsed_count
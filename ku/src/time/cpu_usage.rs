@@ -0,0 +1,110 @@
+use core::mem;
+
+use super::Tsc;
+
+/// Загрузка процессора в скользящем окне из последних [`CpuUsage::WINDOW`] интервалов.
+///
+/// Каждый интервал --- это либо время исполнения процесса,
+/// либо время, проведённое процессором в цикле простоя.
+/// Вместе с интервалами поддерживаются их суммы,
+/// так что и учёт очередного интервала и вычисление загрузки занимают `O(1)`.
+#[derive(Clone, Copy, Debug)]
+pub struct CpuUsage {
+    /// Суммарная длительность интервалов исполнения процессов в окне, в тактах процессора.
+    busy: i64,
+
+    /// Длительности интервалов в тактах процессора и признаки занятости процессора в них.
+    intervals: [(i64, bool); CpuUsage::WINDOW],
+
+    /// Позиция в [`CpuUsage::intervals`], куда будет записан следующий интервал.
+    next: usize,
+
+    /// Суммарная длительность всех интервалов в окне, в тактах процессора.
+    total: i64,
+}
+
+impl CpuUsage {
+    /// Создаёт пустое окно.
+    pub const fn new() -> Self {
+        Self {
+            busy: 0,
+            intervals: [(0, false); Self::WINDOW],
+            next: 0,
+            total: 0,
+        }
+    }
+
+    /// Учитывает интервал от `start` до `end`, в течение которого процессор исполнял процесс.
+    pub fn record_busy(
+        &mut self,
+        start: Tsc,
+        end: Tsc,
+    ) {
+        self.record(start, end, true);
+    }
+
+    /// Учитывает интервал от `start` до `end`, в течение которого процессор простаивал.
+    pub fn record_idle(
+        &mut self,
+        start: Tsc,
+        end: Tsc,
+    ) {
+        self.record(start, end, false);
+    }
+
+    /// Загрузка процессора в процентах.
+    /// Равна [`None`], если ещё не учтено ни одного интервала ненулевой длительности.
+    pub fn percent(&self) -> Option<u8> {
+        Self::total_percent([self])
+    }
+
+    /// Суммарная загрузка нескольких процессоров с окнами `usages` в процентах.
+    /// Равна [`None`], если ни для одного из них
+    /// ещё не учтено ни одного интервала ненулевой длительности.
+    pub fn total_percent<'a>(usages: impl IntoIterator<Item = &'a CpuUsage>) -> Option<u8> {
+        let (busy, total) = usages.into_iter().fold((0_i128, 0_i128), |(busy, total), usage| {
+            (
+                busy + i128::from(usage.busy),
+                total + i128::from(usage.total),
+            )
+        });
+
+        if total > 0 {
+            (busy * 100 / total).try_into().ok()
+        } else {
+            None
+        }
+    }
+
+    /// Учитывает интервал от `start` до `end`,
+    /// вытесняя из окна самый старый из учтённых интервалов.
+    fn record(
+        &mut self,
+        start: Tsc,
+        end: Tsc,
+        busy: bool,
+    ) {
+        let duration = (end.get() - start.get()).max(0);
+
+        let (old_duration, old_busy) =
+            mem::replace(&mut self.intervals[self.next], (duration, busy));
+        self.next = (self.next + 1) % Self::WINDOW;
+
+        if old_busy {
+            self.busy -= old_duration;
+        }
+        if busy {
+            self.busy += duration;
+        }
+        self.total += duration - old_duration;
+    }
+
+    /// Количество последних интервалов, по которым вычисляется загрузка процессора.
+    pub const WINDOW: usize = 64;
+}
+
+impl Default for CpuUsage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,132 @@
+#![forbid(unsafe_code)]
+
+use core::fmt;
+
+use chrono::{
+    DateTime,
+    Utc,
+};
+
+use crate::sync::SequenceLock;
+
+use super::{
+    NSECS_PER_SEC,
+    Tsc,
+    correlation_interval::{
+        AtomicCorrelationInterval,
+        CorrelationInterval,
+    },
+};
+
+/// Быстрые часы реального времени, аналогичные
+/// [vDSO](https://en.wikipedia.org/wiki/VDSO) в Linux.
+///
+/// Хранят откалиброванную частоту процессора и привязку одного его такта к реальному времени.
+/// Ядро обновляет их по тикам часов реального времени методом [`FastClock::update()`].
+/// А пространство пользователя читает из общей для всех процессов страницы
+/// [`crate::SystemInfo`], отображённой только на чтение.
+/// Так что [`FastClock::datetime()`] переводит такт процессора в реальное время
+/// одной арифметикой, без системных вызовов и почти без обращений к памяти.
+///
+/// Согласованность параметров при конкурентном обновлении обеспечивает [`SequenceLock`].
+pub struct FastClock(SequenceLock<Calibration>);
+
+impl FastClock {
+    /// Возвращает ещё не откалиброванные часы.
+    pub const fn new() -> Self {
+        Self(SequenceLock::new(Calibration {
+            base_nanoseconds: 0,
+            base_tsc: 0,
+            tsc_per_second: 0,
+        }))
+    }
+
+    /// Возвращает реальное время, которое соответствует такту процессора `tsc`.
+    /// Если часы ещё не откалиброваны, возвращает [`None`].
+    pub fn datetime(
+        &self,
+        tsc: Tsc,
+    ) -> Option<DateTime<Utc>> {
+        let calibration = self.0.read();
+        if calibration.tsc_per_second <= 0 {
+            return None;
+        }
+
+        let tsc_offset = i128::from(tsc.get() - calibration.base_tsc);
+        let nanoseconds = i128::from(calibration.base_nanoseconds) +
+            tsc_offset * i128::from(NSECS_PER_SEC) / i128::from(calibration.tsc_per_second);
+        let nanoseconds = i64::try_from(nanoseconds).ok()?;
+
+        DateTime::from_timestamp(
+            nanoseconds.div_euclid(NSECS_PER_SEC),
+            nanoseconds.rem_euclid(NSECS_PER_SEC).try_into().ok()?,
+        )
+    }
+
+    /// Откалибровывает часы по часам, к которым привязан `atomic_correlation_interval`.
+    /// Часы должны показывать количество тиков с частотой `TICKS_PER_SECOND`,
+    /// прошедших с начала Unix--эпохи.
+    /// Если между базовым и последним тиком этих часов не прошло ни одного тика,
+    /// оставляет калибровку прежней.
+    pub fn update<const TICKS_PER_SECOND: i64>(
+        &self,
+        atomic_correlation_interval: &AtomicCorrelationInterval<TICKS_PER_SECOND>,
+    ) {
+        let correlation_interval = atomic_correlation_interval.load();
+        if let Some(calibration) = Calibration::new(&correlation_interval) {
+            self.0.write_lock().set(calibration);
+        }
+    }
+}
+
+impl Default for FastClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for FastClock {
+    fn fmt(
+        &self,
+        formatter: &mut fmt::Formatter,
+    ) -> fmt::Result {
+        write!(formatter, "{:?}", self.0.read())
+    }
+}
+
+/// Параметры быстрых часов [`FastClock`].
+#[derive(Clone, Copy, Debug)]
+struct Calibration {
+    /// Реальное время в наносекундах от начала Unix--эпохи,
+    /// которое соответствует такту процессора [`Calibration::base_tsc`].
+    base_nanoseconds: i64,
+
+    /// Такт процессора, привязанный к реальному времени.
+    base_tsc: i64,
+
+    /// Частота процессора.
+    tsc_per_second: i64,
+}
+
+impl Calibration {
+    /// Вычисляет калибровку по интервалу `correlation_interval`.
+    /// Если в интервале ещё не прошло ни одного тика, возвращает [`None`].
+    fn new<const TICKS_PER_SECOND: i64>(
+        correlation_interval: &CorrelationInterval<TICKS_PER_SECOND>
+    ) -> Option<Self> {
+        let tsc_per_second = correlation_interval.tsc_per_second();
+        if tsc_per_second <= 0 {
+            return None;
+        }
+
+        let base = correlation_interval.base();
+        let base_nanoseconds =
+            i128::from(base.count()) * i128::from(NSECS_PER_SEC) / i128::from(TICKS_PER_SECOND);
+
+        Some(Self {
+            base_nanoseconds: base_nanoseconds.try_into().ok()?,
+            base_tsc: base.tsc(),
+            tsc_per_second,
+        })
+    }
+}
@@ -7,10 +7,21 @@ mod correlation_interval;
 /// для привязки тактов процессора к другому источнику времени, в один момент времени.
 mod correlation_point;
 
+/// Структура [`CpuUsage`] для вычисления загрузки процессора
+/// в скользящем окне из последних интервалов его работы и простоя.
+mod cpu_usage;
+
+/// Быстрые часы реального времени [`FastClock`],
+/// которые пространство пользователя читает без системных вызовов.
+mod fast_clock;
+
 /// Вспомогательная структура [`Hz`] для форматирования
 /// [частоты](https://en.wikipedia.org/wiki/Hertz) при журналировании.
 mod hz;
 
+/// Часы [`Monotonic`], которые никогда не идут назад.
+mod monotonic;
+
 /// Устаревший
 /// [программируемый таймер](https://en.wikipedia.org/wiki/Programmable_interval_timer)
 /// [Intel 8253/8254](https://en.wikipedia.org/wiki/Intel_8253).
@@ -29,6 +40,10 @@ pub mod rtc;
 /// А также структура [`TscDuration`] для хранения интервалов времени в тактах процессора.
 mod tsc;
 
+/// Поправки [`TscOffsets`] к счётчикам тактов процессоров,
+/// которые приводят их к счётчику Bootstrap Processor.
+mod tsc_offsets;
+
 use core::hint;
 
 use chrono::{
@@ -40,12 +55,18 @@ use x86_64::instructions;
 
 pub use correlation_interval::AtomicCorrelationInterval;
 pub use correlation_point::CorrelationPoint;
+pub use cpu_usage::CpuUsage;
+pub use fast_clock::FastClock;
 pub use hz::Hz;
+pub use monotonic::Monotonic;
 pub use tsc::{
     Tsc,
     TscDuration,
+    has_rdtscp,
     tsc,
+    tscp,
 };
+pub use tsc_offsets::TscOffsets;
 
 use rtc::Rtc;
 
@@ -69,13 +90,30 @@ pub fn datetime_ms(tsc: Tsc) -> DateTime<Utc> {
 // ANCHOR_END: datetime
 
 /// Сообщает системное время в текущий момент с разрешением в наносекунды.
+///
+/// Использует быстрые часы [`FastClock`], а пока они не откалиброваны ---
+/// показания часов реального времени [`Rtc`].
 pub fn now() -> DateTime<Utc> {
-    Rtc::datetime::<NSECS_PER_SEC>(Tsc::now())
+    let tsc = Tsc::synchronized();
+    crate::system_info()
+        .clock()
+        .datetime(tsc)
+        .unwrap_or_else(|| Rtc::datetime::<NSECS_PER_SEC>(tsc))
 }
 
 /// Сообщает системное время в текущий момент с разрешением в миллисекунды.
 pub fn now_ms() -> DateTime<Utc> {
-    Rtc::datetime::<MSECS_PER_SEC>(Tsc::now())
+    Rtc::datetime::<MSECS_PER_SEC>(Tsc::synchronized())
+}
+
+/// Возвращает текущий момент [`Tsc::synchronized()`],
+/// не меньший всех предыдущих результатов этой функции на текущем процессоре.
+/// Подходит для потребителей, которым нужна строгая монотонность,
+/// например для упорядочивания записей журнала.
+///
+/// Платой за монотонность является небольшое смещение вперёд, см. [`Monotonic`].
+pub fn monotonic() -> Tsc {
+    MONOTONIC.now()
 }
 
 /// Функция для получения монотонного процессорного времени, которое измеряется его тактами.
@@ -91,6 +129,9 @@ pub fn delay(_duration: Duration) {
     }
 }
 
+/// Часы для [`monotonic()`].
+static MONOTONIC: Monotonic = Monotonic::new();
+
 // ANCHOR: scale
 /// Количество миллисекунд в одной секунде.
 const MSECS_PER_SEC: i64 = 1_000;
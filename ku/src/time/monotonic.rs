@@ -0,0 +1,90 @@
+use core::{
+    fmt,
+    sync::atomic::{
+        AtomicI64,
+        Ordering,
+    },
+};
+
+use super::{
+    Tsc,
+    TscOffsets,
+    tsc::synchronized_tscp,
+};
+
+/// Часы, которые никогда не идут назад.
+///
+/// Даже после приведения счётчиков тактов процессоров к общему знаменателю
+/// с помощью [`TscOffsets`] показания могут на мгновение уменьшиться ---
+/// из-за погрешности измерения поправок или их обновления.
+/// [`Monotonic`] запоминает для каждого процессора максимальное из выданных показаний
+/// и никогда не возвращает меньшего.
+///
+/// # Note
+///
+/// Платой за монотонность является небольшое смещение вперёд.
+/// Если исходные показания отступили назад на `d` тактов,
+/// то до тех пор, пока они снова не догонят максимум,
+/// [`Monotonic`] будет возвращать одно и то же значение.
+/// То есть время будет казаться остановившимся на срок до `d` тактов,
+/// а показания [`Monotonic`] будут опережать исходные не более чем на `d` тактов.
+pub struct Monotonic([AtomicI64; TscOffsets::CAPACITY]);
+
+impl Monotonic {
+    /// Возвращает часы, которые ещё не выдавали показаний.
+    pub const fn new() -> Self {
+        Self([const { AtomicI64::new(i64::MIN) }; TscOffsets::CAPACITY])
+    }
+
+    /// Возвращает показание `tsc`, снятое на процессоре номер `cpu`,
+    /// если оно не меньше всех предыдущих результатов для этого процессора.
+    /// Иначе возвращает максимальный из предыдущих результатов.
+    ///
+    /// Для процессоров, номер которых не меньше [`TscOffsets::CAPACITY`],
+    /// возвращает `tsc` как есть.
+    pub fn clamp(
+        &self,
+        cpu: u32,
+        tsc: Tsc,
+    ) -> Tsc {
+        let max = usize::try_from(cpu).ok().and_then(|cpu| self.0.get(cpu));
+
+        if let Some(max) = max {
+            let previous = max.fetch_max(tsc.get(), Ordering::Relaxed);
+            Tsc::new(previous.max(tsc.get()))
+        } else {
+            tsc
+        }
+    }
+
+    /// Возвращает текущий момент [`Tsc::synchronized()`],
+    /// не меньший всех предыдущих результатов для текущего процессора.
+    pub fn now(&self) -> Tsc {
+        let (tsc, cpu) = synchronized_tscp();
+        self.clamp(cpu, Tsc::new(tsc))
+    }
+}
+
+impl Default for Monotonic {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Monotonic {
+    fn fmt(
+        &self,
+        formatter: &mut fmt::Formatter,
+    ) -> fmt::Result {
+        formatter
+            .debug_map()
+            .entries(
+                self.0
+                    .iter()
+                    .map(|max| max.load(Ordering::Relaxed))
+                    .enumerate()
+                    .filter(|(_, max)| *max != i64::MIN),
+            )
+            .finish()
+    }
+}
@@ -23,14 +23,18 @@ use serde::{
     Deserialize,
     Serialize,
 };
-
-use crate::error::{
-    Error,
-    Error::{
-        NoData,
-        Overflow,
+use spin::Once;
+
+use crate::{
+    error::{
+        Error,
+        Error::{
+            NoData,
+            Overflow,
+        },
+        Result,
     },
-    Result,
+    info,
 };
 
 use super::{
@@ -41,6 +45,10 @@ use super::{
     rtc::Rtc,
 };
 
+// Used in docs.
+#[allow(unused)]
+use super::TscOffsets;
+
 /// Описывает момент времени, храня значение счётчика тактов процессора.
 ///
 /// Похожа на стандартную, но недоступную нам в `#[no_std]`--окружении структуру
@@ -84,6 +92,35 @@ impl Tsc {
         Self(tsc())
     }
 
+    /// Возвращает [`Tsc`] с номером текущего такта процессора
+    /// вместе с номером процессора, на котором этот такт был прочитан, см. [`tscp()`].
+    #[inline(always)]
+    pub fn now_on_cpu() -> (Self, u32) {
+        let (tsc, cpu) = tscp();
+        (Self(tsc), cpu)
+    }
+
+    /// Возвращает [`Tsc`] с номером текущего такта процессора,
+    /// приведённым к счётчику Bootstrap Processor с помощью [`TscOffsets`].
+    ///
+    /// В отличие от [`Tsc::now()`], такие моменты можно сравнивать между собой,
+    /// даже если они получены на разных процессорах.
+    /// Например, когда процесс переезжает с одного процессора на другой.
+    ///
+    /// Если процессор не поддерживает `rdtscp`, см. [`has_rdtscp()`],
+    /// поправка не применяется и результат совпадает с [`Tsc::now()`].
+    #[inline(always)]
+    pub fn synchronized() -> Self {
+        Self(synchronized_tsc())
+    }
+
+    /// Аналог [`Tsc::elapsed()`] для момента `self`,
+    /// полученного методом [`Tsc::synchronized()`], возможно на другом процессоре.
+    #[inline(always)]
+    pub fn synchronized_elapsed(&self) -> TscDuration {
+        TscDuration(synchronized_tsc() - self.0)
+    }
+
     /// Возвращает [`TscDuration`] с количеством тактов процессора,
     /// которое прошло от `self` до текущего момента.
     #[inline(always)]
@@ -239,6 +276,77 @@ pub fn tsc() -> i64 {
 }
 // ANCHOR_END: tsc
 
+/// Возвращает
+/// [номер текущего такта процессора](https://en.wikipedia.org/wiki/Time_Stamp_Counter)
+/// вместе с номером процессора, на котором он был прочитан.
+///
+/// Использует инструкцию [`rdtscp`](https://www.felixcloutier.com/x86/rdtscp),
+/// которая атомарно читает счётчик тактов и регистр
+/// [`IA32_TSC_AUX`](https://wiki.osdev.org/Model_Specific_Registers).
+/// Ядро записывает в `IA32_TSC_AUX` каждого процессора его номер при инициализации.
+/// Поэтому, в отличие от последовательных вызовов [`tsc()`] и чтения номера процессора,
+/// оба значения гарантированно относятся к одному и тому же процессору,
+/// даже если процесс был перенесён на другой процессор между ними.
+/// Это позволяет корректировать показания счётчика тактов для конкретного процессора,
+/// если счётчики разных процессоров не синхронизированы.
+///
+/// Если процессор не поддерживает `rdtscp`, см. [`has_rdtscp()`],
+/// возвращает [`tsc()`] и нулевой номер процессора.
+#[inline(always)]
+pub fn tscp() -> (i64, u32) {
+    if cfg!(miri) {
+        return (1, 0);
+    }
+
+    if !has_rdtscp() {
+        return (tsc(), 0);
+    }
+
+    let mut cpu = 0;
+    let tsc = unsafe { x86_64::__rdtscp(&mut cpu) };
+    let tsc = tsc
+        .try_into()
+        .expect("i64 overflow when storing TSC is expected only after tens of years of uptime");
+
+    (tsc, cpu)
+}
+
+/// Возвращает `true`, если процессор поддерживает инструкцию
+/// [`rdtscp`](https://www.felixcloutier.com/x86/rdtscp).
+///
+/// Поддержка определяется битом 27 регистра `edx` в выводе
+/// [`cpuid`](https://www.felixcloutier.com/x86/cpuid) для листа `0x8000_0001`.
+/// Он проверяется только при первом вызове, дальше используется сохранённый результат.
+#[inline(always)]
+pub fn has_rdtscp() -> bool {
+    *HAS_RDTSCP.call_once(|| {
+        const RDTSCP_BIT: u32 = 1 << 27;
+
+        let highest_extended_leaf = unsafe { x86_64::__cpuid(HIGHEST_EXTENDED_LEAF).eax };
+
+        highest_extended_leaf >= EXTENDED_FEATURES_LEAF &&
+            unsafe { x86_64::__cpuid(EXTENDED_FEATURES_LEAF).edx } & RDTSCP_BIT != 0
+    })
+}
+
+/// Возвращает номер текущего такта процессора, приведённый к счётчику Bootstrap Processor.
+/// Пока общая информация о системе [`crate::SystemInfo`] недоступна
+/// или если процессор не поддерживает `rdtscp`,
+/// возвращает номер такта без поправки.
+#[inline(always)]
+fn synchronized_tsc() -> i64 {
+    synchronized_tscp().0
+}
+
+/// Возвращает номер текущего такта процессора, приведённый к счётчику Bootstrap Processor,
+/// вместе с номером процессора, на котором он был прочитан.
+/// Пока общая информация о системе [`crate::SystemInfo`] недоступна,
+/// возвращает номер такта без поправки.
+#[inline(always)]
+pub(super) fn synchronized_tscp() -> (i64, u32) {
+    info::try_system_info().map_or_else(tscp, |system_info| system_info.tsc_offsets().tscp())
+}
+
 /// Возвращает частоту процессора, вычисленную:
 ///   - С помощью [`Rtc`], если уже прошло два тика [`Rtc`].
 ///   - Иначе, с помощью [`Pit`], если уже прошло два тика [`Pit`].
@@ -270,6 +378,18 @@ fn into_f64<T: NumCast>(value: T) -> f64 {
     cast::cast(value).expect("a cast from i64/u64 to f64 can loose precision but should not fail")
 }
 
+/// Лист [`cpuid`](https://www.felixcloutier.com/x86/cpuid),
+/// который возвращает номер старшего поддерживаемого расширенного листа.
+const HIGHEST_EXTENDED_LEAF: u32 = 0x8000_0000;
+
+/// Лист [`cpuid`](https://www.felixcloutier.com/x86/cpuid)
+/// с расширенными возможностями процессора.
+const EXTENDED_FEATURES_LEAF: u32 = 0x8000_0001;
+
+/// Результат проверки поддержки инструкции
+/// [`rdtscp`](https://www.felixcloutier.com/x86/rdtscp), см. [`has_rdtscp()`].
+static HAS_RDTSCP: Once<bool> = Once::new();
+
 #[cfg(test)]
 mod test {
     use super::fractional_prefix as fp;
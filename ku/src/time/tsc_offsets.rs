@@ -0,0 +1,101 @@
+use core::{
+    fmt,
+    sync::atomic::{
+        AtomicI64,
+        Ordering,
+    },
+};
+
+use super::{
+    has_rdtscp,
+    tscp,
+};
+
+/// Поправки к счётчикам тактов процессоров, которые приводят их к счётчику
+/// Bootstrap Processor.
+///
+/// Счётчики тактов разных процессоров не обязаны быть синхронизированы.
+/// Поэтому ядро при запуске каждого Application Processor измеряет сдвиг
+/// его счётчика относительно счётчика Bootstrap Processor и записывает его сюда.
+/// А [`crate::time::Tsc::synchronized()`] прибавляет поправку процессора,
+/// номер которого возвращает инструкция
+/// [`rdtscp`](https://www.felixcloutier.com/x86/rdtscp) вместе со счётчиком, см. [`tscp()`].
+/// Так показания, снятые на разных процессорах, можно сравнивать между собой.
+pub struct TscOffsets([AtomicI64; TscOffsets::CAPACITY]);
+
+impl TscOffsets {
+    /// Возвращает нулевые поправки для всех процессоров.
+    pub const fn new() -> Self {
+        Self([const { AtomicI64::new(0) }; Self::CAPACITY])
+    }
+
+    /// Возвращает поправку к счётчику тактов процессора номер `cpu`.
+    /// Для процессоров, номер которых не меньше [`TscOffsets::CAPACITY`], она нулевая.
+    pub fn get(
+        &self,
+        cpu: u32,
+    ) -> i64 {
+        usize::try_from(cpu)
+            .ok()
+            .and_then(|cpu| self.0.get(cpu))
+            .map_or(0, |offset| offset.load(Ordering::Relaxed))
+    }
+
+    /// Устанавливает поправку `offset` к счётчику тактов процессора номер `cpu`.
+    ///
+    /// # Panics
+    ///
+    /// Паникует, если номер процессора не меньше [`TscOffsets::CAPACITY`].
+    pub fn set(
+        &self,
+        cpu: u32,
+        offset: i64,
+    ) {
+        let cpu = usize::try_from(cpu).expect("too big CPU id");
+        self.0[cpu].store(offset, Ordering::Relaxed);
+    }
+
+    /// Возвращает номер текущего такта процессора, приведённый к счётчику
+    /// Bootstrap Processor, вместе с номером процессора, на котором он был прочитан.
+    ///
+    /// Без поддержки [`rdtscp`](https://www.felixcloutier.com/x86/rdtscp)
+    /// номер процессора неизвестен, см. [`has_rdtscp()`].
+    /// Тогда возвращает номер такта без поправки.
+    #[inline(always)]
+    pub(super) fn tscp(&self) -> (i64, u32) {
+        let (tsc, cpu) = tscp();
+
+        if has_rdtscp() {
+            (tsc + self.get(cpu), cpu)
+        } else {
+            (tsc, cpu)
+        }
+    }
+
+    /// Максимальное количество процессоров, для которых хранятся поправки.
+    pub const CAPACITY: usize = u8::MAX as usize + 1;
+}
+
+impl Default for TscOffsets {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for TscOffsets {
+    fn fmt(
+        &self,
+        formatter: &mut fmt::Formatter,
+    ) -> fmt::Result {
+        formatter
+            .debug_map()
+            .entries(
+                self.0
+                    .iter()
+                    .map(|offset| offset.load(Ordering::Relaxed))
+                    .enumerate()
+                    .filter(|(_, offset)| *offset != 0),
+            )
+            .finish()
+    }
+}
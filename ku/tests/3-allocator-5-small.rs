@@ -6,8 +6,10 @@ use std::{
     alloc::{
         GlobalAlloc,
         Layout,
+        System,
     },
     cmp,
+    env,
     marker::Sync,
     mem,
     thread,
@@ -27,6 +29,7 @@ use rand::{
     SeedableRng,
     rngs::SmallRng,
 };
+use scopeguard::defer;
 
 use ku::{
     allocator::{
@@ -34,6 +37,8 @@ use ku::{
         DetailedInfo,
         Dispatcher,
         FIXED_SIZE_COUNT,
+        HISTOGRAM_BUCKET_COUNT,
+        Histogram,
         Info,
     },
     log::{
@@ -83,8 +88,6 @@ use {
     rlsf::GlobalTlsf,
     spin::Mutex,
     std::{
-        alloc::System,
-        env,
         fs,
         ptr::{
             self,
@@ -144,6 +147,86 @@ fn basic() {
     assert_eq!(CachingBig::total_memory(), 0, "do you deallocate?");
 }
 
+#[test]
+fn histogram() {
+    static ALLOCATOR: Dispatcher<ThreadLocalCache, Fallback> =
+        Dispatcher::new(ThreadLocalCache::new(), Fallback::new());
+
+    let sizes = [1, 8, 9, 100, 100, 100, 5000, 16 * 1024];
+
+    let allocations: Vec<_> = sizes
+        .iter()
+        .map(|&size| {
+            let layout = Layout::from_size_align(size, 1).unwrap();
+            let ptr = unsafe { ALLOCATOR.alloc(layout) };
+            assert!(!ptr.is_null());
+            (ptr, layout)
+        })
+        .collect();
+
+    for (ptr, layout) in allocations {
+        unsafe {
+            ALLOCATOR.dealloc(ptr, layout);
+        }
+    }
+
+    ALLOCATOR.unmap();
+
+    let detailed_info: Spinlock<DetailedInfo> = Spinlock::new(DetailedInfo::new());
+    ALLOCATOR.detailed_info(&mut detailed_info.lock());
+    let detailed_info = detailed_info.lock();
+    let histogram = detailed_info.histogram();
+    info!(%histogram);
+
+    if !Histogram::IS_SUPPORTED {
+        return;
+    }
+
+    assert_eq!(
+        sizes.map(Histogram::bucket_index),
+        [0, 3, 4, 7, 7, 7, 13, 14],
+    );
+
+    let mut expected = [0; HISTOGRAM_BUCKET_COUNT];
+    for (bucket, count) in [(0, 1), (3, 1), (4, 1), (7, 3), (13, 1), (14, 1)] {
+        expected[bucket] = count;
+    }
+    assert_eq!(histogram.buckets(), expected);
+
+    // Размеры 1 и 8 попадают в один и тот же кэшируемый размер блока,
+    // поэтому запрос на 8 байт, как и повторные запросы на 100 байт,
+    // обслуживается из кэша, заполненного предыдущим запросом.
+    assert_eq!(histogram.cached(), 3);
+    assert_eq!(histogram.uncached(), sizes.len() - 3);
+}
+
+#[test]
+fn reproducible() {
+    let seed = seed();
+
+    let layouts = |seed| {
+        let allocator = Recorder::new(&System);
+        stress("recorder", &allocator, REPRODUCIBLE_ITERATIONS, seed);
+        mem::take(&mut *allocator.layouts.lock())
+    };
+
+    let layouts_1 = layouts(seed);
+    let layouts_2 = layouts(seed);
+    let other_layouts = layouts(seed + 1);
+
+    debug!(seed, allocations = layouts_1.len());
+
+    assert!(!layouts_1.is_empty());
+    assert_eq!(
+        layouts_1, layouts_2,
+        "stress() should repeat the same allocations for the same seed",
+    );
+    assert_ne!(
+        layouts_1, other_layouts,
+        "stress() should depend on the seed",
+    );
+}
+
 #[test]
 fn single_threaded() {
     static ALLOCATOR: Dispatcher<ThreadLocalCache, Fallback> =
@@ -153,7 +236,7 @@ fn single_threaded() {
         "nikka allocator",
         &ALLOCATOR,
         SINGLE_THREADED_ITERATIONS,
-        SEED,
+        seed(),
     );
 
     ALLOCATOR.unmap();
@@ -181,12 +264,12 @@ fn single_threaded() {
 
     #[cfg(feature = "benchmark")]
     {
-        let tcmalloc_stats = stress("tcmalloc", &TCMalloc, SINGLE_THREADED_ITERATIONS, SEED);
-        let jemalloc_stats = stress("jemalloc", &Jemalloc, SINGLE_THREADED_ITERATIONS, SEED);
-        let mimalloc_stats = stress("mimalloc", &MiMalloc, SINGLE_THREADED_ITERATIONS, SEED);
+        let tcmalloc_stats = stress("tcmalloc", &TCMalloc, SINGLE_THREADED_ITERATIONS, seed());
+        let jemalloc_stats = stress("jemalloc", &Jemalloc, SINGLE_THREADED_ITERATIONS, seed());
+        let mimalloc_stats = stress("mimalloc", &MiMalloc, SINGLE_THREADED_ITERATIONS, seed());
 
         let ring = Ring(OneRingAlloc);
-        let ring_stats = stress("ring-alloc", &ring, SINGLE_THREADED_ITERATIONS, SEED);
+        let ring_stats = stress("ring-alloc", &ring, SINGLE_THREADED_ITERATIONS, seed());
 
         static ALLOCATOR_GLOBAL_CACHE: Dispatcher<GlobalCache, Fallback> =
             Dispatcher::new(GlobalCache::new(), Fallback::new());
@@ -194,7 +277,7 @@ fn single_threaded() {
             "nikka allocator (global cache)",
             &ALLOCATOR_GLOBAL_CACHE,
             SINGLE_THREADED_ITERATIONS,
-            SEED,
+            seed(),
         );
         ALLOCATOR_GLOBAL_CACHE.unmap();
 
@@ -204,7 +287,7 @@ fn single_threaded() {
             "nikka allocator (no cache)",
             &ALLOCATOR_NO_CACHE,
             SINGLE_THREADED_ITERATIONS,
-            SEED,
+            seed(),
         );
         ALLOCATOR_NO_CACHE.unmap();
 
@@ -212,25 +295,25 @@ fn single_threaded() {
             "system allocator",
             &System,
             SINGLE_THREADED_ITERATIONS,
-            SEED,
+            seed(),
         );
 
         let dlmalloc_stats = stress(
             "dlmalloc",
             &GlobalDlmalloc,
             SINGLE_THREADED_ITERATIONS,
-            SEED,
+            seed(),
         );
 
         let blink = GlobalBlinkAlloc::new();
-        let blink_stats = stress("blink", &blink, SINGLE_THREADED_ITERATIONS, SEED);
+        let blink_stats = stress("blink", &blink, SINGLE_THREADED_ITERATIONS, seed());
 
         let frusa = Frusa2M::new(&ALLOCATOR);
-        let frusa_stats = stress("frusa", &frusa, SINGLE_THREADED_ITERATIONS, SEED);
+        let frusa_stats = stress("frusa", &frusa, SINGLE_THREADED_ITERATIONS, seed());
         ALLOCATOR.unmap();
 
         let rlsf = GlobalTlsf::<()>::new();
-        let rlsf_stats = stress("rlsf", &rlsf, SINGLE_THREADED_ITERATIONS, SEED);
+        let rlsf_stats = stress("rlsf", &rlsf, SINGLE_THREADED_ITERATIONS, seed());
 
         let mut talc = Talc::new(ErrOnOom);
         let talc_stats = unsafe {
@@ -241,14 +324,14 @@ fn single_threaded() {
             let end = start.add(size);
             talc.claim(Span::new(start, end)).unwrap();
             let talck = talc.lock::<Mutex<()>>();
-            let talc_stats = stress("talc", &talck, SINGLE_THREADED_ITERATIONS, SEED);
+            let talc_stats = stress("talc", &talck, SINGLE_THREADED_ITERATIONS, seed());
             ALLOCATOR.dealloc(start, layout);
             ALLOCATOR.unmap();
             talc_stats
         };
 
         let wee = WeeAlloc::INIT;
-        let wee_stats = stress("wee", &wee, SINGLE_THREADED_ITERATIONS, SEED);
+        let wee_stats = stress("wee", &wee, SINGLE_THREADED_ITERATIONS, seed());
 
         let good = Good::<DEFAULT_SMALLBINS_AMOUNT, DEFAULT_ALIGNMENT_SUB_BINS_AMOUNT>::empty();
         let good_stats = unsafe {
@@ -257,7 +340,7 @@ fn single_threaded() {
             let start = ALLOCATOR.alloc(layout);
             assert!(!start.is_null());
             good.init(start as usize, size);
-            let good_stats = stress("good", &good, SINGLE_THREADED_ITERATIONS, SEED);
+            let good_stats = stress("good", &good, SINGLE_THREADED_ITERATIONS, seed());
             ALLOCATOR.dealloc(start, layout);
             ALLOCATOR.unmap();
             good_stats
@@ -494,7 +577,7 @@ fn multi_threaded() {
                             allocator_name,
                             allocator,
                             MULTI_THREADED_ITERATIONS,
-                            SEED + thread,
+                            seed() + thread,
                         )
                     })
                 })
@@ -668,6 +751,17 @@ fn validate_performance(
     }
 }
 
+/// Возвращает начальное значение генератора псевдослучайных чисел для стресс-тестов.
+/// По умолчанию это [`SEED`], но его можно переопределить
+/// переменной окружения `SEED`, чтобы воспроизвести упавший запуск.
+/// Если стресс-тест падает, он печатает использованное значение.
+/// Потоки многопоточного теста используют значения `seed() + thread`.
+fn seed() -> usize {
+    env::var("SEED")
+        .map(|seed| seed.parse().expect("SEED should be a non-negative integer"))
+        .unwrap_or(SEED)
+}
+
 fn stress(
     allocator_name: &str,
     allocator: &impl GlobalAlloc,
@@ -680,6 +774,12 @@ fn stress(
     let mut stats = Stats::default();
     let start = Instant::now();
 
+    defer! {
+        if thread::panicking() {
+            error!(allocator = allocator_name, seed, "stress test failed");
+        }
+    }
+
     for iteration in 1 ..= iterations {
         let operation = rng.gen_range(0 .. 3);
         let index = if !active.is_empty() {
@@ -1222,6 +1322,60 @@ unsafe impl GlobalAlloc for Ring {
     }
 }
 
+/// Аллокатор, который записывает запрошенные у `allocator` размещения памяти.
+struct Recorder<'a, T: GlobalAlloc> {
+    allocator: &'a T,
+    layouts: Spinlock<Vec<Layout>>,
+}
+
+impl<'a, T: GlobalAlloc> Recorder<'a, T> {
+    fn new(allocator: &'a T) -> Self {
+        Self {
+            allocator,
+            layouts: Spinlock::new(Vec::new()),
+        }
+    }
+}
+
+unsafe impl<T: GlobalAlloc> GlobalAlloc for Recorder<'_, T> {
+    unsafe fn alloc(
+        &self,
+        layout: Layout,
+    ) -> *mut u8 {
+        self.layouts.lock().push(layout);
+        unsafe { self.allocator.alloc(layout) }
+    }
+
+    unsafe fn alloc_zeroed(
+        &self,
+        layout: Layout,
+    ) -> *mut u8 {
+        self.layouts.lock().push(layout);
+        unsafe { self.allocator.alloc_zeroed(layout) }
+    }
+
+    unsafe fn dealloc(
+        &self,
+        ptr: *mut u8,
+        layout: Layout,
+    ) {
+        unsafe {
+            self.allocator.dealloc(ptr, layout);
+        }
+    }
+
+    unsafe fn realloc(
+        &self,
+        ptr: *mut u8,
+        layout: Layout,
+        new_size: usize,
+    ) -> *mut u8 {
+        let new_layout = Layout::from_size_align(new_size, layout.align()).unwrap();
+        self.layouts.lock().push(new_layout);
+        unsafe { self.allocator.realloc(ptr, layout, new_size) }
+    }
+}
+
 #[ctor::ctor]
 fn init() {
     log::init();
@@ -1232,6 +1386,7 @@ const DIFFERENT_SIZES: usize = 1024;
 const MAX_ACTIVE_ALLOCATIONS: usize = 1_000_000;
 const MAX_LB_ALIGN: u32 = 10;
 const MULTI_THREADED_ITERATIONS: usize = 1_000_000;
+const REPRODUCIBLE_ITERATIONS: usize = 10_000;
 const SEED: usize = 314159265;
 const SINGLE_THREADED_ITERATIONS: usize = 1_000_000;
 const SIZE_MULTIPLIER: usize = 1;
@@ -0,0 +1,102 @@
+#![deny(warnings)]
+
+use std::alloc::{
+    GlobalAlloc,
+    Layout,
+    System,
+};
+
+use ku::{
+    allocator::{
+        LEAK_BACKTRACE_DEPTH,
+        LeakTracker,
+    },
+    log::debug,
+    memory::{
+        Page,
+        Virt,
+    },
+};
+
+mod log;
+
+#[test]
+fn leak() {
+    let tracker = LeakTracker::<_, LEAK_COUNT>::new(System);
+    let layout = Layout::from_size_align(LEAK_SIZE, 8).unwrap();
+
+    let freed = allocate(&tracker, layout);
+    let leaked = allocate(&tracker, layout);
+    unsafe {
+        tracker.dealloc(freed, layout);
+    }
+
+    let leaks: Vec<_> = tracker.leaks().collect();
+    for leak in &leaks {
+        debug!(%leak);
+    }
+
+    assert_eq!(leaks.len(), 1);
+    assert_eq!(tracker.lost(), 0);
+
+    let reported = leaks[0];
+    assert_eq!(reported.address(), Virt::from_ptr(leaked));
+    assert_eq!(reported.size(), LEAK_SIZE);
+
+    let backtrace = reported.backtrace();
+    assert!(!backtrace.is_empty());
+    assert!(backtrace.len() <= LEAK_BACKTRACE_DEPTH);
+
+    let caller = Virt::new(leak as fn() as usize).unwrap();
+    assert!(
+        backtrace.iter().any(|&return_address| {
+            (caller .. (caller + Page::SIZE).unwrap()).contains(&return_address)
+        }),
+        "the allocation site should be found in the backtrace",
+    );
+
+    unsafe {
+        tracker.dealloc(leaked, layout);
+    }
+    assert_eq!(tracker.leaks().count(), 0);
+}
+
+#[test]
+fn overflow() {
+    let tracker = LeakTracker::<_, LEAK_COUNT>::new(System);
+    let layout = Layout::from_size_align(LEAK_SIZE, 8).unwrap();
+
+    let ptrs: Vec<_> = (0 .. 2 * LEAK_COUNT).map(|_| allocate(&tracker, layout)).collect();
+    assert_eq!(tracker.leaks().count(), LEAK_COUNT);
+    assert_eq!(tracker.lost(), LEAK_COUNT);
+
+    for ptr in ptrs {
+        unsafe {
+            tracker.dealloc(ptr, layout);
+        }
+    }
+    assert_eq!(tracker.leaks().count(), 0);
+    assert_eq!(tracker.lost(), 0);
+}
+
+#[ctor::ctor]
+fn init() {
+    log::init();
+}
+
+/// Выделяет память под `layout` из `tracker` в отдельном стековом фрейме.
+#[inline(never)]
+fn allocate(
+    tracker: &LeakTracker<System, LEAK_COUNT>,
+    layout: Layout,
+) -> *mut u8 {
+    let ptr = unsafe { tracker.alloc(layout) };
+    assert!(!ptr.is_null());
+    ptr
+}
+
+/// Количество отслеживаемых выделений.
+const LEAK_COUNT: usize = 4;
+
+/// Размер выделяемых блоков.
+const LEAK_SIZE: usize = 100;
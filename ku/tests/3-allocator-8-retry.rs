@@ -0,0 +1,124 @@
+#![deny(warnings)]
+
+use std::{
+    alloc::{
+        GlobalAlloc,
+        Layout,
+        System,
+    },
+    ptr,
+    sync::atomic::{
+        AtomicPtr,
+        AtomicUsize,
+        Ordering,
+    },
+};
+
+use ku::{
+    allocator::RetryAllocator,
+    log::debug,
+};
+
+mod log;
+
+#[test]
+fn retry_hook() {
+    let reserve = unsafe { ALLOCATOR.alloc(LAYOUT) };
+    assert!(!reserve.is_null());
+    RESERVE.store(reserve, Ordering::Relaxed);
+
+    let ptr = unsafe { ALLOCATOR.alloc(LAYOUT) };
+    assert!(
+        ptr.is_null(),
+        "the budget should not be enough for the second allocation",
+    );
+    assert_eq!(HOOK_CALLS.load(Ordering::Relaxed), 0);
+
+    ALLOCATOR.set_retry_hook(Some(free_reserve));
+
+    let ptr = unsafe { ALLOCATOR.alloc(LAYOUT) };
+    let hook_calls = HOOK_CALLS.load(Ordering::Relaxed);
+    debug!(?ptr, hook_calls);
+    assert!(!ptr.is_null());
+    assert_eq!(hook_calls, 1);
+    assert!(RESERVE.load(Ordering::Relaxed).is_null());
+
+    let unsatisfiable = unsafe { ALLOCATOR.alloc(LAYOUT) };
+    assert!(unsatisfiable.is_null());
+    assert_eq!(HOOK_CALLS.load(Ordering::Relaxed), 2);
+
+    ALLOCATOR.set_retry_hook(None);
+
+    let unsatisfiable = unsafe { ALLOCATOR.alloc(LAYOUT) };
+    assert!(unsatisfiable.is_null());
+    assert_eq!(HOOK_CALLS.load(Ordering::Relaxed), 2);
+
+    unsafe {
+        ALLOCATOR.dealloc(ptr, LAYOUT);
+    }
+}
+
+#[ctor::ctor]
+fn init() {
+    log::init();
+}
+
+/// Освобождает резервный буфер [`RESERVE`], если он ещё не освобождён.
+fn free_reserve() {
+    HOOK_CALLS.fetch_add(1, Ordering::Relaxed);
+
+    let reserve = RESERVE.swap(ptr::null_mut(), Ordering::Relaxed);
+    if !reserve.is_null() {
+        unsafe {
+            ALLOCATOR.dealloc(reserve, LAYOUT);
+        }
+    }
+}
+
+/// Аллокатор, который выделяет не больше [`BUDGET`] байт одновременно.
+struct Budget(AtomicUsize);
+
+unsafe impl GlobalAlloc for Budget {
+    unsafe fn alloc(
+        &self,
+        layout: Layout,
+    ) -> *mut u8 {
+        if self
+            .0
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |free| {
+                free.checked_sub(layout.size())
+            })
+            .is_ok()
+        {
+            unsafe { System.alloc(layout) }
+        } else {
+            ptr::null_mut()
+        }
+    }
+
+    unsafe fn dealloc(
+        &self,
+        ptr: *mut u8,
+        layout: Layout,
+    ) {
+        unsafe {
+            System.dealloc(ptr, layout);
+        }
+        self.0.fetch_add(layout.size(), Ordering::Relaxed);
+    }
+}
+
+/// Аллокатор с ограниченным объёмом памяти и повтором запросов.
+static ALLOCATOR: RetryAllocator<Budget> = RetryAllocator::new(Budget(AtomicUsize::new(BUDGET)));
+
+/// Количество вызовов [`free_reserve()`].
+static HOOK_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+/// Резервный буфер, который освобождается при нехватке памяти.
+static RESERVE: AtomicPtr<u8> = AtomicPtr::new(ptr::null_mut());
+
+/// Объём памяти, доступный [`ALLOCATOR`].
+const BUDGET: usize = 6 * 1024;
+
+/// Размер и выравнивание каждого выделения.
+const LAYOUT: Layout = Layout::new::<[u8; 4 * 1024]>();
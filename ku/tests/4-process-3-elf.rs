@@ -41,6 +41,7 @@ use ku::{
     },
     error::{
         Error::{
+            Elf,
             InvalidArgument,
             NoPage,
             Overflow,
@@ -63,13 +64,19 @@ use ku::{
         mmu::PageTableFlags,
         size,
     },
-    process::test_scaffolding::{
-        FileRange,
-        Loader,
-        PageRange,
-        VirtRange,
-        combine,
-        program_header_to_file_range,
+    process::{
+        elf::{
+            self,
+            BuildInfo,
+        },
+        test_scaffolding::{
+            FileRange,
+            Loader,
+            PageRange,
+            VirtRange,
+            combine,
+            program_header_to_file_range,
+        },
     },
 };
 
@@ -699,6 +706,115 @@ fn t08_load_program_header() {
     }
 }
 
+#[test]
+fn t09_build_info() {
+    let build_id: Vec<u8> = (0 .. 20).collect();
+    let version = "0.5.0-1234abcd";
+
+    let notes = [
+        note(b"GNU\0", NT_GNU_BUILD_ID, &build_id),
+        note(BuildInfo::VERSION_NOTE_NAME, NT_VERSION, version.as_bytes()),
+        note(b"Other\0", NT_GNU_BUILD_ID, b"unrelated"),
+    ]
+    .concat();
+    let file = elf_file(&[], Some(&notes));
+    let build_info = BuildInfo::new(&file).unwrap();
+    info!(%build_info);
+
+    assert_eq!(build_info.build_id(), Some(&build_id[..]));
+    assert_eq!(build_info.version(), Some(version));
+    assert_eq!(
+        build_info.to_string(),
+        format!("{{ build_id: 000102030405060708090a0b0c0d0e0f10111213, version: {version} }}"),
+    );
+
+    let file = elf_file(&[], None);
+    let build_info = BuildInfo::new(&file).unwrap();
+    info!(%build_info);
+    assert_eq!(build_info, BuildInfo::default());
+    assert_eq!(build_info.to_string(), "{ build_id: none, version: none }");
+
+    // Описание последнего note обрезано.
+    let file = elf_file(&[], Some(&notes[.. notes.len() - 4]));
+    assert_eq!(BuildInfo::new(&file), Err(InvalidArgument));
+
+    assert!(matches!(BuildInfo::new(&notes), Err(Elf(_))));
+}
+
+#[test]
+fn t10_kernel_segment() {
+    let mut allocator = DummyAllocatorPair::new(PAGE_COUNT, USER_R);
+
+    let user_address = allocator.dst.offset().into_usize();
+    let user_segment = Block::<Virt>::from_index(user_address, user_address + Page::SIZE).unwrap();
+    let kernel_segment =
+        Block::<Virt>::from_index(KERNEL_ADDRESS, KERNEL_ADDRESS + Page::SIZE).unwrap();
+    debug!(%user_segment, %kernel_segment);
+
+    let file = elf_file(&[user_segment, kernel_segment], None);
+    assert!(matches!(
+        unsafe { elf::load(&mut allocator, &file) },
+        Err(Elf(_)),
+    ));
+    allocator.dst.validate_empty();
+
+    let file = elf_file(&[user_segment], None);
+    unsafe {
+        elf::load(&mut allocator, &file).unwrap();
+    }
+    assert_eq!(allocator.dst.mapped_count(user_segment.enclosing()), 1);
+
+    const KERNEL_ADDRESS: usize = 0xFFFF_8000_0000_0000;
+    const PAGE_COUNT: usize = 4;
+}
+
+#[test]
+fn t11_demand_paging() {
+    let first = Block::<Virt>::from_index(0x40_0000, 0x40_0000 + Page::SIZE).unwrap();
+    let second = Block::<Virt>::from_index(0x40_2000, 0x40_2000 + 2 * Page::SIZE).unwrap();
+    let file = elf_file(&[first, second], None);
+
+    assert_eq!(elf::entry_point(&file), Ok(first.start_address()));
+    assert_eq!(
+        elf::loadable_segments(&file, |_| true),
+        Ok(vec![
+            (first, PageTableFlags::PRESENT),
+            (second, PageTableFlags::PRESENT),
+        ]),
+    );
+
+    let gap = (Page::containing(second.start_address()) - 1).unwrap();
+    for page in first.enclosing().into_iter().chain(second.enclosing()) {
+        assert_eq!(
+            elf::page_flags(&file, page),
+            Ok(Some(PageTableFlags::PRESENT)),
+        );
+
+        let mut content = vec![DummyAllocator::GARBAGE; Page::SIZE];
+        elf::load_page(&file, page, &mut content).unwrap();
+        assert!(content.iter().all(|&x| x == 0));
+
+        assert_eq!(
+            elf::load_page(&file, page, &mut content[1 ..]),
+            Err(InvalidArgument),
+        );
+    }
+    assert_eq!(elf::page_flags(&file, gap), Ok(None));
+
+    assert!(matches!(
+        elf::loadable_segments(&file[.. 16], |_| true),
+        Err(Elf(_))
+    ));
+    assert!(matches!(
+        elf::loadable_segments(&file, |_| false),
+        Err(Elf(_))
+    ));
+    assert!(matches!(
+        elf::loadable_segments(&file, |block| block != second.enclosing()),
+        Err(Elf(_))
+    ));
+}
+
 #[derive(Clone, Copy, Debug)]
 struct Segment {
     memory: Block<Virt>,
@@ -817,6 +933,90 @@ fn program_header(
     }
 }
 
+/// Возвращает минимальный ELF--файл с загружаемыми сегментами `loads`,
+/// не содержащими данных из файла,
+/// и note--сегментом с содержимым `notes`, если оно задано.
+fn elf_file(
+    loads: &[Block<Virt>],
+    notes: Option<&[u8]>,
+) -> Vec<u8> {
+    const ELF_HEADER_SIZE: u16 = 64;
+    const PROGRAM_HEADER_SIZE: u16 = 56;
+    const PT_LOAD: u32 = 1;
+    const PT_NOTE: u32 = 4;
+
+    let program_header_count = u16::try_from(loads.len()).unwrap() + u16::from(notes.is_some());
+    let notes_offset = ELF_HEADER_SIZE + program_header_count * PROGRAM_HEADER_SIZE;
+
+    let mut file = Vec::new();
+
+    file.extend_from_slice(b"\x7FELF");
+    file.extend_from_slice(&[2, 1, 1]);
+    file.resize(16, 0);
+    file.extend_from_slice(&2_u16.to_le_bytes());
+    file.extend_from_slice(&0x3E_u16.to_le_bytes());
+    file.extend_from_slice(&1_u32.to_le_bytes());
+    file.extend_from_slice(&0x40_0000_u64.to_le_bytes());
+    file.extend_from_slice(&u64::from(ELF_HEADER_SIZE).to_le_bytes());
+    file.extend_from_slice(&0_u64.to_le_bytes());
+    file.extend_from_slice(&0_u32.to_le_bytes());
+    file.extend_from_slice(&ELF_HEADER_SIZE.to_le_bytes());
+    file.extend_from_slice(&PROGRAM_HEADER_SIZE.to_le_bytes());
+    file.extend_from_slice(&program_header_count.to_le_bytes());
+    file.extend_from_slice(&[0; 6]);
+    assert_eq!(file.len(), usize::from(ELF_HEADER_SIZE));
+
+    for load in loads {
+        let address = size::into_u64(load.start_address().into_usize());
+        file.extend_from_slice(&PT_LOAD.to_le_bytes());
+        file.extend_from_slice(&FLAG_R.to_le_bytes());
+        file.extend_from_slice(&0_u64.to_le_bytes());
+        file.extend_from_slice(&address.to_le_bytes());
+        file.extend_from_slice(&address.to_le_bytes());
+        file.extend_from_slice(&0_u64.to_le_bytes());
+        file.extend_from_slice(&size::into_u64(load.size()).to_le_bytes());
+        file.extend_from_slice(&size::into_u64(Page::SIZE).to_le_bytes());
+    }
+
+    if let Some(notes) = notes {
+        let notes_size = size::into_u64(notes.len());
+        file.extend_from_slice(&PT_NOTE.to_le_bytes());
+        file.extend_from_slice(&FLAG_R.to_le_bytes());
+        file.extend_from_slice(&u64::from(notes_offset).to_le_bytes());
+        file.extend_from_slice(&[0; 16]);
+        file.extend_from_slice(&notes_size.to_le_bytes());
+        file.extend_from_slice(&notes_size.to_le_bytes());
+        file.extend_from_slice(&4_u64.to_le_bytes());
+        assert_eq!(file.len(), usize::from(notes_offset));
+
+        file.extend_from_slice(notes);
+    }
+
+    file
+}
+
+/// Возвращает note с именем `name`, типом `note_type` и описанием `description`,
+/// поля которого выровнены на 4 байта.
+fn note(
+    name: &[u8],
+    note_type: u32,
+    description: &[u8],
+) -> Vec<u8> {
+    let mut note = Vec::new();
+
+    for field in [name.len(), description.len()] {
+        note.extend_from_slice(&u32::try_from(field).unwrap().to_le_bytes());
+    }
+    note.extend_from_slice(&note_type.to_le_bytes());
+
+    for field in [name, description] {
+        note.extend_from_slice(field);
+        note.resize(note.len().next_multiple_of(4), 0);
+    }
+
+    note
+}
+
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
 struct DummyPage {
     flags: PageTableFlags,
@@ -1228,6 +1428,15 @@ impl BigAllocatorPair for DummyAllocatorPair {
         false
     }
 
+    fn is_user_block(
+        &self,
+        block: Block<Page>,
+    ) -> bool {
+        let offset_page = self.dst.offset_page();
+        let pages = Block::from_index(offset_page, offset_page + self.dst.pages.len()).unwrap();
+        pages.contains_block(block)
+    }
+
     unsafe fn copy_mapping(
         &mut self,
         src_block: Block<Page>,
@@ -1274,4 +1483,6 @@ fn init() {
     log::init();
 }
 
+const NT_GNU_BUILD_ID: u32 = 3;
+const NT_VERSION: u32 = 1;
 const TEST_CASE_PATH: &str = "last_failed_t06_stress_combine.json";
@@ -0,0 +1,24 @@
+#![deny(warnings)]
+
+use ku::log::{
+    HexBytes,
+    debug,
+};
+
+mod log;
+
+#[test]
+fn hex_bytes() {
+    let header = [0xDE, 0xAD, 0xBE, 0xEF, 0x00, 0x0A];
+    let hex = HexBytes::new(&header).to_string();
+    debug!(%hex);
+    assert_eq!(hex, "DE AD BE EF 00 0A");
+
+    assert_eq!(HexBytes::new(&[]).to_string(), "");
+    assert_eq!(HexBytes::new(&[0x7]).to_string(), "07");
+}
+
+#[ctor::ctor]
+fn init() {
+    log::init();
+}
@@ -0,0 +1,85 @@
+#![deny(warnings)]
+
+use ku::{
+    log::debug,
+    time::{
+        CpuUsage,
+        Tsc,
+    },
+};
+
+mod log;
+
+#[test]
+fn empty() {
+    let usage = CpuUsage::new();
+    assert_eq!(usage.percent(), None);
+    assert_eq!(CpuUsage::total_percent([&usage, &usage]), None);
+
+    let mut usage = CpuUsage::new();
+    usage.record_busy(Tsc::new(100), Tsc::new(100));
+    assert_eq!(usage.percent(), None);
+}
+
+#[test]
+fn busy_fraction() {
+    let mut usage = CpuUsage::new();
+    let mut now = 0;
+
+    for _ in 0 .. CpuUsage::WINDOW / 2 {
+        record(&mut usage, &mut now, 3, true);
+        record(&mut usage, &mut now, 1, false);
+    }
+
+    debug!(percent = ?usage.percent());
+    assert_eq!(usage.percent(), Some(75));
+
+    let mut idle = CpuUsage::new();
+    record(&mut idle, &mut now, 1, false);
+    assert_eq!(idle.percent(), Some(0));
+
+    // 96 тактов работы из 128 + 1.
+    assert_eq!(CpuUsage::total_percent([&usage, &idle]), Some(74));
+}
+
+#[test]
+fn sliding_window() {
+    let mut usage = CpuUsage::new();
+    let mut now = 0;
+
+    for _ in 0 .. CpuUsage::WINDOW {
+        record(&mut usage, &mut now, 10, true);
+    }
+    assert_eq!(usage.percent(), Some(100));
+
+    for i in 1 ..= CpuUsage::WINDOW {
+        record(&mut usage, &mut now, 10, false);
+        let expected = 100 * (CpuUsage::WINDOW - i) / CpuUsage::WINDOW;
+        assert_eq!(usage.percent(), Some(expected.try_into().unwrap()));
+    }
+    assert_eq!(usage.percent(), Some(0));
+}
+
+#[ctor::ctor]
+fn init() {
+    log::init();
+}
+
+/// Учитывает в `usage` интервал длительностью `duration` тактов, начинающийся в `now`,
+/// и сдвигает `now` на его конец.
+fn record(
+    usage: &mut CpuUsage,
+    now: &mut i64,
+    duration: i64,
+    busy: bool,
+) {
+    let start = Tsc::new(*now);
+    *now += duration;
+    let end = Tsc::new(*now);
+
+    if busy {
+        usage.record_busy(start, end);
+    } else {
+        usage.record_idle(start, end);
+    }
+}
@@ -0,0 +1,136 @@
+#![deny(warnings)]
+
+use std::{
+    sync::atomic::{
+        AtomicBool,
+        Ordering,
+    },
+    thread,
+    time::Duration,
+};
+
+use chrono::DateTime;
+use rstest::rstest;
+
+use ku::{
+    log::debug,
+    time::{
+        AtomicCorrelationInterval,
+        FastClock,
+        Tsc,
+        test_scaffolding::new_point,
+    },
+};
+
+mod log;
+
+#[rstest]
+#[timeout(Duration::from_secs(1))]
+fn calibration() {
+    let clock = FastClock::new();
+    assert_eq!(clock.datetime(Tsc::new(BASE_TSC)), None);
+
+    let rtc = AtomicCorrelationInterval::<1>::new();
+    rtc.init_base(new_point(BASE_SECONDS, BASE_TSC));
+    rtc.store_prev(new_point(BASE_SECONDS, BASE_TSC));
+    clock.update(&rtc);
+    assert_eq!(
+        clock.datetime(Tsc::new(BASE_TSC)),
+        None,
+        "the clock can not be calibrated before the RTC ticks",
+    );
+
+    rtc.store_prev(new_point(BASE_SECONDS + 10, BASE_TSC + 10 * TSC_PER_SECOND));
+    clock.update(&rtc);
+    debug!(?clock);
+
+    for (tsc_offset, nanoseconds) in [
+        (0, 0),
+        (1, 0),
+        (2, 1),
+        (TSC_PER_SECOND / 2, 500_000_000),
+        (TSC_PER_SECOND, 1_000_000_000),
+        (-TSC_PER_SECOND, -1_000_000_000),
+        (100 * TSC_PER_SECOND + 7, 100_000_000_003),
+    ] {
+        let expected = DateTime::from_timestamp_nanos(BASE_SECONDS * 1_000_000_000 + nanoseconds);
+        assert_eq!(
+            clock.datetime(Tsc::new(BASE_TSC + tsc_offset)),
+            Some(expected),
+            "tsc_offset = {tsc_offset}",
+        );
+    }
+}
+
+#[rstest]
+#[timeout(Duration::from_secs(60))]
+fn single_writer() {
+    let run = AtomicBool::new(true);
+    let clock = FastClock::new();
+
+    let fast = rtc(TSC_PER_SECOND);
+    let slow = rtc(TSC_PER_SECOND / 2);
+
+    clock.update(&fast);
+    let fast_time = clock.datetime(Tsc::new(PROBE_TSC)).unwrap();
+    clock.update(&slow);
+    let slow_time = clock.datetime(Tsc::new(PROBE_TSC)).unwrap();
+    assert_ne!(fast_time, slow_time);
+
+    thread::scope(|scope| {
+        let writer = scope.spawn(|| {
+            while run.load(Ordering::Acquire) {
+                clock.update(&fast);
+                clock.update(&slow);
+            }
+        });
+
+        let mut fast_reads = 0;
+        let mut slow_reads = 0;
+
+        while fast_reads < MIN_READS || slow_reads < MIN_READS {
+            let time = clock.datetime(Tsc::new(PROBE_TSC));
+            if time == Some(fast_time) {
+                fast_reads += 1;
+            } else if time == Some(slow_time) {
+                slow_reads += 1;
+            } else {
+                panic!("inconsistent calibration read: {time:?}");
+            }
+        }
+
+        run.store(false, Ordering::Release);
+        writer.join().expect("writer should finish successfully");
+
+        debug!(fast_reads, slow_reads);
+    });
+}
+
+#[ctor::ctor]
+fn init() {
+    log::init();
+}
+
+/// Возвращает часы реального времени, для которых частота процессора равна `tsc_per_second`.
+/// Базовые тики у всех таких часов разные.
+fn rtc(tsc_per_second: i64) -> AtomicCorrelationInterval<1> {
+    let rtc = AtomicCorrelationInterval::new();
+    rtc.init_base(new_point(BASE_SECONDS, tsc_per_second));
+    rtc.store_prev(new_point(BASE_SECONDS + 1, 2 * tsc_per_second));
+    rtc
+}
+
+/// Момент начала отсчёта --- 2023-11-14 22:13:20 UTC.
+const BASE_SECONDS: i64 = 1_700_000_000;
+
+/// Такт процессора, который соответствует [`BASE_SECONDS`].
+const BASE_TSC: i64 = 1_000_000;
+
+/// Минимальное количество согласованных чтений каждой из калибровок.
+const MIN_READS: usize = 1_000;
+
+/// Такт процессора, для которого читатели вычисляют время.
+const PROBE_TSC: i64 = 123 * TSC_PER_SECOND;
+
+/// Частота процессора.
+const TSC_PER_SECOND: i64 = 2_000_000_000;
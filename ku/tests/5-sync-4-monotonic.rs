@@ -0,0 +1,112 @@
+#![deny(warnings)]
+
+use std::{
+    thread,
+    time::Duration,
+};
+
+use rstest::rstest;
+
+use ku::{
+    log::debug,
+    time::{
+        self,
+        Monotonic,
+        Tsc,
+    },
+};
+
+mod log;
+
+#[rstest]
+#[timeout(Duration::from_secs(1))]
+fn backward_jumps() {
+    let monotonic = Monotonic::new();
+    let mut max = i64::MIN;
+    let mut previous = Tsc::new(i64::MIN);
+
+    for (i, tsc) in jumpy_tsc().enumerate() {
+        let clamped = monotonic.clamp(0, Tsc::new(tsc));
+        max = max.max(tsc);
+
+        assert!(previous <= clamped, "the clock went backwards at step {i}");
+        assert_eq!(clamped, Tsc::new(max));
+        previous = clamped;
+    }
+}
+
+#[rstest]
+#[timeout(Duration::from_secs(1))]
+fn per_cpu() {
+    let monotonic = Monotonic::new();
+
+    assert_eq!(monotonic.clamp(0, Tsc::new(1_000)), Tsc::new(1_000));
+    assert_eq!(monotonic.clamp(1, Tsc::new(10)), Tsc::new(10));
+    assert_eq!(monotonic.clamp(0, Tsc::new(500)), Tsc::new(1_000));
+    assert_eq!(monotonic.clamp(1, Tsc::new(5)), Tsc::new(10));
+
+    let too_big_cpu = u32::MAX;
+    assert_eq!(monotonic.clamp(too_big_cpu, Tsc::new(1)), Tsc::new(1));
+    assert_eq!(monotonic.clamp(too_big_cpu, Tsc::new(0)), Tsc::new(0));
+
+    debug!(?monotonic);
+}
+
+#[rstest]
+#[timeout(Duration::from_secs(10))]
+fn concurrent() {
+    let monotonic = Monotonic::new();
+
+    thread::scope(|scope| {
+        for shift in 0 .. THREAD_COUNT {
+            let monotonic = &monotonic;
+            scope.spawn(move || {
+                let mut previous = Tsc::new(i64::MIN);
+                for tsc in jumpy_tsc().map(|tsc| tsc + shift) {
+                    let clamped = monotonic.clamp(0, Tsc::new(tsc));
+                    assert!(previous <= clamped, "the clock went backwards");
+                    previous = clamped;
+                }
+            });
+        }
+    });
+}
+
+#[rstest]
+#[timeout(Duration::from_secs(10))]
+fn tight_loop() {
+    let mut previous = time::monotonic();
+
+    for _ in 0 .. ITERATIONS {
+        let now = time::monotonic();
+        assert!(previous <= now, "time::monotonic() went backwards");
+        previous = now;
+    }
+}
+
+#[ctor::ctor]
+fn init() {
+    log::init();
+}
+
+/// Возвращает растущую последовательность показаний счётчика тактов
+/// с периодическими скачками назад.
+fn jumpy_tsc() -> impl Iterator<Item = i64> {
+    (0 .. ITERATIONS).map(|i| {
+        let tsc = 10 * i64::try_from(i).unwrap();
+        if i % 7 == 3 {
+            tsc - BACKWARD_JUMP
+        } else {
+            tsc
+        }
+    })
+}
+
+/// Величина скачков показаний назад в [`jumpy_tsc()`].
+const BACKWARD_JUMP: i64 = 100;
+
+/// Количество итераций в каждом тесте.
+const ITERATIONS: usize = 100_000;
+
+/// Количество потоков в тесте [`concurrent()`].
+const THREAD_COUNT: i64 = 4;
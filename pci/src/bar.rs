@@ -109,8 +109,76 @@ impl Bar {
         }
     }
 
+    /// Измеряет размер области памяти или
+    /// [портов ввода--вывода](https://en.wikipedia.org/wiki/Memory-mapped_I/O_and_port-mapped_I/O),
+    /// которую описывает этот BAR--регистр PCI--устройства, адресуемого `routing_id`,
+    /// по смещению `offset` в пространстве конфигурации `config_space`.
+    /// Возвращает `0`, если устройство не реализует этот регистр.
+    ///
+    /// Записывает в регистр единицы во все биты, читает из него маску адреса,
+    /// которую устройство способно декодировать, и восстанавливает исходное значение.
+    /// Регистр 64-битного BAR занимает два соседних 32-битных слова,
+    /// оба измеряются так же, см. [`Width`].
+    ///
+    /// На время измерения в регистре команд отключается декодирование адресов
+    /// памяти и портов, иначе устройство откликалось бы на временный адрес из единиц.
+    /// Из-за этого измерение нельзя проводить, пока с устройством работает драйвер ---
+    /// обработчик его прерывания или его собственные обращения к памяти в режиме bus master
+    /// могут прийтись на момент, когда BAR указывает не туда.
+    /// Кроме того, измерения на разных процессорах не должны пересекаться,
+    /// так как каждое из них временно меняет пространство конфигурации устройства.
+    pub fn size(
+        &self,
+        config_space: &mut impl ConfigSpace,
+        routing_id: RoutingId,
+        offset: usize,
+    ) -> u64 {
+        let (type_mask, register_count) = match *self {
+            Self::Memory { width, .. } => (
+                MEMORY_TYPE_MASK,
+                if width == Width::Memory64 {
+                    2
+                } else {
+                    1
+                },
+            ),
+            Self::Port { .. } => (PORT_TYPE_MASK, 1),
+        };
+
+        let decode =
+            (CommandAndStatusRegister::IO_SPACE | CommandAndStatusRegister::MEMORY_SPACE).bits();
+        // Status bits are RW1C, writing back ones read from them would clear them.
+        let command = unsafe { config_space.read(routing_id, COMMAND_ADDRESS) } & COMMAND_MASK;
+        unsafe { config_space.write(routing_id, COMMAND_ADDRESS, command & !decode) };
+
+        let mut mask = 0;
+        for register in 0 .. register_count {
+            let register_offset = offset + register * mem::size_of::<u32>();
+            let original = unsafe { config_space.read(routing_id, register_offset) };
+
+            unsafe {
+                config_space.write(routing_id, register_offset, u32::MAX);
+                mask |= u64::from(config_space.read(routing_id, register_offset)) <<
+                    (register * u32::BITS as usize);
+                config_space.write(routing_id, register_offset, original);
+            }
+        }
+
+        unsafe { config_space.write(routing_id, COMMAND_ADDRESS, command) };
+
+        // The lowest writable address bit gives the region size.
+        // Unlike `!mask + 1` this does not depend on the upper bits,
+        // which some devices leave zero for the I/O BARs.
+        let mask = mask & !u64::from(type_mask);
+        if mask == 0 {
+            0
+        } else {
+            1 << mask.trailing_zeros()
+        }
+    }
+
     /// Возвращает количество байт, которые занимает регистр в пространстве конфигурации.
-    pub(super) fn size(bar: &Option<Self>) -> usize {
+    pub(super) fn register_size(bar: &Option<Self>) -> usize {
         if let &Some(Self::Memory { width, .. }) = bar &&
             width == Width::Memory64
         {
@@ -248,6 +316,18 @@ bitflags! {
 /// Смещение регистра команд и статуса в пространстве конфигурации PCI--устройства.
 pub(super) const COMMAND_ADDRESS: usize = 0x04;
 
+/// Маска регистра команд в слове [`CommandAndStatusRegister`].
+/// Старшая половина слова --- регистр статуса,
+/// его биты сбрасываются записью единицы.
+pub(super) const COMMAND_MASK: u32 = 0xFFFF;
+
+/// Маска служебных битов BAR--регистра, описывающего область памяти.
+const MEMORY_TYPE_MASK: u32 = 0xF;
+
+/// Маска служебных битов BAR--регистра, описывающего диапазон
+/// [портов ввода--вывода](https://en.wikipedia.org/wiki/Memory-mapped_I/O_and_port-mapped_I/O).
+const PORT_TYPE_MASK: u32 = 0x3;
+
 /// Смещение первого BAR--регистра в пространстве конфигурации PCI--устройства.
 pub(super) const BARS_START_ADDRESS: usize = 0x10;
 
@@ -1,6 +1,9 @@
+use core::mem;
+
 use ku::memory::{
     IndexDataPair,
     IndexDataPortPair,
+    Virt,
 };
 
 use super::RoutingId;
@@ -96,3 +99,223 @@ impl Default for PortConfigSpace {
         Self::new()
     }
 }
+
+/// Структура для работы с расширенным
+/// [пространством конфигурации PCI Express](https://en.wikipedia.org/wiki/PCI_configuration_space#Standardized_registers)
+/// через отображённую в память область
+/// Enhanced Configuration Access Mechanism (ECAM).
+///
+/// В отличие от [`PortConfigSpace`], позволяет обращаться ко всем
+/// [`MmioConfigSpace::FUNCTION_SIZE`] байтам пространства конфигурации каждой функции,
+/// а не только к первым 256 байтам.
+/// Физический адрес области ECAM и обслуживаемый ею диапазон шин
+/// задаются в таблице MCFG
+/// [ACPI](https://en.wikipedia.org/wiki/ACPI).
+#[derive(Clone, Copy, Debug)]
+pub struct MmioConfigSpace {
+    /// Виртуальный адрес, по которому отображено пространство конфигурации
+    /// шины [`MmioConfigSpace::start_bus`].
+    base: Virt,
+
+    /// Номер последней шины, которую обслуживает область ECAM, включительно.
+    end_bus: u8,
+
+    /// Номер первой шины, которую обслуживает область ECAM.
+    start_bus: u8,
+}
+
+impl MmioConfigSpace {
+    /// Создаёт структуру для работы с пространством конфигурации шин
+    /// от `start_bus` до `end_bus` включительно через область ECAM,
+    /// отображённую в память по виртуальному адресу `base`.
+    ///
+    /// # Safety
+    ///
+    /// По адресу `base` должны быть отображены
+    /// [`MmioConfigSpace::size()`] байт области ECAM, начиная с шины `start_bus`.
+    /// Отображение должно быть некешируемым, как и для остальных устройств,
+    /// [отображённых в память](https://en.wikipedia.org/wiki/Memory-mapped_I/O).
+    ///
+    /// # Panics
+    ///
+    /// Паникует, если `start_bus` больше `end_bus`.
+    pub unsafe fn new(
+        base: Virt,
+        start_bus: u8,
+        end_bus: u8,
+    ) -> Self {
+        assert!(start_bus <= end_bus);
+
+        Self {
+            base,
+            end_bus,
+            start_bus,
+        }
+    }
+
+    /// Возвращает размер области ECAM для шин от `start_bus` до `end_bus` включительно.
+    pub fn size(
+        start_bus: u8,
+        end_bus: u8,
+    ) -> usize {
+        (usize::from(end_bus - start_bus) + 1) * Self::BUS_SIZE
+    }
+
+    /// Читает величину типа `T` --- [`u8`], [`u16`] или [`u32`] ---
+    /// по смещению `offset` в пространстве конфигурации устройства, адресуемого `routing_id`.
+    /// Обращение выполняется одной инструкцией соответствующей ширины.
+    ///
+    /// # Safety
+    ///
+    /// Определяется спецификацией шины и устройств PCI.
+    ///
+    /// # Panics
+    ///
+    /// Паникует, если `offset` не выровнен по размеру `T`, выходит за пределы
+    /// [`MmioConfigSpace::FUNCTION_SIZE`] или
+    /// шина `routing_id` не обслуживается этой областью ECAM.
+    pub unsafe fn read_value<T: Register>(
+        &self,
+        routing_id: RoutingId,
+        offset: usize,
+    ) -> T {
+        T::from_le(unsafe { self.pointer::<T>(routing_id, offset).read_volatile() })
+    }
+
+    /// Записывает величину `data` типа `T` --- [`u8`], [`u16`] или [`u32`] ---
+    /// по смещению `offset` в пространстве конфигурации устройства, адресуемого `routing_id`.
+    /// Обращение выполняется одной инструкцией соответствующей ширины.
+    ///
+    /// # Safety
+    ///
+    /// Определяется спецификацией шины и устройств PCI.
+    ///
+    /// # Panics
+    ///
+    /// Паникует, если `offset` не выровнен по размеру `T`, выходит за пределы
+    /// [`MmioConfigSpace::FUNCTION_SIZE`] или
+    /// шина `routing_id` не обслуживается этой областью ECAM.
+    pub unsafe fn write_value<T: Register>(
+        &mut self,
+        routing_id: RoutingId,
+        offset: usize,
+        data: T,
+    ) {
+        unsafe {
+            self.pointer::<T>(routing_id, offset).write_volatile(data.to_le());
+        }
+    }
+
+    /// Возвращает смещение относительно [`MmioConfigSpace::base`]
+    /// величины по смещению `offset`
+    /// в пространстве конфигурации устройства, адресуемого `routing_id`.
+    fn offset(
+        &self,
+        routing_id: RoutingId,
+        offset: usize,
+    ) -> usize {
+        let bus = routing_id.bus();
+        assert!(
+            (self.start_bus ..= self.end_bus).contains(&bus),
+            "the bus {bus:02x} is not covered by the ECAM region",
+        );
+        assert!(
+            offset < Self::FUNCTION_SIZE,
+            "out-of-bounds access to memory-mapped PCI configuration space",
+        );
+
+        usize::from(bus - self.start_bus) << Self::BUS_SHIFT |
+            usize::from(routing_id.device()) << Self::DEVICE_SHIFT |
+            usize::from(routing_id.function()) << Self::FUNCTION_SHIFT |
+            offset
+    }
+
+    /// Возвращает указатель на величину типа `T` по смещению `offset`
+    /// в пространстве конфигурации устройства, адресуемого `routing_id`.
+    fn pointer<T: Register>(
+        &self,
+        routing_id: RoutingId,
+        offset: usize,
+    ) -> *mut T {
+        assert_eq!(offset % mem::size_of::<T>(), 0);
+
+        (self.base + self.offset(routing_id, offset))
+            .and_then(Virt::try_into_mut_ptr)
+            .expect("invalid address in memory-mapped PCI configuration space")
+    }
+
+    /// Размер пространства конфигурации одной шины PCI Express.
+    pub const BUS_SIZE: usize = 1 << Self::BUS_SHIFT;
+
+    /// Размер пространства конфигурации одной функции PCI Express.
+    pub const FUNCTION_SIZE: usize = 1 << Self::FUNCTION_SHIFT;
+
+    /// Сдвиг номера шины в смещении внутри области ECAM.
+    const BUS_SHIFT: u32 = 20;
+
+    /// Сдвиг номера устройства в смещении внутри области ECAM.
+    const DEVICE_SHIFT: u32 = 15;
+
+    /// Сдвиг номера функции в смещении внутри области ECAM.
+    const FUNCTION_SHIFT: u32 = 12;
+}
+
+impl ConfigSpace for MmioConfigSpace {
+    unsafe fn read(
+        &mut self,
+        routing_id: RoutingId,
+        offset: usize,
+    ) -> u32 {
+        unsafe { self.read_value(routing_id, offset) }
+    }
+
+    unsafe fn write(
+        &mut self,
+        routing_id: RoutingId,
+        offset: usize,
+        data: u32,
+    ) {
+        unsafe { self.write_value(routing_id, offset, data) };
+    }
+}
+
+/// Величина, к которой можно обратиться в пространстве конфигурации PCI
+/// одной инструкцией: [`u8`], [`u16`] или [`u32`].
+pub trait Register: Copy + sealed::Sealed {
+    /// Преобразует величину из
+    /// [little--endian](https://en.wikipedia.org/wiki/Endianness),
+    /// в котором хранится пространство конфигурации PCI, в порядок байт процессора.
+    fn from_le(value: Self) -> Self;
+
+    /// Преобразует величину из порядка байт процессора в
+    /// [little--endian](https://en.wikipedia.org/wiki/Endianness),
+    /// в котором хранится пространство конфигурации PCI.
+    fn to_le(self) -> Self;
+}
+
+/// Реализует типаж [`Register`] для целых типов.
+macro_rules! impl_register {
+    ($($t:ty),*) => {
+        $(
+            impl sealed::Sealed for $t {}
+
+            impl Register for $t {
+                fn from_le(value: Self) -> Self {
+                    <$t>::from_le(value)
+                }
+
+                fn to_le(self) -> Self {
+                    <$t>::to_le(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_register!(u8, u16, u32);
+
+/// Запрещает реализовывать типаж [`Register`] вне этого модуля.
+mod sealed {
+    /// Типаж--метка для типов, реализующих [`Register`](super::Register).
+    pub trait Sealed {}
+}
@@ -109,6 +109,9 @@ pub struct Device {
     /// Тип PCI--устройства.
     kind: Kind,
 
+    /// Географические координаты PCI--устройства.
+    routing_id: RoutingId,
+
     /// Идентификатор подустройства.
     /// Например, конкретной платы, основанной на микросхеме,
     /// задаваемой основным идентификатором устройства.
@@ -151,6 +154,7 @@ impl Device {
             id,
             is_multi_function,
             kind,
+            routing_id,
             subvendor: if subvendor.id() == 0 { None } else { Some(subvendor) },
             subdevice: if subdevice.id() == 0 { None } else { Some(subdevice) },
         })
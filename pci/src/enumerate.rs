@@ -0,0 +1,74 @@
+use super::{
+    ConfigSpace,
+    Device,
+    RoutingId,
+};
+
+// ANCHOR: enumerate
+/// Возвращает итератор по всем PCI--устройствам,
+/// которые присутствуют в пространстве конфигурации `config_space`.
+///
+/// Перебирает все шины, устройства на них и функции устройств.
+/// Функции с номерами больше нуля проверяются,
+/// только если функция `0` есть и сообщает, что поддерживает несколько функций.
+/// Отсутствующие устройства --- с идентификатором производителя `0xFFFF` --- пропускаются.
+pub fn enumerate<T: ConfigSpace>(config_space: &mut T) -> impl Iterator<Item = Device> + '_ {
+    // ANCHOR_END: enumerate
+    Devices {
+        config_space,
+        next: Some(RoutingId::new(0, 0, 0)),
+    }
+}
+
+/// Итератор по PCI--устройствам, см. [`enumerate()`].
+struct Devices<'a, T: ConfigSpace> {
+    /// Пространство конфигурации PCI.
+    config_space: &'a mut T,
+
+    /// Координаты следующей проверяемой функции
+    /// или [`None`], если перебор закончен.
+    next: Option<RoutingId>,
+}
+
+impl<T: ConfigSpace> Devices<'_, T> {
+    /// Возвращает координаты функции, которую нужно проверить после `routing_id`.
+    /// Если `skip_functions` равен `true`, остальные функции устройства `routing_id`
+    /// пропускаются.
+    /// Если `routing_id` --- последняя функция последнего устройства последней шины,
+    /// возвращает [`None`].
+    fn advance(
+        routing_id: RoutingId,
+        skip_functions: bool,
+    ) -> Option<RoutingId> {
+        let bus = routing_id.bus();
+        let device = routing_id.device();
+        let function = routing_id.function();
+
+        if !skip_functions && function + 1 < RoutingId::MAX_FUNCTION_COUNT {
+            Some(RoutingId::new(bus, device, function + 1))
+        } else if device + 1 < RoutingId::MAX_DEVICE_COUNT {
+            Some(RoutingId::new(bus, device + 1, 0))
+        } else {
+            bus.checked_add(1).map(|bus| RoutingId::new(bus, 0, 0))
+        }
+    }
+}
+
+impl<T: ConfigSpace> Iterator for Devices<'_, T> {
+    type Item = Device;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let routing_id = self.next?;
+            let device = Device::new(self.config_space, routing_id);
+
+            let skip_functions = routing_id.function() == 0 &&
+                !device.is_some_and(|device| *device.is_multi_function());
+            self.next = Self::advance(routing_id, skip_functions);
+
+            if device.is_some() {
+                return device;
+            }
+        }
+    }
+}
@@ -20,13 +20,16 @@ pub use bar::{
 pub use class::Class;
 pub use config_space::{
     ConfigSpace,
+    MmioConfigSpace,
     PortConfigSpace,
+    Register,
 };
 pub use device::{
     Device,
     Kind,
 };
 pub use device_id::DeviceId;
+pub use enumerate::enumerate;
 pub use id::Id;
 pub use routing_id::RoutingId;
 
@@ -57,6 +60,9 @@ mod device;
 /// Идентификатор PCI--устройства.
 mod device_id;
 
+/// Перебор всех PCI--устройств, присутствующих на шинах.
+mod enumerate;
+
 /// Единый тип для идентификаторов PCI устройств, производителей, классов и т.д.
 mod id;
 
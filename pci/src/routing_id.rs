@@ -1,7 +1,7 @@
 use derive_more::Display;
 
 /// Географические координаты PCI--устройства.
-#[derive(Clone, Copy, Debug, Display)]
+#[derive(Clone, Copy, Debug, Display, Eq, PartialEq)]
 #[display("{:02x}:{:02x}.{:01x}", bus, device, function)]
 pub struct RoutingId {
     /// Номер шины.
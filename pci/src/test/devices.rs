@@ -49,7 +49,7 @@ pub fn bridge() -> Vec<MockDevice> {
 ///    Capabilities: [600] Vendor Specific Information: ID=0001 Rev=1 Len=024 <?>
 ///    Kernel driver in use: nouveau
 ///    Kernel modules: nvidiafb, nouveau
-fn normal_0() -> MockDevice {
+pub fn normal_0() -> MockDevice {
     let config_space = hex!(
         r#"
         DE 10 01 12 07 04 10 00 A1 00 00 03 10 00 80 00
@@ -124,7 +124,7 @@ fn normal_0() -> MockDevice {
 ///    Capabilities: [b0] PCI Advanced Features
 ///    Kernel driver in use: ata_piix
 ///    Kernel modules: pata_acpi
-fn normal_1() -> MockDevice {
+pub fn normal_1() -> MockDevice {
     let config_space = hex!(
         r#"
         86 80 00 1E 07 00 B0 02 04 8F 01 01 00 00 00 00
@@ -191,7 +191,7 @@ fn normal_1() -> MockDevice {
 ///    I/O ports at f000 [size=32]
 ///    Kernel driver in use: i801_smbus
 ///    Kernel modules: i2c_i801
-fn normal_2() -> MockDevice {
+pub fn normal_2() -> MockDevice {
     let config_space = hex!(
         r#"
         86 80 22 1e 03 00 80 02 04 00 05 0c 00 00 00 00
@@ -259,7 +259,7 @@ fn normal_2() -> MockDevice {
 ///    Capabilities: [140] Root Complex Link
 ///    Capabilities: [d94] Secondary PCI Express
 ///    Kernel driver in use: pcieport
-fn bridge_0() -> MockDevice {
+pub fn bridge_0() -> MockDevice {
     let config_space = hex!(
         r#"
         86 80 51 01 07 04 10 00 09 00 04 06 10 00 81 00
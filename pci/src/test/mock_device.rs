@@ -1,3 +1,6 @@
+extern crate alloc;
+
+use alloc::vec::Vec;
 use core::mem;
 
 use ku::{
@@ -18,6 +21,7 @@ use crate::{
     RoutingId,
     bar::{
         COMMAND_ADDRESS,
+        COMMAND_MASK,
         Width,
     },
 };
@@ -73,6 +77,10 @@ impl MockDevice {
         self.device
     }
 
+    pub(super) fn is_multi_function(&self) -> bool {
+        self.is_multi_function
+    }
+
     pub(super) fn device(&mut self) -> Device {
         Device::new(&mut self.config_space, RoutingId::new(0, 0, 0)).unwrap()
     }
@@ -111,6 +119,33 @@ impl MockDevice {
         }
     }
 
+    pub(super) fn validate_bar_sizes(&mut self) {
+        let routing_id = RoutingId::new(0, 0, 0);
+        let mut offset = BARS_START_ADDRESS;
+
+        while offset < BARS_END_ADDRESS {
+            let bar = Bar::new(&mut self.config_space, routing_id, offset);
+
+            if let Some(bar) = bar {
+                let data = self.config_space.data;
+                let size = bar.size(&mut self.config_space, routing_id, offset);
+                debug!(%bar, size, "    ");
+
+                let expected_size = match bar {
+                    Bar::Memory { block, .. } => block.size(),
+                    Bar::Port { block } => block.size(),
+                };
+                assert_eq!(size, u64::try_from(expected_size).unwrap());
+                assert_eq!(
+                    self.config_space.data, data,
+                    "the BAR size probe should restore the configuration space",
+                );
+            }
+
+            offset += Bar::register_size(&bar);
+        }
+    }
+
     pub(super) fn validate(&mut self) {
         self.validate_device();
         self.validate_subdevice();
@@ -210,6 +245,50 @@ impl Bar {
     }
 }
 
+pub(super) struct MockBus {
+    devices: Vec<(RoutingId, MockDevice)>,
+}
+
+impl MockBus {
+    pub(super) fn new(devices: Vec<(RoutingId, MockDevice)>) -> Self {
+        Self { devices }
+    }
+
+    fn config_space(
+        &mut self,
+        routing_id: RoutingId,
+    ) -> Option<&mut MockConfigSpace> {
+        self.devices
+            .iter_mut()
+            .find(|(id, _)| *id == routing_id)
+            .map(|(_, device)| &mut device.config_space)
+    }
+}
+
+impl ConfigSpace for MockBus {
+    unsafe fn read(
+        &mut self,
+        routing_id: RoutingId,
+        offset: usize,
+    ) -> u32 {
+        match self.config_space(routing_id) {
+            Some(config_space) => unsafe { config_space.read(routing_id, offset) },
+            None => u32::MAX,
+        }
+    }
+
+    unsafe fn write(
+        &mut self,
+        routing_id: RoutingId,
+        offset: usize,
+        data: u32,
+    ) {
+        if let Some(config_space) = self.config_space(routing_id) {
+            unsafe { config_space.write(routing_id, offset, data) };
+        }
+    }
+}
+
 struct MockConfigSpace {
     bars: [Option<Bar>; 6],
     data: [u8; Self::COUNT],
@@ -242,7 +321,7 @@ impl ConfigSpace for MockConfigSpace {
 
     unsafe fn write(
         &mut self,
-        _routing_id: RoutingId,
+        routing_id: RoutingId,
         offset: usize,
         mut data: u32,
     ) {
@@ -257,6 +336,12 @@ impl ConfigSpace for MockConfigSpace {
             data = (Bar::info(bar) & Bar::mask(bar)) | (data & !Bar::mask(bar));
         }
 
+        if offset == COMMAND_ADDRESS {
+            // The status register bits are RW1C: a one clears the bit, a zero leaves it intact.
+            let status = unsafe { self.read(routing_id, offset) } & !COMMAND_MASK;
+            data = (data & COMMAND_MASK) | (status & !data);
+        }
+
         for i in offset .. offset + mem::size_of::<u32>() {
             self.data[i] = data as u8;
             data >>= u8::BITS;
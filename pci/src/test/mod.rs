@@ -1,3 +1,10 @@
+extern crate alloc;
+
+use alloc::{
+    vec,
+    vec::Vec,
+};
+
 use tracing_core::LevelFilter;
 use tracing_subscriber::{
     self,
@@ -5,9 +12,21 @@ use tracing_subscriber::{
     fmt,
 };
 
-use ku::log::debug;
+use ku::{
+    log::debug,
+    memory::Virt,
+};
+
+use crate::{
+    ConfigSpace,
+    MmioConfigSpace,
+    RoutingId,
+};
 
-use mock_device::MockDevice;
+use mock_device::{
+    MockBus,
+    MockDevice,
+};
 
 mod devices;
 mod mock_device;
@@ -36,6 +55,14 @@ fn bar() {
     }
 }
 
+#[test]
+fn bar_size() {
+    for mut device in devices::normal() {
+        debug!(device = device.name());
+        device.validate_bar_sizes();
+    }
+}
+
 #[test]
 fn normal() {
     for mut device in devices::normal() {
@@ -52,6 +79,79 @@ fn bridge() {
     }
 }
 
+#[test]
+fn enumerate() {
+    let [single_function, multi_function, function] = [
+        devices::normal_1(),
+        devices::normal_0(),
+        devices::normal_2(),
+    ];
+    assert!(!single_function.is_multi_function());
+    assert!(multi_function.is_multi_function());
+
+    let mut bus = MockBus::new(vec![
+        (RoutingId::new(0, 0, 0), single_function),
+        (RoutingId::new(0, 0, 1), devices::normal_2()),
+        (RoutingId::new(0, 3, 0), multi_function),
+        (RoutingId::new(0, 3, 2), function),
+        (RoutingId::new(0, 4, 1), devices::normal_2()),
+        (RoutingId::new(5, 31, 0), devices::bridge_0()),
+        (RoutingId::new(255, 31, 7), devices::normal_2()),
+    ]);
+
+    let routing_ids: Vec<_> = crate::enumerate(&mut bus)
+        .inspect(|device| debug!(routing_id = %device.routing_id(), %device))
+        .map(|device| *device.routing_id())
+        .collect();
+
+    assert_eq!(
+        routing_ids,
+        [
+            RoutingId::new(0, 0, 0),
+            RoutingId::new(0, 3, 0),
+            RoutingId::new(0, 3, 2),
+            RoutingId::new(5, 31, 0),
+        ],
+    );
+}
+
+#[test]
+fn mmio_config_space() {
+    let (start_bus, end_bus) = (1, 2);
+    let mut ecam = vec![0_u32; MmioConfigSpace::size(start_bus, end_bus) / 4];
+    let base = Virt::from_mut_ptr(ecam.as_mut_ptr());
+    let mut config_space = unsafe { MmioConfigSpace::new(base, start_bus, end_bus) };
+
+    let routing_id = RoutingId::new(2, 3, 4);
+    let offset = 0x100;
+    let index = (1 << 20 | 3 << 15 | 4 << 12 | offset) / 4;
+
+    unsafe {
+        config_space.write(routing_id, offset, 0x1234_5678);
+    }
+    assert_eq!(ecam[index], 0x1234_5678);
+
+    unsafe {
+        assert_eq!(config_space.read(routing_id, offset), 0x1234_5678);
+        assert_eq!(config_space.read_value::<u8>(routing_id, offset + 1), 0x56);
+        assert_eq!(
+            config_space.read_value::<u16>(routing_id, offset + 2),
+            0x1234,
+        );
+
+        config_space.write_value::<u8>(routing_id, offset + 3, 0xAB);
+        config_space.write_value::<u16>(routing_id, offset, 0xCDEF);
+    }
+    assert_eq!(ecam[index], 0xAB34_CDEF);
+
+    let last = RoutingId::new(2, 31, 7);
+    unsafe {
+        config_space.write(last, MmioConfigSpace::FUNCTION_SIZE - 4, 0x9ABC_DEF0);
+    }
+    assert_eq!(ecam.last(), Some(&0x9ABC_DEF0));
+    assert_eq!(ecam.iter().filter(|&&x| x != 0).count(), 2);
+}
+
 #[ctor::ctor]
 fn init() {
     let filter = EnvFilter::from_default_env().add_directive(LevelFilter::DEBUG.into());
@@ -12,9 +12,6 @@ const INTERRUPT_LINE_COUNT: u8 = 8;
 pub const PIC_INTERRUPT_COUNT: u8 = INTERRUPT_LINE_COUNT * 2;
 
 pub unsafe fn init(first_interrupt_index: u8) {
-    const PIC0_DATA: u16 = PIC0_COMMAND + 1;
-    const PIC1_DATA: u16 = PIC1_COMMAND + 1;
-
     const ICW1_USE_ICW4: u8 = 0b_1 << 0;
     const ICW1_CASCADE: u8 = 0b_0 << 1;
     const ICW1_LEVEL_TRIGGERED: u8 = 0b_0 << 3;
@@ -34,7 +31,6 @@ pub unsafe fn init(first_interrupt_index: u8) {
         io::outb(PIC1_DATA, icw2_for_pic1);
     }
 
-    const CASCADE_LINE: u8 = 2;
     const CASCADE_LINES_BITMASK: u8 = 1 << CASCADE_LINE;
     const ICW3_FOR_PIC0: u8 = CASCADE_LINES_BITMASK;
     const ICW3_FOR_PIC1: u8 = CASCADE_LINE;
@@ -54,7 +50,7 @@ pub unsafe fn init(first_interrupt_index: u8) {
     //   - modules should be properly prioritized themselves,
     //   - we plan to use IO APIC anyway.
     const ICW4_MANDATORY_BITS: u8 = 0b_1 << 0;
-    const ICW4_AUTOMATIC_END_OF_INTERRUPT: u8 = 0b_1 << 1;
+    const ICW4_AUTOMATIC_END_OF_INTERRUPT: u8 = (AUTOMATIC_END_OF_INTERRUPT as u8) << 1;
     const ICW4_UNBUFFERED_MODE: u8 = 0b_00 << 2;
     const ICW4_NORMAL_NESTED_MODE: u8 = 0b_0 << 4;
     const ICW4: u8 = ICW4_MANDATORY_BITS |
@@ -90,5 +86,101 @@ pub unsafe fn end_of_interrupt(pic_interrupt_number: usize) {
     }
 }
 
+// The mask register (OCW1) is read and written through the data port.
+// The read-modify-write is not atomic, so the callers of mask() and unmask()
+// should not race with each other.
+pub unsafe fn mask(irq: u8) {
+    let (data_port, line) = data_port_and_line(irq);
+
+    unsafe {
+        io::outb(data_port, io::inb(data_port) | (1 << line));
+    }
+}
+
+// Unmasking a line of PIC1 also unmasks the cascade line of PIC0,
+// otherwise the interrupt would never reach the CPU.
+pub unsafe fn unmask(irq: u8) {
+    let (data_port, line) = data_port_and_line(irq);
+
+    unsafe {
+        io::outb(data_port, io::inb(data_port) & !(1 << line));
+
+        if data_port == PIC1_DATA {
+            io::outb(PIC0_DATA, io::inb(PIC0_DATA) & !(1 << CASCADE_LINE));
+        }
+    }
+}
+
+// Bit `irq` is set if the line `irq` is masked.
+pub fn masked() -> u16 {
+    let (pic0_mask, pic1_mask) = unsafe { (io::inb(PIC0_DATA), io::inb(PIC1_DATA)) };
+
+    u16::from_le_bytes([pic0_mask, pic1_mask])
+}
+
+// A spurious interrupt happens when a line de-asserts before the CPU acknowledges it.
+// The PIC then reports its lowest priority line, IRQ7 for PIC0 or IRQ15 for PIC1,
+// without setting the corresponding bit in its in-service register (ISR).
+//
+// In the automatic End Of Interrupt mode the PIC clears the ISR bit of a genuine interrupt
+// on the acknowledge too, so the two can not be told apart.
+// In this mode is_spurious() always returns false to never lose a genuine interrupt.
+pub fn is_spurious(irq: u8) -> bool {
+    if AUTOMATIC_END_OF_INTERRUPT || irq % INTERRUPT_LINE_COUNT != SPURIOUS_LINE {
+        return false;
+    }
+
+    let command_port = if irq < INTERRUPT_LINE_COUNT {
+        PIC0_COMMAND
+    } else {
+        PIC1_COMMAND
+    };
+
+    const OCW3_MANDATORY_BITS: u8 = 0b_1 << 3;
+    const OCW3_READ_ISR: u8 = 0b_11;
+    const OCW3: u8 = OCW3_MANDATORY_BITS | OCW3_READ_ISR;
+
+    let isr = unsafe {
+        io::outb(command_port, OCW3);
+        io::inb(command_port)
+    };
+
+    isr & (1 << SPURIOUS_LINE) == 0
+}
+
+// A spurious IRQ15 reaches the CPU through the cascade line of PIC0 which is really in service,
+// so PIC0 still needs an End Of Interrupt. PIC1 must not get it.
+// A spurious IRQ7 needs no End Of Interrupt at all.
+pub unsafe fn end_of_spurious_interrupt(irq: u8) {
+    const EOI: u8 = 0x20;
+
+    if irq >= INTERRUPT_LINE_COUNT {
+        unsafe {
+            io::outb(PIC0_COMMAND, EOI);
+        }
+    }
+}
+
+fn data_port_and_line(irq: u8) -> (u16, u8) {
+    assert!(
+        irq < PIC_INTERRUPT_COUNT,
+        "invalid PIC interrupt line {irq}"
+    );
+
+    if irq < INTERRUPT_LINE_COUNT {
+        (PIC0_DATA, irq)
+    } else {
+        (PIC1_DATA, irq - INTERRUPT_LINE_COUNT)
+    }
+}
+
+const AUTOMATIC_END_OF_INTERRUPT: bool = true;
+
+const CASCADE_LINE: u8 = 2;
+
 const PIC0_COMMAND: u16 = 0x20;
+const PIC0_DATA: u16 = PIC0_COMMAND + 1;
 const PIC1_COMMAND: u16 = 0xA0;
+const PIC1_DATA: u16 = PIC1_COMMAND + 1;
+
+const SPURIOUS_LINE: u8 = 7;
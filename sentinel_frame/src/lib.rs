@@ -36,6 +36,7 @@ use syn::{
 enum MethodReturnType {
     Unit,
     Primitive(syn::Path),
+    Pair(syn::Path),
     Never,
 }
 
@@ -44,6 +45,21 @@ enum MethodReturnType {
 /// Перед вызовом данной функции добавляет стековый фрейм (`rbp = 0`, `return_address = 0`),
 /// который означает окончание стека.
 ///
+/// Первые шесть аргументов передаются в регистрах, остальные --- на стеке,
+/// ниже стекового фрейма--ограничителя, как того требует
+/// [System V ABI](https://gitlab.com/x86-psABIs/x86-64-ABI).
+/// Стековые аргументы проходят через регистры общего назначения,
+/// поэтому их может быть не больше шести.
+///
+/// Кроме примитивных типов, функция может возвращать `#[repr(C)]` структуру
+/// из целочисленных полей размером не больше двух регистров ---
+/// она возвращается в паре регистров `rax:rdx`.
+/// Структуры большего размера отвергаются при компиляции.
+/// Числа с плавающей точкой и структуры с такими полями
+/// System V ABI возвращает в регистрах `xmm`, поэтому они не поддерживаются.
+/// Типы `f32` и `f64` отвергаются макросом, а за составом полей структуры
+/// должен следить автор функции.
+///
 /// # Examples
 ///
 /// ```
@@ -95,6 +111,7 @@ pub fn with_sentinel_frame(
     });
 
     let mut asm_args = Vec::new();
+    let mut stack_args = Vec::new();
     const ARG_REGISTERS: [&str; 6] = ["rdi", "rsi", "rdx", "rcx", "r8", "r9"];
     let inputs = &sig.inputs;
 
@@ -103,17 +120,17 @@ pub fn with_sentinel_frame(
         Err(e) => return e.to_compile_error().into(),
     };
 
-    if inputs.len() > ARG_REGISTERS.len() {
+    if inputs.len() > ARG_REGISTERS.len() + MAX_STACK_ARGUMENTS {
         errors.push(Error::new_spanned(
             inputs,
             format!(
                 "Functions with more than {} arguments are not supported",
-                ARG_REGISTERS.len()
+                ARG_REGISTERS.len() + MAX_STACK_ARGUMENTS
             ),
         ));
     }
 
-    for (arg, reg) in inputs.iter().zip(ARG_REGISTERS) {
+    for (index, arg) in inputs.iter().enumerate() {
         match arg {
             FnArg::Typed(PatType { pat, ty, .. }) => {
                 if let Err(e) = check_argument_type(ty) {
@@ -131,7 +148,21 @@ pub fn with_sentinel_frame(
                     },
                 };
 
-                asm_args.push(quote_spanned! { arg.span() => in(#reg) #arg_name });
+                // Целые аргументы расширяются до 64 бит, так как вызываемая функция
+                // полагается на то, что старшие биты короткого аргумента
+                // заполнены знаком или нулями.
+                let value = match ty.deref() {
+                    Type::Path(_) => quote! { #arg_name as u64 },
+                    _ => quote_spanned! { arg.span() => #arg_name },
+                };
+
+                if let Some(reg) = ARG_REGISTERS.get(index) {
+                    asm_args.push(quote_spanned! { arg.span() => in(#reg) #value });
+                } else {
+                    let operand = Ident::new(&format!("stack_arg_{index}"), arg.span());
+                    asm_args.push(quote_spanned! { arg.span() => #operand = in(reg) #value });
+                    stack_args.push(operand);
+                }
             },
             FnArg::Receiver(recv) => {
                 errors.push(Error::new_spanned(recv, "Receivers are not supported"));
@@ -148,24 +179,51 @@ pub fn with_sentinel_frame(
     }
 
     let output = Ident::new("output", Span::call_site());
+    let output_rdx = Ident::new("output_rdx", Span::call_site());
     match return_type {
         MethodReturnType::Primitive(_) => asm_args.push(quote! { lateout("rax") #output }),
+        MethodReturnType::Pair(_) => {
+            asm_args.push(quote! { lateout("rax") #output });
+            asm_args.push(quote! { lateout("rdx") #output_rdx });
+        },
         MethodReturnType::Never => asm_args.push(quote! { options(noreturn) }),
         MethodReturnType::Unit => {},
     };
 
-    let asm_block = quote! {
-        unsafe {
-            core::arch::asm!("
+    // Стековые аргументы кладутся в обратном порядке, чтобы первый из них оказался
+    // непосредственно над адресом возврата.
+    // При нечётном их количестве перед ними нужно выравнивание,
+    // так как на момент `call` регистр `rsp` должен быть выровнен на 16 байт.
+    let padding = stack_args.len() % 2;
+    let mut push_stack_args = String::new();
+    if padding != 0 {
+        push_stack_args += "sub rsp, 8\n                ";
+    }
+    for operand in stack_args.iter().rev() {
+        push_stack_args += &format!("push {{{operand}:r}}\n                ");
+    }
+    let cleanup = 8 * (3 + padding + stack_args.len());
+
+    let asm_template = LitStr::new(
+        &format!(
+            "
                 push rbp
                 sub rsp, 8
                 push 0
                 push 0
                 mov rbp, rsp
-                call {func}
-                add rsp, 24
+                {push_stack_args}call {{func}}
+                add rsp, {cleanup}
                 pop rbp
                 ",
+        ),
+        Span::call_site(),
+    );
+
+    let asm_block = quote! {
+        unsafe {
+            core::arch::asm!(
+                #asm_template,
                 func = sym #inner_f_name,
                 #(#asm_args,)*
                 clobber_abi("C")
@@ -180,6 +238,22 @@ pub fn with_sentinel_frame(
             #asm_block
             #output
         },
+        MethodReturnType::Pair(path) => {
+            let size_check = quote_spanned! { path.span() =>
+                const _: () = assert!(
+                    core::mem::size_of::<#path>() <= 2 * core::mem::size_of::<u64>(),
+                    "Only structures that fit into two registers (rax:rdx) can be returned",
+                );
+            };
+
+            quote! {
+                #size_check
+                let mut #output: u64;
+                let mut #output_rdx: u64;
+                #asm_block
+                unsafe { core::mem::transmute_copy::<[u64; 2], #path>(&[#output, #output_rdx]) }
+            }
+        },
     };
 
     quote! {
@@ -219,15 +293,19 @@ fn check_return_type(ty: &ReturnType) -> Result<MethodReturnType, Error> {
     };
 
     match ty.deref() {
-        Type::Path(TypePath { path, .. }) => {
-            check_primitive_type(path)?;
-            Ok(MethodReturnType::Primitive(path.clone()))
-        },
+        Type::Path(TypePath { path, .. }) if check_primitive_type(path).is_ok() =>
+            Ok(MethodReturnType::Primitive(path.clone())),
+        Type::Path(TypePath { path, .. }) if is_float_type(path) => Err(Error::new_spanned(
+            ty,
+            "Floating point types are returned in xmm registers and are not supported",
+        )),
+        Type::Path(TypePath { path, .. }) => Ok(MethodReturnType::Pair(path.clone())),
         Type::Never(_) => Ok(MethodReturnType::Never),
         Type::Tuple(t) if t.elems.is_empty() => Ok(MethodReturnType::Unit),
         _ => Err(Error::new_spanned(
             ty,
-            "Only primitive types, unit type and never (!) are allowed as return types",
+            "Only primitive types, small structures, unit type and never (!) are allowed as \
+             return types",
         )),
     }
 }
@@ -254,6 +332,10 @@ fn check_primitive_type(path: &syn::Path) -> Result<(), Error> {
     Ok(())
 }
 
+fn is_float_type(path: &syn::Path) -> bool {
+    path.get_ident().is_some_and(|ident| ident == "f32" || ident == "f64")
+}
+
 fn check_reference_type(ty: &Type) -> Result<(), Error> {
     // TODO: maybe prohibit impl Trait as there can be `[T]` or `dyn Trait` hiding behind it
     match ty {
@@ -263,3 +345,7 @@ fn check_reference_type(ty: &Type) -> Result<(), Error> {
         _ => Ok(()),
     }
 }
+
+/// Максимальное количество аргументов, передаваемых через стек.
+/// Каждый из них на время ассемблерной вставки занимает свободный регистр общего назначения.
+const MAX_STACK_ARGUMENTS: usize = 6;
@@ -80,6 +80,112 @@ fn arguments_are_passed_properly() {
     assert_eq!(v6, TARGET);
 }
 
+#[with_sentinel_frame]
+fn seven_args(
+    v1: u8,
+    v2: u16,
+    v3: u32,
+    v4: u64,
+    v5: i8,
+    v6: i16,
+    v7: &u32,
+) -> u64 {
+    u64::from(v1) + u64::from(v2) + u64::from(v3) + v4 + v5 as u64 + v6 as u64 + u64::from(*v7)
+}
+
+#[allow(clippy::too_many_arguments)]
+#[with_sentinel_frame]
+fn twelve_args(
+    v1: u64,
+    v2: u64,
+    v3: u64,
+    v4: u64,
+    v5: u64,
+    v6: u64,
+    v7: u8,
+    v8: i32,
+    v9: &u64,
+    v10: *const u64,
+    v11: u64,
+    v12: &mut u64,
+) -> u64 {
+    *v12 = 42;
+
+    v1 + v2 + v3 + v4 + v5 + v6 + u64::from(v7) + v8 as u64 + *v9 + unsafe { *v10 } + v11
+}
+
+#[test]
+fn stack_arguments_are_passed_properly() {
+    assert_eq!(seven_args(1, 2, 4, 8, 16, 32, &64), 127);
+
+    let v9 = 1 << 8;
+    let v10: u64 = 1 << 9;
+    let mut v12 = 0;
+    let output = twelve_args(
+        1,
+        1 << 1,
+        1 << 2,
+        1 << 3,
+        1 << 4,
+        1 << 5,
+        1 << 6,
+        1 << 7,
+        &v9,
+        &v10 as *const u64,
+        1 << 10,
+        &mut v12,
+    );
+    assert_eq!(output, (1 << 11) - 1);
+    assert_eq!(v12, 42);
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(C)]
+struct Pair {
+    first: u64,
+    second: u32,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(C)]
+struct Small {
+    first: u16,
+    second: u32,
+}
+
+#[with_sentinel_frame]
+fn make_pair(
+    first: u64,
+    second: u32,
+) -> Pair {
+    Pair { first, second }
+}
+
+#[with_sentinel_frame]
+fn make_small(
+    first: u16,
+    second: u32,
+) -> Small {
+    Small { first, second }
+}
+
+#[test]
+fn pair_is_returned_properly() {
+    for i in 0 .. 5 {
+        let first = u64::MAX - i;
+        let second = u32::MAX - 2 * i as u32;
+
+        assert_eq!(make_pair(first, second), Pair { first, second });
+        assert_eq!(
+            make_small(first as u16, second),
+            Small {
+                first: first as u16,
+                second,
+            },
+        );
+    }
+}
+
 #[with_sentinel_frame]
 extern "C" fn add(
     a: i32,
@@ -0,0 +1,8 @@
+use sentinel_frame::with_sentinel_frame;
+
+#[with_sentinel_frame]
+fn float() -> f64 {
+    1.0
+}
+
+fn main() {}
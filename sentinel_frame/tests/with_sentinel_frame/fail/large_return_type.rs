@@ -0,0 +1,21 @@
+use sentinel_frame::with_sentinel_frame;
+
+#[repr(C)]
+struct Triple {
+    _a: u64,
+    _b: u64,
+    _c: u64,
+}
+
+#[with_sentinel_frame]
+fn triple() -> Triple {
+    Triple {
+        _a: 1,
+        _b: 2,
+        _c: 3,
+    }
+}
+
+fn main() {
+    triple();
+}
@@ -9,6 +9,12 @@ fn lots_of_args(
     _v5: u8,
     _v6: i8,
     _v7: i16,
+    _v8: i16,
+    _v9: i32,
+    _v10: i32,
+    _v11: i64,
+    _v12: i64,
+    _v13: u16,
 ) {
 }
 
@@ -0,0 +1,163 @@
+/// Буфер принятых из
+/// [последовательного порта](https://en.wikipedia.org/wiki/Serial_port) байт
+/// с программным [управлением потоком XON/XOFF](https://en.wikipedia.org/wiki/Software_flow_control).
+///
+/// Когда буфер почти заполнен, просит удалённую сторону приостановить передачу байтом [`XOFF`],
+/// а когда он почти опустошён --- возобновить её байтом [`XON`].
+/// И наоборот, отслеживает [`XOFF`] и [`XON`] от удалённой стороны,
+/// чтобы приостанавливать собственную передачу.
+///
+/// Пока управление потоком выключено, [`XOFF`] и [`XON`] не отправляются и
+/// не обрабатываются особым образом, а буфер просто теряет не поместившиеся в него байты.
+#[derive(Debug)]
+pub struct FlowControl<const N: usize> {
+    /// [Кольцевой буфер](https://en.wikipedia.org/wiki/Circular_buffer) принятых байт.
+    buffer: [u8; N],
+
+    /// Управление потоком включено.
+    enabled: bool,
+
+    /// Удалённой стороне отправлен [`XOFF`], и она ещё не получила [`XON`].
+    input_stopped: bool,
+
+    /// Количество байт в буфере.
+    len: usize,
+
+    /// Удалённая сторона прислала [`XOFF`] и ещё не прислала [`XON`].
+    output_stopped: bool,
+
+    /// Индекс самого старого байта в буфере.
+    start: usize,
+}
+
+impl<const N: usize> FlowControl<N> {
+    /// Создаёт пустой буфер принятых байт.
+    /// Управление потоком включается, если `enabled` равен `true`.
+    pub const fn new(enabled: bool) -> Self {
+        Self {
+            buffer: [0; N],
+            enabled,
+            input_stopped: false,
+            len: 0,
+            output_stopped: false,
+            start: 0,
+        }
+    }
+
+    /// Возвращает `true`, если управление потоком включено.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Включает или выключает управление потоком.
+    ///
+    /// Возвращает байт, который нужно отправить удалённой стороне, если такой есть:
+    /// при выключении приостановленный приём возобновляется байтом [`XON`].
+    pub fn set_enabled(
+        &mut self,
+        enabled: bool,
+    ) -> Option<u8> {
+        self.enabled = enabled;
+        self.output_stopped = false;
+
+        if !enabled && self.input_stopped {
+            self.input_stopped = false;
+            Some(XON)
+        } else {
+            None
+        }
+    }
+
+    /// Возвращает `true`, если удалённая сторона попросила приостановить передачу.
+    pub fn is_output_stopped(&self) -> bool {
+        self.output_stopped
+    }
+
+    /// Возвращает `true`, если удалённую сторону попросили приостановить передачу.
+    pub fn is_input_stopped(&self) -> bool {
+        self.input_stopped
+    }
+
+    /// Возвращает количество байт в буфере.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Возвращает `true`, если буфер пуст.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Обрабатывает байт `octet`, принятый от удалённой стороны.
+    /// Если буфер полон, байт теряется.
+    ///
+    /// Возвращает байт, который нужно отправить удалённой стороне, если такой есть.
+    pub fn receive(
+        &mut self,
+        octet: u8,
+    ) -> Option<u8> {
+        if self.enabled {
+            match octet {
+                XOFF => {
+                    self.output_stopped = true;
+                    return None;
+                },
+                XON => {
+                    self.output_stopped = false;
+                    return None;
+                },
+                _ => {},
+            }
+        }
+
+        if self.len < N {
+            self.buffer[(self.start + self.len) % N] = octet;
+            self.len += 1;
+        }
+
+        if self.enabled && !self.input_stopped && self.len >= Self::HIGH_WATERMARK {
+            self.input_stopped = true;
+            Some(XOFF)
+        } else {
+            None
+        }
+    }
+
+    /// Извлекает из буфера самый старый байт.
+    ///
+    /// Возвращает извлечённый байт, если буфер не был пуст,
+    /// и байт, который нужно отправить удалённой стороне, если такой есть.
+    pub fn read(&mut self) -> (Option<u8>, Option<u8>) {
+        let octet = if self.len > 0 {
+            let octet = self.buffer[self.start];
+            self.start = (self.start + 1) % N;
+            self.len -= 1;
+            Some(octet)
+        } else {
+            None
+        };
+
+        let control = if self.input_stopped && self.len <= Self::LOW_WATERMARK {
+            self.input_stopped = false;
+            Some(XON)
+        } else {
+            None
+        };
+
+        (octet, control)
+    }
+
+    /// Заполненность буфера, при достижении которой отправляется [`XOFF`].
+    /// Оставшееся место нужно для байт, которые удалённая сторона успеет отправить,
+    /// пока [`XOFF`] до неё доходит.
+    pub const HIGH_WATERMARK: usize = N - N / 4;
+
+    /// Заполненность буфера, при опускании до которой отправляется [`XON`].
+    pub const LOW_WATERMARK: usize = N / 4;
+}
+
+/// Байт `XOFF` --- просьба приостановить передачу.
+pub const XOFF: u8 = 0x13;
+
+/// Байт `XON` --- просьба возобновить передачу.
+pub const XON: u8 = 0x11;
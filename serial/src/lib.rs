@@ -7,7 +7,33 @@
 
 use core::hint;
 
-use x86::io;
+pub use flow_control::{
+    FlowControl,
+    XOFF,
+    XON,
+};
+pub use line_discipline::{
+    LineDiscipline,
+    Mode,
+    Received,
+};
+pub use port::{
+    IoPorts,
+    Ports,
+};
+
+/// Буфер принятых из последовательного порта байт с управлением потоком XON/XOFF.
+mod flow_control;
+
+/// Обработка принятых из последовательного порта байт.
+mod line_discipline;
+
+/// Доступ к портам ввода--вывода.
+mod port;
+
+/// Тесты.
+#[cfg(test)]
+mod test;
 
 pub trait Serial {
     fn new() -> Self;
@@ -16,67 +42,392 @@ pub trait Serial {
         &mut self,
         octet: u8,
     );
+
+    /// Возвращает самый старый из принятых байт, если такой есть.
+    /// Не ждёт поступления данных.
+    fn read_octet(&mut self) -> Option<u8>;
 }
 
-pub struct Com {}
+/// [Универсальный асинхронный приёмопередатчик](https://en.wikipedia.org/wiki/16550_UART),
+/// обслуживающий
+/// [последовательный порт](https://en.wikipedia.org/wiki/Serial_port)
+/// с базовым портом ввода--вывода [`Com::base()`].
+pub struct Com<P: Ports = IoPorts> {
+    /// Базовый порт ввода--вывода, например [`COM1`] или [`COM2`].
+    base: u16,
 
-impl Serial for Com {
-    fn new() -> Self {
-        const COM1_LSB: u16 = 0x03F8;
-        const COM1_MSB: u16 = 0x03F9;
-        const COM1_FIFO: u16 = 0x03FA;
-        const COM1_LINE: u16 = 0x03FB;
-
-        unsafe fn out_u16(value: u16) {
-            unsafe {
-                io::outb(COM1_LSB, value as u8);
-                io::outb(COM1_MSB, (value >> 8) as u8);
+    /// Буфер принятых байт с управлением потоком XON/XOFF, по умолчанию выключенным.
+    flow_control: FlowControl<INPUT_BUFFER_SIZE>,
+
+    /// Дисциплина линии для [`Com::receive()`], по умолчанию в режиме [`Mode::Raw`].
+    line_discipline: LineDiscipline<LINE_SIZE>,
+
+    /// Доступ к портам ввода--вывода.
+    ports: P,
+}
+
+impl Com {
+    /// Инициализирует последовательный порт с базовым портом ввода--вывода `base`.
+    pub fn new_port(base: u16) -> Self {
+        Self::with_ports(base, IoPorts)
+    }
+}
+
+impl<P: Ports> Com<P> {
+    /// Инициализирует последовательный порт с базовым портом ввода--вывода `base`,
+    /// обращаясь к портам ввода--вывода через `ports`.
+    pub fn with_ports(
+        base: u16,
+        ports: P,
+    ) -> Self {
+        let mut com = Self {
+            base,
+            flow_control: FlowControl::new(false),
+            line_discipline: LineDiscipline::new(Mode::Raw),
+            ports,
+        };
+
+        // 1|0|001|0|11 = enable speed change|break disable|odd parity|1 stop bit|8 data bits
+        com.write(LINE_CONTROL, 0b_1_0_001_0_11);
+
+        // (msb << 8) | lsb == 1.8432 MHz / (16 * speed_in_bauds) ==
+        //   1843200 / (16 * speed_in_bauds) == 115200 / speed_in_bauds.
+        // Standard speeds are (in bauds):
+        //   50, 75, 100, 110, 200, 300, 600, 1200, 2400, 4800,
+        //   9600, 19200, 38400, 57600, 115200.
+        const BASE_NUMERATOR: u32 = 115200;
+        const SPEED_IN_BAUDS: u32 = 9600;
+        let divisor: u16 = (BASE_NUMERATOR / SPEED_IN_BAUDS).try_into().expect("invalid speed");
+        com.write(DIVISOR_LSB, divisor as u8);
+        com.write(DIVISOR_MSB, (divisor >> 8) as u8);
+
+        com.write(LINE_CONTROL, 0x0B);
+
+        // Enable FIFO, clear both buffers and raise the receive interrupt on every octet.
+        com.write(
+            FIFO_CONTROL,
+            FIFO_ENABLE | CLEAR_RECEIVE_FIFO | CLEAR_TRANSMIT_FIFO | RECEIVE_TRIGGER_1,
+        );
+
+        com.write(MODEM_CONTROL, MODEM_CONTROL_NORMAL);
+        com.write(INTERRUPT_ENABLE, RECEIVED_DATA_AVAILABLE);
+
+        com
+    }
+
+    /// Базовый порт ввода--вывода.
+    pub fn base(&self) -> u16 {
+        self.base
+    }
+
+    /// Включает или выключает
+    /// [управление потоком XON/XOFF](https://en.wikipedia.org/wiki/Software_flow_control).
+    pub fn set_flow_control(
+        &mut self,
+        enabled: bool,
+    ) {
+        if let Some(control) = self.flow_control.set_enabled(enabled) {
+            self.transmit(control);
+        }
+    }
+
+    /// Устанавливает режим обработки принятых байт методом [`Com::receive()`].
+    /// Недособранная строка при этом отбрасывается.
+    pub fn set_mode(
+        &mut self,
+        mode: Mode,
+    ) {
+        self.line_discipline.set_mode(mode);
+    }
+
+    /// Включает или выключает
+    /// [режим петли](https://en.wikipedia.org/wiki/Loopback#Communications_channels),
+    /// в котором все отправленные байты попадают не в линию, а обратно в приёмник.
+    pub fn set_loopback(
+        &mut self,
+        loopback: bool,
+    ) {
+        let modem_control = if loopback {
+            MODEM_CONTROL_NORMAL | LOOPBACK
+        } else {
+            MODEM_CONTROL_NORMAL
+        };
+
+        self.write(MODEM_CONTROL, modem_control);
+    }
+
+    /// Проверяет работоспособность приёмопередатчика:
+    /// в [режиме петли](https://en.wikipedia.org/wiki/Loopback#Communications_channels)
+    /// отправляет тестовый байт и проверяет, что принят именно он.
+    /// После проверки возвращает приёмопередатчик в обычный режим.
+    ///
+    /// Принятые до проверки байты сохраняются в буфере [`Com::flow_control`].
+    ///
+    /// Возвращает `true`, если проверка прошла успешно.
+    pub fn self_test(&mut self) -> bool {
+        const MAX_ITERATIONS: usize = 1 << 16;
+        const TEST_OCTET: u8 = 0xAE;
+
+        self.poll();
+
+        self.set_loopback(true);
+        self.transmit(TEST_OCTET);
+
+        let received = (0 .. MAX_ITERATIONS).find_map(|_| {
+            if self.read(LINE_STATUS) & DATA_READY != 0 {
+                Some(self.read(DATA))
+            } else {
+                hint::spin_loop();
+                None
             }
+        });
+
+        self.set_loopback(false);
+
+        received == Some(TEST_OCTET)
+    }
+
+    /// Возвращает количество принятых байт, ещё не прочитанных методом [`Com::read_octet()`].
+    pub fn input_len(&self) -> usize {
+        self.flow_control.len()
+    }
+
+    /// Возвращает `true`, если есть принятые байты, которые можно прочитать
+    /// методом [`Com::read_octet()`].
+    /// Сами байты при этом не читает.
+    pub fn has_data(&mut self) -> bool {
+        self.input_len() > 0 || self.read(LINE_STATUS) & DATA_READY != 0
+    }
+
+    /// Возвращает самый старый из принятых байт, если такой есть.
+    /// Не ждёт поступления данных, так что подходит для опроса в цикле
+    /// вместе с [`Com::has_data()`].
+    pub fn read_octet(&mut self) -> Option<u8> {
+        self.poll();
+
+        let (octet, control) = self.flow_control.read();
+        if let Some(control) = control {
+            self.transmit(control);
         }
 
-        unsafe {
-            // 1|0|001|0|11 = enable speed change|break disable|odd parity|1 stop bit|8 data bits
-            io::outb(COM1_LINE, 0b_1_0_001_0_11);
-
-            // (msb << 8) | lsb == 1.8432 MHz / (16 * speed_in_bauds) ==
-            //   1843200 / (16 * speed_in_bauds) == 115200 / speed_in_bauds.
-            // Standard speeds are (in bauds):
-            //   50, 75, 100, 110, 200, 300, 600, 1200, 2400, 4800,
-            //   9600, 19200, 38400, 57600, 115200.
-            const BASE_NUMERATOR: u32 = 115200;
-            const SPEED_IN_BAUDS: u32 = 9600;
-            out_u16((BASE_NUMERATOR / SPEED_IN_BAUDS).try_into().expect("invalid speed"));
-
-            io::outb(COM1_LINE, 0x0B);
-
-            // Reset and clear buffers.
-            io::outb(COM1_FIFO, 0x07);
+        octet
+    }
+
+    /// Читает самый старый из принятых байт и обрабатывает его
+    /// [дисциплиной линии](https://en.wikipedia.org/wiki/Line_discipline)
+    /// в режиме, заданном [`Com::set_mode()`].
+    /// В режиме [`Mode::Cooked`] отправляет эхо обратно в порт,
+    /// а при завершении строки запрашивает метку времени у `now`.
+    ///
+    /// Возвращает то, что нужно передать получателю, если такое есть.
+    /// Не ждёт поступления данных.
+    pub fn receive<T>(
+        &mut self,
+        now: impl FnOnce() -> T,
+    ) -> Option<Received<'_, T>> {
+        let octet = self.read_octet()?;
+
+        let mut echo = Echo::new();
+        let received = self.line_discipline.receive(octet, &mut echo, now);
+        for &octet in echo.octets() {
+            transmit(&mut self.ports, self.base, octet);
         }
 
-        Self {}
+        received
     }
 
-    fn print_octet(
+    /// Переносит байты, принятые портом, в буфер [`Com::flow_control`].
+    /// Вызывается из обработчика прерывания приёмопередатчика,
+    /// так как при инициализации включается прерывание по приёму данных.
+    ///
+    /// Возвращает количество перенесённых байт.
+    pub fn poll(&mut self) -> usize {
+        let mut count = 0;
+
+        while self.read(LINE_STATUS) & DATA_READY != 0 {
+            let octet = self.read(DATA);
+            if let Some(control) = self.flow_control.receive(octet) {
+                self.transmit(control);
+            }
+            count += 1;
+        }
+
+        count
+    }
+
+    /// Отправляет байт `octet`, не обращая внимания на управление потоком.
+    fn transmit(
         &mut self,
         octet: u8,
     ) {
-        fn transmitter_is_ready() -> bool {
-            const COM1_LINE_STATUS_REGISTER: u16 = 0x03FD;
-            const TRANSMITTER_HOLDING_REGISTER_EMPTY: u8 = 1 << 5;
+        transmit(&mut self.ports, self.base, octet);
+    }
 
-            let status = unsafe { io::inb(COM1_LINE_STATUS_REGISTER) };
+    /// Читает регистр `register` приёмопередатчика.
+    fn read(
+        &mut self,
+        register: u16,
+    ) -> u8 {
+        unsafe { self.ports.read(self.base + register) }
+    }
 
-            status & TRANSMITTER_HOLDING_REGISTER_EMPTY != 0
+    /// Записывает `data` в регистр `register` приёмопередатчика.
+    fn write(
+        &mut self,
+        register: u16,
+        data: u8,
+    ) {
+        unsafe {
+            self.ports.write(self.base + register, data);
         }
+    }
+}
 
-        const COM1_DATA: u16 = 0x03F8;
+impl<P: Ports + Default> Serial for Com<P> {
+    fn new() -> Self {
+        Self::with_ports(COM1, P::default())
+    }
 
-        while !transmitter_is_ready() {
+    fn print_octet(
+        &mut self,
+        octet: u8,
+    ) {
+        while self.flow_control.is_output_stopped() {
+            self.poll();
             hint::spin_loop();
         }
 
-        unsafe {
-            io::outb(COM1_DATA, octet);
+        self.transmit(octet);
+    }
+
+    fn read_octet(&mut self) -> Option<u8> {
+        Com::read_octet(self)
+    }
+}
+
+/// Эхо, которое дисциплина линии отправляет в ответ на один принятый байт.
+/// Накапливается, чтобы отправить его в порт уже после обработки байта в [`Com::receive()`].
+struct Echo {
+    /// Количество накопленных байт.
+    len: usize,
+
+    /// Накопленные байты.
+    octets: [u8; MAX_ECHO_SIZE],
+}
+
+impl Echo {
+    /// Накопленные байты эха.
+    fn octets(&self) -> &[u8] {
+        &self.octets[.. self.len]
+    }
+}
+
+impl Serial for Echo {
+    fn new() -> Self {
+        Self {
+            len: 0,
+            octets: [0; MAX_ECHO_SIZE],
         }
     }
+
+    fn print_octet(
+        &mut self,
+        octet: u8,
+    ) {
+        self.octets[self.len] = octet;
+        self.len += 1;
+    }
+
+    fn read_octet(&mut self) -> Option<u8> {
+        None
+    }
 }
+
+/// Отправляет байт `octet` в приёмопередатчик с базовым портом ввода--вывода `base`,
+/// обращаясь к портам ввода--вывода через `ports`.
+/// Не обращает внимания на управление потоком.
+fn transmit<P: Ports>(
+    ports: &mut P,
+    base: u16,
+    octet: u8,
+) {
+    const TRANSMITTER_HOLDING_REGISTER_EMPTY: u8 = 1 << 5;
+
+    while unsafe { ports.read(base + LINE_STATUS) } & TRANSMITTER_HOLDING_REGISTER_EMPTY == 0 {
+        hint::spin_loop();
+    }
+
+    unsafe {
+        ports.write(base + DATA, octet);
+    }
+}
+
+/// Базовый порт ввода--вывода первого последовательного порта.
+pub const COM1: u16 = 0x03F8;
+
+/// Базовый порт ввода--вывода второго последовательного порта.
+pub const COM2: u16 = 0x02F8;
+
+/// Регистр данных, смещение относительно базового порта.
+const DATA: u16 = 0;
+
+/// Младший байт делителя частоты, доступен вместо [`DATA`] при установленном бите
+/// `enable speed change` в [`LINE_CONTROL`].
+const DIVISOR_LSB: u16 = 0;
+
+/// Старший байт делителя частоты, доступен при установленном бите
+/// `enable speed change` в [`LINE_CONTROL`].
+const DIVISOR_MSB: u16 = 1;
+
+/// Регистр управления буферами FIFO.
+const FIFO_CONTROL: u16 = 2;
+
+/// Регистр разрешения прерываний, доступен при сброшенном бите
+/// `enable speed change` в [`LINE_CONTROL`].
+const INTERRUPT_ENABLE: u16 = 1;
+
+/// Регистр управления линией.
+const LINE_CONTROL: u16 = 3;
+
+/// Регистр состояния линии.
+const LINE_STATUS: u16 = 5;
+
+/// Регистр управления модемом.
+const MODEM_CONTROL: u16 = 4;
+
+/// Бит регистра [`FIFO_CONTROL`], очищающий буфер принятых байт.
+const CLEAR_RECEIVE_FIFO: u8 = 1 << 1;
+
+/// Бит регистра [`FIFO_CONTROL`], очищающий буфер отправляемых байт.
+const CLEAR_TRANSMIT_FIFO: u8 = 1 << 2;
+
+/// Бит регистра [`LINE_STATUS`], означающий что есть принятые данные.
+const DATA_READY: u8 = 1 << 0;
+
+/// Бит регистра [`FIFO_CONTROL`], включающий буферы FIFO.
+const FIFO_ENABLE: u8 = 1 << 0;
+
+/// Значение старших бит регистра [`FIFO_CONTROL`],
+/// при котором прерывание по приёму данных возникает после каждого принятого байта.
+const RECEIVE_TRIGGER_1: u8 = 0b_00 << 6;
+
+/// Бит регистра [`INTERRUPT_ENABLE`], разрешающий прерывание по приёму данных.
+const RECEIVED_DATA_AVAILABLE: u8 = 1 << 0;
+
+/// Бит регистра [`MODEM_CONTROL`], включающий режим петли.
+const LOOPBACK: u8 = 1 << 4;
+
+/// Значение регистра [`MODEM_CONTROL`] в обычном режиме ---
+/// включены сигналы `OUT2`, `RTS` и `DTR`.
+/// Без `OUT2` прерывания приёмопередатчика не доходят до контроллера прерываний.
+const MODEM_CONTROL_NORMAL: u8 = 0b_1_0_1_1;
+
+/// Размер буфера принятых байт.
+const INPUT_BUFFER_SIZE: usize = 256;
+
+/// Максимальная длина строки, собираемой в режиме [`Mode::Cooked`].
+const LINE_SIZE: usize = 256;
+
+/// Максимальная длина эха на один принятый байт ---
+/// последовательность [`line_discipline::ERASE`].
+const MAX_ECHO_SIZE: usize = line_discipline::ERASE.len();
@@ -0,0 +1,150 @@
+use super::Serial;
+
+/// Режим обработки принятых из
+/// [последовательного порта](https://en.wikipedia.org/wiki/Serial_port) байт.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Mode {
+    /// Байты передаются получателю как есть, по одному, без эха и редактирования.
+    Raw,
+
+    /// Байты собираются в строку с поддержкой удаления последнего символа,
+    /// каждый принятый байт отправляется обратно в порт для отображения удалённым терминалом.
+    /// Получатель видит только завершённые строки с меткой времени их завершения.
+    Cooked,
+}
+
+/// Результат обработки принятого байта.
+#[derive(Debug, Eq, PartialEq)]
+pub enum Received<'a, T> {
+    /// Байт, принятый в режиме [`Mode::Raw`].
+    Octet(u8),
+
+    /// Завершённая в режиме [`Mode::Cooked`] строка.
+    Line {
+        /// Время завершения строки.
+        timestamp: T,
+
+        /// Содержимое строки без символа её завершения.
+        text: &'a [u8],
+    },
+}
+
+/// [Дисциплина линии](https://en.wikipedia.org/wiki/Line_discipline) ---
+/// обработка принятых из
+/// [последовательного порта](https://en.wikipedia.org/wiki/Serial_port) байт.
+/// Собирает строки длиной до `N` байт.
+#[derive(Debug)]
+pub struct LineDiscipline<const N: usize> {
+    /// Предыдущий принятый байт был `\r`.
+    /// Нужно, чтобы не выдавать пустую строку на `\n` в последовательности `\r\n`.
+    carriage_return: bool,
+
+    /// Длина собираемой строки.
+    len: usize,
+
+    /// Буфер собираемой строки.
+    line: [u8; N],
+
+    /// Режим обработки принятых байт.
+    mode: Mode,
+}
+
+impl<const N: usize> LineDiscipline<N> {
+    /// Создаёт дисциплину линии в режиме `mode`.
+    pub const fn new(mode: Mode) -> Self {
+        Self {
+            carriage_return: false,
+            len: 0,
+            line: [0; N],
+            mode,
+        }
+    }
+
+    /// Режим обработки принятых байт.
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Устанавливает режим обработки принятых байт.
+    /// Недособранная строка при этом отбрасывается.
+    pub fn set_mode(
+        &mut self,
+        mode: Mode,
+    ) {
+        self.mode = mode;
+        self.carriage_return = false;
+        self.len = 0;
+    }
+
+    /// Обрабатывает байт `octet`, принятый из последовательного порта.
+    /// В режиме [`Mode::Cooked`] отправляет эхо в `serial`,
+    /// а при завершении строки запрашивает метку времени у `now`.
+    ///
+    /// Возвращает то, что нужно передать получателю, если такое есть.
+    pub fn receive<S: Serial, T>(
+        &mut self,
+        octet: u8,
+        serial: &mut S,
+        now: impl FnOnce() -> T,
+    ) -> Option<Received<'_, T>> {
+        if self.mode == Mode::Raw {
+            return Some(Received::Octet(octet));
+        }
+
+        let carriage_return = self.carriage_return;
+        self.carriage_return = octet == b'\r';
+
+        match octet {
+            b'\n' if carriage_return => None,
+            b'\r' | b'\n' => {
+                print(serial, b"\r\n");
+                let len = self.len;
+                self.len = 0;
+                Some(Received::Line {
+                    timestamp: now(),
+                    text: &self.line[.. len],
+                })
+            },
+            BACKSPACE | DELETE => {
+                if self.len > 0 {
+                    self.len -= 1;
+                    print(serial, ERASE);
+                }
+                None
+            },
+            _ => {
+                if self.len < N {
+                    self.line[self.len] = octet;
+                    self.len += 1;
+                    serial.print_octet(octet);
+                } else {
+                    serial.print_octet(BELL);
+                }
+                None
+            },
+        }
+    }
+}
+
+/// Отправляет в последовательный порт `serial` байты `octets`.
+fn print<S: Serial>(
+    serial: &mut S,
+    octets: &[u8],
+) {
+    for &octet in octets {
+        serial.print_octet(octet);
+    }
+}
+
+/// Код клавиши `Backspace`.
+const BACKSPACE: u8 = 0x08;
+
+/// Код символа `BEL`, отправляется в ответ на переполнение строки.
+const BELL: u8 = 0x07;
+
+/// Код клавиши `Backspace` в некоторых терминалах.
+const DELETE: u8 = 0x7F;
+
+/// Последовательность, стирающая на терминале последний символ:
+/// возврат на позицию назад, затирание пробелом и снова возврат на позицию назад.
+pub(super) const ERASE: &[u8] = b"\x08 \x08";
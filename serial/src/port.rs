@@ -0,0 +1,55 @@
+use x86::io;
+
+/// Типаж доступа к
+/// [портам ввода--вывода](https://en.wikipedia.org/wiki/Memory-mapped_I/O_and_port-mapped_I/O).
+///
+/// Типаж используется для того, чтобы в тестах можно было создать эмуляцию
+/// портов ввода--вывода и проверить корректность работы кода с ними.
+/// В обычном же режиме используется реализация [`IoPorts`] этого типажа,
+/// которая работает с настоящими портами ввода--вывода.
+pub trait Ports {
+    /// Читает байт из порта `port`.
+    ///
+    /// # Safety
+    ///
+    /// Определяется спецификацией оборудования.
+    unsafe fn read(
+        &mut self,
+        port: u16,
+    ) -> u8;
+
+    /// Записывает байт `data` в порт `port`.
+    ///
+    /// # Safety
+    ///
+    /// Определяется спецификацией оборудования.
+    unsafe fn write(
+        &mut self,
+        port: u16,
+        data: u8,
+    );
+}
+
+/// Настоящие
+/// [порты ввода--вывода](https://en.wikipedia.org/wiki/Memory-mapped_I/O_and_port-mapped_I/O).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct IoPorts;
+
+impl Ports for IoPorts {
+    unsafe fn read(
+        &mut self,
+        port: u16,
+    ) -> u8 {
+        unsafe { io::inb(port) }
+    }
+
+    unsafe fn write(
+        &mut self,
+        port: u16,
+        data: u8,
+    ) {
+        unsafe {
+            io::outb(port, data);
+        }
+    }
+}
@@ -0,0 +1,402 @@
+use super::{
+    COM1,
+    COM2,
+    Com,
+    DATA,
+    FIFO_CONTROL,
+    FlowControl,
+    LINE_STATUS,
+    LineDiscipline,
+    MODEM_CONTROL,
+    Mode,
+    Ports,
+    Received,
+    Serial,
+    XOFF,
+    XON,
+    line_discipline::ERASE,
+};
+
+struct MockSerial {
+    len: usize,
+    output: [u8; OUTPUT_SIZE],
+}
+
+impl Serial for MockSerial {
+    fn new() -> Self {
+        Self {
+            len: 0,
+            output: [0; OUTPUT_SIZE],
+        }
+    }
+
+    fn print_octet(
+        &mut self,
+        octet: u8,
+    ) {
+        self.output[self.len] = octet;
+        self.len += 1;
+    }
+
+    fn read_octet(&mut self) -> Option<u8> {
+        None
+    }
+}
+
+impl MockSerial {
+    fn output(&self) -> &[u8] {
+        &self.output[.. self.len]
+    }
+}
+
+#[test]
+fn cooked_mode() {
+    let mut serial = MockSerial::new();
+    let mut line_discipline = LineDiscipline::<LINE_SIZE>::new(Mode::Cooked);
+    let mut timestamp = 0;
+
+    for &octet in b"helo\x08\x08llo\r\n" {
+        timestamp += 1;
+        match line_discipline.receive(octet, &mut serial, || timestamp) {
+            Some(received) => assert_eq!(
+                received,
+                Received::Line {
+                    timestamp: 10,
+                    text: b"hello",
+                },
+            ),
+            None => assert_ne!(octet, b'\r'),
+        }
+    }
+
+    let mut expected = [0; OUTPUT_SIZE];
+    let mut len = 0;
+    for part in [b"helo".as_slice(), ERASE, ERASE, b"llo\r\n"] {
+        expected[len .. len + part.len()].copy_from_slice(part);
+        len += part.len();
+    }
+    assert_eq!(serial.output(), &expected[.. len]);
+
+    let received = line_discipline.receive(b'\n', &mut serial, || timestamp);
+    assert_eq!(
+        received,
+        Some(Received::Line {
+            timestamp,
+            text: b"",
+        }),
+    );
+}
+
+#[test]
+fn cooked_mode_limits() {
+    let mut serial = MockSerial::new();
+    let mut line_discipline = LineDiscipline::<2>::new(Mode::Cooked);
+
+    assert_eq!(line_discipline.receive(0x7F, &mut serial, || 0), None);
+    for &octet in b"abc" {
+        assert_eq!(line_discipline.receive(octet, &mut serial, || 0), None);
+    }
+    assert_eq!(serial.output(), b"ab\x07");
+
+    assert_eq!(
+        line_discipline.receive(b'\r', &mut serial, || 0),
+        Some(Received::Line {
+            timestamp: 0,
+            text: b"ab",
+        }),
+    );
+}
+
+#[test]
+fn raw_mode() {
+    let mut serial = MockSerial::new();
+    let mut line_discipline = LineDiscipline::<LINE_SIZE>::new(Mode::Raw);
+
+    for &octet in b"a\x08\r\n" {
+        assert_eq!(
+            line_discipline.receive(octet, &mut serial, || 0),
+            Some(Received::Octet(octet)),
+        );
+    }
+
+    assert!(serial.output().is_empty());
+}
+
+#[test]
+fn flow_control() {
+    type Buffer = FlowControl<FLOW_CONTROL_BUFFER_SIZE>;
+
+    let mut flow_control = Buffer::new(true);
+
+    for octet in 0 .. Buffer::HIGH_WATERMARK - 1 {
+        assert_eq!(flow_control.receive(octet as u8), None);
+    }
+    assert!(!flow_control.is_input_stopped());
+
+    assert_eq!(flow_control.receive(b'*'), Some(XOFF));
+    assert!(flow_control.is_input_stopped());
+
+    for _ in Buffer::HIGH_WATERMARK .. FLOW_CONTROL_BUFFER_SIZE + 2 {
+        assert_eq!(flow_control.receive(b'#'), None);
+    }
+    assert_eq!(flow_control.len(), FLOW_CONTROL_BUFFER_SIZE);
+
+    for octet in 0 .. FLOW_CONTROL_BUFFER_SIZE - Buffer::LOW_WATERMARK - 1 {
+        let (received, control) = flow_control.read();
+        assert!(received.is_some());
+        assert_eq!(control, None, "octet = {octet}");
+    }
+
+    let (received, control) = flow_control.read();
+    assert!(received.is_some());
+    assert_eq!(control, Some(XON));
+    assert!(!flow_control.is_input_stopped());
+    assert_eq!(flow_control.len(), Buffer::LOW_WATERMARK);
+
+    while !flow_control.is_empty() {
+        assert_eq!(flow_control.read().1, None);
+    }
+    assert_eq!(flow_control.read(), (None, None));
+}
+
+#[test]
+fn flow_control_order_and_output() {
+    let mut flow_control = FlowControl::<FLOW_CONTROL_BUFFER_SIZE>::new(true);
+
+    assert_eq!(flow_control.receive(b'a'), None);
+    assert_eq!(flow_control.receive(XOFF), None);
+    assert!(flow_control.is_output_stopped());
+    assert_eq!(flow_control.receive(b'b'), None);
+    assert_eq!(flow_control.receive(XON), None);
+    assert!(!flow_control.is_output_stopped());
+
+    assert_eq!(flow_control.read(), (Some(b'a'), None));
+    assert_eq!(flow_control.read(), (Some(b'b'), None));
+    assert_eq!(flow_control.read(), (None, None));
+
+    for _ in 0 .. FLOW_CONTROL_BUFFER_SIZE {
+        flow_control.receive(b'*');
+    }
+    assert!(flow_control.is_input_stopped());
+    assert_eq!(flow_control.set_enabled(false), Some(XON));
+    assert_eq!(flow_control.receive(XOFF), None);
+    assert!(!flow_control.is_output_stopped());
+}
+
+#[test]
+fn flow_control_disabled() {
+    let mut flow_control = FlowControl::<FLOW_CONTROL_BUFFER_SIZE>::new(false);
+
+    for _ in 0 .. 2 * FLOW_CONTROL_BUFFER_SIZE {
+        assert_eq!(flow_control.receive(XOFF), None);
+    }
+
+    assert!(!flow_control.is_input_stopped());
+    assert!(!flow_control.is_output_stopped());
+    assert_eq!(flow_control.len(), FLOW_CONTROL_BUFFER_SIZE);
+    assert_eq!(flow_control.read(), (Some(XOFF), None));
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+enum Access {
+    #[default]
+    None,
+    Read(u16),
+    Write(u16, u8),
+}
+
+#[derive(Default)]
+struct MockPorts {
+    accesses: [Access; ACCESS_COUNT],
+    len: usize,
+    loopback_works: bool,
+    modem_control: u8,
+    received: Option<u8>,
+}
+
+impl MockPorts {
+    fn new(loopback_works: bool) -> Self {
+        Self {
+            loopback_works,
+            ..Self::default()
+        }
+    }
+
+    fn accesses(&self) -> &[Access] {
+        &self.accesses[.. self.len]
+    }
+
+    fn log(
+        &mut self,
+        access: Access,
+    ) {
+        if let Some(slot) = self.accesses.get_mut(self.len) {
+            *slot = access;
+            self.len += 1;
+        }
+    }
+}
+
+impl Ports for MockPorts {
+    unsafe fn read(
+        &mut self,
+        port: u16,
+    ) -> u8 {
+        const DATA_READY: u8 = 1 << 0;
+        const TRANSMITTER_HOLDING_REGISTER_EMPTY: u8 = 1 << 5;
+
+        self.log(Access::Read(port));
+
+        match port % 8 {
+            DATA => self.received.take().unwrap_or_default(),
+            LINE_STATUS =>
+                TRANSMITTER_HOLDING_REGISTER_EMPTY |
+                    if self.received.is_some() {
+                        DATA_READY
+                    } else {
+                        0
+                    },
+            _ => 0,
+        }
+    }
+
+    unsafe fn write(
+        &mut self,
+        port: u16,
+        data: u8,
+    ) {
+        const LOOPBACK: u8 = 1 << 4;
+
+        self.log(Access::Write(port, data));
+
+        match port % 8 {
+            DATA if self.loopback_works && self.modem_control & LOOPBACK != 0 =>
+                self.received = Some(data),
+            MODEM_CONTROL => self.modem_control = data,
+            _ => {},
+        }
+    }
+}
+
+#[test]
+fn com_port() {
+    let com = Com::<MockPorts>::new();
+    assert_eq!(com.base(), COM1);
+
+    for base in [COM1, COM2] {
+        let mut com = Com::with_ports(base, MockPorts::default());
+        assert_eq!(com.base(), base);
+        com.print_octet(b'*');
+
+        let accesses = com.ports.accesses();
+        let ports = base .. base + 8;
+
+        assert!(accesses.iter().all(|access| match *access {
+            Access::None => false,
+            Access::Read(port) | Access::Write(port, _) => ports.contains(&port),
+        }));
+
+        assert!(accesses.contains(&Access::Write(base + 3, 0x0B)));
+        assert!(accesses.contains(&Access::Write(base, 12)));
+        assert!(accesses.contains(&Access::Write(base + 1, 0)));
+        assert!(accesses.contains(&Access::Read(base + 5)));
+        assert_eq!(accesses.last(), Some(&Access::Write(base, b'*')));
+    }
+}
+
+#[test]
+fn self_test() {
+    for loopback_works in [false, true] {
+        let mut com = Com::with_ports(COM2, MockPorts::new(loopback_works));
+        com.ports.received = Some(b'*');
+
+        let start = com.ports.len;
+        assert_eq!(com.self_test(), loopback_works);
+        let accesses = &com.ports.accesses()[start ..];
+
+        if loopback_works {
+            let mut modem_control = [0; 2];
+            let mut count = 0;
+            for access in accesses {
+                if let Access::Write(port, data) = *access &&
+                    port == COM2 + MODEM_CONTROL
+                {
+                    modem_control[count] = data;
+                    count += 1;
+                }
+            }
+            assert_eq!(&modem_control[.. count], [0x1B, 0x0B]);
+            assert!(accesses.contains(&Access::Write(COM2 + DATA, 0xAE)));
+        }
+        assert_eq!(com.ports.modem_control, 0x0B);
+
+        assert_eq!(com.read_octet(), Some(b'*'));
+        assert_eq!(com.read_octet(), None);
+    }
+}
+
+#[test]
+fn com_receive() {
+    let mut com = Com::with_ports(COM1, MockPorts::default());
+    assert!(com.ports.accesses().contains(&Access::Write(COM1 + FIFO_CONTROL, 0x07)));
+
+    assert!(!com.has_data());
+    assert_eq!(receive(&mut com), None);
+
+    com.ports.received = Some(b'*');
+    assert!(com.has_data());
+    assert!(
+        com.has_data(),
+        "has_data() should not consume the received octet"
+    );
+    assert_eq!(receive(&mut com), Some(b'*'));
+
+    assert!(!com.has_data());
+    assert_eq!(receive(&mut com), None);
+
+    fn receive<S: Serial>(serial: &mut S) -> Option<u8> {
+        serial.read_octet()
+    }
+}
+
+#[test]
+fn com_cooked_mode() {
+    let mut com = Com::with_ports(COM1, MockPorts::default());
+
+    com.ports.received = Some(b'\r');
+    assert_eq!(com.receive(|| 0), Some(Received::Octet(b'\r')));
+
+    com.set_mode(Mode::Cooked);
+
+    for (&octet, echo) in b"ax\x08b".iter().zip([b"a".as_slice(), b"x", ERASE, b"b"]) {
+        com.ports.received = Some(octet);
+        com.ports.len = 0;
+        assert_eq!(com.receive(|| 0), None);
+        assert!(written(&com.ports).eq(echo.iter().copied()));
+    }
+
+    com.ports.received = Some(b'\r');
+    com.ports.len = 0;
+    assert_eq!(
+        com.receive(|| 7),
+        Some(Received::Line {
+            timestamp: 7,
+            text: b"ab",
+        }),
+    );
+    assert!(written(&com.ports).eq(b"\r\n".iter().copied()));
+
+    assert_eq!(com.receive(|| 0), None);
+
+    fn written(ports: &MockPorts) -> impl Iterator<Item = u8> + '_ {
+        ports.accesses().iter().filter_map(|access| match *access {
+            Access::Write(port, data) if port == COM1 + DATA => Some(data),
+            _ => None,
+        })
+    }
+}
+
+const ACCESS_COUNT: usize = 32;
+const FLOW_CONTROL_BUFFER_SIZE: usize = 16;
+const LINE_SIZE: usize = 16;
+const OUTPUT_SIZE: usize = 64;
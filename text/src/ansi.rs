@@ -0,0 +1,216 @@
+use core::str;
+
+use super::{
+    Attribute,
+    Color,
+};
+
+/// Результат обработки очередного символа в [`Parser::feed()`].
+#[derive(Debug, Eq, PartialEq)]
+pub(super) enum Output<'a> {
+    /// Символ является частью ещё не законченной управляющей последовательности.
+    Pending,
+
+    /// Закончилась поддерживаемая последовательность
+    /// [SGR](https://en.wikipedia.org/wiki/ANSI_escape_code#SGR),
+    /// после которой нужно печатать с заданными атрибутами.
+    SetAttribute(Attribute),
+
+    /// Текст, который нужно напечатать как есть ---
+    /// либо обычный символ, либо неподдерживаемая управляющая последовательность целиком.
+    Text(&'a str),
+}
+
+/// Конечный автомат для разбора минимального подмножества
+/// [управляющих последовательностей ANSI](https://en.wikipedia.org/wiki/ANSI_escape_code).
+///
+/// Поддерживает только последовательности
+/// [SGR](https://en.wikipedia.org/wiki/ANSI_escape_code#SGR) вида `\x1b[...m` с параметрами:
+///   - `0` или пустым --- сброс атрибутов в [`Parser::DEFAULT_ATTRIBUTE`];
+///   - `1` --- яркий цвет символов;
+///   - `5` и `25` --- включение и выключение мерцания символов;
+///   - `30`--`37` --- цвет символов;
+///   - `40`--`47` --- цвет фона.
+///
+/// Хранит незаконченную последовательность между вызовами,
+/// так что она может быть разбита между несколькими вызовами [`core::fmt::Write::write_str()`].
+pub(super) struct Parser {
+    /// Накопленная незаконченная управляющая последовательность,
+    /// а после её окончания --- последовательность целиком.
+    buffer: [u8; Parser::BUFFER_SIZE],
+
+    /// Длина накопленной последовательности.
+    len: usize,
+
+    /// Состояние автомата.
+    state: State,
+}
+
+/// Состояние автомата [`Parser`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum State {
+    /// Обычный текст.
+    Ground,
+
+    /// Прочитан символ `\x1b`.
+    Escape,
+
+    /// Прочитано начало последовательности `\x1b[`,
+    /// ожидаются параметры или завершающий символ.
+    Csi,
+}
+
+impl Parser {
+    /// Создаёт автомат в состоянии обычного текста.
+    pub(super) const fn new() -> Self {
+        Self {
+            buffer: [0; Self::BUFFER_SIZE],
+            len: 0,
+            state: State::Ground,
+        }
+    }
+
+    /// Обрабатывает очередной символ `ch`.
+    /// Атрибуты, заданные последовательностью SGR, вычисляет относительно текущих `attribute`
+    /// для режима экрана `blink_enabled`, см. [`Attribute::new()`].
+    pub(super) fn feed(
+        &mut self,
+        ch: char,
+        attribute: Attribute,
+        blink_enabled: bool,
+    ) -> Output<'_> {
+        match (self.state, ch) {
+            (State::Ground, ESCAPE) => {
+                self.start(ch);
+                Output::Pending
+            },
+            (State::Ground, _) => {
+                self.len = 0;
+                self.push(ch);
+                self.text()
+            },
+            (State::Escape, '[') => {
+                self.push(ch);
+                self.state = State::Csi;
+                Output::Pending
+            },
+            (State::Csi, '0' ..= '9' | ';') if self.len < Self::MAX_SEQUENCE_LEN => {
+                self.push(ch);
+                Output::Pending
+            },
+            (State::Csi, 'm') => {
+                let sgr = self.sgr(attribute, blink_enabled);
+                self.push(ch);
+                self.state = State::Ground;
+                sgr.map_or_else(|| self.text(), Output::SetAttribute)
+            },
+            _ => {
+                self.push(ch);
+                self.state = State::Ground;
+                self.text()
+            },
+        }
+    }
+
+    /// Начинает новую управляющую последовательность с символа `ch`.
+    fn start(
+        &mut self,
+        ch: char,
+    ) {
+        self.len = 0;
+        self.push(ch);
+        self.state = State::Escape;
+    }
+
+    /// Дописывает символ `ch` в [`Parser::buffer`].
+    fn push(
+        &mut self,
+        ch: char,
+    ) {
+        self.len += ch.encode_utf8(&mut self.buffer[self.len ..]).len();
+    }
+
+    /// Возвращает накопленную последовательность как текст, который нужно напечатать как есть.
+    fn text(&self) -> Output<'_> {
+        Output::Text(
+            str::from_utf8(&self.buffer[.. self.len])
+                .expect("the buffer contains only whole chars"),
+        )
+    }
+
+    /// Разбирает параметры накопленной последовательности `\x1b[...` как параметры SGR
+    /// и применяет их к атрибутам `attribute` режима экрана `blink_enabled`.
+    /// Возвращает [`None`], если среди параметров есть неподдерживаемые.
+    fn sgr(
+        &self,
+        attribute: Attribute,
+        blink_enabled: bool,
+    ) -> Option<Attribute> {
+        let parameters = str::from_utf8(&self.buffer[PREFIX_LEN .. self.len]).ok()?;
+
+        let mut foreground = attribute.foreground();
+        let mut background = attribute.background();
+        let mut blink = attribute.is_blinking();
+
+        for parameter in parameters.split(';') {
+            match parameter {
+                "" | "0" => {
+                    foreground = Self::DEFAULT_ATTRIBUTE.foreground();
+                    background = Self::DEFAULT_ATTRIBUTE.background();
+                    blink = Self::DEFAULT_ATTRIBUTE.is_blinking();
+                },
+                "1" => foreground |= Color::LIGHT,
+                "5" => blink = true,
+                "25" => blink = false,
+                _ => {
+                    let code = parameter.parse::<u8>().ok()?;
+                    let color = *COLORS.get(usize::from(code % 10))?;
+                    match code / 10 {
+                        3 => foreground = color | (foreground & Color::LIGHT),
+                        4 => background = color,
+                        _ => return None,
+                    }
+                },
+            }
+        }
+
+        let attribute = Attribute::new(foreground, background, blink_enabled);
+
+        // Without the blink mode the blink bit means a bright background.
+        if blink_enabled {
+            Some(attribute.with_blink(blink))
+        } else {
+            Some(attribute)
+        }
+    }
+
+    /// Атрибуты, которые устанавливает сброс `\x1b[0m`.
+    pub(super) const DEFAULT_ATTRIBUTE: Attribute =
+        Attribute::new(Color::GRAY, Color::BLACK, false);
+
+    /// Размер [`Parser::buffer`] --- с местом под один произвольный символ UTF-8,
+    /// который завершает последовательность максимальной длины.
+    const BUFFER_SIZE: usize = Self::MAX_SEQUENCE_LEN + 4;
+
+    /// Максимальная длина незаконченной управляющей последовательности в байтах.
+    /// Более длинные последовательности считаются неподдерживаемыми.
+    const MAX_SEQUENCE_LEN: usize = 16;
+}
+
+/// Цвета VGA, соответствующие цветам ANSI с номерами от `0` до `7`.
+const COLORS: [Color; 8] = [
+    Color::BLACK,
+    Color::RED,
+    Color::GREEN,
+    Color::BROWN,
+    Color::BLUE,
+    Color::MAGENTA,
+    Color::CYAN,
+    Color::GRAY,
+];
+
+/// Символ, с которого начинается управляющая последовательность.
+const ESCAPE: char = '\x1b';
+
+/// Длина префикса `\x1b[` последовательности SGR.
+const PREFIX_LEN: usize = 2;
@@ -0,0 +1,70 @@
+/// Возвращает код символа `ch` в
+/// [кодовой странице CP437](https://en.wikipedia.org/wiki/Code_page_437),
+/// которую использует текстовый режим графического контроллера
+/// [Video Graphics Array (VGA)](https://en.wikipedia.org/wiki/Video_Graphics_Array).
+///
+/// - Печатные символы [ASCII](https://en.wikipedia.org/wiki/ASCII) отображаются сами в себя.
+/// - Символы псевдографики, стрелки, буквы Latin-1 и прочие символы,
+///   имеющие графическое представление в CP437, отображаются в свои коды CP437.
+/// - Управляющие и остальные символы отображаются в `?`.
+pub(super) const fn encode(ch: char) -> u8 {
+    if ch.is_ascii() {
+        let octet = ch as u8;
+        return if octet >= b' ' && octet < DELETE {
+            octet
+        } else {
+            UNKNOWN
+        };
+    }
+
+    let mut code = 0;
+    while code < TABLE.len() {
+        if TABLE[code] == ch {
+            return code as u8;
+        }
+        code += 1;
+    }
+
+    UNKNOWN
+}
+
+/// Код символа ASCII `DEL`, он не является печатным.
+/// Под этим кодом CP437 отображает символ `⌂`.
+const DELETE: u8 = 0x7F;
+
+/// Символ, которым отображаются не имеющие представления в CP437 символы.
+const UNKNOWN: u8 = b'?';
+
+/// Таблица символов [CP437](https://en.wikipedia.org/wiki/Code_page_437) по их кодам.
+/// Печатные символы ASCII обрабатываются отдельно,
+/// а на их местах, как и на месте кода `0`, стоят заглушки `\0`.
+#[rustfmt::skip]
+const TABLE: [char; 0x100] = [
+    // 0x00
+    '\0', '☺', '☻', '♥', '♦', '♣', '♠', '•', '◘', '○', '◙', '♂', '♀', '♪', '♫', '☼',
+    // 0x10
+    '►', '◄', '↕', '‼', '¶', '§', '▬', '↨', '↑', '↓', '→', '←', '∟', '↔', '▲', '▼',
+    // 0x20 .. 0x7E --- печатные символы ASCII.
+    '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0',
+    '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0',
+    '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0',
+    '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0',
+    '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0',
+    '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '⌂',
+    // 0x80
+    'Ç', 'ü', 'é', 'â', 'ä', 'à', 'å', 'ç', 'ê', 'ë', 'è', 'ï', 'î', 'ì', 'Ä', 'Å',
+    // 0x90
+    'É', 'æ', 'Æ', 'ô', 'ö', 'ò', 'û', 'ù', 'ÿ', 'Ö', 'Ü', '¢', '£', '¥', '₧', 'ƒ',
+    // 0xA0
+    'á', 'í', 'ó', 'ú', 'ñ', 'Ñ', 'ª', 'º', '¿', '⌐', '¬', '½', '¼', '¡', '«', '»',
+    // 0xB0
+    '░', '▒', '▓', '│', '┤', '╡', '╢', '╖', '╕', '╣', '║', '╗', '╝', '╜', '╛', '┐',
+    // 0xC0
+    '└', '┴', '┬', '├', '─', '┼', '╞', '╟', '╚', '╔', '╩', '╦', '╠', '═', '╬', '╧',
+    // 0xD0
+    '╨', '╤', '╥', '╙', '╘', '╒', '╓', '╫', '╪', '┘', '┌', '█', '▄', '▌', '▐', '▀',
+    // 0xE0
+    'α', 'ß', 'Γ', 'π', 'Σ', 'σ', 'µ', 'τ', 'Φ', 'Θ', 'Ω', 'δ', '∞', 'φ', 'ε', '∩',
+    // 0xF0
+    '≡', '±', '≥', '≤', '⌠', '⌡', '÷', '≈', '°', '∙', '·', '√', 'ⁿ', '²', '■', '\u{A0}',
+];
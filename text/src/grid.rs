@@ -1,5 +1,6 @@
 use core::{
     cmp,
+    mem,
     ops::{
         Deref,
         DerefMut,
@@ -9,13 +10,26 @@ use core::{
 
 use volatile::Volatile;
 
+use ku::error::{
+    Error::InvalidArgument,
+    Result,
+};
+
 use serial::Serial;
 
 use super::{
     Attribute,
+    COLUMN_COUNT,
     Color,
+    ROW_COUNT,
+    cp437,
+    scrollback::Scrollback,
 };
 
+// Used in docs.
+#[allow(unused)]
+use ku::error::Error;
+
 /// Возвращает `true`, если `octet` соответствует символу
 /// [ASCII (American Standard Code for Information Interchange)](https://en.wikipedia.org/wiki/ASCII),
 /// графическое представление.
@@ -121,14 +135,29 @@ pub struct Grid<'a> {
     /// Горизонтальное текстовое разрешение --- количество символов в одной строке.
     column_count: usize,
 
+    /// Последний напечатанный символ был частью слова и
+    /// заполнил строку до конца, то есть произошёл неявный переход на следующую строку.
+    implicit_newline: bool,
+
     /// Индекс в [`Grid::buffer`], с которого начинается строка символов,
     /// содержащая текущее положение.
     /// То есть, положение в котором находится курсор и
     /// в котором будет напечатан следующий символ.
     row_start: usize,
 
+    /// Диапазон строк экрана, который прокручивается при переходе за его нижний край,
+    /// см. [`Grid::set_scroll_region()`].
+    scroll_region: Range<usize>,
+
+    /// История строк, ушедших за верхний край экрана.
+    /// Если она не задана методом [`Grid::set_scrollback()`], строки просто теряются.
+    scrollback: Option<Scrollback<'a>>,
+
     /// Количество пробелов в символе табуляции --- `\t`.
     tab_width: usize,
+
+    /// Режим переноса по словам, см. [`Grid::set_word_wrap()`].
+    word_wrap: bool,
 }
 // ANCHOR_END: grid
 
@@ -158,7 +187,11 @@ impl<'a> Grid<'a> {
             tab_width,
             row_start: 0,
             column: 0,
-            attribute: Attribute::new(Color::GRAY, Color::BLACK),
+            implicit_newline: false,
+            scroll_region: 0 .. row_count,
+            scrollback: None,
+            word_wrap: false,
+            attribute: Attribute::new(Color::GRAY, Color::BLACK, false),
         }
     }
 
@@ -199,11 +232,52 @@ impl<'a> Grid<'a> {
         self.buffer[position].write(glyph);
     }
 
+    /// Записывает символ `character` с атрибутами `attribute`
+    /// в строку `row` и колонку `column` экрана.
+    /// В отличие от [`Grid::print_character()`], не меняет текущую позицию [`Grid::position()`]
+    /// и не интерпретирует управляющие символы.
+    ///
+    /// Возвращает ошибку [`Error::InvalidArgument`], если координаты выходят за пределы экрана.
+    pub fn write_glyph_at(
+        &mut self,
+        row: usize,
+        column: usize,
+        character: u8,
+        attribute: Attribute,
+    ) -> Result<()> {
+        if row >= self.row_count() || column >= self.column_count() {
+            return Err(InvalidArgument);
+        }
+
+        let position = row * self.column_count() + column;
+        self.buffer[position].write(Glyph::new(character, attribute));
+
+        Ok(())
+    }
+
+    /// Возвращает символ в позиции `position` видимого экрана.
+    /// В отличие от [`Grid::glyph()`], при просмотре истории возвращает символ истории,
+    /// а не текущего вывода.
+    pub fn visible_glyph(
+        &self,
+        position: usize,
+    ) -> Glyph {
+        self.scrollback
+            .as_ref()
+            .and_then(|scrollback| scrollback.visible_glyph(position))
+            .unwrap_or_else(|| self.glyph(position))
+    }
+
     /// Возвращает количестве отображаемых символов на экране.
     pub fn len(&self) -> usize {
         self.buffer.len()
     }
 
+    /// Возвращает вертикальное текстовое разрешение --- количество строк на экране.
+    pub fn row_count(&self) -> usize {
+        self.len() / self.column_count()
+    }
+
     /// Возвращает текущую позицию как индекс в [`Grid::buffer`].
     /// То есть, положение в котором находится курсор и
     /// в котором будет напечатан следующий символ.
@@ -219,6 +293,108 @@ impl<'a> Grid<'a> {
     ) {
         self.column = position % self.column_count();
         self.row_start = position - self.column;
+        self.implicit_newline = false;
+    }
+
+    /// Задаёт память `storage` для истории строк, ушедших за верхний край экрана.
+    /// Первые [`Grid::len()`] символов `storage` отводятся под запасной экран,
+    /// на который продолжается вывод, пока просматривается история.
+    /// Остальные --- под
+    /// [кольцевой буфер](https://en.wikipedia.org/wiki/Circular_buffer) строк истории.
+    /// Предыдущая история, если она была, забывается.
+    ///
+    /// Возвращает ошибку [`Error::InvalidArgument`],
+    /// если в `storage` не помещаются запасной экран и хотя бы одна строка истории.
+    pub fn set_scrollback(
+        &mut self,
+        storage: &'a mut [Glyph],
+    ) -> Result<()> {
+        let scrollback = Scrollback::new(storage, self.column_count(), self.len())?;
+        self.scroll_to_bottom();
+        self.scrollback = Some(scrollback);
+        Ok(())
+    }
+
+    /// Возвращает диапазон строк экрана, который прокручивается при переходе за его нижний край.
+    pub fn scroll_region(&self) -> Range<usize> {
+        self.scroll_region.clone()
+    }
+
+    /// Задаёт диапазон строк экрана `top .. bottom`,
+    /// который прокручивается при переходе за его нижний край ---
+    /// аналог управляющей последовательности
+    /// [DECSTBM](https://vt100.net/docs/vt510-rm/DECSTBM.html).
+    /// Строки вне диапазона, например, строка состояния сверху и строка ввода снизу,
+    /// при прокрутке остаются на месте.
+    /// Если диапазон состоит из одной строки, при переходе за её край
+    /// она не прокручивается, а очищается и перезаписывается.
+    ///
+    /// Если текущая позиция находится вне диапазона,
+    /// она переносится в начало его верхней строки.
+    /// Вывод в строки вне диапазона возможен после явной установки позиции
+    /// методом [`Grid::set_position()`].
+    /// При переходе за нижний край экрана вне диапазона прокрутки
+    /// последняя строка экрана очищается и перезаписывается.
+    ///
+    /// Возвращает ошибку [`Error::InvalidArgument`], если диапазон пуст
+    /// или выходит за пределы экрана.
+    pub fn set_scroll_region(
+        &mut self,
+        top: usize,
+        bottom: usize,
+    ) -> Result<()> {
+        if top >= bottom || bottom > self.row_count() {
+            return Err(InvalidArgument);
+        }
+
+        self.scroll_region = top .. bottom;
+
+        let column_count = self.column_count();
+        if !self.scroll_region.contains(&(self.row_start / column_count)) {
+            self.set_position(top * column_count);
+        }
+
+        Ok(())
+    }
+
+    /// Восстанавливает прокрутку всего экрана.
+    pub fn reset_scroll_region(&mut self) {
+        self.scroll_region = 0 .. self.row_count();
+    }
+
+    /// Возвращает количество строк в истории.
+    pub fn scrollback_len(&self) -> usize {
+        self.scrollback.as_ref().map_or(0, |scrollback| scrollback.line_count())
+    }
+
+    /// Возвращает, на сколько строк вверх от текущего вывода прокручен видимый экран.
+    /// Ноль означает, что видимый экран показывает текущий вывод.
+    pub fn scrollback_offset(&self) -> usize {
+        self.scrollback.as_ref().map_or(0, |scrollback| scrollback.offset())
+    }
+
+    /// Прокручивает видимый экран на `lines` строк вверх по истории, но не дальше её начала.
+    /// Вывод при этом продолжается, но не меняет видимый экран,
+    /// пока он не будет возвращён к текущему выводу.
+    pub fn scroll_up(
+        &mut self,
+        lines: usize,
+    ) {
+        self.show_scrollback(self.scrollback_offset().saturating_add(lines));
+    }
+
+    /// Прокручивает видимый экран на `lines` строк вниз по истории,
+    /// но не дальше текущего вывода.
+    pub fn scroll_down(
+        &mut self,
+        lines: usize,
+    ) {
+        self.show_scrollback(self.scrollback_offset().saturating_sub(lines));
+    }
+
+    /// Возвращает видимый экран к текущему выводу.
+    pub fn scroll_to_bottom(&mut self) {
+        self.show_scrollback(0);
     }
 
     /// Возвращает количество пробелов в символе табуляции --- `\t`.
@@ -226,6 +402,27 @@ impl<'a> Grid<'a> {
         self.tab_width
     }
 
+    /// Возвращает `true`, если включён режим переноса по словам.
+    pub fn word_wrap(&self) -> bool {
+        self.word_wrap
+    }
+
+    /// Включает или отключает режим переноса по словам.
+    ///
+    /// По умолчанию режим выключен, и слово, не помещающееся в конец строки,
+    /// разрывается в произвольном месте.
+    /// В режиме переноса по словам такое слово целиком переносится на следующую строку.
+    /// Разрываются только слова, которые длиннее строки.
+    /// А пробел, следующий за словом, которое ровно заполнило строку,
+    /// не печатается в начале следующей строки.
+    pub fn set_word_wrap(
+        &mut self,
+        word_wrap: bool,
+    ) {
+        self.word_wrap = word_wrap;
+        self.implicit_newline = false;
+    }
+
     /// Возвращает `true`, если текущая позиция соответствует началу строки.
     pub fn is_newline(&self) -> bool {
         self.column == 0
@@ -245,10 +442,12 @@ impl<'a> Grid<'a> {
 
         self.log_printed_data(serial, 0 .. position);
 
-        if self.row_start >= self.len() {
+        let scroll_region = self.scroll_region_positions();
+        if self.row_start >= scroll_region.end {
+            self.row_start = scroll_region.end;
             self.scroll();
         } else {
-            self.clear(position .. self.len());
+            self.clear(position .. scroll_region.end);
         }
 
         position
@@ -284,37 +483,152 @@ impl<'a> Grid<'a> {
         ch: char,
     ) {
         // ANCHOR_END: print_character
+        let is_word_character = !ch.is_whitespace();
+
+        if self.word_wrap && mem::take(&mut self.implicit_newline) {
+            if is_word_character {
+                self.wrap_word();
+            } else if ch == ' ' {
+                return;
+            }
+        }
+
         match ch {
             '\t' => self.tab(),
             '\r' => self.column = 0,
             '\n' => self.newline(),
             _ => self.character(ch),
         }
+
+        self.implicit_newline = is_word_character && self.is_newline();
+    }
+
+    /// Переносит на текущую строку окончание предыдущей строки,
+    /// если оно является началом печатаемого слова и это слово короче строки.
+    /// Вызывается в режиме переноса по словам, когда слово не поместилось в предыдущую строку.
+    fn wrap_word(&mut self) {
+        let column_count = self.column_count();
+        let previous_row_end = self.row_start;
+        let previous_row_start = previous_row_end - column_count;
+
+        let word_len = (previous_row_start .. previous_row_end)
+            .rev()
+            .take_while(|&position| self.buffer[position].read().character() != b' ')
+            .count();
+
+        if word_len == column_count {
+            return;
+        }
+
+        let word_start = previous_row_end - word_len;
+        for i in 0 .. word_len {
+            let glyph = self.buffer[word_start + i].read();
+            self.buffer[self.row_start + i].write(glyph);
+        }
+        self.clear(word_start .. previous_row_end);
+        self.column = word_len;
     }
 
     // ANCHOR: scroll
-    /// Выполняет прокрутку содержимого экрана на одну строку вверх,
-    /// и очищает нижнюю строку.
-    /// Используется в ситуации, когда весь экран заполнен текстом,
+    /// Выполняет прокрутку содержимого диапазона строк [`Grid::scroll_region()`]
+    /// на одну строку вверх, и очищает его нижнюю строку.
+    /// Используется в ситуации, когда диапазон заполнен текстом,
     /// и требуется перейти на следующую строку.
     pub(super) fn scroll(&mut self) {
         // ANCHOR_END: scroll
         let column_count = self.column_count();
-        for i in 0..self.len() - column_count {
+        let scroll_region = self.scroll_region_positions();
+        if let Some(scrollback) = &mut self.scrollback {
+            let top_row = scroll_region.start .. scroll_region.start + column_count;
+            scrollback.push(&self.buffer[top_row]);
+        }
+        for i in scroll_region.start .. scroll_region.end - column_count {
             let glyph = self.buffer[i + column_count].read();
             self.buffer[i].write(glyph);
         }
-        self.clear(self.len() - column_count..self.len());
-        if self.row_start >= column_count {
+        self.clear(scroll_region.end - column_count .. scroll_region.end);
+        if (scroll_region.start + column_count ..= scroll_region.end).contains(&self.row_start) {
             self.row_start -= column_count;
         }
+        if let Some(scrollback) = &mut self.scrollback {
+            scrollback.render(self.buffer);
+        }
+    }
+
+    /// Возвращает копию содержимого экрана вместе с текущими позицией и атрибутами.
+    /// Как и [`Grid::glyph()`], копирует текущий вывод, а не просматриваемую историю.
+    ///
+    /// # Panics
+    ///
+    /// Паникует, если экран содержит больше [`Snapshot::CAPACITY`] символов.
+    pub(super) fn snapshot(&self) -> Snapshot {
+        assert!(
+            self.len() <= Snapshot::CAPACITY,
+            "the screen is too large for a snapshot",
+        );
+
+        let mut glyphs = [Glyph::new(b' ', self.attribute); Snapshot::CAPACITY];
+        for (position, glyph) in glyphs[.. self.len()].iter_mut().enumerate() {
+            *glyph = self.glyph(position);
+        }
+
+        Snapshot {
+            attribute: self.attribute,
+            glyphs,
+            len: self.len(),
+            position: self.position(),
+        }
+    }
+
+    /// Восстанавливает содержимое экрана, текущие позицию и атрибуты
+    /// из копии `snapshot`, сделанной методом [`Grid::snapshot()`].
+    ///
+    /// # Panics
+    ///
+    /// Паникует, если `snapshot` сделана для экрана другого размера.
+    pub(super) fn restore(
+        &mut self,
+        snapshot: &Snapshot,
+    ) {
+        assert_eq!(
+            snapshot.len,
+            self.len(),
+            "the snapshot was taken for a screen of another size",
+        );
+
+        self.scroll_to_bottom();
+
+        for (position, glyph) in snapshot.glyphs().iter().enumerate() {
+            self.set_glyph(position, *glyph);
+        }
+
+        self.attribute = snapshot.attribute;
+        self.set_position(snapshot.position);
+    }
+
+    /// Возвращает диапазон [`Grid::scroll_region()`] как диапазон индексов в [`Grid::buffer`].
+    fn scroll_region_positions(&self) -> Range<usize> {
+        let column_count = self.column_count();
+        self.scroll_region.start * column_count .. self.scroll_region.end * column_count
+    }
+
+    /// Прокручивает видимый экран на `offset` строк вверх от текущего вывода.
+    fn show_scrollback(
+        &mut self,
+        offset: usize,
+    ) {
+        if let Some(scrollback) = &mut self.scrollback {
+            scrollback.show(&mut self.buffer, offset);
+        }
     }
 
     // ANCHOR: adjust_position
     /// Корректирует значение [`Grid::column`] и значение [`Grid::row_start`],
     /// если первое из них вышло за пределы строки.
-    /// При этом, если [`Grid::row_start`] выходит за пределы экрана,
-    /// выполняет прокрутку содержимого экрана методом [`Grid::scroll()`].
+    /// При этом, если [`Grid::row_start`] выходит за нижний край [`Grid::scroll_region()`],
+    /// выполняет прокрутку содержимого диапазона методом [`Grid::scroll()`].
+    /// А если [`Grid::row_start`] вне этого диапазона выходит за пределы экрана,
+    /// очищает последнюю строку экрана и возвращается в её начало.
     fn adjust_position(&mut self) {
         // ANCHOR_END: adjust_position
         let column_count = self.column_count();
@@ -322,8 +636,11 @@ impl<'a> Grid<'a> {
             self.column = 0;
             self.row_start += column_count;
         }
-        if self.row_start >= self.len() {
+        if self.row_start == self.scroll_region_positions().end {
             self.scroll();
+        } else if self.row_start >= self.len() {
+            self.row_start = self.len() - column_count;
+            self.clear(self.row_start .. self.len());
         }
     }
 
@@ -334,17 +651,16 @@ impl<'a> Grid<'a> {
     /// - Отображается `ch`, если он соответствует печатному символу
     ///   [ASCII (American Standard Code for Information Interchange)](https://en.wikipedia.org/wiki/ASCII).
     ///   См. функцию [`is_printable()`].
+    /// - Либо его аналог из
+    ///   [кодовой страницы CP437](https://en.wikipedia.org/wiki/Code_page_437),
+    ///   если он в ней есть. См. функцию [`cp437::encode()`].
     /// - В противном случае отображает `?`.
     fn character(
         &mut self,
         ch: char,
     ) {
         // ANCHOR_END: character
-        let display_char = if is_graphical(ch as u8) || ch as u8 == b' ' {
-            ch as u8
-        } else {
-            b'?'
-        };
+        let display_char = cp437::encode(ch);
         
         let glyph = Glyph {
             character: display_char,
@@ -395,13 +711,7 @@ impl<'a> Grid<'a> {
                 self.buffer[position].write(glyph);
             }
             self.column += 1;
-            if self.column >= self.column_count() {
-                self.column = 0;
-                self.row_start += self.column_count();
-                if self.row_start >= self.len() {
-                    self.scroll();
-                }
-            }
+            self.adjust_position();
         }
     }
 
@@ -457,3 +767,41 @@ impl<'a> Grid<'a> {
         printed_data_end
     }
 }
+
+/// Копия содержимого экрана вместе с текущими позицией и атрибутами,
+/// см. [`crate::Text::snapshot()`] и [`crate::Text::restore()`].
+#[derive(Clone, Debug)]
+pub struct Snapshot {
+    /// Текущие атрибуты при печати.
+    attribute: Attribute,
+
+    /// Символы экрана.
+    /// Используются только первые [`Snapshot::len`] из них.
+    glyphs: [Glyph; Snapshot::CAPACITY],
+
+    /// Количество символов на экране.
+    len: usize,
+
+    /// Текущая позиция.
+    position: usize,
+}
+
+impl Snapshot {
+    /// Возвращает текущие атрибуты при печати.
+    pub fn attribute(&self) -> Attribute {
+        self.attribute
+    }
+
+    /// Возвращает символы экрана.
+    pub fn glyphs(&self) -> &[Glyph] {
+        &self.glyphs[.. self.len]
+    }
+
+    /// Возвращает текущую позицию.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Максимальное количество символов на экране, который можно скопировать.
+    pub const CAPACITY: usize = COLUMN_COUNT * ROW_COUNT;
+}
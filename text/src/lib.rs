@@ -36,6 +36,10 @@ use ku::{
     },
 };
 
+use ansi::{
+    Output,
+    Parser,
+};
 use cursor::VgaCursor;
 use grid::{
     GlyphWrapper,
@@ -43,7 +47,23 @@ use grid::{
 };
 
 pub use cursor::Cursor;
-pub use grid::Glyph;
+pub use grid::{
+    Glyph,
+    Snapshot,
+};
+pub use palette::{
+    set_blink_enabled,
+    set_palette,
+};
+pub use widget::Rect;
+
+/// Разбор управляющих последовательностей
+/// [ANSI](https://en.wikipedia.org/wiki/ANSI_escape_code), задающих цвета.
+mod ansi;
+
+/// Перекодировка символов Unicode в
+/// [кодовую страницу CP437](https://en.wikipedia.org/wiki/Code_page_437).
+mod cp437;
 
 /// Управление курсором в текстовом режиме графического контроллера
 /// [Video Graphics Array (VGA)](https://en.wikipedia.org/wiki/Video_Graphics_Array).
@@ -53,10 +73,20 @@ mod cursor;
 /// [Video Graphics Array (VGA)](https://en.wikipedia.org/wiki/Video_Graphics_Array).
 mod grid;
 
+/// Палитра текстового режима графического контроллера
+/// [Video Graphics Array (VGA)](https://en.wikipedia.org/wiki/Video_Graphics_Array).
+mod palette;
+
+/// История строк, ушедших за верхний край экрана.
+mod scrollback;
+
 /// Тесты.
 #[cfg(test)]
 mod test;
 
+/// Рамки и индикаторы прогресса поверх [`Grid::write_glyph_at()`].
+mod widget;
+
 bitflags! {
     #[derive(Clone, Copy, Debug, Eq, PartialEq)]
     /// Цвета символов и фона в текстовом режиме графического контроллера
@@ -125,21 +155,75 @@ bitflags! {
 pub struct Attribute(u8);
 
 impl Attribute {
-    /// Возвращает атрибуты для символа, имеющего цвет `foreground` на фоне цвета `background`.
+    /// Возвращает атрибуты для немерцающего символа,
+    /// имеющего цвет `foreground` на фоне цвета `background`.
+    ///
+    /// Аргумент `blink_enabled` задаёт режим экрана, для которого предназначены атрибуты,
+    /// см. [`set_blink_enabled()`].
+    /// В режиме мерцания старший бит атрибутов отведён под него.
+    /// Тогда фон может быть только одним из восьми тёмных цветов,
+    /// и бит [`Color::LIGHT`] цвета `background` отбрасывается.
+    /// Иначе он задаёт яркость фона.
+    /// Мерцание задаётся методом [`Attribute::with_blink()`].
     pub const fn new(
         foreground: Color,
         background: Color,
+        blink_enabled: bool,
+    ) -> Attribute {
+        let background = if blink_enabled {
+            background.bits() & !Color::LIGHT.bits()
+        } else {
+            background.bits()
+        };
+
+        Attribute(background << Self::BACKGROUND_SHIFT | foreground.bits())
+    }
+
+    /// Возвращает атрибуты, отличающиеся от исходных только мерцанием символа:
+    /// символ мерцает, если `blink` равен `true`.
+    ///
+    /// Предназначен только для атрибутов режима мерцания, см. [`Attribute::new()`].
+    /// Вне этого режима тот же бит атрибутов означает яркость фона.
+    pub const fn with_blink(
+        self,
+        blink: bool,
     ) -> Attribute {
-        Attribute(background.bits() << Self::BACKGROUND_SHIFT | foreground.bits())
+        if blink {
+            Attribute(self.0 | Self::BLINK)
+        } else {
+            Attribute(self.0 & !Self::BLINK)
+        }
     }
 
-    /// Возвращает цвет фона.
+    /// Возвращает цвет символа.
+    pub const fn foreground(&self) -> Color {
+        Color::from_bits(self.0 & Self::FOREGROUND_MASK).expect("undefined color")
+    }
+
+    /// Возвращает цвет фона вместе со старшим битом атрибутов.
+    ///
+    /// В режиме мерцания бит [`Color::LIGHT`] результата означает не яркость фона,
+    /// а мерцание, см. [`Attribute::is_blinking()`].
+    /// Поэтому [`Attribute::new()`] в этом режиме его и отбрасывает.
     pub const fn background(&self) -> Color {
         Color::from_bits(self.0 >> Self::BACKGROUND_SHIFT).expect("undefined color")
     }
 
+    /// Возвращает `true`, если установлен старший бит атрибутов,
+    /// то есть в режиме мерцания символ с этими атрибутами мерцает.
+    /// Вне этого режима тот же бит означает яркость фона, см. [`Attribute::background()`].
+    pub const fn is_blinking(&self) -> bool {
+        self.0 & Self::BLINK != 0
+    }
+
     /// Битовый сдвиг для цвета фона в байте атрибутов символа.
     const BACKGROUND_SHIFT: u8 = 4;
+
+    /// Бит мерцания символа в байте атрибутов символа.
+    const BLINK: u8 = Color::LIGHT.bits() << Self::BACKGROUND_SHIFT;
+
+    /// Маска цвета символа в байте атрибутов символа.
+    const FOREGROUND_MASK: u8 = (1 << Self::BACKGROUND_SHIFT) - 1;
 }
 
 /// Структура, позволяющая печатать на экран в текстовом режиме графического контроллера
@@ -147,11 +231,26 @@ impl Attribute {
 /// И одновременно выводить печатаемые символы в
 /// [последовательный порт](https://en.wikipedia.org/wiki/Serial_port)
 /// для отладочных целей.
+///
+/// Распознаёт в печатаемом тексте последовательности
+/// [SGR](https://en.wikipedia.org/wiki/ANSI_escape_code#SGR) вида `\x1b[31m`
+/// и меняет в соответствии с ними текущие атрибуты [`Grid::attribute()`].
+/// В последовательный порт такие последовательности не выводятся.
+/// Неподдерживаемые последовательности печатаются как есть.
 #[derive(Deref, DerefMut)]
 pub struct Text<'a, C: Cursor, S: Serial> {
     /// Управление курсором.
     cursor: C,
 
+    /// Разбор управляющих последовательностей ANSI,
+    /// в том числе разбитых между несколькими вызовами [`Text::write_str()`].
+    escape: Parser,
+
+    /// Режим экрана, для которого [`Text`] строит атрибуты
+    /// из последовательностей [SGR](https://en.wikipedia.org/wiki/ANSI_escape_code#SGR),
+    /// см. [`Attribute::new()`] и [`Text::set_blink_mode()`].
+    blink_enabled: bool,
+
     /// Управление содержимым экрана.
     #[deref]
     #[deref_mut]
@@ -181,17 +280,36 @@ impl<'a, C: Cursor, S: Serial> Text<'a, C, S> {
         serial: S,
     ) -> Self {
         Self {
-            grid,
+            blink_enabled: false,
             cursor,
+            escape: Parser::new(),
+            grid,
             serial,
         }
     }
 
+    /// Задаёт режим экрана, для которого строятся атрибуты
+    /// из последовательностей [SGR](https://en.wikipedia.org/wiki/ANSI_escape_code#SGR):
+    /// в режиме мерцания, если `enabled` равен `true`, или в режиме яркого фона.
+    /// Сам контроллер атрибутов переключается функцией [`set_blink_enabled()`].
+    pub fn set_blink_mode(
+        &mut self,
+        enabled: bool,
+    ) {
+        self.blink_enabled = enabled;
+    }
+
     /// Управление курсором.
     pub fn cursor_mut(&mut self) -> &mut C {
         &mut self.cursor
     }
 
+    /// [Последовательный порт](https://en.wikipedia.org/wiki/Serial_port),
+    /// в который дублируется вывод.
+    pub fn serial_mut(&mut self) -> &mut S {
+        &mut self.serial
+    }
+
     /// Устанавливает текущую позицию.
     /// То есть, положение в котором находится курсор и
     /// в котором будет напечатан следующий символ.
@@ -225,10 +343,80 @@ impl<'a, C: Cursor, S: Serial> Text<'a, C, S> {
         }
     }
 
-    /// Очищает экран. Для этого заполняет его пробелами с текущими атрибутами.
+    /// Очищает диапазон прокрутки экрана [`Grid::scroll_region()`],
+    /// а если он не задан --- весь экран.
+    /// Для этого заполняет его пробелами с текущими атрибутами.
+    /// И переносит текущую позицию в начало верхней строки диапазона.
     pub fn clear(&mut self) {
-        self.grid.clear(0 .. self.grid.len());
-        self.set_position(0);
+        let column_count = self.grid.column_count();
+        let start = self.grid.scroll_region().start * column_count;
+        let end = self.grid.scroll_region().end * column_count;
+        self.grid.clear(start .. end);
+        self.set_position(start);
+    }
+
+    /// Возвращает копию содержимого экрана вместе с текущими позицией и атрибутами.
+    /// Позволяет, например, сохранить экран перед выводом сообщения о панике
+    /// и вернуть его после. См. [`Text::restore()`].
+    pub fn snapshot(&self) -> Snapshot {
+        self.grid.snapshot()
+    }
+
+    /// Восстанавливает содержимое экрана, текущие позицию и атрибуты
+    /// из копии `snapshot`, сделанной методом [`Text::snapshot()`].
+    /// Переносит курсор в восстановленную позицию.
+    pub fn restore(
+        &mut self,
+        snapshot: &Snapshot,
+    ) {
+        self.grid.restore(snapshot);
+        self.set_position(snapshot.position());
+    }
+
+    /// Задаёт диапазон строк экрана `top .. bottom`,
+    /// который прокручивается при переходе за его нижний край.
+    /// См. [`Grid::set_scroll_region()`].
+    pub fn set_scroll_region(
+        &mut self,
+        top: usize,
+        bottom: usize,
+    ) -> ku::error::Result<()> {
+        self.grid.set_scroll_region(top, bottom)?;
+        self.cursor.set(self.grid.position());
+        Ok(())
+    }
+
+    /// Прокручивает видимый экран на `lines` строк вверх по истории.
+    /// Пока видимый экран не показывает текущий вывод, курсор скрыт.
+    /// См. [`Grid::scroll_up()`].
+    pub fn scroll_up(
+        &mut self,
+        lines: usize,
+    ) {
+        self.grid.scroll_up(lines);
+        self.update_cursor_visibility();
+    }
+
+    /// Прокручивает видимый экран на `lines` строк вниз по истории.
+    /// См. [`Grid::scroll_down()`].
+    pub fn scroll_down(
+        &mut self,
+        lines: usize,
+    ) {
+        self.grid.scroll_down(lines);
+        self.update_cursor_visibility();
+    }
+
+    /// Возвращает видимый экран к текущему выводу.
+    /// См. [`Grid::scroll_to_bottom()`].
+    pub fn scroll_to_bottom(&mut self) {
+        self.grid.scroll_to_bottom();
+        self.update_cursor_visibility();
+    }
+
+    /// Скрывает курсор, если видимый экран показывает историю, и отображает в противном случае.
+    fn update_cursor_visibility(&mut self) {
+        self.cursor.set_disable(self.grid.scrollback_offset() != 0);
     }
 }
 
@@ -238,10 +426,18 @@ impl<'a, C: Cursor, S: Serial> Write for Text<'a, C, S> {
         text: &str,
     ) -> Result {
         for ch in text.chars() {
-            self.grid.print_character(ch)
-        }
-        for octet in text.as_bytes() {
-            self.serial.print_octet(*octet);
+            match self.escape.feed(ch, self.grid.attribute(), self.blink_enabled) {
+                Output::Pending => {},
+                Output::SetAttribute(attribute) => self.grid.set_attribute(attribute),
+                Output::Text(text) => {
+                    for ch in text.chars() {
+                        self.grid.print_character(ch);
+                    }
+                    for octet in text.as_bytes() {
+                        self.serial.print_octet(*octet);
+                    }
+                },
+            }
         }
 
         self.cursor.set(self.grid.position());
@@ -264,8 +460,6 @@ lazy_static! {
     /// [последовательный порт](https://en.wikipedia.org/wiki/Serial_port)
     /// для отладочных целей.
     pub static ref TEXT: IrqSpinlock<VgaText, { PanicStrategy::KnockDown }> = {
-        const COLUMN_COUNT: usize = 80;
-        const ROW_COUNT: usize = 25;
         let buffer = 0xB_8000 as *mut [Volatile<GlyphWrapper>; COLUMN_COUNT * ROW_COUNT];
         let tab_width = 8;
         let grid = Grid::new(unsafe { &mut *buffer }, COLUMN_COUNT, ROW_COUNT, tab_width);
@@ -273,10 +467,25 @@ lazy_static! {
         let mut cursor = cursor::create_vga_cursor();
         cursor.set_height(2);
 
-        IrqSpinlock::new(Text::new(grid, cursor, Serial::new()))
+        palette::set_blink_enabled(BLINK_ENABLED);
+
+        let mut text = Text::new(grid, cursor, Serial::new());
+        text.set_blink_mode(BLINK_ENABLED);
+
+        IrqSpinlock::new(text)
     };
 }
 
+/// Включён ли режим мерцания на экране [`TEXT`], см. [`set_blink_enabled()`].
+/// Атрибуты для [`TEXT`] нужно строить в этом режиме, см. [`Attribute::new()`].
+pub const BLINK_ENABLED: bool = true;
+
+/// Горизонтальное текстовое разрешение экрана [`TEXT`].
+const COLUMN_COUNT: usize = 80;
+
+/// Вертикальное текстовое разрешение экрана [`TEXT`].
+const ROW_COUNT: usize = 25;
+
 /// Позволяет задать цвета текста `foreground` и фона `background`.
 /// При указании базовых атрибутов `base`, по умолчанию `background` берётся из них.
 /// Не предполагается к непосредственному использованию вне макросов
@@ -284,7 +493,7 @@ lazy_static! {
 #[macro_export]
 macro_rules! make_attribute {
     ($foreground:expr, $background:expr) => {
-        $crate::Attribute::new($foreground, $background)
+        $crate::Attribute::new($foreground, $background, $crate::BLINK_ENABLED)
     };
     ($base:expr; $foreground:expr, $background:expr) => {
         $crate::make_attribute!($foreground, $background)
@@ -0,0 +1,208 @@
+use ku::{
+    error::{
+        Error::InvalidArgument,
+        Result,
+    },
+    sync::Spinlock,
+};
+
+use serial::{
+    IoPorts,
+    Ports,
+};
+
+use super::Color;
+
+// Used in docs.
+#[allow(unused)]
+use {
+    super::Attribute,
+    ku::error::Error,
+};
+
+/// Палитра текстового режима графического контроллера
+/// [Video Graphics Array (VGA)](https://en.wikipedia.org/wiki/Video_Graphics_Array).
+///
+/// Цвета [`Color`] из атрибутов символов отображаются в RGB через регистры
+/// [цифро--аналогового преобразователя (DAC)](https://en.wikipedia.org/wiki/Video_DAC).
+/// Каждый регистр DAC хранит по шесть бит на красную, зелёную и синюю компоненты.
+/// Кроме того, контроллер атрибутов определяет, задаёт ли старший бит цвета фона
+/// его яркость или мерцание символа.
+///
+/// Для тестов может быть создана поверх эмулируемых
+/// [портов ввода--вывода](https://en.wikipedia.org/wiki/Memory-mapped_I/O_and_port-mapped_I/O),
+/// см. [`Ports`].
+pub struct VgaPalette<P: Ports>(P);
+
+impl<P: Ports> VgaPalette<P> {
+    /// Создаёт палитру поверх портов ввода--вывода `ports`.
+    pub(super) const fn new(ports: P) -> Self {
+        Self(ports)
+    }
+
+    /// Возвращает компоненты RGB, в которые отображается цвет `index`.
+    /// Компоненты приводятся из шести бит регистра DAC к восьми битам.
+    ///
+    /// Возвращает ошибку [`Error::InvalidArgument`],
+    /// если `index` не является одним из базовых цветов [`Color`].
+    pub fn get(
+        &mut self,
+        index: Color,
+    ) -> Result<(u8, u8, u8)> {
+        let register = Self::register(index)?;
+
+        let mut rgb = [0; 3];
+        unsafe {
+            self.0.write(DAC_READ_INDEX, register);
+            for component in &mut rgb {
+                *component = self.0.read(DAC_DATA);
+            }
+        }
+        let [red, green, blue] = rgb.map(|component| (component << 2) | (component >> 4));
+
+        Ok((red, green, blue))
+    }
+
+    /// Задаёт компоненты RGB `rgb`, в которые отображается цвет `index`.
+    /// Регистры DAC хранят только старшие шесть бит каждой компоненты.
+    ///
+    /// Возвращает ошибку [`Error::InvalidArgument`],
+    /// если `index` не является одним из базовых цветов [`Color`].
+    pub fn set(
+        &mut self,
+        index: Color,
+        rgb: (u8, u8, u8),
+    ) -> Result<()> {
+        let register = Self::register(index)?;
+        let (red, green, blue) = rgb;
+
+        unsafe {
+            self.0.write(DAC_WRITE_INDEX, register);
+            for component in [red, green, blue] {
+                self.0.write(DAC_DATA, component >> 2);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Выбирает, как контроллер атрибутов трактует старший бит цвета фона
+    /// в [`Attribute`] --- бит [`Color::LIGHT`].
+    ///
+    /// - Если `enabled` равен `true`, этот бит включает мерцание символа,
+    ///   а фон может быть только одним из восьми тёмных цветов.
+    /// - Иначе он задаёт яркость фона, и для фона доступны все шестнадцать цветов.
+    pub fn set_blink_enabled(
+        &mut self,
+        enabled: bool,
+    ) {
+        unsafe {
+            self.select_attribute_register(MODE_CONTROL);
+            let mode_control = self.0.read(ATTRIBUTE_DATA_READ);
+            let mode_control = if enabled {
+                mode_control | BLINK_ENABLE
+            } else {
+                mode_control & !BLINK_ENABLE
+            };
+            self.0.write(ATTRIBUTE_ADDRESS_DATA, mode_control);
+        }
+    }
+
+    /// Выбирает регистр контроллера атрибутов `index`,
+    /// после чего его можно прочитать из [`ATTRIBUTE_DATA_READ`] и
+    /// записать в [`ATTRIBUTE_ADDRESS_DATA`].
+    ///
+    /// # Safety
+    ///
+    /// Порт [`ATTRIBUTE_ADDRESS_DATA`] поочерёдно принимает номер регистра и данные.
+    /// Поэтому никто другой не должен обращаться к контроллеру атрибутов,
+    /// пока запись в выбранный регистр не завершена.
+    unsafe fn select_attribute_register(
+        &mut self,
+        index: u8,
+    ) {
+        unsafe {
+            // Чтение регистра состояния переводит порт в режим приёма номера регистра.
+            self.0.read(INPUT_STATUS);
+            self.0.write(ATTRIBUTE_ADDRESS_DATA, index | PALETTE_ADDRESS_SOURCE);
+        }
+    }
+
+    /// Возвращает номер регистра DAC, в который отображается цвет `index`.
+    ///
+    /// Возвращает ошибку [`Error::InvalidArgument`],
+    /// если `index` не является одним из базовых цветов [`Color`].
+    fn register(index: Color) -> Result<u8> {
+        if Color::all().contains(index) {
+            Ok(DAC_REGISTERS[usize::from(index.bits())])
+        } else {
+            Err(InvalidArgument)
+        }
+    }
+}
+
+/// Задаёт компоненты RGB `rgb`, в которые отображается цвет `index`
+/// на экране в текстовом режиме.
+/// Например, позволяет сделать фон консоли мягче.
+///
+/// Возвращает ошибку [`Error::InvalidArgument`],
+/// если `index` не является одним из базовых цветов [`Color`].
+pub fn set_palette(
+    index: Color,
+    rgb: (u8, u8, u8),
+) -> Result<()> {
+    PALETTE.lock().set(index, rgb)
+}
+
+/// Выбирает, как трактуется старший бит цвета фона в [`Attribute`]
+/// на экране в текстовом режиме:
+/// как включение мерцания, если `enabled` равен `true`, или как яркость фона.
+/// Подробнее см. [`VgaPalette::set_blink_enabled()`].
+pub fn set_blink_enabled(enabled: bool) {
+    PALETTE.lock().set_blink_enabled(enabled)
+}
+
+/// Палитра текстового режима.
+static PALETTE: Spinlock<VgaPalette<IoPorts>> = Spinlock::new(VgaPalette::new(IoPorts));
+
+/// Порт контроллера атрибутов, поочерёдно принимающий номер регистра и данные для него.
+pub(super) const ATTRIBUTE_ADDRESS_DATA: u16 = 0x03C0;
+
+/// Порт, из которого читается выбранный регистр контроллера атрибутов.
+pub(super) const ATTRIBUTE_DATA_READ: u16 = 0x03C1;
+
+/// Бит регистра [`MODE_CONTROL`], включающий мерцание вместо яркого фона.
+pub(super) const BLINK_ENABLE: u8 = 1 << 3;
+
+/// Порт данных DAC.
+/// Компоненты регистра читаются и записываются в нём по очереди ---
+/// красная, зелёная и синяя, после чего номер регистра увеличивается.
+pub(super) const DAC_DATA: u16 = 0x03C9;
+
+/// Порт, в который записывается номер регистра DAC перед чтением из [`DAC_DATA`].
+pub(super) const DAC_READ_INDEX: u16 = 0x03C7;
+
+/// Номера регистров DAC, в которые по умолчанию отображаются цвета [`Color`].
+/// Отображение задаётся регистрами палитры контроллера атрибутов,
+/// которые BIOS настраивает на совместимость с
+/// [EGA](https://en.wikipedia.org/wiki/Enhanced_Graphics_Adapter).
+/// Поэтому, например, коричневый цвет хранится в регистре `0x14`,
+/// а яркие цвета --- в регистрах `0x38`--`0x3F`.
+#[rustfmt::skip]
+pub(super) const DAC_REGISTERS: [u8; 16] = [
+    0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x14, 0x07, 0x38, 0x39, 0x3A, 0x3B, 0x3C, 0x3D, 0x3E, 0x3F,
+];
+
+/// Порт, в который записывается номер регистра DAC перед записью в [`DAC_DATA`].
+pub(super) const DAC_WRITE_INDEX: u16 = 0x03C8;
+
+/// Регистр состояния, чтение которого переводит порт [`ATTRIBUTE_ADDRESS_DATA`]
+/// в режим приёма номера регистра.
+pub(super) const INPUT_STATUS: u16 = 0x03DA;
+
+/// Номер регистра управления режимом контроллера атрибутов.
+pub(super) const MODE_CONTROL: u8 = 0x10;
+
+/// Бит, который нужно выставлять в номере регистра контроллера атрибутов,
+/// чтобы не отключать вывод изображения на экран.
+pub(super) const PALETTE_ADDRESS_SOURCE: u8 = 1 << 5;
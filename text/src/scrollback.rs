@@ -0,0 +1,190 @@
+use core::{
+    cmp,
+    mem,
+};
+
+use ku::error::{
+    Error::InvalidArgument,
+    Result,
+};
+
+use super::grid::{
+    Buffer,
+    Glyph,
+};
+
+// Used in docs.
+#[allow(unused)]
+use ku::error::Error;
+
+/// История строк, ушедших за верхний край экрана, ---
+/// [кольцевой буфер](https://en.wikipedia.org/wiki/Circular_buffer) строк.
+/// А также запасной экран, позволяющий продолжать вывод,
+/// пока пользователь просматривает историю.
+///
+/// Пока история не просматривается, запасной экран [`Scrollback::spare`] не используется.
+/// При просмотре истории он меняется местами с экраном, в который выполняется вывод.
+/// Так вывод продолжается на запасной экран, а видимый экран показывает историю.
+pub(super) struct Scrollback<'a> {
+    /// Горизонтальное текстовое разрешение --- количество символов в одной строке.
+    column_count: usize,
+
+    /// Кольцевой буфер строк истории.
+    history: &'a mut [Glyph],
+
+    /// Количество строк, сохранённых в [`Scrollback::history`].
+    line_count: usize,
+
+    /// На сколько строк вверх от текущего вывода прокручен видимый экран.
+    /// Ноль означает, что видимый экран показывает текущий вывод.
+    offset: usize,
+
+    /// Запасной экран.
+    /// Пока [`Scrollback::offset`] равен нулю, не используется.
+    /// Иначе --- это видимый экран, показывающий историю.
+    spare: &'a mut Buffer,
+
+    /// Номер строки в [`Scrollback::history`], в которой хранится самая старая строка истории.
+    start: usize,
+}
+
+impl<'a> Scrollback<'a> {
+    /// Создаёт историю для экрана из `screen_len` символов по `column_count` в строке.
+    /// Первые `screen_len` символов `storage` отводятся под запасной экран,
+    /// а остальные --- под строки истории.
+    ///
+    /// Возвращает ошибку [`Error::InvalidArgument`],
+    /// если в `storage` не помещаются запасной экран и хотя бы одна строка истории.
+    pub(super) fn new(
+        storage: &'a mut [Glyph],
+        column_count: usize,
+        screen_len: usize,
+    ) -> Result<Self> {
+        if storage.len() < screen_len + column_count {
+            return Err(InvalidArgument);
+        }
+
+        let (spare, history) = storage.split_at_mut(screen_len);
+        let history_len = history.len() / column_count * column_count;
+
+        // SAFETY: `Volatile<GlyphWrapper>` и `GlyphWrapper` являются `#[repr(transparent)]`
+        // обёртками над `Glyph`, поэтому их срезы имеют одинаковое представление в памяти.
+        let spare = unsafe { &mut *(spare as *mut [Glyph] as *mut Buffer) };
+
+        Ok(Self {
+            column_count,
+            history: &mut history[.. history_len],
+            line_count: 0,
+            offset: 0,
+            spare,
+            start: 0,
+        })
+    }
+
+    /// Максимальное количество строк в истории.
+    pub(super) fn capacity(&self) -> usize {
+        self.history.len() / self.column_count
+    }
+
+    /// Количество строк в истории.
+    pub(super) fn line_count(&self) -> usize {
+        self.line_count
+    }
+
+    /// На сколько строк вверх от текущего вывода прокручен видимый экран.
+    pub(super) fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Возвращает символ в позиции `position` видимого экрана,
+    /// если он показывает историю.
+    pub(super) fn visible_glyph(
+        &self,
+        position: usize,
+    ) -> Option<Glyph> {
+        (self.offset > 0).then(|| self.spare[position].read())
+    }
+
+    /// Добавляет в историю строку `line`, ушедшую за верхний край экрана.
+    /// Если история заполнена, самая старая строка забывается.
+    ///
+    /// Если история просматривается, сдвигает [`Scrollback::offset`] так,
+    /// чтобы видимый экран продолжал показывать те же строки.
+    /// Видимый экран при этом нужно перерисовать методом [`Scrollback::render()`],
+    /// так как самая старая строка могла быть забыта.
+    pub(super) fn push(
+        &mut self,
+        line: &Buffer,
+    ) {
+        let index = (self.start + self.line_count) % self.capacity();
+        let begin = index * self.column_count;
+        for (glyph, cell) in self.history[begin .. begin + self.column_count].iter_mut().zip(line) {
+            *glyph = cell.read();
+        }
+
+        if self.line_count < self.capacity() {
+            self.line_count += 1;
+        } else {
+            self.start = (self.start + 1) % self.capacity();
+        }
+
+        if self.offset > 0 {
+            self.offset = cmp::min(self.offset + 1, self.line_count);
+        }
+    }
+
+    /// Прокручивает видимый экран на `offset` строк вверх от текущего вывода,
+    /// но не дальше начала истории.
+    ///
+    /// `screen` --- экран, в который выполняется вывод.
+    /// При переходе к просмотру истории и обратно он меняется местами с
+    /// запасным экраном [`Scrollback::spare`], а его содержимое копируется.
+    pub(super) fn show(
+        &mut self,
+        screen: &mut &'a mut Buffer,
+        offset: usize,
+    ) {
+        let offset = cmp::min(offset, self.line_count);
+
+        if (self.offset == 0) != (offset == 0) {
+            for (spare, cell) in self.spare.iter_mut().zip(screen.iter()) {
+                spare.write(cell.read());
+            }
+            mem::swap(screen, &mut self.spare);
+        }
+
+        self.offset = offset;
+        self.render(screen);
+    }
+
+    /// Перерисовывает видимый экран, если история просматривается.
+    /// `screen` --- экран, в который выполняется вывод.
+    pub(super) fn render(
+        &mut self,
+        screen: &Buffer,
+    ) {
+        if self.offset == 0 {
+            return;
+        }
+
+        let capacity = self.capacity();
+        let column_count = self.column_count;
+        let top = self.line_count - self.offset;
+
+        for (row, line) in self.spare.chunks_mut(column_count).enumerate() {
+            let index = top + row;
+            if index < self.line_count {
+                let begin = (self.start + index) % capacity * column_count;
+                let history = &self.history[begin .. begin + column_count];
+                for (cell, glyph) in line.iter_mut().zip(history) {
+                    cell.write(*glyph);
+                }
+            } else {
+                let begin = (index - self.line_count) * column_count;
+                for (cell, glyph) in line.iter_mut().zip(&screen[begin ..]) {
+                    cell.write(glyph.read());
+                }
+            }
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use core::{
+    array,
     cell::Cell,
     cmp,
     fmt::{
@@ -19,15 +20,27 @@ use tracing_subscriber::{
 };
 use volatile::Volatile;
 
-use ku::memory::{
-    IndexDataPair,
-    size,
+use ku::{
+    error::Error::InvalidArgument,
+    memory::{
+        IndexDataPair,
+        size,
+    },
 };
 
-use serial::Serial;
+use serial::{
+    Ports,
+    Serial,
+};
 
 use super::{
+    Attribute,
+    Color,
+    Glyph,
+    Rect,
+    Snapshot,
     Text,
+    cp437,
     cursor::{
         self,
         Cursor,
@@ -38,6 +51,11 @@ use super::{
         GlyphWrapper,
         Grid,
     },
+    palette::{
+        self,
+        VgaPalette,
+    },
+    widget,
 };
 
 const COLUMN_COUNT: usize = 80;
@@ -412,6 +430,615 @@ fn carriage_return() {
     }
 }
 
+#[test]
+fn write_glyph_at() {
+    for column_count in 4 ..= 20 {
+        for row_count in 3 ..= 10 {
+            let len = column_count * row_count;
+
+            let mut buffer = mock_buffer();
+            let mut grid = mock_grid(&mut buffer[.. len], column_count, row_count, TAB_WIDTH);
+
+            fill(&mut grid, '*', column_count + 1);
+            let position = grid.position();
+
+            let attribute = Attribute::new(Color::YELLOW, Color::BLUE, false);
+            grid.write_glyph_at(row_count - 1, column_count - 1, b'#', attribute).unwrap();
+
+            assert_position(
+                &grid,
+                position,
+                "After writing a glyph at the bottom right corner.\n",
+            );
+
+            assert_eq!(
+                grid.write_glyph_at(row_count, 0, b'#', attribute),
+                Err(InvalidArgument),
+            );
+            assert_eq!(
+                grid.write_glyph_at(0, column_count, b'#', attribute),
+                Err(InvalidArgument),
+            );
+
+            let glyph = buffer[len - 1].read();
+            assert_eq!(glyph.character(), b'#');
+            assert_eq!(glyph.attribute(), attribute);
+        }
+    }
+}
+
+#[test]
+fn draw_box() {
+    let column_count = 20;
+    let row_count = 10;
+    let len = column_count * row_count;
+
+    let mut buffer = mock_buffer();
+    let mut grid = mock_grid(&mut buffer[.. len], column_count, row_count, TAB_WIDTH);
+    let attribute = Attribute::new(Color::WHITE, Color::BLUE, false);
+
+    grid.draw_box(Rect::new(1, 2, 4, 6), attribute).unwrap();
+    grid.draw_box(Rect::new(8, 15, 0, 3), attribute).unwrap();
+    grid.draw_box(Rect::new(7, 17, 100, 100), attribute).unwrap();
+    assert_eq!(
+        grid.draw_box(Rect::new(row_count, 0, 1, 1), attribute),
+        Err(InvalidArgument),
+    );
+
+    assert_eq!(grid.position(), 0);
+
+    let character =
+        |row: usize, column: usize| buffer[row * column_count + column].read().character();
+
+    assert_eq!(character(1, 2), widget::TOP_LEFT);
+    assert_eq!(character(1, 7), widget::TOP_RIGHT);
+    assert_eq!(character(4, 2), widget::BOTTOM_LEFT);
+    assert_eq!(character(4, 7), widget::BOTTOM_RIGHT);
+    for column in 3 .. 7 {
+        assert_eq!(character(1, column), widget::HORIZONTAL);
+        assert_eq!(character(4, column), widget::HORIZONTAL);
+        assert_eq!(character(2, column), 0);
+    }
+    for row in 2 .. 4 {
+        assert_eq!(character(row, 2), widget::VERTICAL);
+        assert_eq!(character(row, 7), widget::VERTICAL);
+    }
+    assert_eq!(buffer[column_count + 2].read().attribute(), attribute);
+
+    assert_eq!(character(8, 15), 0);
+
+    assert_eq!(character(7, 17), widget::TOP_LEFT);
+    assert_eq!(character(7, 19), widget::TOP_RIGHT);
+    assert_eq!(character(9, 17), widget::BOTTOM_LEFT);
+    assert_eq!(character(9, 19), widget::BOTTOM_RIGHT);
+}
+
+#[test]
+fn progress_bar() {
+    const WIDTH: usize = 10;
+
+    let column_count = 20;
+    let row_count = 3;
+    let len = column_count * row_count;
+
+    for (fraction, full, half) in [
+        (-1.0, 0, 0),
+        (0.0, 0, 0),
+        (0.05, 0, 1),
+        (0.25, 2, 1),
+        (0.5, 5, 0),
+        (0.99, 9, 1),
+        (1.0, 10, 0),
+        (2.0, 10, 0),
+        (f64::NAN, 0, 0),
+    ] {
+        let mut buffer = mock_buffer();
+        let mut grid = mock_grid(&mut buffer[.. len], column_count, row_count, TAB_WIDTH);
+        let attribute = Attribute::new(Color::GREEN, Color::BLACK, false);
+
+        grid.progress_bar(1, 2, WIDTH, fraction, attribute).unwrap();
+        assert_eq!(grid.position(), 0);
+
+        let bar: [_; WIDTH] = array::from_fn(|i| buffer[column_count + 2 + i].read().character());
+        let count = |character| bar.iter().filter(|&&x| x == character).count();
+
+        assert_eq!(
+            count(widget::FULL),
+            full,
+            "fraction = {fraction}, bar = {bar:?}"
+        );
+        assert_eq!(
+            count(widget::HALF),
+            half,
+            "fraction = {fraction}, bar = {bar:?}"
+        );
+        assert_eq!(
+            count(widget::EMPTY),
+            WIDTH - full - half,
+            "fraction = {fraction}, bar = {bar:?}",
+        );
+        assert_eq!(buffer[column_count + 1].read().character(), 0);
+        assert_eq!(buffer[column_count + 2 + WIDTH].read().character(), 0);
+    }
+}
+
+#[test]
+fn cp437() {
+    for (ch, code) in [
+        (' ', 0x20),
+        ('A', 0x41),
+        ('~', 0x7E),
+        ('\0', b'?'),
+        ('\x7F', b'?'),
+        ('☺', 0x01),
+        ('→', 0x1A),
+        ('←', 0x1B),
+        ('↑', 0x18),
+        ('↓', 0x19),
+        ('⌂', 0x7F),
+        ('Ç', 0x80),
+        ('é', 0x82),
+        ('Ä', 0x8E),
+        ('ñ', 0xA4),
+        ('½', 0xAB),
+        ('░', 0xB0),
+        ('│', 0xB3),
+        ('┐', 0xBF),
+        ('└', 0xC0),
+        ('─', 0xC4),
+        ('═', 0xCD),
+        ('┘', 0xD9),
+        ('┌', 0xDA),
+        ('█', 0xDB),
+        ('ß', 0xE1),
+        ('π', 0xE3),
+        ('°', 0xF8),
+        ('■', 0xFE),
+        ('\u{A0}', 0xFF),
+        ('Ж', b'?'),
+        ('€', b'?'),
+        ('😀', b'?'),
+    ] {
+        assert_eq!(cp437::encode(ch), code, "ch = {ch:?}");
+    }
+
+    let column_count = 20;
+    let row_count = 3;
+    let len = column_count * row_count;
+
+    let mut buffer = mock_buffer();
+    let mut grid = mock_grid(&mut buffer[.. len], column_count, row_count, TAB_WIDTH);
+
+    for ch in "┌─→é€".chars() {
+        grid.print_character(ch);
+    }
+
+    assert_position(&grid, 5, "After printing 5 non-ASCII characters.\n");
+
+    for (position, code) in [0xDA, 0xC4, 0x1A, 0x82, b'?'].into_iter().enumerate() {
+        assert_eq!(buffer[position].read().character(), code);
+    }
+}
+
+#[test]
+fn scrollback() {
+    let column_count = 20;
+    let row_count = 5;
+    let len = column_count * row_count;
+    let scrollback_lines = 50;
+
+    let mut buffer = mock_buffer();
+    let glyph = Glyph::new(0, Attribute::new(Color::GRAY, Color::BLACK, false));
+    let mut small_storage = [glyph; LEN];
+    let mut storage = [glyph; LEN];
+    let mut grid = mock_grid(&mut buffer[.. len], column_count, row_count, TAB_WIDTH);
+
+    assert_eq!(
+        grid.set_scrollback(&mut small_storage[.. len]),
+        Err(InvalidArgument),
+    );
+    grid.set_scrollback(&mut storage[.. len + scrollback_lines * column_count])
+        .unwrap();
+
+    let expected_line = |line: usize| -> [u8; 8] {
+        let mut expected = *b"line 000";
+        for (digit, power) in expected[5 ..].iter_mut().zip([100, 10, 1]) {
+            *digit += (line / power % 10) as u8;
+        }
+        expected
+    };
+    let print_line = |grid: &mut Grid, line: usize| {
+        for octet in expected_line(line) {
+            grid.print_character(octet.into());
+        }
+        grid.print_character('\n');
+    };
+    let visible_line = |grid: &Grid, row: usize| -> [u8; 8] {
+        array::from_fn(|column| grid.visible_glyph(row * column_count + column).character())
+    };
+
+    for line in 0 .. 100 {
+        print_line(&mut grid, line);
+    }
+
+    let position = grid.position();
+    assert_eq!(grid.scrollback_len(), scrollback_lines);
+    assert_eq!(grid.scrollback_offset(), 0);
+    assert_eq!(visible_line(&grid, 0), expected_line(96));
+
+    grid.scroll_up(3);
+    assert_eq!(grid.scrollback_offset(), 3);
+    for row in 0 .. row_count {
+        assert_eq!(visible_line(&grid, row), expected_line(93 + row));
+    }
+    assert_eq!(grid.position(), position);
+
+    print_line(&mut grid, 100);
+    print_line(&mut grid, 101);
+    assert_eq!(grid.scrollback_offset(), 5);
+    for row in 0 .. row_count {
+        assert_eq!(visible_line(&grid, row), expected_line(93 + row));
+    }
+
+    grid.scroll_down(1);
+    assert_eq!(visible_line(&grid, 0), expected_line(94));
+
+    grid.scroll_up(usize::MAX);
+    assert_eq!(grid.scrollback_offset(), scrollback_lines);
+    for row in 0 .. row_count {
+        assert_eq!(visible_line(&grid, row), expected_line(48 + row));
+    }
+
+    grid.scroll_to_bottom();
+    assert_eq!(grid.scrollback_offset(), 0);
+    assert_eq!(grid.position(), position);
+    drop(grid);
+
+    for row in 0 .. row_count - 1 {
+        let line: [u8; 8] =
+            array::from_fn(|column| buffer[row * column_count + column].read().character());
+        assert_eq!(line, expected_line(98 + row));
+    }
+}
+
+#[test]
+fn word_wrap() {
+    const COLUMN_COUNT: usize = 12;
+
+    for (text, character_wrap, word_wrap) in [
+        (
+            "the quick brown fox jumps",
+            ["the quick br", "own fox jump", "s           "],
+            ["the quick   ", "brown fox   ", "jumps       "],
+        ),
+        (
+            "abcdefghijkl mn",
+            ["abcdefghijkl", " mn         ", "            "],
+            ["abcdefghijkl", "mn          ", "            "],
+        ),
+        (
+            "abcdefghijklmnop qr",
+            ["abcdefghijkl", "mnop qr     ", "            "],
+            ["abcdefghijkl", "mnop qr     ", "            "],
+        ),
+        (
+            "a bcdefghijklmnop",
+            ["a bcdefghijk", "lmnop       ", "            "],
+            ["a           ", "bcdefghijklm", "nop         "],
+        ),
+    ] {
+        for (enabled, expected) in [(false, character_wrap), (true, word_wrap)] {
+            let row_count = 4;
+            let len = COLUMN_COUNT * row_count;
+
+            let mut buffer = mock_buffer();
+            let mut grid = mock_grid(&mut buffer[.. len], COLUMN_COUNT, row_count, TAB_WIDTH);
+            grid.clear(0 .. len);
+            grid.set_word_wrap(enabled);
+
+            for ch in text.chars() {
+                grid.print_character(ch);
+            }
+
+            for (row, expected) in expected.iter().enumerate() {
+                let found: [u8; COLUMN_COUNT] =
+                    array::from_fn(|column| grid.glyph(row * COLUMN_COUNT + column).character());
+                assert_eq!(
+                    &found,
+                    expected.as_bytes(),
+                    "text = {text:?}, word wrap = {enabled}, row = {row}",
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn scroll_region() {
+    const COLUMN_COUNT: usize = 8;
+    let row_count = 5;
+    let len = COLUMN_COUNT * row_count;
+
+    let mut buffer = mock_buffer();
+    let mut grid = mock_grid(&mut buffer[.. len], COLUMN_COUNT, row_count, TAB_WIDTH);
+    grid.clear(0 .. len);
+
+    let print = |grid: &mut Grid, text: &str| {
+        for ch in text.chars() {
+            grid.print_character(ch);
+        }
+    };
+    let row = |grid: &Grid, row: usize| -> [u8; COLUMN_COUNT] {
+        array::from_fn(|column| grid.glyph(row * COLUMN_COUNT + column).character())
+    };
+
+    for (top, bottom) in [(3, 3), (4, 2), (0, row_count + 1)] {
+        assert_eq!(grid.set_scroll_region(top, bottom), Err(InvalidArgument));
+    }
+    assert_eq!(grid.scroll_region(), 0 .. row_count);
+
+    grid.set_scroll_region(1, 4).unwrap();
+    assert_eq!(grid.scroll_region(), 1 .. 4);
+    assert_position(
+        &grid,
+        COLUMN_COUNT,
+        "Expected the position parked outside the scroll region to move to its top row.\n",
+    );
+
+    grid.set_position(2 * COLUMN_COUNT + 3);
+    grid.set_scroll_region(1, 4).unwrap();
+    assert_position(
+        &grid,
+        2 * COLUMN_COUNT + 3,
+        "Expected the position inside the scroll region to stay put.\n",
+    );
+
+    grid.set_position(0);
+    print(&mut grid, "status");
+    grid.set_position(4 * COLUMN_COUNT);
+    print(&mut grid, "input");
+
+    grid.set_position(COLUMN_COUNT);
+    for line in 0 .. 10 {
+        print(&mut grid, "line ");
+        grid.print_character(char::from(b'0' + line));
+        grid.print_character('\n');
+    }
+
+    assert_eq!(&row(&grid, 0), b"status  ");
+    assert_eq!(&row(&grid, 1), b"line 8  ");
+    assert_eq!(&row(&grid, 2), b"line 9  ");
+    assert_eq!(&row(&grid, 3), b"        ");
+    assert_eq!(&row(&grid, 4), b"input   ");
+    assert_position(
+        &grid,
+        3 * COLUMN_COUNT,
+        "Expected the position to stay at the bottom row of the scroll region.\n",
+    );
+
+    grid.set_position(4 * COLUMN_COUNT);
+    print(&mut grid, "command\n");
+    assert_eq!(&row(&grid, 3), b"        ");
+    assert_eq!(&row(&grid, 4), b"        ");
+    assert_position(
+        &grid,
+        4 * COLUMN_COUNT,
+        "Expected the last row of the screen outside the scroll region to be overwritten.\n",
+    );
+
+    grid.set_scroll_region(2, 3).unwrap();
+    assert_position(
+        &grid,
+        2 * COLUMN_COUNT,
+        "Expected the position parked outside the scroll region to move to its top row.\n",
+    );
+    print(&mut grid, "first\nsecond");
+    assert_eq!(&row(&grid, 0), b"status  ");
+    assert_eq!(&row(&grid, 1), b"line 8  ");
+    assert_eq!(&row(&grid, 2), b"second  ");
+    assert_eq!(&row(&grid, 3), b"        ");
+
+    grid.reset_scroll_region();
+    assert_eq!(grid.scroll_region(), 0 .. row_count);
+
+    grid.set_position(4 * COLUMN_COUNT);
+    print(&mut grid, "\n");
+    assert_eq!(&row(&grid, 0), b"line 8  ");
+    assert_eq!(&row(&grid, 1), b"second  ");
+    assert_position(
+        &grid,
+        4 * COLUMN_COUNT,
+        "Expected the whole screen to scroll after the scroll region is reset.\n",
+    );
+}
+
+#[test]
+fn ansi() {
+    let column_count = 20;
+    let row_count = 3;
+    let len = column_count * row_count;
+
+    let mut buffer = mock_buffer();
+    let grid = mock_grid(&mut buffer[.. len], column_count, row_count, TAB_WIDTH);
+    let cursor = MockCursor::new();
+    let mut text = Text::new(grid, cursor.get(), RecordingSerial::new());
+    text.clear();
+
+    let default = text.attribute();
+    assert_eq!(default, Attribute::new(Color::GRAY, Color::BLACK, false));
+
+    // The sequences are split between `write_str()` calls on purpose.
+    write!(text, "a\x1b[31mb\x1b[1;44mc\x1b[").unwrap();
+    write!(text, "0md\x1b[7me\x1b[32").unwrap();
+    write!(text, "mf").unwrap();
+
+    let green = Attribute::new(Color::GREEN, Color::BLACK, false);
+    let expected = [
+        (b'a', default),
+        (b'b', Attribute::new(Color::RED, Color::BLACK, false)),
+        (b'c', Attribute::new(Color::LIGHT_RED, Color::BLUE, false)),
+        (b'd', default),
+        (cp437::encode('\x1b'), default),
+        (b'[', default),
+        (b'7', default),
+        (b'm', default),
+        (b'e', default),
+        (b'f', green),
+    ];
+
+    for (position, (character, attribute)) in expected.into_iter().enumerate() {
+        let glyph = text.glyph(position);
+        assert_eq!(glyph.character(), character, "position = {position}");
+        assert_eq!(glyph.attribute(), attribute, "position = {position}");
+    }
+    assert_eq!(text.position(), expected.len());
+    assert_eq!(text.attribute(), green);
+
+    assert_eq!(text.serial_mut().octets(), b"abcd\x1b[7mef");
+}
+
+#[test]
+fn snapshot() {
+    let column_count = 80;
+    let row_count = 25;
+    let len = column_count * row_count;
+    assert_eq!(len, Snapshot::CAPACITY);
+
+    let mut buffer = mock_buffer();
+    let grid = mock_grid(&mut buffer[.. len], column_count, row_count, TAB_WIDTH);
+    let cursor = MockCursor::new();
+    let mut text = Text::new(grid, cursor.get(), MockSerial::new());
+    text.clear();
+
+    let attribute = Attribute::new(Color::GREEN, Color::BLACK, false);
+    text.set_attribute(attribute);
+    writeln!(text, "saved line").unwrap();
+    write!(text, "saved").unwrap();
+
+    let snapshot = text.snapshot();
+    let position = column_count + "saved".len();
+    assert_eq!(snapshot.position(), position);
+    assert_eq!(snapshot.attribute(), attribute);
+    assert_eq!(snapshot.glyphs().len(), len);
+    assert_eq!(snapshot.glyphs()[0], Glyph::new(b's', attribute));
+
+    text.set_attribute(Attribute::new(Color::WHITE, Color::RED, false));
+    text.clear();
+    for _ in 0 .. 2 * row_count {
+        writeln!(text, "panic").unwrap();
+    }
+    assert_ne!(cursor.get().get(), position);
+
+    text.restore(&snapshot);
+
+    for (position, glyph) in snapshot.glyphs().iter().enumerate() {
+        assert_eq!(text.glyph(position), *glyph);
+    }
+    assert_eq!(text.position(), position);
+    assert_eq!(cursor.get().get(), position);
+    assert_eq!(text.attribute(), attribute);
+
+    write!(text, " again").unwrap();
+    assert_position(
+        &text,
+        column_count + "saved again".len(),
+        "The output should continue from the restored position.\n",
+    );
+}
+
+#[test]
+fn palette() {
+    let mut ports = MockPalette::new();
+
+    let mut palette = VgaPalette::new(&mut ports);
+    palette.set(Color::BLUE, (0x20, 0x40, 0x80)).unwrap();
+    palette.set(Color::BROWN, (0xFF, 0x00, 0x7F)).unwrap();
+    palette.set(Color::WHITE, (0x10, 0x10, 0x10)).unwrap();
+
+    assert_eq!(ports.registers[0x01], [0x08, 0x10, 0x20]);
+    assert_eq!(ports.registers[0x14], [0x3F, 0x00, 0x1F]);
+    assert_eq!(ports.registers[0x3F], [0x04, 0x04, 0x04]);
+    assert_eq!(ports.registers[0x06], [0; 3]);
+
+    let mut palette = VgaPalette::new(&mut ports);
+    assert_eq!(palette.get(Color::BLUE), Ok((0x20, 0x41, 0x82)));
+    assert_eq!(palette.get(Color::BROWN), Ok((0xFF, 0x00, 0x7D)));
+
+    let invalid = Color::from_bits_retain(0x10);
+    assert_eq!(palette.set(invalid, (0, 0, 0)), Err(InvalidArgument));
+    assert_eq!(palette.get(invalid), Err(InvalidArgument));
+}
+
+#[test]
+fn blink() {
+    let mut ports = MockPalette::new();
+    let other_bits = 0b_0000_0101;
+    ports.attribute_registers[usize::from(palette::MODE_CONTROL)] = other_bits;
+
+    VgaPalette::new(&mut ports).set_blink_enabled(true);
+    assert_eq!(ports.mode_control(), other_bits | palette::BLINK_ENABLE);
+    assert_eq!(ports.attribute_index, None);
+
+    VgaPalette::new(&mut ports).set_blink_enabled(false);
+    assert_eq!(ports.mode_control(), other_bits);
+    assert_eq!(ports.attribute_index, None);
+
+    ports.attribute_index = Some(0x13);
+    VgaPalette::new(&mut ports).set_blink_enabled(true);
+    assert_eq!(ports.mode_control(), other_bits | palette::BLINK_ENABLE);
+    assert_eq!(ports.attribute_registers[0x13], 0);
+}
+
+#[test]
+fn blink_attribute() {
+    let attribute = Attribute::new(Color::YELLOW, Color::BLUE, true);
+    assert!(!attribute.is_blinking());
+
+    let blinking = attribute.with_blink(true);
+    assert!(blinking.is_blinking());
+    assert_eq!(blinking.foreground(), Color::YELLOW);
+    assert_eq!(blinking.with_blink(false), attribute);
+
+    let bright_background = Attribute::new(Color::WHITE, Color::LIGHT_CYAN, true);
+    assert_eq!(bright_background.background(), Color::CYAN);
+    assert!(!bright_background.is_blinking());
+
+    let bright_background = Attribute::new(Color::WHITE, Color::LIGHT_CYAN, false);
+    assert_eq!(bright_background.background(), Color::LIGHT_CYAN);
+    assert!(bright_background.is_blinking());
+
+    let mut buffer = mock_buffer();
+    let mut grid = mock_grid(&mut buffer, COLUMN_COUNT, ROW_COUNT, TAB_WIDTH);
+    grid.set_attribute(blinking);
+    grid.print_character('x');
+
+    let glyph = grid.glyph(0);
+    assert_eq!(glyph.character(), b'x');
+    assert_eq!(glyph.attribute(), blinking);
+    assert!(glyph.attribute().is_blinking());
+}
+
+#[test]
+fn blink_sgr() {
+    for blink_enabled in [false, true] {
+        let mut buffer = mock_buffer();
+        let grid = mock_grid(&mut buffer, COLUMN_COUNT, ROW_COUNT, TAB_WIDTH);
+        let cursor = MockCursor::new();
+        let mut text = Text::new(grid, cursor.get(), MockSerial::new());
+        text.set_blink_mode(blink_enabled);
+        text.clear();
+
+        write!(text, "\x1b[5;46ma\x1b[25mb").unwrap();
+
+        // Without the blink mode `\x1b[5m` must not turn the background bright.
+        let gray_on_cyan = Attribute::new(Color::GRAY, Color::CYAN, blink_enabled);
+        let first = text.glyph(0).attribute();
+        assert_eq!(first, gray_on_cyan.with_blink(blink_enabled));
+        assert_eq!(first.is_blinking(), blink_enabled);
+        assert_eq!(text.glyph(1).attribute(), gray_on_cyan);
+    }
+}
+
 fn fill_line(
     grid: &mut Grid,
     ch: char,
@@ -790,6 +1417,125 @@ impl Serial for MockSerial {
         _: u8,
     ) {
     }
+
+    fn read_octet(&mut self) -> Option<u8> {
+        None
+    }
+}
+
+struct RecordingSerial {
+    len: usize,
+    octets: [u8; 64],
+}
+
+impl RecordingSerial {
+    fn octets(&self) -> &[u8] {
+        &self.octets[.. self.len]
+    }
+}
+
+impl Serial for RecordingSerial {
+    fn new() -> Self {
+        Self {
+            len: 0,
+            octets: [0; 64],
+        }
+    }
+
+    fn print_octet(
+        &mut self,
+        octet: u8,
+    ) {
+        self.octets[self.len] = octet;
+        self.len += 1;
+    }
+
+    fn read_octet(&mut self) -> Option<u8> {
+        None
+    }
+}
+
+struct MockPalette {
+    attribute_index: Option<u8>,
+    attribute_registers: [u8; 0x20],
+    component: usize,
+    index: usize,
+    registers: [[u8; 3]; 0x100],
+}
+
+impl MockPalette {
+    fn new() -> Self {
+        Self {
+            attribute_index: None,
+            attribute_registers: [0; 0x20],
+            component: 0,
+            index: 0,
+            registers: [[0; 3]; 0x100],
+        }
+    }
+
+    fn next(&mut self) -> &mut u8 {
+        let component = &mut self.registers[self.index][self.component];
+        self.component += 1;
+        if self.component == 3 {
+            self.component = 0;
+            self.index = (self.index + 1) % 0x100;
+        }
+        component
+    }
+
+    fn mode_control(&self) -> u8 {
+        self.attribute_registers[usize::from(palette::MODE_CONTROL)]
+    }
+}
+
+impl Ports for &mut MockPalette {
+    unsafe fn read(
+        &mut self,
+        port: u16,
+    ) -> u8 {
+        match port {
+            palette::ATTRIBUTE_DATA_READ => {
+                let index = self.attribute_index.expect("no attribute register selected");
+                self.attribute_registers[usize::from(index)]
+            },
+            palette::DAC_DATA => *self.next(),
+            palette::INPUT_STATUS => {
+                self.attribute_index = None;
+                0
+            },
+            _ => panic!("wrong VGA palette port read"),
+        }
+    }
+
+    unsafe fn write(
+        &mut self,
+        port: u16,
+        data: u8,
+    ) {
+        match port {
+            palette::ATTRIBUTE_ADDRESS_DATA => match self.attribute_index.take() {
+                Some(index) => self.attribute_registers[usize::from(index)] = data,
+                None => {
+                    assert_ne!(
+                        data & palette::PALETTE_ADDRESS_SOURCE,
+                        0,
+                        "the screen should not be blanked",
+                    );
+                    self.attribute_index = Some(data & !palette::PALETTE_ADDRESS_SOURCE);
+                },
+            },
+            palette::DAC_DATA => {
+                assert!(data < 1 << 6, "DAC registers have 6 bits per component");
+                *self.next() = data;
+            },
+            palette::DAC_READ_INDEX | palette::DAC_WRITE_INDEX => {
+                self.component = 0;
+                self.index = data.into();
+            },
+            _ => panic!("wrong VGA palette port written"),
+        }
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -0,0 +1,221 @@
+use core::cmp;
+
+use ku::error::{
+    Error::InvalidArgument,
+    Result,
+};
+
+use super::{
+    Attribute,
+    cp437,
+    grid::Grid,
+};
+
+// Used in docs.
+#[allow(unused)]
+use ku::error::Error;
+
+/// Прямоугольная область экрана.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Rect {
+    /// Номер колонки левого верхнего угла.
+    column: usize,
+
+    /// Количество строк.
+    height: usize,
+
+    /// Номер строки левого верхнего угла.
+    row: usize,
+
+    /// Количество колонок.
+    width: usize,
+}
+
+impl Rect {
+    /// Создаёт прямоугольник размером `height`x`width`
+    /// с левым верхним углом в строке `row` и колонке `column`.
+    pub const fn new(
+        row: usize,
+        column: usize,
+        height: usize,
+        width: usize,
+    ) -> Self {
+        Self {
+            column,
+            height,
+            row,
+            width,
+        }
+    }
+
+    /// Номер колонки левого верхнего угла.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// Количество строк.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Номер строки левого верхнего угла.
+    pub fn row(&self) -> usize {
+        self.row
+    }
+
+    /// Количество колонок.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+}
+
+impl Grid<'_> {
+    /// Рисует рамку по границе прямоугольника `rect` символами
+    /// [псевдографики CP437](https://en.wikipedia.org/wiki/Box-drawing_characters)
+    /// с атрибутами `attribute`.
+    /// Содержимое внутри рамки и текущая позиция [`Grid::position()`] не меняются.
+    ///
+    /// Часть прямоугольника, выходящая за пределы экрана, отбрасывается.
+    /// Прямоугольник высотой или шириной в один символ рисуется линией,
+    /// а пустой прямоугольник --- не рисуется вовсе.
+    ///
+    /// Возвращает ошибку [`Error::InvalidArgument`],
+    /// если левый верхний угол `rect` находится за пределами экрана.
+    pub fn draw_box(
+        &mut self,
+        rect: Rect,
+        attribute: Attribute,
+    ) -> Result<()> {
+        let rect = self.clamp(rect)?;
+
+        if rect.height == 0 || rect.width == 0 {
+            return Ok(());
+        }
+
+        let top = rect.row;
+        let bottom = rect.row + rect.height - 1;
+        let left = rect.column;
+        let right = rect.column + rect.width - 1;
+
+        if rect.height == 1 {
+            for column in left ..= right {
+                self.write_glyph_at(top, column, HORIZONTAL, attribute)?;
+            }
+            return Ok(());
+        }
+
+        if rect.width == 1 {
+            for row in top ..= bottom {
+                self.write_glyph_at(row, left, VERTICAL, attribute)?;
+            }
+            return Ok(());
+        }
+
+        for column in left + 1 .. right {
+            self.write_glyph_at(top, column, HORIZONTAL, attribute)?;
+            self.write_glyph_at(bottom, column, HORIZONTAL, attribute)?;
+        }
+
+        for row in top + 1 .. bottom {
+            self.write_glyph_at(row, left, VERTICAL, attribute)?;
+            self.write_glyph_at(row, right, VERTICAL, attribute)?;
+        }
+
+        self.write_glyph_at(top, left, TOP_LEFT, attribute)?;
+        self.write_glyph_at(top, right, TOP_RIGHT, attribute)?;
+        self.write_glyph_at(bottom, left, BOTTOM_LEFT, attribute)?;
+        self.write_glyph_at(bottom, right, BOTTOM_RIGHT, attribute)
+    }
+
+    /// Рисует в строке `row` начиная с колонки `column` индикатор прогресса
+    /// шириной `width` символов, заполненный на долю `fraction`.
+    /// Заполненная часть отображается символами `█`,
+    /// последняя заполненная наполовину клетка --- символом `▌`,
+    /// а незаполненная часть --- символами `░`.
+    /// Текущая позиция [`Grid::position()`] не меняется.
+    ///
+    /// Доля `fraction` приводится к отрезку `[0, 1]`,
+    /// а часть индикатора, выходящая за правый край экрана, отбрасывается.
+    ///
+    /// Возвращает ошибку [`Error::InvalidArgument`],
+    /// если начало индикатора находится за пределами экрана.
+    pub fn progress_bar(
+        &mut self,
+        row: usize,
+        column: usize,
+        width: usize,
+        fraction: f64,
+        attribute: Attribute,
+    ) -> Result<()> {
+        let rect = self.clamp(Rect::new(row, column, 1, width))?;
+
+        let fraction = if fraction >= 0.0 {
+            fraction.min(1.0)
+        } else {
+            0.0
+        };
+        let halves = (fraction * (2 * rect.width) as f64) as usize;
+        let full = halves / 2;
+        let half = halves % 2;
+
+        for i in 0 .. rect.width {
+            let character = if i < full {
+                FULL
+            } else if i < full + half {
+                HALF
+            } else {
+                EMPTY
+            };
+            self.write_glyph_at(rect.row, rect.column + i, character, attribute)?;
+        }
+
+        Ok(())
+    }
+
+    /// Отбрасывает часть прямоугольника `rect`, выходящую за пределы экрана.
+    ///
+    /// Возвращает ошибку [`Error::InvalidArgument`],
+    /// если левый верхний угол `rect` находится за пределами экрана.
+    fn clamp(
+        &self,
+        rect: Rect,
+    ) -> Result<Rect> {
+        if rect.row >= self.row_count() || rect.column >= self.column_count() {
+            return Err(InvalidArgument);
+        }
+
+        Ok(Rect::new(
+            rect.row,
+            rect.column,
+            cmp::min(rect.height, self.row_count() - rect.row),
+            cmp::min(rect.width, self.column_count() - rect.column),
+        ))
+    }
+}
+
+/// Символ CP437 `└`.
+pub(super) const BOTTOM_LEFT: u8 = cp437::encode('└');
+
+/// Символ CP437 `┘`.
+pub(super) const BOTTOM_RIGHT: u8 = cp437::encode('┘');
+
+/// Символ CP437 `░` --- незаполненная клетка индикатора прогресса.
+pub(super) const EMPTY: u8 = cp437::encode('░');
+
+/// Символ CP437 `█` --- заполненная клетка индикатора прогресса.
+pub(super) const FULL: u8 = cp437::encode('█');
+
+/// Символ CP437 `▌` --- заполненная наполовину клетка индикатора прогресса.
+pub(super) const HALF: u8 = cp437::encode('▌');
+
+/// Символ CP437 `─`.
+pub(super) const HORIZONTAL: u8 = cp437::encode('─');
+
+/// Символ CP437 `┌`.
+pub(super) const TOP_LEFT: u8 = cp437::encode('┌');
+
+/// Символ CP437 `┐`.
+pub(super) const TOP_RIGHT: u8 = cp437::encode('┐');
+
+/// Символ CP437 `│`.
+pub(super) const VERTICAL: u8 = cp437::encode('│');
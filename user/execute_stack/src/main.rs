@@ -0,0 +1,34 @@
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use core::{
+    hint,
+    mem,
+};
+
+use lib::{
+    entry,
+    syscall,
+};
+
+entry!(main);
+
+fn main() {
+    let code = hint::black_box([RET; CODE_SIZE]);
+    let function = unsafe { mem::transmute::<*const u8, extern "C" fn()>(code.as_ptr()) };
+
+    // The stack is not executable, so the kernel should kill the process right here.
+    function();
+
+    syscall::exit(EXIT_CODE);
+}
+
+/// Size of the code placed on the stack.
+const CODE_SIZE: usize = 16;
+
+/// The exit code reporting that the code on the stack has been executed.
+const EXIT_CODE: usize = 1;
+
+/// The `ret` instruction.
+const RET: u8 = 0xC3;
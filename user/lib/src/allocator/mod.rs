@@ -15,20 +15,30 @@ use ku::{
         Dispatcher,
         FIXED_SIZE_COUNT,
         Info,
+        RetryAllocator,
     },
     log::debug,
     sync::Spinlock,
 };
 
+#[cfg(feature = "leak-tracker")]
+use ku::{
+    allocator::LeakTracker,
+    log::warn,
+};
+
 use map::MapAllocator;
 
 // Used in docs.
 #[allow(unused)]
-use crate::syscall;
+use {
+    crate::syscall,
+    ku::backtrace::Backtrace,
+};
 
 /// Статистика глобального аллокатора памяти общего назначения в пространстве пользователя.
 pub fn info() -> Info {
-    GLOBAL_ALLOCATOR.info()
+    dispatcher().info()
 }
 
 /// Распечатывает детальную статистику аллокатора.
@@ -37,8 +47,42 @@ pub fn dump_info() {
     static DETAILED_INFO: Spinlock<DetailedInfo> = Spinlock::new(DetailedInfo::new());
 
     let mut allocator_info = DETAILED_INFO.lock();
-    GLOBAL_ALLOCATOR.detailed_info(&mut allocator_info);
-    debug!(%allocator_info);
+    dispatcher().detailed_info(&mut allocator_info);
+    debug!(%allocator_info, histogram = %allocator_info.histogram());
+}
+
+/// Распечатывает все ещё не освобождённые выделения памяти вместе с трассировками стека
+/// в точках их выделения.
+/// Трассировки можно расшифровать командой `llvm-symbolizer`, см. [`Backtrace`].
+///
+/// Вызывается автоматически при штатном завершении процесса.
+/// Доступна только при включённой опции `leak-tracker`.
+#[cfg(feature = "leak-tracker")]
+pub fn dump_leaks() {
+    let mut count = 0;
+
+    let leak_tracker = GLOBAL_ALLOCATOR.allocator();
+
+    for leak in leak_tracker.leaks() {
+        warn!(%leak, "memory leak");
+        count += 1;
+    }
+
+    let lost = leak_tracker.lost();
+    if count > 0 || lost > 0 {
+        warn!(count, lost, "leaked allocations");
+    }
+}
+
+/// Задаёт функцию `hook`, которую глобальный аллокатор вызывает при нехватке памяти
+/// перед тем, как один раз повторить запрос.
+/// Функция может, например, освободить кэши процесса или вызвать [`syscall::sched_yield()`],
+/// чтобы другие процессы успели освободить память.
+///
+/// Если `hook` равен `None` или повторный запрос тоже не удался,
+/// нехватка памяти приводит к панике.
+pub fn set_retry_hook(hook: Option<fn()>) {
+    GLOBAL_ALLOCATOR.set_retry_hook(hook);
 }
 
 /// Обработчик ошибок глобального аллокатора памяти общего назначения.
@@ -90,8 +134,45 @@ unsafe impl Send for SingleThreadedCache {
 unsafe impl Sync for SingleThreadedCache {
 }
 
+/// Аллокатор памяти общего назначения, на котором построен [`GLOBAL_ALLOCATOR`].
+type UserDispatcher = Dispatcher<SingleThreadedCache, MapAllocator>;
+
+/// Возвращает аллокатор памяти общего назначения, на котором построен [`GLOBAL_ALLOCATOR`].
+#[cfg(not(feature = "leak-tracker"))]
+fn dispatcher() -> &'static UserDispatcher {
+    GLOBAL_ALLOCATOR.allocator()
+}
+
+/// Возвращает аллокатор памяти общего назначения, на котором построен [`GLOBAL_ALLOCATOR`].
+#[cfg(feature = "leak-tracker")]
+fn dispatcher() -> &'static UserDispatcher {
+    GLOBAL_ALLOCATOR.allocator().allocator()
+}
+
+/// Глобальный аллокатор памяти общего назначения в пространстве пользователя, реализованный через
+/// системные вызовы [`syscall::map()`], [`syscall::unmap()`] и [`syscall::copy_mapping()`].
+///
+/// При нехватке памяти вызывает функцию, заданную [`set_retry_hook()`], и повторяет запрос.
+#[cfg(not(feature = "leak-tracker"))]
+#[global_allocator]
+static GLOBAL_ALLOCATOR: RetryAllocator<UserDispatcher> = RetryAllocator::new(Dispatcher::new(
+    SingleThreadedCache::new(),
+    MapAllocator::new(),
+));
+
 /// Глобальный аллокатор памяти общего назначения в пространстве пользователя, реализованный через
 /// системные вызовы [`syscall::map()`], [`syscall::unmap()`] и [`syscall::copy_mapping()`].
+/// Запоминает точки выделения ещё не освобождённых блоков памяти.
+///
+/// При нехватке памяти вызывает функцию, заданную [`set_retry_hook()`], и повторяет запрос.
+#[cfg(feature = "leak-tracker")]
 #[global_allocator]
-static GLOBAL_ALLOCATOR: Dispatcher<SingleThreadedCache, MapAllocator> =
-    Dispatcher::new(SingleThreadedCache::new(), MapAllocator::new());
+static GLOBAL_ALLOCATOR: RetryAllocator<LeakTracker<UserDispatcher, LEAK_TRACKER_CAPACITY>> =
+    RetryAllocator::new(LeakTracker::new(Dispatcher::new(
+        SingleThreadedCache::new(),
+        MapAllocator::new(),
+    )));
+
+/// Максимальное количество одновременно отслеживаемых выделений памяти.
+#[cfg(feature = "leak-tracker")]
+const LEAK_TRACKER_CAPACITY: usize = 1024;
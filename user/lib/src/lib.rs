@@ -16,6 +16,9 @@
 /// системные вызовы [`syscall::map()`], [`syscall::unmap()`] и [`syscall::copy_mapping()`].
 pub mod allocator;
 
+/// Адаптер [`LogWriter`], записывающий отформатированный текст в журнал одним событием.
+mod log_writer;
+
 /// Вспомогательные функции для работы с виртуальными страницами
 /// [`memory::copy_page`] и [`memory::temp_page()`],
 /// а также с таблицами страниц [`memory::page_table()`].
@@ -54,10 +57,15 @@ use ku::{
         LOG_COLLECTOR,
         error,
     },
-    process::ExitCode,
+    process::{
+        ExitCode,
+        elf::BuildInfo,
+    },
     sync,
 };
 
+pub use log_writer::LogWriter;
+
 /// Точка входа в процесс пользователя.
 /// Получает от ядра pid процесса и ссылку `process_info` на информацию о текущем процессе.
 #[unsafe(no_mangle)]
@@ -79,6 +87,9 @@ pub extern "C" fn _start(
         main();
     }
 
+    #[cfg(feature = "leak-tracker")]
+    allocator::dump_leaks();
+
     syscall::exit(ExitCode::Ok.into());
 }
 
@@ -107,6 +118,7 @@ fn panic(panic_info: &PanicInfo) -> ! {
     }
 
     if let Ok(backtrace) = Backtrace::with_stack(ku::process_info().stack()) {
+        let backtrace = backtrace.with_limit(MAX_BACKTRACE_FRAMES);
         error!(message = %panic_info, %backtrace);
     } else {
         error!(message = %panic_info);
@@ -152,5 +164,75 @@ macro_rules! entry {
     };
 }
 
+/// [Note](https://refspecs.linuxbase.org/elf/gabi4+/ch5.pheader.html#note_section)
+/// с версией сборки, которую ядро извлекает из ELF--файла процесса
+/// методом [`BuildInfo::version()`].
+#[repr(C, align(4))]
+struct VersionNote {
+    /// Размер имени note, включая завершающий нулевой байт.
+    name_size: u32,
+
+    /// Размер описания note --- строки с версией.
+    description_size: u32,
+
+    /// Тип note.
+    kind: u32,
+
+    /// Имя note [`BuildInfo::VERSION_NOTE_NAME`], дополненное нулями до границы `u32`.
+    name: [u8; VERSION_NOTE_NAME_SIZE],
+
+    /// Версия сборки, дополненная нулями до границы `u32`.
+    description: [u8; VERSION_NOTE_DESCRIPTION_SIZE],
+}
+
+impl VersionNote {
+    /// Создаёт note с версией сборки `version`.
+    const fn new(version: &str) -> Self {
+        Self {
+            name_size: BuildInfo::VERSION_NOTE_NAME.len() as u32,
+            description_size: version.len() as u32,
+            kind: NT_VERSION,
+            name: padded(BuildInfo::VERSION_NOTE_NAME),
+            description: padded(version.as_bytes()),
+        }
+    }
+}
+
+/// Возвращает `bytes`, дополненные нулями до размера `N`.
+const fn padded<const N: usize>(bytes: &[u8]) -> [u8; N] {
+    let mut padded = [0; N];
+    let mut i = 0;
+
+    while i < bytes.len() {
+        padded[i] = bytes[i];
+        i += 1;
+    }
+
+    padded
+}
+
 /// Адрес обработчика `panic_handler()`, установленный с помощью [`set_panic_handler()`].
 static PANIC_HANDLER: AtomicPtr<()> = AtomicPtr::new(ptr::null_mut());
+
+/// Note с версией сборки в каждом ELF--файле пользовательского процесса.
+/// Секции `.note.*` линкер собирает в note--сегмент,
+/// а идентификатор сборки туда же добавляет опция `--build-id` из `user/.cargo/config.toml`.
+#[used]
+#[unsafe(link_section = ".note.nikka.version")]
+static VERSION_NOTE: VersionNote = VersionNote::new(VERSION);
+
+/// Максимальное количество стековых фреймов в трассировке стека, печатаемой при панике.
+/// Не даёт панике при глубокой рекурсии засорить журнал сотнями фреймов.
+const MAX_BACKTRACE_FRAMES: usize = 32;
+
+/// Тип note с версией сборки.
+const NT_VERSION: u32 = 1;
+
+/// Версия сборки, которая записывается в [`VERSION_NOTE`].
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Размер описания [`VERSION_NOTE`], выровненный на границу `u32`.
+const VERSION_NOTE_DESCRIPTION_SIZE: usize = VERSION.len().next_multiple_of(4);
+
+/// Размер имени [`VERSION_NOTE`], выровненный на границу `u32`.
+const VERSION_NOTE_NAME_SIZE: usize = BuildInfo::VERSION_NOTE_NAME.len().next_multiple_of(4);
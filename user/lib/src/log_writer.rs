@@ -0,0 +1,113 @@
+use core::{
+    fmt,
+    str,
+};
+
+use ku::log::{
+    Level,
+    debug,
+    error,
+    info,
+    trace,
+    warn,
+};
+
+// Used in docs.
+#[allow(unused)]
+use {
+    crate::syscall,
+    ku::log::LOG_COLLECTOR,
+};
+
+/// Адаптер [`fmt::Write`], который накапливает отформатированный текст
+/// и записывает его в журнал через [`LOG_COLLECTOR`] одним событием,
+/// у которого есть только поле `message`.
+/// В отличие от системного вызова [`syscall::log_value()`], позволяет выводить
+/// в одной строке журнала произвольное количество значений
+/// и не добавляет к ним лишнее числовое поле `value`:
+/// ```ignore
+/// write!(LogWriter::new(Level::INFO), "x = {x}, y = {y}")?;
+/// ```
+///
+/// Накопленный текст записывается в журнал при вызове [`LogWriter::flush()`] или
+/// при удалении [`LogWriter`].
+/// Текст, не поместившийся в буфер из [`LogWriter::CAPACITY`] байт, отбрасывается.
+pub struct LogWriter {
+    /// Буфер накопленного текста.
+    buffer: [u8; LogWriter::CAPACITY],
+
+    /// Длина накопленного текста.
+    len: usize,
+
+    /// Уровень, с которым текст записывается в журнал.
+    level: Level,
+}
+
+impl LogWriter {
+    /// Создаёт пустой [`LogWriter`], записывающий в журнал с уровнем `level`.
+    pub const fn new(level: Level) -> Self {
+        Self {
+            buffer: [0; Self::CAPACITY],
+            len: 0,
+            level,
+        }
+    }
+
+    /// Накопленный и ещё не записанный в журнал текст.
+    pub fn message(&self) -> &str {
+        str::from_utf8(&self.buffer[.. self.len])
+            .expect("the buffer contains only whole characters")
+    }
+
+    /// Записывает накопленный текст в журнал одним событием и очищает буфер.
+    /// Если буфер пуст, ничего не делает.
+    pub fn flush(&mut self) {
+        if self.len == 0 {
+            return;
+        }
+
+        let message = self.message();
+        match self.level {
+            Level::TRACE => trace!("{}", message),
+            Level::DEBUG => debug!("{}", message),
+            Level::INFO => info!("{}", message),
+            Level::WARN => warn!("{}", message),
+            Level::ERROR => error!("{}", message),
+        }
+
+        self.len = 0;
+    }
+
+    /// Максимальный размер накопленного текста в байтах.
+    pub const CAPACITY: usize = 256;
+}
+
+impl Drop for LogWriter {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+impl fmt::Write for LogWriter {
+    /// Дописывает `text` в буфер.
+    ///
+    /// Если `text` не помещается целиком, дописывает его наибольший
+    /// помещающийся префикс из целых символов и возвращает ошибку [`fmt::Error`].
+    fn write_str(
+        &mut self,
+        text: &str,
+    ) -> fmt::Result {
+        let free = Self::CAPACITY - self.len;
+        let (len, result) = if text.len() <= free {
+            (text.len(), Ok(()))
+        } else {
+            let len = (0 ..= free).rev().find(|&len| text.is_char_boundary(len)).unwrap_or(0);
+            (len, Err(fmt::Error))
+        };
+
+        self.buffer[self.len .. self.len + len].copy_from_slice(&text.as_bytes()[.. len]);
+        self.len += len;
+
+        result
+    }
+}
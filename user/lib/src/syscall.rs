@@ -11,24 +11,31 @@ use core::{
     },
 };
 
+use chrono::Duration;
 use static_assertions::const_assert_eq;
 use tracing_core::Level;
 
 use ku::{
     error::{
-        Error::InvalidArgument,
+        Error::{
+            InvalidArgument,
+            NoData,
+            PermissionDenied,
+        },
         Result,
     },
     log,
     memory::{
         Block,
         Page,
+        SYSCALL_ALLOWED_FLAGS,
         USER_R,
         Virt,
         mmu::PageTableFlags,
         size,
     },
     process::{
+        ExitCode,
         Pid,
         RSP_OFFSET_IN_TRAP_INFO,
         ResultCode,
@@ -40,7 +47,14 @@ use ku::{
 
 // Used in docs.
 #[allow(unused)]
-use crate::syscall;
+use {
+    crate::syscall,
+    ku::{
+        error::Error,
+        ipc::pipe,
+        memory::USER_RW,
+    },
+};
 
 /// Системный вызов [`syscall::exit()`].
 ///
@@ -73,6 +87,48 @@ pub fn log_value(
     .map(|_| ())
 }
 
+/// Системный вызов [`syscall::log_bytes()`].
+///
+/// Записывает в журнал строку `label` и шестнадцатеричное представление байт `bytes`.
+/// Ядро записывает не более нескольких сотен первых байт `bytes`,
+/// а их полное количество --- в отдельное поле.
+pub fn log_bytes(
+    level: Level,
+    label: &str,
+    bytes: &[u8],
+) -> Result<()> {
+    // Байты часто лежат на стеке, поэтому здесь не используется `Block::from_slice()`,
+    // которая в отладочной сборке паникует на указателях на локальные переменные.
+    syscall(
+        Syscall::LogBytes,
+        size::from(u32::from(log::level_into_symbol(&level))),
+        label.as_ptr() as usize,
+        label.len(),
+        bytes.as_ptr() as usize,
+        bytes.len(),
+    )
+    .map(|_| ())
+}
+
+/// Системный вызов [`syscall::set_log_level()`].
+///
+/// Устанавливает уровень журналирования `level` и для ядра, и для текущего процесса.
+/// Сообщения процесса более подробных уровней отбрасываются ещё до сериализации,
+/// не занимая место в [`ku::info::ProcessInfo::log()`].
+pub fn set_log_level(level: Level) -> Result<()> {
+    log::LOG_COLLECTOR.set_level(level);
+
+    syscall(
+        Syscall::SetLogLevel,
+        size::from(u32::from(log::level_into_symbol(&level))),
+        0,
+        0,
+        0,
+        0,
+    )
+    .map(|_| ())
+}
+
 /// Системный вызов [`syscall::sched_yield()`].
 ///
 /// Перепланирует процесс в конец очереди готовых к исполнению процессов и
@@ -82,6 +138,214 @@ pub fn sched_yield() {
     syscall(Syscall::SchedYield, 0, 0, 0, 0, 0);
 }
 
+/// Системный вызов [`syscall::sleep()`].
+///
+/// Усыпляет вызывающий процесс на время `duration`, округлённое вверх до миллисекунд.
+/// Пока оно не истечёт, процесс не получает процессорного времени.
+/// При нулевой или отрицательной длительности работает так же, как [`sched_yield()`].
+#[allow(unused_must_use)]
+pub fn sleep(duration: Duration) {
+    let mut milliseconds = duration.num_milliseconds();
+    if duration > Duration::milliseconds(milliseconds) {
+        milliseconds += 1;
+    }
+    let milliseconds = usize::try_from(milliseconds).unwrap_or(0);
+
+    syscall(Syscall::Sleep, milliseconds, 0, 0, 0, 0);
+}
+
+/// Системный вызов [`syscall::yield_to()`].
+///
+/// Отдаёт остаток кванта времени процессу `target` ---
+/// если он готов к исполнению, он будет исполнен следующим.
+/// Иначе работает так же, как [`sched_yield()`].
+/// Сам вызывающий процесс перепланируется в конец очереди готовых к исполнению процессов.
+#[allow(unused_must_use)]
+pub fn yield_to(target: Pid) {
+    syscall(Syscall::YieldTo, target.into_usize(), 0, 0, 0, 0);
+}
+
+/// Системный вызов [`syscall::wait()`].
+///
+/// Ожидает завершения одного из непосредственных потомков вызывающего процесса.
+/// Возвращает [`Pid`] завершившегося потомка и его код завершения.
+/// Если потомок завершился ещё до вызова, возвращает его результат без ожидания.
+///
+/// Пока ни один потомок не завершился, ядро блокирует процесс.
+/// После пробуждения системный вызов возвращает [`Error::NoData`] и повторяется.
+///
+/// Возвращает ошибки:
+///   - [`Error::NoProcess`], если у процесса нет потомков, завершения которых можно дождаться.
+///   - [`Error::InvalidArgument`], если потомок завершился с кодом,
+///     которого нет в [`ExitCode`].
+pub fn wait() -> Result<(Pid, ExitCode)> {
+    let mut exit_code = 0_usize;
+    let exit_code_address = ptr::from_mut(&mut exit_code) as usize;
+
+    loop {
+        match syscall(Syscall::Wait, exit_code_address, 0, 0, 0, 0) {
+            Err(NoData) => {},
+            result => {
+                let child = Pid::from_usize(result?)?;
+                let exit_code = ExitCode::try_from(exit_code).map_err(|_| InvalidArgument)?;
+                return Ok((child, exit_code));
+            },
+        }
+    }
+}
+
+/// Системный вызов [`syscall::pipe()`].
+///
+/// Создаёт канал для передачи байт между процессами и
+/// возвращает его концы для чтения и для записи.
+/// Дескрипторы концов канала хранятся в ядре,
+/// поэтому копия процесса, созданная [`exofork()`], наследует оба конца.
+/// Читающая сторона видит конец данных только после того,
+/// как все процессы закроют свои копии конца канала для записи.
+pub fn pipe() -> Result<(PipeReader, PipeWriter)> {
+    let mut handles = [0_usize; 2];
+    let handles_address = ptr::from_mut(&mut handles) as usize;
+
+    syscall(Syscall::Pipe, handles_address, 0, 0, 0, 0)?;
+
+    let [reader, writer] = handles;
+
+    Ok((PipeReader(reader), PipeWriter(writer)))
+}
+
+/// Конец канала для чтения, см. [`pipe()`].
+/// При удалении закрывает свой конец канала.
+#[derive(Debug)]
+pub struct PipeReader(usize);
+
+impl PipeReader {
+    /// Читает из канала в `buffer` столько байт, сколько в нём есть,
+    /// но не больше размера `buffer`.
+    /// Возвращает количество прочитанных байт.
+    /// Ноль означает, что все концы канала для записи закрыты и данных больше не будет.
+    ///
+    /// Пока канал пуст, ядро блокирует процесс.
+    /// После пробуждения системный вызов возвращает [`Error::NoData`] и повторяется.
+    pub fn read(
+        &self,
+        buffer: &mut [u8],
+    ) -> Result<usize> {
+        let start = buffer.as_mut_ptr() as usize;
+
+        loop {
+            match syscall(Syscall::PipeRead, self.0, start, buffer.len(), 0, 0) {
+                Err(NoData) => {},
+                result => return result,
+            }
+        }
+    }
+}
+
+impl Drop for PipeReader {
+    #[allow(unused_must_use)]
+    fn drop(&mut self) {
+        syscall(Syscall::PipeClose, self.0, 0, 0, 0, 0);
+    }
+}
+
+/// Конец канала для записи, см. [`pipe()`].
+/// При удалении закрывает свой конец канала.
+#[derive(Debug)]
+pub struct PipeWriter(usize);
+
+impl PipeWriter {
+    /// Записывает в канал столько байт из `buffer`, сколько в нём есть места.
+    /// Возвращает количество записанных байт.
+    ///
+    /// Пока канал заполнен, ядро блокирует процесс.
+    /// После пробуждения системный вызов возвращает [`Error::NoData`] и повторяется.
+    ///
+    /// Если все концы канала для чтения закрыты,
+    /// возвращает ошибку [`Error::Pipe`] с [`pipe::Error::Closed`].
+    pub fn write(
+        &self,
+        buffer: &[u8],
+    ) -> Result<usize> {
+        let start = buffer.as_ptr() as usize;
+
+        loop {
+            match syscall(Syscall::PipeWrite, self.0, start, buffer.len(), 0, 0) {
+                Err(NoData) => {},
+                result => return result,
+            }
+        }
+    }
+
+    /// Записывает в канал все байты из `buffer`, при необходимости по частям,
+    /// см. [`PipeWriter::write()`].
+    pub fn write_all(
+        &self,
+        mut buffer: &[u8],
+    ) -> Result<()> {
+        while !buffer.is_empty() {
+            let len = self.write(buffer)?;
+            buffer = &buffer[len ..];
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for PipeWriter {
+    #[allow(unused_must_use)]
+    fn drop(&mut self) {
+        syscall(Syscall::PipeClose, self.0, 0, 0, 0, 0);
+    }
+}
+
+/// Системный вызов [`syscall::share()`].
+///
+/// Разрешает процессу `grantee` присоединить к себе вызовом [`attach()`]
+/// блок страниц `block` вызывающего процесса под именем `name`.
+/// После присоединения оба процесса работают с одними и теми же физическими фреймами.
+///
+/// Страницы блока должны быть отображены с флагами [`USER_RW`] и
+/// не должны копироваться при записи.
+/// Присоединить блок может только `grantee` и только под тем же именем `name`.
+/// Для остальных процессов [`attach()`] возвращает ошибку [`Error::PermissionDenied`].
+pub fn share(
+    grantee: Pid,
+    name: usize,
+    block: Block<Page>,
+) -> Result<()> {
+    syscall(
+        Syscall::Share,
+        grantee.into_usize(),
+        name,
+        block.start_address().into_usize(),
+        block.size(),
+        0,
+    )
+    .map(|_| ())
+}
+
+/// Системный вызов [`syscall::attach()`].
+///
+/// Отображает в память вызывающего процесса с флагами [`USER_RW`] блок страниц
+/// размера `size` байт, который процесс `granter` предоставил ему вызовом [`share()`]
+/// под именем `name`.
+/// Свободный участок адресного пространства выбирает ядро и возвращает его.
+///
+/// Удалить отображение можно вызовом [`unmap()`].
+/// Физические фреймы освобождаются, только когда их отображения удалят все процессы.
+pub fn attach(
+    granter: Pid,
+    name: usize,
+    size: usize,
+) -> Result<Block<Page>> {
+    let address = syscall(Syscall::Attach, granter.into_usize(), name, size, 0, 0)?;
+
+    let start = Virt::new(address)?;
+    let end = (start + size)?;
+
+    Block::new(Page::new(start)?, Page::new(end)?)
+}
+
 /// Системный вызов [`syscall::exofork()`].
 ///
 /// Создаёт копию вызывающего процесса и возвращает исходному процессу [`Pid`] копии.
@@ -124,6 +388,35 @@ pub fn map(
     Block::new(Page::new(start)?, Page::new(end)?)
 }
 
+/// Отображает в памяти вызывающего процесса новый блок страниц,
+/// вмещающий `len` байт, с флагами доступа `flags`.
+/// Свободный участок адресного пространства выбирает ядро, см. [`syscall::map()`].
+///
+/// Позволяет получить, например, исполнимую память или память только на чтение,
+/// а не только доступную на запись память, как у аллокатора [`crate::allocator`].
+///
+/// # Errors
+///
+/// - [`Error::InvalidArgument`] --- `len` равен нулю.
+/// - [`Error::PermissionDenied`] --- во `flags` нет флага [`PageTableFlags::USER`]
+///   или есть флаги, которые не входят в [`SYSCALL_ALLOWED_FLAGS`].
+pub fn mmap(
+    len: usize,
+    flags: PageTableFlags,
+) -> Result<Block<Page>> {
+    if len == 0 {
+        return Err(InvalidArgument);
+    }
+
+    if !flags.is_user() || !SYSCALL_ALLOWED_FLAGS.contains(flags) {
+        return Err(PermissionDenied);
+    }
+
+    let pages = Block::from_index(0, len.div_ceil(Page::SIZE))?;
+
+    map(Pid::Current, pages, flags)
+}
+
 /// Системный вызов [`syscall::unmap()`].
 ///
 /// Удаляет из виртуальной памяти целевого процесса `dst_pid` блок страниц `dst_block`.
@@ -196,6 +489,65 @@ pub fn set_state(
     .map(|_| ())
 }
 
+/// Системный вызов [`syscall::set_group()`].
+///
+/// Переводит целевой процесс, заданный идентификатором `dst_pid`, в группу процессов `group`.
+/// Если `group` равен [`Pid::Current`], создаёт новую группу с лидером `dst_pid`.
+/// Иначе `group` должен совпадать с `dst_pid` или с группой вызывающего процесса.
+pub fn set_group(
+    dst_pid: Pid,
+    group: Pid,
+) -> Result<()> {
+    syscall(
+        Syscall::SetGroup,
+        dst_pid.into_usize(),
+        group.into_usize(),
+        0,
+        0,
+        0,
+    )
+    .map(|_| ())
+}
+
+/// Системный вызов [`syscall::set_priority()`].
+///
+/// Устанавливает целевому процессу, заданному идентификатором `dst_pid`,
+/// приоритет `priority` в планировщике.
+/// Процессы с большим приоритетом исполняются раньше.
+/// Целевым может быть сам вызывающий процесс или его непосредственный потомок.
+pub fn set_priority(
+    dst_pid: Pid,
+    priority: u8,
+) -> Result<()> {
+    syscall(
+        Syscall::SetPriority,
+        dst_pid.into_usize(),
+        priority.into(),
+        0,
+        0,
+        0,
+    )
+    .map(|_| ())
+}
+
+/// Системный вызов [`syscall::suspend()`].
+///
+/// Приостанавливает целевой процесс, заданный идентификатором `dst_pid`,
+/// который должен быть потомком вызывающего процесса.
+/// Приостановленный процесс не исполняется, пока не будет возобновлён вызовом [`resume()`].
+pub fn suspend(dst_pid: Pid) -> Result<()> {
+    syscall(Syscall::Suspend, dst_pid.into_usize(), 0, 0, 0, 0).map(|_| ())
+}
+
+/// Системный вызов [`syscall::resume()`].
+///
+/// Возобновляет работу целевого процесса, заданного идентификатором `dst_pid`,
+/// приостановленного вызовом [`suspend()`].
+/// Целевой процесс должен быть потомком вызывающего процесса.
+pub fn resume(dst_pid: Pid) -> Result<()> {
+    syscall(Syscall::Resume, dst_pid.into_usize(), 0, 0, 0, 0).map(|_| ())
+}
+
 // ANCHOR: set_trap_handler
 /// Системный вызов [`syscall::set_trap_handler()`].
 ///
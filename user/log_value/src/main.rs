@@ -7,6 +7,7 @@
 #![no_std]
 
 use core::{
+    fmt::Write,
     panic::PanicInfo,
     ptr::NonNull,
 };
@@ -39,6 +40,7 @@ use ku::{
 };
 
 use lib::{
+    LogWriter,
     entry,
     syscall,
 };
@@ -77,6 +79,32 @@ fn main() {
         .is_ok()
     );
 
+    log_writer();
+
+    let header = [0xDE, 0xAD, 0xBE, 0xEF];
+    let result = syscall::log_bytes(Level::INFO, "header", &header);
+    my_assert!(
+        result.is_ok(),
+        "syscall::log_bytes() failed",
+        ResultCode::from(result).into(),
+    );
+
+    let result = syscall::set_log_level(Level::INFO);
+    my_assert!(
+        result.is_ok(),
+        "syscall::set_log_level() failed",
+        ResultCode::from(result).into(),
+    );
+    my_assert!(log::LOG_COLLECTOR.level() == Level::INFO);
+    log::debug!("this message is dropped before it reaches the log buffer");
+
+    let result = syscall::set_log_level(Level::DEBUG);
+    my_assert!(
+        result.is_ok(),
+        "syscall::set_log_level() failed",
+        ResultCode::from(result).into(),
+    );
+
     log_kernel_block::<Page>(
         0,
         0,
@@ -148,6 +176,41 @@ fn main() {
     );
 }
 
+fn log_writer() {
+    let x = 42;
+    let y = "forty two";
+
+    let mut writer = LogWriter::new(Level::INFO);
+    my_assert!(
+        write!(writer, "x = {x}, y = {y}, hex = {x:#X}").is_ok(),
+        "LogWriter failed to format a short message",
+    );
+    my_assert!(
+        writer.message() == "x = 42, y = forty two, hex = 0x2A",
+        "LogWriter should collect all values into a single message",
+    );
+    writer.flush();
+    my_assert!(
+        writer.message().is_empty(),
+        "LogWriter should be empty after a flush",
+    );
+
+    let mut writer = LogWriter::new(Level::INFO);
+    let mut result = Ok(());
+    for _ in 0 .. LogWriter::CAPACITY {
+        result = result.and(writer.write_str("ж"));
+    }
+    my_assert!(
+        result.is_err(),
+        "LogWriter should report an overflow of its buffer",
+    );
+    my_assert!(
+        writer.message().len() == LogWriter::CAPACITY,
+        "LogWriter should keep the longest fitting prefix of whole characters",
+        writer.message().len(),
+    );
+}
+
 fn generate_page_fault() -> ! {
     unsafe {
         NonNull::<u8>::dangling().as_ptr().read_volatile();
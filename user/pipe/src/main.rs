@@ -0,0 +1,42 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+#![allow(unused_variables)]
+
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use ku::{
+    error::Error,
+    ipc::pipe,
+};
+
+use lib::{
+    entry,
+    syscall,
+};
+
+entry!(main);
+
+fn main() {
+    let (reader, writer) = syscall::pipe().unwrap();
+
+    writer.write_all(MESSAGE).unwrap();
+
+    let mut buffer = [0; MESSAGE.len()];
+    let mut len = 0;
+    while len < buffer.len() {
+        len += reader.read(&mut buffer[len ..]).unwrap();
+    }
+    assert_eq!(&buffer, MESSAGE);
+
+    drop(writer);
+    assert_eq!(reader.read(&mut buffer), Ok(0), "expected the end of data");
+
+    let (reader, writer) = syscall::pipe().unwrap();
+    drop(reader);
+    assert_eq!(writer.write(MESSAGE), Err(Error::Pipe(pipe::Error::Closed)));
+}
+
+/// Сообщение, которое процесс передаёт через канал сам себе.
+const MESSAGE: &[u8] = b"Hello, pipe!";
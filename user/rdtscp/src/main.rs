@@ -0,0 +1,31 @@
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use core::arch::asm;
+
+use ku::time;
+
+use lib::entry;
+
+entry!(main);
+
+fn main() {
+    let (tsc, cpu) = time::tscp();
+
+    unsafe {
+        asm!(
+            "
+            // Return the result to the kernel via the user mode context
+            // saved on the Page Fault.
+            xor rsp, rsp
+            mov rsp, [rsp]
+            ",
+
+            in("rdi") cpu,
+            in("rsi") tsc,
+
+            options(noreturn),
+        );
+    }
+}
@@ -0,0 +1,55 @@
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use core::{
+    arch::asm,
+    hint,
+};
+
+use lib::entry;
+
+entry!(main);
+
+fn main() {
+    let sum = recurse(DEPTH);
+
+    unsafe {
+        asm!(
+            "
+            // Return the result to the kernel via the user mode context
+            // saved on the Page Fault.
+            xor rsp, rsp
+            mov rsp, [rsp]
+            ",
+
+            in("rdi") sum,
+
+            options(noreturn),
+        );
+    }
+}
+
+/// Recurses `depth` times keeping a `FRAME_SIZE` buffer alive in each stack frame.
+/// Returns the number of the stack frames, that is `depth + 1`.
+#[inline(never)]
+fn recurse(depth: usize) -> usize {
+    let index = depth % FRAME_SIZE;
+    let mut frame = [0_u8; FRAME_SIZE];
+    frame[index] = 1;
+    let frame = hint::black_box(&frame);
+
+    let sum = if depth == 0 {
+        0
+    } else {
+        recurse(depth - 1)
+    };
+
+    sum + usize::from(frame[index])
+}
+
+/// Recursion depth.
+const DEPTH: usize = 64;
+
+/// Size of the buffer kept in every stack frame.
+const FRAME_SIZE: usize = 256;
@@ -0,0 +1,30 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+#![allow(unused_variables)]
+
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use chrono::Duration;
+
+use lib::{
+    entry,
+    syscall,
+};
+
+entry!(main);
+
+fn main() {
+    syscall::sleep(Duration::zero());
+
+    for _ in 0 .. SLEEP_COUNT {
+        syscall::sleep(Duration::milliseconds(SLEEP_MILLISECONDS));
+    }
+}
+
+/// Сколько раз процесс засыпает.
+const SLEEP_COUNT: usize = 3;
+
+/// Длительность каждого сна в миллисекундах.
+const SLEEP_MILLISECONDS: i64 = 100;
@@ -0,0 +1,56 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+#![allow(unused_variables)]
+
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use core::ptr::NonNull;
+
+use ku::{
+    memory::{
+        Block,
+        mmu::USER_RW,
+    },
+    process::Pid,
+};
+
+use lib::{
+    entry,
+    syscall,
+};
+
+entry!(main);
+
+fn main() {
+    for _ in 0 .. MAP_COUNT {
+        if syscall::map(Pid::Current, Block::default(), USER_RW).is_ok() {
+            generate_page_fault();
+        }
+        syscall::unmap(Pid::Current, Block::default()).ok();
+    }
+
+    for _ in 0 .. SCHED_YIELD_COUNT {
+        syscall::sched_yield();
+    }
+}
+
+fn generate_page_fault() -> ! {
+    unsafe {
+        NonNull::<u8>::dangling().as_ptr().read_volatile();
+    }
+
+    unreachable!();
+}
+
+/// Количество пар из заведомо ошибочного системного вызова `map()` для пустого блока
+/// и системного вызова `unmap()` для него же.
+/// Должно совпадать с константами в тестах `kernel/tests/6-um-2-syscall-stats.rs` и
+/// `kernel/tests/6-um-2-syscall-trace.rs`.
+const MAP_COUNT: usize = 5;
+
+/// Количество системных вызовов `sched_yield()`.
+/// Должно совпадать с константами в тестах `kernel/tests/6-um-2-syscall-stats.rs` и
+/// `kernel/tests/6-um-2-syscall-trace.rs`.
+const SCHED_YIELD_COUNT: usize = 7;
@@ -0,0 +1,29 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+#![allow(unused_variables)]
+
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use ku::{
+    error::Error::NoProcess,
+    process::ExitCode,
+};
+
+use lib::{
+    entry,
+    syscall,
+};
+
+entry!(main);
+
+fn main() {
+    loop {
+        match syscall::wait() {
+            Ok((child, exit_code)) => assert_eq!(exit_code, ExitCode::Ok, "child = {child}"),
+            Err(NoProcess) => break,
+            Err(error) => panic!("wait() failed: {error:?}"),
+        }
+    }
+}